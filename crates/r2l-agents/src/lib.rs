@@ -1,7 +1,7 @@
 //! Core RL algorithm implementations used by higher-level `r2l` crates.
 //!
 //! This crate contains lower-level on-policy learning algorithms such as A2C,
-//! PPO, and VPG together with their hook interfaces and shared rollout
+//! PPO, PPG, TRPO, and VPG together with their hook interfaces and shared rollout
 //! processing utilities.
 //!
 //! Most users interact with these algorithms through `r2l-api`, which provides
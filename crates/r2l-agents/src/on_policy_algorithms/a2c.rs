@@ -82,6 +82,60 @@ pub trait A2CHook<M: OnPolicyLearningModule> {
     }
 }
 
+// Tuples of hooks run every hook in order, even after an earlier one returned
+// `Break`, so stateful hooks see every call. The tuple breaks if any of its
+// hooks does. An error stops the chain and is returned.
+//
+// Every default returns `Continue`, so a chained hook only changes the
+// learning pass through the methods it overrides.
+macro_rules! a2c_hooks_tuple {
+    ($($idx:tt $hook:ident),+) => {
+        impl<M: OnPolicyLearningModule, $($hook: A2CHook<M>),+> A2CHook<M> for ($($hook,)+) {
+            fn before_learning_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+                &mut self,
+                params: &mut A2CParams,
+                module: &mut M,
+                batches: &[B],
+                advantages: &mut Advantages,
+                returns: &mut Returns,
+            ) -> anyhow::Result<HookResult> {
+                let mut result = HookResult::Continue;
+                $(result = result.merge(
+                    self.$idx.before_learning_hook(params, module, batches, advantages, returns)?,
+                );)+
+                Ok(result)
+            }
+
+            fn batch_hook(
+                &mut self,
+                params: &mut A2CParams,
+                module: &mut M,
+                losses: &mut <M as LearningModule>::Losses,
+                data: &A2CBatchData<M::LearningTensor>,
+            ) -> anyhow::Result<HookResult> {
+                let mut result = HookResult::Continue;
+                $(result = result.merge(self.$idx.batch_hook(params, module, losses, data)?);)+
+                Ok(result)
+            }
+
+            fn after_learning_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+                &mut self,
+                params: &mut A2CParams,
+                module: &mut M,
+                batches: &[B],
+            ) -> anyhow::Result<HookResult> {
+                let mut result = HookResult::Continue;
+                $(result = result.merge(self.$idx.after_learning_hook(params, module, batches)?);)+
+                Ok(result)
+            }
+        }
+    };
+}
+
+a2c_hooks_tuple!(0 H0, 1 H1);
+a2c_hooks_tuple!(0 H0, 1 H1, 2 H2);
+a2c_hooks_tuple!(0 H0, 1 H1, 2 H2, 3 H3);
+
 /// Prototype Advantage Actor-Critic algorithm over finalized trajectory batches.
 pub struct A2C<Module: OnPolicyLearningModule, Hooks: A2CHook<Module>> {
     /// A2C hyperparameters.
@@ -158,6 +212,20 @@ impl<Module: OnPolicyLearningModule, Hooks: A2CHook<Module>> A2C<Module, Hooks>
     }
 }
 
+impl<Module: OnPolicyLearningModule, Hooks: A2CHook<Module>> A2C<Module, Hooks> {
+    /// Runs `hook` after the current hooks.
+    ///
+    /// Layers extra behavior, such as a custom loss term, on top of the
+    /// existing hooks without re-implementing them.
+    pub fn chain_hook<H: A2CHook<Module>>(self, hook: H) -> A2C<Module, (Hooks, H)> {
+        A2C {
+            params: self.params,
+            lm: self.lm,
+            hooks: (self.hooks, hook),
+        }
+    }
+}
+
 impl<M: OnPolicyLearningModule, H: A2CHook<M>> Agent for A2C<M, H> {
     type Tensor = M::InferenceTensor;
     type Actor = M::InferencePolicy;
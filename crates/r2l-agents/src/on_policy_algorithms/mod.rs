@@ -3,14 +3,20 @@
 //! This module provides common rollout-processing helpers such as generalized
 //! advantage estimation together with the concrete
 //! [`mod@crate::on_policy_algorithms::a2c`],
-//! [`mod@crate::on_policy_algorithms::ppo`], and
+//! [`mod@crate::on_policy_algorithms::ppg`],
+//! [`mod@crate::on_policy_algorithms::ppo`],
+//! [`mod@crate::on_policy_algorithms::trpo`], and
 //! [`mod@crate::on_policy_algorithms::vpg`] algorithm modules.
 
 /// Advantage values computed per rollout buffer.
 pub mod a2c;
+/// Phasic Policy Gradient implementation and hook interface.
+pub mod ppg;
 /// Proximal Policy Optimization implementation and hook interface.
 pub mod ppo;
-/// Vanilla Policy Gradient implementation.
+/// Trust Region Policy Optimization implementation and hook interface.
+pub mod trpo;
+/// Vanilla Policy Gradient and REINFORCE implementation and hook interface.
 pub mod vpg;
 
 use derive_more::Deref;
@@ -85,6 +91,38 @@ impl Logps {
     }
 }
 
+/// Value predictions made before learning, grouped by rollout buffer.
+#[derive(Deref, Debug)]
+pub struct Values(pub Vec<Vec<f32>>);
+
+impl Values {
+    /// Recovers the value predictions of the GAE pass as `returns - advantages`.
+    ///
+    /// This must be called before advantages are normalized.
+    pub fn from_advantages_and_returns(advantages: &Advantages, returns: &Returns) -> Self {
+        let values = advantages
+            .iter()
+            .zip(returns.iter())
+            .map(|(advantages, returns)| {
+                returns
+                    .iter()
+                    .zip(advantages)
+                    .map(|(ret, adv)| ret - adv)
+                    .collect()
+            })
+            .collect();
+        Self(values)
+    }
+
+    /// Samples value predictions at the provided `(buffer_index, step_index)` pairs.
+    pub fn sample(&self, indicies: &[(usize, usize)]) -> Vec<f32> {
+        indicies
+            .iter()
+            .map(|(buff_idx, idx)| self.0[*buff_idx][*idx])
+            .collect()
+    }
+}
+
 fn batch_advantages_and_returns<
     T1: R2lTensor,
     T2: R2lTensor,
@@ -145,6 +183,45 @@ pub fn batches_advantages_and_returns<
     Ok((Advantages(advantage_vec), Returns(returns_vec)))
 }
 
+/// Discounted Monte-Carlo returns of one trajectory batch.
+///
+/// Returns are summed up to the end of each episode and are not bootstrapped,
+/// so the steps of an episode still running at the end of the batch only see
+/// the rewards collected so far.
+pub fn discounted_returns(rewards: &[f32], dones: &[bool], gamma: f32) -> Vec<f32> {
+    let mut returns = vec![0.; rewards.len()];
+    let mut running_return = 0.;
+    for i in (0..rewards.len()).rev() {
+        if dones[i] {
+            running_return = 0.;
+        }
+        running_return = rewards[i] + gamma * running_return;
+        returns[i] = running_return;
+    }
+    returns
+}
+
+/// Discounted Monte-Carlo returns of every trajectory batch.
+pub fn batches_discounted_returns<T: R2lTensor, B: TrajectoryBatch<T>>(
+    batches: &[B],
+    gamma: f32,
+) -> Returns {
+    Returns(
+        batches
+            .iter()
+            .map(|batch| {
+                let dones = batch
+                    .terminated()
+                    .iter()
+                    .zip(batch.truncated())
+                    .map(|(terminated, truncated)| *terminated || *truncated)
+                    .collect::<Vec<_>>();
+                discounted_returns(batch.rewards(), &dones, gamma)
+            })
+            .collect(),
+    )
+}
+
 pub fn sample<T1: R2lTensor, T2: R2lTensor, B: TrajectoryBatch<T1>, L: Fn(&T1) -> T2>(
     batches: &[B],
     indices: &[(usize, usize)],
@@ -206,3 +283,16 @@ impl BatchIndexIterator {
         Some(batch_indices.to_owned())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::discounted_returns;
+
+    #[test]
+    fn discounted_returns_reset_at_episode_end() {
+        let rewards = [1., 1., 1., 2., 2.];
+        let dones = [false, false, true, false, false];
+        let returns = discounted_returns(&rewards, &dones, 0.5);
+        assert_eq!(returns, vec![1.75, 1.5, 1., 3., 2.]);
+    }
+}
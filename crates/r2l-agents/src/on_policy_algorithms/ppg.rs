@@ -0,0 +1,266 @@
+//! Phasic Policy Gradient on top of the PPO batch loop.
+
+use anyhow::Result;
+use r2l_core::{
+    buffers::TrajectoryBatch,
+    models::{Actor, Policy},
+    on_policy::{algorithm::Agent, learning_module::AuxiliaryValueLearningModule},
+    rng::with_rng,
+    tensor::R2lTensor,
+};
+use rand::seq::SliceRandom;
+
+use crate::{
+    HookResult,
+    on_policy_algorithms::{
+        Returns, batches_advantages_and_returns,
+        ppo::{PPO, PPOHook},
+    },
+};
+
+/// Hyperparameters of the PPG auxiliary phase.
+///
+/// The policy phase is configured through the wrapped
+/// [`PPOParams`](crate::on_policy_algorithms::ppo::PPOParams). The defaults
+/// follow the PPG paper.
+pub struct PPGParams {
+    /// Number of rollouts learned by PPO between two auxiliary phases.
+    pub n_policy_iterations: usize,
+    /// Number of passes over the stored rollouts in each auxiliary phase.
+    pub n_aux_epochs: usize,
+    /// Weight of the KL term keeping the policy close to its pre-phase output.
+    pub beta_clone: f32,
+    /// Minibatch size used during the auxiliary phase.
+    pub aux_sample_size: usize,
+}
+
+impl Default for PPGParams {
+    fn default() -> Self {
+        Self {
+            n_policy_iterations: 32,
+            n_aux_epochs: 6,
+            beta_clone: 1.,
+            aux_sample_size: 256,
+        }
+    }
+}
+
+/// Per-minibatch data exposed to [`PPGHook::auxiliary_batch_hook`].
+pub struct PPGAuxiliaryBatchData<T: R2lTensor> {
+    /// Sampled observations in the minibatch.
+    pub observations: Vec<T>,
+    /// Squared error of the auxiliary value head.
+    pub auxiliary_value_loss: T,
+    /// Estimated KL divergence between the pre-phase and the current policy.
+    pub kl: T,
+    /// Loss of the policy network, `auxiliary_value_loss + beta_clone * kl`.
+    pub joint_loss: T,
+    /// Squared error of the value function.
+    pub value_loss: T,
+}
+
+/// Hook interface for the PPG auxiliary phase.
+///
+/// The policy phase runs through the [`PPOHook`] supertrait.
+pub trait PPGHook<M: AuxiliaryValueLearningModule>: PPOHook<M> {
+    fn auxiliary_batch_hook(
+        &mut self,
+        _params: &mut PPGParams,
+        _module: &mut M,
+        _data: &mut PPGAuxiliaryBatchData<M::LearningTensor>,
+    ) -> anyhow::Result<HookResult> {
+        Ok(HookResult::Continue)
+    }
+
+    fn after_auxiliary_phase_hook(
+        &mut self,
+        _params: &mut PPGParams,
+        _module: &mut M,
+    ) -> anyhow::Result<HookResult> {
+        Ok(HookResult::Continue)
+    }
+}
+
+/// Observations and value targets of the rollouts learned since the last
+/// auxiliary phase.
+pub struct AuxiliaryRolloutStorage<T: R2lTensor> {
+    observations: Vec<Vec<T>>,
+    returns: Vec<Vec<f32>>,
+    rollouts: usize,
+}
+
+impl<T: R2lTensor> Default for AuxiliaryRolloutStorage<T> {
+    fn default() -> Self {
+        Self {
+            observations: vec![],
+            returns: vec![],
+            rollouts: 0,
+        }
+    }
+}
+
+impl<T: R2lTensor> AuxiliaryRolloutStorage<T> {
+    /// Stores the observations of one rollout with their return targets.
+    pub fn push<B: TrajectoryBatch<T>>(&mut self, batches: &[B], returns: &Returns) {
+        for (batch, returns) in batches.iter().zip(returns.iter()) {
+            self.observations.push(batch.states().to_vec());
+            self.returns.push(returns.clone());
+        }
+        self.rollouts += 1;
+    }
+
+    /// Number of rollouts stored.
+    pub fn rollouts(&self) -> usize {
+        self.rollouts
+    }
+
+    /// Number of observations stored across all rollouts.
+    pub fn len(&self) -> usize {
+        self.observations.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every stored rollout.
+    pub fn clear(&mut self) {
+        self.observations.clear();
+        self.returns.clear();
+        self.rollouts = 0;
+    }
+}
+
+/// Phasic Policy Gradient.
+///
+/// Every rollout is learned by the wrapped [`PPO`] and kept in an
+/// [`AuxiliaryRolloutStorage`]. After
+/// [`n_policy_iterations`](PPGParams::n_policy_iterations) rollouts, an
+/// auxiliary phase fits the value function and the auxiliary value head of the
+/// policy network to the stored returns, while a KL term keeps the policy
+/// output where it was before the phase.
+pub struct PPG<Module: AuxiliaryValueLearningModule, Hooks: PPGHook<Module>> {
+    /// Auxiliary-phase hyperparameters.
+    pub params: PPGParams,
+    /// PPO running the policy phase. Holds the learning module and the hooks.
+    pub ppo: PPO<Module, Hooks>,
+    /// Rollouts kept for the next auxiliary phase.
+    pub storage: AuxiliaryRolloutStorage<Module::InferenceTensor>,
+}
+
+impl<Module: AuxiliaryValueLearningModule, Hooks: PPGHook<Module>> PPG<Module, Hooks> {
+    fn auxiliary_phase(&mut self) -> anyhow::Result<()> {
+        let lm = &mut self.ppo.lm;
+        let storage = &self.storage;
+        // Actions are resampled from the policy before the phase, so the
+        // log-ratio estimate of the KL divergence is unbiased.
+        let actor = lm.inference_policy();
+        let mut actions = vec![];
+        let mut logps = vec![];
+        for observations in &storage.observations {
+            let rollout_actions = observations
+                .iter()
+                .map(|observation| actor.action(observation.clone()))
+                .collect::<Result<Vec<_>>>()?;
+            logps.push(actor.log_probs(observations, &rollout_actions)?.to_vec());
+            actions.push(rollout_actions);
+        }
+        let mut indices = (0..storage.observations.len())
+            .flat_map(|i| (0..storage.observations[i].len()).map(move |j| (i, j)))
+            .collect::<Vec<_>>();
+        for _ in 0..self.params.n_aux_epochs {
+            with_rng(|rng| indices.shuffle(rng));
+            for indices in indices.chunks(self.params.aux_sample_size) {
+                let observations = indices
+                    .iter()
+                    .map(|(i, j)| Module::lifter(&storage.observations[*i][*j]))
+                    .collect::<Vec<_>>();
+                let batch_actions = indices
+                    .iter()
+                    .map(|(i, j)| Module::lifter(&actions[*i][*j]))
+                    .collect::<Vec<_>>();
+                let sample = |data: &[Vec<f32>]| {
+                    indices
+                        .iter()
+                        .map(|(i, j)| data[*i][*j])
+                        .collect::<Vec<_>>()
+                };
+                let logp_old = lm.tensor_from_slice(&sample(&logps));
+                let returns = lm.tensor_from_slice(&sample(&storage.returns));
+                let ones = lm.tensor_from_slice(&vec![1.; indices.len()]);
+                let logp = lm.policy().log_probs(&observations, &batch_actions)?;
+                let log_ratio = logp.sub(&logp_old)?;
+                let kl = log_ratio.exp()?.sub(&ones)?.sub(&log_ratio)?.mean()?;
+                let auxiliary_values = lm.auxiliary_values(&observations)?;
+                let auxiliary_value_loss = returns.sub(&auxiliary_values)?.sqr()?.mean()?;
+                let joint_loss =
+                    auxiliary_value_loss.add(&kl.mul_scalar(self.params.beta_clone)?)?;
+                let values = lm.values(&observations)?;
+                let value_loss = returns.sub(&values)?.sqr()?.mean()?;
+                let mut data = PPGAuxiliaryBatchData {
+                    observations,
+                    auxiliary_value_loss,
+                    kl,
+                    joint_loss,
+                    value_loss,
+                };
+                r2l_core::return_on_hook_result!(self.ppo.hooks.auxiliary_batch_hook(
+                    &mut self.params,
+                    lm,
+                    &mut data
+                )?);
+                lm.auxiliary_update(data.joint_loss, data.value_loss)?;
+            }
+        }
+        r2l_core::return_on_hook_result!(
+            self.ppo
+                .hooks
+                .after_auxiliary_phase_hook(&mut self.params, lm)?
+        );
+        Ok(())
+    }
+
+    /// Learning entrypoint over finalized trajectory batches.
+    pub fn learn<B: TrajectoryBatch<Module::InferenceTensor>>(
+        &mut self,
+        batches: &[B],
+    ) -> Result<()> {
+        let (advantages, returns) = batches_advantages_and_returns(
+            batches,
+            &self.ppo.lm,
+            self.ppo.params.gamma,
+            self.ppo.params.lambda,
+            Module::lifter,
+        )?;
+        self.storage.push(batches, &returns);
+        self.ppo
+            .learn_from_estimates(batches, advantages, returns)?;
+        if self.storage.rollouts() >= self.params.n_policy_iterations {
+            let result = self.auxiliary_phase();
+            self.storage.clear();
+            result?;
+        }
+        Ok(())
+    }
+}
+
+impl<M: AuxiliaryValueLearningModule, H: PPGHook<M>> Agent for PPG<M, H> {
+    type Tensor = M::InferenceTensor;
+    type Actor = M::InferencePolicy;
+
+    fn actor(&self) -> Self::Actor {
+        self.ppo.actor()
+    }
+
+    fn learn<B: TrajectoryBatch<Self::Tensor>>(&mut self, buffers: &[B]) -> Result<()> {
+        PPG::learn(self, buffers)
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.ppo.set_learning_rate(learning_rate);
+    }
+
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.ppo.set_progress_remaining(progress_remaining);
+    }
+}
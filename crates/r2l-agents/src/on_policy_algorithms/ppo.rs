@@ -13,8 +13,8 @@ use r2l_core::{
 use crate::{
     HookResult,
     on_policy_algorithms::{
-        Advantages, BatchIndexIterator, Logps, Returns, batches_advantages_and_returns, logps,
-        sample,
+        Advantages, BatchIndexIterator, Logps, Returns, Values, batches_advantages_and_returns,
+        logps, sample,
     },
 };
 
@@ -22,6 +22,12 @@ use crate::{
 pub struct PPOParams {
     /// Clipping range applied to the PPO policy ratio.
     pub clip_range: f32,
+    /// Clipping range applied to the change of value predictions, if any.
+    ///
+    /// When set, new value predictions are clipped around the predictions made
+    /// before learning, as done by Stable-Baselines3. This depends on the
+    /// reward scale.
+    pub clip_range_vf: Option<f32>,
     /// Discount factor used for return and advantage estimation.
     pub gamma: f32,
     /// GAE lambda used for advantage estimation.
@@ -34,6 +40,7 @@ impl Default for PPOParams {
     fn default() -> Self {
         Self {
             clip_range: 0.2,
+            clip_range_vf: None,
             lambda: 0.8,
             gamma: 0.98,
             sample_size: 64,
@@ -51,6 +58,13 @@ pub struct PPOBatchData<T: R2lTensor> {
     pub logp: T,
     /// Value-function predictions for the sampled observations.
     pub values_pred: T,
+    /// Value predictions made before learning for the sampled observations.
+    pub values_old: T,
+    /// Value predictions clipped around `values_old` when
+    /// [`PPOParams::clip_range_vf`] is set.
+    pub values_clipped: Option<T>,
+    /// Value loss, computed from `values_clipped` when present.
+    pub value_loss: T,
     /// Difference between current and old log-probabilities.
     pub logp_diff: T,
     /// Probability ratio `exp(logp_diff)` used by the PPO objective.
@@ -88,8 +102,70 @@ pub trait PPOHook<M: OnPolicyLearningModule> {
     ) -> anyhow::Result<HookResult> {
         Ok(HookResult::Continue)
     }
+
+    /// Called with the remaining fraction of training before each rollout is learned.
+    fn progress_hook(&mut self, _params: &mut PPOParams, _progress_remaining: f64) {}
+}
+
+// Tuples of hooks run every hook in order, even after an earlier one returned
+// `Break`, so stateful hooks see every call. The tuple breaks if any of its
+// hooks does, except in `rollout_hook`. An error stops the chain and is
+// returned.
+//
+// The default `rollout_hook` breaks after the first epoch, so only the first
+// hook decides whether another epoch runs. Chaining a hook that only
+// overrides `batch_hook` then keeps the epochs of the hook it is chained to.
+macro_rules! ppo_hooks_tuple {
+    ($($idx:tt $hook:ident),+) => {
+        impl<M: OnPolicyLearningModule, $($hook: PPOHook<M>),+> PPOHook<M> for ($($hook,)+) {
+            fn before_learning_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+                &mut self,
+                params: &mut PPOParams,
+                module: &mut M,
+                batches: &[B],
+                advantages: &mut Advantages,
+                returns: &mut Returns,
+            ) -> anyhow::Result<HookResult> {
+                let mut result = HookResult::Continue;
+                $(result = result.merge(
+                    self.$idx.before_learning_hook(params, module, batches, advantages, returns)?,
+                );)+
+                Ok(result)
+            }
+
+            fn rollout_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+                &mut self,
+                params: &mut PPOParams,
+                module: &mut M,
+                batches: &[B],
+            ) -> anyhow::Result<HookResult> {
+                let results = [$(self.$idx.rollout_hook(params, module, batches)?),+];
+                Ok(results[0])
+            }
+
+            fn batch_hook(
+                &mut self,
+                params: &mut PPOParams,
+                module: &mut M,
+                losses: &mut <M as LearningModule>::Losses,
+                data: &PPOBatchData<M::LearningTensor>,
+            ) -> anyhow::Result<HookResult> {
+                let mut result = HookResult::Continue;
+                $(result = result.merge(self.$idx.batch_hook(params, module, losses, data)?);)+
+                Ok(result)
+            }
+
+            fn progress_hook(&mut self, params: &mut PPOParams, progress_remaining: f64) {
+                $(self.$idx.progress_hook(params, progress_remaining);)+
+            }
+        }
+    };
 }
 
+ppo_hooks_tuple!(0 H0, 1 H1);
+ppo_hooks_tuple!(0 H0, 1 H1, 2 H2);
+ppo_hooks_tuple!(0 H0, 1 H1, 2 H2, 3 H3);
+
 /// Prototype PPO variant over finalized trajectory batches.
 pub struct PPO<Module: OnPolicyLearningModule, Hooks: PPOHook<Module>> {
     /// PPO hyperparameters.
@@ -107,6 +183,7 @@ impl<Module: OnPolicyLearningModule, Hooks: PPOHook<Module>> PPO<Module, Hooks>
         advantages: &Advantages,
         logps: &Logps,
         returns: &Returns,
+        values: &Values,
     ) -> anyhow::Result<()> {
         let mut index_iterator = BatchIndexIterator::new(batches, self.params.sample_size);
         let lm = &mut self.lm;
@@ -119,8 +196,22 @@ impl<Module: OnPolicyLearningModule, Hooks: PPOHook<Module>> PPO<Module, Hooks>
             let logp_old = lm.tensor_from_slice(&logps.sample(&indices));
             let returns = lm.tensor_from_slice(&returns.sample(&indices));
             let logp = lm.policy().log_probs(&observations, &actions)?;
+            let values_old = lm.tensor_from_slice(&values.sample(&indices));
             let values_pred = lm.values(&observations)?;
-            let value_loss = returns.sub(&values_pred)?.sqr()?.mean()?;
+            let values_clipped = self
+                .params
+                .clip_range_vf
+                .map(|clip_range_vf| {
+                    values_pred
+                        .sub(&values_old)?
+                        .clamp(-clip_range_vf, clip_range_vf)?
+                        .add(&values_old)
+                })
+                .transpose()?;
+            let value_loss = returns
+                .sub(values_clipped.as_ref().unwrap_or(&values_pred))?
+                .sqr()?
+                .mean()?;
             let logp_diff = logp.sub(&logp_old)?;
             let ratio = logp_diff.exp()?;
             let clip_ratio =
@@ -128,12 +219,16 @@ impl<Module: OnPolicyLearningModule, Hooks: PPOHook<Module>> PPO<Module, Hooks>
             let clipped_adv = clip_ratio.mul(&advantages)?;
             let ratio_adv = ratio.mul(&advantages)?;
             let policy_loss = ratio_adv.minimum(&clipped_adv)?.neg()?.mean()?;
-            let mut losses = Module::Losses::from_policy_value_losses(policy_loss, value_loss);
+            let mut losses =
+                Module::Losses::from_policy_value_losses(policy_loss, value_loss.clone());
             let ppo_data = PPOBatchData {
                 observations,
                 actions,
                 logp,
                 values_pred,
+                values_old,
+                values_clipped,
+                value_loss,
                 logp_diff,
                 ratio,
             };
@@ -153,9 +248,10 @@ impl<Module: OnPolicyLearningModule, Hooks: PPOHook<Module>> PPO<Module, Hooks>
         advantages: Advantages,
         returns: Returns,
         logps: Logps,
+        values: Values,
     ) -> anyhow::Result<()> {
         loop {
-            self.batch_loop(batches, &advantages, &logps, &returns)?;
+            self.batch_loop(batches, &advantages, &logps, &returns, &values)?;
             let rollout_hook_res = self
                 .hooks
                 .rollout_hook(&mut self.params, &mut self.lm, batches);
@@ -168,13 +264,24 @@ impl<Module: OnPolicyLearningModule, Hooks: PPOHook<Module>> PPO<Module, Hooks>
         &mut self,
         batches: &[B],
     ) -> Result<()> {
-        let (mut advantages, mut returns) = batches_advantages_and_returns(
+        let (advantages, returns) = batches_advantages_and_returns(
             batches,
             &self.lm,
             self.params.gamma,
             self.params.lambda,
             Module::lifter,
         )?;
+        self.learn_from_estimates(batches, advantages, returns)
+    }
+
+    /// Runs the PPO epochs with advantages and returns estimated by the caller.
+    pub(crate) fn learn_from_estimates<B: TrajectoryBatch<Module::InferenceTensor>>(
+        &mut self,
+        batches: &[B],
+        mut advantages: Advantages,
+        mut returns: Returns,
+    ) -> Result<()> {
+        let values = Values::from_advantages_and_returns(&advantages, &returns);
         r2l_core::return_on_hook_result!(self.hooks.before_learning_hook(
             &mut self.params,
             &mut self.lm,
@@ -184,11 +291,26 @@ impl<Module: OnPolicyLearningModule, Hooks: PPOHook<Module>> PPO<Module, Hooks>
         )?);
         let actor = self.lm.inference_policy();
         let logps = logps(batches, &actor)?;
-        self.learning_loop(batches, advantages, returns, logps)?;
+        self.learning_loop(batches, advantages, returns, logps, values)?;
         Ok(())
     }
 }
 
+impl<Module: OnPolicyLearningModule, Hooks: PPOHook<Module>> PPO<Module, Hooks> {
+    /// Runs `hook` after the current hooks.
+    ///
+    /// Layers extra behavior, such as a custom loss term, on top of the
+    /// existing hooks without re-implementing them. The current hooks keep
+    /// deciding how many epochs run.
+    pub fn chain_hook<H: PPOHook<Module>>(self, hook: H) -> PPO<Module, (Hooks, H)> {
+        PPO {
+            params: self.params,
+            lm: self.lm,
+            hooks: (self.hooks, hook),
+        }
+    }
+}
+
 impl<M: OnPolicyLearningModule, H: PPOHook<M>> Agent for PPO<M, H> {
     type Tensor = M::InferenceTensor;
     type Actor = M::InferencePolicy;
@@ -204,4 +326,9 @@ impl<M: OnPolicyLearningModule, H: PPOHook<M>> Agent for PPO<M, H> {
     fn set_learning_rate(&mut self, learning_rate: f64) {
         self.lm.set_learning_rate(learning_rate);
     }
+
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.hooks
+            .progress_hook(&mut self.params, progress_remaining);
+    }
 }
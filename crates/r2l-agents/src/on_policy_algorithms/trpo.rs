@@ -0,0 +1,364 @@
+//! Trust Region Policy Optimization over finalized trajectory batches.
+
+use anyhow::Result;
+use r2l_core::{
+    buffers::TrajectoryBatch,
+    models::Policy,
+    on_policy::{algorithm::Agent, learning_module::TrustRegionLearningModule},
+    tensor::R2lTensor,
+};
+
+use crate::{
+    HookResult,
+    on_policy_algorithms::{
+        Advantages, BatchIndexIterator, Returns, batches_advantages_and_returns, sample,
+    },
+};
+
+/// Hyperparameters controlling TRPO training behavior.
+///
+/// The defaults follow Stable-Baselines3.
+pub struct TRPOParams {
+    /// Discount factor used for return and advantage estimation.
+    pub gamma: f32,
+    /// GAE lambda used for advantage estimation.
+    pub lambda: f32,
+    /// Upper bound on the KL divergence between the old and the updated policy.
+    pub target_kl: f32,
+    /// Maximum number of conjugate-gradient iterations.
+    pub cg_max_steps: usize,
+    /// Damping added to the Fisher matrix to keep conjugate gradient stable.
+    pub cg_damping: f32,
+    /// Factor the step is shrunk by after each rejected line-search step.
+    pub line_search_shrinking_factor: f32,
+    /// Maximum number of line-search steps before the update is abandoned.
+    pub line_search_max_iter: usize,
+    /// Number of passes over the rollout used to fit the value function.
+    pub n_critic_updates: usize,
+    /// Minibatch size used while fitting the value function.
+    pub sample_size: usize,
+}
+
+impl Default for TRPOParams {
+    fn default() -> Self {
+        Self {
+            gamma: 0.99,
+            lambda: 0.95,
+            target_kl: 0.01,
+            cg_max_steps: 15,
+            cg_damping: 0.1,
+            line_search_shrinking_factor: 0.8,
+            line_search_max_iter: 10,
+            n_critic_updates: 10,
+            sample_size: 128,
+        }
+    }
+}
+
+/// Outcome of one natural-gradient policy update, exposed to
+/// [`TRPOHook::policy_update_hook`].
+#[derive(Debug, Clone)]
+pub struct TRPOPolicyUpdate {
+    /// Surrogate objective before the update.
+    pub objective_before: f32,
+    /// Surrogate objective after the update, equal to `objective_before` when
+    /// no step was accepted.
+    pub objective_after: f32,
+    /// Estimated KL divergence between the old and the updated policy.
+    pub kl: f32,
+    /// Number of line-search steps tried.
+    pub line_search_steps: usize,
+    /// Whether a line-search step satisfied the KL constraint and improved the
+    /// objective.
+    pub accepted: bool,
+}
+
+/// Hook interface for customizing TRPO training over [`TrajectoryBatch`] inputs.
+pub trait TRPOHook<M: TrustRegionLearningModule> {
+    fn before_learning_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+        &mut self,
+        _params: &mut TRPOParams,
+        _module: &mut M,
+        _batches: &[B],
+        _advantages: &mut Advantages,
+        _returns: &mut Returns,
+    ) -> anyhow::Result<HookResult> {
+        Ok(HookResult::Continue)
+    }
+
+    fn policy_update_hook(
+        &mut self,
+        _params: &mut TRPOParams,
+        _module: &mut M,
+        _update: &TRPOPolicyUpdate,
+    ) -> anyhow::Result<HookResult> {
+        Ok(HookResult::Continue)
+    }
+
+    fn value_batch_hook(
+        &mut self,
+        _params: &mut TRPOParams,
+        _module: &mut M,
+        _value_loss: &mut M::LearningTensor,
+    ) -> anyhow::Result<HookResult> {
+        Ok(HookResult::Continue)
+    }
+
+    fn after_learning_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+        &mut self,
+        _params: &mut TRPOParams,
+        _module: &mut M,
+        _batches: &[B],
+    ) -> anyhow::Result<HookResult> {
+        Ok(HookResult::Continue)
+    }
+
+    /// Called with the remaining fraction of training before each rollout is learned.
+    fn progress_hook(&mut self, _params: &mut TRPOParams, _progress_remaining: f64) {}
+}
+
+/// Trust Region Policy Optimization.
+///
+/// The policy takes a natural-gradient step found by conjugate gradient on
+/// Fisher-vector products (see
+/// [`TrustRegionLearningModule::fisher_vector_product`]), scaled to the KL
+/// bound and shrunk by a backtracking line search until the KL constraint
+/// holds and the surrogate objective improves. The value function is then
+/// regressed on the returns with its own optimizer.
+pub struct TRPO<Module: TrustRegionLearningModule, Hooks: TRPOHook<Module>> {
+    /// TRPO hyperparameters.
+    pub params: TRPOParams,
+    /// Learning module containing policy, value function, and optimizer state.
+    pub lm: Module,
+    /// Hook implementation used to customize learning behavior.
+    pub hooks: Hooks,
+}
+
+impl<Module: TrustRegionLearningModule, Hooks: TRPOHook<Module>> TRPO<Module, Hooks> {
+    fn policy_update<B: TrajectoryBatch<Module::InferenceTensor>>(
+        &mut self,
+        batches: &[B],
+        advantages: &Advantages,
+    ) -> anyhow::Result<TRPOPolicyUpdate> {
+        let params = &self.params;
+        let lm = &mut self.lm;
+        let indices = (0..batches.len())
+            .flat_map(|i| (0..batches[i].len()).map(move |j| (i, j)))
+            .collect::<Vec<_>>();
+        let (observations, actions) = sample(batches, &indices, Module::lifter);
+        let advantages = lm.tensor_from_slice(&advantages.sample(&indices));
+        let logp_old = lm.policy().log_probs(&observations, &actions)?.to_vec();
+        let logp_old_tensor = lm.tensor_from_slice(&logp_old);
+        let surrogate = |lm: &Module| -> anyhow::Result<(Module::LearningTensor, Vec<f32>)> {
+            let logp = lm.policy().log_probs(&observations, &actions)?;
+            let ratio = logp.sub(&logp_old_tensor)?.exp()?;
+            Ok((ratio.mul(&advantages)?.mean()?, logp.to_vec()))
+        };
+        let (objective, _) = surrogate(lm)?;
+        let objective_before = objective.to_vec()[0];
+        let mut update = TRPOPolicyUpdate {
+            objective_before,
+            objective_after: objective_before,
+            kl: 0.,
+            line_search_steps: 0,
+            accepted: false,
+        };
+        let gradient = lm.policy_gradient(&objective)?;
+        let mut damped_fvp = |vector: &[f32]| -> anyhow::Result<Vec<f32>> {
+            let fvp = lm.fisher_vector_product(&observations, vector)?;
+            Ok(fvp
+                .iter()
+                .zip(vector)
+                .map(|(f, v)| f + params.cg_damping * v)
+                .collect())
+        };
+        let direction = conjugate_gradient(&mut damped_fvp, &gradient, params.cg_max_steps)?;
+        let curvature = dot(&direction, &damped_fvp(&direction)?);
+        if curvature.is_nan() || curvature <= 0. {
+            return Ok(update);
+        }
+        let step_size = (2. * params.target_kl / curvature).sqrt();
+        let parameters = lm.policy_parameters()?;
+        let mut coefficient = step_size;
+        for step in 1..=params.line_search_max_iter {
+            let candidate = parameters
+                .iter()
+                .zip(&direction)
+                .map(|(p, d)| p + coefficient * d)
+                .collect::<Vec<_>>();
+            lm.set_policy_parameters(&candidate)?;
+            let (objective, logp) = surrogate(lm)?;
+            let objective_after = objective.to_vec()[0];
+            let kl = approx_kl(&logp_old, &logp);
+            update.line_search_steps = step;
+            if kl < params.target_kl && objective_after > objective_before {
+                update.objective_after = objective_after;
+                update.kl = kl;
+                update.accepted = true;
+                return Ok(update);
+            }
+            coefficient *= params.line_search_shrinking_factor;
+        }
+        lm.set_policy_parameters(&parameters)?;
+        Ok(update)
+    }
+
+    fn value_loop<B: TrajectoryBatch<Module::InferenceTensor>>(
+        &mut self,
+        batches: &[B],
+        returns: &Returns,
+    ) -> anyhow::Result<()> {
+        for _ in 0..self.params.n_critic_updates {
+            let mut index_iterator = BatchIndexIterator::new(batches, self.params.sample_size);
+            while let Some(indices) = index_iterator.iter() {
+                let (observations, _) = sample(batches, &indices, Module::lifter);
+                let returns = self.lm.tensor_from_slice(&returns.sample(&indices));
+                let values_pred = self.lm.values(&observations)?;
+                let mut value_loss = returns.sub(&values_pred)?.sqr()?.mean()?;
+                r2l_core::return_on_hook_result!(self.hooks.value_batch_hook(
+                    &mut self.params,
+                    &mut self.lm,
+                    &mut value_loss
+                )?);
+                self.lm.update_value_function(value_loss)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Learning entrypoint over finalized trajectory batches.
+    pub fn learn<B: TrajectoryBatch<Module::InferenceTensor>>(
+        &mut self,
+        batches: &[B],
+    ) -> Result<()> {
+        let (mut advantages, mut returns) = batches_advantages_and_returns(
+            batches,
+            &self.lm,
+            self.params.gamma,
+            self.params.lambda,
+            Module::lifter,
+        )?;
+        r2l_core::return_on_hook_result!(self.hooks.before_learning_hook(
+            &mut self.params,
+            &mut self.lm,
+            batches,
+            &mut advantages,
+            &mut returns
+        )?);
+        let update = self.policy_update(batches, &advantages)?;
+        r2l_core::return_on_hook_result!(self.hooks.policy_update_hook(
+            &mut self.params,
+            &mut self.lm,
+            &update
+        )?);
+        self.value_loop(batches, &returns)?;
+        r2l_core::return_on_hook_result!(self.hooks.after_learning_hook(
+            &mut self.params,
+            &mut self.lm,
+            batches
+        )?);
+        Ok(())
+    }
+}
+
+impl<M: TrustRegionLearningModule, H: TRPOHook<M>> Agent for TRPO<M, H> {
+    type Tensor = M::InferenceTensor;
+    type Actor = M::InferencePolicy;
+
+    fn actor(&self) -> Self::Actor {
+        self.lm.inference_policy()
+    }
+
+    fn learn<B: TrajectoryBatch<Self::Tensor>>(&mut self, buffers: &[B]) -> Result<()> {
+        TRPO::learn(self, buffers)
+    }
+
+    /// Sets the learning rate of the value-function optimizer. The policy step
+    /// size is set by [`TRPOParams::target_kl`].
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.lm.set_learning_rate(learning_rate);
+    }
+
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.hooks
+            .progress_hook(&mut self.params, progress_remaining);
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Approximately solves `A x = b` for a symmetric positive-definite `A` given
+/// only matrix-vector products.
+fn conjugate_gradient(
+    mut matrix_vector_product: impl FnMut(&[f32]) -> anyhow::Result<Vec<f32>>,
+    b: &[f32],
+    max_steps: usize,
+) -> anyhow::Result<Vec<f32>> {
+    let mut x = vec![0.; b.len()];
+    let mut residual = b.to_vec();
+    let mut direction = b.to_vec();
+    let mut residual_norm = dot(&residual, &residual);
+    for _ in 0..max_steps {
+        if residual_norm < 1e-10 {
+            break;
+        }
+        let product = matrix_vector_product(&direction)?;
+        let alpha = residual_norm / dot(&direction, &product);
+        for ((x, r), (d, p)) in x
+            .iter_mut()
+            .zip(residual.iter_mut())
+            .zip(direction.iter().zip(&product))
+        {
+            *x += alpha * d;
+            *r -= alpha * p;
+        }
+        let new_residual_norm = dot(&residual, &residual);
+        let beta = new_residual_norm / residual_norm;
+        for (d, r) in direction.iter_mut().zip(&residual) {
+            *d = r + beta * *d;
+        }
+        residual_norm = new_residual_norm;
+    }
+    Ok(x)
+}
+
+// Unbiased and non-negative estimate of KL(old || new) from samples of the old
+// policy: mean((ratio - 1) - log(ratio)).
+fn approx_kl(logp_old: &[f32], logp: &[f32]) -> f32 {
+    let total = logp_old
+        .iter()
+        .zip(logp)
+        .map(|(old, new)| {
+            let log_ratio = new - old;
+            log_ratio.exp() - 1. - log_ratio
+        })
+        .sum::<f32>();
+    total / logp_old.len() as f32
+}
+
+#[cfg(test)]
+mod test {
+    use super::conjugate_gradient;
+
+    #[test]
+    fn conjugate_gradient_solves_spd_system() {
+        let matrix = [[4., 1., 0.], [1., 3., 1.], [0., 1., 2.]];
+        let expected = [1., -2., 3.];
+        let b: Vec<f32> = matrix
+            .iter()
+            .map(|row: &[f32; 3]| row.iter().zip(&expected).map(|(a, x)| a * x).sum())
+            .collect();
+        let product = |v: &[f32]| {
+            Ok(matrix
+                .iter()
+                .map(|row| row.iter().zip(v).map(|(a, x)| a * x).sum())
+                .collect())
+        };
+        let x = conjugate_gradient(product, &b, 10).unwrap();
+        for (x, expected) in x.iter().zip(expected) {
+            assert!((x - expected).abs() < 1e-4, "{x} != {expected}");
+        }
+    }
+}
@@ -3,15 +3,19 @@
 use anyhow::Result;
 use r2l_core::{
     buffers::TrajectoryBatch,
-    models::Policy,
+    models::{LearningModule, Policy},
     on_policy::{
         algorithm::Agent, learning_module::OnPolicyLearningModule, losses::FromPolicyValueLosses,
     },
     tensor::R2lTensor,
 };
 
-use crate::on_policy_algorithms::{
-    Advantages, BatchIndexIterator, Returns, batches_advantages_and_returns, sample,
+use crate::{
+    HookResult,
+    on_policy_algorithms::{
+        Advantages, BatchIndexIterator, Returns, batches_advantages_and_returns,
+        batches_discounted_returns, sample,
+    },
 };
 
 /// Hyperparameters controlling VPG training behavior.
@@ -22,6 +26,12 @@ pub struct VPGParams {
     pub lambda: f32,
     /// Minibatch size used during the learning pass.
     pub sample_size: usize,
+    /// Whether the learned value function is used as a baseline.
+    ///
+    /// When disabled, the policy is trained with REINFORCE: advantages are the
+    /// discounted Monte-Carlo returns instead of GAE estimates, and the value
+    /// function is not trained.
+    pub baseline: bool,
 }
 
 impl Default for VPGParams {
@@ -30,19 +40,73 @@ impl Default for VPGParams {
             gamma: 0.98,
             lambda: 0.8,
             sample_size: 64,
+            baseline: true,
         }
     }
 }
 
+/// Per-minibatch data exposed to [`VPGHook::batch_hook`].
+pub struct VPGBatchData<T: R2lTensor> {
+    /// Sampled observations in the minibatch.
+    pub observations: Vec<T>,
+    /// Sampled actions in the minibatch.
+    pub actions: Vec<T>,
+    /// Policy log-probabilities for the sampled actions.
+    pub logp: T,
+    /// Value-function predictions for the sampled observations, `None` when
+    /// the value function is not trained.
+    pub values_pred: Option<T>,
+}
+
+/// Hook interface for customizing VPG training over trajectory batches.
+pub trait VPGHook<M: OnPolicyLearningModule> {
+    fn before_learning_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+        &mut self,
+        _params: &mut VPGParams,
+        _module: &mut M,
+        _batches: &[B],
+        _advantages: &mut Advantages,
+        _returns: &mut Returns,
+    ) -> anyhow::Result<HookResult> {
+        Ok(HookResult::Continue)
+    }
+
+    fn batch_hook(
+        &mut self,
+        _params: &mut VPGParams,
+        _module: &mut M,
+        _losses: &mut <M as LearningModule>::Losses,
+        _data: &VPGBatchData<M::LearningTensor>,
+    ) -> anyhow::Result<HookResult> {
+        Ok(HookResult::Continue)
+    }
+
+    fn after_learning_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+        &mut self,
+        _params: &mut VPGParams,
+        _module: &mut M,
+        _batches: &[B],
+    ) -> anyhow::Result<HookResult> {
+        Ok(HookResult::Continue)
+    }
+
+    /// Called with the remaining fraction of training before each rollout is learned.
+    fn progress_hook(&mut self, _params: &mut VPGParams, _progress_remaining: f64) {}
+}
+
 /// Prototype Vanilla Policy Gradient algorithm over finalized trajectory batches.
-pub struct VPG<Module: OnPolicyLearningModule> {
+///
+/// With [`VPGParams::baseline`] disabled this is REINFORCE.
+pub struct VPG<Module: OnPolicyLearningModule, Hooks: VPGHook<Module>> {
     /// VPG hyperparameters.
     pub params: VPGParams,
     /// Learning module containing policy, value function, and optimizer state.
     pub lm: Module,
+    /// Hook implementation used to customize learning behavior.
+    pub hooks: Hooks,
 }
 
-impl<Module: OnPolicyLearningModule> VPG<Module> {
+impl<Module: OnPolicyLearningModule, Hooks: VPGHook<Module>> VPG<Module, Hooks> {
     fn batch_loop<B: TrajectoryBatch<Module::InferenceTensor>>(
         &mut self,
         batches: &[B],
@@ -57,34 +121,76 @@ impl<Module: OnPolicyLearningModule> VPG<Module> {
             };
             let (observations, actions) = sample(batches, &indices, Module::lifter);
             let advantages = lm.tensor_from_slice(&advantages.sample(&indices));
-            let returns = lm.tensor_from_slice(&returns.sample(&indices));
             let logp = lm.policy().log_probs(&observations, &actions)?;
-            let values_pred = lm.values(&observations)?;
             let policy_loss = advantages.mul(&logp)?.neg()?.mean()?;
-            let value_loss = returns.sub(&values_pred)?.sqr()?.mean()?;
-            let losses = Module::Losses::from_policy_value_losses(policy_loss, value_loss);
+            // REINFORCE leaves the value function alone, a constant loss has
+            // no gradient on it
+            let (values_pred, value_loss) = if self.params.baseline {
+                let returns = lm.tensor_from_slice(&returns.sample(&indices));
+                let values_pred = lm.values(&observations)?;
+                let value_loss = returns.sub(&values_pred)?.sqr()?.mean()?;
+                (Some(values_pred), value_loss)
+            } else {
+                (None, lm.tensor_from_slice(&[0.]).mean()?)
+            };
+            let mut losses = Module::Losses::from_policy_value_losses(policy_loss, value_loss);
+            let vpg_data = VPGBatchData {
+                observations,
+                actions,
+                logp,
+                values_pred,
+            };
+            r2l_core::return_on_hook_result!(self.hooks.batch_hook(
+                &mut self.params,
+                lm,
+                &mut losses,
+                &vpg_data
+            )?);
             lm.update(losses)?;
         }
     }
 
+    fn advantages_and_returns<B: TrajectoryBatch<Module::InferenceTensor>>(
+        &self,
+        batches: &[B],
+    ) -> Result<(Advantages, Returns)> {
+        if self.params.baseline {
+            return batches_advantages_and_returns(
+                batches,
+                &self.lm,
+                self.params.gamma,
+                self.params.lambda,
+                Module::lifter,
+            );
+        }
+        let returns = batches_discounted_returns(batches, self.params.gamma);
+        Ok((Advantages(returns.0.clone()), returns))
+    }
+
     /// Prototype learning entrypoint over finalized trajectory batches.
     pub fn learn<B: TrajectoryBatch<Module::InferenceTensor>>(
         &mut self,
         batches: &[B],
     ) -> Result<()> {
-        let (advantages, returns) = batches_advantages_and_returns(
+        let (mut advantages, mut returns) = self.advantages_and_returns(batches)?;
+        r2l_core::return_on_hook_result!(self.hooks.before_learning_hook(
+            &mut self.params,
+            &mut self.lm,
             batches,
-            &self.lm,
-            self.params.gamma,
-            self.params.lambda,
-            Module::lifter,
-        )?;
+            &mut advantages,
+            &mut returns
+        )?);
         self.batch_loop(batches, &advantages, &returns)?;
+        r2l_core::return_on_hook_result!(self.hooks.after_learning_hook(
+            &mut self.params,
+            &mut self.lm,
+            batches
+        )?);
         Ok(())
     }
 }
 
-impl<M: OnPolicyLearningModule> Agent for VPG<M> {
+impl<M: OnPolicyLearningModule, H: VPGHook<M>> Agent for VPG<M, H> {
     type Tensor = M::InferenceTensor;
     type Actor = M::InferencePolicy;
 
@@ -99,4 +205,9 @@ impl<M: OnPolicyLearningModule> Agent for VPG<M> {
     fn set_learning_rate(&mut self, learning_rate: f64) {
         self.lm.set_learning_rate(learning_rate);
     }
+
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.hooks
+            .progress_hook(&mut self.params, progress_remaining);
+    }
 }
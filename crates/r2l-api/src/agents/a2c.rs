@@ -1,14 +1,28 @@
+use std::collections::BTreeMap;
+
+use anyhow::bail;
 use burn::{module::AutodiffModule, tensor::backend::AutodiffBackend};
 use r2l_agents::on_policy_algorithms::a2c::A2C;
 use r2l_burn::{
-    distributions::PolicyKind, learning_module::PolicyValueModuleKind as BurnPolicyValueModuleKind,
+    distributions::PolicyKind,
+    learning_module::{
+        PolicyValueModuleKind as BurnPolicyValueModuleKind,
+        PolicyValueModuleState as BurnPolicyValueModuleState,
+    },
 };
 use r2l_candle::{
-    distributions::CandlePolicyKind, learning_module::PolicyValueModule as CandlePolicyValueModule,
+    distributions::CandlePolicyKind,
+    learning_module::{
+        PolicyValueModule as CandlePolicyValueModule,
+        PolicyValueModuleState as CandlePolicyValueModuleState,
+    },
+};
+use r2l_core::{
+    buffers::TrajectoryBatch,
+    on_policy::{algorithm::Agent, learning_module::StatefulLearningModule},
 };
-use r2l_core::{buffers::TrajectoryBatch, on_policy::algorithm::Agent};
 
-use crate::hooks::a2c::DefaultA2CHook;
+use crate::{agents::PopulationAgent, hooks::a2c::DefaultA2CHook};
 
 /// A2C agent specialized to the Burn backend.
 ///
@@ -81,3 +95,75 @@ impl Agent for A2CCandleAgent {
         self.0.shutdown();
     }
 }
+
+impl<B: AutodiffBackend> PopulationAgent for A2CBurnAgent<B> {
+    type State = BurnPolicyValueModuleState<B, PolicyKind<B>>;
+
+    fn state(&self) -> anyhow::Result<Self::State> {
+        self.0.lm.state()
+    }
+
+    fn load_state(&mut self, state: &Self::State) -> anyhow::Result<()> {
+        self.0.lm.load_state(state)
+    }
+
+    fn hyperparameters(&self) -> BTreeMap<String, f64> {
+        let mut hyperparameters = BTreeMap::new();
+        hyperparameters.insert("learning_rate".to_owned(), self.0.lm.policy_learning_rate());
+        hyperparameters.insert(
+            "entropy_coeff".to_owned(),
+            f64::from(self.0.hooks.entropy_coeff),
+        );
+        hyperparameters
+    }
+
+    fn set_hyperparameters(
+        &mut self,
+        hyperparameters: &BTreeMap<String, f64>,
+    ) -> anyhow::Result<()> {
+        for (name, &value) in hyperparameters {
+            match name.as_str() {
+                "learning_rate" => self.set_learning_rate(value),
+                "entropy_coeff" => self.0.hooks.entropy_coeff = value as f32,
+                _ => bail!("A2C has no `{name}` hyperparameter"),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PopulationAgent for A2CCandleAgent {
+    type State = CandlePolicyValueModuleState;
+
+    fn state(&self) -> anyhow::Result<Self::State> {
+        self.0.lm.state()
+    }
+
+    fn load_state(&mut self, state: &Self::State) -> anyhow::Result<()> {
+        self.0.lm.load_state(state)
+    }
+
+    fn hyperparameters(&self) -> BTreeMap<String, f64> {
+        let mut hyperparameters = BTreeMap::new();
+        hyperparameters.insert("learning_rate".to_owned(), self.0.lm.policy_learning_rate());
+        hyperparameters.insert(
+            "entropy_coeff".to_owned(),
+            f64::from(self.0.hooks.entropy_coeff),
+        );
+        hyperparameters
+    }
+
+    fn set_hyperparameters(
+        &mut self,
+        hyperparameters: &BTreeMap<String, f64>,
+    ) -> anyhow::Result<()> {
+        for (name, &value) in hyperparameters {
+            match name.as_str() {
+                "learning_rate" => self.set_learning_rate(value),
+                "entropy_coeff" => self.0.hooks.entropy_coeff = value as f32,
+                _ => bail!("A2C has no `{name}` hyperparameter"),
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,2 +1,41 @@
 pub mod a2c;
+pub mod ppg;
 pub mod ppo;
+pub mod trpo;
+pub mod vpg;
+
+use std::collections::BTreeMap;
+
+use r2l_core::on_policy::algorithm::Agent;
+
+/// Agent that can be a member of a
+/// [`PopulationBasedTraining`](crate::PopulationBasedTraining) run.
+///
+/// Members copy the learning module state of better members and perturb the
+/// hyperparameters exposed here by name.
+pub trait PopulationAgent: Agent {
+    /// Parameters and optimizer state of the learning module.
+    type State: Clone;
+
+    /// Copies the parameters and optimizer state of the learning module.
+    fn state(&self) -> anyhow::Result<Self::State>;
+
+    /// Overwrites the parameters and optimizer state of the learning module
+    /// with the state of an agent of the same architecture.
+    fn load_state(&mut self, state: &Self::State) -> anyhow::Result<()>;
+
+    /// Current values of the perturbable hyperparameters, by name.
+    fn hyperparameters(&self) -> BTreeMap<String, f64>;
+
+    /// Sets hyperparameters by the names of
+    /// [`hyperparameters`](Self::hyperparameters), failing on unknown names.
+    ///
+    /// A value set here replaces the schedule of its hyperparameter. The
+    /// learning rate schedule belongs to the outer hooks, which
+    /// [`PopulationBasedTraining`](crate::PopulationBasedTraining) overrides
+    /// after every rollout.
+    fn set_hyperparameters(
+        &mut self,
+        hyperparameters: &BTreeMap<String, f64>,
+    ) -> anyhow::Result<()>;
+}
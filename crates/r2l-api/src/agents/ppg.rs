@@ -0,0 +1,280 @@
+use burn::{module::AutodiffModule, tensor::backend::AutodiffBackend};
+use r2l_agents::on_policy_algorithms::ppg::PPG;
+use r2l_burn::{
+    distributions::PolicyKind, learning_module::PolicyValueModuleKind as BurnPolicyValueModuleKind,
+};
+use r2l_candle::{
+    distributions::CandlePolicyKind, learning_module::PolicyValueModule as CandlePolicyValueModule,
+};
+use r2l_core::{buffers::TrajectoryBatch, on_policy::algorithm::Agent};
+
+use crate::hooks::ppg::DefaultPPGHook;
+
+/// PPG agent specialized to the Burn backend.
+///
+/// This is the concrete agent type produced by
+/// [`PPGBurnAgentBuilder`](crate::PPGBurnAgentBuilder) and
+/// [`PPGBurnAlgorithmBuilder`](crate::PPGBurnAlgorithmBuilder). It wraps the
+/// core [`PPG`](r2l_agents::on_policy_algorithms::ppg::PPG) implementation
+/// with Burn learning modules and the default PPG training hook.
+///
+/// Use this type when you want an [`Agent`](r2l_core::on_policy::algorithm::Agent)
+/// backed by Burn instead of the default Candle backend.
+pub struct PPGBurnAgent<B: AutodiffBackend>(
+    pub PPG<BurnPolicyValueModuleKind<B>, DefaultPPGHook<BurnPolicyValueModuleKind<B>>>,
+);
+
+impl<B: AutodiffBackend> Agent for PPGBurnAgent<B> {
+    type Tensor = burn::Tensor<B::InnerBackend, 1>;
+    type Actor = <PolicyKind<B> as AutodiffModule<B>>::InnerModule;
+
+    fn actor(&self) -> Self::Actor {
+        self.0.actor()
+    }
+
+    fn learn<BT: TrajectoryBatch<Self::Tensor>>(&mut self, buffers: &[BT]) -> anyhow::Result<()> {
+        self.0.learn(buffers)
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.0.set_learning_rate(learning_rate);
+    }
+
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.0.set_progress_remaining(progress_remaining);
+    }
+
+    fn shutdown(&mut self) {
+        self.0.shutdown();
+    }
+}
+
+/// PPG agent specialized to the Candle backend.
+///
+/// This is the default concrete PPG agent type used by
+/// [`PPGAgentBuilder`](crate::PPGAgentBuilder),
+/// [`PPGCandleAgentBuilder`](crate::PPGCandleAgentBuilder), and
+/// [`PPGAlgorithmBuilder`](crate::PPGAlgorithmBuilder). It wraps the core
+/// [`PPG`](r2l_agents::on_policy_algorithms::ppg::PPG) implementation with
+/// Candle learning modules and the default PPG training hook.
+///
+/// Use this type when you want an [`Agent`](r2l_core::on_policy::algorithm::Agent)
+/// on the default Candle backend, optionally selecting a device through
+/// [`with_candle`](crate::PPGAlgorithmBuilder::with_candle).
+pub struct PPGCandleAgent(
+    pub PPG<CandlePolicyValueModule, DefaultPPGHook<CandlePolicyValueModule>>,
+);
+
+impl Agent for PPGCandleAgent {
+    type Tensor = candle_core::Tensor;
+    type Actor = CandlePolicyKind;
+
+    fn actor(&self) -> Self::Actor {
+        self.0.actor()
+    }
+
+    fn learn<BT: TrajectoryBatch<Self::Tensor>>(&mut self, buffers: &[BT]) -> anyhow::Result<()> {
+        self.0.learn(buffers)
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.0.set_learning_rate(learning_rate);
+    }
+
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.0.set_progress_remaining(progress_remaining);
+    }
+
+    fn shutdown(&mut self) {
+        self.0.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use candle_core::Device;
+    use r2l_agents::on_policy_algorithms::{
+        ppg::{AuxiliaryRolloutStorage, PPG, PPGAuxiliaryBatchData, PPGHook, PPGParams},
+        ppo::{PPO, PPOHook, PPOParams},
+    };
+    use r2l_core::{
+        HookResult,
+        buffers::{Memory, buffer::TrajectoryBuffer},
+        env::Space,
+        init::WeightInit,
+        models::{ActivationFunction, OptimizerConfig},
+        on_policy::learning_module::{AuxiliaryValueLearningModule, TrustRegionLearningModule},
+        tensor::{R2lTensor, TensorData},
+    };
+
+    use crate::{
+        BurnBackend,
+        builders::learning_module::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType},
+    };
+
+    const OBSERVATION_SIZE: usize = 4;
+
+    // Records the policy parameters around the auxiliary phase and the losses
+    // of its minibatches.
+    #[derive(Default)]
+    struct PhaseRecorder {
+        parameters_before: Option<Vec<f32>>,
+        parameters_after: Option<Vec<f32>>,
+        auxiliary_value_losses: Vec<f32>,
+        value_losses: Vec<f32>,
+    }
+
+    impl<M: AuxiliaryValueLearningModule + TrustRegionLearningModule> PPOHook<M> for PhaseRecorder {}
+
+    impl<M: AuxiliaryValueLearningModule + TrustRegionLearningModule> PPGHook<M> for PhaseRecorder {
+        fn auxiliary_batch_hook(
+            &mut self,
+            _params: &mut PPGParams,
+            module: &mut M,
+            data: &mut PPGAuxiliaryBatchData<M::LearningTensor>,
+        ) -> Result<HookResult> {
+            if self.parameters_before.is_none() {
+                self.parameters_before = Some(module.policy_parameters()?);
+            }
+            self.auxiliary_value_losses
+                .push(data.auxiliary_value_loss.to_vec()[0]);
+            self.value_losses.push(data.value_loss.to_vec()[0]);
+            Ok(HookResult::Continue)
+        }
+
+        fn after_auxiliary_phase_hook(
+            &mut self,
+            _params: &mut PPGParams,
+            module: &mut M,
+        ) -> Result<HookResult> {
+            self.parameters_after = Some(module.policy_parameters()?);
+            Ok(HookResult::Continue)
+        }
+    }
+
+    fn builder() -> OnPolicyLearningModuleBuilder {
+        OnPolicyLearningModuleBuilder {
+            shared_hidden_layers: vec![],
+            policy_hidden_layers: vec![16],
+            value_hidden_layers: vec![16],
+            activation_function: ActivationFunction::default(),
+            log_std_init: 0.0,
+            weight_init: WeightInit::default(),
+            learning_module_type: OnPolicyLearningModuleType::Split {
+                policy_max_grad_norm: None,
+                policy_optimizer: OptimizerConfig::adam(1e-2),
+                value_max_grad_norm: None,
+                value_optimizer: OptimizerConfig::adam(1e-2),
+            },
+        }
+    }
+
+    fn ppg<M: AuxiliaryValueLearningModule + TrustRegionLearningModule>(
+        lm: M,
+    ) -> PPG<M, PhaseRecorder> {
+        PPG {
+            params: PPGParams {
+                n_policy_iterations: 2,
+                n_aux_epochs: 8,
+                beta_clone: 1.,
+                aux_sample_size: 64,
+            },
+            ppo: PPO {
+                params: PPOParams::default(),
+                lm,
+                hooks: PhaseRecorder::default(),
+            },
+            storage: AuxiliaryRolloutStorage::default(),
+        }
+    }
+
+    // 32 steps of episodes ending every 8 steps, with a reward of one per step.
+    fn rollout<T: R2lTensor>() -> TrajectoryBuffer<T> {
+        let observation = |step: usize| {
+            let step = step as f32;
+            T::from_vec_and_shape(
+                vec![step / 8., (step * 0.3).cos(), (step * 0.7).sin(), 1.],
+                vec![OBSERVATION_SIZE],
+            )
+        };
+        let mut buffer = TrajectoryBuffer::default();
+        for step in 0..32 {
+            let action = if step % 3 == 0 { [1., 0.] } else { [0., 1.] };
+            buffer.push(Memory {
+                state: observation(step % 8),
+                next_state: observation((step + 1) % 8),
+                action: T::from_vec_and_shape(action.to_vec(), vec![2]),
+                reward: 1.,
+                terminated: step % 8 == 7,
+                truncated: false,
+            });
+        }
+        buffer
+    }
+
+    fn check_auxiliary_phase<M: AuxiliaryValueLearningModule + TrustRegionLearningModule>(
+        lm: M,
+    ) -> Result<()> {
+        let mut ppg = ppg(lm);
+        let rollout = [rollout::<M::InferenceTensor>()];
+        let views = rollout
+            .iter()
+            .map(TrajectoryBuffer::to_trajectory_view)
+            .collect::<Vec<_>>();
+
+        ppg.learn(&views)?;
+        assert_eq!(ppg.storage.rollouts(), 1);
+        assert_eq!(ppg.storage.len(), 32);
+        assert!(ppg.ppo.hooks.auxiliary_value_losses.is_empty());
+
+        ppg.learn(&views)?;
+        assert!(ppg.storage.is_empty());
+        assert_eq!(ppg.storage.rollouts(), 0);
+        let recorder = &ppg.ppo.hooks;
+        // one minibatch of the 64 stored observations per epoch
+        assert_eq!(recorder.auxiliary_value_losses.len(), 8);
+        assert_ne!(recorder.parameters_before, recorder.parameters_after);
+        let first = recorder.auxiliary_value_losses[0];
+        let last = recorder.auxiliary_value_losses[7];
+        assert!(
+            last < first,
+            "auxiliary value loss went from {first} to {last}"
+        );
+        let first = recorder.value_losses[0];
+        let last = recorder.value_losses[7];
+        assert!(last < first, "value loss went from {first} to {last}");
+        Ok(())
+    }
+
+    fn action_space() -> Space<TensorData> {
+        Space::Discrete(2)
+    }
+
+    #[test]
+    fn candle_auxiliary_phase_trains_the_head_and_clears_the_storage() -> Result<()> {
+        for head_hidden_layers in [vec![], vec![8]] {
+            let lm = builder().build_candle_with_auxiliary_value_head(
+                OBSERVATION_SIZE,
+                action_space(),
+                &head_hidden_layers,
+                &Device::Cpu,
+            )?;
+            check_auxiliary_phase(lm)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn burn_auxiliary_phase_trains_the_head_and_clears_the_storage() -> Result<()> {
+        for head_hidden_layers in [vec![], vec![8]] {
+            let lm = builder().build_burn_with_auxiliary_value_head::<BurnBackend, _>(
+                OBSERVATION_SIZE,
+                action_space(),
+                &head_hidden_layers,
+            )?;
+            check_auxiliary_phase(lm)?;
+        }
+        Ok(())
+    }
+}
@@ -1,14 +1,28 @@
+use std::collections::BTreeMap;
+
+use anyhow::bail;
 use burn::{module::AutodiffModule, tensor::backend::AutodiffBackend};
 use r2l_agents::on_policy_algorithms::ppo::PPO;
 use r2l_burn::{
-    distributions::PolicyKind, learning_module::PolicyValueModuleKind as BurnPolicyValueModuleKind,
+    distributions::PolicyKind,
+    learning_module::{
+        PolicyValueModuleKind as BurnPolicyValueModuleKind,
+        PolicyValueModuleState as BurnPolicyValueModuleState,
+    },
 };
 use r2l_candle::{
-    distributions::CandlePolicyKind, learning_module::PolicyValueModule as CandlePolicyValueModule,
+    distributions::CandlePolicyKind,
+    learning_module::{
+        PolicyValueModule as CandlePolicyValueModule,
+        PolicyValueModuleState as CandlePolicyValueModuleState,
+    },
+};
+use r2l_core::{
+    buffers::TrajectoryBatch,
+    on_policy::{algorithm::Agent, learning_module::StatefulLearningModule},
 };
-use r2l_core::{buffers::TrajectoryBatch, on_policy::algorithm::Agent};
 
-use crate::hooks::ppo::DefaultPPOHook;
+use crate::{agents::PopulationAgent, hooks::ppo::DefaultPPOHook};
 
 /// PPO agent specialized to the Burn backend.
 pub struct PPOBurnAgent<B: AutodiffBackend>(
@@ -31,6 +45,10 @@ impl<B: AutodiffBackend> Agent for PPOBurnAgent<B> {
         self.0.set_learning_rate(learning_rate);
     }
 
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.0.set_progress_remaining(progress_remaining);
+    }
+
     fn shutdown(&mut self) {
         self.0.shutdown();
     }
@@ -57,7 +75,181 @@ impl Agent for PPOCandleAgent {
         self.0.set_learning_rate(learning_rate);
     }
 
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.0.set_progress_remaining(progress_remaining);
+    }
+
     fn shutdown(&mut self) {
         self.0.shutdown();
     }
 }
+
+impl<B: AutodiffBackend> PopulationAgent for PPOBurnAgent<B> {
+    type State = BurnPolicyValueModuleState<B, PolicyKind<B>>;
+
+    fn state(&self) -> anyhow::Result<Self::State> {
+        self.0.lm.state()
+    }
+
+    fn load_state(&mut self, state: &Self::State) -> anyhow::Result<()> {
+        self.0.lm.load_state(state)
+    }
+
+    fn hyperparameters(&self) -> BTreeMap<String, f64> {
+        let mut hyperparameters = BTreeMap::new();
+        hyperparameters.insert("learning_rate".to_owned(), self.0.lm.policy_learning_rate());
+        hyperparameters.insert(
+            "entropy_coeff".to_owned(),
+            f64::from(self.0.hooks.entropy_coeff),
+        );
+        hyperparameters.insert("clip_range".to_owned(), f64::from(self.0.params.clip_range));
+        hyperparameters
+    }
+
+    fn set_hyperparameters(
+        &mut self,
+        hyperparameters: &BTreeMap<String, f64>,
+    ) -> anyhow::Result<()> {
+        for (name, &value) in hyperparameters {
+            match name.as_str() {
+                "learning_rate" => self.set_learning_rate(value),
+                "entropy_coeff" => {
+                    // the value replaces its schedule, which would overwrite it every rollout
+                    self.0.hooks.schedules.entropy_coeff = None;
+                    self.0.hooks.entropy_coeff = value as f32;
+                }
+                "clip_range" => {
+                    self.0.hooks.schedules.clip_range = None;
+                    self.0.params.clip_range = value as f32;
+                }
+                _ => bail!("PPO has no `{name}` hyperparameter"),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PopulationAgent for PPOCandleAgent {
+    type State = CandlePolicyValueModuleState;
+
+    fn state(&self) -> anyhow::Result<Self::State> {
+        self.0.lm.state()
+    }
+
+    fn load_state(&mut self, state: &Self::State) -> anyhow::Result<()> {
+        self.0.lm.load_state(state)
+    }
+
+    fn hyperparameters(&self) -> BTreeMap<String, f64> {
+        let mut hyperparameters = BTreeMap::new();
+        hyperparameters.insert("learning_rate".to_owned(), self.0.lm.policy_learning_rate());
+        hyperparameters.insert(
+            "entropy_coeff".to_owned(),
+            f64::from(self.0.hooks.entropy_coeff),
+        );
+        hyperparameters.insert("clip_range".to_owned(), f64::from(self.0.params.clip_range));
+        hyperparameters
+    }
+
+    fn set_hyperparameters(
+        &mut self,
+        hyperparameters: &BTreeMap<String, f64>,
+    ) -> anyhow::Result<()> {
+        for (name, &value) in hyperparameters {
+            match name.as_str() {
+                "learning_rate" => self.set_learning_rate(value),
+                "entropy_coeff" => {
+                    // the value replaces its schedule, which would overwrite it every rollout
+                    self.0.hooks.schedules.entropy_coeff = None;
+                    self.0.hooks.entropy_coeff = value as f32;
+                }
+                "clip_range" => {
+                    self.0.hooks.schedules.clip_range = None;
+                    self.0.params.clip_range = value as f32;
+                }
+                _ => bail!("PPO has no `{name}` hyperparameter"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use anyhow::Result;
+    use candle_core::Device;
+    use r2l_agents::on_policy_algorithms::ppo::{PPO, PPOParams};
+    use r2l_core::{buffers::buffer::TrajectoryBuffer, tensor::R2lTensor};
+
+    use super::{PPOBurnAgent, PPOCandleAgent};
+    use crate::{
+        BurnBackend, Schedule,
+        agents::PopulationAgent,
+        builders::ppo::hook::DefaultPPOHookBuilder,
+        hooks::ppo::{
+            DefaultPPOHook,
+            test::{OBSERVATION_SIZE, action_space, builder, rollout},
+        },
+    };
+
+    fn hooks<T>() -> DefaultPPOHook<T> {
+        DefaultPPOHookBuilder::new(1)
+            .with_log_progress(false)
+            .with_total_epochs(1)
+            .with_clip_range_schedule(Schedule::linear(0.2, 0.1))
+            .with_entropy_coeff_schedule(Schedule::linear(0.1, 0.))
+            .build()
+    }
+
+    // The perturbed values still hold after the next rollout was learned.
+    fn check_perturbed_values_replace_schedules<A: PopulationAgent>(mut agent: A) -> Result<()>
+    where
+        A::Tensor: R2lTensor,
+    {
+        let perturbed = BTreeMap::from([
+            ("clip_range".to_owned(), 0.25),
+            ("entropy_coeff".to_owned(), 0.5),
+        ]);
+        agent.set_hyperparameters(&perturbed)?;
+        agent.set_progress_remaining(0.5);
+        let rollout = [rollout::<A::Tensor>(1.)];
+        let views = rollout
+            .iter()
+            .map(TrajectoryBuffer::to_trajectory_view)
+            .collect::<Vec<_>>();
+        agent.learn(&views)?;
+        let hyperparameters = agent.hyperparameters();
+        assert_eq!(hyperparameters["clip_range"], 0.25);
+        assert_eq!(hyperparameters["entropy_coeff"], 0.5);
+        Ok(())
+    }
+
+    fn params() -> PPOParams {
+        PPOParams {
+            sample_size: 8,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn candle_perturbed_values_replace_schedules() -> Result<()> {
+        let lm = builder().build_candle(OBSERVATION_SIZE, action_space(), &Device::Cpu)?;
+        check_perturbed_values_replace_schedules(PPOCandleAgent(PPO {
+            lm,
+            hooks: hooks(),
+            params: params(),
+        }))
+    }
+
+    #[test]
+    fn burn_perturbed_values_replace_schedules() -> Result<()> {
+        let lm = builder().build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?;
+        check_perturbed_values_replace_schedules(PPOBurnAgent(PPO {
+            lm,
+            hooks: hooks(),
+            params: params(),
+        }))
+    }
+}
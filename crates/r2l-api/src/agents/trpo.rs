@@ -0,0 +1,91 @@
+use burn::{module::AutodiffModule, tensor::backend::AutodiffBackend};
+use r2l_agents::on_policy_algorithms::trpo::TRPO;
+use r2l_burn::{
+    distributions::PolicyKind, learning_module::PolicyValueModuleKind as BurnPolicyValueModuleKind,
+};
+use r2l_candle::{
+    distributions::CandlePolicyKind, learning_module::PolicyValueModule as CandlePolicyValueModule,
+};
+use r2l_core::{buffers::TrajectoryBatch, on_policy::algorithm::Agent};
+
+use crate::hooks::trpo::DefaultTRPOHook;
+
+/// TRPO agent specialized to the Burn backend.
+///
+/// This is the concrete agent type produced by
+/// [`TRPOBurnAgentBuilder`](crate::TRPOBurnAgentBuilder) and
+/// [`TRPOBurnAlgorithmBuilder`](crate::TRPOBurnAlgorithmBuilder). It wraps the
+/// core [`TRPO`](r2l_agents::on_policy_algorithms::trpo::TRPO) implementation
+/// with Burn learning modules and the default TRPO training hook.
+///
+/// Use this type when you want an [`Agent`](r2l_core::on_policy::algorithm::Agent)
+/// backed by Burn instead of the default Candle backend.
+pub struct TRPOBurnAgent<B: AutodiffBackend>(
+    pub TRPO<BurnPolicyValueModuleKind<B>, DefaultTRPOHook<BurnPolicyValueModuleKind<B>>>,
+);
+
+impl<B: AutodiffBackend> Agent for TRPOBurnAgent<B> {
+    type Tensor = burn::Tensor<B::InnerBackend, 1>;
+    type Actor = <PolicyKind<B> as AutodiffModule<B>>::InnerModule;
+
+    fn actor(&self) -> Self::Actor {
+        self.0.actor()
+    }
+
+    fn learn<BT: TrajectoryBatch<Self::Tensor>>(&mut self, buffers: &[BT]) -> anyhow::Result<()> {
+        self.0.learn(buffers)
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.0.set_learning_rate(learning_rate);
+    }
+
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.0.set_progress_remaining(progress_remaining);
+    }
+
+    fn shutdown(&mut self) {
+        self.0.shutdown();
+    }
+}
+
+/// TRPO agent specialized to the Candle backend.
+///
+/// This is the default concrete TRPO agent type used by
+/// [`TRPOAgentBuilder`](crate::TRPOAgentBuilder),
+/// [`TRPOCandleAgentBuilder`](crate::TRPOCandleAgentBuilder), and
+/// [`TRPOAlgorithmBuilder`](crate::TRPOAlgorithmBuilder). It wraps the core
+/// [`TRPO`](r2l_agents::on_policy_algorithms::trpo::TRPO) implementation with
+/// Candle learning modules and the default TRPO training hook.
+///
+/// Use this type when you want an [`Agent`](r2l_core::on_policy::algorithm::Agent)
+/// on the default Candle backend, optionally selecting a device through
+/// [`with_candle`](crate::TRPOAlgorithmBuilder::with_candle).
+pub struct TRPOCandleAgent(
+    pub TRPO<CandlePolicyValueModule, DefaultTRPOHook<CandlePolicyValueModule>>,
+);
+
+impl Agent for TRPOCandleAgent {
+    type Tensor = candle_core::Tensor;
+    type Actor = CandlePolicyKind;
+
+    fn actor(&self) -> Self::Actor {
+        self.0.actor()
+    }
+
+    fn learn<BT: TrajectoryBatch<Self::Tensor>>(&mut self, buffers: &[BT]) -> anyhow::Result<()> {
+        self.0.learn(buffers)
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.0.set_learning_rate(learning_rate);
+    }
+
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.0.set_progress_remaining(progress_remaining);
+    }
+
+    fn shutdown(&mut self) {
+        self.0.shutdown();
+    }
+}
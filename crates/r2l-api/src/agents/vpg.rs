@@ -0,0 +1,257 @@
+use burn::{module::AutodiffModule, tensor::backend::AutodiffBackend};
+use r2l_agents::on_policy_algorithms::vpg::VPG;
+use r2l_burn::{
+    distributions::PolicyKind, learning_module::PolicyValueModuleKind as BurnPolicyValueModuleKind,
+};
+use r2l_candle::{
+    distributions::CandlePolicyKind, learning_module::PolicyValueModule as CandlePolicyValueModule,
+};
+use r2l_core::{buffers::TrajectoryBatch, on_policy::algorithm::Agent};
+
+use crate::hooks::vpg::DefaultVPGHook;
+
+/// VPG agent specialized to the Burn backend.
+///
+/// This is the concrete agent type produced by
+/// [`VPGBurnAgentBuilder`](crate::VPGBurnAgentBuilder) and
+/// [`VPGBurnAlgorithmBuilder`](crate::VPGBurnAlgorithmBuilder). It wraps the
+/// core [`VPG`](r2l_agents::on_policy_algorithms::vpg::VPG) implementation
+/// with Burn learning modules and the default VPG training hook.
+///
+/// Use this type when you want an [`Agent`](r2l_core::on_policy::algorithm::Agent)
+/// backed by Burn instead of the default Candle backend.
+pub struct VPGBurnAgent<B: AutodiffBackend>(
+    pub VPG<BurnPolicyValueModuleKind<B>, DefaultVPGHook<BurnPolicyValueModuleKind<B>>>,
+);
+
+impl<B: AutodiffBackend> Agent for VPGBurnAgent<B> {
+    type Tensor = burn::Tensor<B::InnerBackend, 1>;
+    type Actor = <PolicyKind<B> as AutodiffModule<B>>::InnerModule;
+
+    fn actor(&self) -> Self::Actor {
+        self.0.actor()
+    }
+
+    fn learn<BT: TrajectoryBatch<Self::Tensor>>(&mut self, buffers: &[BT]) -> anyhow::Result<()> {
+        self.0.learn(buffers)
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.0.set_learning_rate(learning_rate);
+    }
+
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.0.set_progress_remaining(progress_remaining);
+    }
+
+    fn shutdown(&mut self) {
+        self.0.shutdown();
+    }
+}
+
+/// VPG agent specialized to the Candle backend.
+///
+/// This is the default concrete VPG agent type used by
+/// [`VPGAgentBuilder`](crate::VPGAgentBuilder),
+/// [`VPGCandleAgentBuilder`](crate::VPGCandleAgentBuilder), and
+/// [`VPGAlgorithmBuilder`](crate::VPGAlgorithmBuilder). It wraps the core
+/// [`VPG`](r2l_agents::on_policy_algorithms::vpg::VPG) implementation with
+/// Candle learning modules and the default VPG training hook.
+///
+/// Use this type when you want an [`Agent`](r2l_core::on_policy::algorithm::Agent)
+/// on the default Candle backend, optionally selecting a device through
+/// [`with_candle`](crate::VPGAlgorithmBuilder::with_candle).
+pub struct VPGCandleAgent(
+    pub VPG<CandlePolicyValueModule, DefaultVPGHook<CandlePolicyValueModule>>,
+);
+
+impl Agent for VPGCandleAgent {
+    type Tensor = candle_core::Tensor;
+    type Actor = CandlePolicyKind;
+
+    fn actor(&self) -> Self::Actor {
+        self.0.actor()
+    }
+
+    fn learn<BT: TrajectoryBatch<Self::Tensor>>(&mut self, buffers: &[BT]) -> anyhow::Result<()> {
+        self.0.learn(buffers)
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.0.set_learning_rate(learning_rate);
+    }
+
+    fn set_progress_remaining(&mut self, progress_remaining: f64) {
+        self.0.set_progress_remaining(progress_remaining);
+    }
+
+    fn shutdown(&mut self) {
+        self.0.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use candle_core::Device;
+    use r2l_agents::on_policy_algorithms::vpg::{VPG, VPGBatchData, VPGHook, VPGParams};
+    use r2l_core::{
+        HookResult,
+        buffers::{Memory, buffer::TrajectoryBuffer},
+        env::Space,
+        init::WeightInit,
+        models::{ActivationFunction, LearningModule, OptimizerConfig},
+        on_policy::{
+            algorithm::Agent,
+            learning_module::{OnPolicyLearningModule, TrustRegionLearningModule},
+        },
+        tensor::{R2lTensor, TensorData},
+    };
+
+    use crate::{
+        BurnBackend,
+        builders::learning_module::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType},
+    };
+
+    const OBSERVATION_SIZE: usize = 4;
+
+    // Records the progress it is given and whether batches carried values.
+    #[derive(Default)]
+    struct Recorder {
+        progress_remaining: Vec<f64>,
+        batches_with_values: usize,
+        batches_without_values: usize,
+    }
+
+    impl<M: OnPolicyLearningModule> VPGHook<M> for Recorder {
+        fn batch_hook(
+            &mut self,
+            _params: &mut VPGParams,
+            _module: &mut M,
+            _losses: &mut <M as LearningModule>::Losses,
+            data: &VPGBatchData<M::LearningTensor>,
+        ) -> Result<HookResult> {
+            match data.values_pred {
+                Some(_) => self.batches_with_values += 1,
+                None => self.batches_without_values += 1,
+            }
+            Ok(HookResult::Continue)
+        }
+
+        fn progress_hook(&mut self, _params: &mut VPGParams, progress_remaining: f64) {
+            self.progress_remaining.push(progress_remaining);
+        }
+    }
+
+    fn builder(learning_module_type: OnPolicyLearningModuleType) -> OnPolicyLearningModuleBuilder {
+        OnPolicyLearningModuleBuilder {
+            shared_hidden_layers: vec![],
+            policy_hidden_layers: vec![16],
+            value_hidden_layers: vec![16],
+            activation_function: ActivationFunction::default(),
+            log_std_init: 0.0,
+            weight_init: WeightInit::default(),
+            learning_module_type,
+        }
+    }
+
+    // AdamW decays every parameter it steps, so a value function left alone
+    // by REINFORCE has to be skipped by the optimizer, not just given a zero
+    // gradient.
+    fn joint() -> OnPolicyLearningModuleType {
+        OnPolicyLearningModuleType::Joint {
+            max_grad_norm: None,
+            optimizer: OptimizerConfig::adamw(1e-2),
+        }
+    }
+
+    fn split() -> OnPolicyLearningModuleType {
+        joint().with_value_optimizer(OptimizerConfig::adamw(1e-2))
+    }
+
+    fn observation<T: R2lTensor>(step: usize) -> T {
+        let step = step as f32;
+        T::from_vec_and_shape(
+            vec![step / 8., (step * 0.3).cos(), (step * 0.7).sin(), 1.],
+            vec![OBSERVATION_SIZE],
+        )
+    }
+
+    // 32 steps of episodes ending every 8 steps, with a reward of one per step.
+    fn rollout<T: R2lTensor>() -> TrajectoryBuffer<T> {
+        let mut buffer = TrajectoryBuffer::default();
+        for step in 0..32 {
+            let action = if step % 3 == 0 { [1., 0.] } else { [0., 1.] };
+            buffer.push(Memory {
+                state: observation(step % 8),
+                next_state: observation((step + 1) % 8),
+                action: T::from_vec_and_shape(action.to_vec(), vec![2]),
+                reward: 1.,
+                terminated: step % 8 == 7,
+                truncated: false,
+            });
+        }
+        buffer
+    }
+
+    fn values<M: OnPolicyLearningModule>(lm: &M) -> Result<Vec<f32>> {
+        let observations = (0..8)
+            .map(|step| M::lifter(&observation(step)))
+            .collect::<Vec<_>>();
+        Ok(lm.values(&observations)?.to_vec())
+    }
+
+    // Learns one rollout and returns whether the value function changed.
+    fn check_learning<M: TrustRegionLearningModule>(lm: M, baseline: bool) -> Result<bool> {
+        let mut vpg = VPG {
+            params: VPGParams {
+                baseline,
+                ..Default::default()
+            },
+            lm,
+            hooks: Recorder::default(),
+        };
+        vpg.set_progress_remaining(0.25);
+        assert_eq!(vpg.hooks.progress_remaining, [0.25]);
+
+        let rollout = [rollout::<M::InferenceTensor>()];
+        let views = rollout
+            .iter()
+            .map(TrajectoryBuffer::to_trajectory_view)
+            .collect::<Vec<_>>();
+        let parameters = vpg.lm.policy_parameters()?;
+        let values_before = values(&vpg.lm)?;
+        Agent::learn(&mut vpg, &views)?;
+        assert_ne!(vpg.lm.policy_parameters()?, parameters);
+        // batches carry values exactly when the value function is trained
+        assert_eq!(vpg.hooks.batches_with_values > 0, baseline);
+        assert_eq!(vpg.hooks.batches_without_values > 0, !baseline);
+        Ok(values(&vpg.lm)? != values_before)
+    }
+
+    fn action_space() -> Space<TensorData> {
+        Space::Discrete(2)
+    }
+
+    #[test]
+    fn candle_reinforce_leaves_the_value_function_alone() -> Result<()> {
+        for layout in [joint, split] {
+            let build =
+                || builder(layout()).build_candle(OBSERVATION_SIZE, action_space(), &Device::Cpu);
+            assert!(!check_learning(build()?, false)?);
+            assert!(check_learning(build()?, true)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn burn_reinforce_leaves_the_value_function_alone() -> Result<()> {
+        for layout in [joint, split] {
+            let build =
+                || builder(layout()).build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space());
+            assert!(!check_learning(build()?, false)?);
+            assert!(check_learning(build()?, true)?);
+        }
+        Ok(())
+    }
+}
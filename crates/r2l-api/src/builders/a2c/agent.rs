@@ -2,9 +2,14 @@ use std::sync::mpsc::Sender;
 
 use burn::prelude::Backend;
 use candle_core::Device;
-use candle_nn::ParamsAdamW;
 use r2l_agents::on_policy_algorithms::a2c::{A2C, A2CParams};
-use r2l_core::{env::Space, models::ActivationFunction, tensor::R2lTensor};
+use r2l_core::{
+    env::Space,
+    episode::EpisodeMonitor,
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+    tensor::R2lTensor,
+};
 
 use crate::{
     BurnBackend,
@@ -16,7 +21,8 @@ use crate::{
         },
         learning_module::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType},
     },
-    hooks::a2c::A2CStats,
+    hooks::{a2c::A2CStats, early_stopping::LossMonitor},
+    metrics::MetricsLogger,
 };
 
 /// Builder for A2C agents.
@@ -53,13 +59,15 @@ impl A2CAgentBuilder {
             hook_builder: DefaultA2CHookBuilder::new(n_envs),
             params: A2CParams::default(),
             learning_module_builder: OnPolicyLearningModuleBuilder {
+                shared_hidden_layers: vec![],
                 policy_hidden_layers: vec![64, 64],
                 value_hidden_layers: vec![64, 64],
                 activation_function: ActivationFunction::default(),
                 log_std_init: 0.0,
+                weight_init: WeightInit::default(),
                 learning_module_type: OnPolicyLearningModuleType::Joint {
                     max_grad_norm: None,
-                    params: ParamsAdamW {
+                    optimizer: OptimizerConfig::AdamW {
                         lr: 3e-4,
                         beta1: 0.9,
                         beta2: 0.999,
@@ -151,6 +159,21 @@ impl AgentBuilder for A2CAgentBuilder {
         let params = self.params;
         Ok(A2CCandleAgent(A2C { lm, hooks, params }))
     }
+
+    fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.hook_builder = self.hook_builder.with_metrics_logger(logger);
+        self
+    }
+
+    fn with_loss_monitor(mut self, monitor: Option<LossMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_loss_monitor(monitor);
+        self
+    }
 }
 
 impl AgentBuilder for A2CBurnAgentBuilder {
@@ -170,4 +193,19 @@ impl AgentBuilder for A2CBurnAgentBuilder {
         let params = self.params;
         Ok(A2CBurnAgent(A2C { lm, hooks, params }))
     }
+
+    fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.hook_builder = self.hook_builder.with_metrics_logger(logger);
+        self
+    }
+
+    fn with_loss_monitor(mut self, monitor: Option<LossMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_loss_monitor(monitor);
+        self
+    }
 }
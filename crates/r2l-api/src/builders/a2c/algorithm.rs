@@ -1,11 +1,11 @@
 use std::sync::mpsc::Sender;
 
 use candle_core::Device;
-use candle_nn::ParamsAdamW;
 use r2l_agents::on_policy_algorithms::a2c::A2CParams;
 use r2l_core::{
     env::{Env, EnvBuilder},
-    models::ActivationFunction,
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
     tensor::R2lTensor,
 };
 use r2l_gym::GymEnvBuilder;
@@ -83,6 +83,15 @@ where
         self
     }
 
+    /// Sets the hidden layer sizes of a trunk shared by the policy and value
+    /// networks.
+    pub fn with_shared_hidden_layers(mut self, shared_hidden_layers: Vec<usize>) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_shared_hidden_layers(shared_hidden_layers);
+        self
+    }
+
     /// Sets the hidden layer sizes used by the policy network.
     pub fn with_policy_hidden_layers(mut self, policy_hidden_layers: Vec<usize>) -> Self {
         self.agent_builder = self
@@ -105,40 +114,46 @@ where
         self
     }
 
+    /// Sets how the weights of the policy and value networks are initialized.
+    pub fn with_weight_init(mut self, weight_init: WeightInit) -> Self {
+        self.agent_builder = self.agent_builder.with_weight_init(weight_init);
+        self
+    }
+
     /// Sets the optimizer learning rate for all configured optimizers.
     pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
         self.agent_builder = self.agent_builder.with_learning_rate(learning_rate);
-        self.learning_rate_schedule = Some(crate::LearningRateSchedule::Constant(learning_rate));
+        self.learning_rate_schedule = Some(crate::Schedule::constant(learning_rate));
         self
     }
 
-    /// Sets the AdamW `beta1` parameter for all configured optimizers.
+    /// Sets the Adam `beta1` parameter for all configured Adam optimizers.
     pub fn with_beta1(mut self, beta1: f64) -> Self {
         self.agent_builder = self.agent_builder.with_beta1(beta1);
         self
     }
 
-    /// Sets the AdamW `beta2` parameter for all configured optimizers.
+    /// Sets the Adam `beta2` parameter for all configured Adam optimizers.
     pub fn with_beta2(mut self, beta2: f64) -> Self {
         self.agent_builder = self.agent_builder.with_beta2(beta2);
         self
     }
 
-    /// Sets the AdamW epsilon parameter for all configured optimizers.
+    /// Sets the epsilon parameter for all configured optimizers.
     pub fn with_epsilon(mut self, epsilon: f64) -> Self {
         self.agent_builder = self.agent_builder.with_epsilon(epsilon);
         self
     }
 
-    /// Sets the AdamW weight decay parameter for all configured optimizers.
+    /// Sets the weight decay parameter for all configured optimizers.
     pub fn with_weight_decay(mut self, weight_decay: f64) -> Self {
         self.agent_builder = self.agent_builder.with_weight_decay(weight_decay);
         self
     }
 
     /// Uses a joint policy-value learning module configuration.
-    pub fn with_joint(mut self, max_grad_norm: Option<f32>, params: ParamsAdamW) -> Self {
-        self.agent_builder = self.agent_builder.with_joint(max_grad_norm, params);
+    pub fn with_joint(mut self, max_grad_norm: Option<f32>, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_joint(max_grad_norm, optimizer);
         self
     }
 
@@ -146,19 +161,37 @@ where
     pub fn with_split(
         mut self,
         policy_max_grad_norm: Option<f32>,
-        policy_params: ParamsAdamW,
+        policy_optimizer: OptimizerConfig,
         value_max_grad_norm: Option<f32>,
-        value_params: ParamsAdamW,
+        value_optimizer: OptimizerConfig,
     ) -> Self {
         self.agent_builder = self.agent_builder.with_split(
             policy_max_grad_norm,
-            policy_params,
+            policy_optimizer,
             value_max_grad_norm,
-            value_params,
+            value_optimizer,
         );
         self
     }
 
+    /// Uses the same optimizer for every configured optimizer slot.
+    pub fn with_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_optimizer(optimizer);
+        self
+    }
+
+    /// Sets the policy optimizer, switching to separate policy and value optimizers.
+    pub fn with_policy_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_policy_optimizer(optimizer);
+        self
+    }
+
+    /// Sets the value optimizer, switching to separate policy and value optimizers.
+    pub fn with_value_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_value_optimizer(optimizer);
+        self
+    }
+
     /// Sets the hidden layer sizes used by the value network.
     pub fn with_value_hidden_layers(mut self, value_hidden_layers: Vec<usize>) -> Self {
         self.agent_builder = self
@@ -222,6 +255,10 @@ impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
             sampler_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             agent_builder,
             seed,
@@ -230,6 +267,10 @@ impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
             sampler_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             agent_builder: agent_builder.with_candle(device),
             seed,
@@ -242,6 +283,10 @@ impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
             sampler_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             agent_builder,
             seed,
@@ -250,6 +295,10 @@ impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
             sampler_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             agent_builder: agent_builder.with_burn(),
             seed,
@@ -275,6 +324,10 @@ impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
             sampler_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             agent_builder,
             seed,
@@ -283,6 +336,10 @@ impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
             sampler_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             agent_builder: agent_builder.with_candle(device),
             seed,
@@ -295,6 +352,10 @@ impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
             sampler_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             agent_builder,
             seed,
@@ -303,6 +364,10 @@ impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
             sampler_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             agent_builder: agent_builder.with_burn(),
             seed,
@@ -1,6 +1,14 @@
 use std::{marker::PhantomData, sync::mpsc::Sender};
 
-use crate::hooks::a2c::{A2CStats, DefaultA2CHook, DefaultA2CHookReporter};
+use r2l_core::episode::EpisodeMonitor;
+
+use crate::{
+    hooks::{
+        a2c::{A2CStats, DefaultA2CHook, DefaultA2CHookReporter},
+        early_stopping::LossMonitor,
+    },
+    metrics::MetricsLogger,
+};
 
 /// Builder for the default A2C training hook.
 ///
@@ -9,13 +17,16 @@ use crate::hooks::a2c::{A2CStats, DefaultA2CHook, DefaultA2CHookReporter};
 /// gradient clipping, and optional reporting.
 #[derive(Debug, Clone)]
 pub struct DefaultA2CHookBuilder {
-    normalize_advantage: bool,
-    log_progress: bool,
-    entropy_coeff: f32,
-    vf_coeff: Option<f32>,
-    gradient_clipping: Option<f32>,
+    pub(crate) normalize_advantage: bool,
+    pub(crate) log_progress: bool,
+    pub(crate) entropy_coeff: f32,
+    pub(crate) vf_coeff: Option<f32>,
+    pub(crate) gradient_clipping: Option<f32>,
     n_envs: usize,
     tx: Option<Sender<A2CStats>>,
+    episode_monitor: Option<EpisodeMonitor>,
+    logger: Option<MetricsLogger>,
+    loss_monitor: Option<LossMonitor>,
 }
 
 impl DefaultA2CHookBuilder {
@@ -29,6 +40,9 @@ impl DefaultA2CHookBuilder {
             vf_coeff: None,
             gradient_clipping: None,
             tx: None,
+            episode_monitor: None,
+            logger: None,
+            loss_monitor: None,
         }
     }
 
@@ -68,6 +82,27 @@ impl DefaultA2CHookBuilder {
         self
     }
 
+    /// Reads rollout episode statistics from the sampler's [`EpisodeMonitor`].
+    ///
+    /// Without a monitor, episodes are reconstructed from the rollout batches.
+    pub fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.episode_monitor = episode_monitor;
+        self
+    }
+
+    /// Installs a [`MetricsLogger`] receiving the stats of every rollout.
+    pub fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Raises the [`LossMonitor`] flag on a NaN or infinite loss, skipping
+    /// the update that would apply it.
+    pub fn with_loss_monitor(mut self, loss_monitor: Option<LossMonitor>) -> Self {
+        self.loss_monitor = loss_monitor;
+        self
+    }
+
     /// Builds the default A2C hook.
     pub fn build<T>(self) -> DefaultA2CHook<T> {
         DefaultA2CHook {
@@ -75,7 +110,14 @@ impl DefaultA2CHookBuilder {
             entropy_coeff: self.entropy_coeff,
             vf_coeff: self.vf_coeff,
             gradient_clipping: self.gradient_clipping,
-            reporter: DefaultA2CHookReporter::new(self.tx, self.log_progress, self.n_envs),
+            reporter: DefaultA2CHookReporter::new(
+                self.tx,
+                self.log_progress,
+                self.n_envs,
+                self.episode_monitor,
+                self.logger,
+            ),
+            loss_monitor: self.loss_monitor,
             _lm: PhantomData,
         }
     }
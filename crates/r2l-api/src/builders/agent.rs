@@ -1,10 +1,18 @@
 use candle_core::Device;
-use candle_nn::ParamsAdamW;
 use r2l_core::{
-    env::Space, models::ActivationFunction, on_policy::algorithm::Agent, tensor::R2lTensor,
+    env::Space,
+    episode::EpisodeMonitor,
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+    on_policy::algorithm::Agent,
+    tensor::R2lTensor,
 };
 
-use crate::builders::learning_module::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType};
+use crate::{
+    builders::learning_module::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType},
+    hooks::early_stopping::LossMonitor,
+    metrics::MetricsLogger,
+};
 
 /// Trait implemented by concrete `Agent` builders.
 ///
@@ -22,6 +30,37 @@ pub trait AgentBuilder {
         action_space: Space<T>,
         seed: Option<u64>,
     ) -> anyhow::Result<Self::Agent>;
+
+    /// Connects the agent's reporting to the episodes completed by the training sampler.
+    ///
+    /// Builders without episode reporting ignore the monitor.
+    fn with_episode_monitor(self, _episode_monitor: Option<EpisodeMonitor>) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+
+    /// Connects the agent's reporting to a [`MetricsLogger`].
+    ///
+    /// Builders without reporting ignore the logger.
+    fn with_metrics_logger(self, _logger: Option<MetricsLogger>) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+
+    /// Makes the agent's hooks raise the [`LossMonitor`] flag on a NaN or
+    /// infinite loss.
+    ///
+    /// Builders without loss checks ignore the monitor.
+    fn with_loss_monitor(self, _monitor: Option<LossMonitor>) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
 }
 
 /// Shared builder for on-policy `Agent` implementations.
@@ -94,6 +133,17 @@ impl<Params, HookBuilder, Backend> OnPolicyAgentBuilder<Params, HookBuilder, Bac
         }
     }
 
+    /// Sets the hidden layer sizes of a trunk shared by the policy and value
+    /// networks.
+    ///
+    /// The policy and value hidden layers are stacked on top of the trunk, and
+    /// both losses train it. This needs the joint optimizer layout and fails at
+    /// build time for separate policy and value optimizers.
+    pub fn with_shared_hidden_layers(mut self, shared_hidden_layers: Vec<usize>) -> Self {
+        self.learning_module_builder.shared_hidden_layers = shared_hidden_layers;
+        self
+    }
+
     /// Sets the hidden layer sizes used by the policy network.
     pub fn with_policy_hidden_layers(mut self, policy_hidden_layers: Vec<usize>) -> Self {
         self.learning_module_builder.policy_hidden_layers = policy_hidden_layers;
@@ -112,6 +162,12 @@ impl<Params, HookBuilder, Backend> OnPolicyAgentBuilder<Params, HookBuilder, Bac
         self
     }
 
+    /// Sets how the weights of the policy and value networks are initialized.
+    pub fn with_weight_init(mut self, weight_init: WeightInit) -> Self {
+        self.learning_module_builder.weight_init = weight_init;
+        self
+    }
+
     /// Sets the optimizer learning rate for all configured optimizers.
     pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
         self.learning_module_builder.learning_module_type = self
@@ -121,7 +177,7 @@ impl<Params, HookBuilder, Backend> OnPolicyAgentBuilder<Params, HookBuilder, Bac
         self
     }
 
-    /// Sets the AdamW `beta1` parameter for all configured optimizers.
+    /// Sets the Adam `beta1` parameter for all configured Adam optimizers.
     pub fn with_beta1(mut self, beta1: f64) -> Self {
         self.learning_module_builder.learning_module_type = self
             .learning_module_builder
@@ -130,7 +186,7 @@ impl<Params, HookBuilder, Backend> OnPolicyAgentBuilder<Params, HookBuilder, Bac
         self
     }
 
-    /// Sets the AdamW `beta2` parameter for all configured optimizers.
+    /// Sets the Adam `beta2` parameter for all configured Adam optimizers.
     pub fn with_beta2(mut self, beta2: f64) -> Self {
         self.learning_module_builder.learning_module_type = self
             .learning_module_builder
@@ -139,7 +195,7 @@ impl<Params, HookBuilder, Backend> OnPolicyAgentBuilder<Params, HookBuilder, Bac
         self
     }
 
-    /// Sets the AdamW epsilon parameter for all configured optimizers.
+    /// Sets the epsilon parameter for all configured optimizers.
     pub fn with_epsilon(mut self, epsilon: f64) -> Self {
         self.learning_module_builder.learning_module_type = self
             .learning_module_builder
@@ -148,7 +204,7 @@ impl<Params, HookBuilder, Backend> OnPolicyAgentBuilder<Params, HookBuilder, Bac
         self
     }
 
-    /// Sets the AdamW weight decay parameter for all configured optimizers.
+    /// Sets the weight decay parameter for all configured optimizers.
     pub fn with_weight_decay(mut self, weight_decay: f64) -> Self {
         self.learning_module_builder.learning_module_type = self
             .learning_module_builder
@@ -158,10 +214,10 @@ impl<Params, HookBuilder, Backend> OnPolicyAgentBuilder<Params, HookBuilder, Bac
     }
 
     /// Uses a joint policy-value learning module configuration.
-    pub fn with_joint(mut self, max_grad_norm: Option<f32>, params: ParamsAdamW) -> Self {
+    pub fn with_joint(mut self, max_grad_norm: Option<f32>, optimizer: OptimizerConfig) -> Self {
         self.learning_module_builder.learning_module_type = OnPolicyLearningModuleType::Joint {
             max_grad_norm,
-            params,
+            optimizer,
         };
         self
     }
@@ -170,19 +226,46 @@ impl<Params, HookBuilder, Backend> OnPolicyAgentBuilder<Params, HookBuilder, Bac
     pub fn with_split(
         mut self,
         policy_max_grad_norm: Option<f32>,
-        policy_params: ParamsAdamW,
+        policy_optimizer: OptimizerConfig,
         value_max_grad_norm: Option<f32>,
-        value_params: ParamsAdamW,
+        value_optimizer: OptimizerConfig,
     ) -> Self {
         self.learning_module_builder.learning_module_type = OnPolicyLearningModuleType::Split {
             policy_max_grad_norm,
-            policy_params,
+            policy_optimizer,
             value_max_grad_norm,
-            value_params,
+            value_optimizer,
         };
         self
     }
 
+    /// Uses the same optimizer for every configured optimizer slot.
+    pub fn with_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.learning_module_builder.learning_module_type = self
+            .learning_module_builder
+            .learning_module_type
+            .with_optimizer(optimizer);
+        self
+    }
+
+    /// Sets the policy optimizer, switching to separate policy and value optimizers.
+    pub fn with_policy_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.learning_module_builder.learning_module_type = self
+            .learning_module_builder
+            .learning_module_type
+            .with_policy_optimizer(optimizer);
+        self
+    }
+
+    /// Sets the value optimizer, switching to separate policy and value optimizers.
+    pub fn with_value_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.learning_module_builder.learning_module_type = self
+            .learning_module_builder
+            .learning_module_type
+            .with_value_optimizer(optimizer);
+        self
+    }
+
     /// Sets the hidden layer sizes used by the value network.
     pub fn with_value_hidden_layers(mut self, value_hidden_layers: Vec<usize>) -> Self {
         self.learning_module_builder.value_hidden_layers = value_hidden_layers;
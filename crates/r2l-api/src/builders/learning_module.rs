@@ -1,147 +1,248 @@
-use burn::{
-    grad_clipping::GradientClippingConfig, optim::AdamWConfig, tensor::backend::AutodiffBackend,
-};
+use anyhow::bail;
+use burn::tensor::backend::AutodiffBackend;
 use candle_core::{DType, Device};
-use candle_nn::{ParamsAdamW, VarBuilder, VarMap};
+use candle_nn::{VarBuilder, VarMap};
 use r2l_burn::{
     distributions::PolicyKind, learning_module::PolicyValueModuleKind as BurnPolicyValueModule,
 };
 use r2l_candle::{
     distributions::CandlePolicyKind, learning_module::PolicyValueModule as CandlePolicyValueModule,
 };
-use r2l_core::{env::Space, models::ActivationFunction, tensor::R2lTensor};
+use r2l_core::{
+    env::Space,
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+    tensor::R2lTensor,
+};
 
 /// Optimizer layout for on-policy policy/value learning modules.
 ///
 /// This controls whether policy and value learning share a single optimizer
-/// configuration or use separate optimizer configurations.
+/// or use separate optimizers, each with its own [`OptimizerConfig`].
 pub enum OnPolicyLearningModuleType {
-    /// Use one joint optimizer configuration for both policy and value updates.
+    /// Use one joint optimizer for both policy and value updates.
     Joint {
         max_grad_norm: Option<f32>,
-        params: ParamsAdamW,
+        optimizer: OptimizerConfig,
     },
-    /// Use separate optimizer configurations for policy and value updates.
+    /// Use separate optimizers for policy and value updates.
     Split {
         policy_max_grad_norm: Option<f32>,
-        policy_params: ParamsAdamW,
+        policy_optimizer: OptimizerConfig,
         value_max_grad_norm: Option<f32>,
-        value_params: ParamsAdamW,
+        value_optimizer: OptimizerConfig,
     },
 }
 
 impl OnPolicyLearningModuleType {
-    /// Returns a copy with the learning rate updated everywhere it applies.
-    fn map_params<F>(self, mut f: F) -> Self
+    /// Returns a copy with `f` applied to every contained optimizer config.
+    fn map_optimizers<F>(self, f: F) -> Self
     where
-        F: FnMut(&mut ParamsAdamW),
+        F: Fn(OptimizerConfig) -> OptimizerConfig,
     {
         match self {
             Self::Joint {
                 max_grad_norm,
-                mut params,
-            } => {
-                f(&mut params);
-                Self::Joint {
-                    params,
-                    max_grad_norm,
-                }
-            }
+                optimizer,
+            } => Self::Joint {
+                max_grad_norm,
+                optimizer: f(optimizer),
+            },
             Self::Split {
                 policy_max_grad_norm,
-                mut policy_params,
+                policy_optimizer,
                 value_max_grad_norm,
-                mut value_params,
-            } => {
-                f(&mut policy_params);
-                f(&mut value_params);
-                Self::Split {
-                    policy_max_grad_norm,
-                    policy_params,
-                    value_max_grad_norm,
-                    value_params,
-                }
-            }
+                value_optimizer,
+            } => Self::Split {
+                policy_max_grad_norm,
+                policy_optimizer: f(policy_optimizer),
+                value_max_grad_norm,
+                value_optimizer: f(value_optimizer),
+            },
+        }
+    }
+
+    /// Returns the policy and value grad norms and optimizers, duplicating a joint layout.
+    fn split_parts(self) -> (Option<f32>, OptimizerConfig, Option<f32>, OptimizerConfig) {
+        match self {
+            Self::Joint {
+                max_grad_norm,
+                optimizer,
+            } => (max_grad_norm, optimizer, max_grad_norm, optimizer),
+            Self::Split {
+                policy_max_grad_norm,
+                policy_optimizer,
+                value_max_grad_norm,
+                value_optimizer,
+            } => (
+                policy_max_grad_norm,
+                policy_optimizer,
+                value_max_grad_norm,
+                value_optimizer,
+            ),
+        }
+    }
+
+    /// Returns the grad norm and optimizer that update the policy.
+    fn policy_optimizer(&self) -> (Option<f32>, OptimizerConfig) {
+        match self {
+            Self::Joint {
+                max_grad_norm,
+                optimizer,
+            } => (*max_grad_norm, *optimizer),
+            Self::Split {
+                policy_max_grad_norm,
+                policy_optimizer,
+                ..
+            } => (*policy_max_grad_norm, *policy_optimizer),
         }
     }
 
-    /// Sets the AdamW learning rate on all contained optimizer configs.
+    /// Replaces every contained optimizer config, keeping the layout.
+    pub fn with_optimizer(self, optimizer: OptimizerConfig) -> Self {
+        self.map_optimizers(|_| optimizer)
+    }
+
+    /// Sets the policy optimizer, switching a joint layout to a split one.
+    pub fn with_policy_optimizer(self, policy_optimizer: OptimizerConfig) -> Self {
+        let (policy_max_grad_norm, _, value_max_grad_norm, value_optimizer) = self.split_parts();
+        Self::Split {
+            policy_max_grad_norm,
+            policy_optimizer,
+            value_max_grad_norm,
+            value_optimizer,
+        }
+    }
+
+    /// Sets the value optimizer, switching a joint layout to a split one.
+    pub fn with_value_optimizer(self, value_optimizer: OptimizerConfig) -> Self {
+        let (policy_max_grad_norm, policy_optimizer, value_max_grad_norm, _) = self.split_parts();
+        Self::Split {
+            policy_max_grad_norm,
+            policy_optimizer,
+            value_max_grad_norm,
+            value_optimizer,
+        }
+    }
+
+    /// Sets the learning rate on all contained optimizer configs.
     pub fn with_lr(self, lr: f64) -> Self {
-        self.map_params(|params| params.lr = lr)
+        self.map_optimizers(|optimizer| optimizer.with_learning_rate(lr))
     }
 
-    /// Sets the AdamW `beta1` parameter on all contained optimizer configs.
+    /// Sets the Adam `beta1` parameter on all contained optimizer configs.
+    /// Optimizers without `beta1` are left unchanged.
     pub fn with_beta1(self, beta1: f64) -> Self {
-        self.map_params(|params| params.beta1 = beta1)
+        self.map_optimizers(|optimizer| optimizer.with_beta1(beta1).unwrap_or(optimizer))
     }
 
-    /// Sets the AdamW `beta2` parameter on all contained optimizer configs.
+    /// Sets the Adam `beta2` parameter on all contained optimizer configs.
+    /// Optimizers without `beta2` are left unchanged.
     pub fn with_beta2(self, beta2: f64) -> Self {
-        self.map_params(|params| params.beta2 = beta2)
+        self.map_optimizers(|optimizer| optimizer.with_beta2(beta2).unwrap_or(optimizer))
     }
 
-    /// Sets the AdamW epsilon parameter on all contained optimizer configs.
+    /// Sets the epsilon parameter on all contained optimizer configs. SGD
+    /// has none and is left unchanged.
     pub fn with_epsilon(self, epsilon: f64) -> Self {
-        self.map_params(|params| params.eps = epsilon)
+        self.map_optimizers(|optimizer| optimizer.with_epsilon(epsilon).unwrap_or(optimizer))
     }
 
-    /// Sets the AdamW weight decay on all contained optimizer configs.
+    /// Sets the weight decay on all contained optimizer configs.
     pub fn with_weight_decay(self, weight_decay: f64) -> Self {
-        self.map_params(|params| params.weight_decay = weight_decay)
+        self.map_optimizers(|optimizer| optimizer.with_weight_decay(weight_decay))
     }
 }
 
 pub(crate) struct OnPolicyLearningModuleBuilder {
+    pub(crate) shared_hidden_layers: Vec<usize>,
     pub(crate) policy_hidden_layers: Vec<usize>,
     pub(crate) value_hidden_layers: Vec<usize>,
     pub(crate) activation_function: ActivationFunction,
     pub(crate) log_std_init: f32,
+    pub(crate) weight_init: WeightInit,
     pub(crate) learning_module_type: OnPolicyLearningModuleType,
 }
 
 impl OnPolicyLearningModuleBuilder {
+    /// Returns the policy hidden layers, shared trunk first.
+    fn policy_layers<T: R2lTensor>(&self, action_space: &Space<T>) -> anyhow::Result<Vec<usize>> {
+        if !self.shared_hidden_layers.is_empty() {
+            if matches!(
+                self.learning_module_type,
+                OnPolicyLearningModuleType::Split { .. }
+            ) {
+                bail!("shared hidden layers need a joint optimizer so both losses train them");
+            }
+            if matches!(action_space, Space::Tuple(_) | Space::Dict(_)) {
+                bail!("shared hidden layers are not supported for tuple and dict action spaces");
+            }
+        }
+        Ok([
+            &self.shared_hidden_layers[..],
+            &self.policy_hidden_layers[..],
+        ]
+        .concat())
+    }
+
+    /// Returns the policy hidden layers the auxiliary value head is stacked on.
+    fn auxiliary_head_layers<T: R2lTensor>(
+        &self,
+        action_space: &Space<T>,
+    ) -> anyhow::Result<Vec<usize>> {
+        if matches!(action_space, Space::Tuple(_) | Space::Dict(_)) {
+            bail!("auxiliary value heads are not supported for tuple and dict action spaces");
+        }
+        self.policy_layers(action_space)
+    }
+
     pub fn build_candle<T: R2lTensor>(
         self,
         observation_size: usize,
         action_space: Space<T>,
         device: &Device,
     ) -> anyhow::Result<CandlePolicyValueModule> {
+        let policy_hidden_layers = self.policy_layers(&action_space)?;
         let policy_varmap = VarMap::new();
         let policy_vb = VarBuilder::from_varmap(&policy_varmap, DType::F32, device);
         let policy = CandlePolicyKind::build(
             action_space,
             &policy_vb,
-            &self.policy_hidden_layers,
+            &policy_hidden_layers,
             observation_size,
             self.activation_function,
             self.log_std_init,
+            self.weight_init,
         )?;
         match self.learning_module_type {
             OnPolicyLearningModuleType::Joint {
                 max_grad_norm,
-                params,
+                optimizer,
             } => CandlePolicyValueModule::build_joint(
                 policy,
+                &self.shared_hidden_layers,
                 &self.value_hidden_layers,
                 policy_varmap,
                 max_grad_norm,
-                params,
+                &optimizer,
                 self.activation_function,
+                self.weight_init,
             ),
             OnPolicyLearningModuleType::Split {
                 policy_max_grad_norm,
-                policy_params,
+                policy_optimizer,
                 value_max_grad_norm,
-                value_params,
+                value_optimizer,
             } => CandlePolicyValueModule::build_split(
                 policy,
                 &self.value_hidden_layers,
                 policy_varmap,
                 policy_max_grad_norm,
                 value_max_grad_norm,
-                policy_params,
-                value_params,
+                &policy_optimizer,
+                &value_optimizer,
                 self.activation_function,
+                self.weight_init,
             ),
         }
     }
@@ -152,9 +253,10 @@ impl OnPolicyLearningModuleBuilder {
         action_space: Space<T>,
     ) -> anyhow::Result<BurnPolicyValueModule<B>> {
         let action_size = action_space.size();
+        let policy_hidden_layers = self.policy_layers(&action_space)?;
         let policy_layers = &[
             &[observation_size][..],
-            &self.policy_hidden_layers[..],
+            &policy_hidden_layers[..],
             &[action_size],
         ]
         .concat();
@@ -163,70 +265,623 @@ impl OnPolicyLearningModuleBuilder {
             policy_layers,
             self.activation_function,
             self.log_std_init,
+            self.weight_init,
         );
+        let value_input_size = self
+            .shared_hidden_layers
+            .last()
+            .copied()
+            .unwrap_or(observation_size);
+        let value_layers = &[&[value_input_size][..], &self.value_hidden_layers[..], &[1]].concat();
         let learning_module = match self.learning_module_type {
             OnPolicyLearningModuleType::Joint {
                 max_grad_norm,
-                params,
-            } => {
-                let value_layers =
-                    &[&[observation_size][..], &self.value_hidden_layers[..], &[1]].concat();
-                let mut optimizer_config = AdamWConfig::new()
-                    .with_beta_1(params.beta1 as f32)
-                    .with_beta_2(params.beta2 as f32)
-                    .with_epsilon(params.eps as f32)
-                    .with_weight_decay(params.weight_decay as f32);
-                if let Some(max_grad_norm) = max_grad_norm {
-                    optimizer_config = optimizer_config
-                        .with_grad_clipping(Some(GradientClippingConfig::Norm(max_grad_norm)));
-                }
-                BurnPolicyValueModule::joint(
-                    policy,
-                    value_layers,
-                    self.activation_function,
-                    optimizer_config,
-                    params.lr,
-                )
-            }
+                optimizer,
+            } => BurnPolicyValueModule::joint(
+                policy,
+                self.shared_hidden_layers.len(),
+                value_layers,
+                self.activation_function,
+                &optimizer,
+                max_grad_norm,
+                self.weight_init,
+            ),
             OnPolicyLearningModuleType::Split {
                 policy_max_grad_norm,
-                policy_params,
+                policy_optimizer,
                 value_max_grad_norm,
-                value_params,
-            } => {
-                let value_layers =
-                    &[&[observation_size][..], &self.value_hidden_layers[..], &[1]].concat();
-                let mut policy_optimizer = AdamWConfig::new()
-                    .with_beta_1(policy_params.beta1 as f32)
-                    .with_beta_2(policy_params.beta2 as f32)
-                    .with_epsilon(policy_params.eps as f32)
-                    .with_weight_decay(policy_params.weight_decay as f32);
-                if let Some(policy_max_grad_norm) = policy_max_grad_norm {
-                    policy_optimizer = policy_optimizer.with_grad_clipping(Some(
-                        GradientClippingConfig::Norm(policy_max_grad_norm),
-                    ));
-                }
-                let mut value_optimizer = AdamWConfig::new()
-                    .with_beta_1(value_params.beta1 as f32)
-                    .with_beta_2(value_params.beta2 as f32)
-                    .with_epsilon(value_params.eps as f32)
-                    .with_weight_decay(value_params.weight_decay as f32);
-                if let Some(value_max_grad_norm) = value_max_grad_norm {
-                    value_optimizer = value_optimizer.with_grad_clipping(Some(
-                        GradientClippingConfig::Norm(value_max_grad_norm),
-                    ));
+                value_optimizer,
+            } => BurnPolicyValueModule::split(
+                policy,
+                value_layers,
+                self.activation_function,
+                &policy_optimizer,
+                policy_max_grad_norm,
+                &value_optimizer,
+                value_max_grad_norm,
+                self.weight_init,
+            ),
+        };
+        Ok(learning_module)
+    }
+
+    /// Builds a Candle module whose policy network has an auxiliary value head
+    /// with `head_hidden_layers`, using the activation and initialization of
+    /// the other networks.
+    pub fn build_candle_with_auxiliary_value_head<T: R2lTensor>(
+        self,
+        observation_size: usize,
+        action_space: Space<T>,
+        head_hidden_layers: &[usize],
+        device: &Device,
+    ) -> anyhow::Result<CandlePolicyValueModule> {
+        let policy_hidden_layers = self.auxiliary_head_layers(&action_space)?;
+        let (max_grad_norm, optimizer) = self.learning_module_type.policy_optimizer();
+        let activation = self.activation_function;
+        let weight_init = self.weight_init;
+        self.build_candle(observation_size, action_space, device)?
+            .with_auxiliary_value_head(
+                &policy_hidden_layers,
+                head_hidden_layers,
+                activation,
+                &optimizer,
+                max_grad_norm,
+                weight_init,
+            )
+    }
+
+    /// Builds a Burn module whose policy network has an auxiliary value head
+    /// with `head_hidden_layers`, using the activation and initialization of
+    /// the other networks.
+    pub fn build_burn_with_auxiliary_value_head<B: AutodiffBackend, T: R2lTensor>(
+        self,
+        observation_size: usize,
+        action_space: Space<T>,
+        head_hidden_layers: &[usize],
+    ) -> anyhow::Result<BurnPolicyValueModule<B>> {
+        let policy_hidden_layers = self.auxiliary_head_layers(&action_space)?;
+        let (max_grad_norm, optimizer) = self.learning_module_type.policy_optimizer();
+        let activation = self.activation_function;
+        let weight_init = self.weight_init;
+        Ok(self
+            .build_burn(observation_size, action_space)?
+            .with_auxiliary_value_head(
+                observation_size,
+                &policy_hidden_layers,
+                head_hidden_layers,
+                activation,
+                &optimizer,
+                max_grad_norm,
+                weight_init,
+            ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use candle_core::Device;
+    use r2l_core::{
+        env::Space,
+        init::{InitScheme, WeightInit},
+        models::{ActivationFunction, GradNorms, OptimizerConfig, Policy},
+        on_policy::{
+            learning_module::{
+                OnPolicyLearningModule, StatefulLearningModule, TrustRegionLearningModule,
+            },
+            losses::FromPolicyValueLosses,
+        },
+        rng::set_seed,
+        tensor::{R2lTensor, TensorData},
+    };
+
+    use super::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType};
+    use crate::BurnBackend;
+
+    const OBSERVATION_SIZE: usize = 4;
+
+    fn builder(learning_module_type: OnPolicyLearningModuleType) -> OnPolicyLearningModuleBuilder {
+        OnPolicyLearningModuleBuilder {
+            shared_hidden_layers: vec![],
+            policy_hidden_layers: vec![8],
+            value_hidden_layers: vec![8],
+            activation_function: ActivationFunction::default(),
+            log_std_init: 0.0,
+            weight_init: WeightInit::default(),
+            learning_module_type,
+        }
+    }
+
+    fn joint() -> OnPolicyLearningModuleType {
+        OnPolicyLearningModuleType::Joint {
+            max_grad_norm: None,
+            optimizer: OptimizerConfig::adam(1e-2),
+        }
+    }
+
+    fn split() -> OnPolicyLearningModuleType {
+        joint().with_value_optimizer(OptimizerConfig::adam(1e-2))
+    }
+
+    fn action_space() -> Space<TensorData> {
+        Space::Discrete(2)
+    }
+
+    // Six observations with one-hot actions.
+    fn batch<LM: OnPolicyLearningModule>(
+        lm: &LM,
+    ) -> (Vec<LM::LearningTensor>, Vec<LM::LearningTensor>) {
+        let observations = (0..6)
+            .map(|i| {
+                let i = i as f32;
+                lm.tensor_from_slice(&[i / 6., 1. - i / 3., (i * 0.7).sin(), 0.5])
+            })
+            .collect();
+        let actions = (0..6)
+            .map(|i| lm.tensor_from_slice(if i % 3 == 0 { &[1., 0.] } else { &[0., 1.] }))
+            .collect();
+        (observations, actions)
+    }
+
+    fn policy_loss<LM: OnPolicyLearningModule>(
+        lm: &LM,
+        observations: &[LM::LearningTensor],
+        actions: &[LM::LearningTensor],
+    ) -> Result<LM::LearningTensor> {
+        lm.policy().log_probs(observations, actions)?.mean()?.neg()
+    }
+
+    fn value_loss<LM: OnPolicyLearningModule>(
+        lm: &LM,
+        observations: &[LM::LearningTensor],
+    ) -> Result<LM::LearningTensor> {
+        let targets = lm.tensor_from_slice(&vec![1.; observations.len()]);
+        lm.values(observations)?.sub(&targets)?.sqr()?.mean()
+    }
+
+    fn update<LM: OnPolicyLearningModule>(lm: &mut LM) -> Result<()> {
+        let (observations, actions) = batch(lm);
+        let losses = LM::Losses::from_policy_value_losses(
+            policy_loss(lm, &observations, &actions)?,
+            value_loss(lm, &observations)?,
+        );
+        lm.update(losses)
+    }
+
+    // Policy parameters followed by the values of the batch.
+    fn snapshot<LM: TrustRegionLearningModule>(lm: &LM) -> Result<Vec<f32>> {
+        let (observations, _) = batch(lm);
+        let mut snapshot = lm.policy_parameters()?;
+        snapshot.extend(lm.values(&observations)?.to_vec());
+        Ok(snapshot)
+    }
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-5, "{a} != {b}");
+        }
+    }
+
+    // Both modules take the same steps once `target` loaded the state of
+    // `source`. Adam's second step depends on its moments, so this only holds
+    // when the optimizer state was copied along with the parameters.
+    fn check_state_transfer<LM>(mut source: LM, mut target: LM) -> Result<()>
+    where
+        LM: StatefulLearningModule + TrustRegionLearningModule,
+    {
+        update(&mut source)?;
+        update(&mut source)?;
+        assert_ne!(snapshot(&source)?, snapshot(&target)?);
+        target.load_state(&source.state()?)?;
+        assert_close(&snapshot(&source)?, &snapshot(&target)?);
+        for _ in 0..3 {
+            update(&mut source)?;
+            update(&mut target)?;
+        }
+        assert_close(&snapshot(&source)?, &snapshot(&target)?);
+        Ok(())
+    }
+
+    #[test]
+    fn candle_state_transfer_copies_parameters_and_optimizer_state() -> Result<()> {
+        for layout in [joint, split] {
+            let build =
+                || builder(layout()).build_candle(OBSERVATION_SIZE, action_space(), &Device::Cpu);
+            check_state_transfer(build()?, build()?)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn burn_state_transfer_copies_parameters_and_optimizer_state() -> Result<()> {
+        for layout in [joint, split] {
+            let build =
+                || builder(layout()).build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space());
+            check_state_transfer(build()?, build()?)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn state_of_another_architecture_is_rejected() -> Result<()> {
+        let mut wider = builder(joint());
+        wider.policy_hidden_layers = vec![16];
+        let source =
+            builder(joint()).build_candle(OBSERVATION_SIZE, action_space(), &Device::Cpu)?;
+        let mut target = wider.build_candle(OBSERVATION_SIZE, action_space(), &Device::Cpu)?;
+        assert!(target.load_state(&source.state()?).is_err());
+
+        let mut wider = builder(joint());
+        wider.policy_hidden_layers = vec![16];
+        let source =
+            builder(joint()).build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?;
+        let mut target = wider.build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?;
+        assert!(target.load_state(&source.state()?).is_err());
+        let mut split_target =
+            builder(split()).build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?;
+        assert!(split_target.load_state(&source.state()?).is_err());
+        Ok(())
+    }
+
+    // Setting the parameters is visible in the policy output, and reading them
+    // back returns what was set.
+    fn check_parameter_round_trip<LM: TrustRegionLearningModule>(mut lm: LM) -> Result<()> {
+        let (observations, actions) = batch(&lm);
+        let parameters = lm.policy_parameters()?;
+        let logp = lm.policy().log_probs(&observations, &actions)?.to_vec();
+        let moved = parameters
+            .iter()
+            .enumerate()
+            .map(|(i, p)| p + 0.01 * (i as f32).sin())
+            .collect::<Vec<_>>();
+        lm.set_policy_parameters(&moved)?;
+        assert_eq!(lm.policy_parameters()?, moved);
+        assert_ne!(
+            lm.policy().log_probs(&observations, &actions)?.to_vec(),
+            logp
+        );
+        lm.set_policy_parameters(&parameters)?;
+        assert_eq!(lm.policy_parameters()?, parameters);
+        assert_eq!(
+            lm.policy().log_probs(&observations, &actions)?.to_vec(),
+            logp
+        );
+        assert!(lm.set_policy_parameters(&parameters[1..]).is_err());
+        Ok(())
+    }
+
+    fn test_vector(len: usize) -> Vec<f32> {
+        (0..len).map(|i| ((i * 7 % 11) as f32 - 5.) / 5.).collect()
+    }
+
+    // Checks the finite-difference product of `lm` against `expected`, and that
+    // the Fisher matrix is positive semi-definite and maps zero to zero.
+    fn check_fisher_vector_product<LM: TrustRegionLearningModule>(
+        lm: &mut LM,
+        observations: &[LM::LearningTensor],
+        vector: &[f32],
+        expected: &[f32],
+    ) -> Result<()> {
+        let parameters = lm.policy_parameters()?;
+        let fvp = lm.fisher_vector_product(observations, vector)?;
+        assert_eq!(lm.policy_parameters()?, parameters);
+        let norm = expected.iter().map(|e| e * e).sum::<f32>().sqrt();
+        let error = fvp
+            .iter()
+            .zip(expected)
+            .map(|(f, e)| (f - e) * (f - e))
+            .sum::<f32>()
+            .sqrt();
+        assert!(norm > 0.);
+        assert!(
+            error < 1e-3 * norm,
+            "error {error} for a product of norm {norm}"
+        );
+        let curvature = fvp.iter().zip(vector).map(|(f, v)| f * v).sum::<f32>();
+        assert!(curvature >= 0.);
+        let zero = vec![0.; parameters.len()];
+        let product = lm.fisher_vector_product(observations, &zero)?;
+        assert!(product.iter().all(|p| *p == 0.));
+        Ok(())
+    }
+
+    // The Fisher matrix of a categorical policy is `mean_s sum_a p_a g_a g_a^T`,
+    // where `g_a` is the gradient of the log-probability of action `a` at
+    // state `s`.
+    fn check_categorical_fisher<LM: TrustRegionLearningModule>(mut lm: LM) -> Result<()> {
+        let (observations, _) = batch(&lm);
+        let vector = test_vector(lm.policy_parameters()?.len());
+        let n = observations.len();
+        let mut expected = vec![0.; vector.len()];
+        for action in [[1., 0.], [0., 1.]] {
+            let actions = vec![lm.tensor_from_slice(&action); n];
+            let probs = lm
+                .policy()
+                .log_probs(&observations, &actions)?
+                .exp()?
+                .to_vec();
+            for (i, prob) in probs.iter().enumerate() {
+                // masks the batch down to state `i`
+                let mask = (0..n)
+                    .map(|j| if i == j { n as f32 } else { 0. })
+                    .collect::<Vec<_>>();
+                let logp = lm
+                    .tensor_from_slice(&mask)
+                    .mul(&lm.policy().log_probs(&observations, &actions)?)?
+                    .mean()?;
+                let gradient = lm.policy_gradient(&logp)?;
+                let dot = gradient
+                    .iter()
+                    .zip(&vector)
+                    .map(|(g, v)| g * v)
+                    .sum::<f32>();
+                for (expected, g) in expected.iter_mut().zip(&gradient) {
+                    *expected += prob * dot * g / n as f32;
                 }
-                BurnPolicyValueModule::split(
-                    policy,
-                    value_layers,
-                    self.activation_function,
-                    policy_optimizer,
-                    policy_params.lr,
-                    value_optimizer,
-                    value_params.lr,
-                )
             }
+        }
+        check_fisher_vector_product(&mut lm, &observations, &vector, &expected)
+    }
+
+    // A diagonal Gaussian with means `mu` and log standard deviations `l` has
+    // the Fisher matrix `diag(1 / sigma^2)` in `mu` and `2 I` in `l`. When the
+    // means are linear in the parameters, the Fisher product is therefore the
+    // gradient of `mean_s sum_d dmu_sd mu_sd / sigma_d^2 + 2 sum_d dl_d l_d`,
+    // where the differences are taken between the parameters shifted by `v`
+    // and the current ones.
+    fn check_gaussian_fisher<LM: TrustRegionLearningModule>(mut lm: LM) -> Result<()> {
+        let (observations, _) = batch(&lm);
+        let parameters = lm.policy_parameters()?;
+        let vector = test_vector(parameters.len());
+        let before = lm.policy().distribution_parameters(&observations)?.to_vec();
+        let shifted = parameters
+            .iter()
+            .zip(&vector)
+            .map(|(p, v)| p + v)
+            .collect::<Vec<_>>();
+        lm.set_policy_parameters(&shifted)?;
+        let after = lm.policy().distribution_parameters(&observations)?.to_vec();
+        lm.set_policy_parameters(&parameters)?;
+        let log_std = lm.policy().log_std()?.unwrap();
+        let action_size = log_std.len();
+        let n = observations.len();
+        // the means of every state come before the log standard deviations
+        assert_eq!(before.len(), (n + 1) * action_size);
+        let weights = (0..before.len())
+            .map(|i| {
+                let difference = after[i] - before[i];
+                if i < n * action_size {
+                    difference / (2. * log_std[i % action_size]).exp() / n as f32
+                } else {
+                    2. * difference
+                }
+            })
+            .collect::<Vec<_>>();
+        let weighted = lm
+            .tensor_from_slice(&weights)
+            .mul(&lm.policy().distribution_parameters(&observations)?)?
+            .mean()?;
+        let expected = lm
+            .policy_gradient(&weighted)?
+            .iter()
+            .map(|g| g * weights.len() as f32)
+            .collect::<Vec<_>>();
+        check_fisher_vector_product(&mut lm, &observations, &vector, &expected)
+    }
+
+    // A Gaussian policy whose means are a linear function of the observations.
+    fn linear_gaussian() -> (OnPolicyLearningModuleBuilder, Space<TensorData>) {
+        let builder = OnPolicyLearningModuleBuilder {
+            policy_hidden_layers: vec![],
+            log_std_init: -0.5,
+            ..builder(split())
         };
-        Ok(learning_module)
+        let action_space = Space::Box {
+            min: None,
+            max: None,
+            shape: vec![2],
+        };
+        (builder, action_space)
+    }
+
+    #[test]
+    fn candle_policy_parameters_round_trip() -> Result<()> {
+        for layout in [joint, split] {
+            check_parameter_round_trip(builder(layout()).build_candle(
+                OBSERVATION_SIZE,
+                action_space(),
+                &Device::Cpu,
+            )?)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn burn_policy_parameters_round_trip() -> Result<()> {
+        for layout in [joint, split] {
+            check_parameter_round_trip(
+                builder(layout()).build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?,
+            )?;
+        }
+        Ok(())
+    }
+
+    // The backends lay their weight matrices out differently, so the policies
+    // are compared as sorted parameter lists.
+    fn sorted_policy_parameters<LM: TrustRegionLearningModule>(lm: &LM) -> Result<Vec<f32>> {
+        let mut parameters = lm.policy_parameters()?;
+        parameters.sort_by(f32::total_cmp);
+        Ok(parameters)
+    }
+
+    #[test]
+    fn burn_and_candle_policies_start_from_the_same_weights() -> Result<()> {
+        for scheme in [
+            InitScheme::Orthogonal,
+            InitScheme::XavierUniform,
+            InitScheme::XavierNormal,
+            InitScheme::KaimingUniform,
+            InitScheme::KaimingNormal,
+        ] {
+            let seeded_builder = || {
+                set_seed(5);
+                let mut builder = builder(split());
+                builder.policy_hidden_layers = vec![8, 8];
+                builder.weight_init = WeightInit::new(scheme).with_policy_head_gain(0.5);
+                builder
+            };
+            let candle = sorted_policy_parameters(&seeded_builder().build_candle(
+                OBSERVATION_SIZE,
+                action_space(),
+                &Device::Cpu,
+            )?)?;
+            let burn = sorted_policy_parameters(
+                &seeded_builder().build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?,
+            )?;
+            // only the biases are zero
+            assert_eq!(candle.iter().filter(|p| **p == 0.).count(), 8 + 8 + 2);
+            assert_close(&candle, &burn);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn candle_fisher_vector_product_matches_the_categorical_fisher() -> Result<()> {
+        check_categorical_fisher(builder(split()).build_candle(
+            OBSERVATION_SIZE,
+            action_space(),
+            &Device::Cpu,
+        )?)
+    }
+
+    #[test]
+    fn burn_fisher_vector_product_matches_the_categorical_fisher() -> Result<()> {
+        check_categorical_fisher(
+            builder(split()).build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?,
+        )
+    }
+
+    #[test]
+    fn candle_fisher_vector_product_matches_the_gaussian_fisher() -> Result<()> {
+        let (builder, action_space) = linear_gaussian();
+        check_gaussian_fisher(builder.build_candle(OBSERVATION_SIZE, action_space, &Device::Cpu)?)
+    }
+
+    #[test]
+    fn burn_fisher_vector_product_matches_the_gaussian_fisher() -> Result<()> {
+        let (builder, action_space) = linear_gaussian();
+        check_gaussian_fisher(builder.build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space)?)
+    }
+
+    // Both losses reach the shared trunk: the value loss has a gradient on
+    // exactly the trunk entries of the policy parameters, and the policy loss
+    // has one there too. Without a trunk the value loss leaves the policy
+    // alone.
+    fn check_shared_trunk_gradients<LM: TrustRegionLearningModule>(
+        mut shared: LM,
+        mut separate: LM,
+    ) -> Result<()> {
+        // the trunk is one hidden layer of 8 units with biases
+        let trunk_size = OBSERVATION_SIZE * 8 + 8;
+        let (observations, actions) = batch(&shared);
+        let value_gradient = shared.policy_gradient(&value_loss(&shared, &observations)?)?;
+        let trunk = (0..value_gradient.len())
+            .filter(|i| value_gradient[*i] != 0.)
+            .collect::<Vec<_>>();
+        assert_eq!(trunk.len(), trunk_size);
+        let policy_gradient =
+            shared.policy_gradient(&policy_loss(&shared, &observations, &actions)?)?;
+        assert!(trunk.iter().all(|i| policy_gradient[*i] != 0.));
+
+        let (observations, _) = batch(&separate);
+        let value_gradient = separate.policy_gradient(&value_loss(&separate, &observations)?)?;
+        assert!(value_gradient.iter().all(|g| *g == 0.));
+        Ok(())
+    }
+
+    // Updates are only measured while collecting, and the norms reported
+    // under a tight clip are clipped.
+    fn check_grad_norm_collection<LM: OnPolicyLearningModule>(
+        mut lm: LM,
+        collect: impl Fn(&mut LM, bool),
+        take: impl Fn(&mut LM) -> GradNorms,
+    ) -> Result<()> {
+        update(&mut lm)?;
+        assert_eq!(take(&mut lm).updates, 0);
+        collect(&mut lm, true);
+        update(&mut lm)?;
+        update(&mut lm)?;
+        let grad_norms = take(&mut lm);
+        assert_eq!(grad_norms.updates, 2);
+        let (pre_clip, post_clip) = (
+            grad_norms.pre_clip().unwrap(),
+            grad_norms.post_clip().unwrap(),
+        );
+        assert!(
+            post_clip > 0. && post_clip < pre_clip,
+            "{post_clip} >= {pre_clip}"
+        );
+        collect(&mut lm, false);
+        update(&mut lm)?;
+        assert_eq!(take(&mut lm).updates, 0);
+        Ok(())
+    }
+
+    fn clipped() -> OnPolicyLearningModuleType {
+        OnPolicyLearningModuleType::Joint {
+            max_grad_norm: Some(1e-3),
+            optimizer: OptimizerConfig::adam(1e-2),
+        }
+    }
+
+    fn clipped_split() -> OnPolicyLearningModuleType {
+        clipped().with_value_optimizer(OptimizerConfig::adam(1e-2))
+    }
+
+    #[test]
+    fn candle_grad_norms_are_only_measured_while_collected() -> Result<()> {
+        for layout in [clipped, clipped_split] {
+            check_grad_norm_collection(
+                builder(layout()).build_candle(OBSERVATION_SIZE, action_space(), &Device::Cpu)?,
+                |lm, collect| lm.collect_grad_norms(collect),
+                |lm| lm.take_grad_norms(),
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn burn_grad_norms_are_only_measured_while_collected() -> Result<()> {
+        for layout in [clipped, clipped_split] {
+            check_grad_norm_collection(
+                builder(layout()).build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?,
+                |lm, collect| lm.collect_grad_norms(collect),
+                |lm| lm.take_grad_norms(),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn shared_trunk() -> OnPolicyLearningModuleBuilder {
+        let mut builder = builder(joint());
+        builder.shared_hidden_layers = vec![8];
+        builder
+    }
+
+    #[test]
+    fn candle_shared_trunk_gets_policy_and_value_gradients() -> Result<()> {
+        check_shared_trunk_gradients(
+            shared_trunk().build_candle(OBSERVATION_SIZE, action_space(), &Device::Cpu)?,
+            builder(joint()).build_candle(OBSERVATION_SIZE, action_space(), &Device::Cpu)?,
+        )
+    }
+
+    #[test]
+    fn burn_shared_trunk_gets_policy_and_value_gradients() -> Result<()> {
+        check_shared_trunk_gradients(
+            shared_trunk().build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?,
+            builder(joint()).build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?,
+        )
     }
 }
@@ -2,5 +2,8 @@ pub(crate) mod a2c;
 pub(crate) mod agent;
 pub(crate) mod learning_module;
 pub(crate) mod on_policy;
+pub(crate) mod ppg;
 pub(crate) mod ppo;
 pub(crate) mod sampler;
+pub(crate) mod trpo;
+pub(crate) mod vpg;
@@ -1,7 +1,9 @@
+use std::time::Duration;
+
 use r2l_core::{
     env::{Env, EnvBuilder},
     on_policy::algorithm::{
-        Agent, DefaultAdapter, OnPolicyAdapters, OnPolicyAlgorithm, OnPolicyRuntime,
+        Agent, DefaultAdapter, OnPolicyAdapters, OnPolicyAlgorithm, OnPolicyRuntime, Sampler,
     },
     rng::set_seed,
     tensor::R2lTensor,
@@ -15,14 +17,18 @@ use crate::{
     builders::{
         agent::AgentBuilder,
         sampler::{
-            DirectSamplerSelection, NormalizedSamplerSelection, SamplerBuilder, SamplerHookBuilder,
-            StepHookBound,
+            DirectSamplerSelection, NormalizedSamplerSelection, RewardNormalizerParams,
+            SamplerBuilder, SamplerHookBuilder, StepHookBound,
         },
     },
     hooks::{
-        on_policy::{DefaultOnPolicyAlgorithmHooks, LearningSchedule},
+        early_stopping::{
+            LossMonitor, NoImprovement, NonFiniteLoss, RewardThreshold, StopCondition, TimeBudget,
+        },
+        on_policy::{DefaultOnPolicyAlgorithmHooks, EvaluationCallback, LearningSchedule},
         sampler::EpisodeBoundHook,
     },
+    metrics::MetricsLogger,
 };
 
 type DefaultOnPolicyAlgorithm<A, EB, SH> = OnPolicyAlgorithm<
@@ -52,6 +58,9 @@ type NormalizedOnPolicyAlgorithm<A, EB, SH> = OnPolicyAlgorithm<
     >,
 >;
 
+/// Rollouts trained for when no learning schedule is set.
+pub(crate) const DEFAULT_TOTAL_ROLLOUTS: usize = 300;
+
 type NormalizedOnPolicyAlgorithmFor<AB, EB, SH> =
     NormalizedOnPolicyAlgorithm<<AB as AgentBuilder>::Agent, EB, SH>;
 
@@ -63,6 +72,7 @@ type NormalizedOnPolicyAlgorithmFor<AB, EB, SH> =
 /// - agent construction
 /// - learning schedule configuration
 /// - optional evaluation of the best actor during training
+/// - optional early stopping on [`StopCondition`]s
 ///
 /// Algorithm-specific builders such as `PPOAlgorithmBuilder` and
 /// `A2CAlgorithmBuilder` build on top of this type.
@@ -74,7 +84,11 @@ pub struct OnPolicyAlgorithmBuilder<
 > {
     pub(crate) sampler_builder: SamplerBuilder<EB, SH, ST>,
     pub(crate) learning_schedule: LearningSchedule,
-    pub(crate) learning_rate_schedule: Option<crate::LearningRateSchedule>,
+    pub(crate) learning_rate_schedule: Option<crate::Schedule>,
+    pub(crate) metrics_logger: Option<MetricsLogger>,
+    pub(crate) evaluation_callback: Option<EvaluationCallback>,
+    pub(crate) stop_conditions: Vec<Box<dyn StopCondition>>,
+    pub(crate) loss_monitor: Option<LossMonitor>,
     pub(crate) evaluator_builder: Option<BestActorEvaluatorBuilder<EB>>,
     pub(crate) agent_builder: AB,
     pub(crate) seed: Option<u64>,
@@ -90,8 +104,12 @@ impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST
             sampler_builder,
             agent_builder,
             evaluator_builder: None,
-            learning_schedule: LearningSchedule::rollout_bound(300),
+            learning_schedule: LearningSchedule::rollout_bound(DEFAULT_TOTAL_ROLLOUTS),
             learning_rate_schedule: None,
+            metrics_logger: None,
+            evaluation_callback: None,
+            stop_conditions: vec![],
+            loss_monitor: None,
             seed: None,
         }
     }
@@ -106,6 +124,10 @@ impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST
             agent_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             seed,
         } = self;
@@ -115,6 +137,10 @@ impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST
             evaluator_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             seed,
         }
     }
@@ -130,6 +156,10 @@ impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST
             agent_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             seed,
         } = self;
@@ -139,6 +169,10 @@ impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST
             evaluator_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             seed,
         }
     }
@@ -161,12 +195,72 @@ impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST
     /// Sets the learning-rate schedule applied over the training duration.
     pub fn with_learning_rate_schedule(
         mut self,
-        learning_rate_schedule: crate::LearningRateSchedule,
+        learning_rate_schedule: impl Into<crate::Schedule>,
+    ) -> Self {
+        self.learning_rate_schedule = Some(learning_rate_schedule.into());
+        self
+    }
+
+    /// Sets the logger receiving training, rollout and evaluation metrics.
+    ///
+    /// The logger is shared by the agent hooks, the outer training loop and the
+    /// evaluator, and its global step counts the sampled environment steps.
+    pub fn with_metrics_logger(mut self, logger: MetricsLogger) -> Self {
+        self.metrics_logger = Some(logger);
+        self
+    }
+
+    /// Reports the mean reward of every evaluation pass, with the number of
+    /// sampled environment steps, to `callback`. Training stops early when it
+    /// returns `false`.
+    ///
+    /// The callback is only called when an evaluator is configured.
+    pub fn with_evaluation_callback(
+        mut self,
+        callback: impl FnMut(usize, f32) -> bool + Send + 'static,
     ) -> Self {
-        self.learning_rate_schedule = Some(learning_rate_schedule);
+        self.evaluation_callback = Some(Box::new(callback));
         self
     }
 
+    /// Stops training early once `condition` is met.
+    ///
+    /// Conditions compose: training stops at the first one met, or at the end
+    /// of the learning schedule. Conditions on evaluation rewards are only
+    /// checked when an evaluator is configured.
+    pub fn with_stop_condition(mut self, condition: impl StopCondition + 'static) -> Self {
+        self.stop_conditions.push(Box::new(condition));
+        self
+    }
+
+    /// Stops training once an evaluation's mean reward reaches `threshold`.
+    pub fn with_reward_threshold(self, threshold: f32) -> Self {
+        self.with_stop_condition(RewardThreshold::new(threshold))
+    }
+
+    /// Stops training once `patience` evaluations in a row did not improve on
+    /// the best mean reward.
+    ///
+    /// Returns an error when `patience` is zero.
+    pub fn with_no_improvement_patience(self, patience: usize) -> anyhow::Result<Self> {
+        Ok(self.with_stop_condition(NoImprovement::new(patience)?))
+    }
+
+    /// Stops training once `budget` of wall-clock time has passed.
+    pub fn with_time_budget(self, budget: Duration) -> Self {
+        self.with_stop_condition(TimeBudget::new(budget))
+    }
+
+    /// Stops training once a loss is NaN or infinite. The update of that loss
+    /// is skipped, so the agent keeps its last finite parameters.
+    pub fn with_stop_on_non_finite_loss(mut self) -> Self {
+        let monitor = self
+            .loss_monitor
+            .get_or_insert_with(LossMonitor::new)
+            .clone();
+        self.with_stop_condition(NonFiniteLoss::new(monitor))
+    }
+
     /// Sets the seed used by r2l, Gym reset seeds, and backend-specific RNGs.
     pub fn with_seed(mut self, seed: u64) -> Self {
         self.seed = Some(seed);
@@ -272,6 +366,10 @@ impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST
             sampler_builder,
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             agent_builder,
             seed,
@@ -280,6 +378,10 @@ impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST
             sampler_builder: sampler_builder.with_obs_normalizer(obs_clip),
             learning_schedule,
             learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
             evaluator_builder,
             agent_builder,
             seed,
@@ -287,10 +389,13 @@ impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST
     }
 }
 
-impl<AB: AgentBuilder, EB: EnvBuilder<Env: Env<Tensor: R2lTensor>>, ST>
-    OnPolicyAlgorithmBuilder<AB, EB, StepHookBound<EB::Env>, ST>
+impl<AB: AgentBuilder, EB: EnvBuilder<Env: Env<Tensor: R2lTensor>>>
+    OnPolicyAlgorithmBuilder<AB, EB, StepHookBound<EB::Env>, DirectSamplerSelection>
 {
     /// Enables reward normalization for step-bounded training rollouts.
+    ///
+    /// If observation normalization is enabled afterwards, the normalized
+    /// sampler takes over reward normalization.
     pub fn with_reward_normalizer(mut self, gamma: f32, clip_reward: f32) -> Self {
         self.sampler_builder.hook_builder = self
             .sampler_builder
@@ -300,6 +405,21 @@ impl<AB: AgentBuilder, EB: EnvBuilder<Env: Env<Tensor: R2lTensor>>, ST>
     }
 }
 
+impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>>
+    OnPolicyAlgorithmBuilder<AB, EB, SH, NormalizedSamplerSelection>
+{
+    /// Enables reward normalization inside the normalized sampler.
+    ///
+    /// Running return statistics are updated step by step, for step-bounded
+    /// and episode-bounded rollouts alike. Evaluation samplers share the
+    /// statistics read-only.
+    pub fn with_reward_normalizer(mut self, gamma: f32, clip_reward: f32) -> Self {
+        self.sampler_builder.sampler_type.reward_normalizer =
+            Some(RewardNormalizerParams { gamma, clip_reward });
+        self
+    }
+}
+
 impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>>
     OnPolicyAlgorithmBuilder<AB, EB, SH, DirectSamplerSelection>
 {
@@ -333,12 +453,30 @@ impl<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>>
         let action_space = env_description.action_space;
         let agent = self
             .agent_builder
+            .with_episode_monitor(sampler.episode_monitor())
+            .with_metrics_logger(self.metrics_logger.clone())
+            .with_loss_monitor(self.loss_monitor)
             .build(observation_size, action_space, self.seed)?;
-        let evaluator = self.evaluator_builder.map(|eb| eb.build());
+        let evaluator = self.evaluator_builder.map(|eb| {
+            let eb = match &self.metrics_logger {
+                Some(logger) => eb.with_metrics_logger(logger.clone()),
+                None => eb,
+            };
+            eb.build()
+        });
         let mut hooks = DefaultOnPolicyAlgorithmHooks::new(self.learning_schedule, evaluator);
         if let Some(learning_rate_schedule) = self.learning_rate_schedule {
             hooks = hooks.with_learning_rate_schedule(learning_rate_schedule);
         }
+        if let Some(logger) = self.metrics_logger {
+            hooks = hooks.with_metrics_logger(logger);
+        }
+        if let Some(callback) = self.evaluation_callback {
+            hooks = hooks.with_evaluation_callback(callback);
+        }
+        for condition in self.stop_conditions {
+            hooks = hooks.with_stop_condition(condition);
+        }
         Ok(OnPolicyAlgorithm {
             runtime: OnPolicyRuntime {
                 sampler,
@@ -372,16 +510,24 @@ impl<
         let action_space = env_description.action_space;
         let sampler = self.sampler_builder.build();
         let eval_obs_normalizer = sampler.obs_normalizer(NormalizerMode::ReadOnly);
+        let eval_reward_normalizer = sampler.reward_normalizer(NormalizerMode::ReadOnly);
         let agent = self
             .agent_builder
+            .with_episode_monitor(sampler.episode_monitor())
+            .with_metrics_logger(self.metrics_logger.clone())
+            .with_loss_monitor(self.loss_monitor)
             .build(observation_size, action_space, self.seed)?;
         let evaluator = self.evaluator_builder.map(|evaluator_builder| {
+            let evaluator_builder = match &self.metrics_logger {
+                Some(logger) => evaluator_builder.with_metrics_logger(logger.clone()),
+                None => evaluator_builder,
+            };
             let eval_sampler = R2lNormalizedSampler::build_with_obs_normalizer(
                 evaluator_builder.env_builder().clone(),
                 EpisodeBoundHook::new(evaluator_builder.n_episodes()),
                 evaluator_builder.execution_mode(),
                 eval_obs_normalizer,
-                false,
+                eval_reward_normalizer,
             );
             evaluator_builder.build_with_sampler(eval_sampler)
         });
@@ -389,6 +535,15 @@ impl<
         if let Some(learning_rate_schedule) = self.learning_rate_schedule {
             hooks = hooks.with_learning_rate_schedule(learning_rate_schedule);
         }
+        if let Some(logger) = self.metrics_logger {
+            hooks = hooks.with_metrics_logger(logger);
+        }
+        if let Some(callback) = self.evaluation_callback {
+            hooks = hooks.with_evaluation_callback(callback);
+        }
+        for condition in self.stop_conditions {
+            hooks = hooks.with_stop_condition(condition);
+        }
         Ok(OnPolicyAlgorithm {
             runtime: OnPolicyRuntime {
                 sampler,
@@ -399,3 +554,15 @@ impl<
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::PPOAlgorithmBuilder;
+
+    #[test]
+    fn no_improvement_patience_must_be_positive() {
+        let builder = || PPOAlgorithmBuilder::gym("CartPole-v1", 1);
+        assert!(builder().with_no_improvement_patience(0).is_err());
+        assert!(builder().with_no_improvement_patience(3).is_ok());
+    }
+}
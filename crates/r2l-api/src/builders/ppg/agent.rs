@@ -0,0 +1,301 @@
+use std::sync::mpsc::Sender;
+
+use burn::prelude::Backend;
+use candle_core::Device;
+use r2l_agents::on_policy_algorithms::{
+    ppg::{AuxiliaryRolloutStorage, PPG, PPGParams},
+    ppo::{PPO, PPOParams},
+};
+use r2l_core::{
+    env::Space,
+    episode::EpisodeMonitor,
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+    tensor::R2lTensor,
+};
+
+use crate::{
+    BurnBackend,
+    agents::ppg::{PPGBurnAgent, PPGCandleAgent},
+    builders::{
+        agent::{
+            AgentBuilder, BurnBackend as BuilderBurnBackend, CandleBackend, OnPolicyAgentBuilder,
+        },
+        learning_module::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType},
+        ppg::hook::DefaultPPGHookBuilder,
+    },
+    hooks::{early_stopping::LossMonitor, ppg::PPGAuxiliaryStats, ppo::PPOStats},
+    metrics::MetricsLogger,
+};
+
+/// Hyperparameters of both PPG phases.
+#[derive(Default)]
+pub struct PPGAgentParams {
+    /// Hyperparameters of the PPO policy phase.
+    pub ppo: PPOParams,
+    /// Hyperparameters of the auxiliary phase.
+    pub ppg: PPGParams,
+    /// Hidden layers of the auxiliary value head, empty for a single linear
+    /// layer on the policy features.
+    pub auxiliary_head_hidden_layers: Vec<usize>,
+}
+
+/// Builder for PPG agents.
+///
+/// The policy network gets an auxiliary value head, trained with the policy
+/// optimizer during the auxiliary phase.
+pub type PPGAgentBuilder =
+    OnPolicyAgentBuilder<PPGAgentParams, DefaultPPGHookBuilder, CandleBackend>;
+
+/// PPG agent builder specialized to the Candle backend.
+pub type PPGCandleAgentBuilder = PPGAgentBuilder;
+
+/// PPG agent builder specialized to the Burn backend.
+pub type PPGBurnAgentBuilder =
+    OnPolicyAgentBuilder<PPGAgentParams, DefaultPPGHookBuilder, BuilderBurnBackend>;
+
+impl PPGBurnAgentBuilder {
+    fn seed(&self, seed: Option<u64>) {
+        if let Some(seed) = seed {
+            BurnBackend::seed(&Default::default(), seed);
+        }
+    }
+}
+
+impl PPGAgentBuilder {
+    fn seed(&self, seed: Option<u64>) {
+        if let Some(seed) = seed {
+            self.backend.seed(seed);
+        }
+    }
+
+    /// Creates a PPG agent builder with default hyperparameters.
+    pub fn new(n_envs: usize) -> Self {
+        Self {
+            hook_builder: DefaultPPGHookBuilder::new(n_envs),
+            params: PPGAgentParams::default(),
+            learning_module_builder: OnPolicyLearningModuleBuilder {
+                shared_hidden_layers: vec![],
+                policy_hidden_layers: vec![64, 64],
+                value_hidden_layers: vec![64, 64],
+                activation_function: ActivationFunction::default(),
+                log_std_init: 0.0,
+                weight_init: WeightInit::default(),
+                learning_module_type: OnPolicyLearningModuleType::Split {
+                    policy_max_grad_norm: None,
+                    policy_optimizer: OptimizerConfig::adam(5e-4),
+                    value_max_grad_norm: None,
+                    value_optimizer: OptimizerConfig::adam(5e-4),
+                },
+            },
+            backend: CandleBackend {
+                device: Device::Cpu,
+            },
+        }
+    }
+}
+
+impl<Backend> OnPolicyAgentBuilder<PPGAgentParams, DefaultPPGHookBuilder, Backend> {
+    /// Sets whether to log the training progress during learning.
+    pub fn with_log_progress(mut self, log_progress: bool) -> Self {
+        self.hook_builder = self.hook_builder.with_log_progress(log_progress);
+        self
+    }
+
+    /// Enables or disables advantage normalization.
+    pub fn with_normalize_advantage(mut self, normalize_advantage: bool) -> Self {
+        self.hook_builder = self
+            .hook_builder
+            .with_normalize_advantage(normalize_advantage);
+        self
+    }
+
+    /// Sets the entropy coefficient of the policy phase.
+    pub fn with_entropy_coeff(mut self, entropy_coeff: f32) -> Self {
+        self.hook_builder = self.hook_builder.with_entropy_coeff(entropy_coeff);
+        self
+    }
+
+    /// Sets the target KL threshold of the policy phase.
+    pub fn with_target_kl(mut self, target_kl: Option<f32>) -> Self {
+        self.hook_builder = self.hook_builder.with_target_kl(target_kl);
+        self
+    }
+
+    /// Sets gradient clipping for the policy phase.
+    pub fn with_gradient_clipping(mut self, gradient_clipping: Option<f32>) -> Self {
+        self.hook_builder = self.hook_builder.with_gradient_clipping(gradient_clipping);
+        self
+    }
+
+    /// Installs a reporter channel for the `PPOStats` of the policy phase.
+    pub fn with_reporter(mut self, tx: Option<Sender<PPOStats>>) -> Self {
+        self.hook_builder = self.hook_builder.with_reporter(tx);
+        self
+    }
+
+    /// Installs a reporter channel for `PPGAuxiliaryStats`.
+    pub fn with_auxiliary_reporter(mut self, tx: Option<Sender<PPGAuxiliaryStats>>) -> Self {
+        self.hook_builder = self.hook_builder.with_auxiliary_reporter(tx);
+        self
+    }
+
+    /// Sets the PPO clip range.
+    pub fn with_clip_range(mut self, clip_range: f32) -> Self {
+        self.params.ppo.clip_range = clip_range;
+        self
+    }
+
+    /// Sets the discount factor.
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.params.ppo.gamma = gamma;
+        self
+    }
+
+    /// Sets the GAE lambda parameter.
+    pub fn with_lambda(mut self, lambda: f32) -> Self {
+        self.params.ppo.lambda = lambda;
+        self
+    }
+
+    /// Sets the minibatch size of the policy phase.
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.params.ppo.sample_size = sample_size;
+        self
+    }
+
+    /// Sets the number of rollouts learned between two auxiliary phases.
+    pub fn with_n_policy_iterations(mut self, n_policy_iterations: usize) -> Self {
+        self.params.ppg.n_policy_iterations = n_policy_iterations;
+        self
+    }
+
+    /// Sets the number of passes over the stored rollouts in each auxiliary phase.
+    pub fn with_n_aux_epochs(mut self, n_aux_epochs: usize) -> Self {
+        self.params.ppg.n_aux_epochs = n_aux_epochs;
+        self
+    }
+
+    /// Sets the weight of the KL term of the auxiliary phase.
+    pub fn with_beta_clone(mut self, beta_clone: f32) -> Self {
+        self.params.ppg.beta_clone = beta_clone;
+        self
+    }
+
+    /// Sets the minibatch size of the auxiliary phase.
+    pub fn with_aux_sample_size(mut self, aux_sample_size: usize) -> Self {
+        self.params.ppg.aux_sample_size = aux_sample_size;
+        self
+    }
+
+    /// Sets the hidden layers of the auxiliary value head.
+    ///
+    /// They use the activation function and weight initialization of the
+    /// policy and value networks.
+    pub fn with_auxiliary_head_hidden_layers(mut self, hidden_layers: Vec<usize>) -> Self {
+        self.params.auxiliary_head_hidden_layers = hidden_layers;
+        self
+    }
+}
+
+impl AgentBuilder for PPGAgentBuilder {
+    type Agent = PPGCandleAgent;
+
+    fn build<T: R2lTensor>(
+        self,
+        observation_size: usize,
+        action_space: Space<T>,
+        seed: Option<u64>,
+    ) -> anyhow::Result<Self::Agent> {
+        self.seed(seed);
+        let device = self.backend.device.clone();
+        let PPGAgentParams {
+            ppo,
+            ppg,
+            auxiliary_head_hidden_layers,
+        } = self.params;
+        let lm = self
+            .learning_module_builder
+            .build_candle_with_auxiliary_value_head(
+                observation_size,
+                action_space,
+                &auxiliary_head_hidden_layers,
+                &device,
+            )?;
+        let hooks = self.hook_builder.build();
+        Ok(PPGCandleAgent(PPG {
+            params: ppg,
+            ppo: PPO {
+                lm,
+                hooks,
+                params: ppo,
+            },
+            storage: AuxiliaryRolloutStorage::default(),
+        }))
+    }
+
+    fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.hook_builder = self.hook_builder.with_metrics_logger(logger);
+        self
+    }
+
+    fn with_loss_monitor(mut self, monitor: Option<LossMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_loss_monitor(monitor);
+        self
+    }
+}
+
+impl AgentBuilder for PPGBurnAgentBuilder {
+    type Agent = PPGBurnAgent<BurnBackend>;
+
+    fn build<T: R2lTensor>(
+        self,
+        observation_size: usize,
+        action_space: Space<T>,
+        seed: Option<u64>,
+    ) -> anyhow::Result<Self::Agent> {
+        self.seed(seed);
+        let PPGAgentParams {
+            ppo,
+            ppg,
+            auxiliary_head_hidden_layers,
+        } = self.params;
+        let lm = self
+            .learning_module_builder
+            .build_burn_with_auxiliary_value_head::<BurnBackend, _>(
+                observation_size,
+                action_space,
+                &auxiliary_head_hidden_layers,
+            )?;
+        let hooks = self.hook_builder.build();
+        Ok(PPGBurnAgent(PPG {
+            params: ppg,
+            ppo: PPO {
+                lm,
+                hooks,
+                params: ppo,
+            },
+            storage: AuxiliaryRolloutStorage::default(),
+        }))
+    }
+
+    fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.hook_builder = self.hook_builder.with_metrics_logger(logger);
+        self
+    }
+
+    fn with_loss_monitor(mut self, monitor: Option<LossMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_loss_monitor(monitor);
+        self
+    }
+}
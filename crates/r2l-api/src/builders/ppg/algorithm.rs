@@ -0,0 +1,396 @@
+use std::sync::mpsc::Sender;
+
+use candle_core::Device;
+use r2l_core::{
+    env::{Env, EnvBuilder},
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+    tensor::R2lTensor,
+};
+use r2l_gym::GymEnvBuilder;
+
+use crate::builders::{
+    agent::{AgentBuilder, OnPolicyAgentBuilder},
+    learning_module::OnPolicyLearningModuleType,
+    on_policy::OnPolicyAlgorithmBuilder,
+    ppg::{
+        agent::{PPGAgentParams, PPGBurnAgentBuilder, PPGCandleAgentBuilder},
+        hook::DefaultPPGHookBuilder,
+    },
+    sampler::{SamplerBuilder, SamplerHookBuilder, StepHookBound},
+};
+use crate::hooks::{ppg::PPGAuxiliaryStats, ppo::PPOStats};
+
+impl<B, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
+    OnPolicyAlgorithmBuilder<
+        OnPolicyAgentBuilder<PPGAgentParams, DefaultPPGHookBuilder, B>,
+        EB,
+        SH,
+        ST,
+    >
+where
+    OnPolicyAgentBuilder<PPGAgentParams, DefaultPPGHookBuilder, B>: AgentBuilder,
+{
+    /// Sets whether to log the progress of both phases during learning.
+    pub fn with_log_progress(mut self, log_progress: bool) -> Self {
+        self.agent_builder = self.agent_builder.with_log_progress(log_progress);
+        self
+    }
+
+    /// Enables or disables advantage normalization in the policy phase.
+    pub fn with_normalize_advantage(mut self, normalize_advantage: bool) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_normalize_advantage(normalize_advantage);
+        self
+    }
+
+    /// Sets the maximum number of PPO epochs per rollout.
+    pub fn with_total_epochs(mut self, total_epochs: usize) -> Self {
+        self.agent_builder.hook_builder = self
+            .agent_builder
+            .hook_builder
+            .with_total_epochs(total_epochs);
+        self
+    }
+
+    /// Sets the entropy coefficient of the policy phase.
+    pub fn with_entropy_coeff(mut self, entropy_coeff: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_entropy_coeff(entropy_coeff);
+        self
+    }
+
+    /// Sets the target KL threshold of the policy phase.
+    pub fn with_target_kl(mut self, target_kl: Option<f32>) -> Self {
+        self.agent_builder = self.agent_builder.with_target_kl(target_kl);
+        self
+    }
+
+    /// Sets gradient clipping for the policy phase.
+    pub fn with_gradient_clipping(mut self, gradient_clipping: Option<f32>) -> Self {
+        self.agent_builder = self.agent_builder.with_gradient_clipping(gradient_clipping);
+        self
+    }
+
+    /// Installs a reporter channel for the [`PPOStats`](crate::PPOStats) of the
+    /// policy phase.
+    pub fn with_reporter(mut self, reporter: Option<Sender<PPOStats>>) -> Self {
+        self.agent_builder = self.agent_builder.with_reporter(reporter);
+        self
+    }
+
+    /// Installs a reporter channel for
+    /// [`PPGAuxiliaryStats`](crate::PPGAuxiliaryStats).
+    pub fn with_auxiliary_reporter(mut self, reporter: Option<Sender<PPGAuxiliaryStats>>) -> Self {
+        self.agent_builder = self.agent_builder.with_auxiliary_reporter(reporter);
+        self
+    }
+
+    /// Sets the PPO clip range.
+    pub fn with_clip_range(mut self, clip_range: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_clip_range(clip_range);
+        self
+    }
+
+    /// Sets the discount factor.
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_gamma(gamma);
+        self
+    }
+
+    /// Sets the GAE lambda parameter.
+    pub fn with_lambda(mut self, lambda: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_lambda(lambda);
+        self
+    }
+
+    /// Sets the minibatch size of the policy phase.
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.agent_builder = self.agent_builder.with_sample_size(sample_size);
+        self
+    }
+
+    /// Sets the number of rollouts learned between two auxiliary phases.
+    pub fn with_n_policy_iterations(mut self, n_policy_iterations: usize) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_n_policy_iterations(n_policy_iterations);
+        self
+    }
+
+    /// Sets the number of passes over the stored rollouts in each auxiliary phase.
+    pub fn with_n_aux_epochs(mut self, n_aux_epochs: usize) -> Self {
+        self.agent_builder = self.agent_builder.with_n_aux_epochs(n_aux_epochs);
+        self
+    }
+
+    /// Sets the weight of the KL term of the auxiliary phase.
+    pub fn with_beta_clone(mut self, beta_clone: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_beta_clone(beta_clone);
+        self
+    }
+
+    /// Sets the minibatch size of the auxiliary phase.
+    pub fn with_aux_sample_size(mut self, aux_sample_size: usize) -> Self {
+        self.agent_builder = self.agent_builder.with_aux_sample_size(aux_sample_size);
+        self
+    }
+
+    /// Sets the hidden layers of the auxiliary value head.
+    pub fn with_auxiliary_head_hidden_layers(mut self, hidden_layers: Vec<usize>) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_auxiliary_head_hidden_layers(hidden_layers);
+        self
+    }
+
+    /// Sets the hidden layer sizes of a trunk shared by the policy and value
+    /// networks.
+    pub fn with_shared_hidden_layers(mut self, shared_hidden_layers: Vec<usize>) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_shared_hidden_layers(shared_hidden_layers);
+        self
+    }
+
+    /// Sets the hidden layer sizes used by the policy network.
+    pub fn with_policy_hidden_layers(mut self, policy_hidden_layers: Vec<usize>) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_policy_hidden_layers(policy_hidden_layers);
+        self
+    }
+
+    /// Sets the hidden layer sizes used by the value network.
+    pub fn with_value_hidden_layers(mut self, value_hidden_layers: Vec<usize>) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_value_hidden_layers(value_hidden_layers);
+        self
+    }
+
+    /// Sets the hidden-layer activation function used by policy and value networks.
+    pub fn with_activation_function(mut self, activation_function: ActivationFunction) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_activation_function(activation_function);
+        self
+    }
+
+    /// Sets the initial log standard deviation for Gaussian policies.
+    pub fn with_log_std_init(mut self, log_std_init: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_log_std_init(log_std_init);
+        self
+    }
+
+    /// Sets how the weights of the policy and value networks are initialized.
+    pub fn with_weight_init(mut self, weight_init: WeightInit) -> Self {
+        self.agent_builder = self.agent_builder.with_weight_init(weight_init);
+        self
+    }
+
+    /// Sets the optimizer learning rate for all configured optimizers.
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
+        self.agent_builder = self.agent_builder.with_learning_rate(learning_rate);
+        self.learning_rate_schedule = Some(crate::Schedule::constant(learning_rate));
+        self
+    }
+
+    /// Uses a joint policy-value learning module configuration.
+    pub fn with_joint(mut self, max_grad_norm: Option<f32>, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_joint(max_grad_norm, optimizer);
+        self
+    }
+
+    /// Uses the same optimizer for every configured optimizer slot.
+    pub fn with_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_optimizer(optimizer);
+        self
+    }
+
+    /// Sets the policy optimizer, also used by the auxiliary value head.
+    pub fn with_policy_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_policy_optimizer(optimizer);
+        self
+    }
+
+    /// Sets the value optimizer, switching to separate policy and value optimizers.
+    pub fn with_value_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_value_optimizer(optimizer);
+        self
+    }
+
+    /// Replaces the full learning module configuration.
+    pub fn with_learning_module_type(
+        mut self,
+        learning_module_type: OnPolicyLearningModuleType,
+    ) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_learning_module_type(learning_module_type);
+        self
+    }
+}
+
+/// High-level PPG algorithm builder specialized to the Candle backend.
+pub type PPGCandleAlgorithmBuilder<
+    EB,
+    SH = StepHookBound<<EB as EnvBuilder>::Env>,
+    ST = crate::builders::sampler::DirectSamplerSelection,
+> = OnPolicyAlgorithmBuilder<PPGCandleAgentBuilder, EB, SH, ST>;
+
+impl PPGCandleAlgorithmBuilder<GymEnvBuilder> {
+    /// Creates a PPG algorithm builder for a Gym environment.
+    pub fn gym<EB: Into<GymEnvBuilder>>(builder: EB, n_envs: usize) -> Self {
+        Self::from_sampler_and_agent_builder(
+            SamplerBuilder::new(builder, n_envs),
+            PPGCandleAgentBuilder::new(n_envs),
+        )
+    }
+}
+
+impl<EB: EnvBuilder<Env: Env<Tensor: R2lTensor>>> PPGCandleAlgorithmBuilder<EB> {
+    /// Creates a PPG algorithm builder for a custom environment builder.
+    pub fn new(builder: EB, n_envs: usize) -> Self {
+        Self::from_sampler_and_agent_builder(
+            SamplerBuilder::new(builder, n_envs),
+            PPGCandleAgentBuilder::new(n_envs),
+        )
+    }
+}
+
+/// High-level PPG algorithm builder specialized to the Burn backend.
+pub type PPGBurnAlgorithmBuilder<
+    EB,
+    SH = StepHookBound<<EB as EnvBuilder>::Env>,
+    ST = crate::builders::sampler::DirectSamplerSelection,
+> = OnPolicyAlgorithmBuilder<PPGBurnAgentBuilder, EB, SH, ST>;
+
+impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
+    PPGBurnAlgorithmBuilder<EB, SH, ST>
+{
+    /// Switches the algorithm builder to the Candle backend.
+    pub fn with_candle(self, device: candle_core::Device) -> PPGCandleAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_candle(device),
+            seed,
+        }
+    }
+
+    /// Keeps the algorithm builder on the Burn backend.
+    pub fn with_burn(self) -> PPGBurnAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_burn(),
+            seed,
+        }
+    }
+}
+
+/// Default high-level PPG algorithm builder.
+///
+/// This alias uses the Candle backend by default.
+pub type PPGAlgorithmBuilder<
+    EB,
+    SH = StepHookBound<<EB as EnvBuilder>::Env>,
+    ST = crate::builders::sampler::DirectSamplerSelection,
+> = PPGCandleAlgorithmBuilder<EB, SH, ST>;
+
+impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
+    PPGCandleAlgorithmBuilder<EB, SH, ST>
+{
+    /// Switches the algorithm builder to the Candle backend.
+    pub fn with_candle(self, device: Device) -> PPGCandleAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_candle(device),
+            seed,
+        }
+    }
+
+    /// Switches the algorithm builder to the Burn backend.
+    pub fn with_burn(self) -> PPGBurnAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_burn(),
+            seed,
+        }
+    }
+}
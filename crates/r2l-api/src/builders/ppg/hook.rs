@@ -0,0 +1,117 @@
+use std::sync::mpsc::Sender;
+
+use r2l_core::episode::EpisodeMonitor;
+
+use crate::{
+    builders::ppo::hook::DefaultPPOHookBuilder,
+    hooks::{
+        early_stopping::LossMonitor,
+        ppg::{DefaultPPGHook, DefaultPPGHookReporter, PPGAuxiliaryStats},
+        ppo::PPOStats,
+    },
+    metrics::MetricsLogger,
+};
+
+/// Builder for the default PPG training hook.
+///
+/// The policy phase is configured like PPO, through a wrapped
+/// [`DefaultPPOHookBuilder`]. It defaults to one epoch per rollout, as in the
+/// PPG paper.
+#[derive(Debug, Clone)]
+pub struct DefaultPPGHookBuilder {
+    ppo: DefaultPPOHookBuilder,
+    log_progress: bool,
+    tx: Option<Sender<PPGAuxiliaryStats>>,
+    logger: Option<MetricsLogger>,
+}
+
+impl DefaultPPGHookBuilder {
+    /// Creates a default PPG hook builder.
+    pub fn new(n_envs: usize) -> Self {
+        Self {
+            ppo: DefaultPPOHookBuilder::new(n_envs).with_total_epochs(1),
+            log_progress: true,
+            tx: None,
+            logger: None,
+        }
+    }
+
+    /// Sets whether to log the progress of both phases during learning.
+    pub fn with_log_progress(mut self, log_progress: bool) -> Self {
+        self.ppo = self.ppo.with_log_progress(log_progress);
+        self.log_progress = log_progress;
+        self
+    }
+
+    /// Enables or disables advantage normalization in the policy phase.
+    pub fn with_normalize_advantage(mut self, normalize_advantage: bool) -> Self {
+        self.ppo = self.ppo.with_normalize_advantage(normalize_advantage);
+        self
+    }
+
+    /// Sets the maximum number of PPO epochs per rollout.
+    pub fn with_total_epochs(mut self, total_epochs: usize) -> Self {
+        self.ppo = self.ppo.with_total_epochs(total_epochs);
+        self
+    }
+
+    /// Sets the entropy coefficient of the policy phase.
+    pub fn with_entropy_coeff(mut self, entropy_coeff: f32) -> Self {
+        self.ppo = self.ppo.with_entropy_coeff(entropy_coeff);
+        self
+    }
+
+    /// Sets the optional target KL threshold of the policy phase.
+    pub fn with_target_kl(mut self, target_kl: Option<f32>) -> Self {
+        self.ppo = self.ppo.with_target_kl(target_kl);
+        self
+    }
+
+    /// Sets the optional gradient clipping threshold of the policy phase.
+    pub fn with_gradient_clipping(mut self, gradient_clipping: Option<f32>) -> Self {
+        self.ppo = self.ppo.with_gradient_clipping(gradient_clipping);
+        self
+    }
+
+    /// Installs a channel used to emit the [`PPOStats`](crate::PPOStats) of
+    /// the policy phase.
+    pub fn with_reporter(mut self, tx: Option<Sender<PPOStats>>) -> Self {
+        self.ppo = self.ppo.with_reporter(tx);
+        self
+    }
+
+    /// Installs a channel used to emit
+    /// [`PPGAuxiliaryStats`](crate::PPGAuxiliaryStats).
+    pub fn with_auxiliary_reporter(mut self, tx: Option<Sender<PPGAuxiliaryStats>>) -> Self {
+        self.tx = tx;
+        self
+    }
+
+    /// Reads rollout episode statistics from the sampler's [`EpisodeMonitor`].
+    pub fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.ppo = self.ppo.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    /// Installs a [`MetricsLogger`] receiving the stats of both phases.
+    pub fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.ppo = self.ppo.with_metrics_logger(logger.clone());
+        self.logger = logger;
+        self
+    }
+
+    /// Raises the [`LossMonitor`] flag on a NaN or infinite loss of either
+    /// phase, skipping the update that would apply it.
+    pub fn with_loss_monitor(mut self, loss_monitor: Option<LossMonitor>) -> Self {
+        self.ppo = self.ppo.with_loss_monitor(loss_monitor);
+        self
+    }
+
+    /// Builds the default PPG hook.
+    pub fn build<T>(self) -> DefaultPPGHook<T> {
+        DefaultPPGHook {
+            ppo: self.ppo.build(),
+            reporter: DefaultPPGHookReporter::new(self.tx, self.log_progress, self.logger),
+        }
+    }
+}
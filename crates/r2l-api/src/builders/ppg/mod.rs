@@ -0,0 +1,3 @@
+pub mod agent;
+pub mod algorithm;
+pub mod hook;
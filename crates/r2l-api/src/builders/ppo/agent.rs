@@ -2,9 +2,14 @@ use std::sync::mpsc::Sender;
 
 use burn::prelude::Backend;
 use candle_core::Device;
-use candle_nn::ParamsAdamW;
 use r2l_agents::on_policy_algorithms::ppo::{PPO, PPOParams};
-use r2l_core::{env::Space, models::ActivationFunction, tensor::R2lTensor};
+use r2l_core::{
+    env::Space,
+    episode::EpisodeMonitor,
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+    tensor::R2lTensor,
+};
 
 use crate::{
     BurnBackend,
@@ -16,7 +21,8 @@ use crate::{
         learning_module::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType},
         ppo::hook::DefaultPPOHookBuilder,
     },
-    hooks::ppo::PPOStats,
+    hooks::{early_stopping::LossMonitor, ppo::PPOStats, schedule::Schedule},
+    metrics::MetricsLogger,
 };
 
 /// Builder for PPO agents.
@@ -53,12 +59,14 @@ impl PPOAgentBuilder {
             hook_builder: DefaultPPOHookBuilder::new(n_envs),
             params: PPOParams::default(),
             learning_module_builder: OnPolicyLearningModuleBuilder {
+                shared_hidden_layers: vec![],
                 policy_hidden_layers: vec![64, 64],
                 value_hidden_layers: vec![64, 64],
                 activation_function: ActivationFunction::default(),
                 log_std_init: 0.0,
+                weight_init: WeightInit::default(),
                 learning_module_type: OnPolicyLearningModuleType::Joint {
-                    params: ParamsAdamW {
+                    optimizer: OptimizerConfig::AdamW {
                         lr: 3e-4,
                         beta1: 0.9,
                         beta2: 0.999,
@@ -126,6 +134,49 @@ impl<Backend> OnPolicyAgentBuilder<PPOParams, DefaultPPOHookBuilder, Backend> {
         self
     }
 
+    /// Sets the optional value-function clip range.
+    pub fn with_clip_range_vf(mut self, clip_range_vf: Option<f32>) -> Self {
+        self.params.clip_range_vf = clip_range_vf;
+        self
+    }
+
+    /// Sets the PPO clip range as a schedule over training progress.
+    pub fn with_clip_range_schedule(mut self, schedule: impl Into<Schedule>) -> Self {
+        let schedule = schedule.into();
+        self.params.clip_range = schedule.initial_value() as f32;
+        self.hook_builder = self.hook_builder.with_clip_range_schedule(schedule);
+        self
+    }
+
+    /// Sets the value-function clip range as a schedule over training progress.
+    pub fn with_clip_range_vf_schedule(mut self, schedule: impl Into<Schedule>) -> Self {
+        let schedule = schedule.into();
+        self.params.clip_range_vf = Some(schedule.initial_value() as f32);
+        self.hook_builder = self.hook_builder.with_clip_range_vf_schedule(schedule);
+        self
+    }
+
+    /// Sets the entropy coefficient as a schedule over training progress.
+    pub fn with_entropy_coeff_schedule(mut self, schedule: impl Into<Schedule>) -> Self {
+        let schedule = schedule.into();
+        self.hook_builder = self.hook_builder.with_entropy_coeff_schedule(schedule);
+        self
+    }
+
+    /// Sets the value-function loss coefficient as a schedule over training progress.
+    pub fn with_vf_coeff_schedule(mut self, schedule: impl Into<Schedule>) -> Self {
+        let schedule = schedule.into();
+        self.hook_builder = self.hook_builder.with_vf_coeff_schedule(schedule);
+        self
+    }
+
+    /// Sets the target KL threshold as a schedule over training progress.
+    pub fn with_target_kl_schedule(mut self, schedule: impl Into<Schedule>) -> Self {
+        let schedule = schedule.into();
+        self.hook_builder = self.hook_builder.with_target_kl_schedule(schedule);
+        self
+    }
+
     /// Sets the discount factor.
     pub fn with_gamma(mut self, gamma: f32) -> Self {
         self.params.gamma = gamma;
@@ -163,6 +214,21 @@ impl AgentBuilder for PPOAgentBuilder {
         let params = self.params;
         Ok(PPOCandleAgent(PPO { lm, hooks, params }))
     }
+
+    fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.hook_builder = self.hook_builder.with_metrics_logger(logger);
+        self
+    }
+
+    fn with_loss_monitor(mut self, monitor: Option<LossMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_loss_monitor(monitor);
+        self
+    }
 }
 
 impl AgentBuilder for PPOBurnAgentBuilder {
@@ -182,4 +248,19 @@ impl AgentBuilder for PPOBurnAgentBuilder {
         let params = self.params;
         Ok(PPOBurnAgent(PPO { lm, hooks, params }))
     }
+
+    fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.hook_builder = self.hook_builder.with_metrics_logger(logger);
+        self
+    }
+
+    fn with_loss_monitor(mut self, monitor: Option<LossMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_loss_monitor(monitor);
+        self
+    }
 }
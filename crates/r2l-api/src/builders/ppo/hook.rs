@@ -1,6 +1,15 @@
 use std::{marker::PhantomData, sync::mpsc::Sender};
 
-use crate::hooks::ppo::{DefaultPPOHook, DefaultPPOHookReporter, PPOStats, TargetKl};
+use r2l_core::episode::EpisodeMonitor;
+
+use crate::{
+    hooks::{
+        early_stopping::LossMonitor,
+        ppo::{DefaultPPOHook, DefaultPPOHookReporter, PPOSchedules, PPOStats, TargetKl},
+        schedule::Schedule,
+    },
+    metrics::MetricsLogger,
+};
 
 /// Builder for the default PPO training hook.
 ///
@@ -10,15 +19,19 @@ use crate::hooks::ppo::{DefaultPPOHook, DefaultPPOHookReporter, PPOStats, Target
 /// optional reporting.
 #[derive(Debug, Clone)]
 pub struct DefaultPPOHookBuilder {
-    normalize_advantage: bool,
-    log_progress: bool,
-    total_epochs: usize,
-    entropy_coeff: f32,
-    vf_coeff: Option<f32>,
-    target_kl: Option<f32>,
-    gradient_clipping: Option<f32>,
+    pub(crate) normalize_advantage: bool,
+    pub(crate) log_progress: bool,
+    pub(crate) total_epochs: usize,
+    pub(crate) entropy_coeff: f32,
+    pub(crate) vf_coeff: Option<f32>,
+    schedules: PPOSchedules,
+    pub(crate) target_kl: Option<f32>,
+    pub(crate) gradient_clipping: Option<f32>,
     n_envs: usize,
     tx: Option<Sender<PPOStats>>,
+    episode_monitor: Option<EpisodeMonitor>,
+    logger: Option<MetricsLogger>,
+    loss_monitor: Option<LossMonitor>,
 }
 
 impl DefaultPPOHookBuilder {
@@ -32,10 +45,14 @@ impl DefaultPPOHookBuilder {
             total_epochs: 10,
             entropy_coeff: 0.,
             vf_coeff: None,
+            schedules: PPOSchedules::default(),
             target_kl: None,
             gradient_clipping: None,
             n_envs,
             tx: None,
+            episode_monitor: None,
+            logger: None,
+            loss_monitor: None,
         }
     }
 
@@ -69,6 +86,44 @@ impl DefaultPPOHookBuilder {
         self
     }
 
+    /// Drives `PPOParams::clip_range` with a schedule over training progress.
+    pub fn with_clip_range_schedule(mut self, schedule: impl Into<Schedule>) -> Self {
+        let schedule = schedule.into();
+        self.schedules.clip_range = Some(schedule);
+        self
+    }
+
+    /// Drives `PPOParams::clip_range_vf` with a schedule over training progress.
+    pub fn with_clip_range_vf_schedule(mut self, schedule: impl Into<Schedule>) -> Self {
+        let schedule = schedule.into();
+        self.schedules.clip_range_vf = Some(schedule);
+        self
+    }
+
+    /// Drives the entropy coefficient with a schedule over training progress.
+    pub fn with_entropy_coeff_schedule(mut self, schedule: impl Into<Schedule>) -> Self {
+        let schedule = schedule.into();
+        self.entropy_coeff = schedule.initial_value() as f32;
+        self.schedules.entropy_coeff = Some(schedule);
+        self
+    }
+
+    /// Drives the value-function loss coefficient with a schedule over training progress.
+    pub fn with_vf_coeff_schedule(mut self, schedule: impl Into<Schedule>) -> Self {
+        let schedule = schedule.into();
+        self.vf_coeff = Some(schedule.initial_value() as f32);
+        self.schedules.vf_coeff = Some(schedule);
+        self
+    }
+
+    /// Drives the target KL threshold with a schedule over training progress.
+    pub fn with_target_kl_schedule(mut self, schedule: impl Into<Schedule>) -> Self {
+        let schedule = schedule.into();
+        self.target_kl = Some(schedule.initial_value() as f32);
+        self.schedules.target_kl = Some(schedule);
+        self
+    }
+
     /// Sets the optional target KL threshold used for early stopping.
     pub fn with_target_kl(mut self, target_kl: Option<f32>) -> Self {
         self.target_kl = target_kl;
@@ -87,6 +142,27 @@ impl DefaultPPOHookBuilder {
         self
     }
 
+    /// Reads rollout episode statistics from the sampler's [`EpisodeMonitor`].
+    ///
+    /// Without a monitor, episodes are reconstructed from the rollout batches.
+    pub fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.episode_monitor = episode_monitor;
+        self
+    }
+
+    /// Installs a [`MetricsLogger`] receiving the stats of every rollout.
+    pub fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Raises the [`LossMonitor`] flag on a NaN or infinite loss, skipping
+    /// the update that would apply it.
+    pub fn with_loss_monitor(mut self, loss_monitor: Option<LossMonitor>) -> Self {
+        self.loss_monitor = loss_monitor;
+        self
+    }
+
     /// Builds the default PPO hook.
     pub fn build<T>(self) -> DefaultPPOHook<T> {
         DefaultPPOHook {
@@ -94,6 +170,7 @@ impl DefaultPPOHookBuilder {
             total_epochs: self.total_epochs,
             entropy_coeff: self.entropy_coeff,
             vf_coeff: self.vf_coeff,
+            schedules: self.schedules,
             target_kl: self.target_kl.map(|target| TargetKl {
                 target,
                 target_exceeded: false,
@@ -101,7 +178,14 @@ impl DefaultPPOHookBuilder {
             gradient_clipping: self.gradient_clipping,
             current_epoch: 0,
             rollout_idx: 0,
-            reporter: DefaultPPOHookReporter::new(self.tx, self.log_progress, self.n_envs),
+            reporter: DefaultPPOHookReporter::new(
+                self.tx,
+                self.log_progress,
+                self.n_envs,
+                self.episode_monitor,
+                self.logger,
+            ),
+            loss_monitor: self.loss_monitor,
             _lm: PhantomData,
         }
     }
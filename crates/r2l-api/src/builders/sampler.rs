@@ -5,14 +5,12 @@ use r2l_core::{
     tensor::R2lTensor,
 };
 use r2l_sampler::{
-    NormalizedSamplerHook, NormalizerMode, R2lNormalizedSampler, R2lSampler, SamplerExecutionMode,
-    SamplerHook,
+    NormalizedSamplerHook, NormalizerMode, R2lNormalizedSampler, R2lSampler, RewardNormalizer,
+    SamplerExecutionMode, SamplerHook,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::{
-    hooks::sampler::{EpisodeBoundHook, StepBoundHook},
-    utils::RewardNormalizer,
-};
+use crate::hooks::sampler::{EpisodeBoundHook, StepBoundHook};
 
 /// Builder trait for sampler hook configurations.
 ///
@@ -29,16 +27,29 @@ pub trait SamplerHookBuilder {
 
     /// Builds the hook used by [`SamplerBuilder`] when constructing a sampler.
     fn build(self, n_envs: usize) -> Self::Target;
+
+    /// Removes the reward normalization settings carried by this configuration.
+    ///
+    /// Normalized samplers normalize rewards themselves while transitions are
+    /// collected, so they take these settings away from the hook.
+    fn take_reward_normalizer(&mut self) -> Option<RewardNormalizerParams> {
+        None
+    }
 }
 
-struct RewardNormalizerParams {
-    gamma: f32,
-    clip_reward: f32,
+/// Reward normalization settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RewardNormalizerParams {
+    /// Discount factor of the returns whose running variance scales rewards.
+    pub gamma: f32,
+    /// Bound applied to normalized rewards.
+    pub clip_reward: f32,
 }
 
 impl RewardNormalizerParams {
-    fn build_normalizer(&self, n_envs: usize) -> RewardNormalizer {
-        RewardNormalizer::new(n_envs, self.gamma, self.clip_reward)
+    fn build_normalizer(&self) -> RewardNormalizer {
+        RewardNormalizer::new(NormalizerMode::Update, self.gamma, self.clip_reward)
     }
 }
 
@@ -73,10 +84,14 @@ impl<E: Env<Tensor: R2lTensor>> SamplerHookBuilder for StepHookBound<E> {
     type Env = E;
     type Target = StepBoundHook<Self::Env>;
 
-    fn build(self, n_env: usize) -> Self::Target {
-        let reward_normalizer = self.reward_normalizer.map(|p| p.build_normalizer(n_env));
+    fn build(self, _n_envs: usize) -> Self::Target {
+        let reward_normalizer = self.reward_normalizer.map(|p| p.build_normalizer());
         StepBoundHook::new(self.n_step, reward_normalizer)
     }
+
+    fn take_reward_normalizer(&mut self) -> Option<RewardNormalizerParams> {
+        self.reward_normalizer.take()
+    }
 }
 
 /// Episode-bounded sampler hook configuration.
@@ -120,6 +135,7 @@ pub struct DirectSamplerSelection;
 
 pub struct NormalizedSamplerSelection {
     pub(crate) obs_clip: Option<f32>,
+    pub(crate) reward_normalizer: Option<RewardNormalizerParams>,
 }
 
 pub struct SamplerBuilder<
@@ -133,6 +149,9 @@ pub struct SamplerBuilder<
     pub(crate) sampler_type: ST,
 }
 
+/// Execution mode of the training environments when none is set.
+pub(crate) const DEFAULT_EXECUTION_MODE: SamplerExecutionMode = SamplerExecutionMode::Vec;
+
 /// Default sampler builder using a step-bounded rollout policy.
 pub type DefaultSamplerBuilder<EB> = SamplerBuilder<EB, StepHookBound<<EB as EnvBuilder>::Env>>;
 
@@ -146,7 +165,7 @@ impl<EB: EnvBuilder<Env: Env<Tensor: R2lTensor>>> DefaultSamplerBuilder<EB> {
         Self {
             env_builder,
             hook_builder: StepHookBound::new(1024),
-            execution_mode: SamplerExecutionMode::Vec,
+            execution_mode: DEFAULT_EXECUTION_MODE,
             sampler_type: DirectSamplerSelection,
         }
     }
@@ -207,7 +226,10 @@ impl<EB: EnvBuilder, S: SamplerHookBuilder<Env = EB::Env>, ST> SamplerBuilder<EB
             env_builder,
             hook_builder,
             execution_mode,
-            sampler_type: NormalizedSamplerSelection { obs_clip },
+            sampler_type: NormalizedSamplerSelection {
+                obs_clip,
+                reward_normalizer: None,
+            },
         }
     }
 }
@@ -228,9 +250,25 @@ impl<
     S: SamplerHookBuilder<Env = EB::Env, Target: NormalizedSamplerHook<E = <EB as EnvBuilder>::Env>>,
 > SamplerBuilder<EB, S, NormalizedSamplerSelection>
 {
+    /// Enables reward normalization inside the sampler.
+    ///
+    /// Rewards are scaled step by step as transitions are collected, for any
+    /// rollout hook. Settings given to [`StepHookBound::with_reward_normalizer`]
+    /// are picked up the same way.
+    pub fn with_reward_normalizer(mut self, gamma: f32, clip_reward: f32) -> Self {
+        self.sampler_type.reward_normalizer = Some(RewardNormalizerParams { gamma, clip_reward });
+        self
+    }
+
     /// Builds the configured normalized sampler instance.
-    pub fn build(self) -> R2lNormalizedSampler<EB::Env, S::Target> {
+    pub fn build(mut self) -> R2lNormalizedSampler<EB::Env, S::Target> {
         let n_envs = self.env_builder.num_envs();
+        let hook_reward_normalizer = self.hook_builder.take_reward_normalizer();
+        let reward_normalizer = self
+            .sampler_type
+            .reward_normalizer
+            .or(hook_reward_normalizer)
+            .map(|params| params.build_normalizer());
         let hook = self.hook_builder.build(n_envs);
         R2lNormalizedSampler::build(
             self.env_builder,
@@ -238,7 +276,7 @@ impl<
             self.execution_mode,
             self.sampler_type.obs_clip,
             NormalizerMode::Update,
-            false,
+            reward_normalizer,
         )
     }
 }
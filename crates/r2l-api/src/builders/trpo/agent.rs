@@ -0,0 +1,239 @@
+use std::sync::mpsc::Sender;
+
+use anyhow::bail;
+use burn::prelude::Backend;
+use candle_core::Device;
+use r2l_agents::on_policy_algorithms::trpo::{TRPO, TRPOParams};
+use r2l_core::{
+    env::Space,
+    episode::EpisodeMonitor,
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+    tensor::R2lTensor,
+};
+
+use crate::{
+    BurnBackend,
+    agents::trpo::{TRPOBurnAgent, TRPOCandleAgent},
+    builders::{
+        agent::{
+            AgentBuilder, BurnBackend as BuilderBurnBackend, CandleBackend, OnPolicyAgentBuilder,
+        },
+        learning_module::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType},
+        trpo::hook::DefaultTRPOHookBuilder,
+    },
+    hooks::{early_stopping::LossMonitor, trpo::TRPOStats},
+    metrics::MetricsLogger,
+};
+
+/// Builder for TRPO agents.
+///
+/// TRPO steps the policy with natural gradients, so only the value optimizer
+/// of the learning module is used, and the policy and value optimizers must
+/// be separate.
+pub type TRPOAgentBuilder = OnPolicyAgentBuilder<TRPOParams, DefaultTRPOHookBuilder, CandleBackend>;
+
+/// TRPO agent builder specialized to the Candle backend.
+pub type TRPOCandleAgentBuilder = TRPOAgentBuilder;
+
+/// TRPO agent builder specialized to the Burn backend.
+pub type TRPOBurnAgentBuilder =
+    OnPolicyAgentBuilder<TRPOParams, DefaultTRPOHookBuilder, BuilderBurnBackend>;
+
+impl TRPOBurnAgentBuilder {
+    fn seed(&self, seed: Option<u64>) {
+        if let Some(seed) = seed {
+            BurnBackend::seed(&Default::default(), seed);
+        }
+    }
+}
+
+impl TRPOAgentBuilder {
+    fn seed(&self, seed: Option<u64>) {
+        if let Some(seed) = seed {
+            self.backend.seed(seed);
+        }
+    }
+
+    /// Creates a TRPO agent builder with default hyperparameters.
+    pub fn new(n_envs: usize) -> Self {
+        Self {
+            hook_builder: DefaultTRPOHookBuilder::new(n_envs),
+            params: TRPOParams::default(),
+            learning_module_builder: OnPolicyLearningModuleBuilder {
+                shared_hidden_layers: vec![],
+                policy_hidden_layers: vec![64, 64],
+                value_hidden_layers: vec![64, 64],
+                activation_function: ActivationFunction::default(),
+                log_std_init: 0.0,
+                weight_init: WeightInit::default(),
+                learning_module_type: OnPolicyLearningModuleType::Split {
+                    policy_max_grad_norm: None,
+                    policy_optimizer: OptimizerConfig::adam(1e-3),
+                    value_max_grad_norm: None,
+                    value_optimizer: OptimizerConfig::adam(1e-3),
+                },
+            },
+            backend: CandleBackend {
+                device: Device::Cpu,
+            },
+        }
+    }
+}
+
+impl<Backend> OnPolicyAgentBuilder<TRPOParams, DefaultTRPOHookBuilder, Backend> {
+    fn ensure_split(&self) -> anyhow::Result<()> {
+        if matches!(
+            self.learning_module_builder.learning_module_type,
+            OnPolicyLearningModuleType::Joint { .. }
+        ) {
+            bail!("TRPO needs separate policy and value optimizers");
+        }
+        Ok(())
+    }
+
+    /// Sets whether to log the training progress during learning.
+    pub fn with_log_progress(mut self, log_progress: bool) -> Self {
+        self.hook_builder = self.hook_builder.with_log_progress(log_progress);
+        self
+    }
+
+    /// Enables or disables advantage normalization.
+    pub fn with_normalize_advantage(mut self, normalize_advantage: bool) -> Self {
+        self.hook_builder = self
+            .hook_builder
+            .with_normalize_advantage(normalize_advantage);
+        self
+    }
+
+    /// Installs a reporter channel for `TRPOStats`.
+    pub fn with_reporter(mut self, tx: Option<Sender<TRPOStats>>) -> Self {
+        self.hook_builder = self.hook_builder.with_reporter(tx);
+        self
+    }
+
+    /// Sets the discount factor.
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.params.gamma = gamma;
+        self
+    }
+
+    /// Sets the GAE lambda parameter.
+    pub fn with_lambda(mut self, lambda: f32) -> Self {
+        self.params.lambda = lambda;
+        self
+    }
+
+    /// Sets the KL bound of each policy update.
+    pub fn with_target_kl(mut self, target_kl: f32) -> Self {
+        self.params.target_kl = target_kl;
+        self
+    }
+
+    /// Sets the maximum number of conjugate-gradient iterations.
+    pub fn with_cg_max_steps(mut self, cg_max_steps: usize) -> Self {
+        self.params.cg_max_steps = cg_max_steps;
+        self
+    }
+
+    /// Sets the damping added to the Fisher matrix.
+    pub fn with_cg_damping(mut self, cg_damping: f32) -> Self {
+        self.params.cg_damping = cg_damping;
+        self
+    }
+
+    /// Sets the factor the step is shrunk by after a rejected line-search step.
+    pub fn with_line_search_shrinking_factor(mut self, line_search_shrinking_factor: f32) -> Self {
+        self.params.line_search_shrinking_factor = line_search_shrinking_factor;
+        self
+    }
+
+    /// Sets the maximum number of line-search steps.
+    pub fn with_line_search_max_iter(mut self, line_search_max_iter: usize) -> Self {
+        self.params.line_search_max_iter = line_search_max_iter;
+        self
+    }
+
+    /// Sets the number of passes over the rollout used to fit the value function.
+    pub fn with_n_critic_updates(mut self, n_critic_updates: usize) -> Self {
+        self.params.n_critic_updates = n_critic_updates;
+        self
+    }
+
+    /// Sets the minibatch size used while fitting the value function.
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.params.sample_size = sample_size;
+        self
+    }
+}
+
+impl AgentBuilder for TRPOAgentBuilder {
+    type Agent = TRPOCandleAgent;
+
+    fn build<T: R2lTensor>(
+        self,
+        observation_size: usize,
+        action_space: Space<T>,
+        seed: Option<u64>,
+    ) -> anyhow::Result<Self::Agent> {
+        self.ensure_split()?;
+        self.seed(seed);
+        let device = self.backend.device.clone();
+        let lm =
+            self.learning_module_builder
+                .build_candle(observation_size, action_space, &device)?;
+        let hooks = self.hook_builder.build();
+        let params = self.params;
+        Ok(TRPOCandleAgent(TRPO { lm, hooks, params }))
+    }
+
+    fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.hook_builder = self.hook_builder.with_metrics_logger(logger);
+        self
+    }
+
+    fn with_loss_monitor(mut self, monitor: Option<LossMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_loss_monitor(monitor);
+        self
+    }
+}
+
+impl AgentBuilder for TRPOBurnAgentBuilder {
+    type Agent = TRPOBurnAgent<BurnBackend>;
+
+    fn build<T: R2lTensor>(
+        self,
+        observation_size: usize,
+        action_space: Space<T>,
+        seed: Option<u64>,
+    ) -> anyhow::Result<Self::Agent> {
+        self.ensure_split()?;
+        self.seed(seed);
+        let lm = self
+            .learning_module_builder
+            .build_burn::<BurnBackend, _>(observation_size, action_space)?;
+        let hooks = self.hook_builder.build();
+        let params = self.params;
+        Ok(TRPOBurnAgent(TRPO { lm, hooks, params }))
+    }
+
+    fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.hook_builder = self.hook_builder.with_metrics_logger(logger);
+        self
+    }
+
+    fn with_loss_monitor(mut self, monitor: Option<LossMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_loss_monitor(monitor);
+        self
+    }
+}
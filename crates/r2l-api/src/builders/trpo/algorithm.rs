@@ -0,0 +1,340 @@
+use std::sync::mpsc::Sender;
+
+use candle_core::Device;
+use r2l_agents::on_policy_algorithms::trpo::TRPOParams;
+use r2l_core::{
+    env::{Env, EnvBuilder},
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+    tensor::R2lTensor,
+};
+use r2l_gym::GymEnvBuilder;
+
+use crate::builders::{
+    agent::{AgentBuilder, OnPolicyAgentBuilder},
+    learning_module::OnPolicyLearningModuleType,
+    on_policy::OnPolicyAlgorithmBuilder,
+    sampler::{SamplerBuilder, SamplerHookBuilder, StepHookBound},
+    trpo::{
+        agent::{TRPOBurnAgentBuilder, TRPOCandleAgentBuilder},
+        hook::DefaultTRPOHookBuilder,
+    },
+};
+use crate::hooks::trpo::TRPOStats;
+
+impl<B, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
+    OnPolicyAlgorithmBuilder<
+        OnPolicyAgentBuilder<TRPOParams, DefaultTRPOHookBuilder, B>,
+        EB,
+        SH,
+        ST,
+    >
+where
+    OnPolicyAgentBuilder<TRPOParams, DefaultTRPOHookBuilder, B>: AgentBuilder,
+{
+    /// Sets whether to log the training progress during learning.
+    pub fn with_log_progress(mut self, log_progress: bool) -> Self {
+        self.agent_builder = self.agent_builder.with_log_progress(log_progress);
+        self
+    }
+
+    /// Enables or disables advantage normalization in the underlying TRPO hook.
+    pub fn with_normalize_advantage(mut self, normalize_advantage: bool) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_normalize_advantage(normalize_advantage);
+        self
+    }
+
+    /// Installs a reporter channel for [`TRPOStats`](crate::TRPOStats).
+    pub fn with_reporter(mut self, reporter: Option<Sender<TRPOStats>>) -> Self {
+        self.agent_builder = self.agent_builder.with_reporter(reporter);
+        self
+    }
+
+    /// Sets the discount factor.
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_gamma(gamma);
+        self
+    }
+
+    /// Sets the GAE lambda parameter.
+    pub fn with_lambda(mut self, lambda: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_lambda(lambda);
+        self
+    }
+
+    /// Sets the KL bound of each policy update.
+    pub fn with_target_kl(mut self, target_kl: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_target_kl(target_kl);
+        self
+    }
+
+    /// Sets the maximum number of conjugate-gradient iterations.
+    pub fn with_cg_max_steps(mut self, cg_max_steps: usize) -> Self {
+        self.agent_builder = self.agent_builder.with_cg_max_steps(cg_max_steps);
+        self
+    }
+
+    /// Sets the damping added to the Fisher matrix.
+    pub fn with_cg_damping(mut self, cg_damping: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_cg_damping(cg_damping);
+        self
+    }
+
+    /// Sets the factor the step is shrunk by after a rejected line-search step.
+    pub fn with_line_search_shrinking_factor(mut self, line_search_shrinking_factor: f32) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_line_search_shrinking_factor(line_search_shrinking_factor);
+        self
+    }
+
+    /// Sets the maximum number of line-search steps.
+    pub fn with_line_search_max_iter(mut self, line_search_max_iter: usize) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_line_search_max_iter(line_search_max_iter);
+        self
+    }
+
+    /// Sets the number of passes over the rollout used to fit the value function.
+    pub fn with_n_critic_updates(mut self, n_critic_updates: usize) -> Self {
+        self.agent_builder = self.agent_builder.with_n_critic_updates(n_critic_updates);
+        self
+    }
+
+    /// Sets the minibatch size used while fitting the value function.
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.agent_builder = self.agent_builder.with_sample_size(sample_size);
+        self
+    }
+
+    /// Sets the hidden layer sizes used by the policy network.
+    pub fn with_policy_hidden_layers(mut self, policy_hidden_layers: Vec<usize>) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_policy_hidden_layers(policy_hidden_layers);
+        self
+    }
+
+    /// Sets the hidden layer sizes used by the value network.
+    pub fn with_value_hidden_layers(mut self, value_hidden_layers: Vec<usize>) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_value_hidden_layers(value_hidden_layers);
+        self
+    }
+
+    /// Sets the hidden-layer activation function used by policy and value networks.
+    pub fn with_activation_function(mut self, activation_function: ActivationFunction) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_activation_function(activation_function);
+        self
+    }
+
+    /// Sets the initial log standard deviation for Gaussian policies.
+    pub fn with_log_std_init(mut self, log_std_init: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_log_std_init(log_std_init);
+        self
+    }
+
+    /// Sets how the weights of the policy and value networks are initialized.
+    pub fn with_weight_init(mut self, weight_init: WeightInit) -> Self {
+        self.agent_builder = self.agent_builder.with_weight_init(weight_init);
+        self
+    }
+
+    /// Sets the learning rate of the value optimizer.
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
+        self.agent_builder = self.agent_builder.with_learning_rate(learning_rate);
+        self.learning_rate_schedule = Some(crate::Schedule::constant(learning_rate));
+        self
+    }
+
+    /// Sets the optimizer used to fit the value function.
+    pub fn with_value_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_value_optimizer(optimizer);
+        self
+    }
+
+    /// Replaces the full learning module configuration.
+    ///
+    /// TRPO only supports [`OnPolicyLearningModuleType::Split`].
+    pub fn with_learning_module_type(
+        mut self,
+        learning_module_type: OnPolicyLearningModuleType,
+    ) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_learning_module_type(learning_module_type);
+        self
+    }
+}
+
+/// High-level TRPO algorithm builder specialized to the Candle backend.
+pub type TRPOCandleAlgorithmBuilder<
+    EB,
+    SH = StepHookBound<<EB as EnvBuilder>::Env>,
+    ST = crate::builders::sampler::DirectSamplerSelection,
+> = OnPolicyAlgorithmBuilder<TRPOCandleAgentBuilder, EB, SH, ST>;
+
+impl TRPOCandleAlgorithmBuilder<GymEnvBuilder> {
+    /// Creates an TRPO algorithm builder for a Gym environment.
+    pub fn gym<EB: Into<GymEnvBuilder>>(builder: EB, n_envs: usize) -> Self {
+        Self::from_sampler_and_agent_builder(
+            SamplerBuilder::new(builder, n_envs),
+            TRPOCandleAgentBuilder::new(n_envs),
+        )
+    }
+}
+
+impl<EB: EnvBuilder<Env: Env<Tensor: R2lTensor>>> TRPOCandleAlgorithmBuilder<EB> {
+    /// Creates an TRPO algorithm builder for a custom environment builder.
+    pub fn new(builder: EB, n_envs: usize) -> Self {
+        Self::from_sampler_and_agent_builder(
+            SamplerBuilder::new(builder, n_envs),
+            TRPOCandleAgentBuilder::new(n_envs),
+        )
+    }
+}
+
+/// High-level TRPO algorithm builder specialized to the Burn backend.
+pub type TRPOBurnAlgorithmBuilder<
+    EB,
+    SH = StepHookBound<<EB as EnvBuilder>::Env>,
+    ST = crate::builders::sampler::DirectSamplerSelection,
+> = OnPolicyAlgorithmBuilder<TRPOBurnAgentBuilder, EB, SH, ST>;
+
+impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
+    TRPOBurnAlgorithmBuilder<EB, SH, ST>
+{
+    /// Switches the algorithm builder to the Candle backend.
+    pub fn with_candle(
+        self,
+        device: candle_core::Device,
+    ) -> TRPOCandleAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_candle(device),
+            seed,
+        }
+    }
+
+    /// Keeps the algorithm builder on the Burn backend.
+    pub fn with_burn(self) -> TRPOBurnAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_burn(),
+            seed,
+        }
+    }
+}
+
+/// Default high-level TRPO algorithm builder.
+///
+/// This alias uses the Candle backend by default.
+pub type TRPOAlgorithmBuilder<
+    EB,
+    SH = StepHookBound<<EB as EnvBuilder>::Env>,
+    ST = crate::builders::sampler::DirectSamplerSelection,
+> = TRPOCandleAlgorithmBuilder<EB, SH, ST>;
+
+impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
+    TRPOCandleAlgorithmBuilder<EB, SH, ST>
+{
+    /// Switches the algorithm builder to the Candle backend.
+    pub fn with_candle(self, device: Device) -> TRPOCandleAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_candle(device),
+            seed,
+        }
+    }
+
+    /// Switches the algorithm builder to the Burn backend.
+    pub fn with_burn(self) -> TRPOBurnAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_burn(),
+            seed,
+        }
+    }
+}
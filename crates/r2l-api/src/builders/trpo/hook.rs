@@ -0,0 +1,96 @@
+use std::{marker::PhantomData, sync::mpsc::Sender};
+
+use r2l_core::episode::EpisodeMonitor;
+
+use crate::{
+    hooks::{
+        early_stopping::LossMonitor,
+        trpo::{DefaultTRPOHook, DefaultTRPOHookReporter, TRPOStats},
+    },
+    metrics::MetricsLogger,
+};
+
+/// Builder for the default TRPO training hook.
+///
+/// This builder configures advantage normalization and optional reporting for
+/// the hook used by the TRPO agent and algorithm builders.
+#[derive(Debug, Clone)]
+pub struct DefaultTRPOHookBuilder {
+    normalize_advantage: bool,
+    log_progress: bool,
+    n_envs: usize,
+    tx: Option<Sender<TRPOStats>>,
+    episode_monitor: Option<EpisodeMonitor>,
+    logger: Option<MetricsLogger>,
+    loss_monitor: Option<LossMonitor>,
+}
+
+impl DefaultTRPOHookBuilder {
+    /// Creates a default TRPO hook builder.
+    pub fn new(n_envs: usize) -> Self {
+        Self {
+            n_envs,
+            normalize_advantage: true,
+            log_progress: true,
+            tx: None,
+            episode_monitor: None,
+            logger: None,
+            loss_monitor: None,
+        }
+    }
+
+    /// Sets whether to log training progress during learning.
+    pub fn with_log_progress(mut self, log_progress: bool) -> Self {
+        self.log_progress = log_progress;
+        self
+    }
+
+    /// Enables or disables advantage normalization before learning.
+    pub fn with_normalize_advantage(mut self, normalize_advantage: bool) -> Self {
+        self.normalize_advantage = normalize_advantage;
+        self
+    }
+
+    /// Installs a channel used to emit [`TRPOStats`](crate::TRPOStats).
+    pub fn with_reporter(mut self, tx: Option<Sender<TRPOStats>>) -> Self {
+        self.tx = tx;
+        self
+    }
+
+    /// Reads rollout episode statistics from the sampler's [`EpisodeMonitor`].
+    ///
+    /// Without a monitor, episodes are reconstructed from the rollout batches.
+    pub fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.episode_monitor = episode_monitor;
+        self
+    }
+
+    /// Installs a [`MetricsLogger`] receiving the stats of every rollout.
+    pub fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Raises the [`LossMonitor`] flag on a NaN or infinite loss, skipping
+    /// the update that would apply it.
+    pub fn with_loss_monitor(mut self, loss_monitor: Option<LossMonitor>) -> Self {
+        self.loss_monitor = loss_monitor;
+        self
+    }
+
+    /// Builds the default TRPO hook.
+    pub fn build<T>(self) -> DefaultTRPOHook<T> {
+        DefaultTRPOHook {
+            normalize_advantage: self.normalize_advantage,
+            reporter: DefaultTRPOHookReporter::new(
+                self.tx,
+                self.log_progress,
+                self.n_envs,
+                self.episode_monitor,
+                self.logger,
+            ),
+            loss_monitor: self.loss_monitor,
+            _lm: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::sync::mpsc::Sender;
+
+use burn::prelude::Backend;
+use candle_core::Device;
+use r2l_agents::on_policy_algorithms::vpg::{VPG, VPGParams};
+use r2l_core::{
+    env::Space,
+    episode::EpisodeMonitor,
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+    tensor::R2lTensor,
+};
+
+use crate::{
+    BurnBackend,
+    agents::vpg::{VPGBurnAgent, VPGCandleAgent},
+    builders::{
+        agent::{
+            AgentBuilder, BurnBackend as BuilderBurnBackend, CandleBackend, OnPolicyAgentBuilder,
+        },
+        learning_module::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType},
+        vpg::hook::DefaultVPGHookBuilder,
+    },
+    hooks::{early_stopping::LossMonitor, vpg::VPGStats},
+    metrics::MetricsLogger,
+};
+
+/// Builder for VPG agents.
+///
+/// This is the main entry point for configuring VPG-specific agent behavior,
+/// such as the baseline, advantage normalization and VPG hook settings.
+pub type VPGAgentBuilder = OnPolicyAgentBuilder<VPGParams, DefaultVPGHookBuilder, CandleBackend>;
+
+/// VPG agent builder specialized to the Candle backend.
+pub type VPGCandleAgentBuilder = VPGAgentBuilder;
+
+/// VPG agent builder specialized to the Burn backend.
+pub type VPGBurnAgentBuilder =
+    OnPolicyAgentBuilder<VPGParams, DefaultVPGHookBuilder, BuilderBurnBackend>;
+
+impl VPGBurnAgentBuilder {
+    fn seed(&self, seed: Option<u64>) {
+        if let Some(seed) = seed {
+            BurnBackend::seed(&Default::default(), seed);
+        }
+    }
+}
+
+impl VPGAgentBuilder {
+    fn seed(&self, seed: Option<u64>) {
+        if let Some(seed) = seed {
+            self.backend.seed(seed);
+        }
+    }
+
+    /// Creates a VPG agent builder with default hyperparameters.
+    pub fn new(n_envs: usize) -> Self {
+        Self {
+            hook_builder: DefaultVPGHookBuilder::new(n_envs),
+            params: VPGParams::default(),
+            learning_module_builder: OnPolicyLearningModuleBuilder {
+                shared_hidden_layers: vec![],
+                policy_hidden_layers: vec![64, 64],
+                value_hidden_layers: vec![64, 64],
+                activation_function: ActivationFunction::default(),
+                log_std_init: 0.0,
+                weight_init: WeightInit::default(),
+                learning_module_type: OnPolicyLearningModuleType::Joint {
+                    max_grad_norm: None,
+                    optimizer: OptimizerConfig::AdamW {
+                        lr: 3e-4,
+                        beta1: 0.9,
+                        beta2: 0.999,
+                        eps: 1e-5,
+                        weight_decay: 1e-4,
+                    },
+                },
+            },
+            backend: CandleBackend {
+                device: Device::Cpu,
+            },
+        }
+    }
+}
+
+impl<Backend> OnPolicyAgentBuilder<VPGParams, DefaultVPGHookBuilder, Backend> {
+    /// Sets whether to log the training progress during learning.
+    pub fn with_log_progress(mut self, log_progress: bool) -> Self {
+        self.hook_builder = self.hook_builder.with_log_progress(log_progress);
+        self
+    }
+
+    /// Enables or disables advantage normalization.
+    pub fn with_normalize_advantage(mut self, normalize_advantage: bool) -> Self {
+        self.hook_builder = self
+            .hook_builder
+            .with_normalize_advantage(normalize_advantage);
+        self
+    }
+
+    /// Sets the entropy coefficient.
+    pub fn with_entropy_coeff(mut self, entropy_coeff: f32) -> Self {
+        self.hook_builder = self.hook_builder.with_entropy_coeff(entropy_coeff);
+        self
+    }
+
+    /// Sets the value-function loss coefficient.
+    pub fn with_vf_coeff(mut self, vf_coeff: Option<f32>) -> Self {
+        self.hook_builder = self.hook_builder.with_vf_coeff(vf_coeff);
+        self
+    }
+
+    /// Sets gradient clipping for the default VPG hook.
+    pub fn with_gradient_clipping(mut self, gradient_clipping: Option<f32>) -> Self {
+        self.hook_builder = self.hook_builder.with_gradient_clipping(gradient_clipping);
+        self
+    }
+
+    /// Installs a reporter channel for `VPGStats`.
+    pub fn with_reporter(mut self, tx: Option<Sender<VPGStats>>) -> Self {
+        self.hook_builder = self.hook_builder.with_reporter(tx);
+        self
+    }
+
+    /// Sets the discount factor.
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.params.gamma = gamma;
+        self
+    }
+
+    /// Sets the GAE lambda parameter.
+    pub fn with_lambda(mut self, lambda: f32) -> Self {
+        self.params.lambda = lambda;
+        self
+    }
+
+    /// Sets the rollout sample size used during training updates.
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.params.sample_size = sample_size;
+        self
+    }
+
+    /// Sets whether the value function is used as a baseline.
+    ///
+    /// Disabling it trains the policy with REINFORCE on Monte-Carlo returns.
+    pub fn with_baseline(mut self, baseline: bool) -> Self {
+        self.params.baseline = baseline;
+        self
+    }
+}
+
+impl AgentBuilder for VPGAgentBuilder {
+    type Agent = VPGCandleAgent;
+
+    fn build<T: R2lTensor>(
+        self,
+        observation_size: usize,
+        action_space: Space<T>,
+        seed: Option<u64>,
+    ) -> anyhow::Result<Self::Agent> {
+        self.seed(seed);
+        let device = self.backend.device.clone();
+        let lm =
+            self.learning_module_builder
+                .build_candle(observation_size, action_space, &device)?;
+        let hooks = self.hook_builder.build();
+        let params = self.params;
+        Ok(VPGCandleAgent(VPG { lm, hooks, params }))
+    }
+
+    fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.hook_builder = self.hook_builder.with_metrics_logger(logger);
+        self
+    }
+
+    fn with_loss_monitor(mut self, monitor: Option<LossMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_loss_monitor(monitor);
+        self
+    }
+}
+
+impl AgentBuilder for VPGBurnAgentBuilder {
+    type Agent = VPGBurnAgent<BurnBackend>;
+
+    fn build<T: R2lTensor>(
+        self,
+        observation_size: usize,
+        action_space: Space<T>,
+        seed: Option<u64>,
+    ) -> anyhow::Result<Self::Agent> {
+        self.seed(seed);
+        let lm = self
+            .learning_module_builder
+            .build_burn::<BurnBackend, _>(observation_size, action_space)?;
+        let hooks = self.hook_builder.build();
+        let params = self.params;
+        Ok(VPGBurnAgent(VPG { lm, hooks, params }))
+    }
+
+    fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_episode_monitor(episode_monitor);
+        self
+    }
+
+    fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.hook_builder = self.hook_builder.with_metrics_logger(logger);
+        self
+    }
+
+    fn with_loss_monitor(mut self, monitor: Option<LossMonitor>) -> Self {
+        self.hook_builder = self.hook_builder.with_loss_monitor(monitor);
+        self
+    }
+}
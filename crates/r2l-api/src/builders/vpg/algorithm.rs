@@ -0,0 +1,384 @@
+use std::sync::mpsc::Sender;
+
+use candle_core::Device;
+use r2l_agents::on_policy_algorithms::vpg::VPGParams;
+use r2l_core::{
+    env::{Env, EnvBuilder},
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+    tensor::R2lTensor,
+};
+use r2l_gym::GymEnvBuilder;
+
+use crate::builders::{
+    agent::{AgentBuilder, OnPolicyAgentBuilder},
+    learning_module::OnPolicyLearningModuleType,
+    on_policy::OnPolicyAlgorithmBuilder,
+    sampler::{SamplerBuilder, SamplerHookBuilder, StepHookBound},
+    vpg::{
+        agent::{VPGBurnAgentBuilder, VPGCandleAgentBuilder},
+        hook::DefaultVPGHookBuilder,
+    },
+};
+use crate::hooks::vpg::VPGStats;
+
+impl<B, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
+    OnPolicyAlgorithmBuilder<OnPolicyAgentBuilder<VPGParams, DefaultVPGHookBuilder, B>, EB, SH, ST>
+where
+    OnPolicyAgentBuilder<VPGParams, DefaultVPGHookBuilder, B>: AgentBuilder,
+{
+    /// Sets whether to log the training progress during learning.
+    pub fn with_log_progress(mut self, log_progress: bool) -> Self {
+        self.agent_builder = self.agent_builder.with_log_progress(log_progress);
+        self
+    }
+
+    /// Enables or disables advantage normalization in the underlying VPG hook.
+    pub fn with_normalize_advantage(mut self, normalize_advantage: bool) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_normalize_advantage(normalize_advantage);
+        self
+    }
+
+    /// Sets the entropy coefficient.
+    pub fn with_entropy_coeff(mut self, entropy_coeff: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_entropy_coeff(entropy_coeff);
+        self
+    }
+
+    /// Sets the optional value-function loss coefficient.
+    pub fn with_vf_coeff(mut self, vf_coeff: Option<f32>) -> Self {
+        self.agent_builder = self.agent_builder.with_vf_coeff(vf_coeff);
+        self
+    }
+
+    /// Sets optional gradient clipping in the underlying VPG hook.
+    pub fn with_gradient_clipping(mut self, gradient_clipping: Option<f32>) -> Self {
+        self.agent_builder = self.agent_builder.with_gradient_clipping(gradient_clipping);
+        self
+    }
+
+    /// Installs a reporter channel for [`VPGStats`](crate::VPGStats).
+    pub fn with_reporter(mut self, tx: Option<Sender<VPGStats>>) -> Self {
+        self.agent_builder = self.agent_builder.with_reporter(tx);
+        self
+    }
+
+    /// Sets the discount factor.
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_gamma(gamma);
+        self
+    }
+
+    /// Sets the GAE lambda parameter.
+    pub fn with_lambda(mut self, lambda: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_lambda(lambda);
+        self
+    }
+
+    /// Sets the rollout sample size used during training updates.
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.agent_builder = self.agent_builder.with_sample_size(sample_size);
+        self
+    }
+
+    /// Sets whether the value function is used as a baseline.
+    ///
+    /// Disabling it trains the policy with REINFORCE on Monte-Carlo returns.
+    pub fn with_baseline(mut self, baseline: bool) -> Self {
+        self.agent_builder = self.agent_builder.with_baseline(baseline);
+        self
+    }
+
+    /// Sets the hidden layer sizes of a trunk shared by the policy and value
+    /// networks.
+    pub fn with_shared_hidden_layers(mut self, shared_hidden_layers: Vec<usize>) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_shared_hidden_layers(shared_hidden_layers);
+        self
+    }
+
+    /// Sets the hidden layer sizes used by the policy network.
+    pub fn with_policy_hidden_layers(mut self, policy_hidden_layers: Vec<usize>) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_policy_hidden_layers(policy_hidden_layers);
+        self
+    }
+
+    /// Sets the hidden-layer activation function used by policy and value networks.
+    pub fn with_activation_function(mut self, activation_function: ActivationFunction) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_activation_function(activation_function);
+        self
+    }
+
+    /// Sets the initial log standard deviation for Gaussian policies.
+    pub fn with_log_std_init(mut self, log_std_init: f32) -> Self {
+        self.agent_builder = self.agent_builder.with_log_std_init(log_std_init);
+        self
+    }
+
+    /// Sets how the weights of the policy and value networks are initialized.
+    pub fn with_weight_init(mut self, weight_init: WeightInit) -> Self {
+        self.agent_builder = self.agent_builder.with_weight_init(weight_init);
+        self
+    }
+
+    /// Sets the optimizer learning rate for all configured optimizers.
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
+        self.agent_builder = self.agent_builder.with_learning_rate(learning_rate);
+        self.learning_rate_schedule = Some(crate::Schedule::constant(learning_rate));
+        self
+    }
+
+    /// Sets the Adam `beta1` parameter for all configured Adam optimizers.
+    pub fn with_beta1(mut self, beta1: f64) -> Self {
+        self.agent_builder = self.agent_builder.with_beta1(beta1);
+        self
+    }
+
+    /// Sets the Adam `beta2` parameter for all configured Adam optimizers.
+    pub fn with_beta2(mut self, beta2: f64) -> Self {
+        self.agent_builder = self.agent_builder.with_beta2(beta2);
+        self
+    }
+
+    /// Sets the epsilon parameter for all configured optimizers.
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.agent_builder = self.agent_builder.with_epsilon(epsilon);
+        self
+    }
+
+    /// Sets the weight decay parameter for all configured optimizers.
+    pub fn with_weight_decay(mut self, weight_decay: f64) -> Self {
+        self.agent_builder = self.agent_builder.with_weight_decay(weight_decay);
+        self
+    }
+
+    /// Uses a joint policy-value learning module configuration.
+    pub fn with_joint(mut self, max_grad_norm: Option<f32>, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_joint(max_grad_norm, optimizer);
+        self
+    }
+
+    /// Uses separate optimizer settings for the policy and value modules.
+    pub fn with_split(
+        mut self,
+        policy_max_grad_norm: Option<f32>,
+        policy_optimizer: OptimizerConfig,
+        value_max_grad_norm: Option<f32>,
+        value_optimizer: OptimizerConfig,
+    ) -> Self {
+        self.agent_builder = self.agent_builder.with_split(
+            policy_max_grad_norm,
+            policy_optimizer,
+            value_max_grad_norm,
+            value_optimizer,
+        );
+        self
+    }
+
+    /// Uses the same optimizer for every configured optimizer slot.
+    pub fn with_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_optimizer(optimizer);
+        self
+    }
+
+    /// Sets the policy optimizer, switching to separate policy and value optimizers.
+    pub fn with_policy_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_policy_optimizer(optimizer);
+        self
+    }
+
+    /// Sets the value optimizer, switching to separate policy and value optimizers.
+    pub fn with_value_optimizer(mut self, optimizer: OptimizerConfig) -> Self {
+        self.agent_builder = self.agent_builder.with_value_optimizer(optimizer);
+        self
+    }
+
+    /// Sets the hidden layer sizes used by the value network.
+    pub fn with_value_hidden_layers(mut self, value_hidden_layers: Vec<usize>) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_value_hidden_layers(value_hidden_layers);
+        self
+    }
+
+    /// Replaces the full learning module configuration.
+    pub fn with_learning_module_type(
+        mut self,
+        learning_module_type: OnPolicyLearningModuleType,
+    ) -> Self {
+        self.agent_builder = self
+            .agent_builder
+            .with_learning_module_type(learning_module_type);
+        self
+    }
+}
+
+/// High-level VPG algorithm builder specialized to the Candle backend.
+pub type VPGCandleAlgorithmBuilder<
+    EB,
+    SH = StepHookBound<<EB as EnvBuilder>::Env>,
+    ST = crate::builders::sampler::DirectSamplerSelection,
+> = OnPolicyAlgorithmBuilder<VPGCandleAgentBuilder, EB, SH, ST>;
+
+impl VPGCandleAlgorithmBuilder<GymEnvBuilder> {
+    /// Creates a VPG algorithm builder for a Gym environment.
+    pub fn gym<EB: Into<GymEnvBuilder>>(builder: EB, n_envs: usize) -> Self {
+        Self::from_sampler_and_agent_builder(
+            SamplerBuilder::new(builder, n_envs),
+            VPGCandleAgentBuilder::new(n_envs),
+        )
+    }
+}
+
+impl<EB: EnvBuilder<Env: Env<Tensor: R2lTensor>>> VPGCandleAlgorithmBuilder<EB> {
+    /// Creates a VPG algorithm builder for a custom environment builder.
+    pub fn new(builder: EB, n_envs: usize) -> Self {
+        Self::from_sampler_and_agent_builder(
+            SamplerBuilder::new(builder, n_envs),
+            VPGCandleAgentBuilder::new(n_envs),
+        )
+    }
+}
+
+/// High-level VPG algorithm builder specialized to the Burn backend.
+pub type VPGBurnAlgorithmBuilder<
+    EB,
+    SH = StepHookBound<<EB as EnvBuilder>::Env>,
+    ST = crate::builders::sampler::DirectSamplerSelection,
+> = OnPolicyAlgorithmBuilder<VPGBurnAgentBuilder, EB, SH, ST>;
+
+impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
+    VPGBurnAlgorithmBuilder<EB, SH, ST>
+{
+    /// Switches the algorithm builder to the Candle backend.
+    pub fn with_candle(self, device: candle_core::Device) -> VPGCandleAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_candle(device),
+            seed,
+        }
+    }
+
+    /// Keeps the algorithm builder on the Burn backend.
+    pub fn with_burn(self) -> VPGBurnAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_burn(),
+            seed,
+        }
+    }
+}
+
+/// Default high-level VPG algorithm builder.
+///
+/// This alias uses the Candle backend by default.
+pub type VPGAlgorithmBuilder<
+    EB,
+    SH = StepHookBound<<EB as EnvBuilder>::Env>,
+    ST = crate::builders::sampler::DirectSamplerSelection,
+> = VPGCandleAlgorithmBuilder<EB, SH, ST>;
+
+impl<EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>, ST>
+    VPGCandleAlgorithmBuilder<EB, SH, ST>
+{
+    /// Switches the algorithm builder to the Candle backend.
+    pub fn with_candle(self, device: Device) -> VPGCandleAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_candle(device),
+            seed,
+        }
+    }
+
+    /// Switches the algorithm builder to the Burn backend.
+    pub fn with_burn(self) -> VPGBurnAlgorithmBuilder<EB, SH, ST> {
+        let OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder,
+            seed,
+        } = self;
+        OnPolicyAlgorithmBuilder {
+            sampler_builder,
+            learning_schedule,
+            learning_rate_schedule,
+            metrics_logger,
+            evaluation_callback,
+            stop_conditions,
+            loss_monitor,
+            evaluator_builder,
+            agent_builder: agent_builder.with_burn(),
+            seed,
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::{marker::PhantomData, sync::mpsc::Sender};
+
+use r2l_core::episode::EpisodeMonitor;
+
+use crate::{
+    hooks::{
+        early_stopping::LossMonitor,
+        vpg::{DefaultVPGHook, DefaultVPGHookReporter, VPGStats},
+    },
+    metrics::MetricsLogger,
+};
+
+/// Builder for the default VPG training hook.
+///
+/// This builder configures the hook behavior used by the VPG agent and
+/// algorithm builders, including advantage normalization, loss coefficients,
+/// gradient clipping, and optional reporting.
+#[derive(Debug, Clone)]
+pub struct DefaultVPGHookBuilder {
+    pub(crate) normalize_advantage: bool,
+    pub(crate) log_progress: bool,
+    pub(crate) entropy_coeff: f32,
+    pub(crate) vf_coeff: Option<f32>,
+    pub(crate) gradient_clipping: Option<f32>,
+    n_envs: usize,
+    tx: Option<Sender<VPGStats>>,
+    episode_monitor: Option<EpisodeMonitor>,
+    logger: Option<MetricsLogger>,
+    loss_monitor: Option<LossMonitor>,
+}
+
+impl DefaultVPGHookBuilder {
+    /// Creates a default VPG hook builder.
+    pub fn new(n_envs: usize) -> Self {
+        Self {
+            n_envs,
+            normalize_advantage: false,
+            log_progress: true,
+            entropy_coeff: 0.,
+            vf_coeff: None,
+            gradient_clipping: None,
+            tx: None,
+            episode_monitor: None,
+            logger: None,
+            loss_monitor: None,
+        }
+    }
+
+    /// Sets whether to log training progress during learning.
+    pub fn with_log_progress(mut self, log_progress: bool) -> Self {
+        self.log_progress = log_progress;
+        self
+    }
+
+    /// Enables or disables advantage normalization before learning.
+    pub fn with_normalize_advantage(mut self, normalize_advantage: bool) -> Self {
+        self.normalize_advantage = normalize_advantage;
+        self
+    }
+
+    /// Sets the entropy coefficient added during optimization.
+    pub fn with_entropy_coeff(mut self, entropy_coeff: f32) -> Self {
+        self.entropy_coeff = entropy_coeff;
+        self
+    }
+
+    /// Sets the optional value-function loss coefficient.
+    pub fn with_vf_coeff(mut self, vf_coeff: Option<f32>) -> Self {
+        self.vf_coeff = vf_coeff;
+        self
+    }
+
+    /// Sets the optional gradient clipping threshold used during learning.
+    pub fn with_gradient_clipping(mut self, gradient_clipping: Option<f32>) -> Self {
+        self.gradient_clipping = gradient_clipping;
+        self
+    }
+
+    /// Installs a channel used to emit [`VPGStats`](crate::VPGStats).
+    pub fn with_reporter(mut self, tx: Option<Sender<VPGStats>>) -> Self {
+        self.tx = tx;
+        self
+    }
+
+    /// Reads rollout episode statistics from the sampler's [`EpisodeMonitor`].
+    ///
+    /// Without a monitor, episodes are reconstructed from the rollout batches.
+    pub fn with_episode_monitor(mut self, episode_monitor: Option<EpisodeMonitor>) -> Self {
+        self.episode_monitor = episode_monitor;
+        self
+    }
+
+    /// Installs a [`MetricsLogger`] receiving the stats of every rollout.
+    pub fn with_metrics_logger(mut self, logger: Option<MetricsLogger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Raises the [`LossMonitor`] flag on a NaN or infinite loss, skipping
+    /// the update that would apply it.
+    pub fn with_loss_monitor(mut self, loss_monitor: Option<LossMonitor>) -> Self {
+        self.loss_monitor = loss_monitor;
+        self
+    }
+
+    /// Builds the default VPG hook.
+    pub fn build<T>(self) -> DefaultVPGHook<T> {
+        DefaultVPGHook {
+            normalize_advantage: self.normalize_advantage,
+            entropy_coeff: self.entropy_coeff,
+            vf_coeff: self.vf_coeff,
+            gradient_clipping: self.gradient_clipping,
+            reporter: DefaultVPGHookReporter::new(
+                self.tx,
+                self.log_progress,
+                self.n_envs,
+                self.episode_monitor,
+                self.logger,
+            ),
+            loss_monitor: self.loss_monitor,
+            _lm: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,623 @@
+use std::{collections::BTreeMap, fs};
+
+use anyhow::{Context, Result, anyhow, bail};
+use candle_core::Device;
+use r2l_core::{
+    env::EnvBuilder,
+    models::{Actor, OptimizerConfig},
+    on_policy::algorithm::{
+        Agent, OnPolicyAdapters, OnPolicyAlgorithm, OnPolicyAlgorithmHooks, Sampler,
+    },
+};
+
+use crate::{
+    A2CAgentBuilder, A2CAlgorithmBuilder, BestActorEvaluatorBuilder, EpisodeHookBound,
+    MetricsLogger, OnPolicyAlgorithmBuilder, OnPolicyLearningModuleType, PPOAgentBuilder,
+    PPOAlgorithmBuilder, StepHookBound, VPGAgentBuilder, VPGAlgorithmBuilder,
+    builders::{
+        agent::AgentBuilder,
+        on_policy::DEFAULT_TOTAL_ROLLOUTS,
+        sampler::{self, NormalizedSamplerSelection, SamplerHookBuilder},
+    },
+    config::{
+        AlgorithmConfig, BackendConfig, EnvConfig, ExperimentConfig, OptimizerKind,
+        OptimizerSettings, RolloutBound, ScheduleConfig, TrainingBudget,
+    },
+    evaluators::best_actor_evaluator,
+};
+
+/// File name of the best policy inside a run directory.
+pub const BEST_POLICY_FILE: &str = "best_policy.safetensors";
+
+/// Type-erased training run built from an [`ExperimentConfig`].
+///
+/// Implemented by every [`OnPolicyAlgorithm`].
+pub trait Trainable {
+    /// Runs the training loop until the learning schedule is exhausted.
+    fn train(&mut self) -> Result<()>;
+
+    /// Serializes the current policy, if its actor supports serialization.
+    fn serialized_policy(&self) -> Option<Vec<u8>>;
+}
+
+impl<
+    A: Agent,
+    S: Sampler,
+    H: OnPolicyAlgorithmHooks<A = A, S = S, C = C>,
+    C: OnPolicyAdapters<A::Actor, S>,
+> Trainable for OnPolicyAlgorithm<A, S, H, C>
+{
+    fn train(&mut self) -> Result<()> {
+        OnPolicyAlgorithm::train(self)
+    }
+
+    fn serialized_policy(&self) -> Option<Vec<u8>> {
+        self.runtime.actor().try_serialize()
+    }
+}
+
+/// Native environment builders that [`EnvConfig::Native`] entries refer to
+/// by name.
+pub struct EnvRegistry<EB: EnvBuilder> {
+    builders: BTreeMap<String, EB>,
+}
+
+impl<EB: EnvBuilder> Default for EnvRegistry<EB> {
+    fn default() -> Self {
+        Self {
+            builders: BTreeMap::new(),
+        }
+    }
+}
+
+impl<EB: EnvBuilder + Clone> EnvRegistry<EB> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `builder` under `name`, replacing any previous entry.
+    pub fn with_env(mut self, name: impl Into<String>, builder: EB) -> Self {
+        self.builders.insert(name.into(), builder);
+        self
+    }
+
+    /// Returns the builder registered under `name`.
+    pub fn get(&self, name: &str) -> Option<&EB> {
+        self.builders.get(name)
+    }
+}
+
+/// Applies the settings shared by every algorithm.
+fn configure<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>>(
+    config: &ExperimentConfig,
+    mut builder: OnPolicyAlgorithmBuilder<AB, EB, SH>,
+) -> Result<OnPolicyAlgorithmBuilder<AB, EB, SH>> {
+    if let Some(seed) = config.seed {
+        builder = builder.with_seed(seed);
+    }
+    if let Some(execution_mode) = config.sampler.execution_mode {
+        builder = builder.with_execution_mode(execution_mode);
+    }
+    if let Some(budget) = config.training.budget {
+        builder = builder.with_learning_schedule(budget.into());
+    }
+    if let Some(learning_rate) = &config.training.learning_rate {
+        builder = builder.with_learning_rate_schedule(learning_rate.build()?);
+    }
+    if let Some(run_dir) = &config.run_dir {
+        let logger = MetricsLogger::new()
+            .with_csv(run_dir.join("metrics.csv"))?
+            .with_tensorboard(run_dir.join("tensorboard"))?;
+        builder = builder.with_metrics_logger(logger);
+    }
+    if let Some(evaluator) = &config.evaluator {
+        let env_builder = builder.sampler_builder.env_builder.clone();
+        let mut evaluator_builder = BestActorEvaluatorBuilder::from_env_builder_type(env_builder);
+        if let Some(n_episodes) = evaluator.n_episodes {
+            evaluator_builder = evaluator_builder.with_n_episodes(n_episodes);
+        }
+        if let Some(frequency) = evaluator.frequency {
+            if frequency == 0 {
+                bail!("the evaluator frequency must be positive");
+            }
+            evaluator_builder = evaluator_builder.with_evaluator_frequency(frequency);
+        }
+        if let Some(execution_mode) = evaluator.execution_mode {
+            evaluator_builder = evaluator_builder.with_execution_mode(execution_mode);
+        }
+        let best_actor_path = evaluator.best_actor_path.clone().or_else(|| {
+            config
+                .run_dir
+                .as_ref()
+                .map(|run_dir| run_dir.join(BEST_POLICY_FILE))
+        });
+        if let Some(best_actor_path) = best_actor_path {
+            evaluator_builder = evaluator_builder.with_best_actor_path(best_actor_path);
+        }
+        if let Some(csv_states) = &evaluator.csv_states {
+            evaluator_builder = evaluator_builder.with_csv_states(csv_states);
+        }
+        builder = builder.with_evaluator(Some(evaluator_builder));
+    }
+    Ok(builder)
+}
+
+/// Switches to the normalized sampler with the configured normalizers.
+fn normalize<AB: AgentBuilder, EB: EnvBuilder, SH: SamplerHookBuilder<Env = EB::Env>>(
+    config: &ExperimentConfig,
+    builder: OnPolicyAlgorithmBuilder<AB, EB, SH>,
+) -> OnPolicyAlgorithmBuilder<AB, EB, SH, NormalizedSamplerSelection> {
+    let mut builder = builder.with_observation_normalizer(config.sampler.obs_clip);
+    if let Some(params) = config.sampler.reward_normalizer {
+        builder = builder.with_reward_normalizer(params.gamma, params.clip_reward);
+    }
+    builder
+}
+
+// The network and algorithm setters are inherent methods of each algorithm
+// builder, so they are applied by macros rather than generic functions.
+macro_rules! configure_network {
+    ($config:expr, $builder:expr) => {{
+        let config: &ExperimentConfig = $config;
+        let network = &config.network;
+        let mut builder = $builder;
+        if let Some(layers) = &network.shared_hidden_layers {
+            builder = builder.with_shared_hidden_layers(layers.clone());
+        }
+        if let Some(layers) = &network.policy_hidden_layers {
+            builder = builder.with_policy_hidden_layers(layers.clone());
+        }
+        if let Some(layers) = &network.value_hidden_layers {
+            builder = builder.with_value_hidden_layers(layers.clone());
+        }
+        if let Some(activation) = network.activation {
+            builder = builder.with_activation_function(activation);
+        }
+        if let Some(log_std_init) = network.log_std_init {
+            builder = builder.with_log_std_init(log_std_init);
+        }
+        if let Some(weight_init) = network.weight_init {
+            builder = builder.with_weight_init(weight_init);
+        }
+        if let Some(optimizer) = &config.optimizer {
+            builder = builder.with_optimizer(optimizer.try_into()?);
+        }
+        if let Some(optimizer) = &config.value_optimizer {
+            builder = builder.with_value_optimizer(optimizer.try_into()?);
+        }
+        builder
+    }};
+}
+
+macro_rules! configure_on_policy_params {
+    ($params:expr, $builder:expr) => {{
+        let params = $params;
+        let mut builder = $builder;
+        if let Some(gamma) = params.gamma {
+            builder = builder.with_gamma(gamma);
+        }
+        if let Some(lambda) = params.lambda {
+            builder = builder.with_lambda(lambda);
+        }
+        if let Some(sample_size) = params.sample_size {
+            builder = builder.with_sample_size(sample_size);
+        }
+        if let Some(normalize_advantage) = params.normalize_advantage {
+            builder = builder.with_normalize_advantage(normalize_advantage);
+        }
+        if let Some(max_grad_norm) = params.max_grad_norm {
+            builder = builder.with_gradient_clipping(Some(max_grad_norm));
+        }
+        if let Some(log_progress) = params.log_progress {
+            builder = builder.with_log_progress(log_progress);
+        }
+        builder
+    }};
+}
+
+macro_rules! configure_ppo {
+    ($params:expr, $builder:expr) => {{
+        let params: &crate::config::PPOConfig = $params;
+        let mut builder = configure_on_policy_params!(params, $builder);
+        if let Some(total_epochs) = params.total_epochs {
+            builder = builder.with_total_epochs(total_epochs);
+        }
+        if let Some(clip_range) = &params.clip_range {
+            builder = builder.with_clip_range_schedule(clip_range.build()?);
+        }
+        if let Some(clip_range_vf) = &params.clip_range_vf {
+            builder = builder.with_clip_range_vf_schedule(clip_range_vf.build()?);
+        }
+        if let Some(entropy_coeff) = &params.entropy_coeff {
+            builder = builder.with_entropy_coeff_schedule(entropy_coeff.build()?);
+        }
+        if let Some(vf_coeff) = &params.vf_coeff {
+            builder = builder.with_vf_coeff_schedule(vf_coeff.build()?);
+        }
+        if let Some(target_kl) = &params.target_kl {
+            builder = builder.with_target_kl_schedule(target_kl.build()?);
+        }
+        builder
+    }};
+}
+
+macro_rules! configure_a2c {
+    ($params:expr, $builder:expr) => {{
+        let params: &crate::config::A2CConfig = $params;
+        let mut builder = configure_on_policy_params!(params, $builder);
+        if let Some(entropy_coeff) = params.entropy_coeff {
+            builder = builder.with_entropy_coeff(entropy_coeff);
+        }
+        if let Some(vf_coeff) = params.vf_coeff {
+            builder = builder.with_vf_coeff(Some(vf_coeff));
+        }
+        builder
+    }};
+}
+
+macro_rules! configure_vpg {
+    ($params:expr, $builder:expr) => {{
+        let params: &crate::config::VPGConfig = $params;
+        let mut builder = configure_on_policy_params!(params, $builder);
+        if let Some(baseline) = params.baseline {
+            builder = builder.with_baseline(baseline);
+        }
+        if let Some(entropy_coeff) = params.entropy_coeff {
+            builder = builder.with_entropy_coeff(entropy_coeff);
+        }
+        if let Some(vf_coeff) = params.vf_coeff {
+            builder = builder.with_vf_coeff(Some(vf_coeff));
+        }
+        builder
+    }};
+}
+
+/// Installs the rollout bound and sampler, then builds the algorithm.
+macro_rules! build_boxed {
+    ($config:expr, $builder:expr) => {{
+        let config: &ExperimentConfig = $config;
+        let builder = $builder;
+        let algorithm: Box<dyn Trainable> =
+            match (config.sampler.rollout_bound, config.sampler.normalized()) {
+                (RolloutBound::Steps { steps }, false) => Box::new(
+                    builder
+                        .with_rollout_bound(StepHookBound::new(steps))
+                        .build()?,
+                ),
+                (RolloutBound::Steps { steps }, true) => Box::new(
+                    normalize(
+                        config,
+                        builder.with_rollout_bound(StepHookBound::new(steps)),
+                    )
+                    .build()?,
+                ),
+                (RolloutBound::Episodes { episodes }, false) => Box::new(
+                    builder
+                        .with_rollout_bound(EpisodeHookBound::new(episodes))
+                        .build()?,
+                ),
+                (RolloutBound::Episodes { episodes }, true) => Box::new(
+                    normalize(
+                        config,
+                        builder.with_rollout_bound(EpisodeHookBound::new(episodes)),
+                    )
+                    .build()?,
+                ),
+            };
+        algorithm
+    }};
+}
+
+impl From<&OptimizerConfig> for OptimizerSettings {
+    /// Writes every parameter the settings can hold. RMSprop's `alpha`,
+    /// `centered` and `tf_like` and SGD's `nesterov` have no setting.
+    fn from(optimizer: &OptimizerConfig) -> Self {
+        match *optimizer {
+            OptimizerConfig::Adam {
+                lr,
+                beta1,
+                beta2,
+                eps,
+                weight_decay,
+            }
+            | OptimizerConfig::AdamW {
+                lr,
+                beta1,
+                beta2,
+                eps,
+                weight_decay,
+            } => Self {
+                kind: if matches!(optimizer, OptimizerConfig::Adam { .. }) {
+                    OptimizerKind::Adam
+                } else {
+                    OptimizerKind::AdamW
+                },
+                lr,
+                beta1: Some(beta1),
+                beta2: Some(beta2),
+                eps: Some(eps),
+                weight_decay: Some(weight_decay),
+                momentum: None,
+            },
+            OptimizerConfig::RmsProp {
+                lr,
+                eps,
+                momentum,
+                weight_decay,
+                ..
+            } => Self {
+                kind: OptimizerKind::RmsProp,
+                lr,
+                beta1: None,
+                beta2: None,
+                eps: Some(eps),
+                weight_decay: Some(weight_decay),
+                momentum: Some(momentum),
+            },
+            OptimizerConfig::Sgd {
+                lr,
+                momentum,
+                weight_decay,
+                ..
+            } => Self {
+                kind: OptimizerKind::Sgd,
+                lr,
+                beta1: None,
+                beta2: None,
+                eps: None,
+                weight_decay: Some(weight_decay),
+                momentum: Some(momentum),
+            },
+        }
+    }
+}
+
+// Goes through the shortest decimal form, so that a default of `0.2` is not
+// written as `0.20000000298023224`.
+fn constant(value: f32) -> ScheduleConfig {
+    ScheduleConfig::Constant(value.to_string().parse().unwrap_or(value.into()))
+}
+
+impl BackendConfig {
+    /// Returns the Candle device, or `None` for the Burn backend.
+    fn candle_device(&self) -> Result<Option<Device>> {
+        match self {
+            Self::Candle => Ok(Some(Device::Cpu)),
+            Self::CandleCuda { candle_cuda } => Ok(Some(Device::new_cuda(*candle_cuda)?)),
+            Self::Burn => Ok(None),
+        }
+    }
+}
+
+impl ExperimentConfig {
+    /// Returns the configuration with the defaults of the algorithm builders
+    /// filled in, so that it records every value a run uses.
+    ///
+    /// Values whose absence disables a feature, such as `target_kl` or
+    /// `max_grad_norm`, stay unset, as do the seed and the evaluator paths.
+    pub fn resolved(&self) -> Result<Self> {
+        let mut config = self.clone();
+        let n_envs = config.sampler.n_envs;
+        let learning_module = match &mut config.algorithm {
+            AlgorithmConfig::Ppo(params) => {
+                let defaults = PPOAgentBuilder::new(n_envs);
+                let hook = &defaults.hook_builder;
+                params.gamma.get_or_insert(defaults.params.gamma);
+                params.lambda.get_or_insert(defaults.params.lambda);
+                params
+                    .sample_size
+                    .get_or_insert(defaults.params.sample_size);
+                params.total_epochs.get_or_insert(hook.total_epochs);
+                params
+                    .normalize_advantage
+                    .get_or_insert(hook.normalize_advantage);
+                params
+                    .clip_range
+                    .get_or_insert_with(|| constant(defaults.params.clip_range));
+                if params.clip_range_vf.is_none() {
+                    params.clip_range_vf = defaults.params.clip_range_vf.map(constant);
+                }
+                params
+                    .entropy_coeff
+                    .get_or_insert_with(|| constant(hook.entropy_coeff));
+                if params.vf_coeff.is_none() {
+                    params.vf_coeff = hook.vf_coeff.map(constant);
+                }
+                if params.target_kl.is_none() {
+                    params.target_kl = hook.target_kl.map(constant);
+                }
+                params.max_grad_norm = params.max_grad_norm.or(hook.gradient_clipping);
+                params.log_progress.get_or_insert(hook.log_progress);
+                defaults.learning_module_builder
+            }
+            AlgorithmConfig::A2c(params) => {
+                let defaults = A2CAgentBuilder::new(n_envs);
+                let hook = &defaults.hook_builder;
+                params.gamma.get_or_insert(defaults.params.gamma);
+                params.lambda.get_or_insert(defaults.params.lambda);
+                params
+                    .sample_size
+                    .get_or_insert(defaults.params.sample_size);
+                params
+                    .normalize_advantage
+                    .get_or_insert(hook.normalize_advantage);
+                params.entropy_coeff.get_or_insert(hook.entropy_coeff);
+                params.vf_coeff = params.vf_coeff.or(hook.vf_coeff);
+                params.max_grad_norm = params.max_grad_norm.or(hook.gradient_clipping);
+                params.log_progress.get_or_insert(hook.log_progress);
+                defaults.learning_module_builder
+            }
+            AlgorithmConfig::Vpg(params) => {
+                let defaults = VPGAgentBuilder::new(n_envs);
+                let hook = &defaults.hook_builder;
+                params.gamma.get_or_insert(defaults.params.gamma);
+                params.lambda.get_or_insert(defaults.params.lambda);
+                params
+                    .sample_size
+                    .get_or_insert(defaults.params.sample_size);
+                params.baseline.get_or_insert(defaults.params.baseline);
+                params
+                    .normalize_advantage
+                    .get_or_insert(hook.normalize_advantage);
+                params.entropy_coeff.get_or_insert(hook.entropy_coeff);
+                params.vf_coeff = params.vf_coeff.or(hook.vf_coeff);
+                params.max_grad_norm = params.max_grad_norm.or(hook.gradient_clipping);
+                params.log_progress.get_or_insert(hook.log_progress);
+                defaults.learning_module_builder
+            }
+        };
+
+        let network = &mut config.network;
+        network
+            .shared_hidden_layers
+            .get_or_insert(learning_module.shared_hidden_layers);
+        network
+            .policy_hidden_layers
+            .get_or_insert(learning_module.policy_hidden_layers);
+        network
+            .value_hidden_layers
+            .get_or_insert(learning_module.value_hidden_layers);
+        network
+            .activation
+            .get_or_insert(learning_module.activation_function);
+        network
+            .log_std_init
+            .get_or_insert(learning_module.log_std_init);
+        network
+            .weight_init
+            .get_or_insert(learning_module.weight_init);
+        let optimizer = match (&config.optimizer, learning_module.learning_module_type) {
+            (Some(settings), _) => settings.try_into()?,
+            (None, OnPolicyLearningModuleType::Joint { optimizer, .. }) => optimizer,
+            (
+                None,
+                OnPolicyLearningModuleType::Split {
+                    policy_optimizer, ..
+                },
+            ) => policy_optimizer,
+        };
+        config.optimizer = Some(OptimizerSettings::from(&optimizer));
+        if let Some(settings) = &config.value_optimizer {
+            let optimizer = OptimizerConfig::try_from(settings)?;
+            config.value_optimizer = Some(OptimizerSettings::from(&optimizer));
+        }
+
+        config
+            .sampler
+            .execution_mode
+            .get_or_insert(sampler::DEFAULT_EXECUTION_MODE);
+        config
+            .training
+            .budget
+            .get_or_insert(TrainingBudget::Rollouts {
+                rollouts: DEFAULT_TOTAL_ROLLOUTS,
+            });
+        if let Some(evaluator) = &mut config.evaluator {
+            evaluator
+                .n_episodes
+                .get_or_insert(best_actor_evaluator::DEFAULT_N_EPISODES);
+            evaluator
+                .frequency
+                .get_or_insert(best_actor_evaluator::DEFAULT_FREQUENCY);
+            evaluator
+                .execution_mode
+                .get_or_insert(best_actor_evaluator::DEFAULT_EXECUTION_MODE);
+        }
+        Ok(config)
+    }
+
+    /// Builds the configured algorithm on a Gymnasium environment.
+    ///
+    /// Native environments need [`build_with_registry`](Self::build_with_registry).
+    pub fn build(&self) -> Result<Box<dyn Trainable>> {
+        if let EnvConfig::Native { name } = &self.env {
+            bail!("native environment `{name}` needs an EnvRegistry, use build_with_registry");
+        }
+        self.build_with_env(self.env.gym_env_builder()?)
+    }
+
+    /// Builds the configured algorithm on a native environment looked up in
+    /// `registry`.
+    pub fn build_with_registry<EB: EnvBuilder<Env: 'static> + Clone>(
+        &self,
+        registry: &EnvRegistry<EB>,
+    ) -> Result<Box<dyn Trainable>> {
+        let EnvConfig::Native { name } = &self.env else {
+            bail!("build_with_registry needs a native environment, use build for gym environments");
+        };
+        let env_builder = registry
+            .get(name)
+            .ok_or_else(|| anyhow!("no environment registered under `{name}`"))?;
+        self.build_with_env(env_builder.clone())
+    }
+
+    /// Builds the configured algorithm on `env_builder`, ignoring the `env`
+    /// section.
+    ///
+    /// When `run_dir` is set, the [`resolved`](Self::resolved) configuration
+    /// is saved to `run_dir/experiment.toml` first, and metrics are logged to
+    /// `run_dir/metrics.csv` and `run_dir/tensorboard`.
+    pub fn build_with_env<EB: EnvBuilder<Env: 'static>>(
+        &self,
+        env_builder: EB,
+    ) -> Result<Box<dyn Trainable>> {
+        if let Some(run_dir) = &self.run_dir {
+            fs::create_dir_all(run_dir)
+                .with_context(|| format!("failed to create run directory {}", run_dir.display()))?;
+            self.resolved()?.save(run_dir.join("experiment.toml"))?;
+        }
+        let n_envs = self.sampler.n_envs;
+        let algorithm = match &self.algorithm {
+            AlgorithmConfig::Ppo(params) => {
+                let builder = configure(self, PPOAlgorithmBuilder::new(env_builder, n_envs))?;
+                match self.backend.candle_device()? {
+                    Some(device) => build_boxed!(
+                        self,
+                        configure_ppo!(
+                            params,
+                            configure_network!(self, builder.with_candle(device))
+                        )
+                    ),
+                    None => build_boxed!(
+                        self,
+                        configure_ppo!(params, configure_network!(self, builder.with_burn()))
+                    ),
+                }
+            }
+            AlgorithmConfig::A2c(params) => {
+                let builder = configure(self, A2CAlgorithmBuilder::new(env_builder, n_envs))?;
+                match self.backend.candle_device()? {
+                    Some(device) => build_boxed!(
+                        self,
+                        configure_a2c!(
+                            params,
+                            configure_network!(self, builder.with_candle(device))
+                        )
+                    ),
+                    None => build_boxed!(
+                        self,
+                        configure_a2c!(params, configure_network!(self, builder.with_burn()))
+                    ),
+                }
+            }
+            AlgorithmConfig::Vpg(params) => {
+                let builder = configure(self, VPGAlgorithmBuilder::new(env_builder, n_envs))?;
+                match self.backend.candle_device()? {
+                    Some(device) => build_boxed!(
+                        self,
+                        configure_vpg!(
+                            params,
+                            configure_network!(self, builder.with_candle(device))
+                        )
+                    ),
+                    None => build_boxed!(
+                        self,
+                        configure_vpg!(params, configure_network!(self, builder.with_burn()))
+                    ),
+                }
+            }
+        };
+        Ok(algorithm)
+    }
+}
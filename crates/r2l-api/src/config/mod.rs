@@ -0,0 +1,847 @@
+mod build;
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use r2l_core::{
+    init::WeightInit,
+    models::{ActivationFunction, OptimizerConfig},
+};
+use r2l_gym::{GymEnvBuilder, GymValue, GymWrapper};
+use r2l_sampler::SamplerExecutionMode;
+use serde::{Deserialize, Serialize};
+
+use crate::{LearningSchedule, RewardNormalizerParams, Schedule};
+
+pub use build::{BEST_POLICY_FILE, EnvRegistry, Trainable};
+
+/// Full description of an on-policy training run.
+///
+/// ```toml
+/// seed = 42
+/// backend = "burn"
+///
+/// [env]
+/// type = "gym"
+/// id = "Pendulum-v1"
+///
+/// [sampler]
+/// n_envs = 4
+/// rollout_bound = { steps = 1024 }
+/// obs_clip = 10.0
+///
+/// [training]
+/// budget = { total_steps = 1000000 }
+/// learning_rate = { type = "linear", start = 3e-4, end = 0.0 }
+///
+/// [algorithm]
+/// name = "ppo"
+/// gamma = 0.99
+/// clip_range = 0.2
+///
+/// [network]
+/// policy_hidden_layers = [64, 64]
+///
+/// [optimizer]
+/// type = "adam"
+/// lr = 3e-4
+/// ```
+///
+/// Unset optional values keep the defaults of the algorithm builders;
+/// [`resolved`](Self::resolved) writes those defaults into the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentConfig {
+    /// Seed used by r2l, Gym reset seeds, and backend-specific RNGs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Backend the agent is built on.
+    #[serde(default)]
+    pub backend: BackendConfig,
+    /// Directory the effective configuration, the metrics and the best policy
+    /// are written to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_dir: Option<PathBuf>,
+    /// Training environment.
+    pub env: EnvConfig,
+    /// Rollout collection.
+    pub sampler: SamplerConfig,
+    /// Training duration and learning-rate schedule.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub training: TrainingConfig,
+    /// Algorithm and its hyperparameters.
+    pub algorithm: AlgorithmConfig,
+    /// Policy and value networks.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub network: NetworkConfig,
+    /// Optimizer of both networks, or of the policy network when
+    /// `value_optimizer` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub optimizer: Option<OptimizerSettings>,
+    /// Separate optimizer of the value network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_optimizer: Option<OptimizerSettings>,
+    /// Best-actor evaluation during training.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evaluator: Option<EvaluatorConfig>,
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// File format of a configuration, chosen by file extension.
+pub(crate) enum ConfigFormat {
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub(crate) fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Ok(Self::Toml),
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            _ => bail!(
+                "unsupported experiment config format: {}, expected a .toml, .yaml or .yml file",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl ExperimentConfig {
+    /// Reads a configuration from a `.toml`, `.yaml` or `.yml` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read experiment config {}", path.display()))?;
+        let config = match ConfigFormat::from_path(path)? {
+            ConfigFormat::Toml => Self::from_toml_str(&content),
+            ConfigFormat::Yaml => Self::from_yaml_str(&content),
+        };
+        config.with_context(|| format!("invalid experiment config {}", path.display()))
+    }
+
+    /// Parses a TOML configuration.
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Parses a YAML configuration.
+    pub fn from_yaml_str(content: &str) -> Result<Self> {
+        Ok(yaml_serde::from_str(content)?)
+    }
+
+    /// Serializes the configuration as TOML.
+    pub fn to_toml_string(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// Serializes the configuration as YAML.
+    pub fn to_yaml_string(&self) -> Result<String> {
+        Ok(yaml_serde::to_string(self)?)
+    }
+
+    /// Writes the configuration to a `.toml`, `.yaml` or `.yml` file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let content = match ConfigFormat::from_path(path)? {
+            ConfigFormat::Toml => self.to_toml_string()?,
+            ConfigFormat::Yaml => self.to_yaml_string()?,
+        };
+        fs::write(path, content)
+            .with_context(|| format!("failed to write experiment config {}", path.display()))
+    }
+
+    /// Overrides one value, addressed by its dotted path such as
+    /// `algorithm.gamma` or `sampler.rollout_bound.steps`.
+    ///
+    /// `value` is parsed as YAML, so `0.98`, `[64, 64]` and
+    /// `{ type: linear, start: 3e-4, end: 0 }` keep their types and anything
+    /// else is a string. Missing tables along the path are created.
+    pub fn with_override(self, key: &str, value: &str) -> Result<Self> {
+        let value: yaml_serde::Value = yaml_serde::from_str(value)
+            .with_context(|| format!("invalid value for `{key}`: {value}"))?;
+        let mut config = yaml_serde::to_value(&self)?;
+        let mut table = &mut config;
+        let mut segments = key.split('.').peekable();
+        while let Some(segment) = segments.next() {
+            if segment.is_empty() {
+                bail!("invalid override key `{key}`");
+            }
+            if table.is_null() {
+                *table = yaml_serde::Value::Mapping(yaml_serde::Mapping::new());
+            }
+            let Some(mapping) = table.as_mapping_mut() else {
+                bail!("cannot override `{key}`, `{segment}` is not inside a table");
+            };
+            let segment = yaml_serde::Value::String(segment.to_owned());
+            if segments.peek().is_none() {
+                mapping.insert(segment, value);
+                break;
+            }
+            table = mapping.entry(segment).or_insert(yaml_serde::Value::Null);
+        }
+        yaml_serde::from_value(config).with_context(|| format!("invalid override `{key}`"))
+    }
+}
+
+/// Training environment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum EnvConfig {
+    /// A Gymnasium environment created from its id.
+    Gym {
+        /// Gymnasium environment id, such as `Pendulum-v1`.
+        id: String,
+        /// Keyword arguments passed to `gymnasium.make`.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        kwargs: BTreeMap<String, EnvKwarg>,
+        /// Step limit passed to `gymnasium.make`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_episode_steps: Option<usize>,
+        /// Python wrappers applied in order after `gymnasium.make`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        wrappers: Vec<GymWrapperConfig>,
+    },
+    /// A native environment looked up by name in an [`EnvRegistry`].
+    Native {
+        /// Name the environment builder was registered under.
+        name: String,
+    },
+}
+
+impl EnvConfig {
+    /// Builds the Gymnasium environment builder of a `gym` environment.
+    pub fn gym_env_builder(&self) -> Result<GymEnvBuilder> {
+        let Self::Gym {
+            id,
+            kwargs,
+            max_episode_steps,
+            wrappers,
+        } = self
+        else {
+            bail!("expected a gym environment, found {self:?}");
+        };
+        let mut builder = GymEnvBuilder::new(id).with_kwargs(
+            kwargs
+                .iter()
+                .map(|(key, value)| (key, GymValue::from(value))),
+        );
+        if let Some(max_episode_steps) = max_episode_steps {
+            builder = builder.with_max_episode_steps(*max_episode_steps);
+        }
+        for wrapper in wrappers {
+            builder = builder.with_python_wrapper(GymWrapper::from(wrapper));
+        }
+        Ok(builder)
+    }
+}
+
+/// Keyword argument of a Gymnasium environment or wrapper.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnvKwarg {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    List(Vec<EnvKwarg>),
+    Dict(BTreeMap<String, EnvKwarg>),
+}
+
+impl From<&EnvKwarg> for GymValue {
+    fn from(value: &EnvKwarg) -> Self {
+        match value {
+            EnvKwarg::Bool(value) => Self::Bool(*value),
+            EnvKwarg::Int(value) => Self::Int(*value),
+            EnvKwarg::Float(value) => Self::Float(*value),
+            EnvKwarg::Str(value) => Self::Str(value.clone()),
+            EnvKwarg::List(values) => Self::List(values.iter().map(Self::from).collect()),
+            EnvKwarg::Dict(values) => Self::Dict(
+                values
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Self::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Python wrapper applied to a Gymnasium environment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GymWrapperConfig {
+    /// Import path of the wrapper class, such as
+    /// `gymnasium.wrappers.TimeAwareObservation`.
+    pub path: String,
+    /// Keyword arguments passed to the wrapper constructor.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub kwargs: BTreeMap<String, EnvKwarg>,
+}
+
+impl From<&GymWrapperConfig> for GymWrapper {
+    fn from(config: &GymWrapperConfig) -> Self {
+        config
+            .kwargs
+            .iter()
+            .fold(GymWrapper::new(&config.path), |wrapper, (key, value)| {
+                wrapper.with_kwarg(key, GymValue::from(value))
+            })
+    }
+}
+
+/// Rollout collection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SamplerConfig {
+    /// Number of training environments.
+    pub n_envs: usize,
+    /// When a rollout ends.
+    pub rollout_bound: RolloutBound,
+    /// How training environments are executed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<SamplerExecutionMode>,
+    /// Enables observation normalization, clipping normalized observations to
+    /// `±obs_clip`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obs_clip: Option<f32>,
+    /// Enables reward normalization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reward_normalizer: Option<RewardNormalizerParams>,
+}
+
+impl SamplerConfig {
+    /// Whether rollouts are collected by the normalized sampler.
+    pub fn normalized(&self) -> bool {
+        self.obs_clip.is_some() || self.reward_normalizer.is_some()
+    }
+}
+
+/// Length of a rollout, per environment, written as `{ steps = 1024 }` or
+/// `{ episodes = 4 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum RolloutBound {
+    /// A fixed number of environment steps.
+    Steps { steps: usize },
+    /// A fixed number of completed episodes.
+    Episodes { episodes: usize },
+}
+
+/// Training duration and learning-rate schedule.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrainingConfig {
+    /// When training stops.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<TrainingBudget>,
+    /// Learning rate of every optimizer over the training duration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub learning_rate: Option<ScheduleConfig>,
+}
+
+/// When training stops, written as `{ total_steps = 1000000 }` or
+/// `{ rollouts = 300 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum TrainingBudget {
+    /// After at least this many sampled environment steps.
+    TotalSteps { total_steps: usize },
+    /// After this many rollouts.
+    Rollouts { rollouts: usize },
+}
+
+impl From<TrainingBudget> for LearningSchedule {
+    fn from(budget: TrainingBudget) -> Self {
+        match budget {
+            TrainingBudget::TotalSteps { total_steps } => Self::total_step_bound(total_steps),
+            TrainingBudget::Rollouts { rollouts } => Self::rollout_bound(rollouts),
+        }
+    }
+}
+
+/// Serializable form of a [`Schedule`].
+///
+/// A plain number is a constant schedule; the other shapes are tables such as
+/// `{ type = "linear", start = 3e-4, end = 0.0 }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScheduleConfig {
+    /// See [`Schedule::constant`].
+    Constant(f64),
+    /// A schedule that changes over training.
+    Shaped(ScheduleShape),
+}
+
+/// Shape of a [`ScheduleConfig`] that changes over training, selected by
+/// `type`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ScheduleShape {
+    /// See [`Schedule::linear`].
+    Linear { start: f64, end: f64 },
+    /// See [`Schedule::cosine`].
+    Cosine { start: f64, end: f64 },
+    /// See [`Schedule::exponential`].
+    Exponential { start: f64, decay_rate: f64 },
+    /// `(progress_remaining, value)` breakpoints, see [`Schedule::piecewise`].
+    Piecewise { points: Vec<(f64, f64)> },
+    /// See [`Schedule::warmup_decay`].
+    WarmupDecay {
+        warmup_fraction: f64,
+        peak: f64,
+        end: f64,
+    },
+}
+
+impl ScheduleConfig {
+    /// Builds the schedule, failing on an empty piecewise schedule.
+    pub fn build(&self) -> Result<Schedule> {
+        let shape = match self {
+            Self::Constant(value) => return Ok(Schedule::constant(*value)),
+            Self::Shaped(shape) => shape,
+        };
+        let schedule = match shape {
+            ScheduleShape::Linear { start, end } => Schedule::linear(*start, *end),
+            ScheduleShape::Cosine { start, end } => Schedule::cosine(*start, *end),
+            ScheduleShape::Exponential { start, decay_rate } => {
+                Schedule::exponential(*start, *decay_rate)
+            }
+            ScheduleShape::Piecewise { points } => Schedule::piecewise(points.clone())?,
+            ScheduleShape::WarmupDecay {
+                warmup_fraction,
+                peak,
+                end,
+            } => Schedule::warmup_decay(*warmup_fraction, *peak, *end),
+        };
+        Ok(schedule)
+    }
+}
+
+/// Algorithm and its hyperparameters, selected by `name`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum AlgorithmConfig {
+    Ppo(PPOConfig),
+    A2c(A2CConfig),
+    Vpg(VPGConfig),
+}
+
+/// PPO hyperparameters. Coefficients and clip ranges accept schedules.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PPOConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gamma: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lambda: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_epochs: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize_advantage: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clip_range: Option<ScheduleConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clip_range_vf: Option<ScheduleConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entropy_coeff: Option<ScheduleConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vf_coeff: Option<ScheduleConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_kl: Option<ScheduleConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_grad_norm: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_progress: Option<bool>,
+}
+
+/// A2C hyperparameters.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct A2CConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gamma: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lambda: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize_advantage: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entropy_coeff: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vf_coeff: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_grad_norm: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_progress: Option<bool>,
+}
+
+/// VPG hyperparameters. Disabling `baseline` gives REINFORCE.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VPGConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gamma: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lambda: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize_advantage: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entropy_coeff: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vf_coeff: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_grad_norm: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_progress: Option<bool>,
+}
+
+/// Policy and value networks.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Hidden layers of a trunk shared by the policy and value networks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shared_hidden_layers: Option<Vec<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_hidden_layers: Option<Vec<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_hidden_layers: Option<Vec<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activation: Option<ActivationFunction>,
+    /// Initial log standard deviation of Gaussian policies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_std_init: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight_init: Option<WeightInit>,
+}
+
+/// Optimizer type of an [`OptimizerSettings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptimizerKind {
+    Adam,
+    #[serde(rename = "adamw")]
+    AdamW,
+    RmsProp,
+    Sgd,
+}
+
+/// Optimizer with PyTorch's defaults for every unset parameter.
+///
+/// Setting a parameter the optimizer does not have, such as `beta1` on SGD,
+/// is an error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OptimizerSettings {
+    #[serde(rename = "type")]
+    pub kind: OptimizerKind,
+    pub lr: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub beta1: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub beta2: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eps: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_decay: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub momentum: Option<f64>,
+}
+
+impl TryFrom<&OptimizerSettings> for OptimizerConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(settings: &OptimizerSettings) -> Result<Self> {
+        let mut optimizer = match settings.kind {
+            OptimizerKind::Adam => Self::adam(settings.lr),
+            OptimizerKind::AdamW => Self::adamw(settings.lr),
+            OptimizerKind::RmsProp => Self::rms_prop(settings.lr),
+            OptimizerKind::Sgd => Self::sgd(settings.lr),
+        };
+        if let Some(beta1) = settings.beta1 {
+            optimizer = optimizer.with_beta1(beta1)?;
+        }
+        if let Some(beta2) = settings.beta2 {
+            optimizer = optimizer.with_beta2(beta2)?;
+        }
+        if let Some(eps) = settings.eps {
+            optimizer = optimizer.with_epsilon(eps)?;
+        }
+        if let Some(weight_decay) = settings.weight_decay {
+            optimizer = optimizer.with_weight_decay(weight_decay);
+        }
+        if let Some(momentum) = settings.momentum {
+            optimizer = optimizer.with_momentum(momentum)?;
+        }
+        Ok(optimizer)
+    }
+}
+
+/// Backend the agent is built on: `"candle"`, `"burn"`, or
+/// `{ candle_cuda = 0 }` for Candle on a CUDA device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendConfig {
+    /// Candle on the CPU.
+    #[default]
+    Candle,
+    /// Burn on the `NdArray` backend.
+    Burn,
+    /// Candle on the CUDA device with the given ordinal.
+    #[serde(untagged)]
+    CandleCuda { candle_cuda: usize },
+}
+
+/// Best-actor evaluation during training.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvaluatorConfig {
+    /// Evaluation episodes per environment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n_episodes: Option<usize>,
+    /// Rollouts between evaluations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_mode: Option<SamplerExecutionMode>,
+    /// Where the best-performing actor is saved, `run_dir/best_policy.safetensors`
+    /// by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_actor_path: Option<PathBuf>,
+    /// Where evaluation states are saved as CSV.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub csv_states: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod test {
+    use r2l_core::models::OptimizerConfig;
+    use r2l_gym::{GymEnvBuilder, GymValue, GymWrapper};
+
+    use super::{
+        AlgorithmConfig, BackendConfig, EnvConfig, ExperimentConfig, OptimizerKind,
+        OptimizerSettings, RolloutBound, ScheduleConfig, ScheduleShape,
+    };
+
+    const CONFIG: &str = r#"
+seed = 7
+backend = "burn"
+
+[env]
+type = "gym"
+id = "Pendulum-v1"
+kwargs = { g = 9.81 }
+
+[sampler]
+n_envs = 4
+rollout_bound = { steps = 256 }
+execution_mode = "thread"
+reward_normalizer = { gamma = 0.99, clip_reward = 10.0 }
+
+[training]
+budget = { total_steps = 100000 }
+learning_rate = { type = "linear", start = 3e-4, end = 0.0 }
+
+[algorithm]
+name = "ppo"
+gamma = 0.98
+clip_range = 0.2
+entropy_coeff = { type = "piecewise", points = [[1.0, 0.01], [0.0, 0.0]] }
+
+[network]
+policy_hidden_layers = [64, 64]
+activation = "relu"
+
+[optimizer]
+type = "adam"
+lr = 3e-4
+eps = 1e-5
+"#;
+
+    #[test]
+    fn toml_and_yaml_round_trip() {
+        let config = ExperimentConfig::from_toml_str(CONFIG).unwrap();
+        assert_eq!(config.backend, BackendConfig::Burn);
+        assert_eq!(
+            config.sampler.rollout_bound,
+            RolloutBound::Steps { steps: 256 }
+        );
+        assert!(config.sampler.normalized());
+        let AlgorithmConfig::Ppo(ppo) = &config.algorithm else {
+            panic!("expected a PPO config");
+        };
+        assert_eq!(ppo.clip_range, Some(ScheduleConfig::Constant(0.2)));
+
+        let toml = config.to_toml_string().unwrap();
+        assert_eq!(ExperimentConfig::from_toml_str(&toml).unwrap(), config);
+        let yaml = config.to_yaml_string().unwrap();
+        assert_eq!(ExperimentConfig::from_yaml_str(&yaml).unwrap(), config);
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let config = CONFIG.replace("gamma = 0.98", "gamma = 0.98\nclip_rnage = 0.1");
+        assert!(ExperimentConfig::from_toml_str(&config).is_err());
+    }
+
+    #[test]
+    fn overrides_replace_nested_values() {
+        let config = ExperimentConfig::from_toml_str(CONFIG)
+            .unwrap()
+            .with_override("algorithm.gamma", "0.9")
+            .unwrap()
+            .with_override("sampler.rollout_bound.steps", "512")
+            .unwrap()
+            .with_override("network.value_hidden_layers", "[32, 32]")
+            .unwrap()
+            .with_override("evaluator.n_episodes", "3")
+            .unwrap()
+            .with_override("env.id", "Acrobot-v1")
+            .unwrap();
+        let AlgorithmConfig::Ppo(params) = &config.algorithm else {
+            panic!("expected a PPO config");
+        };
+        assert_eq!(params.gamma, Some(0.9));
+        assert_eq!(
+            config.sampler.rollout_bound,
+            RolloutBound::Steps { steps: 512 }
+        );
+        assert_eq!(config.network.value_hidden_layers, Some(vec![32, 32]));
+        assert_eq!(config.evaluator.unwrap().n_episodes, Some(3));
+        assert!(matches!(config.env, EnvConfig::Gym { ref id, .. } if id == "Acrobot-v1"));
+
+        let config = ExperimentConfig::from_toml_str(CONFIG).unwrap();
+        assert!(
+            config
+                .clone()
+                .with_override("algorithm.gamma", "high")
+                .is_err()
+        );
+        assert!(config.clone().with_override("seed.value", "1").is_err());
+        assert!(config.with_override("algorithm.clip_rnage", "0.1").is_err());
+    }
+
+    #[test]
+    fn resolving_fills_builder_defaults() {
+        let config = ExperimentConfig::from_toml_str(CONFIG).unwrap();
+        let resolved = config.resolved().unwrap();
+        let AlgorithmConfig::Ppo(params) = &resolved.algorithm else {
+            panic!("expected a PPO config");
+        };
+        assert_eq!(params.gamma, Some(0.98));
+        assert_eq!(params.total_epochs, Some(10));
+        assert_eq!(params.clip_range, Some(ScheduleConfig::Constant(0.2)));
+        assert!(matches!(
+            params.entropy_coeff,
+            Some(ScheduleConfig::Shaped(ScheduleShape::Piecewise { .. }))
+        ));
+        assert!(params.lambda.is_some() && params.sample_size.is_some());
+        assert_eq!(resolved.network.policy_hidden_layers, Some(vec![64, 64]));
+        assert_eq!(resolved.network.value_hidden_layers, Some(vec![64, 64]));
+        assert_eq!(resolved.network.activation, config.network.activation);
+        let optimizer = resolved.optimizer.as_ref().unwrap();
+        assert_eq!(optimizer.eps, Some(1e-5));
+        assert_eq!(optimizer.beta1, Some(0.9));
+        assert!(resolved.value_optimizer.is_none());
+        assert!(resolved.evaluator.is_none());
+        assert_eq!(resolved.seed, Some(7));
+
+        assert_eq!(resolved.resolved().unwrap(), resolved);
+        let toml = resolved.to_toml_string().unwrap();
+        assert_eq!(ExperimentConfig::from_toml_str(&toml).unwrap(), resolved);
+    }
+
+    #[test]
+    fn gym_kwargs_and_wrappers_reach_the_env_builder() {
+        let env: EnvConfig = toml::from_str(
+            r#"
+type = "gym"
+id = "CarRacing-v3"
+kwargs = { continuous = false, lap_complete_percent = 0.9, domain = { seed = 3 } }
+max_episode_steps = 500
+
+[[wrappers]]
+path = "rl_zoo3.wrappers.FrameSkip"
+kwargs = { skip = 2 }
+
+[[wrappers]]
+path = "rl_zoo3.wrappers.YAMLCompatResizeObservation"
+kwargs = { shape = [64, 64] }
+
+[[wrappers]]
+path = "gymnasium.wrappers.ClipAction"
+"#,
+        )
+        .unwrap();
+        let expected = GymEnvBuilder::new("CarRacing-v3")
+            .with_kwarg("continuous", false)
+            .with_kwarg("lap_complete_percent", 0.9)
+            .with_kwarg(
+                "domain",
+                GymValue::Dict(
+                    [("seed".to_owned(), GymValue::Int(3))]
+                        .into_iter()
+                        .collect(),
+                ),
+            )
+            .with_max_episode_steps(500)
+            .with_python_wrapper(
+                GymWrapper::new("rl_zoo3.wrappers.FrameSkip").with_kwarg("skip", 2),
+            )
+            .with_python_wrapper(
+                GymWrapper::new("rl_zoo3.wrappers.YAMLCompatResizeObservation")
+                    .with_kwarg("shape", vec![64, 64]),
+            )
+            .with_python_wrapper("gymnasium.wrappers.ClipAction");
+        assert_eq!(env.gym_env_builder().unwrap(), expected);
+        let native = EnvConfig::Native {
+            name: "line".into(),
+        };
+        assert!(native.gym_env_builder().is_err());
+    }
+
+    #[test]
+    fn optimizer_settings_reject_parameters_the_optimizer_lacks() {
+        let settings = OptimizerSettings {
+            kind: OptimizerKind::Sgd,
+            lr: 0.1,
+            beta1: None,
+            beta2: None,
+            eps: None,
+            weight_decay: None,
+            momentum: Some(0.9),
+        };
+        assert_eq!(
+            OptimizerConfig::try_from(&settings).unwrap(),
+            OptimizerConfig::Sgd {
+                lr: 0.1,
+                momentum: 0.9,
+                nesterov: false,
+                weight_decay: 0.,
+            }
+        );
+        let adam_with_momentum = OptimizerSettings {
+            kind: OptimizerKind::Adam,
+            ..settings.clone()
+        };
+        assert!(OptimizerConfig::try_from(&adam_with_momentum).is_err());
+        let sgd_with_epsilon = OptimizerSettings {
+            eps: Some(1e-5),
+            ..settings
+        };
+        assert!(OptimizerConfig::try_from(&sgd_with_epsilon).is_err());
+    }
+}
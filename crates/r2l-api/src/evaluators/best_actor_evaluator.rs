@@ -2,14 +2,23 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use r2l_core::{
-    buffers::TrajectoryBatch,
+    buffers::{TrajectoryBatch, buffer::TrajectoryView},
     env::{EnvBuilder, EnvBuilderType},
+    episode::{EpisodeStats, EpisodeTracker, mean_of},
     models::Actor,
     on_policy::algorithm::{Agent, OnPolicyAdapters, OnPolicyRuntime, Sampler},
+    tensor::R2lTensor,
 };
 use r2l_sampler::{R2lSampler, SamplerExecutionMode};
 
-use crate::hooks::sampler::EpisodeBoundHook;
+use crate::{hooks::sampler::EpisodeBoundHook, metrics::MetricsLogger};
+
+/// Evaluation episodes per environment when none is set.
+pub(crate) const DEFAULT_N_EPISODES: usize = 5;
+/// Rollouts between evaluations when none is set.
+pub(crate) const DEFAULT_FREQUENCY: usize = 1;
+/// Execution mode of the evaluation environments when none is set.
+pub(crate) const DEFAULT_EXECUTION_MODE: SamplerExecutionMode = SamplerExecutionMode::Thread;
 
 struct EvalState {
     avg_reward: f32,
@@ -25,6 +34,7 @@ pub struct BestActorEvaluatorBuilder<EB: EnvBuilder> {
     evaluator_frequency: usize,
     csv_states_path: Option<PathBuf>,
     eval_states: Vec<EvalState>,
+    logger: Option<MetricsLogger>,
 }
 
 impl<EB: EnvBuilder> BestActorEvaluatorBuilder<EB> {
@@ -32,25 +42,27 @@ impl<EB: EnvBuilder> BestActorEvaluatorBuilder<EB> {
     pub fn from_env_builder_type(env_builder: EnvBuilderType<EB>) -> Self {
         Self {
             env_builder,
-            evaluator_frequency: 1,
-            n_episodes: 5,
-            execution_mode: SamplerExecutionMode::Thread,
+            evaluator_frequency: DEFAULT_FREQUENCY,
+            n_episodes: DEFAULT_N_EPISODES,
+            execution_mode: DEFAULT_EXECUTION_MODE,
             eval_path: None,
             csv_states_path: None,
             eval_states: vec![],
+            logger: None,
         }
     }
 
     /// Creates an evaluator builder from a homogeneous environment builder.
     pub fn new(env_builder: EB) -> Self {
         Self {
-            evaluator_frequency: 1,
+            evaluator_frequency: DEFAULT_FREQUENCY,
             env_builder: EnvBuilderType::homogenous(env_builder, 10),
-            n_episodes: 5,
-            execution_mode: SamplerExecutionMode::Thread,
+            n_episodes: DEFAULT_N_EPISODES,
+            execution_mode: DEFAULT_EXECUTION_MODE,
             eval_path: None,
             csv_states_path: None,
             eval_states: vec![],
+            logger: None,
         }
     }
 
@@ -92,6 +104,12 @@ impl<EB: EnvBuilder> BestActorEvaluatorBuilder<EB> {
         self
     }
 
+    /// Logs the results of every evaluation pass to `logger`.
+    pub fn with_metrics_logger(mut self, logger: MetricsLogger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
     /// Builds a best-actor evaluator for the requested actor type.
     pub fn build<A: Actor>(
         self,
@@ -108,8 +126,10 @@ impl<EB: EnvBuilder> BestActorEvaluatorBuilder<EB> {
             best_actor_path: self.eval_path,
             best_rewards: f32::MIN,
             best_actor: None,
+            last_mean_reward: None,
             csv_states_path: self.csv_states_path,
             eval_states: self.eval_states,
+            logger: self.logger,
         }
     }
 
@@ -122,8 +142,10 @@ impl<EB: EnvBuilder> BestActorEvaluatorBuilder<EB> {
             best_actor_path: self.eval_path,
             best_rewards: f32::MIN,
             best_actor: None,
+            last_mean_reward: None,
             csv_states_path: self.csv_states_path,
             eval_states: self.eval_states,
+            logger: self.logger,
         }
     }
 
@@ -154,20 +176,36 @@ fn assert_file_path_is_valid(path: PathBuf) -> PathBuf {
     path
 }
 
+// Fallback for samplers that do not publish episode statistics.
+fn completed_episodes<T: R2lTensor>(trajectories: &[TrajectoryView<'_, T>]) -> Vec<EpisodeStats> {
+    let mut episodes = vec![];
+    for trajectory in trajectories {
+        let mut tracker = EpisodeTracker::new();
+        for (reward, done) in trajectory.rewards().iter().zip(trajectory.dones()) {
+            if let Some(stats) = tracker.record(*reward, done) {
+                episodes.push(stats);
+            }
+        }
+    }
+    episodes
+}
+
 /// Evaluates an actor through the sampler path and keeps the best one seen.
 ///
 /// This evaluator collects episode-bounded rollouts with [`R2lSampler`],
-/// computes the average completed-episode reward, and retains the best actor
-/// observed so far.
+/// computes the average raw return of the completed episodes reported by the
+/// sampler, and retains the best actor observed so far.
 pub struct BestActorEvaluator<A: Actor, S: Sampler> {
     sampler: S,
     best_actor_path: Option<PathBuf>,
     best_actor: Option<A>,
     best_rewards: f32,
+    last_mean_reward: Option<f32>,
     current_evaluator_step: usize,
     evaluator_frequency: usize,
     csv_states_path: Option<PathBuf>,
     eval_states: Vec<EvalState>,
+    logger: Option<MetricsLogger>,
 }
 
 impl<A: Actor, ES: Sampler> BestActorEvaluator<A, ES> {
@@ -178,16 +216,18 @@ impl<A: Actor, ES: Sampler> BestActorEvaluator<A, ES> {
     >(
         &mut self,
         rt: &mut OnPolicyRuntime<AG, TS, C>,
-    ) {
+    ) -> Result<()> {
         self.current_evaluator_step += 1;
+        self.last_mean_reward = None;
         if self
             .current_evaluator_step
             .is_multiple_of(self.evaluator_frequency)
         {
             let actor = rt.actor();
             let adapted_actor = rt.adapted_actor();
-            self.eval_adapted(adapted_actor, actor);
+            self.eval_adapted(adapted_actor, actor)?;
         }
+        Ok(())
     }
 
     /// Evaluates the actor and stores it if it outperforms the current best actor.
@@ -195,21 +235,19 @@ impl<A: Actor, ES: Sampler> BestActorEvaluator<A, ES> {
         &mut self,
         adapted_actor: impl Actor<Tensor = ES::Tensor> + Clone,
         actor: A,
-    ) {
-        self.sampler.reset_all_envs();
-        self.sampler.collect_rollouts(adapted_actor);
-        let trajectories = self.sampler.trajectory_views();
-        let total_reward: f32 = trajectories
-            .as_ref()
-            .iter()
-            .map(|x| x.rewards().iter().sum::<f32>())
-            .sum();
-        let total_episodes: f32 = trajectories
-            .as_ref()
-            .iter()
-            .map(|b| b.episode_terminations() as f32)
-            .sum();
-        let avg_reward = total_reward / total_episodes;
+    ) -> Result<()> {
+        self.sampler.reset_all_envs()?;
+        self.sampler.collect_rollouts(adapted_actor)?;
+        let episodes = match self.sampler.episode_monitor() {
+            Some(monitor) => monitor.rollout_episodes(),
+            None => completed_episodes(self.sampler.trajectory_views().as_ref()),
+        };
+        // partial episodes never reach the average
+        let Some(avg_reward) = mean_of(&episodes, |stats| stats.reward) else {
+            return Ok(());
+        };
+        let total_episodes = episodes.len() as f32;
+        self.last_mean_reward = Some(avg_reward);
         if avg_reward > self.best_rewards {
             self.best_rewards = avg_reward;
             self.best_actor = Some(actor);
@@ -220,6 +258,23 @@ impl<A: Actor, ES: Sampler> BestActorEvaluator<A, ES> {
                 total_episodes,
             });
         }
+        if let Some(logger) = &self.logger {
+            let rewards = episodes
+                .iter()
+                .map(|stats| stats.reward)
+                .collect::<Vec<_>>();
+            logger.log_scalar("eval/mean_reward", avg_reward as f64)?;
+            logger.log_scalar("eval/episodes", total_episodes as f64)?;
+            logger.log_histogram("eval/episode_rewards", &rewards)?;
+            logger.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Mean reward of the evaluation pass run by the latest [`eval`](Self::eval)
+    /// call, `None` when it skipped evaluation or no episode finished.
+    pub fn last_mean_reward(&self) -> Option<f32> {
+        self.last_mean_reward
     }
 
     /// Serializes the current best actor and writes eval stats next to it.
@@ -1,8 +1,10 @@
 use std::marker::PhantomData;
 
+use anyhow::Result;
 use r2l_core::{
     buffers::buffer::TrajectoryView,
     env::{Env, EnvBuilder, EnvBuilderType},
+    episode::EpisodeStats,
     models::Actor,
     on_policy::algorithm::{DefaultAdapter, OnPolicyAdapters, Sampler},
 };
@@ -63,6 +65,12 @@ where
     }
 }
 
+// Tensor type of the actor the adapter hands to the evaluation sampler.
+type SamplerTensor<E, A, AD> = <<AD as OnPolicyAdapters<
+    A,
+    R2lSampler<E, EpisodeBoundHook<E>>,
+>>::SamplerActor as Actor>::Tensor;
+
 impl<E: Env, A: Actor, AD: OnPolicyAdapters<A, R2lSampler<E, EpisodeBoundHook<E>>>>
     Evaluator<E, A, AD>
 {
@@ -70,11 +78,18 @@ impl<E: Env, A: Actor, AD: OnPolicyAdapters<A, R2lSampler<E, EpisodeBoundHook<E>
     pub fn eval(
         &mut self,
         actor: A,
-    ) -> impl AsRef<[TrajectoryView<'_, <<AD as OnPolicyAdapters<A, R2lSampler<E, EpisodeBoundHook<E>>>>::SamplerActor as Actor>::Tensor>]>
-    {
+    ) -> Result<impl AsRef<[TrajectoryView<'_, SamplerTensor<E, A, AD>>]>> {
         let adapted_actor = self.adapter.adapt_actor(actor);
-        self.sampler.reset_all_envs();
-        self.sampler.collect_rollouts(adapted_actor);
-        self.sampler.trajectory_views()
+        self.sampler.reset_all_envs()?;
+        self.sampler.collect_rollouts(adapted_actor)?;
+        Ok(self.sampler.trajectory_views())
+    }
+
+    /// Returns the episodes completed during the latest [`eval`](Self::eval) call.
+    pub fn episodes(&self) -> Vec<EpisodeStats> {
+        self.sampler
+            .episode_monitor()
+            .map(|monitor| monitor.rollout_episodes())
+            .unwrap_or_default()
     }
 }
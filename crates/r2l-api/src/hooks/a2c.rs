@@ -15,11 +15,18 @@ use r2l_candle::learning_module::{
     PolicyValueLosses as CandlePolicyValueLosses, PolicyValueModule as CandlePolicyValueModule,
 };
 use r2l_core::{
-    HookResult, buffers::TrajectoryBatch, models::Policy,
+    HookResult, buffers::TrajectoryBatch, episode::EpisodeMonitor, models::Policy,
     on_policy::learning_module::OnPolicyLearningModule,
 };
 
-use crate::utils::{fmt_stat, mean};
+use crate::{
+    hooks::{
+        diagnostics::{DiagnosticsTracker, TrainingDiagnostics},
+        early_stopping::LossMonitor,
+    },
+    metrics::{MetricsLogger, MetricsReport},
+    utils::{EpisodeReporter, fmt_stat, mean},
+};
 
 /// Per-batch training statistics emitted by the default A2C hook.
 ///
@@ -47,10 +54,14 @@ pub struct A2CStats {
     pub batch_stats: Vec<A2CBatchStats>,
     /// Current action-distribution standard deviation when available.
     pub std: Option<f32>,
-    /// Average completed-episode reward observed across the active env set.
+    /// Average raw return of the episodes completed during the rollout.
     pub average_reward: f32,
+    /// Average length of the episodes completed during the rollout.
+    pub average_episode_length: f32,
     /// Current policy optimizer learning rate.
     pub learning_rate: f64,
+    /// Value-function, gradient, entropy and timing diagnostics.
+    pub diagnostics: TrainingDiagnostics,
 }
 
 impl A2CStats {
@@ -92,8 +103,12 @@ impl A2CStats {
 
 impl std::fmt::Display for A2CStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let rows = [
+        let mut rows = vec![
             ("Average reward", fmt_stat(self.average_reward)),
+            (
+                "Average episode length",
+                fmt_stat(self.average_episode_length),
+            ),
             ("Policy gradient loss", fmt_stat(self.policy_loss())),
             ("Entropy loss", fmt_stat(self.entropy_loss())),
             ("Value loss", fmt_stat(self.value_loss())),
@@ -103,6 +118,7 @@ impl std::fmt::Display for A2CStats {
                 self.std.map(|std| std.to_string()).unwrap_or("n/a".into()),
             ),
         ];
+        rows.extend(self.diagnostics.rows());
 
         let key_width = rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
 
@@ -117,32 +133,53 @@ impl std::fmt::Display for A2CStats {
     }
 }
 
+impl MetricsReport for A2CStats {
+    fn log_metrics(&self, logger: &MetricsLogger) -> Result<()> {
+        logger.log_scalar("train/policy_loss", self.policy_loss() as f64)?;
+        logger.log_scalar("train/entropy_loss", self.entropy_loss() as f64)?;
+        logger.log_scalar("train/value_loss", self.value_loss() as f64)?;
+        logger.log_scalar("train/learning_rate", self.learning_rate)?;
+        if let Some(std) = self.std {
+            logger.log_scalar("train/std", std as f64)?;
+        }
+        self.diagnostics.log_metrics(logger)
+    }
+}
+
 pub(crate) struct DefaultA2CHookReporter {
     pub(crate) rollout_idx: usize,
     pub(crate) report: A2CStats,
     pub(crate) tx: Option<Sender<A2CStats>>,
     pub(crate) log_progress: bool,
-    pub(crate) unfinished_episode_rewards: Vec<f32>,
-    pub(crate) latest_average_reward: f32,
+    pub(crate) episodes: EpisodeReporter,
+    pub(crate) logger: Option<MetricsLogger>,
+    pub(crate) diagnostics: DiagnosticsTracker,
 }
 
 impl DefaultA2CHookReporter {
-    pub fn new(tx: Option<Sender<A2CStats>>, log_progress: bool, n_envs: usize) -> Option<Self> {
-        if tx.is_some() || log_progress {
+    pub fn new(
+        tx: Option<Sender<A2CStats>>,
+        log_progress: bool,
+        n_envs: usize,
+        episode_monitor: Option<EpisodeMonitor>,
+        logger: Option<MetricsLogger>,
+    ) -> Option<Self> {
+        if tx.is_some() || log_progress || logger.is_some() {
             Some(Self {
                 rollout_idx: 0,
                 report: A2CStats::default(),
                 tx,
                 log_progress,
-                unfinished_episode_rewards: vec![0.; n_envs],
-                latest_average_reward: 0.,
+                logger,
+                episodes: EpisodeReporter::new(n_envs, episode_monitor),
+                diagnostics: DiagnosticsTracker::new(),
             })
         } else {
             None
         }
     }
 
-    pub(crate) fn send_report(&mut self) {
+    pub(crate) fn send_report(&mut self) -> Result<()> {
         self.rollout_idx += 1;
         let progress = std::mem::replace(
             &mut self.report,
@@ -154,44 +191,26 @@ impl DefaultA2CHookReporter {
         if self.log_progress {
             println!("{progress}");
         }
+        if let Some(logger) = &self.logger {
+            logger.log_report(&progress)?;
+        }
         if let Some(tx) = &self.tx {
             tx.send(progress).unwrap();
         }
-        self.report.average_reward = self.latest_average_reward;
+        self.report.average_reward = self.episodes.average_reward;
+        self.report.average_episode_length = self.episodes.average_length;
+        Ok(())
     }
 }
 
 impl DefaultA2CHookReporter {
-    fn update_average_reward<T: r2l_core::tensor::R2lTensor, B: TrajectoryBatch<T>>(
+    fn update_episode_stats<T: r2l_core::tensor::R2lTensor, B: TrajectoryBatch<T>>(
         &mut self,
         batches: &[B],
     ) {
-        let mut completed_episode_rewards = vec![];
-        for (running_reward, batch) in self
-            .unfinished_episode_rewards
-            .iter_mut()
-            .zip(batches.iter())
-        {
-            for (reward, done) in batch.rewards().iter().copied().zip(
-                batch
-                    .terminated()
-                    .iter()
-                    .zip(batch.truncated().iter())
-                    .map(|(terminated, truncated)| *terminated || *truncated),
-            ) {
-                *running_reward += reward;
-                if done {
-                    completed_episode_rewards.push(*running_reward);
-                    *running_reward = 0.;
-                }
-            }
-        }
-
-        if !completed_episode_rewards.is_empty() {
-            self.latest_average_reward = completed_episode_rewards.iter().sum::<f32>()
-                / completed_episode_rewards.len() as f32;
-        }
-        self.report.average_reward = self.latest_average_reward;
+        self.episodes.update(batches);
+        self.report.average_reward = self.episodes.average_reward;
+        self.report.average_episode_length = self.episodes.average_length;
     }
 }
 
@@ -200,7 +219,8 @@ impl DefaultA2CHookReporter {
 /// This hook applies the crate's standard A2C training behavior:
 /// advantage normalization when enabled, optional value-loss weighting,
 /// optional entropy regularization, optional gradient clipping, and optional
-/// rollout reporting through [`A2CStats`].
+/// rollout reporting through [`A2CStats`]. With a [`LossMonitor`], a NaN or
+/// infinite loss skips its update.
 ///
 /// The generic parameter tracks the concrete learning-module backend and is not
 /// usually named directly by callers.
@@ -209,6 +229,7 @@ pub struct DefaultA2CHook<T = ()> {
     pub(crate) entropy_coeff: f32,
     pub(crate) vf_coeff: Option<f32>,
     pub(crate) gradient_clipping: Option<f32>,
+    pub(crate) loss_monitor: Option<LossMonitor>,
     pub(crate) reporter: Option<DefaultA2CHookReporter>,
     pub(crate) _lm: PhantomData<T>,
 }
@@ -224,8 +245,13 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> A2CHook<BurnPolicyValueModule<B, D>>
         module: &mut BurnPolicyValueModule<B, D>,
         _buffers: &[C],
         advantages: &mut Advantages,
-        _returns: &mut Returns,
+        returns: &mut Returns,
     ) -> Result<HookResult> {
+        if let Some(reporter) = &mut self.reporter {
+            reporter.diagnostics.start_learning(advantages, returns);
+            module.collect_grad_norms(true);
+            module.take_grad_norms();
+        }
         if self.normalize_advantage {
             advantages.normalize();
         }
@@ -245,13 +271,24 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> A2CHook<BurnPolicyValueModule<B, D>>
         losses.set_vf_coeff(self.vf_coeff);
         let entropy = module.policy().entropy(&data.observations)?;
         let entropy_loss = entropy.neg() * self.entropy_coeff;
-        if let Some(DefaultA2CHookReporter { report, .. }) = &mut self.reporter {
+        if let Some(DefaultA2CHookReporter {
+            report,
+            diagnostics,
+            ..
+        }) = &mut self.reporter
+        {
+            diagnostics.record_entropy(&module.policy().entropy_per_dimension(&data.observations)?);
             report.collect_batch_data(A2CBatchStats {
                 policy_loss: losses.policy_loss.to_data().to_vec::<f32>().unwrap()[0],
                 entropy_loss: entropy_loss.to_data().to_vec::<f32>().unwrap()[0],
                 value_loss: losses.value_loss.to_data().to_vec::<f32>().unwrap()[0],
             });
         }
+        if let Some(monitor) = &self.loss_monitor
+            && !monitor.check(&[&losses.policy_loss, &losses.value_loss, &entropy_loss])
+        {
+            return Ok(HookResult::Break);
+        }
         if self.entropy_coeff != 0. {
             losses.add_entropy_loss(entropy_loss);
         }
@@ -267,10 +304,13 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> A2CHook<BurnPolicyValueModule<B, D>>
         buffers: &[C],
     ) -> Result<HookResult> {
         if let Some(reporter) = &mut self.reporter {
-            reporter.update_average_reward(buffers);
+            reporter.update_episode_stats(buffers);
             reporter.report.std = module.policy().std().ok();
             reporter.report.learning_rate = module.policy_learning_rate();
-            reporter.send_report();
+            reporter.report.diagnostics = reporter
+                .diagnostics
+                .finish(module.take_grad_norms(), module.policy().log_std()?);
+            reporter.send_report()?;
         }
         Ok(HookResult::Continue)
     }
@@ -285,8 +325,13 @@ impl A2CHook<CandlePolicyValueModule> for DefaultA2CHook<CandlePolicyValueModule
         module: &mut CandlePolicyValueModule,
         _buffers: &[B],
         advantages: &mut Advantages,
-        _returns: &mut Returns,
+        returns: &mut Returns,
     ) -> Result<HookResult> {
+        if let Some(reporter) = &mut self.reporter {
+            reporter.diagnostics.start_learning(advantages, returns);
+            module.collect_grad_norms(true);
+            module.take_grad_norms();
+        }
         if self.normalize_advantage {
             advantages.normalize();
         }
@@ -305,13 +350,24 @@ impl A2CHook<CandlePolicyValueModule> for DefaultA2CHook<CandlePolicyValueModule
         let entropy = module.policy().entropy(&data.observations)?;
         let device = entropy.device();
         let entropy_loss = (Tensor::full(self.entropy_coeff, (), device)? * entropy.neg()?)?;
-        if let Some(DefaultA2CHookReporter { report, .. }) = &mut self.reporter {
+        if let Some(DefaultA2CHookReporter {
+            report,
+            diagnostics,
+            ..
+        }) = &mut self.reporter
+        {
+            diagnostics.record_entropy(&module.policy().entropy_per_dimension(&data.observations)?);
             report.collect_batch_data(A2CBatchStats {
                 policy_loss: losses.policy_loss.to_scalar()?,
                 entropy_loss: entropy_loss.to_scalar()?,
                 value_loss: losses.value_loss.to_scalar()?,
             });
         }
+        if let Some(monitor) = &self.loss_monitor
+            && !monitor.check(&[&losses.policy_loss, &losses.value_loss, &entropy_loss])
+        {
+            return Ok(HookResult::Break);
+        }
         if self.entropy_coeff != 0. {
             losses.add_entropy_loss(entropy_loss)?;
         }
@@ -325,11 +381,96 @@ impl A2CHook<CandlePolicyValueModule> for DefaultA2CHook<CandlePolicyValueModule
         buffers: &[B],
     ) -> Result<HookResult> {
         if let Some(reporter) = &mut self.reporter {
-            reporter.update_average_reward(buffers);
+            reporter.update_episode_stats(buffers);
             reporter.report.std = module.policy().std().ok();
             reporter.report.learning_rate = module.policy_learning_rate();
-            reporter.send_report();
+            reporter.report.diagnostics = reporter
+                .diagnostics
+                .finish(module.take_grad_norms(), module.policy().log_std()?);
+            reporter.send_report()?;
         }
         Ok(HookResult::Continue)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use candle_core::Device;
+    use r2l_agents::on_policy_algorithms::a2c::{A2C, A2CBatchData, A2CHook, A2CParams};
+    use r2l_core::{
+        HookResult,
+        buffers::buffer::TrajectoryBuffer,
+        models::LearningModule,
+        on_policy::{algorithm::Agent, learning_module::OnPolicyLearningModule},
+    };
+
+    use super::DefaultA2CHook;
+    use crate::{
+        BurnBackend,
+        builders::a2c::hook::DefaultA2CHookBuilder,
+        hooks::ppo::test::{OBSERVATION_SIZE, action_space, builder, rollout},
+    };
+
+    // Counts batches without overriding any other hook.
+    #[derive(Default)]
+    struct Batches(usize);
+
+    impl<M: OnPolicyLearningModule> A2CHook<M> for Batches {
+        fn batch_hook(
+            &mut self,
+            _params: &mut A2CParams,
+            _module: &mut M,
+            _losses: &mut <M as LearningModule>::Losses,
+            _data: &A2CBatchData<M::LearningTensor>,
+        ) -> Result<HookResult> {
+            self.0 += 1;
+            Ok(HookResult::Continue)
+        }
+    }
+
+    // A chained hook that only overrides `batch_hook` sees all four batches
+    // of the learning pass.
+    fn check_chained_batch_hook<M: OnPolicyLearningModule>(lm: M) -> Result<()>
+    where
+        DefaultA2CHook<M>: A2CHook<M>,
+    {
+        let hooks = DefaultA2CHookBuilder::new(1)
+            .with_log_progress(false)
+            .build::<M>();
+        let mut a2c = A2C {
+            params: A2CParams {
+                sample_size: 8,
+                ..Default::default()
+            },
+            lm,
+            hooks,
+        }
+        .chain_hook(Batches::default());
+
+        let rollout = [rollout::<M::InferenceTensor>(1.)];
+        let views = rollout
+            .iter()
+            .map(TrajectoryBuffer::to_trajectory_view)
+            .collect::<Vec<_>>();
+        Agent::learn(&mut a2c, &views)?;
+        assert_eq!(a2c.hooks.1.0, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn candle_chained_batch_hooks_keep_every_batch() -> Result<()> {
+        check_chained_batch_hook(builder().build_candle(
+            OBSERVATION_SIZE,
+            action_space(),
+            &Device::Cpu,
+        )?)
+    }
+
+    #[test]
+    fn burn_chained_batch_hooks_keep_every_batch() -> Result<()> {
+        check_chained_batch_hook(
+            builder().build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?,
+        )
+    }
+}
@@ -0,0 +1,239 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use r2l_agents::on_policy_algorithms::{Advantages, Returns};
+use r2l_core::models::GradNorms;
+
+use crate::{
+    metrics::{MetricsLogger, MetricsReport},
+    utils::fmt_stat,
+};
+
+/// Learning diagnostics reported by the default PPO and A2C hooks.
+///
+/// Both backends fill in the same fields, so runs can be compared across
+/// backends.
+#[derive(Default, Debug, Clone)]
+pub struct TrainingDiagnostics {
+    /// Fraction of the return variance explained by the rollout value
+    /// predictions. `NaN` when the returns have no variance.
+    pub explained_variance: f32,
+    /// Mean of the advantages before normalization.
+    pub advantage_mean: f32,
+    /// Standard deviation of the advantages before normalization.
+    pub advantage_std: f32,
+    /// Mean of the return targets.
+    pub return_mean: f32,
+    /// Standard deviation of the return targets.
+    pub return_std: f32,
+    /// Mean global norm of the policy-side gradients before clipping.
+    pub grad_norm: Option<f32>,
+    /// Mean global norm of the policy-side gradients after clipping.
+    pub clipped_grad_norm: Option<f32>,
+    /// Mean action-distribution entropy of every action dimension.
+    pub entropy_per_dimension: Vec<f32>,
+    /// Log standard deviation of every action dimension, for Gaussian
+    /// policies.
+    pub log_std: Option<Vec<f32>>,
+    /// Time spent collecting the rollout, measured from the end of the
+    /// previous learning pass.
+    pub collection_time: Duration,
+    /// Time spent learning from the rollout.
+    pub learning_time: Duration,
+}
+
+impl TrainingDiagnostics {
+    pub(crate) fn rows(&self) -> Vec<(&'static str, String)> {
+        let fmt_optional = |value: Option<f32>| value.map(fmt_stat).unwrap_or("n/a".into());
+        let fmt_vec = |values: &[f32]| {
+            let values = values
+                .iter()
+                .map(|value| fmt_stat(*value))
+                .collect::<Vec<_>>();
+            format!("[{}]", values.join(", "))
+        };
+        vec![
+            ("Explained variance", fmt_stat(self.explained_variance)),
+            (
+                "Advantage mean/std",
+                format!(
+                    "{} / {}",
+                    fmt_stat(self.advantage_mean),
+                    fmt_stat(self.advantage_std)
+                ),
+            ),
+            (
+                "Return mean/std",
+                format!(
+                    "{} / {}",
+                    fmt_stat(self.return_mean),
+                    fmt_stat(self.return_std)
+                ),
+            ),
+            ("Gradient norm", fmt_optional(self.grad_norm)),
+            (
+                "Clipped gradient norm",
+                fmt_optional(self.clipped_grad_norm),
+            ),
+            (
+                "Entropy per dimension",
+                fmt_vec(&self.entropy_per_dimension),
+            ),
+            (
+                "Log standard deviation",
+                self.log_std.as_deref().map(fmt_vec).unwrap_or("n/a".into()),
+            ),
+            (
+                "Collection time (s)",
+                fmt_stat(self.collection_time.as_secs_f32()),
+            ),
+            (
+                "Learning time (s)",
+                fmt_stat(self.learning_time.as_secs_f32()),
+            ),
+        ]
+    }
+}
+
+impl MetricsReport for TrainingDiagnostics {
+    fn log_metrics(&self, logger: &MetricsLogger) -> Result<()> {
+        logger.log_scalar("train/explained_variance", self.explained_variance as f64)?;
+        logger.log_scalar("train/advantage_mean", self.advantage_mean as f64)?;
+        logger.log_scalar("train/advantage_std", self.advantage_std as f64)?;
+        logger.log_scalar("train/return_mean", self.return_mean as f64)?;
+        logger.log_scalar("train/return_std", self.return_std as f64)?;
+        if let Some(grad_norm) = self.grad_norm {
+            logger.log_scalar("train/grad_norm", grad_norm as f64)?;
+        }
+        if let Some(clipped_grad_norm) = self.clipped_grad_norm {
+            logger.log_scalar("train/clipped_grad_norm", clipped_grad_norm as f64)?;
+        }
+        for (dim, entropy) in self.entropy_per_dimension.iter().enumerate() {
+            logger.log_scalar(&format!("train/entropy/{dim}"), *entropy as f64)?;
+        }
+        if let Some(log_std) = &self.log_std {
+            for (dim, log_std) in log_std.iter().enumerate() {
+                logger.log_scalar(&format!("train/log_std/{dim}"), *log_std as f64)?;
+            }
+        }
+        logger.log_scalar(
+            "time/collection_seconds",
+            self.collection_time.as_secs_f64(),
+        )?;
+        logger.log_scalar("time/learning_seconds", self.learning_time.as_secs_f64())?;
+        Ok(())
+    }
+}
+
+fn mean_std(values: &[f32]) -> (f32, f32) {
+    if values.is_empty() {
+        return (0., 0.);
+    }
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f32>()
+        / values.len() as f32;
+    (mean, variance.sqrt())
+}
+
+/// Collects [`TrainingDiagnostics`] over one learning pass.
+pub(crate) struct DiagnosticsTracker {
+    report: TrainingDiagnostics,
+    entropy_sums: Vec<f32>,
+    entropy_batches: usize,
+    learning_start: Instant,
+    learning_end: Instant,
+}
+
+impl DiagnosticsTracker {
+    pub(crate) fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            report: TrainingDiagnostics::default(),
+            entropy_sums: vec![],
+            entropy_batches: 0,
+            learning_start: now,
+            learning_end: now,
+        }
+    }
+
+    /// Starts a learning pass. Must see the advantages before normalization.
+    ///
+    /// The rollout value predictions are recovered as `returns - advantages`,
+    /// which holds for GAE targets.
+    pub(crate) fn start_learning(&mut self, advantages: &Advantages, returns: &Returns) {
+        self.learning_start = Instant::now();
+        self.report.collection_time = self.learning_start - self.learning_end;
+        let advantages = advantages.0.concat();
+        let returns = returns.0.concat();
+        (self.report.advantage_mean, self.report.advantage_std) = mean_std(&advantages);
+        (self.report.return_mean, self.report.return_std) = mean_std(&returns);
+        // the residual of the value predictions is the advantage
+        self.report.explained_variance = if self.report.return_std == 0. {
+            f32::NAN
+        } else {
+            1. - self.report.advantage_std.powi(2) / self.report.return_std.powi(2)
+        };
+        self.entropy_sums.clear();
+        self.entropy_batches = 0;
+    }
+
+    /// Adds the per-dimension entropy of one minibatch.
+    pub(crate) fn record_entropy(&mut self, entropy_per_dimension: &[f32]) {
+        if self.entropy_sums.len() != entropy_per_dimension.len() {
+            self.entropy_sums = vec![0.; entropy_per_dimension.len()];
+            self.entropy_batches = 0;
+        }
+        for (sum, entropy) in self.entropy_sums.iter_mut().zip(entropy_per_dimension) {
+            *sum += entropy;
+        }
+        self.entropy_batches += 1;
+    }
+
+    /// Ends the learning pass and returns its diagnostics.
+    pub(crate) fn finish(
+        &mut self,
+        grad_norms: GradNorms,
+        log_std: Option<Vec<f32>>,
+    ) -> TrainingDiagnostics {
+        self.learning_end = Instant::now();
+        let mut report = std::mem::take(&mut self.report);
+        report.learning_time = self.learning_end - self.learning_start;
+        report.grad_norm = grad_norms.pre_clip();
+        report.clipped_grad_norm = grad_norms.post_clip();
+        report.entropy_per_dimension = self
+            .entropy_sums
+            .iter()
+            .map(|sum| sum / self.entropy_batches as f32)
+            .collect();
+        report.log_std = log_std;
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use r2l_agents::on_policy_algorithms::{Advantages, Returns};
+
+    use super::DiagnosticsTracker;
+
+    #[test]
+    fn explained_variance_uses_the_advantages_as_residuals() {
+        let mut tracker = DiagnosticsTracker::new();
+        let returns = Returns(vec![vec![1., 2., 3., 4.]]);
+        let perfect = Advantages(vec![vec![0.; 4]]);
+        tracker.start_learning(&perfect, &returns);
+        let report = tracker.finish(Default::default(), None);
+        assert_eq!(report.explained_variance, 1.);
+        assert_eq!(report.return_mean, 2.5);
+
+        // predicting the mean return explains nothing
+        let mean_baseline = Advantages(vec![vec![-1.5, -0.5, 0.5, 1.5]]);
+        tracker.start_learning(&mean_baseline, &returns);
+        let report = tracker.finish(Default::default(), None);
+        assert!(report.explained_variance.abs() < 1e-6);
+        assert_eq!(report.advantage_mean, 0.);
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, bail};
+use r2l_core::tensor::R2lTensor;
+
+/// Condition stopping training before the [`LearningSchedule`] ends.
+///
+/// [`DefaultOnPolicyAlgorithmHooks`] checks its conditions after every
+/// training step and every evaluation pass, and stops training as soon as one
+/// of them is met.
+///
+/// [`LearningSchedule`]: crate::LearningSchedule
+/// [`DefaultOnPolicyAlgorithmHooks`]: crate::DefaultOnPolicyAlgorithmHooks
+pub trait StopCondition: Send {
+    /// Called once before the first rollout.
+    fn start(&mut self) {}
+
+    /// Called after the agent learned from a rollout, with the number of
+    /// sampled environment steps. Returns whether training should stop.
+    fn after_training(&mut self, _total_steps: usize) -> bool {
+        false
+    }
+
+    /// Called after every evaluation pass with its mean reward. Returns
+    /// whether training should stop.
+    fn after_evaluation(&mut self, _total_steps: usize, _mean_reward: f32) -> bool {
+        false
+    }
+
+    /// Why the condition stopped training.
+    fn reason(&self) -> String;
+}
+
+impl<C: StopCondition + ?Sized> StopCondition for Box<C> {
+    fn start(&mut self) {
+        (**self).start();
+    }
+
+    fn after_training(&mut self, total_steps: usize) -> bool {
+        (**self).after_training(total_steps)
+    }
+
+    fn after_evaluation(&mut self, total_steps: usize, mean_reward: f32) -> bool {
+        (**self).after_evaluation(total_steps, mean_reward)
+    }
+
+    fn reason(&self) -> String {
+        (**self).reason()
+    }
+}
+
+/// Stops once an evaluation's mean reward reaches `threshold`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardThreshold {
+    threshold: f32,
+    reached: Option<f32>,
+}
+
+impl RewardThreshold {
+    /// Stops once a mean reward is at least `threshold`.
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            reached: None,
+        }
+    }
+}
+
+impl StopCondition for RewardThreshold {
+    fn after_evaluation(&mut self, _total_steps: usize, mean_reward: f32) -> bool {
+        if mean_reward >= self.threshold {
+            self.reached = Some(mean_reward);
+        }
+        self.reached.is_some()
+    }
+
+    fn reason(&self) -> String {
+        format!(
+            "mean evaluation reward {} reached the threshold {}",
+            self.reached.unwrap_or(f32::NAN),
+            self.threshold
+        )
+    }
+}
+
+/// Stops once the best mean evaluation reward has not improved by more than
+/// `min_delta` for `patience` evaluations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoImprovement {
+    patience: usize,
+    min_delta: f32,
+    best: Option<f32>,
+    stale_evaluations: usize,
+}
+
+impl NoImprovement {
+    /// Stops after `patience` evaluations without a better mean reward.
+    ///
+    /// The patience must be at least one evaluation.
+    pub fn new(patience: usize) -> Result<Self> {
+        if patience == 0 {
+            bail!("the patience must be at least one evaluation");
+        }
+        Ok(Self {
+            patience,
+            min_delta: 0.,
+            best: None,
+            stale_evaluations: 0,
+        })
+    }
+
+    /// Only counts rewards exceeding the best one by more than `min_delta` as
+    /// improvements.
+    pub fn with_min_delta(mut self, min_delta: f32) -> Self {
+        self.min_delta = min_delta;
+        self
+    }
+}
+
+impl StopCondition for NoImprovement {
+    fn after_evaluation(&mut self, _total_steps: usize, mean_reward: f32) -> bool {
+        match self.best {
+            Some(best) if mean_reward <= best + self.min_delta => self.stale_evaluations += 1,
+            _ => {
+                self.best = Some(mean_reward);
+                self.stale_evaluations = 0;
+            }
+        }
+        self.stale_evaluations >= self.patience
+    }
+
+    fn reason(&self) -> String {
+        format!(
+            "no improvement over the best mean evaluation reward {} for {} evaluations",
+            self.best.unwrap_or(f32::NAN),
+            self.stale_evaluations
+        )
+    }
+}
+
+/// Stops once `budget` of wall-clock time has passed since training started.
+///
+/// Training only stops between rollouts, so it can exceed the budget by the
+/// time of a rollout, a training step and an evaluation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeBudget {
+    budget: Duration,
+    started: Option<Instant>,
+}
+
+impl TimeBudget {
+    /// Stops once `budget` has passed.
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            started: None,
+        }
+    }
+}
+
+impl StopCondition for TimeBudget {
+    fn start(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    fn after_training(&mut self, _total_steps: usize) -> bool {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        started.elapsed() >= self.budget
+    }
+
+    fn reason(&self) -> String {
+        format!("the time budget of {:?} is exhausted", self.budget)
+    }
+}
+
+/// Flag raised by the agent hooks when a loss is NaN or infinite.
+///
+/// Clones share the flag. Agent builders pass it to their hooks, which skip
+/// the update of a non-finite loss and end the rollout's training.
+#[derive(Debug, Clone, Default)]
+pub struct LossMonitor(Arc<AtomicBool>);
+
+impl LossMonitor {
+    /// Creates a lowered flag.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a non-finite loss was seen.
+    pub fn non_finite(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Raises the flag unless every value of `losses` is finite, and returns
+    /// whether they are.
+    pub(crate) fn check<T: R2lTensor>(&self, losses: &[&T]) -> bool {
+        let finite = losses
+            .iter()
+            .all(|loss| loss.to_vec().iter().all(|value| value.is_finite()));
+        if !finite {
+            self.0.store(true, Ordering::Relaxed);
+        }
+        finite
+    }
+
+    /// Same as [`check`](Self::check), for losses already read back.
+    pub(crate) fn check_values(&self, losses: &[f32]) -> bool {
+        let finite = losses.iter().all(|value| value.is_finite());
+        if !finite {
+            self.0.store(true, Ordering::Relaxed);
+        }
+        finite
+    }
+}
+
+/// Stops once the agent hooks saw a NaN or infinite loss.
+#[derive(Debug, Clone, Default)]
+pub struct NonFiniteLoss {
+    monitor: LossMonitor,
+}
+
+impl NonFiniteLoss {
+    /// Reads the flag of `monitor`, which the agent hooks must also hold.
+    pub fn new(monitor: LossMonitor) -> Self {
+        Self { monitor }
+    }
+}
+
+impl StopCondition for NonFiniteLoss {
+    fn after_training(&mut self, _total_steps: usize) -> bool {
+        self.monitor.non_finite()
+    }
+
+    fn reason(&self) -> String {
+        "a training loss is NaN or infinite".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use anyhow::Result;
+
+    use super::{
+        LossMonitor, NoImprovement, NonFiniteLoss, RewardThreshold, StopCondition, TimeBudget,
+    };
+
+    #[test]
+    fn conditions_stop_on_their_signal() -> Result<()> {
+        let mut threshold = RewardThreshold::new(195.);
+        assert!(!threshold.after_evaluation(1000, 120.));
+        assert!(threshold.after_evaluation(2000, 200.));
+
+        let mut no_improvement = NoImprovement::new(2)?.with_min_delta(1.);
+        assert!(!no_improvement.after_evaluation(1000, 10.));
+        assert!(!no_improvement.after_evaluation(2000, 10.5));
+        assert!(!no_improvement.after_evaluation(3000, 12.));
+        assert!(!no_improvement.after_evaluation(4000, 11.));
+        assert!(no_improvement.after_evaluation(5000, 12.5));
+
+        let mut budget = TimeBudget::new(Duration::ZERO);
+        budget.start();
+        assert!(budget.after_training(1000));
+
+        let monitor = LossMonitor::new();
+        let mut non_finite = NonFiniteLoss::new(monitor.clone());
+        assert!(monitor.check_values(&[0.5, -2.]));
+        assert!(!non_finite.after_training(1000));
+        assert!(!monitor.check_values(&[0.5, f32::NAN]));
+        assert!(non_finite.after_training(2000));
+        Ok(())
+    }
+
+    #[test]
+    fn no_improvement_needs_some_patience() {
+        assert!(NoImprovement::new(0).is_err());
+    }
+}
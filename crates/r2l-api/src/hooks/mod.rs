@@ -1,4 +1,10 @@
 pub mod a2c;
+pub mod diagnostics;
+pub mod early_stopping;
 pub mod on_policy;
+pub mod ppg;
 pub mod ppo;
 pub mod sampler;
+pub mod schedule;
+pub mod trpo;
+pub mod vpg;
@@ -5,13 +5,18 @@ use r2l_core::{
     HookResult,
     buffers::TrajectoryBatch,
     env::Env,
+    episode::mean_of,
     on_policy::algorithm::{
         Agent, OnPolicyAdapters, OnPolicyAlgorithmHooks, OnPolicyRuntime, Sampler,
     },
     tensor::R2lTensor,
 };
 
-use crate::BestActorEvaluator;
+use crate::{
+    BestActorEvaluator,
+    hooks::{early_stopping::StopCondition, schedule::Schedule},
+    metrics::MetricsLogger,
+};
 
 /// Training-stop policy for [`DefaultOnPolicyAlgorithmHooks`].
 ///
@@ -51,6 +56,7 @@ impl LearningSchedule {
 }
 
 /// Learning-rate policy applied over the progress of an on-policy training run.
+#[deprecated(note = "use `Schedule`, which these variants convert into")]
 #[derive(Debug, Clone, Copy)]
 pub enum LearningRateSchedule {
     /// Keep the learning rate fixed throughout training.
@@ -59,25 +65,36 @@ pub enum LearningRateSchedule {
     Linear(f64),
 }
 
+#[allow(deprecated)]
 impl LearningRateSchedule {
     /// Returns the learning rate for the remaining fraction of training.
     pub fn value(self, progress_remaining: f64) -> f64 {
-        match self {
-            Self::Constant(learning_rate) => learning_rate,
-            Self::Linear(initial_learning_rate) => {
-                initial_learning_rate * progress_remaining.clamp(0.0, 1.0)
-            }
+        Schedule::from(self).value(progress_remaining)
+    }
+}
+
+#[allow(deprecated)]
+impl From<LearningRateSchedule> for Schedule {
+    fn from(schedule: LearningRateSchedule) -> Self {
+        match schedule {
+            LearningRateSchedule::Constant(learning_rate) => Schedule::constant(learning_rate),
+            LearningRateSchedule::Linear(learning_rate) => Schedule::linear(learning_rate, 0.0),
         }
     }
 }
 
+/// Called with the number of sampled environment steps and the mean reward
+/// after every evaluation pass. Training stops when it returns `false`.
+pub type EvaluationCallback = Box<dyn FnMut(usize, f32) -> bool + Send>;
+
 /// Default outer-loop hooks used by high-level on-policy algorithm builders.
 ///
 /// This hook is responsible for lifecycle behavior around training rather than
 /// algorithm-specific loss logic. It tracks rollout progress, applies the
 /// configured [`LearningSchedule`] to decide when training should stop,
-/// optionally evaluates the current actor, and shuts down the runtime when the
-/// algorithm exits.
+/// optionally evaluates the current actor, optionally stops early on
+/// [`StopCondition`]s, optionally logs rollout episode statistics to a
+/// [`MetricsLogger`], and shuts down the runtime when the algorithm exits.
 pub struct DefaultOnPolicyAlgorithmHooks<
     A: Agent,
     S: Sampler,
@@ -86,8 +103,14 @@ pub struct DefaultOnPolicyAlgorithmHooks<
     S2: Sampler<Tensor = S::Tensor>,
 > {
     learning_schedule: LearningSchedule,
-    learning_rate_schedule: Option<LearningRateSchedule>,
+    learning_rate_schedule: Option<Schedule>,
     evaluator: Option<BestActorEvaluator<A::Actor, S2>>,
+    evaluation_callback: Option<EvaluationCallback>,
+    stop_conditions: Vec<Box<dyn StopCondition>>,
+    stop_reason: Option<String>,
+    logger: Option<MetricsLogger>,
+    total_steps: usize,
+    logging_error: Option<anyhow::Error>,
     should_stop: bool,
     _phantom: PhantomData<(A, S, C, E)>,
 }
@@ -109,6 +132,12 @@ impl<
             learning_schedule,
             learning_rate_schedule: None,
             evaluator,
+            evaluation_callback: None,
+            stop_conditions: vec![],
+            stop_reason: None,
+            logger: None,
+            total_steps: 0,
+            logging_error: None,
             should_stop: false,
             _phantom: PhantomData,
         }
@@ -117,11 +146,76 @@ impl<
     /// Applies a learning-rate schedule over the configured training duration.
     pub fn with_learning_rate_schedule(
         mut self,
-        learning_rate_schedule: LearningRateSchedule,
+        learning_rate_schedule: impl Into<Schedule>,
     ) -> Self {
-        self.learning_rate_schedule = Some(learning_rate_schedule);
+        self.learning_rate_schedule = Some(learning_rate_schedule.into());
+        self
+    }
+
+    /// Reports every evaluation result to `callback`, which can stop training.
+    pub fn with_evaluation_callback(mut self, callback: EvaluationCallback) -> Self {
+        self.evaluation_callback = Some(callback);
         self
     }
+
+    /// Stops training once `condition` is met, in addition to the learning
+    /// schedule and the other conditions.
+    pub fn with_stop_condition(mut self, condition: Box<dyn StopCondition>) -> Self {
+        self.stop_conditions.push(condition);
+        self
+    }
+
+    /// Why a [`StopCondition`] stopped training, if one did.
+    pub fn stop_reason(&self) -> Option<&str> {
+        self.stop_reason.as_deref()
+    }
+
+    // Records the reason of the first met condition.
+    fn check_stop_conditions(
+        &mut self,
+        mut is_met: impl FnMut(&mut dyn StopCondition) -> bool,
+    ) -> bool {
+        for condition in &mut self.stop_conditions {
+            if is_met(condition.as_mut()) {
+                self.stop_reason = Some(condition.reason());
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Logs rollout episode statistics to `logger` and advances its global
+    /// step by the number of environment steps of every rollout.
+    pub fn with_metrics_logger(mut self, logger: MetricsLogger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    fn log_rollout(&self, runtime: &OnPolicyRuntime<A, S, C>) -> Result<()> {
+        let Some(logger) = &self.logger else {
+            return Ok(());
+        };
+        logger.set_step(self.total_steps as u64);
+        let Some(monitor) = runtime.sampler.episode_monitor() else {
+            return Ok(());
+        };
+        let episodes = monitor.rollout_episodes();
+        if let Some(mean_reward) = mean_of(&episodes, |stats| stats.reward) {
+            logger.log_scalar("rollout/ep_rew_mean", mean_reward as f64)?;
+        }
+        if let Some(mean_length) = mean_of(&episodes, |stats| stats.length as f32) {
+            logger.log_scalar("rollout/ep_len_mean", mean_length as f64)?;
+        }
+        if !episodes.is_empty() {
+            let rewards = episodes
+                .iter()
+                .map(|stats| stats.reward)
+                .collect::<Vec<_>>();
+            logger.log_histogram("rollout/episode_rewards", &rewards)?;
+        }
+        logger.log_scalar("rollout/total_episodes", monitor.total_episodes() as f64)?;
+        logger.flush()
+    }
 }
 
 impl<
@@ -140,6 +234,9 @@ impl<
         &mut self,
         _runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
     ) -> HookResult {
+        for condition in &mut self.stop_conditions {
+            condition.start();
+        }
         HookResult::Continue
     }
 
@@ -147,6 +244,15 @@ impl<
         &mut self,
         runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
     ) -> HookResult {
+        let rollout_steps: usize = {
+            let rollouts = runtime.trajectory_containers();
+            rollouts.as_ref().iter().map(|e| e.actions().len()).sum()
+        };
+        self.total_steps += rollout_steps;
+        if let Err(err) = self.log_rollout(runtime) {
+            self.logging_error = Some(err);
+            return HookResult::Break;
+        }
         let progress_remaining = match &mut self.learning_schedule {
             LearningSchedule::RolloutBound {
                 total_rollouts,
@@ -161,9 +267,6 @@ impl<
                 total_steps,
                 current_step,
             } => {
-                let rollouts = runtime.trajectory_containers();
-                let rollout_steps: usize =
-                    rollouts.as_ref().iter().map(|e| e.actions().len()).sum();
                 *current_step += rollout_steps;
                 self.should_stop = current_step >= total_steps;
                 let completed_steps = (*current_step).min(*total_steps);
@@ -171,11 +274,12 @@ impl<
             }
         };
 
-        if let Some(learning_rate_schedule) = self.learning_rate_schedule {
+        if let Some(learning_rate_schedule) = &self.learning_rate_schedule {
             runtime
                 .agent
                 .set_learning_rate(learning_rate_schedule.value(progress_remaining));
         }
+        runtime.agent.set_progress_remaining(progress_remaining);
 
         HookResult::Continue
     }
@@ -185,9 +289,24 @@ impl<
         runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
     ) -> HookResult {
         if let Some(evaluator) = &mut self.evaluator {
-            evaluator.eval(runtime);
+            if let Err(err) = evaluator.eval(runtime) {
+                self.logging_error = Some(err);
+                return HookResult::Break;
+            }
+            if let Some(mean_reward) = evaluator.last_mean_reward() {
+                if let Some(callback) = &mut self.evaluation_callback
+                    && !callback(self.total_steps, mean_reward)
+                {
+                    return HookResult::Break;
+                }
+                let total_steps = self.total_steps;
+                if self.check_stop_conditions(|c| c.after_evaluation(total_steps, mean_reward)) {
+                    return HookResult::Break;
+                }
+            }
         }
-        if self.should_stop {
+        let total_steps = self.total_steps;
+        if self.should_stop || self.check_stop_conditions(|c| c.after_training(total_steps)) {
             HookResult::Break
         } else {
             HookResult::Continue
@@ -203,6 +322,9 @@ impl<
             evaluator.shutdown();
         }
         runtime.shutdown();
-        Ok(())
+        match self.logging_error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
     }
 }
@@ -0,0 +1,221 @@
+use std::sync::mpsc::Sender;
+
+use anyhow::Result;
+use r2l_agents::on_policy_algorithms::{
+    Advantages, Returns,
+    ppg::{PPGAuxiliaryBatchData, PPGHook, PPGParams},
+    ppo::{PPOBatchData, PPOHook, PPOParams},
+};
+use r2l_core::{
+    HookResult, buffers::TrajectoryBatch, models::LearningModule,
+    on_policy::learning_module::AuxiliaryValueLearningModule, tensor::R2lTensor,
+};
+
+use crate::{
+    hooks::ppo::DefaultPPOHook,
+    metrics::{MetricsLogger, MetricsReport},
+    utils::{fmt_stat, mean},
+};
+
+/// Statistics emitted by the default PPG hook after each auxiliary phase.
+#[derive(Default, Debug, Clone)]
+pub struct PPGAuxiliaryStats {
+    /// Index of the auxiliary phase the stats belong to.
+    pub phase_idx: usize,
+    /// Auxiliary value-head losses of every minibatch.
+    pub auxiliary_value_losses: Vec<f32>,
+    /// KL divergences to the pre-phase policy of every minibatch.
+    pub kls: Vec<f32>,
+    /// Value-function losses of every minibatch.
+    pub value_losses: Vec<f32>,
+}
+
+impl PPGAuxiliaryStats {
+    pub fn auxiliary_value_loss(&self) -> f32 {
+        mean(&self.auxiliary_value_losses)
+    }
+
+    pub fn kl(&self) -> f32 {
+        mean(&self.kls)
+    }
+
+    pub fn value_loss(&self) -> f32 {
+        mean(&self.value_losses)
+    }
+}
+
+impl std::fmt::Display for PPGAuxiliaryStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = [
+            (
+                "Auxiliary value loss",
+                fmt_stat(self.auxiliary_value_loss()),
+            ),
+            ("KL divergence", fmt_stat(self.kl())),
+            ("Value loss", fmt_stat(self.value_loss())),
+        ];
+
+        let key_width = rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+
+        writeln!(f, "PPG auxiliary phase stats (phase {})", self.phase_idx)?;
+        writeln!(f, "{:-<1$}", "", key_width + 15)?;
+
+        for (key, value) in rows {
+            writeln!(f, "{key:<key_width$} | {value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl MetricsReport for PPGAuxiliaryStats {
+    fn log_metrics(&self, logger: &MetricsLogger) -> Result<()> {
+        logger.log_scalar(
+            "auxiliary/auxiliary_value_loss",
+            self.auxiliary_value_loss() as f64,
+        )?;
+        logger.log_scalar("auxiliary/kl", self.kl() as f64)?;
+        logger.log_scalar("auxiliary/value_loss", self.value_loss() as f64)?;
+        Ok(())
+    }
+}
+
+pub(crate) struct DefaultPPGHookReporter {
+    pub(crate) phase_idx: usize,
+    pub(crate) report: PPGAuxiliaryStats,
+    pub(crate) tx: Option<Sender<PPGAuxiliaryStats>>,
+    pub(crate) log_progress: bool,
+    pub(crate) logger: Option<MetricsLogger>,
+}
+
+impl DefaultPPGHookReporter {
+    pub fn new(
+        tx: Option<Sender<PPGAuxiliaryStats>>,
+        log_progress: bool,
+        logger: Option<MetricsLogger>,
+    ) -> Option<Self> {
+        if tx.is_some() || log_progress || logger.is_some() {
+            Some(Self {
+                phase_idx: 0,
+                report: PPGAuxiliaryStats::default(),
+                tx,
+                log_progress,
+                logger,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn send_report(&mut self) -> Result<()> {
+        self.phase_idx += 1;
+        let progress = std::mem::replace(
+            &mut self.report,
+            PPGAuxiliaryStats {
+                phase_idx: self.phase_idx,
+                ..Default::default()
+            },
+        );
+        if self.log_progress {
+            println!("{progress}");
+        }
+        if let Some(logger) = &self.logger {
+            logger.log_report(&progress)?;
+        }
+        if let Some(tx) = &self.tx {
+            tx.send(progress).unwrap();
+        }
+        Ok(())
+    }
+}
+
+/// Default training hook used by [`PPGAgentBuilder`](crate::PPGAgentBuilder).
+///
+/// The policy phase is handled by a [`DefaultPPOHook`], so it behaves and
+/// reports like PPO. The auxiliary phase is reported through
+/// [`PPGAuxiliaryStats`], and skips its update on a NaN or infinite loss when
+/// the PPO hook has a loss monitor.
+///
+/// The generic parameter tracks the concrete learning-module backend and is not
+/// usually named directly by callers.
+pub struct DefaultPPGHook<T = ()> {
+    pub(crate) ppo: DefaultPPOHook<T>,
+    pub(crate) reporter: Option<DefaultPPGHookReporter>,
+}
+
+impl<M: AuxiliaryValueLearningModule> PPOHook<M> for DefaultPPGHook<M>
+where
+    DefaultPPOHook<M>: PPOHook<M>,
+{
+    fn before_learning_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+        &mut self,
+        params: &mut PPOParams,
+        module: &mut M,
+        batches: &[B],
+        advantages: &mut Advantages,
+        returns: &mut Returns,
+    ) -> Result<HookResult> {
+        self.ppo
+            .before_learning_hook(params, module, batches, advantages, returns)
+    }
+
+    fn rollout_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+        &mut self,
+        params: &mut PPOParams,
+        module: &mut M,
+        batches: &[B],
+    ) -> Result<HookResult> {
+        self.ppo.rollout_hook(params, module, batches)
+    }
+
+    fn batch_hook(
+        &mut self,
+        params: &mut PPOParams,
+        module: &mut M,
+        losses: &mut <M as LearningModule>::Losses,
+        data: &PPOBatchData<M::LearningTensor>,
+    ) -> Result<HookResult> {
+        self.ppo.batch_hook(params, module, losses, data)
+    }
+
+    fn progress_hook(&mut self, params: &mut PPOParams, progress_remaining: f64) {
+        self.ppo.progress_hook(params, progress_remaining);
+    }
+}
+
+impl<M: AuxiliaryValueLearningModule> PPGHook<M> for DefaultPPGHook<M>
+where
+    DefaultPPOHook<M>: PPOHook<M>,
+{
+    fn auxiliary_batch_hook(
+        &mut self,
+        _params: &mut PPGParams,
+        _module: &mut M,
+        data: &mut PPGAuxiliaryBatchData<M::LearningTensor>,
+    ) -> Result<HookResult> {
+        if let Some(DefaultPPGHookReporter { report, .. }) = &mut self.reporter {
+            report
+                .auxiliary_value_losses
+                .push(data.auxiliary_value_loss.to_vec()[0]);
+            report.kls.push(data.kl.to_vec()[0]);
+            report.value_losses.push(data.value_loss.to_vec()[0]);
+        }
+        if let Some(monitor) = &self.ppo.loss_monitor
+            && !monitor.check(&[&data.joint_loss, &data.value_loss])
+        {
+            return Ok(HookResult::Break);
+        }
+        Ok(HookResult::Continue)
+    }
+
+    fn after_auxiliary_phase_hook(
+        &mut self,
+        _params: &mut PPGParams,
+        _module: &mut M,
+    ) -> Result<HookResult> {
+        if let Some(reporter) = &mut self.reporter {
+            reporter.send_report()?;
+        }
+        Ok(HookResult::Continue)
+    }
+}
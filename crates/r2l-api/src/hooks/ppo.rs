@@ -1,5 +1,6 @@
 use std::{marker::PhantomData, sync::mpsc::Sender};
 
+use anyhow::Result;
 use burn::{grad_clipping::GradientClipping, tensor::backend::AutodiffBackend};
 use candle_core::Tensor;
 use r2l_agents::on_policy_algorithms::{
@@ -14,11 +15,19 @@ use r2l_candle::learning_module::{
     PolicyValueLosses as CandlePolicyValueLosses, PolicyValueModule as CandlePolicyValueModule,
 };
 use r2l_core::{
-    HookResult, buffers::TrajectoryBatch, models::Policy,
-    on_policy::learning_module::OnPolicyLearningModule,
+    HookResult, buffers::TrajectoryBatch, episode::EpisodeMonitor, models::Policy,
+    on_policy::learning_module::OnPolicyLearningModule, tensor::R2lTensor,
 };
 
-use crate::utils::{fmt_stat, mean};
+use crate::{
+    hooks::{
+        diagnostics::{DiagnosticsTracker, TrainingDiagnostics},
+        early_stopping::LossMonitor,
+        schedule::Schedule,
+    },
+    metrics::{MetricsLogger, MetricsReport},
+    utils::{EpisodeReporter, fmt_stat, mean},
+};
 
 /// Per-batch training statistics emitted by the default PPO hook.
 ///
@@ -36,6 +45,8 @@ pub struct PPOBatchStats {
     pub approx_kl: f32,
     /// Value-function loss computed for the batch.
     pub value_loss: f32,
+    /// Fraction of value predictions clipped by the value clip range, when set.
+    pub value_clip_fraction: Option<f32>,
 }
 
 /// Aggregated statistics emitted by the default PPO hook after a learning
@@ -51,12 +62,18 @@ pub struct PPOStats {
     pub batch_stats: Vec<PPOBatchStats>,
     /// Current action-distribution standard deviation when available.
     pub std: Option<f32>,
-    /// Average completed-episode reward observed across the active env set.
+    /// Average raw return of the episodes completed during the rollout.
     pub average_reward: f32,
+    /// Average length of the episodes completed during the rollout.
+    pub average_episode_length: f32,
     /// Current policy optimizer learning rate.
     pub learning_rate: f64,
+    /// Value-function, gradient, entropy and timing diagnostics.
+    pub diagnostics: TrainingDiagnostics,
     /// PPO clip range used during the rollout.
     pub clip_range: f32,
+    /// Value-function clip range used during the rollout, if any.
+    pub clip_range_vf: Option<f32>,
 }
 
 impl PPOStats {
@@ -104,6 +121,27 @@ impl PPOStats {
         )
     }
 
+    /// Returns the mean value clip fraction when value clipping is enabled.
+    pub fn value_clip_fraction(&self) -> Option<f32> {
+        let fractions = self
+            .batch_stats
+            .iter()
+            .filter_map(|s| s.value_clip_fraction)
+            .collect::<Vec<_>>();
+        (!fractions.is_empty()).then(|| mean(&fractions))
+    }
+
+    /// Returns the mean approximate KL divergence across all collected batch stats.
+    pub fn approx_kl(&self) -> f32 {
+        mean(
+            &self
+                .batch_stats
+                .iter()
+                .map(|s| s.approx_kl)
+                .collect::<Vec<_>>(),
+        )
+    }
+
     /// Appends one batch report to this rollout report.
     pub fn collect_batch_data(&mut self, batch_stats: PPOBatchStats) {
         self.batch_stats.push(batch_stats);
@@ -112,8 +150,12 @@ impl PPOStats {
 
 impl std::fmt::Display for PPOStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let rows = [
+        let mut rows = vec![
             ("Average reward", fmt_stat(self.average_reward)),
+            (
+                "Average episode length",
+                fmt_stat(self.average_episode_length),
+            ),
             ("Clip fraction", fmt_stat(self.clip_fraction())),
             ("Policy gradient loss", fmt_stat(self.policy_loss())),
             ("Entropy loss", fmt_stat(self.entropy_loss())),
@@ -124,6 +166,10 @@ impl std::fmt::Display for PPOStats {
                 self.std.map(|std| std.to_string()).unwrap_or("n/a".into()),
             ),
         ];
+        if let Some(value_clip_fraction) = self.value_clip_fraction() {
+            rows.insert(3, ("Value clip fraction", fmt_stat(value_clip_fraction)));
+        }
+        rows.extend(self.diagnostics.rows());
 
         let key_width = rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
 
@@ -138,6 +184,76 @@ impl std::fmt::Display for PPOStats {
     }
 }
 
+impl MetricsReport for PPOStats {
+    fn log_metrics(&self, logger: &MetricsLogger) -> Result<()> {
+        logger.log_scalar("train/policy_loss", self.policy_loss() as f64)?;
+        logger.log_scalar("train/entropy_loss", self.entropy_loss() as f64)?;
+        logger.log_scalar("train/value_loss", self.value_loss() as f64)?;
+        logger.log_scalar("train/approx_kl", self.approx_kl() as f64)?;
+        logger.log_scalar("train/clip_fraction", self.clip_fraction() as f64)?;
+        if let Some(value_clip_fraction) = self.value_clip_fraction() {
+            logger.log_scalar("train/value_clip_fraction", value_clip_fraction as f64)?;
+        }
+        logger.log_scalar("train/learning_rate", self.learning_rate)?;
+        logger.log_scalar("train/clip_range", self.clip_range as f64)?;
+        if let Some(std) = self.std {
+            logger.log_scalar("train/std", std as f64)?;
+        }
+        self.diagnostics.log_metrics(logger)
+    }
+}
+
+/// Clip-range policy applied over the progress of a PPO training run.
+#[deprecated(note = "use `Schedule`, which these variants convert into")]
+#[derive(Debug, Clone, Copy)]
+pub enum ClipRangeSchedule {
+    /// Keep the clip range fixed throughout training.
+    Constant(f32),
+    /// Decay the initial clip range linearly to zero.
+    Linear(f32),
+}
+
+#[allow(deprecated)]
+impl ClipRangeSchedule {
+    /// Returns the clip range for the remaining fraction of training.
+    pub fn value(self, progress_remaining: f64) -> f32 {
+        Schedule::from(self).value(progress_remaining) as f32
+    }
+}
+
+#[allow(deprecated)]
+impl From<ClipRangeSchedule> for Schedule {
+    fn from(schedule: ClipRangeSchedule) -> Self {
+        match schedule {
+            ClipRangeSchedule::Constant(clip_range) => Schedule::constant(f64::from(clip_range)),
+            ClipRangeSchedule::Linear(clip_range) => Schedule::linear(f64::from(clip_range), 0.0),
+        }
+    }
+}
+
+/// Schedules applied by [`DefaultPPOHook`] before each rollout is learned.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PPOSchedules {
+    pub clip_range: Option<Schedule>,
+    pub clip_range_vf: Option<Schedule>,
+    pub entropy_coeff: Option<Schedule>,
+    pub vf_coeff: Option<Schedule>,
+    pub target_kl: Option<Schedule>,
+}
+
+// fraction of the sampled value predictions that moved further than the value clip range
+fn value_clip_fraction<T: R2lTensor>(data: &PPOBatchData<T>, params: &PPOParams) -> Option<f32> {
+    let clip_range_vf = params.clip_range_vf?;
+    let values_pred = data.values_pred.to_vec();
+    let values_old = data.values_old.to_vec();
+    let clipped = values_pred
+        .iter()
+        .zip(&values_old)
+        .filter(|(pred, old)| (**pred - **old).abs() > clip_range_vf)
+        .count();
+    Some(clipped as f32 / values_pred.len() as f32)
+}
+
 pub(crate) struct TargetKl {
     pub target: f32,
     pub target_exceeded: bool,
@@ -153,58 +269,43 @@ pub(crate) struct DefaultPPOHookReporter {
     report: PPOStats,
     tx: Option<Sender<PPOStats>>,
     log_progress: bool,
-    unfinished_episode_rewards: Vec<f32>,
-    latest_average_reward: f32,
+    episodes: EpisodeReporter,
+    logger: Option<MetricsLogger>,
+    diagnostics: DiagnosticsTracker,
 }
 
 impl DefaultPPOHookReporter {
-    pub fn new(tx: Option<Sender<PPOStats>>, log_progress: bool, n_envs: usize) -> Option<Self> {
-        if tx.is_some() || log_progress {
+    pub fn new(
+        tx: Option<Sender<PPOStats>>,
+        log_progress: bool,
+        n_envs: usize,
+        episode_monitor: Option<EpisodeMonitor>,
+        logger: Option<MetricsLogger>,
+    ) -> Option<Self> {
+        if tx.is_some() || log_progress || logger.is_some() {
             Some(Self {
                 report: PPOStats::default(),
                 tx,
                 log_progress,
-                unfinished_episode_rewards: vec![0.; n_envs],
-                latest_average_reward: 0.,
+                logger,
+                episodes: EpisodeReporter::new(n_envs, episode_monitor),
+                diagnostics: DiagnosticsTracker::new(),
             })
         } else {
             None
         }
     }
 
-    fn update_average_reward<T: r2l_core::tensor::R2lTensor, B: TrajectoryBatch<T>>(
+    fn update_episode_stats<T: r2l_core::tensor::R2lTensor, B: TrajectoryBatch<T>>(
         &mut self,
         batches: &[B],
     ) {
-        let mut completed_episode_rewards = vec![];
-        for (running_reward, batch) in self
-            .unfinished_episode_rewards
-            .iter_mut()
-            .zip(batches.iter())
-        {
-            for (reward, done) in batch.rewards().iter().copied().zip(
-                batch
-                    .terminated()
-                    .iter()
-                    .zip(batch.truncated().iter())
-                    .map(|(terminated, truncated)| *terminated || *truncated),
-            ) {
-                *running_reward += reward;
-                if done {
-                    completed_episode_rewards.push(*running_reward);
-                    *running_reward = 0.;
-                }
-            }
-        }
-
-        if !completed_episode_rewards.is_empty() {
-            self.latest_average_reward = completed_episode_rewards.iter().sum::<f32>()
-                / completed_episode_rewards.len() as f32;
-        }
-        self.report.average_reward = self.latest_average_reward;
+        self.episodes.update(batches);
+        self.report.average_reward = self.episodes.average_reward;
+        self.report.average_episode_length = self.episodes.average_length;
     }
 
-    fn send_report(&mut self, rollout_idx: usize) {
+    fn send_report(&mut self, rollout_idx: usize) -> Result<()> {
         let progress = std::mem::replace(
             &mut self.report,
             PPOStats {
@@ -215,10 +316,15 @@ impl DefaultPPOHookReporter {
         if self.log_progress {
             println!("{progress}");
         }
+        if let Some(logger) = &self.logger {
+            logger.log_report(&progress)?;
+        }
         if let Some(tx) = &self.tx {
             tx.send(progress).unwrap();
         }
-        self.report.average_reward = self.latest_average_reward;
+        self.report.average_reward = self.episodes.average_reward;
+        self.report.average_episode_length = self.episodes.average_length;
+        Ok(())
     }
 }
 
@@ -228,7 +334,9 @@ impl DefaultPPOHookReporter {
 /// normalization when enabled, repeated PPO epochs, optional value-loss
 /// weighting, optional entropy regularization, optional gradient clipping,
 /// optional target-KL early stopping, and optional rollout reporting through
-/// [`PPOStats`].
+/// [`PPOStats`]. Clip ranges, coefficients and the target KL can follow a
+/// [`Schedule`] over training progress. With a [`LossMonitor`], a NaN or
+/// infinite loss skips its update and ends the rollout's epochs.
 ///
 /// The generic parameter tracks the concrete learning-module backend and is not
 /// usually named directly by callers.
@@ -237,14 +345,53 @@ pub struct DefaultPPOHook<T = ()> {
     pub(crate) total_epochs: usize,
     pub(crate) entropy_coeff: f32,
     pub(crate) vf_coeff: Option<f32>,
+    pub(crate) schedules: PPOSchedules,
     pub(crate) target_kl: Option<TargetKl>,
     pub(crate) gradient_clipping: Option<f32>,
     pub(crate) current_epoch: usize,
     pub(crate) reporter: Option<DefaultPPOHookReporter>,
     pub(crate) rollout_idx: usize,
+    pub(crate) loss_monitor: Option<LossMonitor>,
     pub(crate) _lm: PhantomData<T>,
 }
 
+impl<T> DefaultPPOHook<T> {
+    fn apply_schedules(&mut self, params: &mut PPOParams, progress_remaining: f64) {
+        let PPOSchedules {
+            clip_range,
+            clip_range_vf,
+            entropy_coeff,
+            vf_coeff,
+            target_kl,
+        } = &self.schedules;
+        let value = |schedule: &Schedule| schedule.value(progress_remaining) as f32;
+        if let Some(clip_range) = clip_range {
+            params.clip_range = value(clip_range);
+        }
+        if let Some(clip_range_vf) = clip_range_vf {
+            params.clip_range_vf = Some(value(clip_range_vf));
+        }
+        if let Some(entropy_coeff) = entropy_coeff {
+            self.entropy_coeff = value(entropy_coeff);
+        }
+        if let Some(vf_coeff) = vf_coeff {
+            self.vf_coeff = Some(value(vf_coeff));
+        }
+        if let Some(target_kl) = target_kl {
+            let target = value(target_kl);
+            match &mut self.target_kl {
+                Some(target_kl) => target_kl.target = target,
+                None => {
+                    self.target_kl = Some(TargetKl {
+                        target,
+                        target_exceeded: false,
+                    })
+                }
+            }
+        }
+    }
+}
+
 impl<B: AutodiffBackend, D: BurnPolicy<B>> PPOHook<BurnPolicyValueModule<B, D>>
     for DefaultPPOHook<BurnPolicyValueModule<B, D>>
 {
@@ -256,10 +403,15 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> PPOHook<BurnPolicyValueModule<B, D>>
         module: &mut BurnPolicyValueModule<B, D>,
         _batches: &[BT],
         advantages: &mut Advantages,
-        _returns: &mut Returns,
+        returns: &mut Returns,
     ) -> anyhow::Result<HookResult> {
         self.current_epoch = 0;
         self.rollout_idx += 1;
+        if let Some(reporter) = &mut self.reporter {
+            reporter.diagnostics.start_learning(advantages, returns);
+            module.collect_grad_norms(true);
+            module.take_grad_norms();
+        }
         if self.normalize_advantage {
             advantages.normalize();
         }
@@ -281,14 +433,23 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> PPOHook<BurnPolicyValueModule<B, D>>
         } else {
             false
         };
-        let should_stop = self.current_epoch == self.total_epochs || target_kl_exceeded;
+        let should_stop = self.current_epoch == self.total_epochs
+            || target_kl_exceeded
+            || self
+                .loss_monitor
+                .as_ref()
+                .is_some_and(LossMonitor::non_finite);
         if should_stop {
             if let Some(reporter) = &mut self.reporter {
-                reporter.update_average_reward(batches);
+                reporter.update_episode_stats(batches);
                 reporter.report.std = module.policy().std().ok();
                 reporter.report.learning_rate = module.policy_learning_rate();
+                reporter.report.diagnostics = reporter
+                    .diagnostics
+                    .finish(module.take_grad_norms(), module.policy().log_std()?);
                 reporter.report.clip_range = params.clip_range;
-                reporter.send_report(self.rollout_idx);
+                reporter.report.clip_range_vf = params.clip_range_vf;
+                reporter.send_report(self.rollout_idx)?;
             }
             Ok(HookResult::Break)
         } else {
@@ -296,6 +457,10 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> PPOHook<BurnPolicyValueModule<B, D>>
         }
     }
 
+    fn progress_hook(&mut self, params: &mut PPOParams, progress_remaining: f64) {
+        self.apply_schedules(params, progress_remaining);
+    }
+
     fn batch_hook(
         &mut self,
         params: &mut PPOParams,
@@ -317,7 +482,13 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> PPOHook<BurnPolicyValueModule<B, D>>
                 / ratio.len() as f32
         };
 
-        if let Some(DefaultPPOHookReporter { report, .. }) = &mut self.reporter {
+        if let Some(DefaultPPOHookReporter {
+            report,
+            diagnostics,
+            ..
+        }) = &mut self.reporter
+        {
+            diagnostics.record_entropy(&module.policy().entropy_per_dimension(&data.observations)?);
             let ratio: Vec<f32> = data.ratio.to_data().to_vec().unwrap();
             let clip_fraction = ratio
                 .iter()
@@ -329,9 +500,15 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> PPOHook<BurnPolicyValueModule<B, D>>
                 policy_loss: losses.policy_loss.to_data().to_vec::<f32>().unwrap()[0],
                 entropy_loss: entropy_loss.to_data().to_vec::<f32>().unwrap()[0],
                 value_loss: losses.value_loss.to_data().to_vec::<f32>().unwrap()[0],
+                value_clip_fraction: value_clip_fraction(data, params),
                 approx_kl,
             });
         }
+        if let Some(monitor) = &self.loss_monitor
+            && !monitor.check(&[&losses.policy_loss, &losses.value_loss, &entropy_loss])
+        {
+            return Ok(HookResult::Break);
+        }
         if self.entropy_coeff != 0. {
             losses.add_entropy_loss(entropy_loss);
         }
@@ -355,10 +532,15 @@ impl PPOHook<CandlePolicyValueModule> for DefaultPPOHook<CandlePolicyValueModule
         module: &mut CandlePolicyValueModule,
         _batches: &[BT],
         advantages: &mut Advantages,
-        _returns: &mut Returns,
+        returns: &mut Returns,
     ) -> anyhow::Result<HookResult> {
         self.rollout_idx += 1;
         self.current_epoch = 0;
+        if let Some(reporter) = &mut self.reporter {
+            reporter.diagnostics.start_learning(advantages, returns);
+            module.collect_grad_norms(true);
+            module.take_grad_norms();
+        }
         if self.normalize_advantage {
             advantages.normalize();
         }
@@ -378,14 +560,23 @@ impl PPOHook<CandlePolicyValueModule> for DefaultPPOHook<CandlePolicyValueModule
         } else {
             false
         };
-        let should_stop = self.current_epoch == self.total_epochs || target_kl_exceeded;
+        let should_stop = self.current_epoch == self.total_epochs
+            || target_kl_exceeded
+            || self
+                .loss_monitor
+                .as_ref()
+                .is_some_and(LossMonitor::non_finite);
         if should_stop {
             if let Some(reporter) = &mut self.reporter {
-                reporter.update_average_reward(batches);
+                reporter.update_episode_stats(batches);
                 reporter.report.std = module.policy().std().ok();
                 reporter.report.learning_rate = module.policy_learning_rate();
+                reporter.report.diagnostics = reporter
+                    .diagnostics
+                    .finish(module.take_grad_norms(), module.policy().log_std()?);
                 reporter.report.clip_range = params.clip_range;
-                reporter.send_report(self.rollout_idx);
+                reporter.report.clip_range_vf = params.clip_range_vf;
+                reporter.send_report(self.rollout_idx)?;
             }
             Ok(HookResult::Break)
         } else {
@@ -393,6 +584,10 @@ impl PPOHook<CandlePolicyValueModule> for DefaultPPOHook<CandlePolicyValueModule
         }
     }
 
+    fn progress_hook(&mut self, params: &mut PPOParams, progress_remaining: f64) {
+        self.apply_schedules(params, progress_remaining);
+    }
+
     fn batch_hook(
         &mut self,
         params: &mut PPOParams,
@@ -411,7 +606,13 @@ impl PPOHook<CandlePolicyValueModule> for DefaultPPOHook<CandlePolicyValueModule
             .sub(&log_ratio)?
             .mean_all()?
             .to_scalar::<f32>()?;
-        if let Some(DefaultPPOHookReporter { report, .. }) = &mut self.reporter {
+        if let Some(DefaultPPOHookReporter {
+            report,
+            diagnostics,
+            ..
+        }) = &mut self.reporter
+        {
+            diagnostics.record_entropy(&module.policy().entropy_per_dimension(&data.observations)?);
             let clip_fraction = (&data.ratio - 1.)?
                 .abs()?
                 .gt(params.clip_range)?
@@ -423,9 +624,15 @@ impl PPOHook<CandlePolicyValueModule> for DefaultPPOHook<CandlePolicyValueModule
                 policy_loss: losses.policy_loss.to_scalar()?,
                 entropy_loss: entropy_loss.to_scalar()?,
                 value_loss: losses.value_loss.to_scalar()?,
+                value_clip_fraction: value_clip_fraction(data, params),
                 approx_kl,
             });
         }
+        if let Some(monitor) = &self.loss_monitor
+            && !monitor.check(&[&losses.policy_loss, &losses.value_loss, &entropy_loss])
+        {
+            return Ok(HookResult::Break);
+        }
         if self.entropy_coeff != 0. {
             losses.add_entropy_loss(entropy_loss)?;
         }
@@ -441,3 +648,314 @@ impl PPOHook<CandlePolicyValueModule> for DefaultPPOHook<CandlePolicyValueModule
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use anyhow::Result;
+    use candle_core::Device;
+    use r2l_agents::on_policy_algorithms::ppo::{PPO, PPOBatchData, PPOHook, PPOParams};
+    use r2l_core::{
+        HookResult,
+        buffers::{Memory, TrajectoryBatch, buffer::TrajectoryBuffer},
+        env::Space,
+        init::WeightInit,
+        models::{ActivationFunction, LearningModule, OptimizerConfig},
+        on_policy::{
+            algorithm::Agent,
+            learning_module::{OnPolicyLearningModule, TrustRegionLearningModule},
+        },
+        tensor::{R2lTensor, TensorData},
+    };
+
+    use super::DefaultPPOHook;
+    use crate::{
+        BurnBackend,
+        builders::{
+            learning_module::{OnPolicyLearningModuleBuilder, OnPolicyLearningModuleType},
+            ppo::hook::DefaultPPOHookBuilder,
+        },
+        hooks::early_stopping::LossMonitor,
+    };
+
+    pub(crate) const OBSERVATION_SIZE: usize = 4;
+
+    // Counts the batches and epochs the default hook let through.
+    #[derive(Default)]
+    struct Counter {
+        batches: usize,
+        epochs: usize,
+    }
+
+    impl<M: OnPolicyLearningModule> PPOHook<M> for Counter {
+        fn rollout_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+            &mut self,
+            _params: &mut PPOParams,
+            _module: &mut M,
+            _batches: &[B],
+        ) -> Result<HookResult> {
+            self.epochs += 1;
+            Ok(HookResult::Continue)
+        }
+
+        fn batch_hook(
+            &mut self,
+            _params: &mut PPOParams,
+            _module: &mut M,
+            _losses: &mut <M as LearningModule>::Losses,
+            _data: &PPOBatchData<M::LearningTensor>,
+        ) -> Result<HookResult> {
+            self.batches += 1;
+            Ok(HookResult::Continue)
+        }
+    }
+
+    // Checks every batch's clipped value predictions against the clipping
+    // range, and counts the predictions that moved out of it.
+    #[derive(Default)]
+    struct ValueClipping {
+        batches: usize,
+        clipped: usize,
+    }
+
+    impl<M: OnPolicyLearningModule> PPOHook<M> for ValueClipping {
+        fn batch_hook(
+            &mut self,
+            params: &mut PPOParams,
+            _module: &mut M,
+            _losses: &mut <M as LearningModule>::Losses,
+            data: &PPOBatchData<M::LearningTensor>,
+        ) -> Result<HookResult> {
+            self.batches += 1;
+            let Some(clip_range_vf) = params.clip_range_vf else {
+                assert!(data.values_clipped.is_none());
+                return Ok(HookResult::Continue);
+            };
+            let clipped = data.values_clipped.as_ref().unwrap().to_vec();
+            let predicted = data.values_pred.to_vec();
+            let old = data.values_old.to_vec();
+            for ((clipped, predicted), old) in clipped.into_iter().zip(predicted).zip(old) {
+                let change = predicted - old;
+                let expected = old + change.clamp(-clip_range_vf, clip_range_vf);
+                assert!((clipped - expected).abs() < 1e-5);
+                assert!((clipped - old).abs() <= clip_range_vf + 1e-5);
+                if change.abs() > clip_range_vf {
+                    self.clipped += 1;
+                }
+            }
+            Ok(HookResult::Continue)
+        }
+    }
+
+    pub(crate) fn builder() -> OnPolicyLearningModuleBuilder {
+        OnPolicyLearningModuleBuilder {
+            shared_hidden_layers: vec![],
+            policy_hidden_layers: vec![16],
+            value_hidden_layers: vec![16],
+            activation_function: ActivationFunction::default(),
+            log_std_init: 0.0,
+            weight_init: WeightInit::default(),
+            learning_module_type: OnPolicyLearningModuleType::Joint {
+                max_grad_norm: None,
+                optimizer: OptimizerConfig::adam(1e-2),
+            },
+        }
+    }
+
+    // 32 steps of episodes ending every 8 steps. A NaN reward at step 5
+    // makes every advantage, and so every policy loss, NaN.
+    pub(crate) fn rollout<T: R2lTensor>(reward_at_5: f32) -> TrajectoryBuffer<T> {
+        let observation = |step: usize| {
+            let step = step as f32;
+            T::from_vec_and_shape(
+                vec![step / 8., (step * 0.3).cos(), (step * 0.7).sin(), 1.],
+                vec![OBSERVATION_SIZE],
+            )
+        };
+        let mut buffer = TrajectoryBuffer::default();
+        for step in 0..32 {
+            let action = if step % 3 == 0 { [1., 0.] } else { [0., 1.] };
+            buffer.push(Memory {
+                state: observation(step % 8),
+                next_state: observation((step + 1) % 8),
+                action: T::from_vec_and_shape(action.to_vec(), vec![2]),
+                reward: if step == 5 { reward_at_5 } else { 1. },
+                terminated: step % 8 == 7,
+                truncated: false,
+            });
+        }
+        buffer
+    }
+
+    // Four batches per epoch over ten epochs, all aborted by the first
+    // non-finite loss.
+    fn check_loss_monitor_break<M: TrustRegionLearningModule>(lm: M) -> Result<()>
+    where
+        DefaultPPOHook<M>: PPOHook<M>,
+    {
+        let monitor = LossMonitor::new();
+        let hooks = DefaultPPOHookBuilder::new(1)
+            .with_log_progress(false)
+            .with_total_epochs(10)
+            .with_loss_monitor(Some(monitor.clone()))
+            .build::<M>();
+        let mut ppo = PPO {
+            params: PPOParams {
+                sample_size: 8,
+                ..Default::default()
+            },
+            lm,
+            hooks,
+        }
+        .chain_hook(Counter::default());
+
+        let rollout = [rollout::<M::InferenceTensor>(f32::NAN)];
+        let views = rollout
+            .iter()
+            .map(TrajectoryBuffer::to_trajectory_view)
+            .collect::<Vec<_>>();
+        let parameters = ppo.lm.policy_parameters()?;
+        Agent::learn(&mut ppo, &views)?;
+        assert!(monitor.non_finite());
+        assert_eq!(ppo.hooks.1.batches, 1);
+        assert_eq!(ppo.hooks.1.epochs, 1);
+        assert_eq!(ppo.lm.policy_parameters()?, parameters);
+        Ok(())
+    }
+
+    // Ten epochs move the value predictions well past a tight clipping
+    // range, while no clipping range leaves them unclipped.
+    fn check_value_clipping<M: TrustRegionLearningModule>(
+        lm: M,
+        clip_range_vf: Option<f32>,
+    ) -> Result<()>
+    where
+        DefaultPPOHook<M>: PPOHook<M>,
+    {
+        let hooks = DefaultPPOHookBuilder::new(1)
+            .with_log_progress(false)
+            .with_total_epochs(10)
+            .build::<M>();
+        let mut ppo = PPO {
+            params: PPOParams {
+                sample_size: 8,
+                clip_range_vf,
+                ..Default::default()
+            },
+            lm,
+            hooks,
+        }
+        .chain_hook(ValueClipping::default());
+
+        let rollout = [rollout::<M::InferenceTensor>(1.)];
+        let views = rollout
+            .iter()
+            .map(TrajectoryBuffer::to_trajectory_view)
+            .collect::<Vec<_>>();
+        Agent::learn(&mut ppo, &views)?;
+        let checks = &ppo.hooks.1;
+        assert_eq!(checks.batches, 40);
+        assert_eq!(checks.clipped > 0, clip_range_vf.is_some());
+        Ok(())
+    }
+
+    pub(crate) fn action_space() -> Space<TensorData> {
+        Space::Discrete(2)
+    }
+
+    #[test]
+    fn candle_non_finite_loss_aborts_the_batch_loop() -> Result<()> {
+        check_loss_monitor_break(builder().build_candle(
+            OBSERVATION_SIZE,
+            action_space(),
+            &Device::Cpu,
+        )?)
+    }
+
+    #[test]
+    fn burn_non_finite_loss_aborts_the_batch_loop() -> Result<()> {
+        check_loss_monitor_break(
+            builder().build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?,
+        )
+    }
+
+    #[test]
+    fn candle_value_predictions_are_clipped() -> Result<()> {
+        for clip_range_vf in [Some(1e-3), None] {
+            let lm = builder().build_candle(OBSERVATION_SIZE, action_space(), &Device::Cpu)?;
+            check_value_clipping(lm, clip_range_vf)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn burn_value_predictions_are_clipped() -> Result<()> {
+        for clip_range_vf in [Some(1e-3), None] {
+            let lm = builder().build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?;
+            check_value_clipping(lm, clip_range_vf)?;
+        }
+        Ok(())
+    }
+
+    // Counts batches without overriding `rollout_hook`.
+    #[derive(Default)]
+    struct Batches(usize);
+
+    impl<M: OnPolicyLearningModule> PPOHook<M> for Batches {
+        fn batch_hook(
+            &mut self,
+            _params: &mut PPOParams,
+            _module: &mut M,
+            _losses: &mut <M as LearningModule>::Losses,
+            _data: &PPOBatchData<M::LearningTensor>,
+        ) -> Result<HookResult> {
+            self.0 += 1;
+            Ok(HookResult::Continue)
+        }
+    }
+
+    // A chained hook that only overrides `batch_hook` sees all ten epochs of
+    // four batches the default hook runs.
+    fn check_chained_batch_hook<M: TrustRegionLearningModule>(lm: M) -> Result<()>
+    where
+        DefaultPPOHook<M>: PPOHook<M>,
+    {
+        let hooks = DefaultPPOHookBuilder::new(1)
+            .with_log_progress(false)
+            .with_total_epochs(10)
+            .build::<M>();
+        let mut ppo = PPO {
+            params: PPOParams {
+                sample_size: 8,
+                ..Default::default()
+            },
+            lm,
+            hooks,
+        }
+        .chain_hook(Batches::default());
+
+        let rollout = [rollout::<M::InferenceTensor>(1.)];
+        let views = rollout
+            .iter()
+            .map(TrajectoryBuffer::to_trajectory_view)
+            .collect::<Vec<_>>();
+        Agent::learn(&mut ppo, &views)?;
+        assert_eq!(ppo.hooks.1.0, 40);
+        Ok(())
+    }
+
+    #[test]
+    fn candle_chained_batch_hooks_keep_every_epoch() -> Result<()> {
+        check_chained_batch_hook(builder().build_candle(
+            OBSERVATION_SIZE,
+            action_space(),
+            &Device::Cpu,
+        )?)
+    }
+
+    #[test]
+    fn burn_chained_batch_hooks_keep_every_epoch() -> Result<()> {
+        check_chained_batch_hook(
+            builder().build_burn::<BurnBackend, _>(OBSERVATION_SIZE, action_space())?,
+        )
+    }
+}
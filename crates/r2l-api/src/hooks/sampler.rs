@@ -2,12 +2,10 @@ use std::marker::PhantomData;
 
 use r2l_core::{env::Env, tensor::R2lTensor};
 use r2l_sampler::{
-    NormalizedSamplerHook, R2lNormalizedSamplerCore, R2lSamplerCore, RolloutMode, SamplerHook,
-    SamplerHookResult,
+    NormalizedSamplerHook, R2lNormalizedSamplerCore, R2lSamplerCore, RewardNormalizer, RolloutMode,
+    SamplerHook, SamplerHookResult,
 };
 
-use crate::utils::RewardNormalizer;
-
 /// Sampler hook that requests rollout collection until a fixed number of
 /// episodes has been scheduled.
 ///
@@ -63,7 +61,8 @@ impl<E: Env<Tensor: R2lTensor>> NormalizedSamplerHook for EpisodeBoundHook<E> {
 /// has been scheduled.
 ///
 /// When configured with a reward normalizer, the hook normalizes a completed
-/// rollout before handing it to the agent.
+/// rollout of [`R2lSampler`](r2l_sampler::R2lSampler) before handing it to the
+/// agent. Normalized samplers normalize rewards themselves.
 pub struct StepBoundHook<E: Env<Tensor: R2lTensor>> {
     num_steps: usize,
     steps_scheduled: usize,
@@ -103,7 +102,7 @@ impl<E: Env<Tensor: R2lTensor>> SamplerHook for StepBoundHook<E> {
             && let Some(normalizer) = &mut self.reward_normalizer
         {
             let mut buffers = core.buffers.lock().unwrap();
-            normalizer.normalize(&mut buffers);
+            normalizer.normalize_buffers(&mut buffers);
         }
         self.next_result()
     }
@@ -119,19 +118,11 @@ impl<E: Env<Tensor: R2lTensor>> SamplerHook for StepBoundHook<E> {
 impl<E: Env<Tensor: R2lTensor>> NormalizedSamplerHook for StepBoundHook<E> {
     type E = E;
 
-    fn hook(&mut self, core: &mut R2lNormalizedSamplerCore<Self::E>) -> SamplerHookResult {
-        if self.steps_scheduled == self.num_steps
-            && let Some(normalizer) = &mut self.reward_normalizer
-        {
-            normalizer.normalize(&mut core.buffers);
-        }
+    fn hook(&mut self, _core: &mut R2lNormalizedSamplerCore<Self::E>) -> SamplerHookResult {
         self.next_result()
     }
 
     fn reset(&mut self) {
         self.steps_scheduled = 0;
-        if let Some(normalizer) = &mut self.reward_normalizer {
-            normalizer.reset_returns();
-        }
     }
 }
@@ -0,0 +1,259 @@
+use std::{f64::consts::PI, fmt, sync::Arc};
+
+use anyhow::{Result, bail};
+
+/// Hyperparameter value driven by the progress of a training run.
+///
+/// Schedules are evaluated with the remaining fraction of training, which
+/// goes from `1.0` at the start of training to `0.0` at the end, the same
+/// convention as Stable-Baselines3. The shapes below are described in terms
+/// of the elapsed fraction `progress = 1.0 - progress_remaining`, except for
+/// the breakpoints of [`piecewise`](Self::piecewise), which are given in
+/// `progress_remaining` like the argument of [`value`](Self::value).
+///
+/// The default hooks use schedules for the learning rate and for PPO's clip
+/// ranges, entropy coefficient, value-function coefficient and target KL.
+#[derive(Clone)]
+pub struct Schedule(ScheduleKind);
+
+#[derive(Clone)]
+enum ScheduleKind {
+    Constant(f64),
+    Linear {
+        start: f64,
+        end: f64,
+    },
+    Cosine {
+        start: f64,
+        end: f64,
+    },
+    Exponential {
+        start: f64,
+        decay_rate: f64,
+    },
+    Piecewise(Vec<(f64, f64)>),
+    WarmupDecay {
+        warmup_fraction: f64,
+        peak: f64,
+        end: f64,
+    },
+    Fn(Arc<dyn Fn(f64) -> f64 + Send + Sync>),
+}
+
+impl Schedule {
+    /// Keeps the value fixed throughout training.
+    pub fn constant(value: f64) -> Self {
+        Self(ScheduleKind::Constant(value))
+    }
+
+    /// Interpolates linearly from `start` to `end`.
+    pub fn linear(start: f64, end: f64) -> Self {
+        Self(ScheduleKind::Linear { start, end })
+    }
+
+    /// Anneals from `start` to `end` along half a cosine period.
+    pub fn cosine(start: f64, end: f64) -> Self {
+        Self(ScheduleKind::Cosine { start, end })
+    }
+
+    /// Decays as `start * decay_rate^progress`, reaching `start * decay_rate`
+    /// at the end of training.
+    pub fn exponential(start: f64, decay_rate: f64) -> Self {
+        Self(ScheduleKind::Exponential { start, decay_rate })
+    }
+
+    /// Interpolates linearly between `(progress_remaining, value)`
+    /// breakpoints, so `(1.0, value)` is the start of training and
+    /// `(0.0, value)` its end.
+    ///
+    /// The value is held constant beyond the outermost breakpoints. Fails if
+    /// `points` is empty.
+    pub fn piecewise(mut points: Vec<(f64, f64)>) -> Result<Self> {
+        if points.is_empty() {
+            bail!("piecewise schedule needs at least one point");
+        }
+        points.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Ok(Self(ScheduleKind::Piecewise(points)))
+    }
+
+    /// Warms up linearly from zero to `peak` over the first `warmup_fraction`
+    /// of training, then anneals to `end` along half a cosine period.
+    pub fn warmup_decay(warmup_fraction: f64, peak: f64, end: f64) -> Self {
+        Self(ScheduleKind::WarmupDecay {
+            warmup_fraction: warmup_fraction.clamp(0.0, 1.0),
+            peak,
+            end,
+        })
+    }
+
+    /// Uses a closure of the remaining fraction of training.
+    pub fn from_fn(f: impl Fn(f64) -> f64 + Send + Sync + 'static) -> Self {
+        Self(ScheduleKind::Fn(Arc::new(f)))
+    }
+
+    /// Returns the value for the remaining fraction of training.
+    pub fn value(&self, progress_remaining: f64) -> f64 {
+        let progress_remaining = progress_remaining.clamp(0.0, 1.0);
+        let progress = 1.0 - progress_remaining;
+        match &self.0 {
+            ScheduleKind::Constant(value) => *value,
+            ScheduleKind::Linear { start, end } => start + (end - start) * progress,
+            ScheduleKind::Cosine { start, end } => cosine(*start, *end, progress),
+            ScheduleKind::Exponential { start, decay_rate } => start * decay_rate.powf(progress),
+            ScheduleKind::Piecewise(points) => piecewise(points, progress_remaining),
+            ScheduleKind::WarmupDecay {
+                warmup_fraction,
+                peak,
+                end,
+            } => {
+                if progress < *warmup_fraction {
+                    peak * progress / warmup_fraction
+                } else if *warmup_fraction >= 1.0 {
+                    *peak
+                } else {
+                    let decay_progress = (progress - warmup_fraction) / (1.0 - warmup_fraction);
+                    cosine(*peak, *end, decay_progress)
+                }
+            }
+            ScheduleKind::Fn(f) => f(progress_remaining),
+        }
+    }
+
+    /// Returns the value at the start of training.
+    pub fn initial_value(&self) -> f64 {
+        self.value(1.0)
+    }
+}
+
+impl From<f64> for Schedule {
+    fn from(value: f64) -> Self {
+        Self::constant(value)
+    }
+}
+
+impl fmt::Debug for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            ScheduleKind::Constant(value) => f.debug_tuple("Constant").field(value).finish(),
+            ScheduleKind::Linear { start, end } => f
+                .debug_struct("Linear")
+                .field("start", start)
+                .field("end", end)
+                .finish(),
+            ScheduleKind::Cosine { start, end } => f
+                .debug_struct("Cosine")
+                .field("start", start)
+                .field("end", end)
+                .finish(),
+            ScheduleKind::Exponential { start, decay_rate } => f
+                .debug_struct("Exponential")
+                .field("start", start)
+                .field("decay_rate", decay_rate)
+                .finish(),
+            ScheduleKind::Piecewise(points) => f.debug_tuple("Piecewise").field(points).finish(),
+            ScheduleKind::WarmupDecay {
+                warmup_fraction,
+                peak,
+                end,
+            } => f
+                .debug_struct("WarmupDecay")
+                .field("warmup_fraction", warmup_fraction)
+                .field("peak", peak)
+                .field("end", end)
+                .finish(),
+            ScheduleKind::Fn(_) => f.write_str("Fn"),
+        }
+    }
+}
+
+fn cosine(start: f64, end: f64, progress: f64) -> f64 {
+    end + (start - end) * 0.5 * (1.0 + (PI * progress).cos())
+}
+
+// `points` are sorted by their remaining progress.
+fn piecewise(points: &[(f64, f64)], progress_remaining: f64) -> f64 {
+    let (first_remaining, first_value) = points[0];
+    if progress_remaining <= first_remaining {
+        return first_value;
+    }
+    for window in points.windows(2) {
+        let [(p0, v0), (p1, v1)] = [window[0], window[1]];
+        if progress_remaining <= p1 {
+            if p1 == p0 {
+                return v1;
+            }
+            return v0 + (v1 - v0) * (progress_remaining - p0) / (p1 - p0);
+        }
+    }
+    points[points.len() - 1].1
+}
+
+#[cfg(test)]
+mod test {
+    use super::Schedule;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn schedules_match_their_endpoints() {
+        let schedules = [
+            (Schedule::constant(0.2), 0.2, 0.2),
+            (Schedule::linear(3e-4, 0.0), 3e-4, 0.0),
+            (Schedule::cosine(1.0, 0.1), 1.0, 0.1),
+            (Schedule::exponential(1.0, 0.01), 1.0, 0.01),
+            (Schedule::warmup_decay(0.1, 1.0, 0.0), 0.0, 0.0),
+            (Schedule::from_fn(|remaining| 2.0 * remaining), 2.0, 0.0),
+        ];
+        for (schedule, start, end) in schedules {
+            assert_close(schedule.value(1.0), start);
+            assert_close(schedule.value(0.0), end);
+        }
+    }
+
+    #[test]
+    fn schedules_interpolate() {
+        assert_close(Schedule::linear(1.0, 0.0).value(0.25), 0.25);
+        assert_close(Schedule::cosine(1.0, 0.0).value(0.5), 0.5);
+        assert_close(Schedule::exponential(1.0, 0.25).value(0.5), 0.5);
+        let warmup = Schedule::warmup_decay(0.2, 1.0, 0.0);
+        assert_close(warmup.value(0.9), 0.5);
+        assert_close(warmup.value(0.8), 1.0);
+        assert_close(warmup.value(0.4), 0.5);
+    }
+
+    #[test]
+    fn piecewise_holds_outside_breakpoints() {
+        let schedule = Schedule::piecewise(vec![(0.5, 0.0), (0.75, 1.0)]).unwrap();
+        assert_close(schedule.value(1.0), 1.0);
+        assert_close(schedule.value(0.625), 0.5);
+        assert_close(schedule.value(0.0), 0.0);
+    }
+
+    #[test]
+    fn piecewise_breakpoints_are_in_remaining_progress() {
+        let schedule = Schedule::piecewise(vec![(1.0, 0.01), (0.5, 0.0)]).unwrap();
+        assert_close(schedule.initial_value(), 0.01);
+        assert_close(schedule.value(0.75), 0.005);
+        assert_close(schedule.value(0.25), 0.0);
+    }
+
+    #[test]
+    fn empty_piecewise_schedules_are_rejected() {
+        assert!(Schedule::piecewise(vec![]).is_err());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_schedules_convert() {
+        use crate::{ClipRangeSchedule, LearningRateSchedule};
+
+        let learning_rate = Schedule::from(LearningRateSchedule::Linear(1e-3));
+        assert_close(learning_rate.value(0.5), 5e-4);
+        assert_close(LearningRateSchedule::Constant(1e-3).value(0.5), 1e-3);
+        let clip_range = Schedule::from(ClipRangeSchedule::Linear(0.5));
+        assert_close(clip_range.value(0.5), 0.25);
+        assert_eq!(ClipRangeSchedule::Constant(0.2).value(0.0), 0.2);
+    }
+}
@@ -0,0 +1,250 @@
+use std::{marker::PhantomData, sync::mpsc::Sender};
+
+use anyhow::Result;
+use r2l_agents::on_policy_algorithms::{
+    Advantages, Returns,
+    trpo::{TRPOHook, TRPOParams, TRPOPolicyUpdate},
+};
+use r2l_core::{
+    HookResult, buffers::TrajectoryBatch, episode::EpisodeMonitor, models::Policy,
+    on_policy::learning_module::TrustRegionLearningModule, tensor::R2lTensor,
+};
+
+use crate::{
+    hooks::early_stopping::LossMonitor,
+    metrics::{MetricsLogger, MetricsReport},
+    utils::{EpisodeReporter, fmt_stat, mean},
+};
+
+/// Aggregated statistics emitted by the default TRPO hook after a learning pass.
+#[derive(Default, Debug, Clone)]
+pub struct TRPOStats {
+    /// Rollout index to which the stats belong to
+    pub rollout_idx: usize,
+    /// Surrogate objective before the policy update.
+    pub objective_before: f32,
+    /// Surrogate objective after the policy update.
+    pub objective_after: f32,
+    /// Estimated KL divergence between the old and the updated policy.
+    pub kl: f32,
+    /// Number of line-search steps tried.
+    pub line_search_steps: usize,
+    /// Whether the line search accepted a step.
+    pub accepted: bool,
+    /// Value-function losses of every value minibatch.
+    pub value_losses: Vec<f32>,
+    /// Current action-distribution standard deviation when available.
+    pub std: Option<f32>,
+    /// Average raw return of the episodes completed during the rollout.
+    pub average_reward: f32,
+    /// Average length of the episodes completed during the rollout.
+    pub average_episode_length: f32,
+}
+
+impl TRPOStats {
+    /// Improvement of the surrogate objective made by the policy update.
+    pub fn objective_improvement(&self) -> f32 {
+        self.objective_after - self.objective_before
+    }
+
+    pub fn value_loss(&self) -> f32 {
+        mean(&self.value_losses)
+    }
+}
+
+impl std::fmt::Display for TRPOStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = [
+            ("Average reward", fmt_stat(self.average_reward)),
+            (
+                "Average episode length",
+                fmt_stat(self.average_episode_length),
+            ),
+            (
+                "Objective improvement",
+                fmt_stat(self.objective_improvement()),
+            ),
+            ("KL divergence", fmt_stat(self.kl)),
+            ("Line search steps", self.line_search_steps.to_string()),
+            ("Step accepted", self.accepted.to_string()),
+            ("Value loss", fmt_stat(self.value_loss())),
+            (
+                "Standard deviation",
+                self.std.map(|std| std.to_string()).unwrap_or("n/a".into()),
+            ),
+        ];
+
+        let key_width = rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+
+        writeln!(f, "TRPO stats (rollout {})", self.rollout_idx)?;
+        writeln!(f, "{:-<1$}", "", key_width + 15)?;
+
+        for (key, value) in rows {
+            writeln!(f, "{key:<key_width$} | {value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl MetricsReport for TRPOStats {
+    fn log_metrics(&self, logger: &MetricsLogger) -> Result<()> {
+        logger.log_scalar(
+            "train/objective_improvement",
+            self.objective_improvement() as f64,
+        )?;
+        logger.log_scalar("train/kl", self.kl as f64)?;
+        logger.log_scalar("train/line_search_steps", self.line_search_steps as f64)?;
+        logger.log_scalar("train/step_accepted", f64::from(u8::from(self.accepted)))?;
+        logger.log_scalar("train/value_loss", self.value_loss() as f64)?;
+        if let Some(std) = self.std {
+            logger.log_scalar("train/std", std as f64)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct DefaultTRPOHookReporter {
+    pub(crate) rollout_idx: usize,
+    pub(crate) report: TRPOStats,
+    pub(crate) tx: Option<Sender<TRPOStats>>,
+    pub(crate) log_progress: bool,
+    pub(crate) episodes: EpisodeReporter,
+    pub(crate) logger: Option<MetricsLogger>,
+}
+
+impl DefaultTRPOHookReporter {
+    pub fn new(
+        tx: Option<Sender<TRPOStats>>,
+        log_progress: bool,
+        n_envs: usize,
+        episode_monitor: Option<EpisodeMonitor>,
+        logger: Option<MetricsLogger>,
+    ) -> Option<Self> {
+        if tx.is_some() || log_progress || logger.is_some() {
+            Some(Self {
+                rollout_idx: 0,
+                report: TRPOStats::default(),
+                tx,
+                log_progress,
+                logger,
+                episodes: EpisodeReporter::new(n_envs, episode_monitor),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn send_report(&mut self) -> Result<()> {
+        self.rollout_idx += 1;
+        let progress = std::mem::replace(
+            &mut self.report,
+            TRPOStats {
+                rollout_idx: self.rollout_idx,
+                ..Default::default()
+            },
+        );
+        if self.log_progress {
+            println!("{progress}");
+        }
+        if let Some(logger) = &self.logger {
+            logger.log_report(&progress)?;
+        }
+        if let Some(tx) = &self.tx {
+            tx.send(progress).unwrap();
+        }
+        self.report.average_reward = self.episodes.average_reward;
+        self.report.average_episode_length = self.episodes.average_length;
+        Ok(())
+    }
+
+    fn update_episode_stats<T: R2lTensor, B: TrajectoryBatch<T>>(&mut self, batches: &[B]) {
+        self.episodes.update(batches);
+        self.report.average_reward = self.episodes.average_reward;
+        self.report.average_episode_length = self.episodes.average_length;
+    }
+}
+
+/// Default training hook used by [`TRPOAgentBuilder`](crate::TRPOAgentBuilder).
+///
+/// This hook normalizes advantages when enabled and reports the outcome of
+/// each policy update through [`TRPOStats`]. With a [`LossMonitor`], a NaN or
+/// infinite policy objective ends the rollout's learning and a non-finite
+/// value loss skips its update.
+///
+/// The generic parameter tracks the concrete learning-module backend and is not
+/// usually named directly by callers.
+pub struct DefaultTRPOHook<T = ()> {
+    pub(crate) normalize_advantage: bool,
+    pub(crate) reporter: Option<DefaultTRPOHookReporter>,
+    pub(crate) loss_monitor: Option<LossMonitor>,
+    pub(crate) _lm: PhantomData<T>,
+}
+
+impl<M: TrustRegionLearningModule> TRPOHook<M> for DefaultTRPOHook<M> {
+    fn before_learning_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+        &mut self,
+        _params: &mut TRPOParams,
+        _module: &mut M,
+        _batches: &[B],
+        advantages: &mut Advantages,
+        _returns: &mut Returns,
+    ) -> Result<HookResult> {
+        if self.normalize_advantage {
+            advantages.normalize();
+        }
+        Ok(HookResult::Continue)
+    }
+
+    fn policy_update_hook(
+        &mut self,
+        _params: &mut TRPOParams,
+        _module: &mut M,
+        update: &TRPOPolicyUpdate,
+    ) -> Result<HookResult> {
+        if let Some(DefaultTRPOHookReporter { report, .. }) = &mut self.reporter {
+            report.objective_before = update.objective_before;
+            report.objective_after = update.objective_after;
+            report.kl = update.kl;
+            report.line_search_steps = update.line_search_steps;
+            report.accepted = update.accepted;
+        }
+        if let Some(monitor) = &self.loss_monitor
+            && !monitor.check_values(&[update.objective_before])
+        {
+            return Ok(HookResult::Break);
+        }
+        Ok(HookResult::Continue)
+    }
+
+    fn value_batch_hook(
+        &mut self,
+        _params: &mut TRPOParams,
+        _module: &mut M,
+        value_loss: &mut M::LearningTensor,
+    ) -> Result<HookResult> {
+        if let Some(DefaultTRPOHookReporter { report, .. }) = &mut self.reporter {
+            report.value_losses.push(value_loss.to_vec()[0]);
+        }
+        if let Some(monitor) = &self.loss_monitor
+            && !monitor.check(&[&*value_loss])
+        {
+            return Ok(HookResult::Break);
+        }
+        Ok(HookResult::Continue)
+    }
+
+    fn after_learning_hook<B: TrajectoryBatch<M::InferenceTensor>>(
+        &mut self,
+        _params: &mut TRPOParams,
+        module: &mut M,
+        batches: &[B],
+    ) -> Result<HookResult> {
+        if let Some(reporter) = &mut self.reporter {
+            reporter.update_episode_stats(batches);
+            reporter.report.std = module.policy().std().ok();
+            reporter.send_report()?;
+        }
+        Ok(HookResult::Continue)
+    }
+}
@@ -0,0 +1,358 @@
+use std::{marker::PhantomData, sync::mpsc::Sender};
+
+use anyhow::Result;
+use burn::{grad_clipping::GradientClipping, tensor::backend::AutodiffBackend};
+use candle_core::Tensor;
+use r2l_agents::on_policy_algorithms::{
+    Advantages, Returns,
+    vpg::{VPGBatchData, VPGHook, VPGParams},
+};
+use r2l_burn::learning_module::{
+    BurnPolicy, PolicyValueLosses as BurnPolicyValueLosses,
+    PolicyValueModule as BurnPolicyValueModule,
+};
+use r2l_candle::learning_module::{
+    PolicyValueLosses as CandlePolicyValueLosses, PolicyValueModule as CandlePolicyValueModule,
+};
+use r2l_core::{
+    HookResult, buffers::TrajectoryBatch, episode::EpisodeMonitor, models::Policy,
+    on_policy::learning_module::OnPolicyLearningModule,
+};
+
+use crate::{
+    hooks::early_stopping::LossMonitor,
+    metrics::{MetricsLogger, MetricsReport},
+    utils::{EpisodeReporter, fmt_stat, mean},
+};
+
+/// Per-batch training statistics emitted by the default VPG hook.
+///
+/// Each value corresponds to a single optimization batch processed during one
+/// VPG learning pass.
+#[derive(Debug, Clone)]
+pub struct VPGBatchStats {
+    /// Entropy regularization term computed for the batch.
+    pub entropy_loss: f32,
+    /// Policy-gradient loss computed for the batch.
+    pub policy_loss: f32,
+    /// Value-function loss computed for the batch.
+    pub value_loss: f32,
+}
+
+/// Aggregated statistics emitted by the default VPG hook after a learning pass.
+///
+/// A report contains all collected [`VPGBatchStats`] for the rollout together
+/// with rollout-level summaries such as average reward and learning rate.
+#[derive(Default, Debug, Clone)]
+pub struct VPGStats {
+    /// Rollout index to which the stats belong to
+    pub rollout_idx: usize,
+    /// Batch-level statistics collected during the most recent learning pass.
+    pub batch_stats: Vec<VPGBatchStats>,
+    /// Current action-distribution standard deviation when available.
+    pub std: Option<f32>,
+    /// Average raw return of the episodes completed during the rollout.
+    pub average_reward: f32,
+    /// Average length of the episodes completed during the rollout.
+    pub average_episode_length: f32,
+    /// Current policy optimizer learning rate.
+    pub learning_rate: f64,
+}
+
+impl VPGStats {
+    pub fn entropy_loss(&self) -> f32 {
+        mean(
+            &self
+                .batch_stats
+                .iter()
+                .map(|s| s.entropy_loss)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn value_loss(&self) -> f32 {
+        mean(
+            &self
+                .batch_stats
+                .iter()
+                .map(|s| s.value_loss)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn policy_loss(&self) -> f32 {
+        mean(
+            &self
+                .batch_stats
+                .iter()
+                .map(|s| s.policy_loss)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Appends one batch report to this rollout report.
+    pub fn collect_batch_data(&mut self, batch_stats: VPGBatchStats) {
+        self.batch_stats.push(batch_stats);
+    }
+}
+
+impl std::fmt::Display for VPGStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = [
+            ("Average reward", fmt_stat(self.average_reward)),
+            (
+                "Average episode length",
+                fmt_stat(self.average_episode_length),
+            ),
+            ("Policy gradient loss", fmt_stat(self.policy_loss())),
+            ("Entropy loss", fmt_stat(self.entropy_loss())),
+            ("Value loss", fmt_stat(self.value_loss())),
+            ("Learning rate", fmt_stat(self.learning_rate as f32)),
+            (
+                "Standard deviation",
+                self.std.map(|std| std.to_string()).unwrap_or("n/a".into()),
+            ),
+        ];
+
+        let key_width = rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+
+        writeln!(f, "VPG stats (rollout {})", self.rollout_idx)?;
+        writeln!(f, "{:-<1$}", "", key_width + 15)?;
+
+        for (key, value) in rows {
+            writeln!(f, "{key:<key_width$} | {value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl MetricsReport for VPGStats {
+    fn log_metrics(&self, logger: &MetricsLogger) -> Result<()> {
+        logger.log_scalar("train/policy_loss", self.policy_loss() as f64)?;
+        logger.log_scalar("train/entropy_loss", self.entropy_loss() as f64)?;
+        logger.log_scalar("train/value_loss", self.value_loss() as f64)?;
+        logger.log_scalar("train/learning_rate", self.learning_rate)?;
+        if let Some(std) = self.std {
+            logger.log_scalar("train/std", std as f64)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct DefaultVPGHookReporter {
+    pub(crate) rollout_idx: usize,
+    pub(crate) report: VPGStats,
+    pub(crate) tx: Option<Sender<VPGStats>>,
+    pub(crate) log_progress: bool,
+    pub(crate) episodes: EpisodeReporter,
+    pub(crate) logger: Option<MetricsLogger>,
+}
+
+impl DefaultVPGHookReporter {
+    pub fn new(
+        tx: Option<Sender<VPGStats>>,
+        log_progress: bool,
+        n_envs: usize,
+        episode_monitor: Option<EpisodeMonitor>,
+        logger: Option<MetricsLogger>,
+    ) -> Option<Self> {
+        if tx.is_some() || log_progress || logger.is_some() {
+            Some(Self {
+                rollout_idx: 0,
+                report: VPGStats::default(),
+                tx,
+                log_progress,
+                logger,
+                episodes: EpisodeReporter::new(n_envs, episode_monitor),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn send_report(&mut self) -> Result<()> {
+        self.rollout_idx += 1;
+        let progress = std::mem::replace(
+            &mut self.report,
+            VPGStats {
+                rollout_idx: self.rollout_idx,
+                ..Default::default()
+            },
+        );
+        if self.log_progress {
+            println!("{progress}");
+        }
+        if let Some(logger) = &self.logger {
+            logger.log_report(&progress)?;
+        }
+        if let Some(tx) = &self.tx {
+            tx.send(progress).unwrap();
+        }
+        self.report.average_reward = self.episodes.average_reward;
+        self.report.average_episode_length = self.episodes.average_length;
+        Ok(())
+    }
+}
+
+impl DefaultVPGHookReporter {
+    fn update_episode_stats<T: r2l_core::tensor::R2lTensor, B: TrajectoryBatch<T>>(
+        &mut self,
+        batches: &[B],
+    ) {
+        self.episodes.update(batches);
+        self.report.average_reward = self.episodes.average_reward;
+        self.report.average_episode_length = self.episodes.average_length;
+    }
+}
+
+/// Default training hook used by [`VPGAgentBuilder`](crate::VPGAgentBuilder).
+///
+/// This hook applies the crate's standard VPG training behavior:
+/// advantage normalization when enabled, optional value-loss weighting,
+/// optional entropy regularization, optional gradient clipping, and optional
+/// rollout reporting through [`VPGStats`]. With a [`LossMonitor`], a NaN or
+/// infinite loss skips its update.
+///
+/// The generic parameter tracks the concrete learning-module backend and is not
+/// usually named directly by callers.
+pub struct DefaultVPGHook<T = ()> {
+    pub(crate) normalize_advantage: bool,
+    pub(crate) entropy_coeff: f32,
+    pub(crate) vf_coeff: Option<f32>,
+    pub(crate) gradient_clipping: Option<f32>,
+    pub(crate) loss_monitor: Option<LossMonitor>,
+    pub(crate) reporter: Option<DefaultVPGHookReporter>,
+    pub(crate) _lm: PhantomData<T>,
+}
+
+impl<B: AutodiffBackend, D: BurnPolicy<B>> VPGHook<BurnPolicyValueModule<B, D>>
+    for DefaultVPGHook<BurnPolicyValueModule<B, D>>
+{
+    fn before_learning_hook<
+        C: TrajectoryBatch<<BurnPolicyValueModule<B, D> as OnPolicyLearningModule>::InferenceTensor>,
+    >(
+        &mut self,
+        _params: &mut VPGParams,
+        module: &mut BurnPolicyValueModule<B, D>,
+        _buffers: &[C],
+        advantages: &mut Advantages,
+        _returns: &mut Returns,
+    ) -> Result<HookResult> {
+        if self.normalize_advantage {
+            advantages.normalize();
+        }
+        if let Some(max_grad_norm) = self.gradient_clipping {
+            module.set_grad_clipping(GradientClipping::Norm(max_grad_norm));
+        }
+        Ok(HookResult::Continue)
+    }
+
+    fn batch_hook(
+        &mut self,
+        _params: &mut VPGParams,
+        module: &mut BurnPolicyValueModule<B, D>,
+        losses: &mut BurnPolicyValueLosses<B>,
+        data: &VPGBatchData<burn::Tensor<B, 1>>,
+    ) -> Result<HookResult> {
+        losses.set_vf_coeff(self.vf_coeff);
+        let entropy = module.policy().entropy(&data.observations)?;
+        let entropy_loss = entropy.neg() * self.entropy_coeff;
+        if let Some(DefaultVPGHookReporter { report, .. }) = &mut self.reporter {
+            report.collect_batch_data(VPGBatchStats {
+                policy_loss: losses.policy_loss.to_data().to_vec::<f32>().unwrap()[0],
+                entropy_loss: entropy_loss.to_data().to_vec::<f32>().unwrap()[0],
+                value_loss: losses.value_loss.to_data().to_vec::<f32>().unwrap()[0],
+            });
+        }
+        if let Some(monitor) = &self.loss_monitor
+            && !monitor.check(&[&losses.policy_loss, &losses.value_loss, &entropy_loss])
+        {
+            return Ok(HookResult::Break);
+        }
+        if self.entropy_coeff != 0. {
+            losses.add_entropy_loss(entropy_loss);
+        }
+        Ok(HookResult::Continue)
+    }
+
+    fn after_learning_hook<
+        C: TrajectoryBatch<<BurnPolicyValueModule<B, D> as OnPolicyLearningModule>::InferenceTensor>,
+    >(
+        &mut self,
+        _params: &mut VPGParams,
+        module: &mut BurnPolicyValueModule<B, D>,
+        buffers: &[C],
+    ) -> Result<HookResult> {
+        if let Some(reporter) = &mut self.reporter {
+            reporter.update_episode_stats(buffers);
+            reporter.report.std = module.policy().std().ok();
+            reporter.report.learning_rate = module.policy_learning_rate();
+            reporter.send_report()?;
+        }
+        Ok(HookResult::Continue)
+    }
+}
+
+impl VPGHook<CandlePolicyValueModule> for DefaultVPGHook<CandlePolicyValueModule> {
+    fn before_learning_hook<
+        B: TrajectoryBatch<<CandlePolicyValueModule as OnPolicyLearningModule>::InferenceTensor>,
+    >(
+        &mut self,
+        _params: &mut VPGParams,
+        module: &mut CandlePolicyValueModule,
+        _buffers: &[B],
+        advantages: &mut Advantages,
+        _returns: &mut Returns,
+    ) -> Result<HookResult> {
+        if self.normalize_advantage {
+            advantages.normalize();
+        }
+        module.set_grad_clipping(self.gradient_clipping);
+        Ok(HookResult::Continue)
+    }
+
+    fn batch_hook(
+        &mut self,
+        _params: &mut VPGParams,
+        module: &mut CandlePolicyValueModule,
+        losses: &mut CandlePolicyValueLosses,
+        data: &VPGBatchData<candle_core::Tensor>,
+    ) -> Result<HookResult> {
+        losses.set_vf_coeff(self.vf_coeff);
+        let entropy = module.policy().entropy(&data.observations)?;
+        let device = entropy.device();
+        let entropy_loss = (Tensor::full(self.entropy_coeff, (), device)? * entropy.neg()?)?;
+        if let Some(DefaultVPGHookReporter { report, .. }) = &mut self.reporter {
+            report.collect_batch_data(VPGBatchStats {
+                policy_loss: losses.policy_loss.to_scalar()?,
+                entropy_loss: entropy_loss.to_scalar()?,
+                value_loss: losses.value_loss.to_scalar()?,
+            });
+        }
+        if let Some(monitor) = &self.loss_monitor
+            && !monitor.check(&[&losses.policy_loss, &losses.value_loss, &entropy_loss])
+        {
+            return Ok(HookResult::Break);
+        }
+        if self.entropy_coeff != 0. {
+            losses.add_entropy_loss(entropy_loss)?;
+        }
+        Ok(HookResult::Continue)
+    }
+
+    fn after_learning_hook<B: TrajectoryBatch<candle_core::Tensor>>(
+        &mut self,
+        _params: &mut VPGParams,
+        module: &mut CandlePolicyValueModule,
+        buffers: &[B],
+    ) -> Result<HookResult> {
+        if let Some(reporter) = &mut self.reporter {
+            reporter.update_episode_stats(buffers);
+            reporter.report.std = module.policy().std().ok();
+            reporter.report.learning_rate = module.policy_learning_rate();
+            reporter.send_report()?;
+        }
+        Ok(HookResult::Continue)
+    }
+}
@@ -3,14 +3,21 @@ use burn::backend::{Autodiff, NdArray};
 // builders + hooks + higher level helpers
 mod agents;
 mod builders;
+mod config;
 mod evaluators;
 mod hooks;
+mod metrics;
+mod tuning;
 mod utils;
 
 pub type BurnBackend = Autodiff<NdArray>;
 
+pub use agents::PopulationAgent;
 pub use agents::a2c::{A2CBurnAgent, A2CCandleAgent};
+pub use agents::ppg::{PPGBurnAgent, PPGCandleAgent};
 pub use agents::ppo::{PPOBurnAgent, PPOCandleAgent};
+pub use agents::trpo::{TRPOBurnAgent, TRPOCandleAgent};
+pub use agents::vpg::{VPGBurnAgent, VPGCandleAgent};
 pub use builders::a2c::agent::{A2CAgentBuilder, A2CBurnAgentBuilder, A2CCandleAgentBuilder};
 pub use builders::a2c::algorithm::{
     A2CAlgorithmBuilder, A2CBurnAlgorithmBuilder, A2CCandleAlgorithmBuilder,
@@ -18,22 +25,65 @@ pub use builders::a2c::algorithm::{
 pub use builders::agent::OnPolicyAgentBuilder;
 pub use builders::learning_module::OnPolicyLearningModuleType;
 pub use builders::on_policy::OnPolicyAlgorithmBuilder;
+pub use builders::ppg::agent::{
+    PPGAgentBuilder, PPGAgentParams, PPGBurnAgentBuilder, PPGCandleAgentBuilder,
+};
+pub use builders::ppg::algorithm::{
+    PPGAlgorithmBuilder, PPGBurnAlgorithmBuilder, PPGCandleAlgorithmBuilder,
+};
 pub use builders::ppo::agent::{PPOAgentBuilder, PPOBurnAgentBuilder, PPOCandleAgentBuilder};
 pub use builders::ppo::algorithm::{
     PPOAlgorithmBuilder, PPOBurnAlgorithmBuilder, PPOCandleAlgorithmBuilder,
 };
 pub use builders::sampler::{DirectSamplerSelection, NormalizedSamplerSelection, SamplerBuilder};
-pub use builders::sampler::{EpisodeHookBound, StepHookBound};
+pub use builders::sampler::{EpisodeHookBound, RewardNormalizerParams, StepHookBound};
+pub use builders::trpo::agent::{TRPOAgentBuilder, TRPOBurnAgentBuilder, TRPOCandleAgentBuilder};
+pub use builders::trpo::algorithm::{
+    TRPOAlgorithmBuilder, TRPOBurnAlgorithmBuilder, TRPOCandleAlgorithmBuilder,
+};
+pub use builders::vpg::agent::{VPGAgentBuilder, VPGBurnAgentBuilder, VPGCandleAgentBuilder};
+pub use builders::vpg::algorithm::{
+    VPGAlgorithmBuilder, VPGBurnAlgorithmBuilder, VPGCandleAlgorithmBuilder,
+};
+pub use config::{
+    A2CConfig, AlgorithmConfig, BEST_POLICY_FILE, BackendConfig, EnvConfig, EnvKwarg, EnvRegistry,
+    EvaluatorConfig, ExperimentConfig, GymWrapperConfig, NetworkConfig, OptimizerKind,
+    OptimizerSettings, PPOConfig, RolloutBound, SamplerConfig, ScheduleConfig, ScheduleShape,
+    Trainable, TrainingBudget, TrainingConfig, VPGConfig,
+};
 pub use evaluators::best_actor_evaluator::{BestActorEvaluator, BestActorEvaluatorBuilder};
 pub use evaluators::simple_evaluator::Evaluator;
 pub use hooks::a2c::{A2CBatchStats, A2CStats, DefaultA2CHook};
-pub use hooks::on_policy::{DefaultOnPolicyAlgorithmHooks, LearningRateSchedule, LearningSchedule};
+pub use hooks::diagnostics::TrainingDiagnostics;
+pub use hooks::early_stopping::{
+    LossMonitor, NoImprovement, NonFiniteLoss, RewardThreshold, StopCondition, TimeBudget,
+};
+#[allow(deprecated)]
+pub use hooks::on_policy::LearningRateSchedule;
+pub use hooks::on_policy::{DefaultOnPolicyAlgorithmHooks, EvaluationCallback, LearningSchedule};
+pub use hooks::ppg::{DefaultPPGHook, PPGAuxiliaryStats};
+#[allow(deprecated)]
+pub use hooks::ppo::ClipRangeSchedule;
 pub use hooks::ppo::{DefaultPPOHook, PPOBatchStats, PPOStats};
 pub use hooks::sampler::{EpisodeBoundHook, StepBoundHook};
+pub use hooks::schedule::Schedule;
+pub use hooks::trpo::{DefaultTRPOHook, TRPOStats};
+pub use hooks::vpg::{DefaultVPGHook, VPGBatchStats, VPGStats};
+pub use metrics::csv::{CsvSink, read_csv_metric};
+pub use metrics::jsonl::JsonLinesSink;
+pub use metrics::tensorboard::TensorBoardSink;
+pub use metrics::{MetricRecord, MetricValue, MetricsLogger, MetricsReport, MetricsSink};
 pub use r2l_core::{
     env::{Env, EnvBuilder, EnvDescription, Snapshot, Space},
-    models::ActivationFunction,
+    env_checker::{EnvCheckReport, EnvChecker, check_env},
+    init::{InitScheme, WeightInit},
+    models::{ActivationFunction, OptimizerConfig},
     on_policy::algorithm::OnPolicyAlgorithm,
     tensor::TensorData,
 };
+pub use r2l_sampler::RewardNormalizer;
 pub use r2l_sampler::{R2lSampler, SamplerExecutionMode};
+pub use tuning::pbt::{PbtMember, PbtMemberResult, PopulationBasedTraining};
+pub use tuning::pruner::SuccessiveHalving;
+pub use tuning::space::{Distribution, ParamValue, Params, SearchSpace};
+pub use tuning::{SearchStrategy, Study, Trial, TrialProcess, TrialRecord, TrialState, best_trial};
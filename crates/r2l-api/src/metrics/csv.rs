@@ -0,0 +1,159 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+
+use crate::metrics::{HistogramSummary, MetricRecord, MetricValue, MetricsSink};
+
+/// Sink writing one CSV row per record.
+///
+/// Rows are `step,wall_time,name,value,count,min,max`. Histograms are written
+/// as their mean, with the sample count and range in the last columns, which
+/// are empty for scalars.
+pub struct CsvSink {
+    writer: BufWriter<File>,
+}
+
+impl CsvSink {
+    /// Creates the CSV file at `path`, replacing any existing file.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "step,wall_time,name,value,count,min,max")?;
+        Ok(Self { writer })
+    }
+}
+
+/// Reads the `(step, value)` rows of `metric`, in file order, from the
+/// contents of a metrics CSV written by [`CsvSink`].
+///
+/// Histogram rows without samples have no value and are skipped.
+pub fn read_csv_metric(contents: &str, metric: &str) -> Result<Vec<(u64, f64)>> {
+    let mut reader = ::csv::Reader::from_reader(contents.as_bytes());
+    let mut points = vec![];
+    for record in reader.records() {
+        let record = record.context("malformed metrics CSV")?;
+        if record.get(2) != Some(metric) || record.get(3).is_none_or(str::is_empty) {
+            continue;
+        }
+        let line = record.position().map_or(0, |position| position.line());
+        let step = record[0]
+            .parse()
+            .with_context(|| format!("invalid step `{}` on line {line}", &record[0]))?;
+        let value = record[3]
+            .parse()
+            .with_context(|| format!("invalid value `{}` on line {line}", &record[3]))?;
+        points.push((step, value));
+    }
+    Ok(points)
+}
+
+fn escape(name: &str) -> String {
+    if name.contains([',', '"', '\n']) {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    } else {
+        name.to_string()
+    }
+}
+
+impl MetricsSink for CsvSink {
+    fn write(&mut self, record: &MetricRecord) -> Result<()> {
+        let name = escape(&record.name);
+        match &record.value {
+            MetricValue::Scalar(value) => writeln!(
+                self.writer,
+                "{},{},{name},{value},,,",
+                record.step, record.wall_time
+            )?,
+            MetricValue::Histogram(values) if values.is_empty() => writeln!(
+                self.writer,
+                "{},{},{name},,0,,",
+                record.step, record.wall_time
+            )?,
+            MetricValue::Histogram(values) => {
+                let summary = HistogramSummary::new(values);
+                writeln!(
+                    self.writer,
+                    "{},{},{name},{},{},{},{}",
+                    record.step,
+                    record.wall_time,
+                    summary.mean(),
+                    summary.count,
+                    summary.min,
+                    summary.max
+                )?
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CsvSink, read_csv_metric};
+    use crate::metrics::{MetricRecord, MetricValue, MetricsSink};
+
+    #[test]
+    fn writes_scalar_and_histogram_rows() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join("r2l_metrics_sink.csv");
+        let mut sink = CsvSink::create(&path)?;
+        sink.write(&MetricRecord {
+            name: "train/loss".to_string(),
+            step: 3,
+            wall_time: 1.5,
+            value: MetricValue::Scalar(0.5),
+        })?;
+        sink.write(&MetricRecord {
+            name: "rollout/returns, raw".to_string(),
+            step: 4,
+            wall_time: 2.5,
+            value: MetricValue::Histogram(vec![1., 2., 6.]),
+        })?;
+        sink.write(&MetricRecord {
+            name: "rollout/empty".to_string(),
+            step: 5,
+            wall_time: 3.5,
+            value: MetricValue::Histogram(vec![]),
+        })?;
+        sink.flush()?;
+        let contents = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(
+            contents.lines().collect::<Vec<_>>(),
+            [
+                "step,wall_time,name,value,count,min,max",
+                "3,1.5,train/loss,0.5,,,",
+                "4,2.5,\"rollout/returns, raw\",3,3,1,6",
+                "5,3.5,rollout/empty,,0,,",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn metrics_are_read_back_by_their_quoted_names() -> anyhow::Result<()> {
+        let contents = "step,wall_time,name,value,count,min,max\n\
+                        3,1.5,eval/mean_reward,0.5,,,\n\
+                        4,2.5,\"eval/mean_reward, raw\",3,3,1,6\n\
+                        5,3.5,eval/mean_reward,,0,,\n\
+                        6,4.5,eval/mean_reward,-1.5,,,\n";
+        assert_eq!(
+            read_csv_metric(contents, "eval/mean_reward")?,
+            [(3, 0.5), (6, -1.5)]
+        );
+        assert_eq!(
+            read_csv_metric(contents, "eval/mean_reward, raw")?,
+            [(4, 3.)]
+        );
+        let invalid = "step,wall_time,name,value,count,min,max\nx,1.5,loss,0.5,,,\n";
+        assert!(read_csv_metric(invalid, "loss").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::Result;
+
+use crate::metrics::{HistogramSummary, MetricRecord, MetricValue, MetricsSink};
+
+/// Sink writing one JSON object per line and record.
+///
+/// Scalars are written as `{"name", "step", "wall_time", "value"}`. Histograms
+/// replace `value` with a `histogram` object holding the count, range, mean
+/// and raw samples. Non-finite numbers are written as `null`.
+pub struct JsonLinesSink {
+    writer: BufWriter<File>,
+}
+
+impl JsonLinesSink {
+    /// Creates the JSON-lines file at `path`, replacing any existing file.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json_line(record: &MetricRecord) -> String {
+    let mut line = format!(
+        "{{\"name\":{},\"step\":{},\"wall_time\":{}",
+        json_string(&record.name),
+        record.step,
+        json_number(record.wall_time)
+    );
+    match &record.value {
+        MetricValue::Scalar(value) => {
+            let _ = write!(line, ",\"value\":{}}}", json_number(*value));
+        }
+        MetricValue::Histogram(values) => {
+            let summary = HistogramSummary::new(values);
+            let samples = values
+                .iter()
+                .map(|value| json_number(*value))
+                .collect::<Vec<_>>()
+                .join(",");
+            let (min, max, mean) = if values.is_empty() {
+                (f64::NAN, f64::NAN, f64::NAN)
+            } else {
+                (summary.min, summary.max, summary.mean())
+            };
+            let _ = write!(
+                line,
+                ",\"histogram\":{{\"count\":{},\"min\":{},\"max\":{},\"mean\":{},\"values\":[{samples}]}}}}",
+                summary.count,
+                json_number(min),
+                json_number(max),
+                json_number(mean)
+            );
+        }
+    }
+    line
+}
+
+impl MetricsSink for JsonLinesSink {
+    fn write(&mut self, record: &MetricRecord) -> Result<()> {
+        writeln!(self.writer, "{}", json_line(record))?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{Value, json};
+
+    use super::JsonLinesSink;
+    use crate::metrics::{MetricRecord, MetricValue, MetricsSink};
+
+    #[test]
+    fn writes_one_json_object_per_record() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join("r2l_metrics_sink.jsonl");
+        let mut sink = JsonLinesSink::create(&path)?;
+        sink.write(&MetricRecord {
+            name: "train/\"loss\"\n".to_string(),
+            step: 3,
+            wall_time: 1.5,
+            value: MetricValue::Scalar(f64::NAN),
+        })?;
+        sink.write(&MetricRecord {
+            name: "rollout/returns".to_string(),
+            step: 4,
+            wall_time: 2.5,
+            value: MetricValue::Histogram(vec![1., 2., 6.]),
+        })?;
+        sink.flush()?;
+        let contents = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        let lines = contents
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<Value>, _>>()?;
+        assert_eq!(
+            lines,
+            [
+                json!({"name": "train/\"loss\"\n", "step": 3, "wall_time": 1.5, "value": null}),
+                json!({
+                    "name": "rollout/returns",
+                    "step": 4,
+                    "wall_time": 2.5,
+                    "histogram": {"count": 3, "min": 1, "max": 6, "mean": 3, "values": [1, 2, 6]},
+                }),
+            ]
+        );
+        Ok(())
+    }
+}
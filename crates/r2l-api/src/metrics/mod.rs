@@ -0,0 +1,205 @@
+pub mod csv;
+pub mod jsonl;
+pub mod tensorboard;
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+
+use crate::metrics::{csv::CsvSink, jsonl::JsonLinesSink, tensorboard::TensorBoardSink};
+
+/// Value carried by a [`MetricRecord`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricValue {
+    /// A single number, such as a loss or an average reward.
+    Scalar(f64),
+    /// A set of samples, such as the returns of every episode in a rollout.
+    Histogram(Vec<f64>),
+}
+
+/// One metric observation, keyed by name and global step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricRecord {
+    /// Metric name, conventionally namespaced like `train/policy_loss`.
+    pub name: String,
+    /// Global step the record belongs to, in sampled environment steps.
+    pub step: u64,
+    /// Seconds since the Unix epoch at which the record was logged.
+    pub wall_time: f64,
+    /// Recorded value.
+    pub value: MetricValue,
+}
+
+/// Destination of the records of a [`MetricsLogger`].
+pub trait MetricsSink: Send {
+    /// Writes one record.
+    fn write(&mut self, record: &MetricRecord) -> Result<()>;
+
+    /// Flushes buffered records to their destination.
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Types that can be reported through a [`MetricsLogger`].
+///
+/// Implemented by the stats emitted by the default hooks, so every reporter
+/// that already sends stats over a channel can also log them.
+pub trait MetricsReport {
+    /// Logs the report at the current global step of `logger`.
+    fn log_metrics(&self, logger: &MetricsLogger) -> Result<()>;
+}
+
+struct MetricsLoggerState {
+    sinks: Vec<Box<dyn MetricsSink>>,
+    step: u64,
+}
+
+/// Shared handle fanning metric records out to a set of sinks.
+///
+/// Cloning the logger is cheap and every clone writes to the same sinks, so a
+/// single logger can be handed to the agent hooks, the outer training loop and
+/// the evaluator. The global step is shared as well: the outer training loop
+/// advances it after each rollout, and records logged through
+/// [`log_scalar`](Self::log_scalar) or [`log_histogram`](Self::log_histogram)
+/// are stamped with it.
+#[derive(Clone)]
+pub struct MetricsLogger(Arc<Mutex<MetricsLoggerState>>);
+
+impl Default for MetricsLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for MetricsLogger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.0.lock().unwrap();
+        f.debug_struct("MetricsLogger")
+            .field("sinks", &state.sinks.len())
+            .field("step", &state.step)
+            .finish()
+    }
+}
+
+impl MetricsLogger {
+    /// Creates a logger without sinks.
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(MetricsLoggerState {
+            sinks: vec![],
+            step: 0,
+        })))
+    }
+
+    /// Adds a sink that receives every subsequent record.
+    pub fn with_sink(self, sink: impl MetricsSink + 'static) -> Self {
+        self.0.lock().unwrap().sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Adds a [`CsvSink`] writing to `path`.
+    pub fn with_csv<P: AsRef<Path>>(self, path: P) -> Result<Self> {
+        Ok(self.with_sink(CsvSink::create(path)?))
+    }
+
+    /// Adds a [`JsonLinesSink`] writing to `path`.
+    pub fn with_jsonl<P: AsRef<Path>>(self, path: P) -> Result<Self> {
+        Ok(self.with_sink(JsonLinesSink::create(path)?))
+    }
+
+    /// Adds a [`TensorBoardSink`] writing an event file into `log_dir`.
+    pub fn with_tensorboard<P: AsRef<Path>>(self, log_dir: P) -> Result<Self> {
+        Ok(self.with_sink(TensorBoardSink::create(log_dir)?))
+    }
+
+    /// Returns the current global step.
+    pub fn step(&self) -> u64 {
+        self.0.lock().unwrap().step
+    }
+
+    /// Sets the global step stamped on subsequent records.
+    pub fn set_step(&self, step: u64) {
+        self.0.lock().unwrap().step = step;
+    }
+
+    /// Writes a record to every sink.
+    pub fn log(&self, record: MetricRecord) -> Result<()> {
+        let mut state = self.0.lock().unwrap();
+        for sink in &mut state.sinks {
+            sink.write(&record)?;
+        }
+        Ok(())
+    }
+
+    /// Logs a scalar at the current global step.
+    pub fn log_scalar(&self, name: &str, value: f64) -> Result<()> {
+        self.log_value(name, MetricValue::Scalar(value))
+    }
+
+    /// Logs a histogram of `values` at the current global step.
+    pub fn log_histogram(&self, name: &str, values: &[f32]) -> Result<()> {
+        let values = values.iter().map(|value| *value as f64).collect();
+        self.log_value(name, MetricValue::Histogram(values))
+    }
+
+    /// Logs a report at the current global step.
+    pub fn log_report(&self, report: &impl MetricsReport) -> Result<()> {
+        report.log_metrics(self)?;
+        self.flush()
+    }
+
+    /// Flushes every sink.
+    pub fn flush(&self) -> Result<()> {
+        let mut state = self.0.lock().unwrap();
+        for sink in &mut state.sinks {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+
+    fn log_value(&self, name: &str, value: MetricValue) -> Result<()> {
+        let record = MetricRecord {
+            name: name.to_string(),
+            step: self.step(),
+            wall_time: wall_time(),
+            value,
+        };
+        self.log(record)
+    }
+}
+
+fn wall_time() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs_f64())
+        .unwrap_or_default()
+}
+
+/// Summary statistics of a histogram record, used by text sinks.
+pub(crate) struct HistogramSummary {
+    pub(crate) count: usize,
+    pub(crate) min: f64,
+    pub(crate) max: f64,
+    pub(crate) sum: f64,
+    pub(crate) sum_squares: f64,
+}
+
+impl HistogramSummary {
+    pub(crate) fn new(values: &[f64]) -> Self {
+        Self {
+            count: values.len(),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            sum: values.iter().sum(),
+            sum_squares: values.iter().map(|value| value * value).sum(),
+        }
+    }
+
+    pub(crate) fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
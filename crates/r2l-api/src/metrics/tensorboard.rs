@@ -0,0 +1,361 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::Result;
+
+use crate::metrics::{HistogramSummary, MetricRecord, MetricValue, MetricsSink, wall_time};
+
+const HISTOGRAM_BUCKETS: usize = 30;
+
+/// Sink writing a TensorBoard event file.
+///
+/// Records are encoded locally as `tensorflow.Event` protocol buffers inside
+/// TFRecord framing, so the resulting `events.out.tfevents.*` file can be
+/// opened with `tensorboard --logdir` without any TensorFlow dependency.
+/// Scalars become scalar summaries and histograms are bucketed into
+/// equal-width bins.
+pub struct TensorBoardSink {
+    writer: BufWriter<File>,
+}
+
+impl TensorBoardSink {
+    /// Creates a new event file in `log_dir`, creating the directory if needed.
+    pub fn create<P: AsRef<Path>>(log_dir: P) -> Result<Self> {
+        let log_dir = log_dir.as_ref();
+        std::fs::create_dir_all(log_dir)?;
+        let wall_time = wall_time();
+        let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string());
+        let file_name = format!(
+            "events.out.tfevents.{}.{hostname}.{}",
+            wall_time as u64,
+            std::process::id()
+        );
+        let mut sink = Self {
+            writer: BufWriter::new(File::create(log_dir.join(file_name))?),
+        };
+        let mut event = vec![];
+        encode_double(&mut event, 1, wall_time);
+        encode_bytes(&mut event, 3, b"brain.Event:2");
+        sink.write_record(&event)?;
+        sink.writer.flush()?;
+        Ok(sink)
+    }
+
+    fn write_record(&mut self, data: &[u8]) -> Result<()> {
+        let length = (data.len() as u64).to_le_bytes();
+        self.writer.write_all(&length)?;
+        self.writer
+            .write_all(&masked_crc32c(&length).to_le_bytes())?;
+        self.writer.write_all(data)?;
+        self.writer.write_all(&masked_crc32c(data).to_le_bytes())?;
+        Ok(())
+    }
+}
+
+impl MetricsSink for TensorBoardSink {
+    fn write(&mut self, record: &MetricRecord) -> Result<()> {
+        self.write_record(&encode_event(record))
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+fn encode_event(record: &MetricRecord) -> Vec<u8> {
+    let mut value = vec![];
+    encode_bytes(&mut value, 1, record.name.as_bytes());
+    match &record.value {
+        MetricValue::Scalar(scalar) => encode_float(&mut value, 2, *scalar as f32),
+        MetricValue::Histogram(values) => encode_bytes(&mut value, 5, &encode_histogram(values)),
+    }
+    let mut summary = vec![];
+    encode_bytes(&mut summary, 1, &value);
+    let mut event = vec![];
+    encode_double(&mut event, 1, record.wall_time);
+    encode_varint_field(&mut event, 2, record.step);
+    encode_bytes(&mut event, 5, &summary);
+    event
+}
+
+fn encode_histogram(values: &[f64]) -> Vec<u8> {
+    let mut histogram = vec![];
+    if values.is_empty() {
+        return histogram;
+    }
+    let summary = HistogramSummary::new(values);
+    let n_buckets = if summary.max > summary.min {
+        HISTOGRAM_BUCKETS
+    } else {
+        1
+    };
+    let width = (summary.max - summary.min) / n_buckets as f64;
+    let mut bucket_limits = (1..=n_buckets)
+        .map(|i| summary.min + width * i as f64)
+        .collect::<Vec<_>>();
+    bucket_limits[n_buckets - 1] = summary.max;
+    let mut buckets = vec![0f64; n_buckets];
+    for value in values {
+        let idx = if width > 0. {
+            (((value - summary.min) / width) as usize).min(n_buckets - 1)
+        } else {
+            0
+        };
+        buckets[idx] += 1.;
+    }
+    encode_double(&mut histogram, 1, summary.min);
+    encode_double(&mut histogram, 2, summary.max);
+    encode_double(&mut histogram, 3, summary.count as f64);
+    encode_double(&mut histogram, 4, summary.sum);
+    encode_double(&mut histogram, 5, summary.sum_squares);
+    encode_packed_doubles(&mut histogram, 6, &bucket_limits);
+    encode_packed_doubles(&mut histogram, 7, &buckets);
+    histogram
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn encode_key(buf: &mut Vec<u8>, field: u64, wire_type: u64) {
+    encode_varint(buf, (field << 3) | wire_type);
+}
+
+fn encode_varint_field(buf: &mut Vec<u8>, field: u64, value: u64) {
+    encode_key(buf, field, 0);
+    encode_varint(buf, value);
+}
+
+fn encode_double(buf: &mut Vec<u8>, field: u64, value: f64) {
+    encode_key(buf, field, 1);
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn encode_float(buf: &mut Vec<u8>, field: u64, value: f32) {
+    encode_key(buf, field, 5);
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn encode_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    encode_key(buf, field, 2);
+    encode_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn encode_packed_doubles(buf: &mut Vec<u8>, field: u64, values: &[f64]) {
+    let bytes = values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect::<Vec<_>>();
+    encode_bytes(buf, field, &bytes);
+}
+
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc = CRC32C_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+fn masked_crc32c(data: &[u8]) -> u32 {
+    let crc = crc32c(data);
+    crc.rotate_right(15).wrapping_add(0xa282_ead8)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TensorBoardSink, crc32c, encode_varint, masked_crc32c};
+    use crate::metrics::{MetricRecord, MetricValue, MetricsSink};
+
+    #[derive(Debug, PartialEq)]
+    enum Field<'a> {
+        Varint(u64),
+        Fixed64([u8; 8]),
+        Bytes(&'a [u8]),
+        Fixed32([u8; 4]),
+    }
+
+    fn decode_varint(buf: &mut &[u8]) -> u64 {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = buf[0];
+            *buf = &buf[1..];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte < 0x80 {
+                return value;
+            }
+            shift += 7;
+        }
+    }
+
+    fn decode_fields(mut buf: &[u8]) -> Vec<(u64, Field<'_>)> {
+        let mut fields = vec![];
+        while !buf.is_empty() {
+            let key = decode_varint(&mut buf);
+            let field = match key & 0x7 {
+                0 => Field::Varint(decode_varint(&mut buf)),
+                1 => {
+                    let (value, rest) = buf.split_at(8);
+                    buf = rest;
+                    Field::Fixed64(value.try_into().unwrap())
+                }
+                2 => {
+                    let len = decode_varint(&mut buf) as usize;
+                    let (value, rest) = buf.split_at(len);
+                    buf = rest;
+                    Field::Bytes(value)
+                }
+                5 => {
+                    let (value, rest) = buf.split_at(4);
+                    buf = rest;
+                    Field::Fixed32(value.try_into().unwrap())
+                }
+                wire_type => panic!("unexpected wire type {wire_type}"),
+            };
+            fields.push((key >> 3, field));
+        }
+        fields
+    }
+
+    fn bytes_field<'a>(fields: &[(u64, Field<'a>)], number: u64) -> &'a [u8] {
+        fields
+            .iter()
+            .find_map(|(field, value)| match value {
+                Field::Bytes(bytes) if *field == number => Some(*bytes),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    // Splits a TFRecord file into its payloads, checking both checksums.
+    fn read_records(mut data: &[u8]) -> Vec<&[u8]> {
+        let mut records = vec![];
+        while !data.is_empty() {
+            let (length, rest) = data.split_at(8);
+            let (length_crc, rest) = rest.split_at(4);
+            assert_eq!(masked_crc32c(length).to_le_bytes(), length_crc);
+            let len = u64::from_le_bytes(length.try_into().unwrap()) as usize;
+            let (payload, rest) = rest.split_at(len);
+            let (payload_crc, rest) = rest.split_at(4);
+            assert_eq!(masked_crc32c(payload).to_le_bytes(), payload_crc);
+            records.push(payload);
+            data = rest;
+        }
+        records
+    }
+
+    #[test]
+    fn crc32c_matches_reference_value() {
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(crc32c(b""), 0);
+    }
+
+    #[test]
+    fn varints_use_seven_bit_groups() {
+        let mut buf = vec![];
+        encode_varint(&mut buf, 300);
+        assert_eq!(buf, vec![0xac, 0x02]);
+    }
+
+    #[test]
+    fn event_file_round_trips_scalars_and_histograms() -> anyhow::Result<()> {
+        let log_dir = std::env::temp_dir().join("r2l_tensorboard_round_trip");
+        let _ = std::fs::remove_dir_all(&log_dir);
+        let mut sink = TensorBoardSink::create(&log_dir)?;
+        sink.write(&MetricRecord {
+            name: "train/policy_loss".to_string(),
+            step: 300,
+            wall_time: 12.5,
+            value: MetricValue::Scalar(0.25),
+        })?;
+        sink.write(&MetricRecord {
+            name: "rollout/returns".to_string(),
+            step: 301,
+            wall_time: 13.5,
+            value: MetricValue::Histogram(vec![1., 2., 2., 4.]),
+        })?;
+        sink.flush()?;
+
+        let mut files = std::fs::read_dir(&log_dir)?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(files.len(), 1);
+        let file = files.pop().unwrap();
+        assert!(
+            file.file_name()
+                .to_string_lossy()
+                .starts_with("events.out.tfevents.")
+        );
+        let data = std::fs::read(file.path())?;
+        let records = read_records(&data);
+        assert_eq!(records.len(), 3);
+
+        let header = decode_fields(records[0]);
+        assert_eq!(bytes_field(&header, 3), b"brain.Event:2");
+
+        let scalar = decode_fields(records[1]);
+        assert_eq!(scalar[0], (1, Field::Fixed64(12.5f64.to_le_bytes())));
+        assert_eq!(scalar[1], (2, Field::Varint(300)));
+        let value = decode_fields(bytes_field(&decode_fields(bytes_field(&scalar, 5)), 1));
+        assert_eq!(value[0], (1, Field::Bytes(b"train/policy_loss")));
+        assert_eq!(value[1], (2, Field::Fixed32(0.25f32.to_le_bytes())));
+
+        let histogram = decode_fields(records[2]);
+        assert_eq!(histogram[1], (2, Field::Varint(301)));
+        let value = decode_fields(bytes_field(&decode_fields(bytes_field(&histogram, 5)), 1));
+        assert_eq!(value[0], (1, Field::Bytes(b"rollout/returns")));
+        let histogram = decode_fields(bytes_field(&value, 5));
+        let double = |number| {
+            histogram
+                .iter()
+                .find_map(|(field, value)| match value {
+                    Field::Fixed64(bytes) if *field == number => Some(f64::from_le_bytes(*bytes)),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        assert_eq!(double(1), 1.);
+        assert_eq!(double(2), 4.);
+        assert_eq!(double(3), 4.);
+        assert_eq!(double(4), 9.);
+        assert_eq!(double(5), 25.);
+        let counts = bytes_field(&histogram, 7)
+            .chunks(8)
+            .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(counts.len(), 30);
+        assert_eq!(counts.iter().sum::<f64>(), 4.);
+        assert_eq!((counts[0], counts[10], counts[29]), (1., 2., 1.));
+
+        std::fs::remove_dir_all(&log_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,599 @@
+pub mod pbt;
+pub mod pruner;
+pub mod space;
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use rand::{SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ExperimentConfig, read_csv_metric,
+    tuning::{
+        pruner::SuccessiveHalving,
+        space::{ParamValue, Params, SearchSpace},
+    },
+};
+
+/// Metric the process executor reads evaluation rewards from.
+const EVAL_METRIC: &str = "eval/mean_reward";
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Lifecycle state of a trial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrialState {
+    Running,
+    Complete,
+    /// Stopped early by the pruner.
+    Pruned,
+    Failed,
+}
+
+/// Stored result of one trial.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrialRecord {
+    pub number: usize,
+    pub params: Params,
+    pub state: TrialState,
+    /// Latest evaluation reward, which trials are ranked by.
+    pub value: Option<f64>,
+    /// Evaluation rewards by sampled environment step.
+    pub intermediate_values: Vec<(usize, f64)>,
+    /// Rewards at the successive-halving rungs the trial reached.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rung_values: Vec<f64>,
+    /// Error of a failed trial.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TrialRecord {
+    fn new(number: usize, params: Params) -> Self {
+        Self {
+            number,
+            params,
+            state: TrialState::Running,
+            value: None,
+            intermediate_values: vec![],
+            rung_values: vec![],
+            error: None,
+        }
+    }
+}
+
+/// Completed trial with the highest value.
+///
+/// Pruned trials trained on smaller budgets and are not considered.
+pub fn best_trial(trials: &[TrialRecord]) -> Option<&TrialRecord> {
+    trials
+        .iter()
+        .filter(|trial| trial.state == TrialState::Complete)
+        .filter_map(|trial| Some((trial, trial.value?)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(trial, _)| trial)
+}
+
+#[derive(Serialize, Deserialize)]
+struct StudyFile {
+    trials: Vec<TrialRecord>,
+}
+
+struct StudyState {
+    trials: BTreeMap<usize, TrialRecord>,
+    pruner: Option<SuccessiveHalving>,
+    storage: Option<PathBuf>,
+    save_error: Option<anyhow::Error>,
+}
+
+impl StudyState {
+    fn save(&mut self) {
+        let Some(path) = &self.storage else {
+            return;
+        };
+        let study = StudyFile {
+            trials: self.trials.values().cloned().collect(),
+        };
+        // Written next to the store first, so an interrupted write never
+        // corrupts it.
+        let tmp_path = path.with_extension("tmp");
+        let result = serde_json::to_string_pretty(&study)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(fs::write(&tmp_path, json)?))
+            .and_then(|()| Ok(fs::rename(&tmp_path, path)?))
+            .with_context(|| format!("failed to save study {}", path.display()));
+        if let Err(err) = result
+            && self.save_error.is_none()
+        {
+            self.save_error = Some(err);
+        }
+    }
+}
+
+fn lock(state: &Mutex<StudyState>) -> MutexGuard<'_, StudyState> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Handle of a running trial, passed to the objective.
+#[derive(Clone)]
+pub struct Trial {
+    number: usize,
+    params: Params,
+    state: Arc<Mutex<StudyState>>,
+}
+
+impl Trial {
+    /// Index of the trial within its study.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Hyperparameters of the trial.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    fn param(&self, name: &str) -> Result<&ParamValue> {
+        self.params
+            .get(name)
+            .ok_or_else(|| anyhow!("no hyperparameter `{name}` in the search space"))
+    }
+
+    /// Float hyperparameter `name`, accepting integers as well.
+    pub fn float(&self, name: &str) -> Result<f64> {
+        match self.param(name)? {
+            ParamValue::Float(value) => Ok(*value),
+            ParamValue::Int(value) => Ok(*value as f64),
+            value => bail!("hyperparameter `{name}` is not a number: {value}"),
+        }
+    }
+
+    /// Integer hyperparameter `name`.
+    pub fn int(&self, name: &str) -> Result<i64> {
+        match self.param(name)? {
+            ParamValue::Int(value) => Ok(*value),
+            value => bail!("hyperparameter `{name}` is not an integer: {value}"),
+        }
+    }
+
+    /// Boolean hyperparameter `name`.
+    pub fn bool(&self, name: &str) -> Result<bool> {
+        match self.param(name)? {
+            ParamValue::Bool(value) => Ok(*value),
+            value => bail!("hyperparameter `{name}` is not a boolean: {value}"),
+        }
+    }
+
+    /// String hyperparameter `name`.
+    pub fn str(&self, name: &str) -> Result<&str> {
+        match self.param(name)? {
+            ParamValue::Str(value) => Ok(value),
+            value => bail!("hyperparameter `{name}` is not a string: {value}"),
+        }
+    }
+
+    /// Overrides `config` with every hyperparameter, using names as override
+    /// paths.
+    pub fn apply(&self, config: ExperimentConfig) -> Result<ExperimentConfig> {
+        self.params
+            .iter()
+            .try_fold(config, |config, (name, value)| {
+                config.with_override(name, &value.to_string())
+            })
+    }
+
+    /// Records evaluation reward `value` after `step` sampled environment
+    /// steps. Returns `false` once the pruner stopped the trial.
+    pub fn report(&self, step: usize, value: f64) -> bool {
+        let mut state = lock(&self.state);
+        let pruner = state.pruner;
+        let Some(record) = state.trials.get_mut(&self.number) else {
+            return false;
+        };
+        if record.state != TrialState::Running {
+            return false;
+        }
+        record.intermediate_values.push((step, value));
+        record.value = Some(value);
+        let mut promoted = true;
+        if let Some(pruner) = pruner {
+            loop {
+                let record = state.trials.get_mut(&self.number).unwrap();
+                let rung = record.rung_values.len();
+                if step < pruner.rung_steps(rung) {
+                    break;
+                }
+                record.rung_values.push(value);
+                let rung_values: Vec<f64> = state
+                    .trials
+                    .values()
+                    .filter_map(|trial| trial.rung_values.get(rung).copied())
+                    .collect();
+                if !pruner.promotes(value, &rung_values) {
+                    state.trials.get_mut(&self.number).unwrap().state = TrialState::Pruned;
+                    promoted = false;
+                    break;
+                }
+            }
+        }
+        state.save();
+        promoted
+    }
+
+    /// Callback for
+    /// [`with_evaluation_callback`](crate::OnPolicyAlgorithmBuilder::with_evaluation_callback)
+    /// that reports every evaluation and stops training once the trial is
+    /// pruned.
+    pub fn evaluation_callback(&self) -> impl FnMut(usize, f32) -> bool + Send + 'static {
+        let trial = self.clone();
+        move |step, mean_reward| trial.report(step, mean_reward as f64)
+    }
+
+    fn finish(&self, result: Result<()>) {
+        let mut state = lock(&self.state);
+        if let Some(record) = state.trials.get_mut(&self.number) {
+            match result {
+                Ok(()) if record.state == TrialState::Running => {
+                    record.state = TrialState::Complete;
+                }
+                Ok(()) => {}
+                Err(err) => {
+                    record.state = TrialState::Failed;
+                    record.error = Some(format!("{err:#}"));
+                }
+            }
+        }
+        state.save();
+    }
+}
+
+/// How trial hyperparameters are chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStrategy {
+    /// `n_trials` independent draws from the search space.
+    Random { n_trials: usize },
+    /// Every combination of the hyperparameters, see [`SearchSpace::grid`].
+    Grid,
+}
+
+/// Child process training one trial, and the metrics CSV it writes its
+/// evaluation rewards to.
+pub struct TrialProcess {
+    pub command: Command,
+    pub metrics_csv: PathBuf,
+}
+
+/// Evaluation rewards in the complete lines of a metrics CSV written by
+/// [`CsvSink`](crate::CsvSink).
+fn read_eval_rewards(metrics_csv: &Path) -> Result<Vec<(usize, f64)>> {
+    let Ok(contents) = fs::read_to_string(metrics_csv) else {
+        return Ok(vec![]);
+    };
+    // the trial may still be writing the last line
+    let complete = &contents[..contents.rfind('\n').map_or(0, |end| end + 1)];
+    let rewards = read_csv_metric(complete, EVAL_METRIC)
+        .with_context(|| format!("failed to read {}", metrics_csv.display()))?;
+    Ok(rewards
+        .into_iter()
+        .map(|(step, value)| (step as usize, value))
+        .collect())
+}
+
+fn run_process(trial: &Trial, mut process: TrialProcess) -> Result<()> {
+    let mut child = process
+        .command
+        .spawn()
+        .with_context(|| format!("failed to start trial {}", trial.number))?;
+    let mut n_reported = 0;
+    loop {
+        let status = child.try_wait()?;
+        let rewards = read_eval_rewards(&process.metrics_csv)?;
+        for (step, value) in rewards.into_iter().skip(n_reported) {
+            n_reported += 1;
+            if !trial.report(step, value) {
+                child.kill()?;
+                child.wait()?;
+                return Ok(());
+            }
+        }
+        match status {
+            Some(status) if status.success() => return Ok(()),
+            Some(status) => bail!("trial {} exited with {status}", trial.number),
+            None => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+/// Hyperparameter search over a [`SearchSpace`].
+///
+/// Trials run on `n_jobs` threads, either inside the process through
+/// [`optimize`](Self::optimize), or as child processes through
+/// [`optimize_processes`](Self::optimize_processes). They report evaluation
+/// rewards during training, which an optional [`SuccessiveHalving`] pruner
+/// uses to stop unpromising trials early. With a storage file, every result
+/// is saved as JSON, and a study started again with the same settings only
+/// runs the trials that did not finish.
+pub struct Study {
+    space: SearchSpace,
+    strategy: SearchStrategy,
+    pruner: Option<SuccessiveHalving>,
+    storage: Option<PathBuf>,
+    n_jobs: usize,
+    seed: u64,
+}
+
+impl Study {
+    /// Creates a study running one trial at a time, without pruning or storage.
+    pub fn new(space: SearchSpace, strategy: SearchStrategy) -> Self {
+        Self {
+            space,
+            strategy,
+            pruner: None,
+            storage: None,
+            n_jobs: 1,
+            seed: 0,
+        }
+    }
+
+    /// Stops unpromising trials with successive halving.
+    pub fn with_pruner(mut self, pruner: SuccessiveHalving) -> Self {
+        self.pruner = Some(pruner);
+        self
+    }
+
+    /// Saves trial results to the JSON file at `path`, and resumes the study
+    /// stored there.
+    pub fn with_storage<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.storage = Some(path.into());
+        self
+    }
+
+    /// Sets the number of trials running at once.
+    pub fn with_n_jobs(mut self, n_jobs: usize) -> Self {
+        assert!(n_jobs > 0, "a study needs at least one job");
+        self.n_jobs = n_jobs;
+        self
+    }
+
+    /// Sets the seed random search draws hyperparameters with.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Hyperparameters of every trial, by trial number.
+    fn trial_params(&self) -> Result<Vec<Params>> {
+        self.space.validate()?;
+        match self.strategy {
+            // Every trial has its own stream, so resumed studies draw the
+            // same hyperparameters.
+            SearchStrategy::Random { n_trials } => Ok((0..n_trials)
+                .map(|number| {
+                    let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(number as u64));
+                    self.space.sample(&mut rng)
+                })
+                .collect()),
+            SearchStrategy::Grid => self.space.grid(),
+        }
+    }
+
+    /// Loads the finished trials of the stored study. Trials that were
+    /// running when it stopped are dropped, and run again.
+    fn load(&self, trial_params: &[Params]) -> Result<BTreeMap<usize, TrialRecord>> {
+        let Some(path) = self.storage.as_ref().filter(|path| path.exists()) else {
+            return Ok(BTreeMap::new());
+        };
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read study {}", path.display()))?;
+        let study: StudyFile = serde_json::from_str(&content)
+            .with_context(|| format!("invalid study {}", path.display()))?;
+        let mut trials = BTreeMap::new();
+        for trial in study.trials {
+            if trial_params.get(trial.number) != Some(&trial.params) {
+                bail!(
+                    "trial {} of {} does not match the search space of the study",
+                    trial.number,
+                    path.display()
+                );
+            }
+            if trial.state != TrialState::Running {
+                trials.insert(trial.number, trial);
+            }
+        }
+        Ok(trials)
+    }
+
+    fn run(&self, run_trial: impl Fn(&Trial) -> Result<()> + Sync) -> Result<Vec<TrialRecord>> {
+        let trial_params = self.trial_params()?;
+        let trials = self.load(&trial_params)?;
+        let pending: VecDeque<usize> = (0..trial_params.len())
+            .filter(|number| !trials.contains_key(number))
+            .collect();
+        let state = Arc::new(Mutex::new(StudyState {
+            trials,
+            pruner: self.pruner,
+            storage: self.storage.clone(),
+            save_error: None,
+        }));
+        let pending = Mutex::new(pending);
+        thread::scope(|scope| {
+            for _ in 0..self.n_jobs {
+                scope.spawn(|| {
+                    loop {
+                        let Some(number) = pending.lock().unwrap().pop_front() else {
+                            break;
+                        };
+                        let trial = Trial {
+                            number,
+                            params: trial_params[number].clone(),
+                            state: state.clone(),
+                        };
+                        {
+                            let mut state = lock(&state);
+                            state
+                                .trials
+                                .insert(number, TrialRecord::new(number, trial.params.clone()));
+                            state.save();
+                        }
+                        trial.finish(run_trial(&trial));
+                    }
+                });
+            }
+        });
+        let mut state = lock(&state);
+        if let Some(err) = state.save_error.take() {
+            return Err(err);
+        }
+        Ok(state.trials.values().cloned().collect())
+    }
+
+    /// Runs every pending trial in this process and returns all trials of the
+    /// study.
+    ///
+    /// The objective trains one trial, typically with a builder configured
+    /// from the trial's hyperparameters and with
+    /// [`Trial::evaluation_callback`] as its evaluation callback. Errors fail
+    /// the trial, not the study.
+    pub fn optimize<F>(&self, objective: F) -> Result<Vec<TrialRecord>>
+    where
+        F: Fn(&Trial) -> Result<()> + Sync,
+    {
+        self.run(objective)
+    }
+
+    /// Runs every pending trial as a child process and returns all trials of
+    /// the study.
+    ///
+    /// `launch` describes the process of a trial. Evaluation rewards are read
+    /// from the metrics CSV it writes, and pruned trials are killed. A
+    /// process exiting with an error fails its trial, not the study.
+    pub fn optimize_processes<F>(&self, launch: F) -> Result<Vec<TrialRecord>>
+    where
+        F: Fn(&Trial) -> Result<TrialProcess> + Sync,
+    {
+        self.run(|trial| run_process(trial, launch(trial)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::{SearchStrategy, Study, TrialState, best_trial, read_eval_rewards};
+    use crate::tuning::{
+        pruner::SuccessiveHalving,
+        space::{ParamValue, SearchSpace},
+    };
+
+    #[test]
+    fn spaces_are_sampled_and_enumerated() {
+        let space = SearchSpace::new()
+            .with_log_uniform("lr", 1e-5, 1e-3)
+            .with_int("epochs", 1, 3)
+            .with_choice("activation", ["relu", "tanh"]);
+        let params = space.sample(&mut StdRng::seed_from_u64(0));
+        let ParamValue::Float(lr) = params["lr"] else {
+            panic!("expected a float learning rate");
+        };
+        assert!((1e-5..1e-3).contains(&lr));
+        assert!(space.grid().is_err());
+
+        let grid = space.with_choice("lr", [1e-4, 3e-4]).grid().unwrap();
+        assert_eq!(grid.len(), 2 * 3 * 2);
+        assert_eq!(grid[0]["activation"].to_string(), "'relu'");
+        assert_eq!(grid[0]["lr"].to_string(), "0.0001");
+    }
+
+    #[test]
+    fn eval_rewards_are_read_from_complete_rows() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join("r2l_tuning_eval_rewards.csv");
+        std::fs::write(
+            &path,
+            "step,wall_time,name,value,count,min,max\n\
+             100,1.5,eval/mean_reward,2.5,,,\n\
+             100,1.5,\"eval/mean_reward, raw\",9,,,\n\
+             200,2.5,eval/mean_reward,3.5,,,\n\
+             300,3.5,eval/mean_rew",
+        )?;
+        let rewards = read_eval_rewards(&path);
+        std::fs::remove_file(&path)?;
+        assert_eq!(rewards?, [(100, 2.5), (200, 3.5)]);
+        Ok(())
+    }
+
+    #[test]
+    fn successive_halving_promotes_the_best_trials() {
+        let pruner = SuccessiveHalving::new(100, 2).unwrap();
+        assert_eq!(pruner.rung_steps(0), 100);
+        assert_eq!(pruner.rung_steps(2), 400);
+        assert!(pruner.promotes(1., &[1.]));
+        assert!(!pruner.promotes(1., &[1., 2.]));
+        assert!(pruner.promotes(3., &[1., 2., 3., 4.]));
+        assert!(!pruner.promotes(2., &[1., 2., 3., 4.]));
+    }
+
+    #[test]
+    fn studies_prune_and_resume() {
+        let storage = std::env::temp_dir().join("r2l_tuning_study.json");
+        let _ = std::fs::remove_file(&storage);
+        let study = Study::new(
+            SearchSpace::new().with_choice("quality", [1i64, 2, 3, 4]),
+            SearchStrategy::Grid,
+        )
+        .with_pruner(SuccessiveHalving::new(100, 2).unwrap())
+        .with_storage(&storage);
+        let runs = AtomicUsize::new(0);
+        let objective = |trial: &super::Trial| {
+            runs.fetch_add(1, Ordering::SeqCst);
+            let quality = trial.float("quality")?;
+            for step in [100, 200, 300, 400] {
+                if !trial.report(step, quality * step as f64) {
+                    break;
+                }
+            }
+            Ok(())
+        };
+        // Trials run in order, so every later trial beats the earlier ones.
+        let trials = study.optimize(objective).unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 4);
+        assert!(
+            trials
+                .iter()
+                .all(|trial| trial.state == TrialState::Complete)
+        );
+        assert_eq!(best_trial(&trials).unwrap().value, Some(1600.));
+
+        let reversed = Study::new(
+            SearchSpace::new().with_choice("quality", [4i64, 3, 2, 1]),
+            SearchStrategy::Grid,
+        )
+        .with_pruner(SuccessiveHalving::new(100, 2).unwrap());
+        let trials = reversed.optimize(objective).unwrap();
+        let states: Vec<TrialState> = trials.iter().map(|trial| trial.state).collect();
+        assert_eq!(states[0], TrialState::Complete);
+        assert!(states[1..].iter().all(|state| *state == TrialState::Pruned));
+
+        let resumed = study.optimize(objective).unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 8);
+        assert_eq!(resumed.len(), 4);
+        let mismatched = Study::new(
+            SearchSpace::new().with_choice("quality", [5i64]),
+            SearchStrategy::Grid,
+        )
+        .with_storage(&storage);
+        assert!(mismatched.optimize(objective).is_err());
+    }
+}
@@ -0,0 +1,765 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+};
+
+use anyhow::{Result, anyhow, bail};
+use r2l_core::{
+    HookResult,
+    on_policy::algorithm::{
+        OnPolicyAdapters, OnPolicyAlgorithm, OnPolicyAlgorithmHooks, OnPolicyRuntime, Sampler,
+    },
+};
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+use crate::agents::PopulationAgent;
+
+/// Range a perturbed hyperparameter is kept in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+    low: f64,
+    high: f64,
+}
+
+impl Bounds {
+    /// Initial value, drawn log-uniformly when both bounds are positive.
+    fn sample(&self, rng: &mut StdRng) -> f64 {
+        if self.low == self.high {
+            self.low
+        } else if self.low > 0. {
+            rng.random_range(self.low.ln()..self.high.ln()).exp()
+        } else {
+            rng.random_range(self.low..self.high)
+        }
+    }
+}
+
+/// Population-Based Training of PPO or A2C agents.
+///
+/// Every member of the population trains in its own thread. After every
+/// `ready_evaluations` evaluations, a member in the bottom `quantile` of the
+/// latest evaluation rewards copies the parameters and optimizer state of a
+/// random member of the top `quantile` (exploit), takes its hyperparameters
+/// and multiplies each of them by one of the perturbation factors (explore).
+///
+/// Perturbed hyperparameters replace their schedules: members set their
+/// current values again after the `post_rollout_hook` of their outer hooks,
+/// where a learning rate schedule is applied.
+pub struct PopulationBasedTraining {
+    population_size: usize,
+    hyperparameters: BTreeMap<String, Bounds>,
+    ready_evaluations: usize,
+    quantile: f64,
+    perturbation_factors: (f64, f64),
+    seed: u64,
+}
+
+impl PopulationBasedTraining {
+    /// Creates a population of `population_size` members exploiting after
+    /// every evaluation, with the bottom and top quarters of the population
+    /// and perturbation factors of 0.8 and 1.2.
+    pub fn new(population_size: usize) -> Self {
+        assert!(
+            population_size >= 2,
+            "a population needs at least two members"
+        );
+        Self {
+            population_size,
+            hyperparameters: BTreeMap::new(),
+            ready_evaluations: 1,
+            quantile: 0.25,
+            perturbation_factors: (0.8, 1.2),
+            seed: 0,
+        }
+    }
+
+    /// Perturbs the hyperparameter `name` within `[low, high]`.
+    ///
+    /// Names are the ones of [`PopulationAgent::hyperparameters`], such as
+    /// `learning_rate`, `entropy_coeff` and `clip_range`. Members start from
+    /// values drawn in the range, log-uniformly when `low > 0`.
+    pub fn with_hyperparameter(mut self, name: impl Into<String>, low: f64, high: f64) -> Self {
+        assert!(
+            low <= high,
+            "the bounds of a hyperparameter need low <= high"
+        );
+        self.hyperparameters
+            .insert(name.into(), Bounds { low, high });
+        self
+    }
+
+    /// Sets the number of evaluations between two exploit steps of a member.
+    pub fn with_ready_evaluations(mut self, ready_evaluations: usize) -> Self {
+        assert!(
+            ready_evaluations > 0,
+            "members need at least one evaluation"
+        );
+        self.ready_evaluations = ready_evaluations;
+        self
+    }
+
+    /// Sets the fraction of the population that exploits the same fraction at
+    /// the top.
+    pub fn with_quantile(mut self, quantile: f64) -> Self {
+        assert!(
+            quantile > 0. && quantile <= 0.5,
+            "the quantile must be in (0, 0.5]"
+        );
+        self.quantile = quantile;
+        self
+    }
+
+    /// Sets the factors explored hyperparameters are multiplied with.
+    pub fn with_perturbation_factors(mut self, low: f64, high: f64) -> Self {
+        self.perturbation_factors = (low, high);
+        self
+    }
+
+    /// Sets the seed of the initial hyperparameters and of the exploit and
+    /// explore draws.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Trains the population and returns the final state of every member.
+    ///
+    /// `build` creates the algorithm of a member, and must report its
+    /// evaluations through [`PbtMember::evaluation_callback`]. Members stop
+    /// on their own learning schedule, and the first error of a member fails
+    /// the run once every member has stopped.
+    pub fn run<A, S, H, C, F>(&self, build: F) -> Result<Vec<PbtMemberResult>>
+    where
+        A: PopulationAgent,
+        A::State: Send,
+        S: Sampler,
+        H: OnPolicyAlgorithmHooks<A = A, S = S, C = C>,
+        C: OnPolicyAdapters<A::Actor, S>,
+        F: Fn(&PbtMember) -> Result<OnPolicyAlgorithm<A, S, H, C>> + Sync,
+    {
+        let population = Mutex::new(Population {
+            members: (0..self.population_size).map(|_| Slot::default()).collect(),
+        });
+        let results: Vec<Result<PbtMemberResult>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.population_size)
+                .map(|index| {
+                    let population = &population;
+                    let build = &build;
+                    scope.spawn(move || self.run_member(index, population, build))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(anyhow!("a population member panicked")))
+                })
+                .collect()
+        });
+        let results = results.into_iter().collect::<Result<Vec<_>>>()?;
+        if results.iter().all(|result| result.last_reward.is_none()) {
+            bail!(
+                "no member reported an evaluation, build them with an evaluator and `PbtMember::evaluation_callback`"
+            );
+        }
+        Ok(results)
+    }
+
+    fn run_member<A, S, H, C, F>(
+        &self,
+        index: usize,
+        population: &Mutex<Population<A::State>>,
+        build: &F,
+    ) -> Result<PbtMemberResult>
+    where
+        A: PopulationAgent,
+        A::State: Send,
+        S: Sampler,
+        H: OnPolicyAlgorithmHooks<A = A, S = S, C = C>,
+        C: OnPolicyAdapters<A::Actor, S>,
+        F: Fn(&PbtMember) -> Result<OnPolicyAlgorithm<A, S, H, C>>,
+    {
+        let member = PbtMember {
+            index,
+            latest_reward: Arc::new(Mutex::new(None)),
+        };
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(index as u64));
+        let OnPolicyAlgorithm { mut runtime, hooks } = build(&member)?;
+        let hyperparameters: BTreeMap<_, _> = self
+            .hyperparameters
+            .iter()
+            .map(|(name, bounds)| (name.clone(), bounds.sample(&mut rng)))
+            .collect();
+        runtime.agent.set_hyperparameters(&hyperparameters)?;
+        let mut algorithm = OnPolicyAlgorithm {
+            runtime,
+            hooks: PbtHooks {
+                inner: hooks,
+                pbt: self,
+                member,
+                population,
+                rng,
+                hyperparameters,
+                evaluations: 0,
+                last_reward: None,
+                exploits: 0,
+                error: None,
+            },
+        };
+        algorithm.train()?;
+        let hooks = algorithm.hooks;
+        Ok(PbtMemberResult {
+            index,
+            last_reward: hooks.last_reward,
+            hyperparameters: self.tracked(&algorithm.runtime.agent.hyperparameters()),
+            exploits: hooks.exploits,
+        })
+    }
+
+    /// The values of the perturbed hyperparameters in `hyperparameters`.
+    fn tracked(&self, hyperparameters: &BTreeMap<String, f64>) -> BTreeMap<String, f64> {
+        hyperparameters
+            .iter()
+            .filter(|(name, _)| self.hyperparameters.contains_key(*name))
+            .map(|(name, value)| (name.clone(), *value))
+            .collect()
+    }
+
+    /// Perturbs every tracked hyperparameter of `hyperparameters` and keeps
+    /// it within its bounds.
+    fn explore(
+        &self,
+        hyperparameters: &BTreeMap<String, f64>,
+        rng: &mut StdRng,
+    ) -> BTreeMap<String, f64> {
+        let (low_factor, high_factor) = self.perturbation_factors;
+        self.hyperparameters
+            .iter()
+            .filter_map(|(name, bounds)| {
+                let value = hyperparameters.get(name)?;
+                let factor = if rng.random_bool(0.5) {
+                    low_factor
+                } else {
+                    high_factor
+                };
+                Some((
+                    name.clone(),
+                    (value * factor).clamp(bounds.low, bounds.high),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Handle of a population member, passed to the builder of its algorithm.
+pub struct PbtMember {
+    index: usize,
+    latest_reward: Arc<Mutex<Option<f32>>>,
+}
+
+impl PbtMember {
+    /// Index of the member within its population.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Evaluation callback ranking the member by its mean evaluation reward,
+    /// for the builder's `with_evaluation_callback`.
+    pub fn evaluation_callback(&self) -> impl FnMut(usize, f32) -> bool + Send + use<> {
+        let latest_reward = self.latest_reward.clone();
+        move |_, mean_reward| {
+            *latest_reward
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(mean_reward);
+            true
+        }
+    }
+
+    fn take_reward(&self) -> Option<f32> {
+        self.latest_reward
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take()
+    }
+}
+
+/// Final state of a population member.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PbtMemberResult {
+    /// Index of the member within its population.
+    pub index: usize,
+    /// Mean reward of its latest evaluation.
+    pub last_reward: Option<f32>,
+    /// Final values of the perturbed hyperparameters.
+    pub hyperparameters: BTreeMap<String, f64>,
+    /// Number of times it restarted from a better member.
+    pub exploits: usize,
+}
+
+/// Latest published state of every member.
+struct Population<St> {
+    members: Vec<Slot<St>>,
+}
+
+struct Slot<St> {
+    // cleared when the member exploits, until its next evaluation
+    score: Option<f32>,
+    state: Option<St>,
+    hyperparameters: BTreeMap<String, f64>,
+}
+
+impl<St> Default for Slot<St> {
+    fn default() -> Self {
+        Self {
+            score: None,
+            state: None,
+            hyperparameters: BTreeMap::new(),
+        }
+    }
+}
+
+impl<St: Clone> Population<St> {
+    fn scores(&self) -> Vec<Option<f32>> {
+        self.members.iter().map(|slot| slot.score).collect()
+    }
+
+    fn snapshot(&self, index: usize) -> Option<(St, BTreeMap<String, f64>)> {
+        let slot = &self.members[index];
+        Some((slot.state.clone()?, slot.hyperparameters.clone()))
+    }
+}
+
+fn lock<St>(population: &Mutex<Population<St>>) -> MutexGuard<'_, Population<St>> {
+    population
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Member `member` should copy when it is in the bottom `quantile` of the
+/// members with a score, one of the members in the top `quantile`.
+fn exploit_candidates(scores: &[Option<f32>], member: usize, quantile: f64) -> Vec<usize> {
+    let mut ranked: Vec<(usize, f32)> = scores
+        .iter()
+        .enumerate()
+        .filter_map(|(index, score)| score.map(|score| (index, score)))
+        .collect();
+    ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    let ranked: Vec<usize> = ranked.into_iter().map(|(index, _)| index).collect();
+    let n_quantile = ((ranked.len() as f64 * quantile).round() as usize)
+        .max(1)
+        .min(ranked.len() / 2);
+    if n_quantile == 0 || !ranked[ranked.len() - n_quantile..].contains(&member) {
+        return vec![];
+    }
+    ranked[..n_quantile].to_vec()
+}
+
+/// Outer hooks of a member, exploiting and exploring after its evaluations.
+struct PbtHooks<'a, H: OnPolicyAlgorithmHooks<A: PopulationAgent>> {
+    inner: H,
+    pbt: &'a PopulationBasedTraining,
+    member: PbtMember,
+    population: &'a Mutex<Population<<H::A as PopulationAgent>::State>>,
+    rng: StdRng,
+    // current values of the perturbed hyperparameters
+    hyperparameters: BTreeMap<String, f64>,
+    evaluations: usize,
+    last_reward: Option<f32>,
+    exploits: usize,
+    error: Option<anyhow::Error>,
+}
+
+impl<H> PbtHooks<'_, H>
+where
+    H: OnPolicyAlgorithmHooks<A: PopulationAgent>,
+{
+    fn exploit_and_explore(
+        &mut self,
+        runtime: &mut OnPolicyRuntime<H::A, H::S, H::C>,
+        reward: f32,
+    ) -> Result<()> {
+        let agent = &mut runtime.agent;
+        let index = self.member.index;
+        let source = {
+            let mut population = lock(self.population);
+            let slot = &mut population.members[index];
+            slot.score = Some(reward);
+            slot.state = Some(agent.state()?);
+            slot.hyperparameters = self.pbt.tracked(&agent.hyperparameters());
+            let candidates = exploit_candidates(&population.scores(), index, self.pbt.quantile);
+            if candidates.is_empty() {
+                return Ok(());
+            }
+            let source = candidates[self.rng.random_range(0..candidates.len())];
+            population.snapshot(source)
+        };
+        let Some((state, hyperparameters)) = source else {
+            return Ok(());
+        };
+        agent.load_state(&state)?;
+        self.hyperparameters = self.pbt.explore(&hyperparameters, &mut self.rng);
+        agent.set_hyperparameters(&self.hyperparameters)?;
+        self.exploits += 1;
+        // the copied state has not been evaluated with its new hyperparameters
+        let mut population = lock(self.population);
+        population.members[index].score = None;
+        population.members[index].state = None;
+        Ok(())
+    }
+}
+
+impl<H> OnPolicyAlgorithmHooks for PbtHooks<'_, H>
+where
+    H: OnPolicyAlgorithmHooks<A: PopulationAgent>,
+{
+    type A = H::A;
+    type S = H::S;
+    type C = H::C;
+
+    fn init_hook(
+        &mut self,
+        runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
+    ) -> HookResult {
+        self.inner.init_hook(runtime)
+    }
+
+    fn post_rollout_hook(
+        &mut self,
+        runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
+    ) -> HookResult {
+        let result = self.inner.post_rollout_hook(runtime);
+        // the inner hooks may have applied a schedule to a perturbed hyperparameter
+        if let Err(err) = runtime.agent.set_hyperparameters(&self.hyperparameters) {
+            self.error = Some(err);
+            return HookResult::Break;
+        }
+        result
+    }
+
+    fn post_training_hook(
+        &mut self,
+        runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
+    ) -> HookResult {
+        let result = self.inner.post_training_hook(runtime);
+        let Some(reward) = self.member.take_reward() else {
+            return result;
+        };
+        self.last_reward = Some(reward);
+        self.evaluations += 1;
+        if !self.evaluations.is_multiple_of(self.pbt.ready_evaluations) {
+            return result;
+        }
+        if let Err(err) = self.exploit_and_explore(runtime, reward) {
+            self.error = Some(err);
+            return HookResult::Break;
+        }
+        result
+    }
+
+    fn shutdown_hook(
+        &mut self,
+        runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
+    ) -> Result<()> {
+        let result = self.inner.shutdown_hook(runtime);
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Barrier},
+    };
+
+    use anyhow::Result;
+    use r2l_core::{
+        HookResult,
+        buffers::{TrajectoryBatch, buffer::TrajectoryView},
+        models::Actor,
+        on_policy::algorithm::{
+            Agent, DefaultAdapter, OnPolicyAlgorithm, OnPolicyAlgorithmHooks, OnPolicyRuntime,
+            Sampler,
+        },
+        tensor::TensorData,
+    };
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::{Bounds, PopulationBasedTraining, exploit_candidates};
+    use crate::agents::PopulationAgent;
+
+    #[derive(Clone)]
+    struct IdentityActor;
+
+    impl Actor for IdentityActor {
+        type Tensor = TensorData;
+
+        fn action(&self, observation: TensorData) -> Result<TensorData> {
+            Ok(observation)
+        }
+    }
+
+    // Its weight grows by the learning rate at every update.
+    struct CounterAgent {
+        weight: f64,
+        learning_rate: f64,
+    }
+
+    impl Agent for CounterAgent {
+        type Tensor = TensorData;
+        type Actor = IdentityActor;
+
+        fn actor(&self) -> IdentityActor {
+            IdentityActor
+        }
+
+        fn learn<B: TrajectoryBatch<TensorData>>(&mut self, _buffers: &[B]) -> Result<()> {
+            self.weight += self.learning_rate;
+            Ok(())
+        }
+
+        fn set_learning_rate(&mut self, learning_rate: f64) {
+            self.learning_rate = learning_rate;
+        }
+    }
+
+    impl PopulationAgent for CounterAgent {
+        type State = f64;
+
+        fn state(&self) -> Result<f64> {
+            Ok(self.weight)
+        }
+
+        fn load_state(&mut self, state: &f64) -> Result<()> {
+            self.weight = *state;
+            Ok(())
+        }
+
+        fn hyperparameters(&self) -> BTreeMap<String, f64> {
+            BTreeMap::from([("learning_rate".to_owned(), self.learning_rate)])
+        }
+
+        fn set_hyperparameters(&mut self, hyperparameters: &BTreeMap<String, f64>) -> Result<()> {
+            for (name, value) in hyperparameters {
+                match name.as_str() {
+                    "learning_rate" => self.learning_rate = *value,
+                    _ => anyhow::bail!("unknown hyperparameter `{name}`"),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    struct NoopSampler;
+
+    impl Sampler for NoopSampler {
+        type Tensor = TensorData;
+
+        fn collect_rollouts<A: Actor<Tensor = TensorData> + Clone>(
+            &mut self,
+            _actor: A,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn trajectory_views<'a>(&'a mut self) -> impl AsRef<[TrajectoryView<'a, TensorData>]> {
+            Vec::new()
+        }
+    }
+
+    // Reports the weight as evaluation reward after every update and breaks
+    // after `rollouts` updates. Members meet at `barrier` before their second
+    // report, so both have published a state by then. Sets the learning rate
+    // to `scheduled_learning_rate` after every rollout, as a schedule does.
+    struct EvaluationHook<F> {
+        callback: Option<F>,
+        rollouts: usize,
+        barrier: Arc<Barrier>,
+        scheduled_learning_rate: Option<f64>,
+    }
+
+    impl<F: FnMut(usize, f32) -> bool> OnPolicyAlgorithmHooks for EvaluationHook<F> {
+        type A = CounterAgent;
+        type S = NoopSampler;
+        type C = DefaultAdapter;
+
+        fn init_hook(
+            &mut self,
+            _runtime: &mut OnPolicyRuntime<CounterAgent, NoopSampler>,
+        ) -> HookResult {
+            HookResult::Continue
+        }
+
+        fn post_rollout_hook(
+            &mut self,
+            runtime: &mut OnPolicyRuntime<CounterAgent, NoopSampler>,
+        ) -> HookResult {
+            if let Some(learning_rate) = self.scheduled_learning_rate {
+                runtime.agent.set_learning_rate(learning_rate);
+            }
+            HookResult::Continue
+        }
+
+        fn post_training_hook(
+            &mut self,
+            runtime: &mut OnPolicyRuntime<CounterAgent, NoopSampler>,
+        ) -> HookResult {
+            self.rollouts -= 1;
+            if self.rollouts == 1 {
+                self.barrier.wait();
+            }
+            if let Some(callback) = &mut self.callback {
+                callback(0, runtime.agent.weight as f32);
+            }
+            if self.rollouts == 0 {
+                HookResult::Break
+            } else {
+                HookResult::Continue
+            }
+        }
+
+        fn shutdown_hook(
+            &mut self,
+            _runtime: &mut OnPolicyRuntime<CounterAgent, NoopSampler>,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn bottom_members_exploit_top_members() {
+        let scores = [Some(1.), Some(4.), None, Some(2.), Some(3.), Some(0.)];
+        // five ranked members, the quantile rounds to one of them
+        assert_eq!(exploit_candidates(&scores, 5, 0.25), vec![1]);
+        assert!(exploit_candidates(&scores, 0, 0.25).is_empty());
+        assert!(exploit_candidates(&scores, 2, 0.25).is_empty());
+        assert_eq!(exploit_candidates(&scores, 0, 0.5), vec![1, 4]);
+        assert!(exploit_candidates(&[Some(1.), None], 0, 0.5).is_empty());
+
+        let pbt = PopulationBasedTraining::new(4)
+            .with_hyperparameter("learning_rate", 1e-4, 1e-3)
+            .with_perturbation_factors(0.5, 2.);
+        let hyperparameters = BTreeMap::from([
+            ("learning_rate".to_owned(), 8e-4),
+            ("clip_range".to_owned(), 0.2),
+        ]);
+        let explored = pbt.explore(&hyperparameters, &mut StdRng::seed_from_u64(0));
+        assert_eq!(explored.len(), 1);
+        assert!([4e-4, 1e-3].contains(&explored["learning_rate"]));
+    }
+
+    #[test]
+    fn explore_keeps_hyperparameters_within_bounds() {
+        let pbt = PopulationBasedTraining::new(2)
+            .with_hyperparameter("learning_rate", 1e-4, 1e-3)
+            .with_hyperparameter("entropy_coeff", 0., 0.1)
+            .with_perturbation_factors(0.1, 10.);
+        let hyperparameters = BTreeMap::from([
+            ("learning_rate".to_owned(), 2e-4),
+            ("entropy_coeff".to_owned(), 0.05),
+        ]);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..32 {
+            let explored = pbt.explore(&hyperparameters, &mut rng);
+            assert!([1e-4, 1e-3].contains(&explored["learning_rate"]));
+            let entropy_coeff = explored["entropy_coeff"];
+            assert!((entropy_coeff - 0.005).abs() < 1e-9 || entropy_coeff == 0.1);
+        }
+        // untracked hyperparameters are neither explored nor reported
+        let tracked = pbt.tracked(&BTreeMap::from([
+            ("learning_rate".to_owned(), 2e-4),
+            ("clip_range".to_owned(), 0.2),
+        ]));
+        assert_eq!(tracked.keys().collect::<Vec<_>>(), ["learning_rate"]);
+    }
+
+    #[test]
+    fn initial_hyperparameters_are_drawn_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let positive = Bounds {
+            low: 1e-5,
+            high: 1e-2,
+        };
+        let samples = (0..1000)
+            .map(|_| positive.sample(&mut rng))
+            .collect::<Vec<_>>();
+        assert!(samples.iter().all(|s| (1e-5..1e-2).contains(s)));
+        // log-uniform draws put a third of the samples in each decade
+        let first_decade = samples.iter().filter(|s| **s < 1e-4).count();
+        assert!((250..420).contains(&first_decade), "{first_decade}");
+        let fixed = Bounds {
+            low: 0.2,
+            high: 0.2,
+        };
+        assert_eq!(fixed.sample(&mut rng), 0.2);
+        let signed = Bounds { low: -1., high: 1. };
+        assert!((-1.0..1.).contains(&signed.sample(&mut rng)));
+    }
+
+    fn run_population(
+        report: bool,
+        scheduled_learning_rate: Option<f64>,
+    ) -> Result<Vec<super::PbtMemberResult>> {
+        let barrier = Arc::new(Barrier::new(2));
+        PopulationBasedTraining::new(2)
+            .with_hyperparameter("learning_rate", 1., 1.)
+            .with_quantile(0.5)
+            .run(|member| {
+                Ok(OnPolicyAlgorithm {
+                    runtime: OnPolicyRuntime {
+                        agent: CounterAgent {
+                            weight: 100. * member.index() as f64,
+                            learning_rate: 0.,
+                        },
+                        sampler: NoopSampler,
+                        adapter: DefaultAdapter,
+                    },
+                    hooks: EvaluationHook {
+                        callback: report.then(|| member.evaluation_callback()),
+                        rollouts: 3,
+                        barrier: barrier.clone(),
+                        scheduled_learning_rate,
+                    },
+                })
+            })
+    }
+
+    #[test]
+    fn bottom_member_restarts_from_the_state_of_the_top_member() -> Result<()> {
+        let results = run_population(true, None)?;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].last_reward, Some(103.));
+        // member 0 copied a weight of at least 101 after its second update
+        assert!(results[0].exploits >= 1);
+        assert!(results[0].last_reward.unwrap() >= 102.);
+        assert!(
+            results
+                .iter()
+                .all(|result| result.hyperparameters["learning_rate"] == 1.)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn perturbed_learning_rates_replace_the_schedule() -> Result<()> {
+        let results = run_population(true, Some(5.))?;
+        // every update still steps by the perturbed learning rate of 1
+        assert_eq!(results[1].last_reward, Some(103.));
+        assert!(
+            results
+                .iter()
+                .all(|result| result.hyperparameters["learning_rate"] == 1.)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_fails_when_no_member_reports_an_evaluation() {
+        assert!(run_population(false, None).is_err());
+    }
+}
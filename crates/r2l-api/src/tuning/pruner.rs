@@ -0,0 +1,103 @@
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// Asynchronous successive halving (ASHA).
+///
+/// Rungs sit at `min_steps * reduction_factor^k` sampled environment steps. A
+/// trial reaching a rung is stopped unless its latest evaluation reward is
+/// among the best `1 / reduction_factor` of the rewards every trial reported
+/// at that rung so far. Trials never wait for each other, so the first trials
+/// of a study are rarely pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "SuccessiveHalvingConfig")]
+pub struct SuccessiveHalving {
+    /// Sampled environment steps of the first rung.
+    pub min_steps: usize,
+    /// Ratio between the budgets of consecutive rungs, and the inverse of the
+    /// fraction of trials promoted past each rung.
+    pub reduction_factor: usize,
+}
+
+// Unvalidated form of `SuccessiveHalving`, so deserializing goes through `new`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuccessiveHalvingConfig {
+    min_steps: usize,
+    reduction_factor: usize,
+}
+
+impl TryFrom<SuccessiveHalvingConfig> for SuccessiveHalving {
+    type Error = anyhow::Error;
+
+    fn try_from(config: SuccessiveHalvingConfig) -> Result<Self> {
+        Self::new(config.min_steps, config.reduction_factor)
+    }
+}
+
+impl SuccessiveHalving {
+    /// Creates the pruner. Fails unless `min_steps > 0` and
+    /// `reduction_factor >= 2`.
+    pub fn new(min_steps: usize, reduction_factor: usize) -> Result<Self> {
+        if min_steps == 0 {
+            bail!("the first rung needs a positive step count");
+        }
+        if reduction_factor < 2 {
+            bail!("the reduction factor must be at least 2, got {reduction_factor}");
+        }
+        Ok(Self {
+            min_steps,
+            reduction_factor,
+        })
+    }
+
+    /// Sampled environment steps of rung `rung`.
+    pub fn rung_steps(&self, rung: usize) -> usize {
+        u32::try_from(rung)
+            .ok()
+            .and_then(|rung| self.reduction_factor.checked_pow(rung))
+            .and_then(|factor| self.min_steps.checked_mul(factor))
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Whether `value` survives a rung where every trial that reached it,
+    /// including this one, reported `rung_values`.
+    pub fn promotes(&self, value: f64, rung_values: &[f64]) -> bool {
+        let mut rung_values = rung_values.to_vec();
+        rung_values.sort_by(|a, b| b.total_cmp(a));
+        let n_promoted = (rung_values.len() / self.reduction_factor).max(1);
+        rung_values
+            .get(n_promoted - 1)
+            .is_none_or(|threshold| value >= *threshold)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SuccessiveHalving;
+
+    #[test]
+    fn invalid_pruners_are_rejected() {
+        assert!(SuccessiveHalving::new(0, 2).is_err());
+        assert!(SuccessiveHalving::new(100, 1).is_err());
+        assert!(SuccessiveHalving::new(100, 2).is_ok());
+    }
+
+    #[test]
+    fn deserialized_pruners_are_validated() -> anyhow::Result<()> {
+        let pruner: SuccessiveHalving =
+            serde_json::from_str(r#"{"min_steps": 100, "reduction_factor": 3}"#)?;
+        assert_eq!(pruner, SuccessiveHalving::new(100, 3)?);
+        let err = serde_json::from_str::<SuccessiveHalving>(
+            r#"{"min_steps": 100, "reduction_factor": 1}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("at least 2"), "{err}");
+        assert!(
+            serde_json::from_str::<SuccessiveHalving>(
+                r#"{"min_steps": 100, "reduction_factor": 3, "rungs": 2}"#
+            )
+            .is_err()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use rand::{RngExt, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ConfigFormat;
+
+/// Value of a hyperparameter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParamValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl From<bool> for ParamValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for ParamValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f64> for ParamValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<&str> for ParamValue {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_owned())
+    }
+}
+
+impl From<String> for ParamValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+/// Formats the value as YAML, the format of
+/// [`ExperimentConfig::with_override`](crate::ExperimentConfig::with_override).
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value:?}"),
+            Self::Str(value) => write!(f, "'{}'", value.replace('\'', "''")),
+        }
+    }
+}
+
+/// Hyperparameters of one trial, by name.
+pub type Params = BTreeMap<String, ParamValue>;
+
+/// Distribution a hyperparameter is drawn from, selected by `type`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Distribution {
+    /// A float drawn uniformly from `[low, high)`.
+    Uniform { low: f64, high: f64 },
+    /// A positive float whose logarithm is uniform, for learning rates and
+    /// other scale parameters.
+    LogUniform { low: f64, high: f64 },
+    /// An integer drawn uniformly from `low..=high`.
+    Int { low: i64, high: i64 },
+    /// One of `values`.
+    Choice { values: Vec<ParamValue> },
+}
+
+impl Distribution {
+    fn validate(&self) -> Result<()> {
+        match self {
+            Self::Uniform { low, high } if low >= high => {
+                bail!("uniform distribution needs low < high, found [{low}, {high})")
+            }
+            Self::LogUniform { low, high } if *low <= 0. || low >= high => {
+                bail!("log-uniform distribution needs 0 < low < high, found [{low}, {high})")
+            }
+            Self::Int { low, high } if low > high => {
+                bail!("int distribution needs low <= high, found {low}..={high}")
+            }
+            Self::Choice { values } if values.is_empty() => {
+                bail!("choice distribution needs at least one value")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> ParamValue {
+        match self {
+            Self::Uniform { low, high } => ParamValue::Float(rng.random_range(*low..*high)),
+            Self::LogUniform { low, high } => {
+                ParamValue::Float(rng.random_range(low.ln()..high.ln()).exp())
+            }
+            Self::Int { low, high } => ParamValue::Int(rng.random_range(*low..=*high)),
+            Self::Choice { values } => values[rng.random_range(0..values.len())].clone(),
+        }
+    }
+
+    /// Every value of a discrete distribution.
+    fn grid(&self) -> Result<Vec<ParamValue>> {
+        match self {
+            Self::Int { low, high } => Ok((*low..=*high).map(ParamValue::Int).collect()),
+            Self::Choice { values } => Ok(values.clone()),
+            Self::Uniform { .. } | Self::LogUniform { .. } => {
+                bail!("grid search needs int or choice distributions, found {self:?}")
+            }
+        }
+    }
+}
+
+/// Hyperparameter distributions by name.
+///
+/// When trials run through [`ExperimentConfig`](crate::ExperimentConfig)s,
+/// names are override paths such as `algorithm.gamma`. In TOML:
+///
+/// ```toml
+/// [params."training.learning_rate"]
+/// type = "log_uniform"
+/// low = 1e-5
+/// high = 1e-3
+///
+/// [params."algorithm.total_epochs"]
+/// type = "choice"
+/// values = [5, 10, 20]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearchSpace {
+    params: BTreeMap<String, Distribution>,
+}
+
+impl SearchSpace {
+    /// Creates an empty search space.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a search space from a `.toml`, `.yaml` or `.yml` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read search space {}", path.display()))?;
+        let space: Self = match ConfigFormat::from_path(path)? {
+            ConfigFormat::Toml => toml::from_str(&content)?,
+            ConfigFormat::Yaml => yaml_serde::from_str(&content)?,
+        };
+        space
+            .validate()
+            .with_context(|| format!("invalid search space {}", path.display()))?;
+        Ok(space)
+    }
+
+    /// Adds a hyperparameter, replacing any previous one of the same name.
+    pub fn with_param(mut self, name: impl Into<String>, distribution: Distribution) -> Self {
+        self.params.insert(name.into(), distribution);
+        self
+    }
+
+    /// Adds a float drawn uniformly from `[low, high)`.
+    pub fn with_uniform(self, name: impl Into<String>, low: f64, high: f64) -> Self {
+        self.with_param(name, Distribution::Uniform { low, high })
+    }
+
+    /// Adds a float drawn log-uniformly from `[low, high)`.
+    pub fn with_log_uniform(self, name: impl Into<String>, low: f64, high: f64) -> Self {
+        self.with_param(name, Distribution::LogUniform { low, high })
+    }
+
+    /// Adds an integer drawn uniformly from `low..=high`.
+    pub fn with_int(self, name: impl Into<String>, low: i64, high: i64) -> Self {
+        self.with_param(name, Distribution::Int { low, high })
+    }
+
+    /// Adds a choice between `values`.
+    pub fn with_choice<V: Into<ParamValue>>(
+        self,
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        self.with_param(name, Distribution::Choice { values })
+    }
+
+    /// Fails on an empty space or an invalid distribution.
+    pub fn validate(&self) -> Result<()> {
+        if self.params.is_empty() {
+            bail!("the search space has no parameters");
+        }
+        for (name, distribution) in &self.params {
+            distribution
+                .validate()
+                .with_context(|| format!("invalid distribution of `{name}`"))?;
+        }
+        Ok(())
+    }
+
+    /// Draws one value of every hyperparameter.
+    pub fn sample(&self, rng: &mut StdRng) -> Params {
+        self.params
+            .iter()
+            .map(|(name, distribution)| (name.clone(), distribution.sample(rng)))
+            .collect()
+    }
+
+    /// Every combination of the values of discrete hyperparameters.
+    pub fn grid(&self) -> Result<Vec<Params>> {
+        let mut grid = vec![Params::new()];
+        for (name, distribution) in &self.params {
+            let values = distribution
+                .grid()
+                .with_context(|| format!("`{name}` cannot be searched on a grid"))?;
+            grid = grid
+                .into_iter()
+                .flat_map(|params| {
+                    values.iter().map(move |value| {
+                        let mut params = params.clone();
+                        params.insert(name.clone(), value.clone());
+                        params
+                    })
+                })
+                .collect();
+        }
+        Ok(grid)
+    }
+}
@@ -1,5 +1,7 @@
 use r2l_core::{
-    buffers::buffer::TrajectoryBuffer, running_mean::RunningMeanStdF32, tensor::R2lTensor,
+    buffers::TrajectoryBatch,
+    episode::{EpisodeMonitor, EpisodeStats, EpisodeTracker, mean_of},
+    tensor::R2lTensor,
 };
 
 pub fn mean(numbers: &[f32]) -> f32 {
@@ -20,51 +22,60 @@ pub fn fmt_stat(x: f32) -> String {
     }
 }
 
-const EPSILON: f32 = 1e-8;
-
-/// Normalizes rewards using the running variance of discounted returns.
-pub struct RewardNormalizer {
-    reward_accumulator: Vec<f32>,
-    return_rms: RunningMeanStdF32,
-    gamma: f32,
-    clip_reward: f32,
+/// Completed-episode summaries shared by the default agent hook reporters.
+///
+/// Episodes are read from the sampler's [`EpisodeMonitor`] when one is
+/// installed. Without a monitor, the reporter falls back to tracking episodes
+/// over the rollout batches, which only sees rewards after sampler-side
+/// normalization. The latest averages are kept when a rollout completes no
+/// episode.
+pub(crate) struct EpisodeReporter {
+    monitor: Option<EpisodeMonitor>,
+    trackers: Vec<EpisodeTracker>,
+    pub(crate) average_reward: f32,
+    pub(crate) average_length: f32,
 }
 
-impl RewardNormalizer {
-    /// Creates a reward normalizer with the given discount and clipping bounds.
-    pub fn new(n_envs: usize, gamma: f32, clip_reward: f32) -> Self {
+impl EpisodeReporter {
+    pub(crate) fn new(n_envs: usize, monitor: Option<EpisodeMonitor>) -> Self {
         Self {
-            reward_accumulator: vec![0.0; n_envs],
-            return_rms: RunningMeanStdF32::new(),
-            gamma,
-            clip_reward,
+            monitor,
+            trackers: vec![EpisodeTracker::new(); n_envs],
+            average_reward: 0.,
+            average_length: 0.,
+        }
+    }
+
+    pub(crate) fn update<T: R2lTensor, B: TrajectoryBatch<T>>(&mut self, batches: &[B]) {
+        let episodes = match &self.monitor {
+            Some(monitor) => monitor.rollout_episodes(),
+            None => self.track_batches(batches),
+        };
+        if let Some(average_reward) = mean_of(&episodes, |stats| stats.reward) {
+            self.average_reward = average_reward;
+        }
+        if let Some(average_length) = mean_of(&episodes, |stats| stats.length as f32) {
+            self.average_length = average_length;
         }
     }
 
-    pub(crate) fn normalize<T: R2lTensor>(&mut self, buffers: &mut [TrajectoryBuffer<T>]) {
-        let n_steps = buffers[0].len();
-        for step in 0..n_steps {
-            for (discounted_return, buffer) in
-                self.reward_accumulator.iter_mut().zip(buffers.iter())
+    fn track_batches<T: R2lTensor, B: TrajectoryBatch<T>>(
+        &mut self,
+        batches: &[B],
+    ) -> Vec<EpisodeStats> {
+        let mut episodes = vec![];
+        for (tracker, batch) in self.trackers.iter_mut().zip(batches.iter()) {
+            for ((reward, terminated), truncated) in batch
+                .rewards()
+                .iter()
+                .zip(batch.terminated())
+                .zip(batch.truncated())
             {
-                *discounted_return = *discounted_return * self.gamma + buffer.rewards()[step];
-            }
-            self.return_rms.update(&self.reward_accumulator);
-            let reward_scale = (self.return_rms.var + EPSILON).sqrt();
-            for (env_idx, buffer) in buffers.iter_mut().enumerate() {
-                let done = buffer.terminated()[step] || buffer.truncated()[step];
-                let reward = buffer.rewards()[step];
-                buffer.rewards_mut()[step] =
-                    (reward / reward_scale).clamp(-self.clip_reward, self.clip_reward);
-                if done {
-                    self.reward_accumulator[env_idx] = 0.0;
+                if let Some(stats) = tracker.record(*reward, *terminated || *truncated) {
+                    episodes.push(stats);
                 }
             }
         }
-    }
-
-    /// Clears environment-local discounted returns while preserving running statistics.
-    pub fn reset_returns(&mut self) {
-        self.reward_accumulator.fill(0.0);
+        episodes
     }
 }
@@ -7,6 +7,7 @@ use burn::{
 };
 use burn_store::{ModuleStore, SafetensorsStore};
 use r2l_core::{
+    init::{LayerRole, WeightInit},
     models::{ActivationFunction, Actor, Policy},
     rng::with_rng,
 };
@@ -28,14 +29,31 @@ impl<B: Backend> BernoulliDistribution<B> {
         hidden_layers: &[usize],
         action_size: usize,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> Self {
         let layers = &[&[observation_size], hidden_layers, &[action_size]].concat();
-        let logits = Sequential::build(layers, activation);
+        let logits = Sequential::build(layers, activation, init, LayerRole::PolicyHead);
         Self {
             logits,
             action_size,
         }
     }
+
+    /// Runs a batch of observations through the first `depth` hidden layers
+    /// of the policy network.
+    pub(crate) fn features(&self, observations: Tensor<B, 2>, depth: usize) -> Tensor<B, 2> {
+        self.logits.features(observations, depth)
+    }
+
+    // Entropy of every bit for each state.
+    fn entropy_per_bit(&self, states: &[Tensor<B, 1>]) -> Tensor<B, 2> {
+        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
+        let probs = sigmoid(self.logits.forward(states)).clamp(1e-6, 1. - 1e-6);
+        let ones = probs.ones_like();
+        let entropy_per_bit = probs.clone() * probs.clone().log()
+            + (ones.clone() - probs.clone()) * (ones - probs).log();
+        entropy_per_bit.neg()
+    }
 }
 
 impl<B: Backend> Actor for BernoulliDistribution<B> {
@@ -85,15 +103,36 @@ impl<B: Backend> Policy for BernoulliDistribution<B> {
     }
 
     fn entropy(&self, states: &[Self::Tensor]) -> anyhow::Result<Self::Tensor> {
-        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
-        let probs = sigmoid(self.logits.forward(states)).clamp(1e-6, 1. - 1e-6);
-        let ones = probs.ones_like();
-        let entropy_per_bit = probs.clone() * probs.clone().log()
-            + (ones.clone() - probs.clone()) * (ones - probs).log();
-        Ok(entropy_per_bit.neg().sum_dim(1).mean())
+        Ok(self.entropy_per_bit(states).sum_dim(1).mean())
+    }
+
+    fn entropy_per_dimension(&self, states: &[Self::Tensor]) -> anyhow::Result<Vec<f32>> {
+        let entropy = self.entropy_per_bit(states).mean_dim(0);
+        Ok(entropy.into_data().iter::<f32>().collect())
     }
 
     fn std(&self) -> anyhow::Result<f32> {
         bail!("standard deviation is not defined for Bernoulli distributions")
     }
+
+    fn distribution_parameters(&self, states: &[Self::Tensor]) -> anyhow::Result<Self::Tensor> {
+        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
+        Ok(self.logits.forward(states).flatten(0, 1))
+    }
+
+    fn kl_divergence(
+        &self,
+        states: &[Self::Tensor],
+        old: &Self::Tensor,
+    ) -> anyhow::Result<Self::Tensor> {
+        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
+        let logits = self.logits.forward(states);
+        let old_probs = sigmoid(old.clone().reshape(logits.shape())).clamp(1e-6, 1. - 1e-6);
+        let probs = sigmoid(logits).clamp(1e-6, 1. - 1e-6);
+        let ones = probs.ones_like();
+        let ones_kl = old_probs.clone() * (old_probs.clone().log() - probs.clone().log());
+        let zeros_kl = (ones.clone() - old_probs.clone())
+            * ((ones.clone() - old_probs).log() - (ones - probs).log());
+        Ok((ones_kl + zeros_kl).sum_dim(1).mean())
+    }
 }
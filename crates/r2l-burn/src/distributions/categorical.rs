@@ -9,6 +9,7 @@ use burn::{
 };
 use burn_store::{ModuleSnapshot, ModuleStore, SafetensorsStore};
 use r2l_core::{
+    init::{LayerRole, WeightInit},
     models::{ActivationFunction, Actor, Policy},
     rng::with_rng,
 };
@@ -30,9 +31,14 @@ pub struct CategoricalDistribution<B: Backend> {
 
 impl<B: Backend> CategoricalDistribution<B> {
     /// Builds a categorical policy network.
-    pub fn build(logits_layers: &[usize], activation: ActivationFunction) -> Self {
+    pub fn build(
+        logits_layers: &[usize],
+        activation: ActivationFunction,
+        init: WeightInit,
+    ) -> Self {
         let action_size = *logits_layers.last().unwrap();
-        let logits: Sequential<B> = Sequential::build(logits_layers, activation);
+        let logits: Sequential<B> =
+            Sequential::build(logits_layers, activation, init, LayerRole::PolicyHead);
         Self {
             logits,
             action_size,
@@ -41,12 +47,29 @@ impl<B: Backend> CategoricalDistribution<B> {
 
     /// Builds a categoriacal policy using a safetensor store
     pub fn from_store(store: &mut SafetensorsStore) -> Self {
+        Self::load(store, ActivationFunction::default())
+            .expect("failed to load CategoricalDistribution from store")
+    }
+
+    /// Builds a categorical policy with the given hidden-layer activation
+    /// from a safetensor store.
+    pub(crate) fn load(
+        store: &mut SafetensorsStore,
+        activation: ActivationFunction,
+    ) -> anyhow::Result<Self> {
         let logits_layers = Sequential::<B>::dims_from_store("logits", store);
-        let mut distribution = Self::build(&logits_layers, ActivationFunction::default());
-        distribution
-            .load_from(store)
-            .expect("failed to load CategoricalDistribution from store");
-        distribution
+        if logits_layers.is_empty() {
+            bail!("the policy has no `logits` network");
+        }
+        let mut distribution = Self::build(&logits_layers, activation, WeightInit::default());
+        distribution.load_from(store)?;
+        Ok(distribution)
+    }
+
+    /// Runs a batch of observations through the first `depth` hidden layers
+    /// of the policy network.
+    pub(crate) fn features(&self, observations: Tensor<B, 2>, depth: usize) -> Tensor<B, 2> {
+        self.logits.features(observations, depth)
     }
 }
 
@@ -104,4 +127,22 @@ impl<B: Backend> Policy for CategoricalDistribution<B> {
     fn std(&self) -> anyhow::Result<f32> {
         bail!("standard deviation is not defined for categorical distributions")
     }
+
+    fn distribution_parameters(&self, states: &[Self::Tensor]) -> anyhow::Result<Self::Tensor> {
+        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
+        Ok(self.logits.forward(states).flatten(0, 1))
+    }
+
+    fn kl_divergence(
+        &self,
+        states: &[Self::Tensor],
+        old: &Self::Tensor,
+    ) -> anyhow::Result<Self::Tensor> {
+        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
+        let logits = self.logits.forward(states);
+        let old_log_probs = log_softmax(old.clone().reshape(logits.shape()), 1);
+        let log_probs = log_softmax(logits, 1);
+        let kl_per_state = (old_log_probs.clone().exp() * (old_log_probs - log_probs)).sum_dim(1);
+        Ok(kl_per_state.mean())
+    }
 }
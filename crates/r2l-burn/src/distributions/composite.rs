@@ -3,6 +3,7 @@ use burn::{Tensor, module::Module, prelude::Backend};
 use burn_store::{ModuleStore, SafetensorsStore};
 use r2l_core::{
     env::Space,
+    init::WeightInit,
     models::{ActivationFunction, Actor, Policy},
     tensor::R2lTensor,
 };
@@ -52,6 +53,28 @@ impl<B: Backend> CompositePolicyChildren<B> {
         }
     }
 
+    fn distribution_parameters(&self, states: &[Tensor<B, 1>]) -> anyhow::Result<Tensor<B, 1>> {
+        match self {
+            Self::Categorical(policy) => policy.distribution_parameters(states),
+            Self::Diag(policy) => policy.distribution_parameters(states),
+            Self::MultiCategorical(policy) => policy.distribution_parameters(states),
+            Self::Bernoulli(policy) => policy.distribution_parameters(states),
+        }
+    }
+
+    fn kl_divergence(
+        &self,
+        states: &[Tensor<B, 1>],
+        old: &Tensor<B, 1>,
+    ) -> anyhow::Result<Tensor<B, 1>> {
+        match self {
+            Self::Categorical(policy) => policy.kl_divergence(states, old),
+            Self::Diag(policy) => policy.kl_divergence(states, old),
+            Self::MultiCategorical(policy) => policy.kl_divergence(states, old),
+            Self::Bernoulli(policy) => policy.kl_divergence(states, old),
+        }
+    }
+
     fn resample_noise(&mut self) -> anyhow::Result<()> {
         match self {
             Self::Categorical(policy) => policy.resample_noise(),
@@ -76,6 +99,7 @@ impl<B: Backend> CompositeDistribution<B> {
         policy_layers: &[usize],
         activation: ActivationFunction,
         log_std_init: f32,
+        init: WeightInit,
     ) -> Self {
         let mut policies = Vec::new();
         let mut action_sizes = Vec::new();
@@ -85,6 +109,7 @@ impl<B: Backend> CompositeDistribution<B> {
                 policy_layers,
                 activation,
                 log_std_init,
+                init,
                 &mut policies,
                 &mut action_sizes,
             );
@@ -100,6 +125,7 @@ impl<B: Backend> CompositeDistribution<B> {
         policy_layers: &[usize],
         activation: ActivationFunction,
         log_std_init: f32,
+        init: WeightInit,
         policies: &mut Vec<CompositePolicyChildren<B>>,
         action_sizes: &mut Vec<usize>,
     ) {
@@ -113,7 +139,7 @@ impl<B: Backend> CompositeDistribution<B> {
                 ]
                 .concat();
                 policies.push(CompositePolicyChildren::Categorical(
-                    CategoricalDistribution::build(&child_layers, activation),
+                    CategoricalDistribution::build(&child_layers, activation, init),
                 ));
                 action_sizes.push(action_size);
             }
@@ -125,7 +151,7 @@ impl<B: Backend> CompositeDistribution<B> {
                 ]
                 .concat();
                 policies.push(CompositePolicyChildren::Diag(
-                    DiagGaussianDistribution::build(&child_layers, activation, log_std_init),
+                    DiagGaussianDistribution::build(&child_layers, activation, log_std_init, init),
                 ));
                 action_sizes.push(action_size);
             }
@@ -136,6 +162,7 @@ impl<B: Backend> CompositeDistribution<B> {
                         &policy_layers[1..policy_layers.len() - 1],
                         nvec.to_vec().into_iter().map(|n| n as usize).collect(),
                         activation,
+                        init,
                     ),
                 ));
                 action_sizes.push(action_size);
@@ -147,6 +174,7 @@ impl<B: Backend> CompositeDistribution<B> {
                         &policy_layers[1..policy_layers.len() - 1],
                         action_size,
                         activation,
+                        init,
                     ),
                 ));
                 action_sizes.push(action_size);
@@ -158,6 +186,7 @@ impl<B: Backend> CompositeDistribution<B> {
                         policy_layers,
                         activation,
                         log_std_init,
+                        init,
                         policies,
                         action_sizes,
                     );
@@ -170,6 +199,7 @@ impl<B: Backend> CompositeDistribution<B> {
                         policy_layers,
                         activation,
                         log_std_init,
+                        init,
                         policies,
                         action_sizes,
                     );
@@ -234,4 +264,27 @@ impl<B: Backend> Policy for CompositeDistribution<B> {
         }
         Ok(())
     }
+
+    fn distribution_parameters(&self, states: &[Self::Tensor]) -> anyhow::Result<Self::Tensor> {
+        let mut parameters = Vec::new();
+        for policy in &self.policies {
+            parameters.push(policy.distribution_parameters(states)?);
+        }
+        Ok(Tensor::cat(parameters, 0))
+    }
+
+    fn kl_divergence(
+        &self,
+        states: &[Self::Tensor],
+        old: &Self::Tensor,
+    ) -> anyhow::Result<Self::Tensor> {
+        let mut offset = 0;
+        let mut kls = Vec::new();
+        for policy in &self.policies {
+            let [len] = policy.distribution_parameters(states)?.dims();
+            kls.push(policy.kl_divergence(states, &old.clone().narrow(0, offset, len))?);
+            offset += len;
+        }
+        Ok(Tensor::cat(kls, 0).sum())
+    }
 }
@@ -6,7 +6,10 @@ use burn::tensor::cast::ToElement;
 use burn::tensor::{Distribution as BurnDistribution, Shape, TensorData};
 use burn::{prelude::Backend, tensor::Tensor};
 use burn_store::{ModuleSnapshot, ModuleStore, SafetensorsStore};
-use r2l_core::models::{ActivationFunction, Actor, Policy};
+use r2l_core::{
+    init::{LayerRole, WeightInit},
+    models::{ActivationFunction, Actor, Policy},
+};
 
 use crate::sequential::Sequential;
 
@@ -23,10 +26,16 @@ pub struct DiagGaussianDistribution<B: Backend> {
 
 impl<B: Backend> DiagGaussianDistribution<B> {
     /// Builds a diagonal-Gaussian policy network.
-    pub fn build(mu_layers: &[usize], activation: ActivationFunction, log_std_init: f32) -> Self {
+    pub fn build(
+        mu_layers: &[usize],
+        activation: ActivationFunction,
+        log_std_init: f32,
+        init: WeightInit,
+    ) -> Self {
         let device = Default::default();
         let action_size = *mu_layers.last().unwrap();
-        let mu_net: Sequential<B> = Sequential::build(mu_layers, activation);
+        let mu_net: Sequential<B> =
+            Sequential::build(mu_layers, activation, init, LayerRole::PolicyHead);
         let log_std = Param::from_data(
             TensorData::new(
                 vec![log_std_init; action_size],
@@ -39,12 +48,29 @@ impl<B: Backend> DiagGaussianDistribution<B> {
 
     /// Builds a diagonal-Guassian policy using a safetensor store
     pub fn from_store(store: &mut SafetensorsStore) -> Self {
+        Self::load(store, ActivationFunction::default())
+            .expect("failed to load DiagGaussianDistribution from store")
+    }
+
+    /// Builds a diagonal-Gaussian policy with the given hidden-layer
+    /// activation from a safetensor store.
+    pub(crate) fn load(
+        store: &mut SafetensorsStore,
+        activation: ActivationFunction,
+    ) -> Result<Self> {
         let mu_layers = Sequential::<B>::dims_from_store("mu_net", store);
-        let mut distribution = Self::build(&mu_layers, ActivationFunction::default(), 0.0);
-        distribution
-            .load_from(store)
-            .expect("failed to load DiagGaussianDistribution from store");
-        distribution
+        if mu_layers.is_empty() {
+            anyhow::bail!("the policy has no `mu_net` network");
+        }
+        let mut distribution = Self::build(&mu_layers, activation, 0.0, WeightInit::default());
+        distribution.load_from(store)?;
+        Ok(distribution)
+    }
+
+    /// Runs a batch of observations through the first `depth` hidden layers
+    /// of the policy network.
+    pub(crate) fn features(&self, observations: Tensor<B, 2>, depth: usize) -> Tensor<B, 2> {
+        self.mu_net.features(observations, depth)
     }
 }
 
@@ -105,11 +131,54 @@ impl<B: Backend> Policy for DiagGaussianDistribution<B> {
         Ok(entropy_per_dim.sum_dim(1).squeeze_dims(&[1]))
     }
 
+    fn entropy_per_dimension(&self, _states: &[Self::Tensor]) -> Result<Vec<f32>> {
+        let entropy = self
+            .log_std
+            .val()
+            .add_scalar(0.5 * ((2. * f32::consts::PI).ln() + 1.));
+        Ok(entropy.into_data().iter::<f32>().collect())
+    }
+
+    // the means of every state followed by the log standard deviations
+    fn distribution_parameters(&self, states: &[Self::Tensor]) -> Result<Self::Tensor> {
+        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
+        let mu = self.mu_net.forward(states);
+        Ok(Tensor::cat(
+            vec![mu.flatten(0, 1), self.log_std.val().flatten(0, 1)],
+            0,
+        ))
+    }
+
+    fn kl_divergence(&self, states: &[Self::Tensor], old: &Self::Tensor) -> Result<Self::Tensor> {
+        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
+        let mu = self.mu_net.forward(states);
+        let [batch_size, action_size] = mu.dims();
+        let old_mu: Tensor<B, 2> = old
+            .clone()
+            .narrow(0, 0, batch_size * action_size)
+            .reshape([batch_size, action_size]);
+        let old_log_std: Tensor<B, 2> = old
+            .clone()
+            .narrow(0, batch_size * action_size, action_size)
+            .reshape([1, action_size]);
+        let log_std = self.log_std.val();
+        let var = log_std.clone().mul_scalar(2.).exp();
+        let old_var = old_log_std.clone().mul_scalar(2.).exp();
+        let mu_difference = old_mu - mu;
+        let kl = (log_std - old_log_std)
+            + (old_var + mu_difference.clone() * mu_difference) / var.mul_scalar(2.);
+        Ok(kl.sub_scalar(0.5).sum_dim(1).mean())
+    }
+
     fn std(&self) -> Result<f32> {
         let std = self.log_std.val().exp().mean().into_scalar().to_f32();
         Ok(std)
     }
 
+    fn log_std(&self) -> Result<Option<Vec<f32>>> {
+        Ok(Some(self.log_std.val().into_data().iter::<f32>().collect()))
+    }
+
     fn resample_noise(&mut self) -> Result<()> {
         todo!()
     }
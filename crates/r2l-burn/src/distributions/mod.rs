@@ -5,17 +5,23 @@
 //! an enum that erases the concrete policy type behind one Burn-facing policy
 //! interface.
 
+use anyhow::bail;
 use burn::{Tensor, module::Module, prelude::Backend};
+use burn_store::{ModuleStore, SafetensorsStore};
 use r2l_core::{
     env::Space,
+    init::WeightInit,
     models::{ActivationFunction, Actor, Policy},
     tensor::R2lTensor,
 };
 
-use crate::distributions::{
-    bernoulli::BernoulliDistribution, categorical::CategoricalDistribution,
-    composite::CompositeDistribution, diagonal::DiagGaussianDistribution,
-    multi_categorical::MultiCategoricalDistribution,
+use crate::{
+    distributions::{
+        bernoulli::BernoulliDistribution, categorical::CategoricalDistribution,
+        composite::CompositeDistribution, diagonal::DiagGaussianDistribution,
+        multi_categorical::MultiCategoricalDistribution,
+    },
+    learning_module::SharedTrunk,
 };
 /// Bernoulli policy distribution for multi-binary action spaces.
 pub mod bernoulli;
@@ -50,10 +56,34 @@ pub enum PolicyKind<B: Backend> {
 }
 
 impl<B: Backend> PolicyKind<B> {
-    fn categorical(policy_layers: &[usize], activation: ActivationFunction) -> Self {
+    /// Builds a policy from the safetensors bytes written by its
+    /// [`Actor::try_serialize`].
+    ///
+    /// Only categorical and diagonal-Gaussian policies can be loaded. The
+    /// bytes do not record the hidden-layer activation, so it has to be
+    /// passed.
+    pub fn from_bytes(bytes: &[u8], activation: ActivationFunction) -> anyhow::Result<Self> {
+        let mut store = SafetensorsStore::from_bytes(Some(bytes.to_vec()));
+        if store.get_snapshot("log_std")?.is_some() {
+            Ok(Self::Diag(DiagGaussianDistribution::load(
+                &mut store, activation,
+            )?))
+        } else {
+            Ok(Self::Categorical(CategoricalDistribution::load(
+                &mut store, activation,
+            )?))
+        }
+    }
+
+    fn categorical(
+        policy_layers: &[usize],
+        activation: ActivationFunction,
+        init: WeightInit,
+    ) -> Self {
         PolicyKind::Categorical(CategoricalDistribution::<B>::build(
             policy_layers,
             activation,
+            init,
         ))
     }
 
@@ -61,11 +91,13 @@ impl<B: Backend> PolicyKind<B> {
         policy_layers: &[usize],
         activation: ActivationFunction,
         log_std_init: f32,
+        init: WeightInit,
     ) -> Self {
         PolicyKind::Diag(DiagGaussianDistribution::build(
             policy_layers,
             activation,
             log_std_init,
+            init,
         ))
     }
 
@@ -73,12 +105,14 @@ impl<B: Backend> PolicyKind<B> {
         policy_layers: &[usize],
         nvec: Vec<usize>,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> Self {
         PolicyKind::MultiCategorical(MultiCategoricalDistribution::build(
             policy_layers[0],
             &policy_layers[1..policy_layers.len() - 1],
             nvec,
             activation,
+            init,
         ))
     }
 
@@ -86,12 +120,14 @@ impl<B: Backend> PolicyKind<B> {
         policy_layers: &[usize],
         action_size: usize,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> Self {
         PolicyKind::Bernoulli(BernoulliDistribution::build(
             policy_layers[0],
             &policy_layers[1..policy_layers.len() - 1],
             action_size,
             activation,
+            init,
         ))
     }
 
@@ -101,29 +137,32 @@ impl<B: Backend> PolicyKind<B> {
         policy_layers: &[usize],
         activation: ActivationFunction,
         log_std_init: f32,
+        init: WeightInit,
     ) -> Self {
         match action_space {
-            Space::Discrete(_) => Self::categorical(policy_layers, activation),
-            Space::Box { .. } => Self::box_policy(policy_layers, activation, log_std_init),
+            Space::Discrete(_) => Self::categorical(policy_layers, activation, init),
+            Space::Box { .. } => Self::box_policy(policy_layers, activation, log_std_init, init),
             Space::MultiDiscrete { nvec, .. } => {
                 let nvec = nvec.to_vec().into_iter().map(|n| n as usize).collect();
-                Self::multi_categorical(policy_layers, nvec, activation)
+                Self::multi_categorical(policy_layers, nvec, activation, init)
             }
             Space::MultiBinary { shape } => {
                 let size = shape.iter().product();
-                Self::bernoulli(policy_layers, size, activation)
+                Self::bernoulli(policy_layers, size, activation, init)
             }
             Space::Tuple(spaces) => PolicyKind::Composite(CompositeDistribution::build(
                 spaces,
                 policy_layers,
                 activation,
                 log_std_init,
+                init,
             )),
             Space::Dict(spaces) => PolicyKind::Composite(CompositeDistribution::build(
                 spaces.into_values().collect(),
                 policy_layers,
                 activation,
                 log_std_init,
+                init,
             )),
         }
     }
@@ -188,6 +227,26 @@ impl<B: Backend> Policy for PolicyKind<B> {
         }
     }
 
+    fn entropy_per_dimension(&self, states: &[Self::Tensor]) -> anyhow::Result<Vec<f32>> {
+        match self {
+            Self::Categorical(cat) => cat.entropy_per_dimension(states),
+            Self::Diag(diag) => diag.entropy_per_dimension(states),
+            Self::MultiCategorical(multi) => multi.entropy_per_dimension(states),
+            Self::Bernoulli(bernoulli) => bernoulli.entropy_per_dimension(states),
+            Self::Composite(composite) => composite.entropy_per_dimension(states),
+        }
+    }
+
+    fn log_std(&self) -> anyhow::Result<Option<Vec<f32>>> {
+        match self {
+            Self::Categorical(cat) => cat.log_std(),
+            Self::Diag(diag) => diag.log_std(),
+            Self::MultiCategorical(multi) => multi.log_std(),
+            Self::Bernoulli(bernoulli) => bernoulli.log_std(),
+            Self::Composite(composite) => composite.log_std(),
+        }
+    }
+
     fn resample_noise(&mut self) -> anyhow::Result<()> {
         match self {
             Self::Categorical(cat) => cat.resample_noise(),
@@ -197,4 +256,42 @@ impl<B: Backend> Policy for PolicyKind<B> {
             Self::Composite(composite) => composite.resample_noise(),
         }
     }
+
+    fn distribution_parameters(&self, states: &[Self::Tensor]) -> anyhow::Result<Self::Tensor> {
+        match self {
+            Self::Categorical(cat) => cat.distribution_parameters(states),
+            Self::Diag(diag) => diag.distribution_parameters(states),
+            Self::MultiCategorical(multi) => multi.distribution_parameters(states),
+            Self::Bernoulli(bernoulli) => bernoulli.distribution_parameters(states),
+            Self::Composite(composite) => composite.distribution_parameters(states),
+        }
+    }
+
+    fn kl_divergence(
+        &self,
+        states: &[Self::Tensor],
+        old: &Self::Tensor,
+    ) -> anyhow::Result<Self::Tensor> {
+        match self {
+            Self::Categorical(cat) => cat.kl_divergence(states, old),
+            Self::Diag(diag) => diag.kl_divergence(states, old),
+            Self::MultiCategorical(multi) => multi.kl_divergence(states, old),
+            Self::Bernoulli(bernoulli) => bernoulli.kl_divergence(states, old),
+            Self::Composite(composite) => composite.kl_divergence(states, old),
+        }
+    }
+}
+
+impl<B: Backend> SharedTrunk<B> for PolicyKind<B> {
+    fn features(&self, observations: Tensor<B, 2>, depth: usize) -> anyhow::Result<Tensor<B, 2>> {
+        match self {
+            Self::Categorical(cat) => Ok(cat.features(observations, depth)),
+            Self::Diag(diag) => Ok(diag.features(observations, depth)),
+            Self::MultiCategorical(multi) => Ok(multi.features(observations, depth)),
+            Self::Bernoulli(bernoulli) => Ok(bernoulli.features(observations, depth)),
+            Self::Composite(_) => {
+                bail!("composite policies have one network per action and no shared trunk")
+            }
+        }
+    }
 }
@@ -11,6 +11,7 @@ use burn::{
 use burn_store::{ModuleStore, SafetensorsStore};
 use r2l_core::{
     env::action_ranges,
+    init::{LayerRole, WeightInit},
     models::{ActivationFunction, Actor, Policy},
     rng::with_rng,
 };
@@ -33,12 +34,33 @@ impl<B: Backend> MultiCategoricalDistribution<B> {
         hidden_layers: &[usize],
         nvec: Vec<usize>,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> Self {
         let logits_size = nvec.iter().sum();
         let layers = &[&[observation_size], hidden_layers, &[logits_size]].concat();
-        let logits = Sequential::build(layers, activation);
+        let logits = Sequential::build(layers, activation, init, LayerRole::PolicyHead);
         Self { logits, nvec }
     }
+
+    /// Runs a batch of observations through the first `depth` hidden layers
+    /// of the policy network.
+    pub(crate) fn features(&self, observations: Tensor<B, 2>, depth: usize) -> Tensor<B, 2> {
+        self.logits.features(observations, depth)
+    }
+
+    // Entropy of every sub-action for each state.
+    fn entropies(&self, states: &[Tensor<B, 1>]) -> Vec<Tensor<B, 1>> {
+        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
+        let logits = self.logits.forward(states);
+        let mut entropies = Vec::new();
+        for (offset, choices) in action_ranges(&self.nvec) {
+            let logits = logits.clone().narrow(1, offset, choices);
+            let probs = softmax(logits.clone(), 1);
+            let log_probs = log_softmax(logits, 1);
+            entropies.push((probs * log_probs).neg().sum_dim(1).squeeze_dim::<1>(1));
+        }
+        entropies
+    }
 }
 
 impl<B: Backend> Actor for MultiCategoricalDistribution<B> {
@@ -93,19 +115,41 @@ impl<B: Backend> Policy for MultiCategoricalDistribution<B> {
     }
 
     fn entropy(&self, states: &[Self::Tensor]) -> anyhow::Result<Self::Tensor> {
-        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
-        let logits = self.logits.forward(states);
-        let mut entropies = Vec::new();
-        for (offset, choices) in action_ranges(&self.nvec) {
-            let logits = logits.clone().narrow(1, offset, choices);
-            let probs = softmax(logits.clone(), 1);
-            let log_probs = log_softmax(logits, 1);
-            entropies.push((probs * log_probs).neg().sum_dim(1).squeeze_dim::<1>(1));
-        }
+        let entropies = self.entropies(states);
         Ok(Tensor::stack::<2>(entropies, 0).sum_dim(0).mean())
     }
 
+    fn entropy_per_dimension(&self, states: &[Self::Tensor]) -> anyhow::Result<Vec<f32>> {
+        Ok(self
+            .entropies(states)
+            .into_iter()
+            .flat_map(|entropy| entropy.mean().into_data().iter::<f32>().collect::<Vec<_>>())
+            .collect())
+    }
+
     fn std(&self) -> anyhow::Result<f32> {
         bail!("standard deviation is not defined for multi-categorical distributions")
     }
+
+    fn distribution_parameters(&self, states: &[Self::Tensor]) -> anyhow::Result<Self::Tensor> {
+        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
+        Ok(self.logits.forward(states).flatten(0, 1))
+    }
+
+    fn kl_divergence(
+        &self,
+        states: &[Self::Tensor],
+        old: &Self::Tensor,
+    ) -> anyhow::Result<Self::Tensor> {
+        let states: Tensor<B, 2> = Tensor::stack(states.to_vec(), 0);
+        let logits = self.logits.forward(states);
+        let old: Tensor<B, 2> = old.clone().reshape(logits.shape());
+        let mut kls = Vec::new();
+        for (offset, choices) in action_ranges(&self.nvec) {
+            let old_log_probs = log_softmax(old.clone().narrow(1, offset, choices), 1);
+            let log_probs = log_softmax(logits.clone().narrow(1, offset, choices), 1);
+            kls.push((old_log_probs.clone().exp() * (old_log_probs - log_probs)).sum_dim(1));
+        }
+        Ok(Tensor::cat(kls, 1).sum_dim(1).mean())
+    }
 }
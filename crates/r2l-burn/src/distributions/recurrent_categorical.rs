@@ -10,6 +10,7 @@ use burn::{
 };
 use burn_store::{ModuleSnapshot, ModuleStore, SafetensorsStore};
 use r2l_core::{
+    init::{LayerRole, WeightInit},
     models::{ActivationFunction, Actor, Policy},
     rng::with_rng,
 };
@@ -57,7 +58,12 @@ impl<B: Backend> RecurrentCategoricalDistribution<B> {
             vec![observation_size]
         };
 
-        let encoder = Sequential::build(&encoder_layers, ActivationFunction::default());
+        let encoder = Sequential::build(
+            &encoder_layers,
+            ActivationFunction::default(),
+            WeightInit::default(),
+            LayerRole::Hidden,
+        );
         let recurrent = RnnConfig::new(recurrent_size, recurrent_size, true).init::<B>(&device);
         let logits = LinearConfig::new(recurrent_size, action_size)
             .with_bias(true)
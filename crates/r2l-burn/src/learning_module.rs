@@ -5,19 +5,41 @@
 //! [`OnPolicyLearningModule`](r2l_core::on_policy::learning_module::OnPolicyLearningModule)
 //! implementation.
 
+use anyhow::bail;
 use burn::{
     grad_clipping::GradientClipping,
-    module::{AutodiffModule, Module, ModuleDisplay},
-    optim::{AdamW, AdamWConfig, GradientsParams, Optimizer, adaptor::OptimizerAdaptor},
+    module::{AutodiffModule, Module, ModuleDisplay, ModuleMapper, ModuleVisitor, Param},
+    optim::GradientsParams,
     prelude::Backend,
-    tensor::{Tensor, backend::AutodiffBackend},
+    tensor::{Tensor, TensorData, backend::AutodiffBackend},
 };
 use r2l_core::{
-    models::{ActivationFunction, LearningModule, Policy, ValueFunction},
-    on_policy::{learning_module::OnPolicyLearningModule, losses::FromPolicyValueLosses},
+    init::{LayerRole, WeightInit},
+    models::{
+        ActivationFunction, GradNorms, LearningModule, OptimizerConfig, Policy, ValueFunction,
+    },
+    on_policy::{
+        learning_module::{
+            AuxiliaryValueLearningModule, OnPolicyLearningModule, StatefulLearningModule,
+            TrustRegionLearningModule,
+        },
+        losses::FromPolicyValueLosses,
+    },
 };
 
-use crate::{distributions::PolicyKind, sequential::Sequential};
+use crate::{
+    distributions::PolicyKind,
+    optimizer::{BurnOptimizer, BurnOptimizerState},
+    sequential::Sequential,
+};
+
+/// Policy whose leading hidden layers can act as a trunk shared with the
+/// value network.
+pub trait SharedTrunk<B: Backend> {
+    /// Runs a batch of observations through the first `depth` hidden layers
+    /// of the policy network.
+    fn features(&self, observations: Tensor<B, 2>, depth: usize) -> anyhow::Result<Tensor<B, 2>>;
+}
 
 // A series constraints that we need for the policy to work nicely with AdamW
 /// Trait alias-like bound for Burn policies used by on-policy learning modules.
@@ -28,6 +50,7 @@ pub trait BurnPolicy<B: AutodiffBackend>:
     AutodiffModule<B, InnerModule: ModuleDisplay + Policy<Tensor = Tensor<B::InnerBackend, 1>>>
     + ModuleDisplay
     + Policy<Tensor = Tensor<B, 1>>
+    + SharedTrunk<B>
 {
 }
 
@@ -35,6 +58,7 @@ impl<B: AutodiffBackend, M> BurnPolicy<B> for M where
     M: AutodiffModule<B, InnerModule: ModuleDisplay + Policy<Tensor = Tensor<B::InnerBackend, 1>>>
         + ModuleDisplay
         + Policy<Tensor = Tensor<B, 1>>
+        + SharedTrunk<B>
 {
 }
 
@@ -97,30 +121,115 @@ impl<B: Backend, M: Module<B>> JointActorModel<B, M> {
     }
 }
 
+/// Value head on the policy features, trained with its own optimizer.
+struct AuxiliaryValueHead<B: AutodiffBackend> {
+    value_net: Sequential<B>,
+    optimizer: BurnOptimizer<Sequential<B>, B>,
+    lr: f64,
+    // number of policy hidden layers the head reads from
+    depth: usize,
+}
+
+impl<B: AutodiffBackend> AuxiliaryValueHead<B> {
+    fn new(
+        layers: &[usize],
+        activation: ActivationFunction,
+        depth: usize,
+        optimizer: &OptimizerConfig,
+        max_grad_norm: Option<f32>,
+        init: WeightInit,
+    ) -> Self {
+        Self {
+            value_net: Sequential::build(layers, activation, init, LayerRole::ValueHead),
+            optimizer: BurnOptimizer::init(optimizer, max_grad_norm),
+            lr: optimizer.learning_rate(),
+            depth,
+        }
+    }
+
+    fn values<M: SharedTrunk<B>>(
+        &self,
+        policy: &M,
+        observations: &[Tensor<B, 1>],
+    ) -> anyhow::Result<Tensor<B, 1>> {
+        let observation: Tensor<B, 2> = Tensor::stack(observations.to_vec(), 0);
+        let features = policy.features(observation, self.depth)?;
+        Ok(self.value_net.forward(features).squeeze())
+    }
+
+    fn state(&self) -> AuxiliaryValueHeadState<B> {
+        AuxiliaryValueHeadState {
+            value_net: parameters(&self.value_net),
+            optimizer: self.optimizer.state(&self.value_net),
+        }
+    }
+
+    fn load_state(&mut self, state: &AuxiliaryValueHeadState<B>) -> anyhow::Result<()> {
+        self.optimizer
+            .load_state(&self.value_net, &state.optimizer)?;
+        self.value_net = assign_parameters(self.value_net.clone(), &state.value_net)?;
+        Ok(())
+    }
+
+    // Takes the gradients of the head out of `grads`, leaving the rest to the
+    // policy.
+    fn step(&mut self, grads: &mut B::Gradients) {
+        let grads = GradientsParams::from_module(grads, &self.value_net);
+        self.value_net = self.optimizer.step(self.lr, self.value_net.clone(), grads);
+    }
+}
+
 /// Burn on-policy learning module with one shared optimizer configuration.
 pub struct JointPolicyValueModule<B: AutodiffBackend, M: BurnPolicy<B>> {
     lr: f64,
     model: JointActorModel<B, M>,
+    // number of leading policy hidden layers the value network reads from
+    shared_depth: usize,
+    auxiliary_head: Option<AuxiliaryValueHead<B>>,
     // NOTE: the optimizer needs to be optimizing both the policy and the value net at the same time
-    optimizer: OptimizerAdaptor<AdamW, JointActorModel<B, M>, B>,
+    optimizer: BurnOptimizer<JointActorModel<B, M>, B>,
+    grad_clipping: Option<GradientClipping>,
+    // only measured while someone collects them
+    grad_norms: Option<GradNorms>,
 }
 
 impl<B: AutodiffBackend, M: BurnPolicy<B>> JointPolicyValueModule<B, M> {
     fn new(
         model: JointActorModel<B, M>,
-        optimizer: OptimizerAdaptor<AdamW, JointActorModel<B, M>, B>,
+        optimizer: BurnOptimizer<JointActorModel<B, M>, B>,
         lr: f64,
+        shared_depth: usize,
+        max_grad_norm: Option<f32>,
     ) -> Self {
         Self {
             lr,
             model,
+            shared_depth,
+            auxiliary_head: None,
             optimizer,
+            grad_clipping: max_grad_norm.map(GradientClipping::Norm),
+            grad_norms: None,
         }
     }
 
     /// Sets gradient clipping for the shared optimizer.
     pub fn set_grad_clipping(&mut self, grad_clipping: GradientClipping) {
-        self.optimizer = self.optimizer.clone().with_grad_clipping(grad_clipping);
+        self.grad_clipping = Some(grad_clipping.clone());
+        self.optimizer.set_grad_clipping(grad_clipping);
+    }
+
+    /// Starts or stops measuring the gradient norms of every update.
+    pub fn collect_grad_norms(&mut self, collect: bool) {
+        let grad_norms = self.grad_norms.take().unwrap_or_default();
+        self.grad_norms = collect.then_some(grad_norms);
+    }
+
+    /// Returns the gradient norms recorded since the last call.
+    pub fn take_grad_norms(&mut self) -> GradNorms {
+        self.grad_norms
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
     }
 
     /// Returns the current policy optimizer learning rate.
@@ -131,6 +240,24 @@ impl<B: AutodiffBackend, M: BurnPolicy<B>> JointPolicyValueModule<B, M> {
     /// Sets the learning rate for the shared optimizer.
     pub fn set_learning_rate(&mut self, learning_rate: f64) {
         self.lr = learning_rate;
+        if let Some(head) = &mut self.auxiliary_head {
+            head.lr = learning_rate;
+        }
+    }
+
+    fn auxiliary_update(
+        &mut self,
+        joint_loss: Tensor<B, 1>,
+        value_loss: Tensor<B, 1>,
+    ) -> anyhow::Result<()> {
+        let Some(head) = &mut self.auxiliary_head else {
+            bail!("the policy has no auxiliary value head");
+        };
+        let mut grads = (joint_loss + value_loss).backward();
+        head.step(&mut grads);
+        let grads = GradientsParams::from_grads(grads, &self.model);
+        self.model = self.optimizer.step(self.lr, self.model.clone(), grads);
+        Ok(())
     }
 }
 
@@ -144,6 +271,9 @@ impl<B: AutodiffBackend, M: BurnPolicy<B>> LearningModule for JointPolicyValueMo
             losses.policy_loss + losses.value_loss
         };
         let grads = loss.backward();
+        if let Some(grad_norms) = &mut self.grad_norms {
+            record_grad_norms(grad_norms, &self.model, &grads, self.grad_clipping.as_ref());
+        }
         let grads = GradientsParams::from_grads(grads, &self.model);
         let new_model = self.optimizer.step(self.lr, self.model.clone(), grads);
         self.model = new_model;
@@ -155,7 +285,10 @@ impl<B: AutodiffBackend, M: BurnPolicy<B>> ValueFunction for JointPolicyValueMod
     type Tensor = Tensor<B, 1>;
 
     fn values(&self, observations: &[Self::Tensor]) -> anyhow::Result<Self::Tensor> {
-        let observation: Tensor<B, 2> = Tensor::stack(observations.to_vec(), 0);
+        let mut observation: Tensor<B, 2> = Tensor::stack(observations.to_vec(), 0);
+        if self.shared_depth > 0 {
+            observation = self.model.policy.features(observation, self.shared_depth)?;
+        }
         let value = self.model.value_net.forward(observation);
         Ok(value.squeeze())
     }
@@ -192,20 +325,25 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> OnPolicyLearningModule for JointPolic
 pub struct SplitPolicyValueModule<B: AutodiffBackend, M: BurnPolicy<B>> {
     policy: M,
     value_net: Sequential<B>,
-    policy_optimizer: OptimizerAdaptor<AdamW, M, B>,
+    policy_optimizer: BurnOptimizer<M, B>,
     policy_lr: f64,
-    value_optimizer: OptimizerAdaptor<AdamW, Sequential<B>, B>,
+    value_optimizer: BurnOptimizer<Sequential<B>, B>,
     value_lr: f64,
+    auxiliary_head: Option<AuxiliaryValueHead<B>>,
+    grad_clipping: Option<GradientClipping>,
+    // only measured while someone collects them
+    grad_norms: Option<GradNorms>,
 }
 
 impl<B: AutodiffBackend, M: BurnPolicy<B>> SplitPolicyValueModule<B, M> {
     fn new(
         policy: M,
         value_net: Sequential<B>,
-        policy_optimizer: OptimizerAdaptor<AdamW, M, B>,
+        policy_optimizer: BurnOptimizer<M, B>,
         policy_lr: f64,
-        value_optimizer: OptimizerAdaptor<AdamW, Sequential<B>, B>,
+        value_optimizer: BurnOptimizer<Sequential<B>, B>,
         value_lr: f64,
+        policy_max_grad_norm: Option<f32>,
     ) -> Self {
         Self {
             policy,
@@ -214,15 +352,30 @@ impl<B: AutodiffBackend, M: BurnPolicy<B>> SplitPolicyValueModule<B, M> {
             policy_lr,
             value_optimizer,
             value_lr,
+            auxiliary_head: None,
+            grad_clipping: policy_max_grad_norm.map(GradientClipping::Norm),
+            grad_norms: None,
         }
     }
 
     /// Sets gradient clipping for the policy optimizer.
     pub fn set_grad_clipping(&mut self, grad_clipping: GradientClipping) {
-        self.policy_optimizer = self
-            .policy_optimizer
-            .clone()
-            .with_grad_clipping(grad_clipping);
+        self.grad_clipping = Some(grad_clipping.clone());
+        self.policy_optimizer.set_grad_clipping(grad_clipping);
+    }
+
+    /// Starts or stops measuring the policy gradient norms of every update.
+    pub fn collect_grad_norms(&mut self, collect: bool) {
+        let grad_norms = self.grad_norms.take().unwrap_or_default();
+        self.grad_norms = collect.then_some(grad_norms);
+    }
+
+    /// Returns the policy gradient norms recorded since the last call.
+    pub fn take_grad_norms(&mut self) -> GradNorms {
+        self.grad_norms
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
     }
 
     /// Returns the current policy optimizer learning rate.
@@ -234,6 +387,35 @@ impl<B: AutodiffBackend, M: BurnPolicy<B>> SplitPolicyValueModule<B, M> {
     pub fn set_learning_rate(&mut self, learning_rate: f64) {
         self.policy_lr = learning_rate;
         self.value_lr = learning_rate;
+        if let Some(head) = &mut self.auxiliary_head {
+            head.lr = learning_rate;
+        }
+    }
+
+    fn auxiliary_update(
+        &mut self,
+        joint_loss: Tensor<B, 1>,
+        value_loss: Tensor<B, 1>,
+    ) -> anyhow::Result<()> {
+        let Some(head) = &mut self.auxiliary_head else {
+            bail!("the policy has no auxiliary value head");
+        };
+        let mut grads = joint_loss.backward();
+        head.step(&mut grads);
+        let grads = GradientsParams::from_grads(grads, &self.policy);
+        self.policy = self
+            .policy_optimizer
+            .step(self.policy_lr, self.policy.clone(), grads);
+        self.update_value_net(value_loss);
+        Ok(())
+    }
+
+    fn update_value_net(&mut self, value_loss: Tensor<B, 1>) {
+        let value_grads = value_loss.backward();
+        let value_grads = GradientsParams::from_grads(value_grads, &self.value_net);
+        self.value_net =
+            self.value_optimizer
+                .step(self.value_lr, self.value_net.clone(), value_grads);
     }
 }
 
@@ -242,6 +424,14 @@ impl<B: AutodiffBackend, M: BurnPolicy<B>> LearningModule for SplitPolicyValueMo
 
     fn update(&mut self, losses: Self::Losses) -> anyhow::Result<()> {
         let policy_grads = losses.policy_loss.backward();
+        if let Some(grad_norms) = &mut self.grad_norms {
+            record_grad_norms(
+                grad_norms,
+                &self.policy,
+                &policy_grads,
+                self.grad_clipping.as_ref(),
+            );
+        }
         let policy_grads = GradientsParams::from_grads(policy_grads, &self.policy);
         self.policy = self
             .policy_optimizer
@@ -251,11 +441,7 @@ impl<B: AutodiffBackend, M: BurnPolicy<B>> LearningModule for SplitPolicyValueMo
         } else {
             losses.value_loss
         };
-        let value_grads = value_loss.backward();
-        let value_grads = GradientsParams::from_grads(value_grads, &self.value_net);
-        self.value_net =
-            self.value_optimizer
-                .step(self.value_lr, self.value_net.clone(), value_grads);
+        self.update_value_net(value_loss);
         Ok(())
     }
 }
@@ -307,43 +493,98 @@ pub enum PolicyValueModule<B: AutodiffBackend, D: BurnPolicy<B>> {
 
 impl<B: AutodiffBackend, D: BurnPolicy<B>> PolicyValueModule<B, D> {
     /// Builds a policy/value module with a shared optimizer configuration.
+    ///
+    /// With a non-zero `shared_depth`, the value network reads the output of
+    /// the first `shared_depth` policy hidden layers instead of the
+    /// observations, so `value_layers` must start with the width of that
+    /// layer. Both losses then train the shared layers.
     pub fn joint(
         policy: D,
+        shared_depth: usize,
         value_layers: &[usize],
         activation: ActivationFunction,
-        optimizer_config: AdamWConfig,
-        lr: f64,
+        optimizer: &OptimizerConfig,
+        max_grad_norm: Option<f32>,
+        init: WeightInit,
     ) -> Self {
-        let value_net: Sequential<B> = Sequential::build(value_layers, activation);
+        let value_net: Sequential<B> =
+            Sequential::build(value_layers, activation, init, LayerRole::ValueHead);
         let model = JointActorModel::new(policy, value_net);
-        let model = JointPolicyValueModule::new(model, optimizer_config.init(), lr);
+        let model = JointPolicyValueModule::new(
+            model,
+            BurnOptimizer::init(optimizer, max_grad_norm),
+            optimizer.learning_rate(),
+            shared_depth,
+            max_grad_norm,
+        );
         Self::Joint(model)
     }
 
     /// Builds a policy/value module with separate policy and value optimizers.
+    #[allow(clippy::too_many_arguments)]
     pub fn split(
         policy: D,
         value_layers: &[usize],
         activation: ActivationFunction,
-        policy_optimizer_config: AdamWConfig,
-        policy_lr: f64,
-        value_optimizer_config: AdamWConfig,
-        value_lr: f64,
+        policy_optimizer: &OptimizerConfig,
+        policy_max_grad_norm: Option<f32>,
+        value_optimizer: &OptimizerConfig,
+        value_max_grad_norm: Option<f32>,
+        init: WeightInit,
     ) -> Self {
-        let value_net: Sequential<B> = Sequential::build(value_layers, activation);
+        let value_net: Sequential<B> =
+            Sequential::build(value_layers, activation, init, LayerRole::ValueHead);
         let model = SplitPolicyValueModule::new(
             policy,
             value_net,
-            policy_optimizer_config.init(),
-            policy_lr,
-            value_optimizer_config.init(),
-            value_lr,
+            BurnOptimizer::init(policy_optimizer, policy_max_grad_norm),
+            policy_optimizer.learning_rate(),
+            BurnOptimizer::init(value_optimizer, value_max_grad_norm),
+            value_optimizer.learning_rate(),
+            policy_max_grad_norm,
         );
         Self::Split(model)
     }
 }
 
 impl<B: AutodiffBackend, D: BurnPolicy<B>> PolicyValueModule<B, D> {
+    /// Adds a value head on the output of the policy hidden layers, as used
+    /// by Phasic Policy Gradient.
+    ///
+    /// The head stacks `hidden_layers`, separated by `activation`, before its
+    /// output layer; an empty slice gives the single linear layer of the PPG
+    /// paper. It has its own optimizer, built from the policy optimizer
+    /// configuration.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_auxiliary_value_head(
+        mut self,
+        observation_size: usize,
+        policy_hidden_layers: &[usize],
+        hidden_layers: &[usize],
+        activation: ActivationFunction,
+        optimizer: &OptimizerConfig,
+        max_grad_norm: Option<f32>,
+        init: WeightInit,
+    ) -> Self {
+        let input_size = policy_hidden_layers
+            .last()
+            .copied()
+            .unwrap_or(observation_size);
+        let head = AuxiliaryValueHead::new(
+            &[&[input_size][..], hidden_layers, &[1]].concat(),
+            activation,
+            policy_hidden_layers.len(),
+            optimizer,
+            max_grad_norm,
+            init,
+        );
+        match &mut self {
+            Self::Joint(lm) => lm.auxiliary_head = Some(head),
+            Self::Split(lm) => lm.auxiliary_head = Some(head),
+        }
+        self
+    }
+
     /// Sets policy-side gradient clipping on the contained optimizer state.
     pub fn set_grad_clipping(&mut self, grad_clipping: GradientClipping) {
         match self {
@@ -360,6 +601,26 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> PolicyValueModule<B, D> {
         }
     }
 
+    /// Starts or stops measuring the norms of the policy-side gradients.
+    /// Measuring them reads the gradients back from the device on every
+    /// update, so they are only measured on request.
+    pub fn collect_grad_norms(&mut self, collect: bool) {
+        match self {
+            Self::Joint(lm) => lm.collect_grad_norms(collect),
+            Self::Split(lm) => lm.collect_grad_norms(collect),
+        }
+    }
+
+    /// Returns the norms of the policy-side gradients applied since the last
+    /// call, before and after clipping. Empty unless
+    /// [`collect_grad_norms`](Self::collect_grad_norms) is on.
+    pub fn take_grad_norms(&mut self) -> GradNorms {
+        match self {
+            Self::Joint(lm) => lm.take_grad_norms(),
+            Self::Split(lm) => lm.take_grad_norms(),
+        }
+    }
+
     /// Sets the learning rate on the contained optimizer state.
     pub fn set_learning_rate(&mut self, learning_rate: f64) {
         match self {
@@ -427,4 +688,292 @@ impl<B: AutodiffBackend, D: BurnPolicy<B>> OnPolicyLearningModule for PolicyValu
     }
 }
 
+// Policy parameters are flattened in the order the module visits them.
+#[derive(Default)]
+struct ParameterCollector {
+    values: Vec<f32>,
+}
+
+impl<B: Backend> ModuleVisitor<B> for ParameterCollector {
+    fn visit_float<const D: usize>(&mut self, param: &Param<Tensor<B, D>>) {
+        self.values.extend(param.val().into_data().iter::<f32>());
+    }
+}
+
+struct GradientCollector<'a, B: AutodiffBackend> {
+    grads: &'a B::Gradients,
+    values: Vec<f32>,
+}
+
+impl<B: AutodiffBackend> ModuleVisitor<B> for GradientCollector<'_, B> {
+    fn visit_float<const D: usize>(&mut self, param: &Param<Tensor<B, D>>) {
+        let tensor = param.val();
+        match tensor.grad(self.grads) {
+            Some(grad) => self.values.extend(grad.into_data().iter::<f32>()),
+            None => self
+                .values
+                .extend(std::iter::repeat_n(0., tensor.shape().num_elements())),
+        }
+    }
+}
+
+// Burn clips every parameter tensor on its own, so the clipped global norm is
+// rebuilt from the clipped norm of each tensor. The squared norms stay on the
+// device until every parameter has been visited.
+struct GradientNormCollector<'a, B: AutodiffBackend> {
+    grads: &'a B::Gradients,
+    grad_clipping: Option<&'a GradientClipping>,
+    pre_clip_squared: Vec<Tensor<B::InnerBackend, 1>>,
+    post_clip_squared: Vec<Tensor<B::InnerBackend, 1>>,
+}
+
+impl<B: AutodiffBackend> ModuleVisitor<B> for GradientNormCollector<'_, B> {
+    fn visit_float<const D: usize>(&mut self, param: &Param<Tensor<B, D>>) {
+        let Some(grad) = param.val().grad(self.grads) else {
+            return;
+        };
+        let norm_squared = grad.clone().powi_scalar(2).sum();
+        let clipped_squared = match self.grad_clipping {
+            Some(GradientClipping::Norm(max_norm)) => {
+                let clip_coef = norm_squared
+                    .clone()
+                    .sqrt()
+                    .add_scalar(1e-6)
+                    .recip()
+                    .mul_scalar(*max_norm)
+                    .clamp_max(1.);
+                norm_squared.clone() * clip_coef.powi_scalar(2)
+            }
+            Some(GradientClipping::Value(threshold)) => {
+                grad.clamp(-threshold, *threshold).powi_scalar(2).sum()
+            }
+            None => norm_squared.clone(),
+        };
+        self.pre_clip_squared.push(norm_squared);
+        self.post_clip_squared.push(clipped_squared);
+    }
+}
+
+fn record_grad_norms<B: AutodiffBackend, M: Module<B>>(
+    grad_norms: &mut GradNorms,
+    module: &M,
+    grads: &B::Gradients,
+    grad_clipping: Option<&GradientClipping>,
+) {
+    let mut collector = GradientNormCollector::<B> {
+        grads,
+        grad_clipping,
+        pre_clip_squared: vec![],
+        post_clip_squared: vec![],
+    };
+    module.visit(&mut collector);
+    if collector.pre_clip_squared.is_empty() {
+        grad_norms.record(0., 0.);
+        return;
+    }
+    let pre_clip = Tensor::cat(collector.pre_clip_squared, 0).sum().sqrt();
+    let post_clip = Tensor::cat(collector.post_clip_squared, 0).sum().sqrt();
+    let norms = Tensor::cat(vec![pre_clip, post_clip], 0).into_data();
+    let norms = norms.iter::<f32>().collect::<Vec<_>>();
+    grad_norms.record(norms[0], norms[1]);
+}
+
+struct ParameterAssigner<'a> {
+    values: &'a [f32],
+    offset: usize,
+}
+
+impl<B: Backend> ModuleMapper<B> for ParameterAssigner<'_> {
+    fn map_float<const D: usize>(&mut self, param: Param<Tensor<B, D>>) -> Param<Tensor<B, D>> {
+        param.map(|tensor| {
+            let shape = tensor.shape();
+            let len = shape.num_elements();
+            let data = TensorData::new(self.values[self.offset..self.offset + len].to_vec(), shape);
+            self.offset += len;
+            Tensor::from_data(data, &tensor.device()).set_require_grad(tensor.is_require_grad())
+        })
+    }
+}
+
+impl<B: AutodiffBackend, D: BurnPolicy<B>> TrustRegionLearningModule for PolicyValueModule<B, D> {
+    fn policy_parameters(&self) -> anyhow::Result<Vec<f32>> {
+        let mut collector = ParameterCollector::default();
+        self.policy().visit(&mut collector);
+        Ok(collector.values)
+    }
+
+    fn set_policy_parameters(&mut self, parameters: &[f32]) -> anyhow::Result<()> {
+        match self {
+            Self::Joint(lm) => {
+                lm.model.policy = assign_parameters(lm.model.policy.clone(), parameters)?
+            }
+            Self::Split(lm) => lm.policy = assign_parameters(lm.policy.clone(), parameters)?,
+        }
+        Ok(())
+    }
+
+    fn policy_gradient(&mut self, loss: &Tensor<B, 1>) -> anyhow::Result<Vec<f32>> {
+        let grads = loss.clone().backward();
+        let mut collector = GradientCollector::<B> {
+            grads: &grads,
+            values: vec![],
+        };
+        self.policy().visit(&mut collector);
+        Ok(collector.values)
+    }
+
+    fn update_value_function(&mut self, value_loss: Tensor<B, 1>) -> anyhow::Result<()> {
+        match self {
+            Self::Joint(_) => {
+                bail!(
+                    "updating the value function alone needs separate policy and value optimizers"
+                )
+            }
+            Self::Split(lm) => {
+                lm.update_value_net(value_loss);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<B: AutodiffBackend, D: BurnPolicy<B>> AuxiliaryValueLearningModule
+    for PolicyValueModule<B, D>
+{
+    fn auxiliary_values(&self, observations: &[Tensor<B, 1>]) -> anyhow::Result<Tensor<B, 1>> {
+        let head = match self {
+            Self::Joint(lm) => lm.auxiliary_head.as_ref(),
+            Self::Split(lm) => lm.auxiliary_head.as_ref(),
+        };
+        let Some(head) = head else {
+            bail!("the policy has no auxiliary value head");
+        };
+        head.values(self.policy(), observations)
+    }
+
+    fn auxiliary_update(
+        &mut self,
+        joint_loss: Tensor<B, 1>,
+        value_loss: Tensor<B, 1>,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Joint(lm) => lm.auxiliary_update(joint_loss, value_loss),
+            Self::Split(lm) => lm.auxiliary_update(joint_loss, value_loss),
+        }
+    }
+}
+
+fn parameters<B: Backend, M: Module<B>>(module: &M) -> Vec<f32> {
+    let mut collector = ParameterCollector::default();
+    module.visit(&mut collector);
+    collector.values
+}
+
+fn assign_parameters<B: Backend, M: Module<B>>(module: M, values: &[f32]) -> anyhow::Result<M> {
+    let num_params = module.num_params();
+    if values.len() != num_params {
+        bail!(
+            "got {} parameters but the module has {num_params}",
+            values.len()
+        );
+    }
+    let mut assigner = ParameterAssigner { values, offset: 0 };
+    Ok(module.map(&mut assigner))
+}
+
+#[derive(Clone)]
+struct AuxiliaryValueHeadState<B: AutodiffBackend> {
+    value_net: Vec<f32>,
+    optimizer: BurnOptimizerState<Sequential<B>, B>,
+}
+
+fn load_auxiliary_head<B: AutodiffBackend>(
+    head: &mut Option<AuxiliaryValueHead<B>>,
+    state: &Option<AuxiliaryValueHeadState<B>>,
+) -> anyhow::Result<()> {
+    match (head, state) {
+        (Some(head), Some(state)) => head.load_state(state),
+        (None, None) => Ok(()),
+        _ => bail!("only one of the modules has an auxiliary value head"),
+    }
+}
+
+/// Parameters and optimizer state of a [`PolicyValueModule`].
+#[derive(Clone)]
+pub struct PolicyValueModuleState<B: AutodiffBackend, D: BurnPolicy<B>>(ModuleState<B, D>);
+
+#[derive(Clone)]
+enum ModuleState<B: AutodiffBackend, D: BurnPolicy<B>> {
+    Joint {
+        model: Vec<f32>,
+        optimizer: BurnOptimizerState<JointActorModel<B, D>, B>,
+        auxiliary_head: Option<AuxiliaryValueHeadState<B>>,
+    },
+    Split {
+        policy: Vec<f32>,
+        policy_optimizer: BurnOptimizerState<D, B>,
+        value_net: Vec<f32>,
+        value_optimizer: BurnOptimizerState<Sequential<B>, B>,
+        auxiliary_head: Option<AuxiliaryValueHeadState<B>>,
+    },
+}
+
+impl<B: AutodiffBackend, D: BurnPolicy<B>> StatefulLearningModule for PolicyValueModule<B, D> {
+    type State = PolicyValueModuleState<B, D>;
+
+    fn state(&self) -> anyhow::Result<Self::State> {
+        let state = match self {
+            Self::Joint(lm) => ModuleState::Joint {
+                model: parameters(&lm.model),
+                optimizer: lm.optimizer.state(&lm.model),
+                auxiliary_head: lm.auxiliary_head.as_ref().map(AuxiliaryValueHead::state),
+            },
+            Self::Split(lm) => ModuleState::Split {
+                policy: parameters(&lm.policy),
+                policy_optimizer: lm.policy_optimizer.state(&lm.policy),
+                value_net: parameters(&lm.value_net),
+                value_optimizer: lm.value_optimizer.state(&lm.value_net),
+                auxiliary_head: lm.auxiliary_head.as_ref().map(AuxiliaryValueHead::state),
+            },
+        };
+        Ok(PolicyValueModuleState(state))
+    }
+
+    fn load_state(&mut self, state: &Self::State) -> anyhow::Result<()> {
+        match (self, &state.0) {
+            (
+                Self::Joint(lm),
+                ModuleState::Joint {
+                    model,
+                    optimizer,
+                    auxiliary_head,
+                },
+            ) => {
+                lm.model = assign_parameters(lm.model.clone(), model)?;
+                lm.optimizer.load_state(&lm.model, optimizer)?;
+                load_auxiliary_head(&mut lm.auxiliary_head, auxiliary_head)
+            }
+            (
+                Self::Split(lm),
+                ModuleState::Split {
+                    policy,
+                    policy_optimizer,
+                    value_net,
+                    value_optimizer,
+                    auxiliary_head,
+                },
+            ) => {
+                lm.policy = assign_parameters(lm.policy.clone(), policy)?;
+                lm.policy_optimizer
+                    .load_state(&lm.policy, policy_optimizer)?;
+                lm.value_net = assign_parameters(lm.value_net.clone(), value_net)?;
+                lm.value_optimizer
+                    .load_state(&lm.value_net, value_optimizer)?;
+                load_auxiliary_head(&mut lm.auxiliary_head, auxiliary_head)
+            }
+            _ => bail!("the state comes from a module with another optimizer layout"),
+        }
+    }
+}
+
 pub type PolicyValueModuleKind<B> = PolicyValueModule<B, PolicyKind<B>>;
@@ -15,4 +15,5 @@
 pub mod distributions;
 /// Burn policy/value learning modules and associated loss types.
 pub mod learning_module;
+mod optimizer;
 mod sequential;
@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+use burn::{
+    grad_clipping::{GradientClipping, GradientClippingConfig},
+    module::{AutodiffModule, Module, ModuleVisitor, Param, ParamId},
+    optim::{
+        Adam, AdamConfig, AdamW, AdamWConfig, GradientsParams, LearningRate, Optimizer, RmsProp,
+        RmsPropConfig, Sgd, SgdConfig, SimpleOptimizer, adaptor::OptimizerAdaptor,
+        decay::WeightDecayConfig, momentum::MomentumConfig,
+    },
+    prelude::Backend,
+    record::Record,
+    tensor::{Tensor, backend::AutodiffBackend},
+};
+use r2l_core::models::OptimizerConfig;
+
+/// RMSprop with epsilon inside the square root and the squared-gradient
+/// average starting at one, as in TensorFlow.
+///
+/// Burn's [`RmsProp`] follows PyTorch, which adds epsilon after the square
+/// root.
+#[derive(Debug, Clone)]
+pub(crate) struct RmsPropTfLike {
+    alpha: f32,
+    eps: f32,
+    momentum: f32,
+    centered: bool,
+    weight_decay: f32,
+}
+
+/// Per-parameter state of [`RmsPropTfLike`].
+#[derive(Record, Clone)]
+pub(crate) struct RmsPropTfLikeState<B: Backend, const D: usize> {
+    square_avg: Tensor<B, D>,
+    grad_avg: Option<Tensor<B, D>>,
+    momentum_buffer: Option<Tensor<B, D>>,
+}
+
+impl<B: Backend> SimpleOptimizer<B> for RmsPropTfLike {
+    type State<const D: usize> = RmsPropTfLikeState<B, D>;
+
+    fn step<const D: usize>(
+        &self,
+        lr: LearningRate,
+        tensor: Tensor<B, D>,
+        mut grad: Tensor<B, D>,
+        state: Option<Self::State<D>>,
+    ) -> (Tensor<B, D>, Option<Self::State<D>>) {
+        if self.weight_decay != 0. {
+            grad = grad + tensor.clone().mul_scalar(self.weight_decay);
+        }
+        let state = state.unwrap_or_else(|| RmsPropTfLikeState {
+            square_avg: grad.ones_like(),
+            grad_avg: self.centered.then(|| grad.zeros_like()),
+            momentum_buffer: (self.momentum > 0.).then(|| grad.zeros_like()),
+        });
+        let square_avg = state
+            .square_avg
+            .mul_scalar(self.alpha)
+            .add(grad.clone().powi_scalar(2).mul_scalar(1. - self.alpha));
+        let grad_avg = state.grad_avg.map(|grad_avg| {
+            grad_avg
+                .mul_scalar(self.alpha)
+                .add(grad.clone().mul_scalar(1. - self.alpha))
+        });
+        let avg = match &grad_avg {
+            Some(grad_avg) => square_avg.clone() - grad_avg.clone().powi_scalar(2),
+            None => square_avg.clone(),
+        };
+        let mut update = grad.div(avg.add_scalar(self.eps).sqrt());
+        let momentum_buffer = state.momentum_buffer.map(|momentum_buffer| {
+            update = momentum_buffer
+                .mul_scalar(self.momentum)
+                .add(update.clone());
+            update.clone()
+        });
+        let tensor = tensor - update.mul_scalar(lr);
+        let state = RmsPropTfLikeState {
+            square_avg,
+            grad_avg,
+            momentum_buffer,
+        };
+        (tensor, Some(state))
+    }
+
+    fn to_device<const D: usize>(state: Self::State<D>, device: &B::Device) -> Self::State<D> {
+        RmsPropTfLikeState {
+            square_avg: state.square_avg.to_device(device),
+            grad_avg: state.grad_avg.map(|grad_avg| grad_avg.to_device(device)),
+            momentum_buffer: state
+                .momentum_buffer
+                .map(|momentum_buffer| momentum_buffer.to_device(device)),
+        }
+    }
+}
+
+// Parameter ids of a module, in the order the module visits them.
+#[derive(Default)]
+struct ParamIds(Vec<ParamId>);
+
+impl<B: Backend> ModuleVisitor<B> for ParamIds {
+    fn visit_float<const D: usize>(&mut self, param: &Param<Tensor<B, D>>) {
+        self.0.push(param.id);
+    }
+}
+
+fn param_ids<B: Backend, M: Module<B>>(module: &M) -> Vec<ParamId> {
+    let mut ids = ParamIds::default();
+    module.visit(&mut ids);
+    ids.0
+}
+
+// Moves the entries of `record` from the parameter ids of one module to the
+// ids at the same positions in another. Generic over the map type, which is
+// Burn's own `HashMap` for optimizer records.
+fn rekey<V, R>(record: R, from: &[ParamId], to: &[ParamId]) -> R
+where
+    R: IntoIterator<Item = (ParamId, V)> + FromIterator<(ParamId, V)>,
+{
+    let mut record: HashMap<ParamId, V> = record.into_iter().collect();
+    from.iter()
+        .zip(to)
+        .filter_map(|(from, to)| record.remove(from).map(|value| (*to, value)))
+        .collect()
+}
+
+type RecordOf<O, M, B> = <OptimizerAdaptor<O, M, B> as Optimizer<M, B>>::Record;
+
+/// Optimizer state of a [`BurnOptimizer`], which Burn keys by parameter id.
+///
+/// The ids of the module it was taken from are kept so the state can be
+/// loaded into another module of the same architecture.
+#[derive(Clone)]
+pub(crate) struct BurnOptimizerState<M: AutodiffModule<B>, B: AutodiffBackend> {
+    ids: Vec<ParamId>,
+    record: BurnOptimizerRecord<M, B>,
+}
+
+#[derive(Clone)]
+enum BurnOptimizerRecord<M: AutodiffModule<B>, B: AutodiffBackend> {
+    Adam(RecordOf<Adam, M, B>),
+    AdamW(RecordOf<AdamW, M, B>),
+    RmsProp(RecordOf<RmsProp, M, B>),
+    RmsPropTfLike(RecordOf<RmsPropTfLike, M, B>),
+    Sgd(RecordOf<Sgd<B::InnerBackend>, M, B>),
+}
+
+/// Burn optimizer state built from an [`OptimizerConfig`].
+///
+/// Burn types optimizers by their algorithm, this erases the algorithm so
+/// learning modules can hold any of them.
+pub(crate) enum BurnOptimizer<M: AutodiffModule<B>, B: AutodiffBackend> {
+    Adam(OptimizerAdaptor<Adam, M, B>),
+    AdamW(OptimizerAdaptor<AdamW, M, B>),
+    RmsProp(OptimizerAdaptor<RmsProp, M, B>),
+    RmsPropTfLike(OptimizerAdaptor<RmsPropTfLike, M, B>),
+    Sgd(OptimizerAdaptor<Sgd<B::InnerBackend>, M, B>),
+}
+
+impl<M: AutodiffModule<B>, B: AutodiffBackend> BurnOptimizer<M, B> {
+    pub fn init(config: &OptimizerConfig, max_grad_norm: Option<f32>) -> Self {
+        let grad_clipping = max_grad_norm.map(GradientClippingConfig::Norm);
+        let weight_decay = |weight_decay: f64| {
+            (weight_decay != 0.).then(|| WeightDecayConfig::new(weight_decay as f32))
+        };
+        match *config {
+            OptimizerConfig::Adam {
+                beta1,
+                beta2,
+                eps,
+                weight_decay: decay,
+                ..
+            } => Self::Adam(
+                AdamConfig::new()
+                    .with_beta_1(beta1 as f32)
+                    .with_beta_2(beta2 as f32)
+                    .with_epsilon(eps as f32)
+                    .with_weight_decay(weight_decay(decay))
+                    .with_grad_clipping(grad_clipping)
+                    .init(),
+            ),
+            OptimizerConfig::AdamW {
+                beta1,
+                beta2,
+                eps,
+                weight_decay,
+                ..
+            } => Self::AdamW(
+                AdamWConfig::new()
+                    .with_beta_1(beta1 as f32)
+                    .with_beta_2(beta2 as f32)
+                    .with_epsilon(eps as f32)
+                    .with_weight_decay(weight_decay as f32)
+                    .with_grad_clipping(grad_clipping)
+                    .init(),
+            ),
+            OptimizerConfig::RmsProp {
+                alpha,
+                eps,
+                momentum,
+                centered,
+                weight_decay,
+                tf_like: true,
+                ..
+            } => {
+                let optimizer = RmsPropTfLike {
+                    alpha: alpha as f32,
+                    eps: eps as f32,
+                    momentum: momentum as f32,
+                    centered,
+                    weight_decay: weight_decay as f32,
+                };
+                let mut optimizer = OptimizerAdaptor::from(optimizer);
+                if let Some(grad_clipping) = grad_clipping {
+                    optimizer = optimizer.with_grad_clipping(grad_clipping.init());
+                }
+                Self::RmsPropTfLike(optimizer)
+            }
+            OptimizerConfig::RmsProp {
+                alpha,
+                eps,
+                momentum,
+                centered,
+                weight_decay: decay,
+                tf_like: false,
+                ..
+            } => Self::RmsProp(
+                RmsPropConfig::new()
+                    .with_alpha(alpha as f32)
+                    .with_epsilon(eps as f32)
+                    .with_momentum(momentum as f32)
+                    .with_centered(centered)
+                    .with_weight_decay(weight_decay(decay))
+                    .with_grad_clipping(grad_clipping)
+                    .init(),
+            ),
+            OptimizerConfig::Sgd {
+                momentum,
+                nesterov,
+                weight_decay: decay,
+                ..
+            } => {
+                let momentum = (momentum > 0.).then(|| {
+                    MomentumConfig::new()
+                        .with_momentum(momentum)
+                        .with_dampening(0.)
+                        .with_nesterov(nesterov)
+                });
+                Self::Sgd(
+                    SgdConfig::new()
+                        .with_momentum(momentum)
+                        .with_weight_decay(weight_decay(decay))
+                        .with_gradient_clipping(grad_clipping)
+                        .init(),
+                )
+            }
+        }
+    }
+
+    pub fn step(&mut self, lr: LearningRate, module: M, grads: GradientsParams) -> M {
+        match self {
+            Self::Adam(optimizer) => optimizer.step(lr, module, grads),
+            Self::AdamW(optimizer) => optimizer.step(lr, module, grads),
+            Self::RmsProp(optimizer) => optimizer.step(lr, module, grads),
+            Self::RmsPropTfLike(optimizer) => optimizer.step(lr, module, grads),
+            Self::Sgd(optimizer) => optimizer.step(lr, module, grads),
+        }
+    }
+
+    pub fn set_grad_clipping(&mut self, grad_clipping: GradientClipping) {
+        *self = match &*self {
+            Self::Adam(optimizer) => {
+                Self::Adam(optimizer.clone().with_grad_clipping(grad_clipping))
+            }
+            Self::AdamW(optimizer) => {
+                Self::AdamW(optimizer.clone().with_grad_clipping(grad_clipping))
+            }
+            Self::RmsProp(optimizer) => {
+                Self::RmsProp(optimizer.clone().with_grad_clipping(grad_clipping))
+            }
+            Self::RmsPropTfLike(optimizer) => {
+                Self::RmsPropTfLike(optimizer.clone().with_grad_clipping(grad_clipping))
+            }
+            Self::Sgd(optimizer) => Self::Sgd(optimizer.clone().with_grad_clipping(grad_clipping)),
+        };
+    }
+
+    /// Copies the optimizer state of the parameters of `module`.
+    pub fn state(&self, module: &M) -> BurnOptimizerState<M, B> {
+        let record = match self {
+            Self::Adam(optimizer) => BurnOptimizerRecord::Adam(optimizer.to_record()),
+            Self::AdamW(optimizer) => BurnOptimizerRecord::AdamW(optimizer.to_record()),
+            Self::RmsProp(optimizer) => BurnOptimizerRecord::RmsProp(optimizer.to_record()),
+            Self::RmsPropTfLike(optimizer) => {
+                BurnOptimizerRecord::RmsPropTfLike(optimizer.to_record())
+            }
+            Self::Sgd(optimizer) => BurnOptimizerRecord::Sgd(optimizer.to_record()),
+        };
+        BurnOptimizerState {
+            ids: param_ids(module),
+            record,
+        }
+    }
+
+    /// Replaces the optimizer state with `state`, taken from a module of the
+    /// same architecture as `module`.
+    pub fn load_state(
+        &mut self,
+        module: &M,
+        state: &BurnOptimizerState<M, B>,
+    ) -> anyhow::Result<()> {
+        let ids = param_ids(module);
+        if ids.len() != state.ids.len() {
+            bail!(
+                "the optimizer state covers {} parameters but the module has {}",
+                state.ids.len(),
+                ids.len()
+            );
+        }
+        let from = &state.ids;
+        *self = match (&*self, state.record.clone()) {
+            (Self::Adam(optimizer), BurnOptimizerRecord::Adam(record)) => {
+                Self::Adam(optimizer.clone().load_record(rekey(record, from, &ids)))
+            }
+            (Self::AdamW(optimizer), BurnOptimizerRecord::AdamW(record)) => {
+                Self::AdamW(optimizer.clone().load_record(rekey(record, from, &ids)))
+            }
+            (Self::RmsProp(optimizer), BurnOptimizerRecord::RmsProp(record)) => {
+                Self::RmsProp(optimizer.clone().load_record(rekey(record, from, &ids)))
+            }
+            (Self::RmsPropTfLike(optimizer), BurnOptimizerRecord::RmsPropTfLike(record)) => {
+                Self::RmsPropTfLike(optimizer.clone().load_record(rekey(record, from, &ids)))
+            }
+            (Self::Sgd(optimizer), BurnOptimizerRecord::Sgd(record)) => {
+                Self::Sgd(optimizer.clone().load_record(rekey(record, from, &ids)))
+            }
+            _ => bail!("the optimizer state comes from another optimizer"),
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use burn::{
+        backend::{Autodiff, NdArray},
+        module::{Module, Param},
+        optim::GradientsParams,
+        prelude::Backend,
+        tensor::Tensor,
+    };
+    use r2l_core::models::OptimizerConfig;
+
+    use super::BurnOptimizer;
+
+    type B = Autodiff<NdArray>;
+
+    #[derive(Module, Debug)]
+    struct Weights<B: Backend> {
+        theta: Param<Tensor<B, 1>>,
+    }
+
+    // Parameters after two steps from [1, -2] on `sum(theta^2 / 2 + theta / 2)`,
+    // whose gradient is `theta + 0.5`. The expected values follow the update
+    // rules of `torch.optim` and, for the TF-like RMSprop, of TensorFlow's
+    // RMSprop as ported by Stable-Baselines3.
+    fn two_steps(config: OptimizerConfig) -> Vec<f32> {
+        let device = Default::default();
+        let mut weights = Weights::<B> {
+            theta: Param::from_tensor(Tensor::from_floats([1., -2.], &device)),
+        };
+        let mut optimizer = BurnOptimizer::init(&config, None);
+        for _ in 0..2 {
+            let theta = weights.theta.val();
+            let loss = (theta.clone().powi_scalar(2).mul_scalar(0.5) + theta.mul_scalar(0.5)).sum();
+            let grads = GradientsParams::from_grads(loss.backward(), &weights);
+            weights = optimizer.step(config.learning_rate(), weights, grads);
+        }
+        weights.theta.val().into_data().to_vec().unwrap()
+    }
+
+    fn assert_close(actual: Vec<f32>, expected: [f32; 2]) {
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-5,
+                "expected {expected}, got {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn adam_matches_pytorch() {
+        assert_close(two_steps(OptimizerConfig::adam(0.1)), [0.800239, -1.800239]);
+        let l2 = OptimizerConfig::adam(0.1).with_weight_decay(0.5);
+        assert_close(two_steps(l2), [0.800279, -1.800209]);
+        let adamw = OptimizerConfig::adamw(0.1).with_weight_decay(0.1);
+        assert_close(two_steps(adamw), [0.781371, -1.761504]);
+    }
+
+    #[test]
+    fn rms_prop_matches_pytorch() {
+        assert_close(
+            two_steps(OptimizerConfig::rms_prop(0.01)),
+            [0.831585, -1.831585],
+        );
+        let centered = OptimizerConfig::RmsProp {
+            lr: 0.01,
+            alpha: 0.99,
+            eps: 1e-8,
+            momentum: 0.9,
+            centered: true,
+            weight_decay: 0.,
+            tf_like: false,
+        };
+        assert_close(two_steps(centered), [0.739951, -1.739951]);
+    }
+
+    #[test]
+    fn tf_like_rms_prop_matches_tensorflow() {
+        let tf_like = OptimizerConfig::RmsProp {
+            lr: 0.01,
+            alpha: 0.9,
+            eps: 1e-7,
+            momentum: 0.,
+            centered: false,
+            weight_decay: 0.,
+            tf_like: true,
+        };
+        assert_close(two_steps(tf_like), [0.972478, -1.972478]);
+        let centered = OptimizerConfig::RmsProp {
+            lr: 0.01,
+            alpha: 0.9,
+            eps: 1e-7,
+            momentum: 0.9,
+            centered: true,
+            weight_decay: 0.,
+            tf_like: true,
+        };
+        assert_close(two_steps(centered), [0.95902, -1.95902]);
+    }
+
+    #[test]
+    fn sgd_momentum_matches_pytorch() {
+        let momentum = OptimizerConfig::sgd(0.1).with_momentum(0.9).unwrap();
+        assert_close(two_steps(momentum), [0.58, -1.58]);
+        let nesterov = OptimizerConfig::Sgd {
+            lr: 0.1,
+            momentum: 0.9,
+            nesterov: true,
+            weight_decay: 0.1,
+        };
+        assert_close(two_steps(nesterov), [0.325936, -1.283807]);
+    }
+}
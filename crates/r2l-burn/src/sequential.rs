@@ -1,9 +1,18 @@
 use burn::nn::activation::{Activation, ActivationConfig};
 use burn::nn::{EluConfig, HardSigmoidConfig, LeakyReluConfig, LinearConfig};
-use burn::{module::Module, nn::Linear, prelude::Backend, tensor::Tensor};
+use burn::{
+    module::{Module, Param},
+    nn::Linear,
+    prelude::Backend,
+    tensor::{Tensor, TensorData},
+};
 use burn_store::{ModuleStore, SafetensorsStore};
-use r2l_core::models::ActivationFunction;
+use r2l_core::{
+    init::{LayerRole, WeightInit},
+    models::ActivationFunction,
+};
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Module)]
 pub enum Layer<B: Backend> {
     Activation(Activation<B>),
@@ -36,10 +45,18 @@ impl<B: Backend> Layer<B> {
         Self::Activation(config.init::<B>(&device))
     }
 
-    fn linear(input: usize, output: usize) -> Self {
+    fn linear(input: usize, output: usize, init: WeightInit, role: LayerRole) -> Self {
         let device = Default::default();
         let liner_config = LinearConfig::new(input, output).with_bias(true);
-        let linear: Linear<B> = liner_config.init::<B>(&device);
+        let mut linear: Linear<B> = liner_config.init::<B>(&device);
+        if let Some(weights) = init.weights(role, input, output) {
+            // Burn stores linear weights as [input, output]
+            let weight =
+                Tensor::<B, 2>::from_data(TensorData::new(weights, [output, input]), &device)
+                    .transpose();
+            linear.weight = Param::from_tensor(weight);
+            linear.bias = Some(Param::from_tensor(Tensor::zeros([output], &device)));
+        }
         Self::LinearLayer(linear)
     }
 }
@@ -57,15 +74,33 @@ impl<B: Backend> Sequential<B> {
         t
     }
 
-    pub fn build(layer_sizes: &[usize], activation: ActivationFunction) -> Self {
+    /// Runs the first `depth` hidden layers, each followed by its activation.
+    pub fn features(&self, mut t: Tensor<B, 2>, depth: usize) -> Tensor<B, 2> {
+        for layer in self.layers.iter().take(2 * depth) {
+            t = layer.forward(t)
+        }
+        t
+    }
+
+    pub fn build(
+        layer_sizes: &[usize],
+        activation: ActivationFunction,
+        init: WeightInit,
+        head_role: LayerRole,
+    ) -> Self {
         let mut last_dim = layer_sizes[0];
         let mut layers = vec![];
         let num_layers = layer_sizes.len();
         for (layer_idx, layer_size) in layer_sizes.iter().enumerate().skip(1) {
             if layer_idx == num_layers - 1 {
-                layers.push(Layer::linear(last_dim, *layer_size));
+                layers.push(Layer::linear(last_dim, *layer_size, init, head_role));
             } else {
-                layers.push(Layer::linear(last_dim, *layer_size));
+                layers.push(Layer::linear(
+                    last_dim,
+                    *layer_size,
+                    init,
+                    LayerRole::Hidden,
+                ));
                 layers.push(Layer::activation(activation));
             }
             last_dim = *layer_size;
@@ -2,6 +2,7 @@ use anyhow::{Result, bail};
 use candle_core::{Device, Tensor};
 use candle_nn::{Module, VarBuilder, ops::sigmoid};
 use r2l_core::{
+    init::{LayerRole, WeightInit},
     models::{ActivationFunction, Actor, Policy},
     rng::with_rng,
 };
@@ -19,6 +20,7 @@ pub struct BernoulliDistribution {
 
 impl BernoulliDistribution {
     /// Builds a Bernoulli policy network.
+    #[allow(clippy::too_many_arguments)]
     pub fn build(
         observation_size: usize,
         action_size: usize,
@@ -27,9 +29,18 @@ impl BernoulliDistribution {
         device: Device,
         prefix: &str,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> Result<Self> {
         let layers = &[hidden_layers, &[action_size]].concat();
-        let logits = build_sequential(observation_size, layers, vb, prefix, activation)?;
+        let logits = build_sequential(
+            observation_size,
+            layers,
+            vb,
+            prefix,
+            activation,
+            init,
+            LayerRole::PolicyHead,
+        )?;
         Ok(Self {
             logits,
             action_size,
@@ -46,6 +57,22 @@ impl BernoulliDistribution {
     pub fn observation_size(&self) -> usize {
         self.logits.input_size()
     }
+
+    /// Runs a batch of observations through the first `depth` hidden layers
+    /// of the policy network.
+    pub(crate) fn features(&self, observations: &Tensor, depth: usize) -> Result<Tensor> {
+        Ok(self.logits.features(observations, depth)?)
+    }
+
+    // Entropy of every bit for each state.
+    fn entropy_per_bit(&self, states: &[Tensor]) -> Result<Tensor> {
+        let states = Tensor::stack(states, 0)?;
+        let logits = self.logits.forward(&states)?;
+        let probs = sigmoid(&logits)?.clamp(1e-6, 1. - 1e-6)?;
+        let entropy_per_bit =
+            (probs.mul(&probs.log()?)? + (1. - &probs)?.mul(&(1. - &probs)?.log()?)?)?;
+        Ok(entropy_per_bit.neg()?)
+    }
 }
 
 impl Actor for BernoulliDistribution {
@@ -85,15 +112,30 @@ impl Policy for BernoulliDistribution {
     }
 
     fn entropy(&self, states: &[Tensor]) -> Result<Tensor> {
-        let states = Tensor::stack(states, 0)?;
-        let logits = self.logits.forward(&states)?;
-        let probs = sigmoid(&logits)?.clamp(1e-6, 1. - 1e-6)?;
-        let entropy_per_bit =
-            (probs.mul(&probs.log()?)? + (1. - &probs)?.mul(&(1. - &probs)?.log()?)?)?;
-        Ok(entropy_per_bit.neg()?.sum(1)?.mean_all()?)
+        Ok(self.entropy_per_bit(states)?.sum(1)?.mean_all()?)
+    }
+
+    fn entropy_per_dimension(&self, states: &[Tensor]) -> Result<Vec<f32>> {
+        Ok(self.entropy_per_bit(states)?.mean(0)?.to_vec1()?)
     }
 
     fn std(&self) -> Result<f32> {
         bail!("standard deviation is not defined for Bernoulli distributions")
     }
+
+    fn distribution_parameters(&self, states: &[Tensor]) -> Result<Tensor> {
+        let states = Tensor::stack(states, 0)?;
+        Ok(self.logits.forward(&states)?.flatten_all()?)
+    }
+
+    fn kl_divergence(&self, states: &[Tensor], old: &Tensor) -> Result<Tensor> {
+        let states = Tensor::stack(states, 0)?;
+        let logits = self.logits.forward(&states)?;
+        let probs = sigmoid(&logits)?.clamp(1e-6, 1. - 1e-6)?;
+        let old_probs = sigmoid(&old.reshape(logits.shape())?)?.clamp(1e-6, 1. - 1e-6)?;
+        let ones_kl = old_probs.mul(&(old_probs.log()? - probs.log()?)?)?;
+        let zeros_kl =
+            (1. - &old_probs)?.mul(&((1. - &old_probs)?.log()? - (1. - &probs)?.log()?)?)?;
+        Ok((ones_kl + zeros_kl)?.sum(1)?.mean_all()?)
+    }
 }
@@ -6,6 +6,7 @@ use candle_nn::VarBuilder;
 use candle_nn::ops::log_softmax;
 use candle_nn::{Module, ops::softmax};
 use r2l_core::{
+    init::{LayerRole, WeightInit},
     models::{ActivationFunction, Actor, Policy, PolicyMetadata},
     rng::with_rng,
 };
@@ -29,6 +30,7 @@ pub struct CategoricalDistribution {
 
 impl CategoricalDistribution {
     /// Builds a categorical policy network.
+    #[allow(clippy::too_many_arguments)]
     pub fn build(
         observation_size: usize,
         action_size: usize,
@@ -37,8 +39,17 @@ impl CategoricalDistribution {
         device: Device,
         prefix: &str,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> Result<Self> {
-        let logits = build_sequential(observation_size, layers, vb, prefix, activation)?;
+        let logits = build_sequential(
+            observation_size,
+            layers,
+            vb,
+            prefix,
+            activation,
+            init,
+            LayerRole::PolicyHead,
+        )?;
         Ok(Self {
             action_size,
             logits,
@@ -50,8 +61,8 @@ impl CategoricalDistribution {
         tensors: HashMap<String, Tensor>,
         device: Device,
         metadata: PolicyMetadata,
-    ) -> Self {
-        let (observation_size, layers) = network_shape(&tensors, "policy");
+    ) -> Result<Self> {
+        let (observation_size, layers) = network_shape(&tensors, "policy")?;
         let vb = VarBuilder::from_tensors(tensors, DType::F32, &device);
         let action_size = *layers.last().unwrap();
         Self::build(
@@ -62,8 +73,8 @@ impl CategoricalDistribution {
             device,
             "policy",
             metadata.activation,
+            WeightInit::default(),
         )
-        .unwrap()
     }
 
     /// Returns the Candle device used by this policy.
@@ -75,6 +86,12 @@ impl CategoricalDistribution {
     pub fn observation_size(&self) -> usize {
         self.logits.input_size()
     }
+
+    /// Runs a batch of observations through the first `depth` hidden layers
+    /// of the policy network.
+    pub(crate) fn features(&self, observations: &Tensor, depth: usize) -> Result<Tensor> {
+        Ok(self.logits.features(observations, depth)?)
+    }
 }
 
 impl Actor for CategoricalDistribution {
@@ -128,4 +145,21 @@ impl Policy for CategoricalDistribution {
     fn std(&self) -> Result<f32> {
         bail!("standard deviation is not defined for categorical distributions")
     }
+
+    fn distribution_parameters(&self, states: &[Tensor]) -> Result<Tensor> {
+        let states = Tensor::stack(states, 0)?;
+        Ok(self.logits.forward(&states)?.flatten_all()?)
+    }
+
+    fn kl_divergence(&self, states: &[Tensor], old: &Tensor) -> Result<Tensor> {
+        let states = Tensor::stack(states, 0)?;
+        let logits = self.logits.forward(&states)?;
+        let old_log_probs = log_softmax(&old.reshape(logits.shape())?, 1)?;
+        let log_probs = log_softmax(&logits, 1)?;
+        let kl_per_state = old_log_probs
+            .exp()?
+            .mul(&(&old_log_probs - log_probs)?)?
+            .sum(1)?;
+        Ok(kl_per_state.mean_all()?)
+    }
 }
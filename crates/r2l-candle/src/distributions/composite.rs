@@ -3,6 +3,7 @@ use candle_core::{Device, Tensor};
 use candle_nn::VarBuilder;
 use r2l_core::{
     env::Space,
+    init::WeightInit,
     models::{ActivationFunction, Actor, Policy},
     tensor::R2lTensor,
 };
@@ -20,6 +21,7 @@ pub struct CompositeDistribution {
 
 impl CompositeDistribution {
     /// Builds one child policy per nested action space.
+    #[allow(clippy::too_many_arguments)]
     pub fn build<T: R2lTensor>(
         action_spaces: Vec<Space<T>>,
         policy_varbuilder: &VarBuilder,
@@ -27,6 +29,7 @@ impl CompositeDistribution {
         observation_size: usize,
         activation: ActivationFunction,
         log_std_init: f32,
+        init: WeightInit,
         prefix: &str,
     ) -> Result<Self> {
         let mut policies = Vec::new();
@@ -41,6 +44,7 @@ impl CompositeDistribution {
                 observation_size,
                 activation,
                 log_std_init,
+                init,
                 &child_prefix,
             )?);
             action_sizes.push(action_size);
@@ -108,4 +112,23 @@ impl Policy for CompositeDistribution {
         }
         Ok(())
     }
+
+    fn distribution_parameters(&self, states: &[Tensor]) -> Result<Tensor> {
+        let mut parameters = Vec::new();
+        for policy in &self.policies {
+            parameters.push(policy.distribution_parameters(states)?);
+        }
+        Ok(Tensor::cat(&parameters, 0)?)
+    }
+
+    fn kl_divergence(&self, states: &[Tensor], old: &Tensor) -> Result<Tensor> {
+        let mut offset = 0;
+        let mut kls = Vec::new();
+        for policy in &self.policies {
+            let len = policy.distribution_parameters(states)?.elem_count();
+            kls.push(policy.kl_divergence(states, &old.narrow(0, offset, len)?)?);
+            offset += len;
+        }
+        Ok(Tensor::stack(&kls, 0)?.sum_all()?)
+    }
 }
@@ -4,7 +4,10 @@ use std::f32;
 use anyhow::Result;
 use candle_core::{DType, Device, Tensor};
 use candle_nn::{Module, VarBuilder};
-use r2l_core::models::{ActivationFunction, Actor, Policy, PolicyMetadata};
+use r2l_core::{
+    init::{LayerRole, WeightInit},
+    models::{ActivationFunction, Actor, Policy, PolicyMetadata},
+};
 use safetensors::serialize as st_serialize;
 
 use crate::sequential::{Sequential, build_sequential, network_shape};
@@ -31,8 +34,17 @@ impl DiagGaussianDistribution {
         log_std: Tensor,
         prefix: &str,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> Result<Self> {
-        let mu_net = build_sequential(observation_size, layers, vb, prefix, activation)?;
+        let mu_net = build_sequential(
+            observation_size,
+            layers,
+            vb,
+            prefix,
+            activation,
+            init,
+            LayerRole::PolicyHead,
+        )?;
         let noise = Tensor::randn(0f32, 1., log_std.shape(), log_std.device()).unwrap();
         let device = vb.device().clone();
         Ok(Self {
@@ -47,11 +59,11 @@ impl DiagGaussianDistribution {
         tensors: HashMap<String, Tensor>,
         device: Device,
         metadata: PolicyMetadata,
-    ) -> Self {
-        let (observation_size, layers) = network_shape(&tensors, "policy");
+    ) -> Result<Self> {
+        let (observation_size, layers) = network_shape(&tensors, "policy")?;
         let vb = VarBuilder::from_tensors(tensors, DType::F32, &device);
         let action_size = *layers.last().unwrap();
-        let log_std = vb.get(action_size, "policy.log_std").unwrap();
+        let log_std = vb.get(action_size, "policy.log_std")?;
         Self::build(
             observation_size,
             &layers,
@@ -59,8 +71,8 @@ impl DiagGaussianDistribution {
             log_std,
             "policy",
             metadata.activation,
+            WeightInit::default(),
         )
-        .unwrap()
     }
 
     /// Returns the Candle device used by this policy.
@@ -72,6 +84,12 @@ impl DiagGaussianDistribution {
     pub fn observation_size(&self) -> usize {
         self.mu_net.input_size()
     }
+
+    /// Runs a batch of observations through the first `depth` hidden layers
+    /// of the policy network.
+    pub(crate) fn features(&self, observations: &Tensor, depth: usize) -> Result<Tensor> {
+        Ok(self.mu_net.features(observations, depth)?)
+    }
 }
 
 impl Actor for DiagGaussianDistribution {
@@ -130,11 +148,42 @@ impl Policy for DiagGaussianDistribution {
         Ok(entropy)
     }
 
+    fn entropy_per_dimension(&self, _states: &[Tensor]) -> Result<Vec<f32>> {
+        let entropy = (&self.log_std + 0.5 * ((2. * std::f64::consts::PI).ln() + 1.))?;
+        Ok(entropy.to_vec1()?)
+    }
+
     fn std(&self) -> Result<f32> {
         let std = self.log_std.exp()?.mean_all()?.to_scalar::<f32>()?;
         Ok(std)
     }
 
+    fn log_std(&self) -> Result<Option<Vec<f32>>> {
+        Ok(Some(self.log_std.to_vec1()?))
+    }
+
+    // the means of every state followed by the log standard deviations
+    fn distribution_parameters(&self, states: &[Tensor]) -> Result<Tensor> {
+        let states = Tensor::stack(states, 0)?;
+        let mu = self.mu_net.forward(&states)?;
+        Ok(Tensor::cat(&[mu.flatten_all()?, self.log_std.clone()], 0)?)
+    }
+
+    fn kl_divergence(&self, states: &[Tensor], old: &Tensor) -> Result<Tensor> {
+        let states = Tensor::stack(states, 0)?;
+        let mu = self.mu_net.forward(&states)?;
+        let (batch_size, action_size) = mu.dims2()?;
+        let old_mu = old
+            .narrow(0, 0, batch_size * action_size)?
+            .reshape(mu.shape())?;
+        let old_log_std = old.narrow(0, batch_size * action_size, action_size)?;
+        let var = (&self.log_std * 2.)?.exp()?.broadcast_as(mu.shape())?;
+        let old_var = (&old_log_std * 2.)?.exp()?.broadcast_as(mu.shape())?;
+        let log_std_ratio = (&self.log_std - old_log_std)?.broadcast_as(mu.shape())?;
+        let kl = ((log_std_ratio + ((old_var + (old_mu - mu)?.sqr()?)? / (var * 2.)?)?)? - 0.5)?;
+        Ok(kl.sum(1)?.mean_all()?)
+    }
+
     fn resample_noise(&mut self) -> Result<()> {
         self.noise = Tensor::randn(0f32, 1., self.noise.shape(), self.noise.device())?;
         Ok(())
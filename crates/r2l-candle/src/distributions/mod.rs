@@ -18,7 +18,7 @@ pub mod multi_categorical;
 
 use std::{f32, fmt::Debug};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use bernoulli::BernoulliDistribution;
 use candle_core::{Device, Tensor};
 use candle_nn::{Init, VarBuilder};
@@ -28,6 +28,7 @@ use diagonal::DiagGaussianDistribution;
 use multi_categorical::MultiCategoricalDistribution;
 use r2l_core::{
     env::Space,
+    init::WeightInit,
     models::{ActivationFunction, Actor, Policy, PolicyMetadata},
     tensor::R2lTensor,
 };
@@ -75,22 +76,39 @@ impl CandlePolicyKind {
         }
     }
 
+    /// Runs a batch of observations through the first `depth` hidden layers
+    /// of the policy network, the trunk a shared value head reads from.
+    pub(crate) fn features(&self, observations: &Tensor, depth: usize) -> Result<Tensor> {
+        match self {
+            Self::Categorical(c) => c.features(observations, depth),
+            Self::DiagGaussian(d) => d.features(observations, depth),
+            Self::MultiCategorical(m) => m.features(observations, depth),
+            Self::Bernoulli(b) => b.features(observations, depth),
+            Self::Composite(_) => {
+                bail!("composite policies have one network per action and no shared trunk")
+            }
+        }
+    }
+
     /// Builds a Candle policy from serialized safetensors bytes.
-    pub fn from_bytes(bytes: &[u8], device: Device) -> Self {
-        let (_, safe_tensors_metadata) = SafeTensors::read_metadata(bytes).unwrap();
-        let metadata = PolicyMetadata::from_safetensors_metadata(
-            safe_tensors_metadata.metadata().as_ref().unwrap(),
-        );
-        let tensors = candle_core::safetensors::load_buffer(bytes, &device).unwrap();
+    ///
+    /// Only categorical and diagonal-Gaussian policies can be loaded.
+    pub fn from_bytes(bytes: &[u8], device: Device) -> Result<Self> {
+        let (_, safe_tensors_metadata) = SafeTensors::read_metadata(bytes)?;
+        let Some(metadata) = safe_tensors_metadata.metadata() else {
+            bail!("the policy has no metadata, it was not saved by a Candle policy");
+        };
+        let metadata = PolicyMetadata::from_safetensors_metadata(metadata)?;
+        let tensors = candle_core::safetensors::load_buffer(bytes, &device)?;
 
         if tensors.contains_key("policy.log_std") {
-            Self::DiagGaussian(DiagGaussianDistribution::from_parts(
+            Ok(Self::DiagGaussian(DiagGaussianDistribution::from_parts(
                 tensors, device, metadata,
-            ))
+            )?))
         } else {
-            Self::Categorical(CategoricalDistribution::from_parts(
+            Ok(Self::Categorical(CategoricalDistribution::from_parts(
                 tensors, device, metadata,
-            ))
+            )?))
         }
     }
 
@@ -102,6 +120,7 @@ impl CandlePolicyKind {
         observation_size: usize,
         activation: ActivationFunction,
         log_std_init: f32,
+        init: WeightInit,
     ) -> Result<Self> {
         Self::build_with_prefix(
             action_space,
@@ -110,10 +129,12 @@ impl CandlePolicyKind {
             observation_size,
             activation,
             log_std_init,
+            init,
             "policy",
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn build_with_prefix<T: R2lTensor>(
         action_space: Space<T>,
         policy_varbuilder: &VarBuilder,
@@ -121,6 +142,7 @@ impl CandlePolicyKind {
         observation_size: usize,
         activation: ActivationFunction,
         log_std_init: f32,
+        init: WeightInit,
         prefix: &str,
     ) -> Result<Self> {
         match action_space {
@@ -134,6 +156,7 @@ impl CandlePolicyKind {
                     policy_varbuilder.device().clone(),
                     prefix,
                     activation,
+                    init,
                 )?))
             }
             Space::Box { shape, .. } => {
@@ -151,6 +174,7 @@ impl CandlePolicyKind {
                     log_std,
                     prefix,
                     activation,
+                    init,
                 )?))
             }
             Space::MultiDiscrete { nvec, .. } => {
@@ -162,6 +186,7 @@ impl CandlePolicyKind {
                     policy_varbuilder.device().clone(),
                     prefix,
                     activation,
+                    init,
                 )?))
             }
             Space::MultiBinary { shape } => {
@@ -174,6 +199,7 @@ impl CandlePolicyKind {
                     policy_varbuilder.device().clone(),
                     prefix,
                     activation,
+                    init,
                 )?))
             }
             Space::Tuple(spaces) => Ok(Self::Composite(CompositeDistribution::build(
@@ -183,6 +209,7 @@ impl CandlePolicyKind {
                 observation_size,
                 activation,
                 log_std_init,
+                init,
                 prefix,
             )?)),
             Space::Dict(spaces) => Ok(Self::Composite(CompositeDistribution::build(
@@ -192,6 +219,7 @@ impl CandlePolicyKind {
                 observation_size,
                 activation,
                 log_std_init,
+                init,
                 prefix,
             )?)),
         }
@@ -243,6 +271,26 @@ impl Policy for CandlePolicyKind {
         }
     }
 
+    fn entropy_per_dimension(&self, states: &[Self::Tensor]) -> Result<Vec<f32>> {
+        match self {
+            Self::Categorical(cat) => cat.entropy_per_dimension(states),
+            Self::DiagGaussian(diag) => diag.entropy_per_dimension(states),
+            Self::MultiCategorical(multi) => multi.entropy_per_dimension(states),
+            Self::Bernoulli(bernoulli) => bernoulli.entropy_per_dimension(states),
+            Self::Composite(composite) => composite.entropy_per_dimension(states),
+        }
+    }
+
+    fn log_std(&self) -> Result<Option<Vec<f32>>> {
+        match self {
+            Self::Categorical(cat) => cat.log_std(),
+            Self::DiagGaussian(diag) => diag.log_std(),
+            Self::MultiCategorical(multi) => multi.log_std(),
+            Self::Bernoulli(bernoulli) => bernoulli.log_std(),
+            Self::Composite(composite) => composite.log_std(),
+        }
+    }
+
     fn std(&self) -> Result<f32> {
         match self {
             Self::Categorical(cat) => cat.std(),
@@ -262,4 +310,24 @@ impl Policy for CandlePolicyKind {
             Self::Composite(composite) => composite.resample_noise(),
         }
     }
+
+    fn distribution_parameters(&self, states: &[Self::Tensor]) -> Result<Self::Tensor> {
+        match self {
+            Self::Categorical(cat) => cat.distribution_parameters(states),
+            Self::DiagGaussian(diag) => diag.distribution_parameters(states),
+            Self::MultiCategorical(multi) => multi.distribution_parameters(states),
+            Self::Bernoulli(bernoulli) => bernoulli.distribution_parameters(states),
+            Self::Composite(composite) => composite.distribution_parameters(states),
+        }
+    }
+
+    fn kl_divergence(&self, states: &[Self::Tensor], old: &Self::Tensor) -> Result<Self::Tensor> {
+        match self {
+            Self::Categorical(cat) => cat.kl_divergence(states, old),
+            Self::DiagGaussian(diag) => diag.kl_divergence(states, old),
+            Self::MultiCategorical(multi) => multi.kl_divergence(states, old),
+            Self::Bernoulli(bernoulli) => bernoulli.kl_divergence(states, old),
+            Self::Composite(composite) => composite.kl_divergence(states, old),
+        }
+    }
 }
@@ -6,6 +6,7 @@ use candle_nn::{
 };
 use r2l_core::{
     env::action_ranges,
+    init::{LayerRole, WeightInit},
     models::{ActivationFunction, Actor, Policy},
     rng::with_rng,
 };
@@ -24,6 +25,7 @@ pub struct MultiCategoricalDistribution {
 
 impl MultiCategoricalDistribution {
     /// Builds a policy network whose output is split according to `nvec`.
+    #[allow(clippy::too_many_arguments)]
     pub fn build(
         observation_size: usize,
         nvec: Vec<usize>,
@@ -32,10 +34,19 @@ impl MultiCategoricalDistribution {
         device: Device,
         prefix: &str,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> Result<Self> {
         let logits_size = nvec.iter().sum();
         let layers = &[hidden_layers, &[logits_size]].concat();
-        let logits = build_sequential(observation_size, layers, vb, prefix, activation)?;
+        let logits = build_sequential(
+            observation_size,
+            layers,
+            vb,
+            prefix,
+            activation,
+            init,
+            LayerRole::PolicyHead,
+        )?;
         Ok(Self {
             nvec,
             logits,
@@ -52,6 +63,26 @@ impl MultiCategoricalDistribution {
     pub fn observation_size(&self) -> usize {
         self.logits.input_size()
     }
+
+    /// Runs a batch of observations through the first `depth` hidden layers
+    /// of the policy network.
+    pub(crate) fn features(&self, observations: &Tensor, depth: usize) -> Result<Tensor> {
+        Ok(self.logits.features(observations, depth)?)
+    }
+
+    // Entropy of every sub-action for each state.
+    fn entropies(&self, states: &[Tensor]) -> Result<Vec<Tensor>> {
+        let states = Tensor::stack(states, 0)?;
+        let logits = self.logits.forward(&states)?;
+        let mut entropies = Vec::new();
+        for (offset, choices) in action_ranges(&self.nvec) {
+            let logits = logits.narrow(1, offset, choices)?;
+            let probs = softmax(&logits, 1)?;
+            let log_probs = log_softmax(&logits, 1)?;
+            entropies.push(probs.mul(&log_probs)?.neg()?.sum(1)?);
+        }
+        Ok(entropies)
+    }
 }
 
 impl Actor for MultiCategoricalDistribution {
@@ -92,20 +123,42 @@ impl Policy for MultiCategoricalDistribution {
     }
 
     fn entropy(&self, states: &[Tensor]) -> Result<Tensor> {
-        let states = Tensor::stack(states, 0)?;
-        let logits = self.logits.forward(&states)?;
-        let mut entropies = Vec::new();
-        for (offset, choices) in action_ranges(&self.nvec) {
-            let logits = logits.narrow(1, offset, choices)?;
-            let probs = softmax(&logits, 1)?;
-            let log_probs = log_softmax(&logits, 1)?;
-            entropies.push(probs.mul(&log_probs)?.neg()?.sum(1)?);
-        }
+        let entropies = self.entropies(states)?;
         let entropy_per_state = Tensor::stack(&entropies, 0)?.sum(0)?;
         Ok(entropy_per_state.mean_all()?)
     }
 
+    fn entropy_per_dimension(&self, states: &[Tensor]) -> Result<Vec<f32>> {
+        self.entropies(states)?
+            .iter()
+            .map(|entropy| Ok(entropy.mean_all()?.to_scalar::<f32>()?))
+            .collect()
+    }
+
     fn std(&self) -> Result<f32> {
         bail!("standard deviation is not defined for multi-categorical distributions")
     }
+
+    fn distribution_parameters(&self, states: &[Tensor]) -> Result<Tensor> {
+        let states = Tensor::stack(states, 0)?;
+        Ok(self.logits.forward(&states)?.flatten_all()?)
+    }
+
+    fn kl_divergence(&self, states: &[Tensor], old: &Tensor) -> Result<Tensor> {
+        let states = Tensor::stack(states, 0)?;
+        let logits = self.logits.forward(&states)?;
+        let old = old.reshape(logits.shape())?;
+        let mut kls = Vec::new();
+        for (offset, choices) in action_ranges(&self.nvec) {
+            let old_log_probs = log_softmax(&old.narrow(1, offset, choices)?, 1)?;
+            let log_probs = log_softmax(&logits.narrow(1, offset, choices)?, 1)?;
+            kls.push(
+                old_log_probs
+                    .exp()?
+                    .mul(&(&old_log_probs - log_probs)?)?
+                    .sum(1)?,
+            );
+        }
+        Ok(Tensor::stack(&kls, 0)?.sum(0)?.mean_all()?)
+    }
 }
@@ -6,12 +6,21 @@
 //! [`OnPolicyLearningModule`](r2l_core::on_policy::learning_module::OnPolicyLearningModule)
 //! implementation.
 
-use anyhow::{Ok, Result};
-use candle_core::{DType, Device, Tensor};
-use candle_nn::{AdamW, Module, Optimizer, ParamsAdamW, VarBuilder, VarMap};
+use std::collections::BTreeMap;
+
+use anyhow::{Ok, Result, anyhow, bail};
+use candle_core::{DType, Device, Tensor, Var};
+use candle_nn::{Module, VarBuilder, VarMap};
 use r2l_core::{
-    models::{ActivationFunction, LearningModule, ValueFunction},
-    on_policy::{learning_module::OnPolicyLearningModule, losses::FromPolicyValueLosses},
+    init::{LayerRole, WeightInit},
+    models::{ActivationFunction, GradNorms, LearningModule, OptimizerConfig, ValueFunction},
+    on_policy::{
+        learning_module::{
+            AuxiliaryValueLearningModule, OnPolicyLearningModule, StatefulLearningModule,
+            TrustRegionLearningModule,
+        },
+        losses::FromPolicyValueLosses,
+    },
 };
 
 use crate::{
@@ -98,6 +107,16 @@ impl SplitPolicyValueOptimizer {
         self.value_optimizer_with_grad
             .set_max_grad_norm(max_grad_norm);
     }
+
+    /// Starts or stops measuring the policy gradient norms.
+    pub fn collect_policy_grad_norms(&mut self, collect: bool) {
+        self.policy_optimizer_with_grad.collect_grad_norms(collect);
+    }
+
+    /// Returns the policy gradient norms recorded since the last call.
+    pub fn take_policy_grad_norms(&mut self) -> GradNorms {
+        self.policy_optimizer_with_grad.take_grad_norms()
+    }
 }
 
 /// The policy and the value function has different optimizers
@@ -143,6 +162,16 @@ impl JointPolicyValueOptimizer {
     pub fn set_grad_clip(&mut self, max_grad_norm: Option<f32>) {
         self.optimizer_with_grad.set_max_grad_norm(max_grad_norm);
     }
+
+    /// Starts or stops measuring the gradient norms.
+    pub fn collect_grad_norms(&mut self, collect: bool) {
+        self.optimizer_with_grad.collect_grad_norms(collect);
+    }
+
+    /// Returns the gradient norms recorded since the last call.
+    pub fn take_grad_norms(&mut self) -> GradNorms {
+        self.optimizer_with_grad.take_grad_norms()
+    }
 }
 
 impl LearningModule for JointPolicyValueOptimizer {
@@ -173,22 +202,45 @@ impl SequentialValueFunction {
         vb: &VarBuilder,
         prefix: &str,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> candle_core::Result<Self> {
-        let value_net = build_sequential(input_dim, layers, vb, prefix, activation)?;
+        let value_net = build_sequential(
+            input_dim,
+            layers,
+            vb,
+            prefix,
+            activation,
+            init,
+            LayerRole::ValueHead,
+        )?;
         candle_core::Result::Ok(Self { value_net })
     }
 }
 
+impl SequentialValueFunction {
+    fn values_from_features(&self, features: &Tensor) -> Result<Tensor> {
+        let value = self.value_net.forward(features)?.squeeze(1)?;
+        Ok(value)
+    }
+}
+
 impl ValueFunction for SequentialValueFunction {
     type Tensor = Tensor;
 
     fn values(&self, observations: &[Tensor]) -> Result<Tensor> {
         let observations = Tensor::stack(observations, 0)?;
-        let value = self.value_net.forward(&observations)?.squeeze(1)?;
-        Ok(value)
+        self.values_from_features(&observations)
     }
 }
 
+/// Value head on the policy features, trained with its own optimizer.
+struct AuxiliaryValueHead {
+    value_function: SequentialValueFunction,
+    optimizer_with_grad: OptimizerWithMaxGrad,
+    // number of policy hidden layers the head reads from
+    depth: usize,
+}
+
 /// Erased optimizer type covering joint and split policy/value optimizers.
 pub enum PolicyValueOptimizer {
     /// Shared optimizer configuration for policy and value updates.
@@ -201,11 +253,10 @@ impl PolicyValueOptimizer {
     /// Builds a joint policy/value optimizer.
     pub fn joint(
         vm: VarMap,
-        params: ParamsAdamW,
+        optimizer: &OptimizerConfig,
         max_grad_norm: Option<f32>,
     ) -> candle_core::Result<Self> {
-        let optimizer = AdamW::new(vm.all_vars(), params)?;
-        let optimizer_with_grad = OptimizerWithMaxGrad::new(optimizer, max_grad_norm, vm);
+        let optimizer_with_grad = OptimizerWithMaxGrad::new(optimizer, max_grad_norm, vm)?;
         candle_core::Result::Ok(Self::Joint(JointPolicyValueOptimizer {
             optimizer_with_grad,
         }))
@@ -215,17 +266,15 @@ impl PolicyValueOptimizer {
     pub fn split(
         policy_vm: VarMap,
         critic_vm: VarMap,
-        policy_params: ParamsAdamW,
-        value_params: ParamsAdamW,
+        policy_optimizer: &OptimizerConfig,
+        value_optimizer: &OptimizerConfig,
         policy_max_grad_norm: Option<f32>,
         value_max_grad_norm: Option<f32>,
     ) -> candle_core::Result<Self> {
-        let policy_optimizer = AdamW::new(policy_vm.all_vars(), policy_params.clone())?;
-        let value_optimizer = AdamW::new(critic_vm.all_vars(), value_params.clone())?;
         let policy_optimizer_with_grad =
-            OptimizerWithMaxGrad::new(policy_optimizer, policy_max_grad_norm, policy_vm);
+            OptimizerWithMaxGrad::new(policy_optimizer, policy_max_grad_norm, policy_vm)?;
         let value_optimizer_with_grad =
-            OptimizerWithMaxGrad::new(value_optimizer, value_max_grad_norm, critic_vm);
+            OptimizerWithMaxGrad::new(value_optimizer, value_max_grad_norm, critic_vm)?;
         candle_core::Result::Ok(Self::Split(SplitPolicyValueOptimizer {
             policy_optimizer_with_grad,
             value_optimizer_with_grad,
@@ -257,6 +306,22 @@ impl PolicyValueOptimizer {
             Self::Split(split) => split.set_policy_grad_clip(max_grad_norm),
         }
     }
+
+    /// Starts or stops measuring the policy-side gradient norms.
+    pub fn collect_policy_grad_norms(&mut self, collect: bool) {
+        match self {
+            Self::Joint(joint) => joint.collect_grad_norms(collect),
+            Self::Split(split) => split.collect_policy_grad_norms(collect),
+        }
+    }
+
+    /// Returns the policy-side gradient norms recorded since the last call.
+    pub fn take_policy_grad_norms(&mut self) -> GradNorms {
+        match self {
+            Self::Joint(joint) => joint.take_grad_norms(),
+            Self::Split(split) => split.take_policy_grad_norms(),
+        }
+    }
 }
 
 impl LearningModule for PolicyValueOptimizer {
@@ -277,53 +342,76 @@ impl LearningModule for PolicyValueOptimizer {
 /// A2C and PPO integrations in the workspace.
 pub struct PolicyValueModule {
     policy: CandlePolicyKind,
+    // policy variables sorted by name, the layout of the flat parameter vector
+    policy_vars: Vec<Var>,
     optimizer: PolicyValueOptimizer,
     value_function: SequentialValueFunction,
+    // number of leading policy hidden layers the value network reads from
+    shared_depth: usize,
+    auxiliary_head: Option<AuxiliaryValueHead>,
     device: Device,
 }
 
 impl PolicyValueModule {
     /// Builds a policy/value module with a shared optimizer configuration.
+    ///
+    /// `shared_layers` are the leading hidden layers of `policy` that the
+    /// value network reads from instead of the observations. Both losses then
+    /// train them. Pass an empty slice for separate networks.
+    #[allow(clippy::too_many_arguments)]
     pub fn build_joint(
         policy: CandlePolicyKind,
+        shared_layers: &[usize],
         value_hidden_layers: &[usize],
         policy_varmap: VarMap,
         max_grad_norm: Option<f32>,
-        params: ParamsAdamW,
+        optimizer: &OptimizerConfig,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> Result<Self> {
         let device = policy.device();
+        let policy_vars = sorted_vars(&policy_varmap);
         let policy_vb = VarBuilder::from_varmap(&policy_varmap, DType::F32, &device);
-        let observation_size = policy.observation_size();
+        let value_input_size = shared_layers
+            .last()
+            .copied()
+            .unwrap_or_else(|| policy.observation_size());
         let value_layers = &[value_hidden_layers, &[1]].concat();
         let value_function = SequentialValueFunction::new(
-            observation_size,
+            value_input_size,
             value_layers,
             &policy_vb,
             "value",
             activation,
+            init,
         )?;
-        let optimizer = PolicyValueOptimizer::joint(policy_varmap, params, max_grad_norm)?;
+        let optimizer = PolicyValueOptimizer::joint(policy_varmap, optimizer, max_grad_norm)?;
         Ok(Self {
             policy,
+            policy_vars,
             optimizer,
             value_function,
+            shared_depth: shared_layers.len(),
+            auxiliary_head: None,
             device,
         })
     }
 
     /// Builds a policy/value module with separate policy and value optimizers.
+    #[allow(clippy::too_many_arguments)]
     pub fn build_split(
         policy: CandlePolicyKind,
         value_hidden_layers: &[usize],
         policy_varmap: VarMap,
         policy_max_grad_norm: Option<f32>,
         value_max_grad_norm: Option<f32>,
-        policy_params: ParamsAdamW,
-        value_params: ParamsAdamW,
+        policy_optimizer: &OptimizerConfig,
+        value_optimizer: &OptimizerConfig,
         activation: ActivationFunction,
+        init: WeightInit,
     ) -> Result<Self> {
         let device = policy.device();
+        let policy_vars = sorted_vars(&policy_varmap);
         let observation_size = policy.observation_size();
         let critic_varmap = VarMap::new();
         let critic_vb = VarBuilder::from_varmap(&critic_varmap, DType::F32, &device);
@@ -334,23 +422,74 @@ impl PolicyValueModule {
             &critic_vb,
             "value",
             activation,
+            init,
         )?;
         let optimizer = PolicyValueOptimizer::split(
             policy_varmap,
             critic_varmap,
-            policy_params,
-            value_params,
+            policy_optimizer,
+            value_optimizer,
             policy_max_grad_norm,
             value_max_grad_norm,
         )?;
         Ok(Self {
             policy,
+            policy_vars,
             optimizer,
             value_function,
+            shared_depth: 0,
+            auxiliary_head: None,
             device: device.clone(),
         })
     }
 
+    /// Adds a value head on the output of the policy hidden layers, as used
+    /// by Phasic Policy Gradient.
+    ///
+    /// The head stacks `hidden_layers`, separated by `activation`, before its
+    /// output layer; an empty slice gives the single linear layer of the PPG
+    /// paper. It has its own optimizer, built from the policy optimizer
+    /// configuration.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_auxiliary_value_head(
+        mut self,
+        policy_hidden_layers: &[usize],
+        hidden_layers: &[usize],
+        activation: ActivationFunction,
+        optimizer: &OptimizerConfig,
+        max_grad_norm: Option<f32>,
+        init: WeightInit,
+    ) -> Result<Self> {
+        let varmap = VarMap::new();
+        let vb = VarBuilder::from_varmap(&varmap, DType::F32, &self.device);
+        let input_size = policy_hidden_layers
+            .last()
+            .copied()
+            .unwrap_or_else(|| self.policy.observation_size());
+        let value_function = SequentialValueFunction::new(
+            input_size,
+            &[hidden_layers, &[1]].concat(),
+            &vb,
+            "auxiliary_value",
+            activation,
+            init,
+        )?;
+        let optimizer_with_grad = OptimizerWithMaxGrad::new(optimizer, max_grad_norm, varmap)?;
+        self.auxiliary_head = Some(AuxiliaryValueHead {
+            value_function,
+            optimizer_with_grad,
+            depth: policy_hidden_layers.len(),
+        });
+        Ok(self)
+    }
+
+    fn auxiliary_head(&self) -> Result<&AuxiliaryValueHead> {
+        match &self.auxiliary_head {
+            Some(head) => Ok(head),
+            None => bail!("the policy has no auxiliary value head"),
+        }
+    }
+
     /// Sets policy-side gradient clipping for future updates.
     pub fn set_grad_clipping(&mut self, gradient_clipping: Option<f32>) {
         self.optimizer.set_grad_clipping(gradient_clipping);
@@ -360,13 +499,32 @@ impl PolicyValueModule {
     pub fn policy_learning_rate(&self) -> f64 {
         self.optimizer.policy_learning_rate()
     }
+
+    /// Starts or stops measuring the norms of the policy-side gradients.
+    /// Measuring them reads the gradients back from the device on every
+    /// update, so they are only measured on request.
+    pub fn collect_grad_norms(&mut self, collect: bool) {
+        self.optimizer.collect_policy_grad_norms(collect);
+    }
+
+    /// Returns the norms of the policy-side gradients applied since the last
+    /// call, before and after clipping. Empty unless
+    /// [`collect_grad_norms`](Self::collect_grad_norms) is on.
+    pub fn take_grad_norms(&mut self) -> GradNorms {
+        self.optimizer.take_policy_grad_norms()
+    }
 }
 
 impl ValueFunction for PolicyValueModule {
     type Tensor = Tensor;
 
     fn values(&self, observations: &[Self::Tensor]) -> anyhow::Result<Self::Tensor> {
-        self.value_function.values(observations)
+        if self.shared_depth == 0 {
+            return self.value_function.values(observations);
+        }
+        let observations = Tensor::stack(observations, 0)?;
+        let features = self.policy.features(&observations, self.shared_depth)?;
+        self.value_function.values_from_features(&features)
     }
 }
 
@@ -394,6 +552,11 @@ impl OnPolicyLearningModule for PolicyValueModule {
 
     fn set_learning_rate(&mut self, learning_rate: f64) {
         self.optimizer.set_learning_rate(learning_rate);
+        if let Some(head) = &mut self.auxiliary_head {
+            head.optimizer_with_grad
+                .optimizer
+                .set_learning_rate(learning_rate);
+        }
     }
 
     fn tensor_from_slice(&self, slice: &[f32]) -> Self::LearningTensor {
@@ -404,3 +567,180 @@ impl OnPolicyLearningModule for PolicyValueModule {
         t.clone()
     }
 }
+
+impl TrustRegionLearningModule for PolicyValueModule {
+    fn policy_parameters(&self) -> Result<Vec<f32>> {
+        let mut parameters = vec![];
+        for var in &self.policy_vars {
+            parameters.extend(var.flatten_all()?.to_vec1::<f32>()?);
+        }
+        Ok(parameters)
+    }
+
+    fn set_policy_parameters(&mut self, parameters: &[f32]) -> Result<()> {
+        let num_params = self
+            .policy_vars
+            .iter()
+            .map(|var| var.elem_count())
+            .sum::<usize>();
+        if parameters.len() != num_params {
+            bail!(
+                "got {} parameters but the policy has {num_params}",
+                parameters.len()
+            );
+        }
+        let mut offset = 0;
+        for var in &self.policy_vars {
+            let len = var.elem_count();
+            let values =
+                Tensor::from_slice(&parameters[offset..offset + len], var.dims(), var.device())?;
+            var.set(&values)?;
+            offset += len;
+        }
+        Ok(())
+    }
+
+    fn policy_gradient(&mut self, loss: &Tensor) -> Result<Vec<f32>> {
+        let grads = loss.backward()?;
+        let mut gradient = vec![];
+        for var in &self.policy_vars {
+            match grads.get(var.as_tensor()) {
+                Some(grad) => gradient.extend(grad.flatten_all()?.to_vec1::<f32>()?),
+                None => gradient.extend(std::iter::repeat_n(0., var.elem_count())),
+            }
+        }
+        Ok(gradient)
+    }
+
+    fn update_value_function(&mut self, value_loss: Tensor) -> Result<()> {
+        match &mut self.optimizer {
+            PolicyValueOptimizer::Split(split) => {
+                split.value_optimizer_with_grad.backward_step(&value_loss)?;
+                Ok(())
+            }
+            PolicyValueOptimizer::Joint(_) => {
+                bail!(
+                    "updating the value function alone needs separate policy and value optimizers"
+                )
+            }
+        }
+    }
+}
+
+impl AuxiliaryValueLearningModule for PolicyValueModule {
+    fn auxiliary_values(&self, observations: &[Tensor]) -> Result<Tensor> {
+        let head = self.auxiliary_head()?;
+        let observations = Tensor::stack(observations, 0)?;
+        let features = self.policy.features(&observations, head.depth)?;
+        head.value_function.values_from_features(&features)
+    }
+
+    fn auxiliary_update(&mut self, joint_loss: Tensor, value_loss: Tensor) -> Result<()> {
+        self.auxiliary_head()?;
+        // The policy steps first: its gradients flow through the head weights,
+        // which the head step overwrites in place.
+        match &mut self.optimizer {
+            PolicyValueOptimizer::Joint(joint) => joint
+                .optimizer_with_grad
+                .backward_step(&joint_loss.add(&value_loss)?)?,
+            PolicyValueOptimizer::Split(split) => {
+                split
+                    .policy_optimizer_with_grad
+                    .backward_step(&joint_loss)?;
+                split.value_optimizer_with_grad.backward_step(&value_loss)?;
+            }
+        }
+        if let Some(head) = &mut self.auxiliary_head {
+            head.optimizer_with_grad.backward_step(&joint_loss)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parameters and optimizer state of a [`PolicyValueModule`], copied out of
+/// its variables by name.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyValueModuleState {
+    pub(crate) tensors: BTreeMap<String, Tensor>,
+    // Adam step counts, by optimizer
+    pub(crate) steps: BTreeMap<String, usize>,
+}
+
+impl PolicyValueModuleState {
+    pub(crate) fn tensor(&self, key: &str) -> Result<&Tensor> {
+        self.tensors
+            .get(key)
+            .ok_or_else(|| anyhow!("the state has no `{key}`, it comes from another architecture"))
+    }
+
+    pub(crate) fn step_count(&self, optimizer: &str) -> Result<usize> {
+        self.steps
+            .get(optimizer)
+            .copied()
+            .ok_or_else(|| anyhow!("the state has no step count for the `{optimizer}` optimizer"))
+    }
+}
+
+impl PolicyValueModule {
+    // optimizers with the prefix of their state keys
+    fn optimizers(&self) -> Vec<(&'static str, &OptimizerWithMaxGrad)> {
+        let mut optimizers = match &self.optimizer {
+            PolicyValueOptimizer::Joint(joint) => vec![("joint", &joint.optimizer_with_grad)],
+            PolicyValueOptimizer::Split(split) => vec![
+                ("policy", &split.policy_optimizer_with_grad),
+                ("value", &split.value_optimizer_with_grad),
+            ],
+        };
+        if let Some(head) = &self.auxiliary_head {
+            optimizers.push(("auxiliary", &head.optimizer_with_grad));
+        }
+        optimizers
+    }
+
+    fn optimizers_mut(&mut self) -> Vec<(&'static str, &mut OptimizerWithMaxGrad)> {
+        let mut optimizers = match &mut self.optimizer {
+            PolicyValueOptimizer::Joint(joint) => vec![("joint", &mut joint.optimizer_with_grad)],
+            PolicyValueOptimizer::Split(split) => vec![
+                ("policy", &mut split.policy_optimizer_with_grad),
+                ("value", &mut split.value_optimizer_with_grad),
+            ],
+        };
+        if let Some(head) = &mut self.auxiliary_head {
+            optimizers.push(("auxiliary", &mut head.optimizer_with_grad));
+        }
+        optimizers
+    }
+}
+
+impl StatefulLearningModule for PolicyValueModule {
+    type State = PolicyValueModuleState;
+
+    fn state(&self) -> Result<Self::State> {
+        let mut state = PolicyValueModuleState::default();
+        for (prefix, optimizer) in self.optimizers() {
+            optimizer.save_state(prefix, &mut state)?;
+        }
+        Ok(state)
+    }
+
+    fn load_state(&mut self, state: &Self::State) -> Result<()> {
+        let mut loaded = 0;
+        for (prefix, optimizer) in self.optimizers_mut() {
+            loaded += optimizer.load_state(prefix, state)?;
+        }
+        if loaded != state.tensors.len() {
+            bail!(
+                "the state holds {} tensors but the module only {loaded}, it comes from another architecture",
+                state.tensors.len()
+            );
+        }
+        Ok(())
+    }
+}
+
+fn sorted_vars(varmap: &VarMap) -> Vec<Var> {
+    let data = varmap.data().lock().unwrap();
+    let mut vars = data.iter().collect::<Vec<_>>();
+    vars.sort_by_key(|(name, _)| *name);
+    vars.into_iter().map(|(_, var)| var.clone()).collect()
+}
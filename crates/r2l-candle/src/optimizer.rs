@@ -1,39 +1,439 @@
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 
-use candle_core::{Result, Tensor, backprop::GradStore};
-use candle_nn::{AdamW, Optimizer, VarMap};
+use candle_core::{Result, Tensor, TensorId, Var, backprop::GradStore};
+use candle_nn::VarMap;
+use r2l_core::models::{GradNorms, OptimizerConfig};
 
-fn clip_grad(t: &Tensor, varmap: &VarMap, max_norm: f32) -> Result<GradStore> {
-    let mut total_norm_squared = 0.0f32;
-    let mut grad_store = t.backward()?;
+use crate::learning_module::PolicyValueModuleState;
+
+// Returns the global gradient norm before and after clipping it to
+// `max_norm`.
+fn clip_grad(
+    grad_store: &mut GradStore,
+    varmap: &VarMap,
+    max_norm: Option<f32>,
+) -> Result<(f32, f32)> {
+    let mut norms_squared = vec![];
     let mut var_ids = vec![];
     let all_vars = varmap.all_vars();
     for var in all_vars.iter() {
         let id = var.id();
         if let Some(grad) = grad_store.get_id(id) {
             var_ids.push(id);
-            let grad_norm_sq = grad.sqr()?.sum_all()?.to_scalar::<f32>()?;
-            total_norm_squared += grad_norm_sq;
+            norms_squared.push(grad.sqr()?.sum_all()?);
+        }
+    }
+    // reduced on the device so reading the norm syncs once
+    let total_norm = if norms_squared.is_empty() {
+        0.
+    } else {
+        Tensor::stack(&norms_squared, 0)?
+            .sum_all()?
+            .sqrt()?
+            .to_scalar::<f32>()?
+    };
+    let Some(max_norm) = max_norm.filter(|max_norm| total_norm > *max_norm) else {
+        return Ok((total_norm, total_norm));
+    };
+    let clip_coef = (max_norm) / (total_norm + 1e-6);
+    for var_id in var_ids {
+        let var = all_vars.iter().find(|t| t.id() == var_id).unwrap();
+        let old_grad = grad_store.get_id(var_id).unwrap();
+        let clip_coef = Tensor::full(clip_coef, old_grad.shape(), old_grad.device())?;
+        let new_grad = old_grad.broadcast_mul(&clip_coef)?;
+        grad_store.insert(var.as_tensor(), new_grad);
+    }
+    Ok((total_norm, total_norm * clip_coef))
+}
+
+struct AdamVar {
+    var: Var,
+    first_moment: Var,
+    second_moment: Var,
+}
+
+/// Adam with the weight decay added to the gradients, as in PyTorch, or
+/// decoupled from them, as in AdamW.
+///
+/// `candle_nn` only ships AdamW, and keeps its moments private.
+#[derive(Debug)]
+pub(crate) struct Adam {
+    vars: Vec<AdamVar>,
+    step_t: usize,
+    lr: f64,
+    beta1: f64,
+    beta2: f64,
+    eps: f64,
+    weight_decay: f64,
+    decoupled_weight_decay: bool,
+}
+
+impl Adam {
+    fn step(&mut self, grads: &GradStore) -> Result<()> {
+        self.step_t += 1;
+        let scale_m = 1. / (1. - self.beta1.powi(self.step_t as i32));
+        let scale_v = 1. / (1. - self.beta2.powi(self.step_t as i32));
+        for var in self.vars.iter() {
+            let theta = &var.var;
+            let Some(g) = grads.get(theta) else {
+                continue;
+            };
+            let (g, decayed_theta) = if self.decoupled_weight_decay {
+                let decay = 1. - self.lr * self.weight_decay;
+                (g.clone(), (theta.as_tensor() * decay)?)
+            } else {
+                let g = with_weight_decay(g, theta, self.weight_decay)?;
+                (g, theta.as_tensor().clone())
+            };
+            let m = &var.first_moment;
+            let v = &var.second_moment;
+            let next_m = ((m.as_tensor() * self.beta1)? + (&g * (1. - self.beta1))?)?;
+            let next_v = ((v.as_tensor() * self.beta2)? + (g.sqr()? * (1. - self.beta2))?)?;
+            let adjusted_m = (&next_m * scale_m)?;
+            let adjusted_v = (&next_v * scale_v)?;
+            let update = (adjusted_m / (adjusted_v.sqrt()? + self.eps)?)?;
+            let next_theta = (decayed_theta - (update * self.lr)?)?;
+            m.set(&next_m)?;
+            v.set(&next_v)?;
+            theta.set(&next_theta)?;
+        }
+        Ok(())
+    }
+}
+
+struct RmsPropVar {
+    var: Var,
+    square_avg: Var,
+    grad_avg: Option<Var>,
+    momentum_buffer: Option<Var>,
+}
+
+#[derive(Debug)]
+pub(crate) struct RmsProp {
+    vars: Vec<RmsPropVar>,
+    lr: f64,
+    alpha: f64,
+    eps: f64,
+    momentum: f64,
+    weight_decay: f64,
+    tf_like: bool,
+}
+
+impl RmsProp {
+    fn step(&mut self, grads: &GradStore) -> Result<()> {
+        for var in self.vars.iter() {
+            let theta = &var.var;
+            let Some(g) = grads.get(theta) else {
+                continue;
+            };
+            let g = with_weight_decay(g, theta, self.weight_decay)?;
+            let next_square_avg =
+                ((var.square_avg.as_tensor() * self.alpha)? + (g.sqr()? * (1. - self.alpha))?)?;
+            let mut avg = next_square_avg.clone();
+            if let Some(grad_avg) = &var.grad_avg {
+                let next_grad_avg =
+                    ((grad_avg.as_tensor() * self.alpha)? + (&g * (1. - self.alpha))?)?;
+                avg = (avg - next_grad_avg.sqr()?)?;
+                grad_avg.set(&next_grad_avg)?;
+            }
+            let denom = if self.tf_like {
+                (avg + self.eps)?.sqrt()?
+            } else {
+                (avg.sqrt()? + self.eps)?
+            };
+            let mut update = (g / denom)?;
+            if let Some(momentum_buffer) = &var.momentum_buffer {
+                update = ((momentum_buffer.as_tensor() * self.momentum)? + update)?;
+                momentum_buffer.set(&update)?;
+            }
+            let next_theta = (theta.as_tensor() - (update * self.lr)?)?;
+            var.square_avg.set(&next_square_avg)?;
+            theta.set(&next_theta)?;
+        }
+        Ok(())
+    }
+}
+
+struct SgdVar {
+    var: Var,
+    momentum_buffer: Option<Var>,
+}
+
+/// SGD with optional momentum, as in PyTorch without dampening.
+///
+/// `candle_nn`'s SGD has no momentum.
+#[derive(Debug)]
+pub(crate) struct Sgd {
+    vars: Vec<SgdVar>,
+    lr: f64,
+    momentum: f64,
+    nesterov: bool,
+    weight_decay: f64,
+}
+
+impl Sgd {
+    fn step(&mut self, grads: &GradStore) -> Result<()> {
+        for var in self.vars.iter() {
+            let theta = &var.var;
+            let Some(g) = grads.get(theta) else {
+                continue;
+            };
+            let mut update = with_weight_decay(g, theta, self.weight_decay)?;
+            if let Some(momentum_buffer) = &var.momentum_buffer {
+                let next_buffer = ((momentum_buffer.as_tensor() * self.momentum)? + &update)?;
+                update = if self.nesterov {
+                    (update + (&next_buffer * self.momentum)?)?
+                } else {
+                    next_buffer.clone()
+                };
+                momentum_buffer.set(&next_buffer)?;
+            }
+            let next_theta = (theta.as_tensor() - (update * self.lr)?)?;
+            theta.set(&next_theta)?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! impl_var_debug {
+    ($($ty:ty),*) => {
+        $(
+            impl Debug for $ty {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.debug_struct(stringify!($ty))
+                        .field("shape", self.var.shape())
+                        .finish()
+                }
+            }
+        )*
+    };
+}
+
+impl_var_debug!(AdamVar, RmsPropVar, SgdVar);
+
+fn with_weight_decay(g: &Tensor, theta: &Var, weight_decay: f64) -> Result<Tensor> {
+    if weight_decay == 0. {
+        Ok(g.clone())
+    } else {
+        g + (theta.as_tensor() * weight_decay)?
+    }
+}
+
+fn zeros_like(var: &Var) -> Result<Var> {
+    Var::zeros(var.shape(), var.dtype(), var.device())
+}
+
+/// Candle optimizer state built from an [`OptimizerConfig`].
+#[derive(Debug)]
+pub(crate) enum OptimizerKind {
+    Adam(Adam),
+    RmsProp(RmsProp),
+    Sgd(Sgd),
+}
+
+impl OptimizerKind {
+    pub fn new(vars: Vec<Var>, config: &OptimizerConfig) -> Result<Self> {
+        let vars = vars
+            .into_iter()
+            .filter(|var| var.dtype().is_float())
+            .collect::<Vec<_>>();
+        let optimizer = match *config {
+            OptimizerConfig::Adam {
+                lr,
+                beta1,
+                beta2,
+                eps,
+                weight_decay,
+            }
+            | OptimizerConfig::AdamW {
+                lr,
+                beta1,
+                beta2,
+                eps,
+                weight_decay,
+            } => {
+                let vars = vars
+                    .into_iter()
+                    .map(|var| {
+                        Ok(AdamVar {
+                            first_moment: zeros_like(&var)?,
+                            second_moment: zeros_like(&var)?,
+                            var,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Self::Adam(Adam {
+                    vars,
+                    step_t: 0,
+                    lr,
+                    beta1,
+                    beta2,
+                    eps,
+                    weight_decay,
+                    decoupled_weight_decay: matches!(config, OptimizerConfig::AdamW { .. }),
+                })
+            }
+            OptimizerConfig::RmsProp {
+                lr,
+                alpha,
+                eps,
+                momentum,
+                centered,
+                weight_decay,
+                tf_like,
+            } => {
+                let vars = vars
+                    .into_iter()
+                    .map(|var| {
+                        // TensorFlow starts the squared-gradient average at one
+                        let square_avg = if tf_like {
+                            Var::ones(var.shape(), var.dtype(), var.device())?
+                        } else {
+                            zeros_like(&var)?
+                        };
+                        let grad_avg = centered.then(|| zeros_like(&var)).transpose()?;
+                        let momentum_buffer =
+                            (momentum > 0.).then(|| zeros_like(&var)).transpose()?;
+                        Ok(RmsPropVar {
+                            var,
+                            square_avg,
+                            grad_avg,
+                            momentum_buffer,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Self::RmsProp(RmsProp {
+                    vars,
+                    lr,
+                    alpha,
+                    eps,
+                    momentum,
+                    weight_decay,
+                    tf_like,
+                })
+            }
+            OptimizerConfig::Sgd {
+                lr,
+                momentum,
+                nesterov,
+                weight_decay,
+            } => {
+                let vars = vars
+                    .into_iter()
+                    .map(|var| {
+                        let momentum_buffer =
+                            (momentum > 0.).then(|| zeros_like(&var)).transpose()?;
+                        Ok(SgdVar {
+                            var,
+                            momentum_buffer,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Self::Sgd(Sgd {
+                    vars,
+                    lr,
+                    momentum,
+                    nesterov,
+                    weight_decay,
+                })
+            }
+        };
+        Ok(optimizer)
+    }
+
+    pub fn step(&mut self, grads: &GradStore) -> Result<()> {
+        match self {
+            Self::Adam(optimizer) => optimizer.step(grads),
+            Self::RmsProp(optimizer) => optimizer.step(grads),
+            Self::Sgd(optimizer) => optimizer.step(grads),
+        }
+    }
+
+    pub fn learning_rate(&self) -> f64 {
+        match self {
+            Self::Adam(optimizer) => optimizer.lr,
+            Self::RmsProp(optimizer) => optimizer.lr,
+            Self::Sgd(optimizer) => optimizer.lr,
+        }
+    }
+
+    pub fn set_learning_rate(&mut self, learning_rate: f64) {
+        match self {
+            Self::Adam(optimizer) => optimizer.lr = learning_rate,
+            Self::RmsProp(optimizer) => optimizer.lr = learning_rate,
+            Self::Sgd(optimizer) => optimizer.lr = learning_rate,
         }
     }
-    let total_norm = total_norm_squared.sqrt();
-    if total_norm > max_norm {
-        let clip_coef = (max_norm) / (total_norm + 1e-6);
-        for var_id in var_ids {
-            let var = all_vars.iter().find(|t| t.id() == var_id).unwrap();
-            let old_grad = grad_store.get_id(var_id).unwrap();
-            let clip_coef = Tensor::full(clip_coef, old_grad.shape(), old_grad.device())?;
-            let new_grad = old_grad.broadcast_mul(&clip_coef)?;
-            grad_store.insert(var.as_tensor(), new_grad);
+
+    /// Optimized variables with their named state buffers.
+    fn buffers(&self) -> Vec<(&Var, Vec<(&'static str, &Var)>)> {
+        match self {
+            Self::Adam(optimizer) => optimizer
+                .vars
+                .iter()
+                .map(|var| {
+                    let buffers = vec![
+                        ("first_moment", &var.first_moment),
+                        ("second_moment", &var.second_moment),
+                    ];
+                    (&var.var, buffers)
+                })
+                .collect(),
+            Self::RmsProp(optimizer) => optimizer
+                .vars
+                .iter()
+                .map(|var| {
+                    let mut buffers = vec![("square_avg", &var.square_avg)];
+                    buffers.extend(var.grad_avg.as_ref().map(|v| ("grad_avg", v)));
+                    buffers.extend(var.momentum_buffer.as_ref().map(|v| ("momentum_buffer", v)));
+                    (&var.var, buffers)
+                })
+                .collect(),
+            Self::Sgd(optimizer) => optimizer
+                .vars
+                .iter()
+                .map(|var| {
+                    let buffers = var
+                        .momentum_buffer
+                        .as_ref()
+                        .map(|v| ("momentum_buffer", v))
+                        .into_iter()
+                        .collect();
+                    (&var.var, buffers)
+                })
+                .collect(),
+        }
+    }
+
+    /// Number of steps taken, for optimizers that correct for it.
+    fn step_count(&self) -> Option<usize> {
+        match self {
+            Self::Adam(optimizer) => Some(optimizer.step_t),
+            Self::RmsProp(_) | Self::Sgd(_) => None,
+        }
+    }
+
+    fn set_step_count(&mut self, step_count: usize) {
+        if let Self::Adam(optimizer) = self {
+            optimizer.step_t = step_count;
         }
     }
-    Ok(grad_store)
+}
+
+// Names of the variables of `varmap`, by tensor id.
+fn var_names(varmap: &VarMap) -> HashMap<TensorId, String> {
+    let data = varmap.data().lock().unwrap();
+    data.iter()
+        .map(|(name, var)| (var.id(), name.clone()))
+        .collect()
 }
 
 pub(crate) struct OptimizerWithMaxGrad {
-    pub optimizer: AdamW,
+    pub optimizer: OptimizerKind,
     pub max_grad_norm: Option<f32>,
     pub varmap: VarMap,
+    // only measured while someone collects them
+    grad_norms: Option<GradNorms>,
 }
 
 impl Debug for OptimizerWithMaxGrad {
@@ -46,25 +446,199 @@ impl Debug for OptimizerWithMaxGrad {
 }
 
 impl OptimizerWithMaxGrad {
-    pub fn new(optimizer: AdamW, max_grad_norm: Option<f32>, varmap: VarMap) -> Self {
-        Self {
+    pub fn new(
+        config: &OptimizerConfig,
+        max_grad_norm: Option<f32>,
+        varmap: VarMap,
+    ) -> Result<Self> {
+        let optimizer = OptimizerKind::new(varmap.all_vars(), config)?;
+        Ok(Self {
             optimizer,
             max_grad_norm,
             varmap,
-        }
+            grad_norms: None,
+        })
     }
 
     pub fn backward_step(&mut self, loss: &Tensor) -> Result<()> {
-        let grads = if let Some(max_norm) = self.max_grad_norm {
-            clip_grad(loss, &self.varmap, max_norm)?
-        } else {
-            loss.backward()?
-        };
+        let mut grads = loss.backward()?;
+        if self.max_grad_norm.is_some() || self.grad_norms.is_some() {
+            let (pre_clip, post_clip) = clip_grad(&mut grads, &self.varmap, self.max_grad_norm)?;
+            if let Some(grad_norms) = &mut self.grad_norms {
+                grad_norms.record(pre_clip, post_clip);
+            }
+        }
         self.optimizer.step(&grads)?;
         Ok(())
     }
 
+    /// Starts or stops measuring the gradient norms of every update.
+    pub fn collect_grad_norms(&mut self, collect: bool) {
+        let grad_norms = self.grad_norms.take().unwrap_or_default();
+        self.grad_norms = collect.then_some(grad_norms);
+    }
+
+    /// Returns the gradient norms recorded since the last call.
+    pub fn take_grad_norms(&mut self) -> GradNorms {
+        self.grad_norms
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     pub fn set_max_grad_norm(&mut self, max_grad_norm: Option<f32>) {
         self.max_grad_norm = max_grad_norm;
     }
+
+    /// Copies the variables and the optimizer state into `state`, keyed by
+    /// `prefix` and the variable names.
+    pub fn save_state(&self, prefix: &str, state: &mut PolicyValueModuleState) -> Result<()> {
+        for (name, var) in self.varmap.data().lock().unwrap().iter() {
+            state
+                .tensors
+                .insert(format!("{prefix}.{name}"), var.as_tensor().copy()?);
+        }
+        let names = var_names(&self.varmap);
+        for (var, buffers) in self.optimizer.buffers() {
+            let name = &names[&var.id()];
+            for (buffer, value) in buffers {
+                state.tensors.insert(
+                    format!("{prefix}.{name}.{buffer}"),
+                    value.as_tensor().copy()?,
+                );
+            }
+        }
+        if let Some(step_count) = self.optimizer.step_count() {
+            state.steps.insert(prefix.to_owned(), step_count);
+        }
+        Ok(())
+    }
+
+    /// Overwrites the variables and the optimizer state with the ones saved
+    /// under `prefix`, and returns the number of tensors read.
+    pub fn load_state(
+        &mut self,
+        prefix: &str,
+        state: &PolicyValueModuleState,
+    ) -> anyhow::Result<usize> {
+        let mut loaded = 0;
+        for (name, var) in self.varmap.data().lock().unwrap().iter() {
+            var.set(state.tensor(&format!("{prefix}.{name}"))?)?;
+            loaded += 1;
+        }
+        let names = var_names(&self.varmap);
+        for (var, buffers) in self.optimizer.buffers() {
+            let name = &names[&var.id()];
+            for (buffer, value) in buffers {
+                value.set(state.tensor(&format!("{prefix}.{name}.{buffer}"))?)?;
+                loaded += 1;
+            }
+        }
+        if self.optimizer.step_count().is_some() {
+            self.optimizer.set_step_count(state.step_count(prefix)?);
+        }
+        Ok(loaded)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use candle_core::{DType, Device, Result, Var};
+    use r2l_core::models::OptimizerConfig;
+
+    use super::OptimizerKind;
+
+    // Parameters after two steps from [1, -2] on `sum(theta^2 / 2 + theta / 2)`,
+    // whose gradient is `theta + 0.5`. The expected values follow the update
+    // rules of `torch.optim` and, for the TF-like RMSprop, of TensorFlow's
+    // RMSprop as ported by Stable-Baselines3.
+    fn two_steps(config: OptimizerConfig) -> Result<Vec<f32>> {
+        let theta = Var::from_slice(&[1f32, -2.], 2, &Device::Cpu)?;
+        let mut optimizer = OptimizerKind::new(vec![theta.clone()], &config)?;
+        for _ in 0..2 {
+            let loss = ((theta.sqr()? * 0.5)? + (theta.as_tensor() * 0.5)?)?.sum_all()?;
+            optimizer.step(&loss.backward()?)?;
+        }
+        theta.to_dtype(DType::F32)?.to_vec1()
+    }
+
+    fn assert_close(actual: Vec<f32>, expected: [f32; 2]) {
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-5,
+                "expected {expected}, got {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn adam_matches_pytorch() -> Result<()> {
+        assert_close(
+            two_steps(OptimizerConfig::adam(0.1))?,
+            [0.800239, -1.800239],
+        );
+        let l2 = OptimizerConfig::adam(0.1).with_weight_decay(0.5);
+        assert_close(two_steps(l2)?, [0.800279, -1.800209]);
+        let adamw = OptimizerConfig::adamw(0.1).with_weight_decay(0.1);
+        assert_close(two_steps(adamw)?, [0.781371, -1.761504]);
+        Ok(())
+    }
+
+    #[test]
+    fn rms_prop_matches_pytorch() -> Result<()> {
+        assert_close(
+            two_steps(OptimizerConfig::rms_prop(0.01))?,
+            [0.831585, -1.831585],
+        );
+        let centered = OptimizerConfig::RmsProp {
+            lr: 0.01,
+            alpha: 0.99,
+            eps: 1e-8,
+            momentum: 0.9,
+            centered: true,
+            weight_decay: 0.,
+            tf_like: false,
+        };
+        assert_close(two_steps(centered)?, [0.739951, -1.739951]);
+        Ok(())
+    }
+
+    #[test]
+    fn tf_like_rms_prop_matches_tensorflow() -> Result<()> {
+        let tf_like = OptimizerConfig::RmsProp {
+            lr: 0.01,
+            alpha: 0.9,
+            eps: 1e-7,
+            momentum: 0.,
+            centered: false,
+            weight_decay: 0.,
+            tf_like: true,
+        };
+        assert_close(two_steps(tf_like)?, [0.972478, -1.972478]);
+        let centered = OptimizerConfig::RmsProp {
+            lr: 0.01,
+            alpha: 0.9,
+            eps: 1e-7,
+            momentum: 0.9,
+            centered: true,
+            weight_decay: 0.,
+            tf_like: true,
+        };
+        assert_close(two_steps(centered)?, [0.95902, -1.95902]);
+        Ok(())
+    }
+
+    #[test]
+    fn sgd_momentum_matches_pytorch() -> Result<()> {
+        let momentum = OptimizerConfig::sgd(0.1).with_momentum(0.9).unwrap();
+        assert_close(two_steps(momentum)?, [0.58, -1.58]);
+        let nesterov = OptimizerConfig::Sgd {
+            lr: 0.1,
+            momentum: 0.9,
+            nesterov: true,
+            weight_decay: 0.1,
+        };
+        assert_close(two_steps(nesterov)?, [0.325936, -1.283807]);
+        Ok(())
+    }
 }
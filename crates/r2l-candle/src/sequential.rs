@@ -4,7 +4,10 @@ use candle_core::{Result, Tensor};
 use candle_nn::init::{FanInOut, NonLinearity, NormalOrUniform};
 use candle_nn::{Activation, Init, Linear, Module, VarBuilder};
 use either::Either;
-use r2l_core::models::ActivationFunction;
+use r2l_core::{
+    init::{LayerRole, WeightInit},
+    models::ActivationFunction,
+};
 
 #[derive(Debug, Clone)]
 struct LinearLayer {
@@ -12,8 +15,26 @@ struct LinearLayer {
 }
 
 impl LinearLayer {
-    fn new(in_dim: usize, out_dim: usize, vb: &VarBuilder, prefix: &str) -> Result<Self> {
+    fn new(
+        in_dim: usize,
+        out_dim: usize,
+        vb: &VarBuilder,
+        prefix: &str,
+        init: WeightInit,
+        role: LayerRole,
+    ) -> Result<Self> {
         let layer_vb = vb.pp(prefix);
+        if let Some(weights) = init.weights(role, in_dim, out_dim) {
+            // `VarBuilder` only takes initialization hints, so the drawn values
+            // are written into the freshly created variables
+            let weight = layer_vb.get_with_hints((out_dim, in_dim), "weight", Init::Const(0.))?;
+            let values = Tensor::from_vec(weights, (out_dim, in_dim), weight.device())?
+                .to_dtype(weight.dtype())?;
+            weight.slice_set(&values, 0, 0)?;
+            let bias = layer_vb.get_with_hints(out_dim, "bias", Init::Const(0.))?;
+            let layer = Linear::new(weight, Some(bias));
+            return Ok(Self { layer });
+        }
         let weight = layer_vb.get_with_hints(
             (out_dim, in_dim),
             "weight",
@@ -135,6 +156,15 @@ impl Sequential {
             .flat_map(|(idx, linear)| linear.named_tensors(&format!("{prefix}{idx}")))
             .collect()
     }
+
+    /// Runs the first `depth` hidden layers, each followed by its activation.
+    pub(crate) fn features(&self, xs: &Tensor, depth: usize) -> Result<Tensor> {
+        let mut xs = xs.clone();
+        for layer in self.layers.iter().take(2 * depth) {
+            xs = layer.forward(&xs)?
+        }
+        Ok(xs)
+    }
 }
 
 impl Module for Sequential {
@@ -153,6 +183,8 @@ pub(crate) fn build_sequential(
     vb: &VarBuilder,
     prefix: &str,
     activation: ActivationFunction,
+    init: WeightInit,
+    head_role: LayerRole,
 ) -> Result<Sequential> {
     let mut last_dim = input_dim;
     let mut nn = Sequential::default();
@@ -160,10 +192,17 @@ pub(crate) fn build_sequential(
     for (layer_idx, layer_size) in layers.iter().enumerate() {
         let layer_pp = format!("{prefix}{layer_idx}");
         if layer_idx == num_layers - 1 {
-            let layer = LinearLayer::new(last_dim, *layer_size, vb, &layer_pp)?;
+            let layer = LinearLayer::new(last_dim, *layer_size, vb, &layer_pp, init, head_role)?;
             nn = nn.add_layer(Layer::linear(layer))
         } else {
-            let lin_layer = LinearLayer::new(last_dim, *layer_size, vb, &layer_pp)?;
+            let lin_layer = LinearLayer::new(
+                last_dim,
+                *layer_size,
+                vb,
+                &layer_pp,
+                init,
+                LayerRole::Hidden,
+            )?;
             nn = nn
                 .add_layer(Layer::linear(lin_layer))
                 .add_layer(Layer::activation(ActivationLayer::new(activation)));
@@ -176,8 +215,10 @@ pub(crate) fn build_sequential(
 pub(crate) fn network_shape(
     tensors: &HashMap<String, Tensor>,
     prefix: &str,
-) -> (usize, Vec<usize>) {
-    let first_weight = tensors.get(&format!("{prefix}0.weight")).unwrap();
+) -> anyhow::Result<(usize, Vec<usize>)> {
+    let Some(first_weight) = tensors.get(&format!("{prefix}0.weight")) else {
+        anyhow::bail!("the policy has no `{prefix}0.weight` tensor");
+    };
     let first_dims = first_weight.dims();
 
     let observation_size = first_dims[1];
@@ -191,7 +232,7 @@ pub(crate) fn network_shape(
         layers.push(dims[0]);
     }
 
-    (observation_size, layers)
+    Ok((observation_size, layers))
 }
 
 impl Sequential {
@@ -0,0 +1,623 @@
+// Command line front end for experiment configs. Training runs write the
+// effective config, metrics and policies into a run directory, which the other
+// subcommands then read back.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{self, Stdio},
+};
+
+use anyhow::{Context, Result, bail};
+use burn::backend::NdArray;
+use candle_core::Device;
+use clap::{Args, Parser, Subcommand};
+use r2l_api::{
+    BEST_POLICY_FILE, BackendConfig, EnvBuilder, Evaluator, ExperimentConfig, SamplerExecutionMode,
+    SearchSpace, SearchStrategy, Study, SuccessiveHalving, Trainable, TrialProcess, TrialState,
+    best_trial,
+};
+use r2l_burn::distributions::PolicyKind;
+use r2l_candle::distributions::CandlePolicyKind;
+use r2l_core::{episode::EpisodeStats, models::Actor};
+
+/// File name of the final policy inside a run directory.
+const POLICY_FILE: &str = "policy.safetensors";
+/// Config file names looked up inside a run directory.
+const CONFIG_FILES: [&str; 2] = ["experiment.toml", "experiment.yaml"];
+const DEFAULT_RUNS_DIR: &str = "runs";
+/// File name of the metrics CSV inside a run directory.
+const METRICS_FILE: &str = "metrics.csv";
+/// File name of the trial store inside a study directory.
+const STUDY_FILE: &str = "study.json";
+
+#[derive(Parser)]
+#[command(
+    name = "r2l",
+    about = "Train and evaluate r2l agents from experiment configs"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Trains the algorithm described by an experiment config.
+    Train {
+        /// Experiment config, a `.toml`, `.yaml` or `.yml` file.
+        config: PathBuf,
+        /// Overrides a config value, such as `algorithm.gamma=0.98`.
+        #[arg(short = 's', long = "set", value_name = "KEY=VALUE", value_parser = parse_override)]
+        overrides: Vec<(String, String)>,
+        /// Run directory, `runs/<config name>` unless the config sets one.
+        #[arg(long)]
+        run_dir: Option<PathBuf>,
+        /// Overrides the seed of the config.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Does not print training progress.
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Evaluates a saved policy and prints its episode statistics.
+    Evaluate {
+        #[command(flatten)]
+        policy: PolicyArgs,
+        /// Evaluation episodes per environment.
+        #[arg(short, long, default_value_t = 10)]
+        n_episodes: usize,
+        /// Number of evaluation environments.
+        #[arg(long, default_value_t = 1)]
+        n_envs: usize,
+    },
+    /// Renders a saved policy playing the environment.
+    Enjoy {
+        #[command(flatten)]
+        policy: PolicyArgs,
+        /// Episodes to play.
+        #[arg(short, long, default_value_t = 3)]
+        n_episodes: usize,
+    },
+    /// Searches hyperparameters of an experiment config, training every trial
+    /// in its own `r2l train` process.
+    Tune(TuneArgs),
+    /// Copies a saved policy and its config into a standalone directory.
+    Export {
+        #[command(flatten)]
+        policy: PolicyArgs,
+        /// Output directory.
+        output: PathBuf,
+        /// Writes the config as YAML instead of TOML.
+        #[arg(long)]
+        yaml: bool,
+    },
+}
+
+#[derive(Args)]
+struct PolicyArgs {
+    /// Run directory, or a saved policy file.
+    run: PathBuf,
+    /// Uses the best policy found by the evaluator instead of the final one.
+    #[arg(long)]
+    best: bool,
+    /// Experiment config, found in the run directory by default.
+    #[arg(long)]
+    config: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct TuneArgs {
+    /// Experiment config, a `.toml`, `.yaml` or `.yml` file.
+    config: PathBuf,
+    /// Search space, a `.toml`, `.yaml` or `.yml` file mapping override paths
+    /// to distributions.
+    #[arg(long)]
+    space: PathBuf,
+    /// Random search trials.
+    #[arg(long, default_value_t = 20, conflicts_with = "grid")]
+    trials: usize,
+    /// Tries every combination of the search space instead of random draws.
+    #[arg(long)]
+    grid: bool,
+    /// Trials trained at once.
+    #[arg(short, long, default_value_t = 1)]
+    jobs: usize,
+    /// Study directory, `runs/<config name>-study` by default. Running the
+    /// same study again resumes it.
+    #[arg(long)]
+    study_dir: Option<PathBuf>,
+    /// Seed of the random search.
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Prunes trials with successive halving, starting at this many sampled
+    /// environment steps.
+    #[arg(long, value_name = "STEPS")]
+    prune_min_steps: Option<usize>,
+    /// Budget ratio between successive-halving rungs.
+    #[arg(long, default_value_t = 3)]
+    reduction_factor: usize,
+}
+
+fn parse_override(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.trim().to_owned(), value.to_owned())),
+        _ => Err(format!("expected KEY=VALUE, found `{arg}`")),
+    }
+}
+
+impl PolicyArgs {
+    /// Returns the experiment config and the path of the selected policy.
+    fn resolve(&self) -> Result<(ExperimentConfig, PathBuf)> {
+        let run_dir = if self.run.is_dir() {
+            self.run.as_path()
+        } else {
+            self.run.parent().unwrap_or(Path::new("."))
+        };
+        let config_path = match &self.config {
+            Some(config) => config.clone(),
+            None => CONFIG_FILES
+                .iter()
+                .map(|file| run_dir.join(file))
+                .find(|path| path.is_file())
+                .with_context(|| {
+                    format!(
+                        "no experiment config in {}, pass one with --config",
+                        run_dir.display()
+                    )
+                })?,
+        };
+        let config = ExperimentConfig::from_file(config_path)?;
+        let policy_path = if !self.run.is_dir() {
+            self.run.clone()
+        } else if self.best {
+            run_dir.join(BEST_POLICY_FILE)
+        } else {
+            run_dir.join(POLICY_FILE)
+        };
+        Ok((config, policy_path))
+    }
+
+    /// Loads the selected policy on the CPU with the backend of the config.
+    fn load(&self) -> Result<(ExperimentConfig, LoadedPolicy)> {
+        let (config, policy_path) = self.resolve()?;
+        if config.sampler.obs_clip.is_some() {
+            eprintln!(
+                "warning: the policy was trained on normalized observations, which are not saved with it"
+            );
+        }
+        let bytes = fs::read(&policy_path)
+            .with_context(|| format!("failed to read policy {}", policy_path.display()))?;
+        let policy = match config.backend {
+            BackendConfig::Candle | BackendConfig::CandleCuda { .. } => {
+                CandlePolicyKind::from_bytes(&bytes, Device::Cpu).map(LoadedPolicy::Candle)
+            }
+            // Burn policies do not record their activation, the config does.
+            BackendConfig::Burn => {
+                PolicyKind::from_bytes(&bytes, config.network.activation.unwrap_or_default())
+                    .map(|policy| LoadedPolicy::Burn(Box::new(policy)))
+            }
+        }
+        .with_context(|| format!("failed to decode policy {}", policy_path.display()))?;
+        Ok((config, policy))
+    }
+}
+
+/// A saved policy loaded with the backend it was trained with.
+enum LoadedPolicy {
+    Candle(CandlePolicyKind),
+    Burn(Box<PolicyKind<NdArray>>),
+}
+
+impl LoadedPolicy {
+    /// Plays the policy and returns the finished episodes.
+    fn run_episodes<EB: EnvBuilder>(
+        self,
+        env_builder: EB,
+        n_episodes: usize,
+        n_envs: usize,
+    ) -> Result<Vec<EpisodeStats>> {
+        match self {
+            Self::Candle(actor) => run_episodes(env_builder, actor, n_episodes, n_envs),
+            Self::Burn(actor) => run_episodes(env_builder, *actor, n_episodes, n_envs),
+        }
+    }
+}
+
+fn run_episodes<EB: EnvBuilder, A: Actor + Clone>(
+    env_builder: EB,
+    actor: A,
+    n_episodes: usize,
+    n_envs: usize,
+) -> Result<Vec<EpisodeStats>> {
+    let mut evaluator = Evaluator::new(env_builder, n_episodes, n_envs, SamplerExecutionMode::Vec);
+    evaluator.eval(actor)?;
+    Ok(evaluator.episodes())
+}
+
+fn train(
+    config_path: PathBuf,
+    overrides: Vec<(String, String)>,
+    run_dir: Option<PathBuf>,
+    seed: Option<u64>,
+    quiet: bool,
+) -> Result<()> {
+    let mut config = ExperimentConfig::from_file(&config_path)?;
+    for (key, value) in &overrides {
+        config = config.with_override(key, value)?;
+    }
+    if quiet {
+        config = config.with_override("algorithm.log_progress", "false")?;
+    }
+    if seed.is_some() {
+        config.seed = seed;
+    }
+    let run_dir = run_dir.or(config.run_dir.take()).unwrap_or_else(|| {
+        let name = config_path.file_stem().unwrap_or("experiment".as_ref());
+        Path::new(DEFAULT_RUNS_DIR).join(name)
+    });
+    config.run_dir = Some(run_dir.clone());
+    println!("Training in {}", run_dir.display());
+    let mut algorithm = config.build()?;
+    train_and_save(algorithm.as_mut(), &run_dir)
+}
+
+/// Trains the algorithm and writes its final policy into the run directory.
+fn train_and_save(algorithm: &mut dyn Trainable, run_dir: &Path) -> Result<()> {
+    algorithm.train()?;
+    match algorithm.serialized_policy() {
+        Some(bytes) => {
+            let policy_path = run_dir.join(POLICY_FILE);
+            fs::write(&policy_path, bytes)
+                .with_context(|| format!("failed to write policy {}", policy_path.display()))?;
+            println!("Saved policy to {}", policy_path.display());
+        }
+        None => eprintln!("warning: the policy does not support serialization and was not saved"),
+    }
+    Ok(())
+}
+
+fn evaluate(policy: PolicyArgs, n_episodes: usize, n_envs: usize, render: bool) -> Result<()> {
+    let (config, policy) = policy.load()?;
+    let mut env_builder = config.env.gym_env_builder()?;
+    if render {
+        env_builder = env_builder.with_render_mode("human");
+    }
+    let episodes = policy.run_episodes(env_builder, n_episodes, n_envs)?;
+    if episodes.is_empty() {
+        bail!("no episode finished during evaluation");
+    }
+    if render {
+        for (idx, episode) in episodes.iter().enumerate() {
+            println!(
+                "episode {idx}: reward {:.2}, length {}",
+                episode.reward, episode.length
+            );
+        }
+    }
+    let n = episodes.len() as f32;
+    let mean_reward = episodes.iter().map(|episode| episode.reward).sum::<f32>() / n;
+    let std_reward = (episodes
+        .iter()
+        .map(|episode| (episode.reward - mean_reward).powi(2))
+        .sum::<f32>()
+        / n)
+        .sqrt();
+    let mean_length = episodes
+        .iter()
+        .map(|episode| episode.length as f32)
+        .sum::<f32>()
+        / n;
+    println!(
+        "{} episodes: mean reward {mean_reward:.2} +/- {std_reward:.2}, mean length {mean_length:.1}",
+        episodes.len()
+    );
+    Ok(())
+}
+
+fn tune(args: TuneArgs) -> Result<()> {
+    let config = ExperimentConfig::from_file(&args.config)?;
+    let space = SearchSpace::from_file(&args.space)?;
+    let strategy = if args.grid {
+        SearchStrategy::Grid
+    } else {
+        SearchStrategy::Random {
+            n_trials: args.trials,
+        }
+    };
+    if args.jobs == 0 {
+        bail!("--jobs must be at least 1");
+    }
+    let pruner = args
+        .prune_min_steps
+        .map(|min_steps| SuccessiveHalving::new(min_steps, args.reduction_factor))
+        .transpose()
+        .context("invalid --prune-min-steps or --reduction-factor")?;
+    let study_dir = args.study_dir.clone().unwrap_or_else(|| {
+        let name = args.config.file_stem().unwrap_or("experiment".as_ref());
+        Path::new(DEFAULT_RUNS_DIR).join(format!("{}-study", name.to_string_lossy()))
+    });
+    fs::create_dir_all(&study_dir)
+        .with_context(|| format!("failed to create {}", study_dir.display()))?;
+    let mut study = Study::new(space, strategy)
+        .with_storage(study_dir.join(STUDY_FILE))
+        .with_n_jobs(args.jobs)
+        .with_seed(args.seed);
+    if let Some(pruner) = pruner {
+        study = study.with_pruner(pruner);
+    }
+    let executable = std::env::current_exe()?;
+    println!("Tuning in {}", study_dir.display());
+    let trials = study.optimize_processes(|trial| {
+        // Rejects hyperparameters the config cannot take before starting a
+        // process.
+        trial.apply(config.clone())?;
+        let run_dir = study_dir.join(format!("trial_{}", trial.number()));
+        let metrics_csv = run_dir.join(METRICS_FILE);
+        // Rewards left behind by an interrupted attempt must not be reported.
+        if metrics_csv.exists() {
+            fs::remove_file(&metrics_csv)?;
+        }
+        let mut command = process::Command::new(&executable);
+        command
+            .arg("train")
+            .arg(&args.config)
+            .arg("--run-dir")
+            .arg(&run_dir)
+            .arg("--quiet")
+            .stdout(Stdio::null());
+        // Pruning and ranking need evaluation rewards.
+        if config.evaluator.is_none() {
+            command.args(["--set", "evaluator={}"]);
+        }
+        for (name, value) in trial.params() {
+            command.arg("--set").arg(format!("{name}={value}"));
+        }
+        println!("Starting trial {}", trial.number());
+        Ok(TrialProcess {
+            command,
+            metrics_csv,
+        })
+    })?;
+    for trial in &trials {
+        let value = trial
+            .value
+            .map_or_else(|| "-".to_owned(), |value| format!("{value:.2}"));
+        let params = trial
+            .params
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!(
+            "trial {:>3} {:<9} {value:>10} {params}",
+            trial.number,
+            format!("{:?}", trial.state).to_lowercase()
+        );
+        if let (TrialState::Failed, Some(error)) = (trial.state, &trial.error) {
+            println!("    {error}");
+        }
+    }
+    let best = best_trial(&trials).context("no trial completed")?;
+    println!(
+        "Best trial {} with reward {:.2}, trained in {}",
+        best.number,
+        best.value.unwrap_or_default(),
+        study_dir.join(format!("trial_{}", best.number)).display()
+    );
+    Ok(())
+}
+
+fn export(policy: PolicyArgs, output: PathBuf, yaml: bool) -> Result<()> {
+    let (mut config, policy_path) = policy.resolve()?;
+    fs::create_dir_all(&output)
+        .with_context(|| format!("failed to create {}", output.display()))?;
+    fs::copy(&policy_path, output.join(POLICY_FILE))
+        .with_context(|| format!("failed to copy policy {}", policy_path.display()))?;
+    // The exported config should not send a new training run into the
+    // original run directory.
+    config.run_dir = None;
+    let config_file = if yaml {
+        CONFIG_FILES[1]
+    } else {
+        CONFIG_FILES[0]
+    };
+    config.save(output.join(config_file))?;
+    println!("Exported {} to {}", policy_path.display(), output.display());
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Train {
+            config,
+            overrides,
+            run_dir,
+            seed,
+            quiet,
+        } => train(config, overrides, run_dir, seed, quiet),
+        Command::Evaluate {
+            policy,
+            n_episodes,
+            n_envs,
+        } => evaluate(policy, n_episodes, n_envs, false),
+        Command::Enjoy { policy, n_episodes } => evaluate(policy, n_episodes, 1, true),
+        Command::Tune(args) => tune(args),
+        Command::Export {
+            policy,
+            output,
+            yaml,
+        } => export(policy, output, yaml),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use anyhow::Result;
+    use clap::Parser;
+    use r2l_api::{Env, EnvDescription, ExperimentConfig, Snapshot, Space, TensorData};
+
+    use crate::{
+        CONFIG_FILES, Cli, Command, POLICY_FILE, PolicyArgs, parse_override, train_and_save,
+    };
+
+    // Observations are one-hot positions on a line of four cells; the episode
+    // terminates at the last cell.
+    struct LineEnv {
+        position: usize,
+    }
+
+    impl LineEnv {
+        const SIZE: usize = 4;
+
+        fn observation(&self) -> TensorData {
+            let mut values = vec![0.; Self::SIZE];
+            values[self.position] = 1.;
+            TensorData::from_vec(values)
+        }
+    }
+
+    impl Env for LineEnv {
+        type Tensor = TensorData;
+
+        fn reset(&mut self, _seed: u64) -> Result<TensorData> {
+            self.position = 0;
+            Ok(self.observation())
+        }
+
+        fn step(&mut self, action: TensorData) -> Result<Snapshot<TensorData>> {
+            if action.data[1] == 1. {
+                self.position += 1;
+            }
+            let terminated = self.position == Self::SIZE - 1;
+            Ok(Snapshot::new(self.observation(), 1., terminated, false))
+        }
+
+        fn env_description(&self) -> EnvDescription<TensorData> {
+            EnvDescription::new(Space::Discrete(Self::SIZE), Space::Discrete(2))
+        }
+    }
+
+    // A small PPO run on the line environment.
+    fn line_config(backend: &str, run_dir: &Path) -> Result<ExperimentConfig> {
+        ExperimentConfig::from_toml_str(&format!(
+            r#"
+seed = 0
+backend = "{backend}"
+run_dir = "{}"
+
+[env]
+type = "native"
+name = "line"
+
+[sampler]
+n_envs = 1
+rollout_bound = {{ steps = 16 }}
+
+[training]
+budget = {{ total_steps = 32 }}
+
+[algorithm]
+name = "ppo"
+log_progress = false
+
+[network]
+policy_hidden_layers = [8]
+value_hidden_layers = [8]
+activation = "tanh"
+"#,
+            run_dir.display()
+        ))
+    }
+
+    fn train_then_evaluate(backend: &str) -> Result<()> {
+        let run_dir =
+            std::env::temp_dir().join(format!("r2l-cli-smoke-{backend}-{}", std::process::id()));
+        let config = line_config(backend, &run_dir)?;
+        let mut algorithm = config.build_with_env(|| Ok(LineEnv { position: 0 }))?;
+        train_and_save(algorithm.as_mut(), &run_dir)?;
+        let policy = PolicyArgs {
+            run: run_dir.clone(),
+            best: false,
+            config: None,
+        };
+        let (saved, policy) = policy.load()?;
+        let episodes = policy.run_episodes(|| Ok(LineEnv { position: 0 }), 2, 1)?;
+        std::fs::remove_dir_all(&run_dir)?;
+        assert_eq!(saved, config.resolved()?);
+        assert_eq!(episodes.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn undecodable_policies_are_errors() -> Result<()> {
+        let run_dir = std::env::temp_dir().join(format!("r2l-cli-corrupt-{}", std::process::id()));
+        std::fs::create_dir_all(&run_dir)?;
+        line_config("candle", &run_dir)?.save(run_dir.join(CONFIG_FILES[0]))?;
+        std::fs::write(run_dir.join(POLICY_FILE), b"not a policy")?;
+        let policy = PolicyArgs {
+            run: run_dir.clone(),
+            best: false,
+            config: None,
+        };
+        let error = policy.load().err();
+        std::fs::remove_dir_all(&run_dir)?;
+        assert!(error.is_some_and(|error| error.to_string().contains("failed to decode policy")));
+        Ok(())
+    }
+
+    #[test]
+    fn candle_policies_are_trained_then_evaluated() -> Result<()> {
+        train_then_evaluate("candle")
+    }
+
+    #[test]
+    fn burn_policies_are_trained_then_evaluated() -> Result<()> {
+        train_then_evaluate("burn")
+    }
+
+    #[test]
+    fn overrides_are_split_on_the_first_equals_sign() {
+        assert_eq!(
+            parse_override("algorithm.gamma=0.98"),
+            Ok(("algorithm.gamma".into(), "0.98".into()))
+        );
+        assert_eq!(
+            parse_override("env.kwargs.mode=a=b"),
+            Ok(("env.kwargs.mode".into(), "a=b".into()))
+        );
+        assert!(parse_override("algorithm.gamma").is_err());
+        assert!(parse_override("=0.98").is_err());
+    }
+
+    #[test]
+    fn train_accepts_repeated_overrides() {
+        let cli = Cli::try_parse_from([
+            "r2l",
+            "train",
+            "ppo.toml",
+            "--set",
+            "algorithm.gamma=0.98",
+            "-s",
+            "sampler.n_envs=8",
+        ])
+        .unwrap();
+        let Command::Train { overrides, .. } = cli.command else {
+            panic!("expected the train subcommand");
+        };
+        assert_eq!(overrides.len(), 2);
+    }
+
+    #[test]
+    fn tune_rejects_trials_with_grid_search() {
+        let args = ["r2l", "tune", "ppo.toml", "--space", "space.toml", "--grid"];
+        let cli = Cli::try_parse_from(args).unwrap();
+        let Command::Tune(tune) = cli.command else {
+            panic!("expected the tune subcommand");
+        };
+        assert!(tune.grid);
+        assert!(Cli::try_parse_from(args.into_iter().chain(["--trials", "5"])).is_err());
+    }
+}
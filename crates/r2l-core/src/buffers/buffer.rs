@@ -120,6 +120,10 @@ impl<T: R2lTensor> TrajectoryBuffer<T> {
         self.states.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
     pub fn terminated(&self) -> &[bool] {
         &self.terminated
     }
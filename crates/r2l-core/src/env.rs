@@ -1,8 +1,9 @@
 use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
+use rand::{Rng, RngExt};
 
-use crate::tensor::R2lTensor;
+use crate::{rng::with_rng, tensor::R2lTensor};
 
 /// Description of an observation or action space.
 #[derive(Debug, Clone)]
@@ -59,6 +60,163 @@ impl<T: R2lTensor> Space<T> {
             Self::Dict(spaces) => spaces.values().map(Self::size).sum(),
         }
     }
+
+    /// Returns the shape of tensors encoding values of this space.
+    ///
+    /// `Discrete` values are one-hot vectors, and `Tuple` and `Dict` values are
+    /// the flattened concatenation of their children.
+    pub fn tensor_shape(&self) -> Vec<usize> {
+        match self {
+            Self::Discrete(size) => vec![*size],
+            Self::Box { shape, .. }
+            | Self::MultiDiscrete { shape, .. }
+            | Self::MultiBinary { shape } => shape.clone(),
+            Self::Tuple(_) | Self::Dict(_) => vec![self.size()],
+        }
+    }
+
+    /// Samples a random value using the shared [`rng`](crate::rng) stream.
+    ///
+    /// See [`sample_with`](Self::sample_with) for the sampling distributions.
+    pub fn sample(&self) -> Result<T> {
+        with_rng(|rng| self.sample_with(rng))
+    }
+
+    /// Samples a random value in r2l's tensor encoding.
+    ///
+    /// - `Discrete` values are one-hot vectors.
+    /// - `MultiDiscrete` values hold one category index per dimension.
+    /// - `MultiBinary` values are zeros and ones.
+    /// - `Box` dimensions are sampled uniformly when bounded on both sides,
+    ///   from a shifted exponential when bounded on one side, and from a
+    ///   standard normal when unbounded, as in Gymnasium.
+    /// - `Tuple` and `Dict` values concatenate the samples of their children.
+    ///
+    /// Returns an error when a `Discrete` or `MultiDiscrete` space has no
+    /// categories to sample from.
+    pub fn sample_with<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<T> {
+        Ok(T::from_vec_and_shape(
+            self.sample_values(rng)?,
+            self.tensor_shape(),
+        ))
+    }
+
+    fn sample_values<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Vec<f32>> {
+        let values = match self {
+            Self::Discrete(0) => bail!("cannot sample from an empty discrete space"),
+            Self::Discrete(size) => {
+                let mut values = vec![0.; *size];
+                values[rng.random_range(0..*size)] = 1.;
+                values
+            }
+            Self::Box { min, max, shape } => {
+                let size: usize = shape.iter().product();
+                let bound = |bound: &Option<T>, fill: f32| {
+                    bound
+                        .as_ref()
+                        .map_or_else(|| vec![fill; size], |bound| bound.to_vec())
+                };
+                bound(min, f32::NEG_INFINITY)
+                    .into_iter()
+                    .zip(bound(max, f32::INFINITY))
+                    .map(|(low, high)| match (low.is_finite(), high.is_finite()) {
+                        (true, true) if low < high => rng.random_range(low..=high),
+                        (true, true) => low,
+                        (true, false) => low + sample_exponential(rng),
+                        (false, true) => high - sample_exponential(rng),
+                        (false, false) => sample_normal(rng),
+                    })
+                    .collect()
+            }
+            Self::MultiDiscrete { nvec, .. } => {
+                let nvec = nvec.to_vec();
+                if let Some(n) = nvec.iter().find(|n| n.is_nan() || **n < 1.) {
+                    bail!("cannot sample a multi-discrete dimension with {n} categories");
+                }
+                nvec.into_iter()
+                    .map(|n| rng.random_range(0..n as usize) as f32)
+                    .collect()
+            }
+            Self::MultiBinary { shape } => (0..shape.iter().product())
+                .map(|_: usize| if rng.random::<bool>() { 1. } else { 0. })
+                .collect(),
+            Self::Tuple(spaces) => sample_children(spaces.iter(), rng)?,
+            Self::Dict(spaces) => sample_children(spaces.values(), rng)?,
+        };
+        Ok(values)
+    }
+
+    /// Returns `true` when `value` is a valid tensor encoding of a value of this space.
+    ///
+    /// The tensor shape must match [`tensor_shape`](Self::tensor_shape), and the
+    /// values must satisfy the constraints of the space, e.g. `Box` bounds or
+    /// one-hot encoding for `Discrete`.
+    pub fn contains(&self, value: &T) -> bool {
+        let (data, shape) = value.to_vec_and_shape();
+        shape == self.tensor_shape() && self.contains_values(&data)
+    }
+
+    fn contains_values(&self, data: &[f32]) -> bool {
+        if data.iter().any(|value| value.is_nan()) {
+            return false;
+        }
+        match self {
+            Self::Discrete(_) => {
+                data.iter().filter(|value| **value == 1.).count() == 1
+                    && data.iter().all(|value| *value == 0. || *value == 1.)
+            }
+            Self::Box { min, max, .. } => {
+                let within = |bound: &Option<T>, ok: fn(f32, f32) -> bool| {
+                    bound
+                        .as_ref()
+                        .is_none_or(|bound| data.iter().zip(bound.to_vec()).all(|(v, b)| ok(*v, b)))
+                };
+                within(min, |value, min| value >= min) && within(max, |value, max| value <= max)
+            }
+            Self::MultiDiscrete { nvec, .. } => data
+                .iter()
+                .zip(nvec.to_vec())
+                .all(|(value, n)| value.fract() == 0. && *value >= 0. && *value < n),
+            Self::MultiBinary { .. } => data.iter().all(|value| *value == 0. || *value == 1.),
+            Self::Tuple(spaces) => contains_children(spaces.iter(), data),
+            Self::Dict(spaces) => contains_children(spaces.values(), data),
+        }
+    }
+}
+
+fn sample_children<'a, T: R2lTensor, R: Rng + ?Sized>(
+    spaces: impl Iterator<Item = &'a Space<T>>,
+    rng: &mut R,
+) -> Result<Vec<f32>> {
+    let mut values = vec![];
+    for space in spaces {
+        values.extend(space.sample_values(rng)?);
+    }
+    Ok(values)
+}
+
+fn contains_children<'a, T: R2lTensor>(
+    mut spaces: impl Iterator<Item = &'a Space<T>>,
+    data: &[f32],
+) -> bool {
+    let mut offset = 0;
+    spaces.all(|space| {
+        let end = offset + space.size();
+        let contained = space.contains_values(&data[offset..end]);
+        offset = end;
+        contained
+    })
+}
+
+fn sample_exponential<R: Rng + ?Sized>(rng: &mut R) -> f32 {
+    -(1. - rng.random::<f32>()).ln()
+}
+
+// Box-Muller transform
+fn sample_normal<R: Rng + ?Sized>(rng: &mut R) -> f32 {
+    let u1 = 1. - rng.random::<f32>();
+    let u2 = rng.random::<f32>();
+    (-2. * u1.ln()).sqrt() * (2. * std::f32::consts::PI * u2).cos()
 }
 
 /// Observation and action space metadata for an environment.
@@ -152,6 +310,30 @@ pub trait EnvBuilder: Sync + Send + 'static {
 }
 // ANCHOR_END: env_builder
 
+/// Batched environment interface that steps several environment slots at once.
+///
+/// Vector environments are an alternative to building one [`Env`] per sampler
+/// worker when the backend can step many environments in a single call, such
+/// as Gymnasium's vector environments. Slots auto-reset: when a slot finishes
+/// an episode, its snapshot reports the terminal flags of the finished episode,
+/// and its `state` is the first observation of the next episode. This matches
+/// the `next_state` stored by the samplers for single environments.
+pub trait VectorEnv {
+    /// Tensor type used for observations and actions.
+    type Tensor: R2lTensor;
+
+    /// Returns the number of environment slots.
+    fn num_envs(&self) -> usize;
+    /// Resets every slot and returns their initial observations.
+    ///
+    /// `seeds` holds one seed per slot.
+    fn reset(&mut self, seeds: &[u64]) -> Result<Vec<Self::Tensor>>;
+    /// Applies one action per slot and returns one snapshot per slot.
+    fn step(&mut self, actions: Vec<Self::Tensor>) -> Result<Vec<Snapshot<Self::Tensor>>>;
+    /// Returns the observation/action space metadata of a single slot.
+    fn env_description(&self) -> EnvDescription<Self::Tensor>;
+}
+
 pub type TensorOfEnvBuilder<EB> = <<EB as EnvBuilder>::Env as Env>::Tensor;
 
 impl<E: Env, F: Sync + Send + 'static> EnvBuilder for F
@@ -237,3 +419,76 @@ pub fn action_ranges(nvec: &[usize]) -> impl Iterator<Item = (usize, usize)> + '
         Some((start, *choices))
     })
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use anyhow::Result;
+
+    use super::Space;
+    use crate::{
+        rng::set_seed,
+        tensor::{R2lTensor, TensorData},
+    };
+
+    #[test]
+    fn samples_are_contained_in_their_space() -> Result<()> {
+        set_seed(7);
+        let spaces = [
+            Space::Discrete(3),
+            Space::Box {
+                min: Some(TensorData::from_vec(vec![-1., f32::NEG_INFINITY])),
+                max: Some(TensorData::from_vec(vec![1., f32::INFINITY])),
+                shape: vec![2],
+            },
+            Space::MultiDiscrete {
+                nvec: TensorData::from_vec(vec![2., 5.]),
+                shape: vec![2],
+            },
+            Space::MultiBinary { shape: vec![2, 2] },
+            Space::Tuple(vec![
+                Space::Discrete(2),
+                Space::MultiBinary { shape: vec![3] },
+            ]),
+            Space::Dict(BTreeMap::from([(
+                "position".to_string(),
+                Space::Discrete(4),
+            )])),
+        ];
+        for space in &spaces {
+            for _ in 0..100 {
+                let sample = space.sample()?;
+                assert!(
+                    space.contains(&sample),
+                    "{space:?} does not contain {sample:?}"
+                );
+                assert_eq!(sample.to_shape(), space.tensor_shape());
+            }
+        }
+
+        let discrete = Space::<TensorData>::Discrete(3);
+        assert!(!discrete.contains(&TensorData::from_vec(vec![1., 1., 0.])));
+        assert!(!discrete.contains(&TensorData::from_vec(vec![0., 1.])));
+        let multi_discrete = &spaces[2];
+        assert!(multi_discrete.contains(&TensorData::from_vec(vec![1., 4.])));
+        assert!(!multi_discrete.contains(&TensorData::from_vec(vec![2., 0.])));
+        assert!(!spaces[1].contains(&TensorData::from_vec(vec![1.5, 0.])));
+        Ok(())
+    }
+
+    #[test]
+    fn empty_discrete_spaces_cannot_be_sampled() {
+        let empty = [
+            Space::<TensorData>::Discrete(0),
+            Space::MultiDiscrete {
+                nvec: TensorData::from_vec(vec![3., 0.]),
+                shape: vec![2],
+            },
+            Space::Tuple(vec![Space::Discrete(2), Space::Discrete(0)]),
+        ];
+        for space in &empty {
+            assert!(space.sample().is_err(), "{space:?} was sampled");
+        }
+    }
+}
@@ -0,0 +1,688 @@
+//! Conformance checks for [`Env`] implementations.
+//!
+//! Environments that disagree with their own [`EnvDescription`] usually fail
+//! far away from the bug, as a tensor-shape error inside a policy forward pass.
+//! [`check_env`] builds environments from an [`EnvBuilder`], drives them
+//! through resets and steps, and reports every inconsistency it finds:
+//!
+//! ```ignore
+//! use r2l_core::env_checker::check_env;
+//!
+//! let report = check_env(&|| Ok(MyEnv))?;
+//! report.into_result()?;
+//! ```
+
+use std::{collections::HashSet, fmt};
+
+use anyhow::Result;
+
+use crate::{
+    env::{Env, EnvBuilder, Snapshot, Space},
+    tensor::R2lTensor,
+};
+
+/// A single inconsistency found by [`EnvChecker`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvViolation {
+    /// Where the violation was found, e.g. `reset` or `step 3`.
+    pub context: String,
+    /// Description of the violation.
+    pub message: String,
+}
+
+impl fmt::Display for EnvViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, self.message)
+    }
+}
+
+/// Outcome of an environment check.
+#[derive(Debug, Clone, Default)]
+pub struct EnvCheckReport {
+    /// Every violation found, in the order the checks ran.
+    pub violations: Vec<EnvViolation>,
+    /// Checks that could not run, e.g. because no episode ended. Warnings do
+    /// not fail the report.
+    pub warnings: Vec<EnvViolation>,
+    /// Number of environment steps taken by the checker.
+    pub steps: usize,
+    /// Number of episodes that ended during the step checks.
+    pub episodes: usize,
+}
+
+impl EnvCheckReport {
+    /// Returns `true` when no violation was found.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Converts the report into an error listing every violation.
+    pub fn into_result(self) -> Result<()> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("{self}"))
+        }
+    }
+
+    fn push(&mut self, context: impl Into<String>, message: impl Into<String>) {
+        self.violations.push(EnvViolation {
+            context: context.into(),
+            message: message.into(),
+        });
+    }
+
+    fn warn(&mut self, context: impl Into<String>, message: impl Into<String>) {
+        self.warnings.push(EnvViolation {
+            context: context.into(),
+            message: message.into(),
+        });
+    }
+}
+
+impl fmt::Display for EnvCheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            write!(f, "environment passed all checks ({} steps)", self.steps)?;
+        } else {
+            write!(f, "environment failed {} check(s):", self.violations.len())?;
+            for violation in &self.violations {
+                write!(f, "\n  - {violation}")?;
+            }
+        }
+        for warning in &self.warnings {
+            write!(f, "\n  warning: {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Configurable environment conformance checker.
+///
+/// The checker validates:
+///
+/// - the observation and action spaces themselves, e.g. `Box` bound shapes and
+///   `MultiDiscrete` category counts;
+/// - every observation returned by `reset` and `step` against the observation
+///   space, including `Box` bounds, one-hot `Discrete` encoding, and
+///   `MultiDiscrete` ranges;
+/// - that rewards are finite;
+/// - that `reset` with the same seed is reproducible, both on one environment
+///   and across two freshly built environments;
+/// - that stepping after an episode ends without a reset either fails or
+///   keeps reporting the episode as done;
+/// - that an environment can be reset and stepped again after an episode ends.
+///
+/// When no episode ends within the step budget, the checks that need one are
+/// skipped and the report carries a warning instead.
+///
+/// Actions are generated deterministically from the action space, so the
+/// checker needs no random number generator and is itself reproducible.
+#[derive(Debug, Clone)]
+pub struct EnvChecker {
+    seed: u64,
+    max_steps: usize,
+    determinism_steps: usize,
+}
+
+impl Default for EnvChecker {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            max_steps: 1000,
+            determinism_steps: 10,
+        }
+    }
+}
+
+impl EnvChecker {
+    /// Creates a checker with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the seed passed to `reset`.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the maximum number of steps taken while looking for the end of an episode.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Sets the number of steps compared between two identically seeded environments.
+    pub fn with_determinism_steps(mut self, determinism_steps: usize) -> Self {
+        self.determinism_steps = determinism_steps;
+        self
+    }
+
+    /// Runs all checks against environments produced by `builder`.
+    ///
+    /// Violations are collected into the returned report. An error is only
+    /// returned when the builder itself fails.
+    pub fn check<EB: EnvBuilder>(&self, builder: &EB) -> Result<EnvCheckReport> {
+        let mut report = EnvCheckReport::default();
+        let mut env = builder.build_env()?;
+        let description = env.env_description();
+        let observation_space = description.observation_space;
+        let action_space = description.action_space;
+        for message in space_violations(&observation_space) {
+            report.push("observation space", message);
+        }
+        for message in space_violations(&action_space) {
+            report.push("action space", message);
+        }
+
+        // reset
+        let first = match env.reset(self.seed) {
+            Ok(observation) => observation,
+            Err(err) => {
+                report.push("reset", format!("failed: {err}"));
+                return Ok(report);
+            }
+        };
+        for message in observation_violations(&observation_space, &first) {
+            report.push("reset", message);
+        }
+        match env.reset(self.seed) {
+            Ok(second) if !same_tensor(&first, &second) => report.push(
+                "reset",
+                format!(
+                    "resetting twice with seed {} gave different observations",
+                    self.seed
+                ),
+            ),
+            Ok(_) => {}
+            Err(err) => report.push("reset", format!("second reset failed: {err}")),
+        }
+
+        // determinism across freshly built environments
+        self.check_determinism(builder, &action_space, &mut report)?;
+
+        // step until the end of an episode, then make sure the env recovers
+        if let Err(err) = env.reset(self.seed) {
+            report.push("reset", format!("failed: {err}"));
+            return Ok(report);
+        }
+        let mut reported = HashSet::new();
+        for step in 0..self.max_steps {
+            let context = format!("step {step}");
+            let action = probe_action(&action_space, step);
+            let snapshot = match env.step(action) {
+                Ok(snapshot) => snapshot,
+                Err(err) => {
+                    report.push(context, format!("failed: {err}"));
+                    return Ok(report);
+                }
+            };
+            report.steps += 1;
+            // the same problem usually repeats on every step, only report it once
+            for message in snapshot_violations(&observation_space, &snapshot) {
+                if reported.insert(message.clone()) {
+                    report.push(context.clone(), message);
+                }
+            }
+            if snapshot.done() {
+                report.episodes += 1;
+                self.check_after_episode(&mut env, &observation_space, &action_space, &mut report);
+                return Ok(report);
+            }
+        }
+        report.warn(
+            format!("step {}", self.max_steps),
+            format!(
+                "no episode ended within {} steps, the checks after an episode end were skipped",
+                self.max_steps
+            ),
+        );
+        Ok(report)
+    }
+
+    fn check_determinism<EB: EnvBuilder>(
+        &self,
+        builder: &EB,
+        action_space: &Space<<EB::Env as Env>::Tensor>,
+        report: &mut EnvCheckReport,
+    ) -> Result<()> {
+        let mut left = builder.build_env()?;
+        let mut right = builder.build_env()?;
+        let (Ok(left_obs), Ok(right_obs)) = (left.reset(self.seed), right.reset(self.seed)) else {
+            report.push("determinism", "reset failed on a freshly built environment");
+            return Ok(());
+        };
+        if !same_tensor(&left_obs, &right_obs) {
+            report.push(
+                "determinism",
+                format!(
+                    "two environments reset with seed {} gave different observations",
+                    self.seed
+                ),
+            );
+            return Ok(());
+        }
+        for step in 0..self.determinism_steps {
+            let (Ok(l), Ok(r)) = (
+                left.step(probe_action(action_space, step)),
+                right.step(probe_action(action_space, step)),
+            ) else {
+                // step failures are reported by the step checks
+                return Ok(());
+            };
+            let same = same_tensor(&l.state, &r.state)
+                && l.reward.to_bits() == r.reward.to_bits()
+                && l.terminated == r.terminated
+                && l.truncated == r.truncated;
+            if !same {
+                report.push(
+                    "determinism",
+                    format!(
+                        "two environments reset with seed {} diverged at step {step}",
+                        self.seed
+                    ),
+                );
+                return Ok(());
+            }
+            if l.done() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_after_episode<E: Env>(
+        &self,
+        env: &mut E,
+        observation_space: &Space<E::Tensor>,
+        action_space: &Space<E::Tensor>,
+        report: &mut EnvCheckReport,
+    ) {
+        let context = "after episode end";
+        // a step without a reset has to be rejected or keep the episode over
+        if let Ok(snapshot) = env.step(probe_action(action_space, 1)) {
+            report.steps += 1;
+            if !snapshot.done() {
+                report.push(
+                    context,
+                    "step without a reset continued the episode instead of failing or \
+                     reporting it as done",
+                );
+            }
+        }
+        match env.reset(self.seed.wrapping_add(1)) {
+            Ok(observation) => {
+                for message in observation_violations(observation_space, &observation) {
+                    report.push(context, format!("reset: {message}"));
+                }
+            }
+            Err(err) => {
+                report.push(context, format!("reset failed: {err}"));
+                return;
+            }
+        }
+        match env.step(probe_action(action_space, 0)) {
+            Ok(snapshot) => {
+                report.steps += 1;
+                for message in snapshot_violations(observation_space, &snapshot) {
+                    report.push(context, format!("step: {message}"));
+                }
+            }
+            Err(err) => report.push(context, format!("step after reset failed: {err}")),
+        }
+    }
+}
+
+/// Checks environments produced by `builder` with the default [`EnvChecker`].
+pub fn check_env<EB: EnvBuilder>(builder: &EB) -> Result<EnvCheckReport> {
+    EnvChecker::new().check(builder)
+}
+
+fn same_tensor<T: R2lTensor>(left: &T, right: &T) -> bool {
+    let (left_data, left_shape) = left.to_vec_and_shape();
+    let (right_data, right_shape) = right.to_vec_and_shape();
+    left_shape == right_shape
+        && left_data
+            .iter()
+            .zip(&right_data)
+            .all(|(l, r)| l.to_bits() == r.to_bits())
+}
+
+fn space_violations<T: R2lTensor>(space: &Space<T>) -> Vec<String> {
+    let mut violations = vec![];
+    collect_space_violations(space, "", &mut violations);
+    violations
+}
+
+fn collect_space_violations<T: R2lTensor>(space: &Space<T>, path: &str, out: &mut Vec<String>) {
+    match space {
+        Space::Discrete(0) => out.push(format!("{path}Discrete space has no values")),
+        Space::Discrete(_) | Space::MultiBinary { .. } => {}
+        Space::Box { min, max, shape } => {
+            let size: usize = shape.iter().product();
+            for (name, bound) in [("min", min), ("max", max)] {
+                if let Some(bound) = bound
+                    && bound.size() != size
+                {
+                    out.push(format!(
+                        "{path}Box {name} bound has {} values, expected {size} for shape {shape:?}",
+                        bound.size()
+                    ));
+                }
+            }
+            if let (Some(min), Some(max)) = (min, max)
+                && min.size() == max.size()
+                && min.to_vec().iter().zip(max.to_vec()).any(|(l, h)| *l > h)
+            {
+                out.push(format!("{path}Box min bound exceeds max bound"));
+            }
+        }
+        Space::MultiDiscrete { nvec, shape } => {
+            let size: usize = shape.iter().product();
+            let nvec = nvec.to_vec();
+            if nvec.len() != size {
+                out.push(format!(
+                    "{path}MultiDiscrete nvec has {} values, expected {size} for shape {shape:?}",
+                    nvec.len()
+                ));
+            }
+            if nvec.iter().any(|n| *n < 1. || n.fract() != 0.) {
+                out.push(format!(
+                    "{path}MultiDiscrete nvec must contain positive integers, got {nvec:?}"
+                ));
+            }
+        }
+        Space::Tuple(spaces) => {
+            for (idx, space) in spaces.iter().enumerate() {
+                collect_space_violations(space, &format!("{path}[{idx}] "), out);
+            }
+        }
+        Space::Dict(spaces) => {
+            for (key, space) in spaces {
+                collect_space_violations(space, &format!("{path}[{key:?}] "), out);
+            }
+        }
+    }
+}
+
+fn snapshot_violations<T: R2lTensor>(space: &Space<T>, snapshot: &Snapshot<T>) -> Vec<String> {
+    let mut violations = observation_violations(space, &snapshot.state);
+    if !snapshot.reward.is_finite() {
+        violations.push(format!("reward is not finite: {}", snapshot.reward));
+    }
+    violations
+}
+
+fn observation_violations<T: R2lTensor>(space: &Space<T>, observation: &T) -> Vec<String> {
+    let (data, shape) = observation.to_vec_and_shape();
+    let expected = space.tensor_shape();
+    if shape != expected {
+        return vec![format!(
+            "observation has shape {shape:?}, expected {expected:?}"
+        )];
+    }
+    let mut violations = vec![];
+    collect_value_violations(space, &data, "", &mut violations);
+    violations
+}
+
+fn collect_value_violations<T: R2lTensor>(
+    space: &Space<T>,
+    data: &[f32],
+    path: &str,
+    out: &mut Vec<String>,
+) {
+    if let Some(value) = data.iter().find(|value| value.is_nan()) {
+        out.push(format!("{path}observation contains {value}"));
+        return;
+    }
+    match space {
+        Space::Discrete(_) => {
+            let ones = data.iter().filter(|value| **value == 1.).count();
+            let zeros = data.iter().filter(|value| **value == 0.).count();
+            if ones != 1 || zeros != data.len() - 1 {
+                out.push(format!(
+                    "{path}Discrete observation is not one-hot encoded: {data:?}"
+                ));
+            }
+        }
+        Space::Box { min, max, .. } => {
+            let below = min.as_ref().is_some_and(|min| {
+                min.size() == data.len() && data.iter().zip(min.to_vec()).any(|(v, l)| *v < l)
+            });
+            let above = max.as_ref().is_some_and(|max| {
+                max.size() == data.len() && data.iter().zip(max.to_vec()).any(|(v, h)| *v > h)
+            });
+            if below || above {
+                out.push(format!("{path}observation is outside the Box bounds"));
+            }
+        }
+        Space::MultiDiscrete { nvec, .. } => {
+            let nvec = nvec.to_vec();
+            let out_of_range = data
+                .iter()
+                .zip(&nvec)
+                .any(|(value, n)| value.fract() != 0. || *value < 0. || value >= n);
+            if out_of_range {
+                out.push(format!(
+                    "{path}MultiDiscrete observation {data:?} is outside the ranges {nvec:?}"
+                ));
+            }
+        }
+        Space::MultiBinary { .. } => {
+            if data.iter().any(|value| *value != 0. && *value != 1.) {
+                out.push(format!(
+                    "{path}MultiBinary observation is not binary: {data:?}"
+                ));
+            }
+        }
+        Space::Tuple(spaces) => collect_child_violations(
+            spaces
+                .iter()
+                .enumerate()
+                .map(|(idx, space)| (format!("{path}[{idx}] "), space)),
+            data,
+            out,
+        ),
+        Space::Dict(spaces) => collect_child_violations(
+            spaces
+                .iter()
+                .map(|(key, space)| (format!("{path}[{key:?}] "), space)),
+            data,
+            out,
+        ),
+    }
+}
+
+fn collect_child_violations<'a, T: R2lTensor>(
+    children: impl IntoIterator<Item = (String, &'a Space<T>)>,
+    data: &[f32],
+    out: &mut Vec<String>,
+) {
+    let mut offset = 0;
+    for (path, space) in children {
+        let end = offset + space.size();
+        collect_value_violations(space, &data[offset..end], &path, out);
+        offset = end;
+    }
+}
+
+// A valid action for `space`, varied by `step` so that all discrete choices get exercised.
+fn probe_action<T: R2lTensor>(space: &Space<T>, step: usize) -> T {
+    T::from_vec_and_shape(probe_action_values(space, step), space.tensor_shape())
+}
+
+fn probe_action_values<T: R2lTensor>(space: &Space<T>, step: usize) -> Vec<f32> {
+    match space {
+        Space::Discrete(size) => {
+            let mut values = vec![0.; *size];
+            if *size > 0 {
+                values[step % size] = 1.;
+            }
+            values
+        }
+        Space::Box { min, max, shape } => {
+            let size = shape.iter().product();
+            match (min, max) {
+                (Some(min), Some(max)) if min.size() == size && max.size() == size => {
+                    // walk through the box along a low discrepancy sequence
+                    let t = (step as f32 * 0.618_034).fract();
+                    min.to_vec()
+                        .into_iter()
+                        .zip(max.to_vec())
+                        .map(|(l, h)| {
+                            if l.is_finite() && h.is_finite() {
+                                l + t * (h - l)
+                            } else {
+                                0f32.clamp(l, h)
+                            }
+                        })
+                        .collect()
+                }
+                _ => vec![0.; size],
+            }
+        }
+        Space::MultiDiscrete { nvec, .. } => nvec
+            .to_vec()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, n)| ((step + idx) % (n.max(1.) as usize)) as f32)
+            .collect(),
+        Space::MultiBinary { shape } => (0..shape.iter().product())
+            .map(|idx: usize| ((step + idx) % 2) as f32)
+            .collect(),
+        Space::Tuple(spaces) => spaces
+            .iter()
+            .flat_map(|space| probe_action_values(space, step))
+            .collect(),
+        Space::Dict(spaces) => spaces
+            .values()
+            .flat_map(|space| probe_action_values(space, step))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::{EnvChecker, check_env};
+    use crate::{
+        env::{Env, EnvDescription, Snapshot, Space},
+        tensor::TensorData,
+    };
+
+    // Observations are one-hot positions on a line of `size` cells; the episode
+    // terminates at the last cell. `broken` makes every observation invalid, and
+    // `wraps` sends steps from the last cell back to the first one.
+    struct LineEnv {
+        position: usize,
+        size: usize,
+        broken: bool,
+        wraps: bool,
+    }
+
+    impl LineEnv {
+        fn new(size: usize) -> Self {
+            Self {
+                position: 0,
+                size,
+                broken: false,
+                wraps: false,
+            }
+        }
+    }
+
+    impl LineEnv {
+        fn observation(&self) -> TensorData {
+            let mut values = vec![0.; self.size];
+            values[self.position] = 1.;
+            if self.broken {
+                values.push(0.);
+                values[0] = 2.;
+            }
+            TensorData::from_vec(values)
+        }
+    }
+
+    impl Env for LineEnv {
+        type Tensor = TensorData;
+
+        fn reset(&mut self, seed: u64) -> Result<TensorData> {
+            self.position = seed as usize % 2;
+            Ok(self.observation())
+        }
+
+        fn step(&mut self, action: TensorData) -> Result<Snapshot<TensorData>> {
+            if self.wraps && self.position == self.size - 1 {
+                self.position = 0;
+            } else if action.data[1] == 1. {
+                self.position = (self.position + 1).min(self.size - 1);
+            }
+            let terminated = self.position == self.size - 1;
+            Ok(Snapshot::new(self.observation(), 1., terminated, false))
+        }
+
+        fn env_description(&self) -> EnvDescription<TensorData> {
+            EnvDescription::new(Space::Discrete(self.size), Space::Discrete(2))
+        }
+    }
+
+    #[test]
+    fn conforming_env_passes() -> Result<()> {
+        let report = check_env(&|| Ok(LineEnv::new(4)))?;
+        assert!(report.is_ok(), "{report}");
+        assert_eq!(report.episodes, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn violations_are_all_reported() -> Result<()> {
+        let report = check_env(&|| {
+            Ok(LineEnv {
+                broken: true,
+                ..LineEnv::new(4)
+            })
+        })?;
+        let contexts: Vec<_> = report
+            .violations
+            .iter()
+            .map(|violation| violation.context.as_str())
+            .collect();
+        assert!(contexts.contains(&"reset"));
+        assert!(contexts.contains(&"step 0"));
+        assert!(contexts.contains(&"after episode end"));
+        assert!(report.into_result().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn stepping_past_the_end_without_a_reset_is_reported() -> Result<()> {
+        let report = check_env(&|| {
+            Ok(LineEnv {
+                wraps: true,
+                ..LineEnv::new(4)
+            })
+        })?;
+        assert_eq!(report.violations.len(), 1, "{report}");
+        assert_eq!(report.violations[0].context, "after episode end");
+        Ok(())
+    }
+
+    #[test]
+    fn missing_episode_ends_are_warned_about() -> Result<()> {
+        let report = EnvChecker::new()
+            .with_max_steps(5)
+            .check(&|| Ok(LineEnv::new(100)))?;
+        assert_eq!(report.episodes, 0);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(
+            report
+                .to_string()
+                .contains("no episode ended within 5 steps")
+        );
+        assert!(report.into_result().is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+//! Completed-episode statistics shared by samplers, hooks, and evaluators.
+//!
+//! Samplers record every transition into a per-environment [`EpisodeTracker`]
+//! before any reward post-processing takes place. Finished episodes are
+//! published to an [`EpisodeMonitor`], a cheaply cloneable handle that
+//! reporters and evaluators can read from without inspecting rollout buffers.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Summary of a single completed episode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EpisodeStats {
+    /// Raw undiscounted return, accumulated before any reward normalization.
+    pub reward: f32,
+    /// Number of environment steps in the episode.
+    pub length: usize,
+    /// Wall-clock time since the episode started.
+    pub duration: Duration,
+}
+
+/// Accumulates the running episode of a single environment.
+#[derive(Debug, Clone)]
+pub struct EpisodeTracker {
+    reward: f32,
+    length: usize,
+    start: Instant,
+}
+
+impl Default for EpisodeTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EpisodeTracker {
+    /// Creates a tracker for an episode starting now.
+    pub fn new() -> Self {
+        Self {
+            reward: 0.,
+            length: 0,
+            start: Instant::now(),
+        }
+    }
+
+    /// Records one transition and returns the episode summary when `done` is set.
+    ///
+    /// The tracker restarts immediately after a completed episode, matching
+    /// the auto-reset behavior of the samplers.
+    pub fn record(&mut self, reward: f32, done: bool) -> Option<EpisodeStats> {
+        self.reward += reward;
+        self.length += 1;
+        if done {
+            let stats = EpisodeStats {
+                reward: self.reward,
+                length: self.length,
+                duration: self.start.elapsed(),
+            };
+            self.reset();
+            Some(stats)
+        } else {
+            None
+        }
+    }
+
+    /// Discards the running episode, e.g. after an explicit environment reset.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[derive(Debug, Default)]
+struct EpisodeHistory {
+    rollout: Vec<EpisodeStats>,
+    total_episodes: usize,
+    total_steps: usize,
+}
+
+/// Shared view of the episodes completed by a sampler.
+///
+/// Episodes are grouped by rollout: a sampler calls
+/// [`begin_rollout`](Self::begin_rollout) at the start of every collection
+/// pass, so [`rollout_episodes`](Self::rollout_episodes) only ever contains
+/// episodes that finished during the latest rollout. Episodes that span
+/// several rollouts are reported once, in the rollout in which they end.
+#[derive(Debug, Clone, Default)]
+pub struct EpisodeMonitor(Arc<Mutex<EpisodeHistory>>);
+
+impl EpisodeMonitor {
+    /// Creates an empty monitor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new rollout, forgetting the episodes of the previous one.
+    pub fn begin_rollout(&self) {
+        self.0.lock().unwrap().rollout.clear();
+    }
+
+    /// Publishes a completed episode.
+    pub fn push(&self, stats: EpisodeStats) {
+        let mut history = self.0.lock().unwrap();
+        history.total_episodes += 1;
+        history.total_steps += stats.length;
+        history.rollout.push(stats);
+    }
+
+    /// Returns the episodes completed during the latest rollout.
+    pub fn rollout_episodes(&self) -> Vec<EpisodeStats> {
+        self.0.lock().unwrap().rollout.clone()
+    }
+
+    /// Returns the mean return of the episodes completed during the latest rollout.
+    pub fn mean_reward(&self) -> Option<f32> {
+        let history = self.0.lock().unwrap();
+        mean_of(&history.rollout, |stats| stats.reward)
+    }
+
+    /// Returns the mean length of the episodes completed during the latest rollout.
+    pub fn mean_length(&self) -> Option<f32> {
+        let history = self.0.lock().unwrap();
+        mean_of(&history.rollout, |stats| stats.length as f32)
+    }
+
+    /// Returns the number of episodes completed since the monitor was created.
+    pub fn total_episodes(&self) -> usize {
+        self.0.lock().unwrap().total_episodes
+    }
+
+    /// Returns the number of steps in all episodes completed since the monitor was created.
+    pub fn total_steps(&self) -> usize {
+        self.0.lock().unwrap().total_steps
+    }
+}
+
+/// Returns the mean of `f` over `episodes`, or `None` when no episode completed.
+pub fn mean_of(episodes: &[EpisodeStats], f: impl Fn(&EpisodeStats) -> f32) -> Option<f32> {
+    if episodes.is_empty() {
+        None
+    } else {
+        Some(episodes.iter().map(f).sum::<f32>() / episodes.len() as f32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EpisodeMonitor, EpisodeTracker};
+
+    #[test]
+    fn tracker_reports_completed_episodes_only() {
+        let monitor = EpisodeMonitor::new();
+        let mut tracker = EpisodeTracker::new();
+        let transitions = [(1., false), (2., true), (3., false), (4., false)];
+        for (reward, done) in transitions {
+            if let Some(stats) = tracker.record(reward, done) {
+                monitor.push(stats);
+            }
+        }
+        let episodes = monitor.rollout_episodes();
+        assert_eq!(episodes.len(), 1);
+        assert_eq!(episodes[0].reward, 3.);
+        assert_eq!(episodes[0].length, 2);
+
+        // the partial episode carries over into the next rollout
+        monitor.begin_rollout();
+        assert_eq!(monitor.mean_reward(), None);
+        monitor.push(tracker.record(5., true).unwrap());
+        assert_eq!(monitor.mean_reward(), Some(12.));
+        assert_eq!(monitor.mean_length(), Some(3.));
+        assert_eq!(monitor.total_episodes(), 2);
+        assert_eq!(monitor.total_steps(), 5);
+    }
+}
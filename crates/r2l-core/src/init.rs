@@ -0,0 +1,269 @@
+//! Backend-independent weight initialization for feed-forward networks.
+
+use std::f64::consts::{PI, SQRT_2};
+
+use rand::{RngExt, rngs::StdRng};
+
+use crate::rng::with_rng;
+
+/// Role of a linear layer inside a policy or value network.
+///
+/// The role selects which gain of a [`WeightInit`] applies to the layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerRole {
+    /// A layer followed by an activation function.
+    Hidden,
+    /// The output layer of a policy network.
+    PolicyHead,
+    /// The output layer of a value network.
+    ValueHead,
+}
+
+/// Distribution the weights of linear layers are drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum InitScheme {
+    /// The backend's own default initialization. Gains are ignored.
+    #[default]
+    Default,
+    /// A (semi-)orthogonal matrix scaled by the gain.
+    Orthogonal,
+    /// Uniform on `±gain * sqrt(6 / (fan_in + fan_out))`.
+    XavierUniform,
+    /// Normal with standard deviation `gain * sqrt(2 / (fan_in + fan_out))`.
+    XavierNormal,
+    /// Uniform on `±gain * sqrt(3 / fan_in)`.
+    KaimingUniform,
+    /// Normal with standard deviation `gain / sqrt(fan_in)`.
+    KaimingNormal,
+}
+
+/// Weight initialization of policy and value networks.
+///
+/// Except for [`InitScheme::Default`], weights are drawn on the CPU from the
+/// seeded random stream in [`crate::rng`] and biases start at zero, so Burn and
+/// Candle networks start from the same parameters for the same seed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeightInit {
+    /// Distribution the weights are drawn from.
+    pub scheme: InitScheme,
+    /// Gain of hidden layers.
+    pub hidden_gain: f64,
+    /// Gain of the policy output layer.
+    pub policy_head_gain: f64,
+    /// Gain of the value output layer.
+    pub value_head_gain: f64,
+}
+
+impl Default for WeightInit {
+    fn default() -> Self {
+        Self::new(InitScheme::Default)
+    }
+}
+
+impl WeightInit {
+    /// Uses `scheme` with a gain of one for every layer.
+    pub fn new(scheme: InitScheme) -> Self {
+        Self {
+            scheme,
+            hidden_gain: 1.,
+            policy_head_gain: 1.,
+            value_head_gain: 1.,
+        }
+    }
+
+    /// Orthogonal initialization with the gains used by Stable-Baselines3:
+    /// `sqrt(2)` for hidden layers, `0.01` for the policy head and `1` for the
+    /// value head.
+    pub fn orthogonal() -> Self {
+        Self {
+            scheme: InitScheme::Orthogonal,
+            hidden_gain: SQRT_2,
+            policy_head_gain: 0.01,
+            value_head_gain: 1.,
+        }
+    }
+
+    /// Sets the gain of hidden layers.
+    pub fn with_hidden_gain(mut self, gain: f64) -> Self {
+        self.hidden_gain = gain;
+        self
+    }
+
+    /// Sets the gain of the policy output layer.
+    pub fn with_policy_head_gain(mut self, gain: f64) -> Self {
+        self.policy_head_gain = gain;
+        self
+    }
+
+    /// Sets the gain of the value output layer.
+    pub fn with_value_head_gain(mut self, gain: f64) -> Self {
+        self.value_head_gain = gain;
+        self
+    }
+
+    /// Returns the gain applied to a layer with the given role.
+    pub fn gain(&self, role: LayerRole) -> f64 {
+        match role {
+            LayerRole::Hidden => self.hidden_gain,
+            LayerRole::PolicyHead => self.policy_head_gain,
+            LayerRole::ValueHead => self.value_head_gain,
+        }
+    }
+
+    /// Draws the weights of a linear layer as a row-major
+    /// `[out_dim, in_dim]` matrix.
+    ///
+    /// Returns `None` for [`InitScheme::Default`], in which case the backend
+    /// initializes the layer itself.
+    pub fn weights(&self, role: LayerRole, in_dim: usize, out_dim: usize) -> Option<Vec<f32>> {
+        let gain = self.gain(role);
+        let fan_in = in_dim as f64;
+        let fan_out = out_dim as f64;
+        let len = in_dim * out_dim;
+        let weights = with_rng(|rng| match self.scheme {
+            InitScheme::Default => None,
+            InitScheme::Orthogonal => Some(orthogonal(rng, out_dim, in_dim, gain)),
+            InitScheme::XavierUniform => {
+                let bound = gain * (6. / (fan_in + fan_out)).sqrt();
+                Some(uniform(rng, len, bound))
+            }
+            InitScheme::XavierNormal => {
+                let std = gain * (2. / (fan_in + fan_out)).sqrt();
+                Some(normal(rng, len, std))
+            }
+            InitScheme::KaimingUniform => {
+                let bound = gain * (3. / fan_in).sqrt();
+                Some(uniform(rng, len, bound))
+            }
+            InitScheme::KaimingNormal => Some(normal(rng, len, gain / fan_in.sqrt())),
+        })?;
+        Some(weights.into_iter().map(|w| w as f32).collect())
+    }
+}
+
+fn uniform(rng: &mut StdRng, len: usize, bound: f64) -> Vec<f64> {
+    (0..len)
+        .map(|_| (rng.random::<f64>() * 2. - 1.) * bound)
+        .collect()
+}
+
+fn normal(rng: &mut StdRng, len: usize, std: f64) -> Vec<f64> {
+    (0..len).map(|_| standard_normal(rng) * std).collect()
+}
+
+// Box-Muller transform
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1 = 1. - rng.random::<f64>();
+    let u2 = rng.random::<f64>();
+    (-2. * u1.ln()).sqrt() * (2. * PI * u2).cos()
+}
+
+/// Draws a `rows x cols` matrix with orthonormal rows or columns, whichever
+/// are fewer, following PyTorch's `orthogonal_`.
+fn orthogonal(rng: &mut StdRng, rows: usize, cols: usize, gain: f64) -> Vec<f64> {
+    // Orthonormalize the columns of a tall gaussian matrix, transposing wide
+    // matrices. Gram-Schmidt yields a positive diagonal in R, which is the
+    // sign correction PyTorch applies after its QR decomposition.
+    let (tall_rows, tall_cols) = if rows < cols {
+        (cols, rows)
+    } else {
+        (rows, cols)
+    };
+    let mut columns: Vec<Vec<f64>> = (0..tall_cols).map(|_| normal(rng, tall_rows, 1.)).collect();
+    for col in 0..tall_cols {
+        let (done, rest) = columns.split_at_mut(col);
+        let current = &mut rest[0];
+        for previous in done.iter() {
+            let projection = dot(current, previous);
+            for (c, p) in current.iter_mut().zip(previous) {
+                *c -= projection * p;
+            }
+        }
+        let norm = dot(current, current).sqrt();
+        for c in current.iter_mut() {
+            *c /= norm;
+        }
+    }
+    let mut matrix = vec![0.; rows * cols];
+    for (col, column) in columns.iter().enumerate() {
+        for (row, value) in column.iter().enumerate() {
+            let idx = if rows < cols {
+                col * cols + row
+            } else {
+                row * cols + col
+            };
+            matrix[idx] = value * gain;
+        }
+    }
+    matrix
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{InitScheme, LayerRole, WeightInit};
+    use crate::rng::set_seed;
+
+    fn gram(weights: &[f32], rows: usize, cols: usize) -> Vec<f64> {
+        let mut gram = vec![0.; rows * rows];
+        for i in 0..rows {
+            for j in 0..rows {
+                gram[i * rows + j] = (0..cols)
+                    .map(|k| weights[i * cols + k] as f64 * weights[j * cols + k] as f64)
+                    .sum();
+            }
+        }
+        gram
+    }
+
+    #[test]
+    fn orthogonal_weights_are_orthonormal_up_to_gain() {
+        set_seed(3);
+        let init = WeightInit::orthogonal().with_hidden_gain(2.);
+        // wide: rows are orthogonal, tall: columns are orthogonal
+        for (rows, cols) in [(4, 9), (9, 4), (5, 5)] {
+            let weights = init.weights(LayerRole::Hidden, cols, rows).unwrap();
+            let (weights, rows, cols) = if rows <= cols {
+                (weights, rows, cols)
+            } else {
+                let transposed = (0..rows * cols)
+                    .map(|idx| weights[(idx % rows) * cols + idx / rows])
+                    .collect();
+                (transposed, cols, rows)
+            };
+            let gram = gram(&weights, rows, cols);
+            for i in 0..rows {
+                for j in 0..rows {
+                    let expected = if i == j { 4. } else { 0. };
+                    assert!((gram[i * rows + j] - expected).abs() < 1e-4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn weights_are_reproducible_and_bounded() {
+        let init = WeightInit::new(InitScheme::XavierUniform).with_policy_head_gain(0.5);
+        set_seed(11);
+        let first = init.weights(LayerRole::PolicyHead, 8, 2).unwrap();
+        set_seed(11);
+        let second = init.weights(LayerRole::PolicyHead, 8, 2).unwrap();
+        assert_eq!(first, second);
+        let bound = 0.5 * (6f32 / 10.).sqrt();
+        assert!(first.iter().all(|w| w.abs() <= bound));
+        assert!(
+            WeightInit::default()
+                .weights(LayerRole::Hidden, 8, 2)
+                .is_none()
+        );
+    }
+}
@@ -39,17 +39,22 @@
 
 pub mod buffers;
 pub mod env;
+pub mod env_checker;
+pub mod episode;
+pub mod init;
 pub mod models;
 pub mod on_policy;
 pub mod rng;
 pub mod running_mean;
 pub mod tensor;
 mod utils;
+pub mod wrappers;
 
 /// Control-flow result returned by training hooks.
 ///
 /// Hook implementations use this to signal whether the surrounding training
 /// loop should continue or stop at the current hook boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HookResult {
     /// Continue the current training loop.
     Continue,
@@ -57,6 +62,17 @@ pub enum HookResult {
     Break,
 }
 
+impl HookResult {
+    /// Combines the results of two hooks run at the same boundary, breaking if
+    /// either of them does.
+    pub fn merge(self, other: HookResult) -> HookResult {
+        match (self, other) {
+            (HookResult::Continue, HookResult::Continue) => HookResult::Continue,
+            _ => HookResult::Break,
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! break_on_hook_result {
     ($hook_res:expr) => {
@@ -89,12 +105,18 @@ pub mod prelude {
         // variable_sized::VariableSizedStateBuffer,
     };
     pub use crate::env::{Env, EnvBuilder, EnvBuilderType, EnvDescription, Space};
-    pub use crate::models::{ActivationFunction, Actor, LearningModule, Policy, ValueFunction};
+    pub use crate::models::{
+        ActivationFunction, Actor, LearningModule, OptimizerConfig, Policy, RandomActor,
+        ValueFunction,
+    };
     // pub use crate::on_policy::algorithm::{
     //     Agent, DefaultAdapter, OnPolicyAdapters, OnPolicyAlgorithm, OnPolicyAlgorithmHooks,
     //     OnPolicyRuntime, Sampler,
     // };
-    pub use crate::on_policy::learning_module::OnPolicyLearningModule;
+    pub use crate::on_policy::learning_module::{
+        AuxiliaryValueLearningModule, OnPolicyLearningModule, StatefulLearningModule,
+        TrustRegionLearningModule,
+    };
     pub use crate::on_policy::losses::FromPolicyValueLosses;
     pub use crate::tensor::{R2lTensor, TensorData};
 }
@@ -1,11 +1,19 @@
 use std::{collections::HashMap, fmt, str::FromStr};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 
-use crate::tensor::R2lTensor;
+use crate::{
+    env::{EnvDescription, Space},
+    tensor::R2lTensor,
+};
 
 /// Activation function used between hidden layers in feed-forward networks.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum ActivationFunction {
     /// Exponential linear unit activation with the backend default alpha.
     Elu,
@@ -64,6 +72,188 @@ impl FromStr for ActivationFunction {
     }
 }
 
+/// Optimizer used to update policy and value parameters.
+///
+/// The configuration is backend agnostic; `r2l-burn` and `r2l-candle` build
+/// their own optimizer state from it. The learning rate of every variant can
+/// be changed during training through
+/// [`OnPolicyLearningModule::set_learning_rate`](crate::on_policy::learning_module::OnPolicyLearningModule::set_learning_rate).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptimizerConfig {
+    /// Adam with the weight decay added to the gradients as an L2 penalty.
+    Adam {
+        lr: f64,
+        beta1: f64,
+        beta2: f64,
+        eps: f64,
+        weight_decay: f64,
+    },
+    /// Adam with decoupled weight decay.
+    AdamW {
+        lr: f64,
+        beta1: f64,
+        beta2: f64,
+        eps: f64,
+        weight_decay: f64,
+    },
+    /// RMSprop with optional momentum and centering.
+    ///
+    /// With `tf_like` set, epsilon is added inside the square root and the
+    /// squared-gradient average starts at one, as in TensorFlow and
+    /// Stable-Baselines3's `RMSpropTFLike`.
+    RmsProp {
+        lr: f64,
+        alpha: f64,
+        eps: f64,
+        momentum: f64,
+        centered: bool,
+        weight_decay: f64,
+        tf_like: bool,
+    },
+    /// Stochastic gradient descent with optional momentum.
+    Sgd {
+        lr: f64,
+        momentum: f64,
+        nesterov: bool,
+        weight_decay: f64,
+    },
+}
+
+impl OptimizerConfig {
+    /// Adam with PyTorch's default parameters.
+    pub fn adam(lr: f64) -> Self {
+        Self::Adam {
+            lr,
+            beta1: 0.9,
+            beta2: 0.999,
+            eps: 1e-8,
+            weight_decay: 0.,
+        }
+    }
+
+    /// AdamW with PyTorch's default parameters.
+    pub fn adamw(lr: f64) -> Self {
+        Self::AdamW {
+            lr,
+            beta1: 0.9,
+            beta2: 0.999,
+            eps: 1e-8,
+            weight_decay: 0.01,
+        }
+    }
+
+    /// RMSprop with PyTorch's default parameters.
+    pub fn rms_prop(lr: f64) -> Self {
+        Self::RmsProp {
+            lr,
+            alpha: 0.99,
+            eps: 1e-8,
+            momentum: 0.,
+            centered: false,
+            weight_decay: 0.,
+            tf_like: false,
+        }
+    }
+
+    /// Plain stochastic gradient descent.
+    pub fn sgd(lr: f64) -> Self {
+        Self::Sgd {
+            lr,
+            momentum: 0.,
+            nesterov: false,
+            weight_decay: 0.,
+        }
+    }
+
+    /// Returns the initial learning rate.
+    pub fn learning_rate(&self) -> f64 {
+        match self {
+            Self::Adam { lr, .. }
+            | Self::AdamW { lr, .. }
+            | Self::RmsProp { lr, .. }
+            | Self::Sgd { lr, .. } => *lr,
+        }
+    }
+
+    /// Sets the initial learning rate.
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
+        match &mut self {
+            Self::Adam { lr, .. }
+            | Self::AdamW { lr, .. }
+            | Self::RmsProp { lr, .. }
+            | Self::Sgd { lr, .. } => *lr = learning_rate,
+        }
+        self
+    }
+
+    /// Sets `beta1`. Only Adam and AdamW have it, any other optimizer is
+    /// rejected.
+    pub fn with_beta1(mut self, value: f64) -> Result<Self> {
+        match &mut self {
+            Self::Adam { beta1, .. } | Self::AdamW { beta1, .. } => *beta1 = value,
+            _ => bail!("{} has no `beta1`", self.name()),
+        }
+        Ok(self)
+    }
+
+    /// Sets `beta2`. Only Adam and AdamW have it, any other optimizer is
+    /// rejected.
+    pub fn with_beta2(mut self, value: f64) -> Result<Self> {
+        match &mut self {
+            Self::Adam { beta2, .. } | Self::AdamW { beta2, .. } => *beta2 = value,
+            _ => bail!("{} has no `beta2`", self.name()),
+        }
+        Ok(self)
+    }
+
+    /// Sets epsilon. SGD has none and is rejected.
+    pub fn with_epsilon(mut self, value: f64) -> Result<Self> {
+        match &mut self {
+            Self::Adam { eps, .. } | Self::AdamW { eps, .. } | Self::RmsProp { eps, .. } => {
+                *eps = value
+            }
+            Self::Sgd { .. } => bail!("{} has no epsilon", self.name()),
+        }
+        Ok(self)
+    }
+
+    /// Sets the weight decay.
+    pub fn with_weight_decay(mut self, value: f64) -> Self {
+        match &mut self {
+            Self::Adam { weight_decay, .. }
+            | Self::AdamW { weight_decay, .. }
+            | Self::RmsProp { weight_decay, .. }
+            | Self::Sgd { weight_decay, .. } => *weight_decay = value,
+        }
+        self
+    }
+
+    /// Sets the momentum. Only RMSprop and SGD have it, any other
+    /// optimizer is rejected.
+    pub fn with_momentum(mut self, value: f64) -> Result<Self> {
+        match &mut self {
+            Self::RmsProp { momentum, .. } | Self::Sgd { momentum, .. } => *momentum = value,
+            _ => bail!("{} has no momentum", self.name()),
+        }
+        Ok(self)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Adam { .. } => "Adam",
+            Self::AdamW { .. } => "AdamW",
+            Self::RmsProp { .. } => "RMSprop",
+            Self::Sgd { .. } => "SGD",
+        }
+    }
+}
+
+impl Default for OptimizerConfig {
+    fn default() -> Self {
+        Self::adamw(1e-3)
+    }
+}
+
 /// Metadata stored next to policy tensors in a safetensors archive.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PolicyMetadata {
@@ -78,10 +268,13 @@ impl PolicyMetadata {
     }
 
     /// Builds policy metadata from the string map stored by safetensors.
-    pub fn from_safetensors_metadata(metadata: &HashMap<String, String>) -> Self {
-        Self {
-            activation: metadata.get("activation").unwrap().parse().unwrap(),
-        }
+    pub fn from_safetensors_metadata(metadata: &HashMap<String, String>) -> Result<Self> {
+        let Some(activation) = metadata.get("activation") else {
+            bail!("the policy metadata has no activation function");
+        };
+        Ok(Self {
+            activation: activation.parse().map_err(anyhow::Error::msg)?,
+        })
     }
 }
 
@@ -102,6 +295,41 @@ pub trait Actor: Send + 'static {
     }
 }
 
+/// Actor that ignores observations and samples uniformly from the action space.
+///
+/// Useful as a random-policy baseline and for warming up observation
+/// normalizers before training. Actions follow the encoding of
+/// [`Space::sample`].
+#[derive(Debug, Clone)]
+pub struct RandomActor<T: R2lTensor> {
+    action_space: Space<T>,
+}
+
+impl<T: R2lTensor> RandomActor<T> {
+    /// Creates a random actor for the action space of `env_description`.
+    pub fn new(env_description: &EnvDescription<T>) -> Self {
+        Self::from_space(env_description.action_space.clone())
+    }
+
+    /// Creates a random actor sampling from `action_space`.
+    pub fn from_space(action_space: Space<T>) -> Self {
+        Self { action_space }
+    }
+
+    /// Returns the sampled action space.
+    pub fn action_space(&self) -> &Space<T> {
+        &self.action_space
+    }
+}
+
+impl<T: R2lTensor> Actor for RandomActor<T> {
+    type Tensor = T;
+
+    fn action(&self, _observation: T) -> Result<T> {
+        self.action_space.sample()
+    }
+}
+
 /// Trainable action distribution interface used by on-policy algorithms.
 ///
 /// A `Policy` extends [`Actor`] with the quantities needed to compute policy
@@ -120,11 +348,36 @@ pub trait Policy: Actor {
     /// Computes the policy entropy for a batch of states.
     fn entropy(&self, states: &[Self::Tensor]) -> Result<Self::Tensor>;
 
+    /// Computes the mean entropy of every action dimension over a batch of
+    /// states. Defaults to the total entropy as a single dimension.
+    fn entropy_per_dimension(&self, states: &[Self::Tensor]) -> Result<Vec<f32>> {
+        Ok(self.entropy(states)?.to_vec())
+    }
+
+    /// Returns the log standard deviation of every action dimension for
+    /// policies with state-independent Gaussian noise.
+    fn log_std(&self) -> Result<Option<Vec<f32>>> {
+        Ok(None)
+    }
+
     /// Resamples exploration noise for policies that use state-independent
     /// noise. Implementations without such noise may keep the default no-op.
     fn resample_noise(&mut self) -> Result<()> {
         Ok(())
     }
+
+    /// Returns the parameters of the action distribution at every state,
+    /// flattened into one tensor that [`Policy::kl_divergence`] reads back.
+    fn distribution_parameters(&self, _states: &[Self::Tensor]) -> Result<Self::Tensor> {
+        bail!("this policy does not expose its distribution parameters")
+    }
+
+    /// Computes the mean analytic KL divergence `KL(old || self)` over a batch
+    /// of states, where `old` holds the [`Policy::distribution_parameters`]
+    /// of another policy at the same states.
+    fn kl_divergence(&self, _states: &[Self::Tensor], _old: &Self::Tensor) -> Result<Self::Tensor> {
+        bail!("this policy has no analytic KL divergence")
+    }
 }
 
 /// Component that applies backend-specific optimizer updates.
@@ -136,6 +389,39 @@ pub trait LearningModule {
     fn update(&mut self, losses: Self::Losses) -> Result<()>;
 }
 
+/// Gradient norms of the updates applied by an optimizer.
+///
+/// Norms are accumulated over updates until the owner takes them, so the
+/// record stays the same size however many updates it covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GradNorms {
+    /// Number of recorded updates.
+    pub updates: usize,
+    /// Sum of the global gradient norms before clipping.
+    pub pre_clip_sum: f32,
+    /// Sum of the global gradient norms after clipping.
+    pub post_clip_sum: f32,
+}
+
+impl GradNorms {
+    /// Records the gradient norms of one update.
+    pub fn record(&mut self, pre_clip: f32, post_clip: f32) {
+        self.updates += 1;
+        self.pre_clip_sum += pre_clip;
+        self.post_clip_sum += post_clip;
+    }
+
+    /// Mean gradient norm before clipping, if any update was recorded.
+    pub fn pre_clip(&self) -> Option<f32> {
+        (self.updates > 0).then(|| self.pre_clip_sum / self.updates as f32)
+    }
+
+    /// Mean gradient norm after clipping, if any update was recorded.
+    pub fn post_clip(&self) -> Option<f32> {
+        (self.updates > 0).then(|| self.post_clip_sum / self.updates as f32)
+    }
+}
+
 /// Batched value-function interface.
 pub trait ValueFunction {
     /// Tensor type used for observations and returned values.
@@ -3,6 +3,7 @@ use anyhow::Result;
 use crate::{
     HookResult, break_on_hook_result,
     buffers::{TrajectoryBatch, buffer::TrajectoryView},
+    episode::EpisodeMonitor,
     models::Actor,
     return_on_hook_result,
     tensor::R2lTensor,
@@ -25,6 +26,11 @@ pub trait Agent {
     /// Sets the learning rate used by future updates.
     fn set_learning_rate(&mut self, learning_rate: f64);
 
+    /// Reports the remaining fraction of training, from `1.0` down to `0.0`.
+    ///
+    /// Agents use it to drive hyperparameter schedules.
+    fn set_progress_remaining(&mut self, _progress_remaining: f64) {}
+
     /// Releases agent resources before the training loop exits.
     fn shutdown(&mut self) {}
 }
@@ -33,14 +39,28 @@ pub trait Sampler {
     type Tensor: R2lTensor;
 
     /// Resets all environments managed by the sampler.
-    fn reset_all_envs(&mut self) {}
+    ///
+    /// Fails when an environment fails to reset.
+    fn reset_all_envs(&mut self) -> Result<()> {
+        Ok(())
+    }
 
     /// Collects rollout data using the provided actor.
-    fn collect_rollouts<A: Actor<Tensor = Self::Tensor> + Clone>(&mut self, actor: A);
+    ///
+    /// Fails when the actor or an environment fails.
+    fn collect_rollouts<A: Actor<Tensor = Self::Tensor> + Clone>(&mut self, actor: A)
+    -> Result<()>;
 
     /// Creates a view for the agents.
     fn trajectory_views<'a>(&'a mut self) -> impl AsRef<[TrajectoryView<'a, Self::Tensor>]>;
 
+    /// Returns the monitor receiving the episodes completed by this sampler.
+    ///
+    /// Samplers that do not track episodes return `None`.
+    fn episode_monitor(&self) -> Option<EpisodeMonitor> {
+        None
+    }
+
     /// Releases sampler resources before the training loop exits.
     fn shutdown(&mut self) {}
 }
@@ -106,10 +126,10 @@ pub struct OnPolicyRuntime<A: Agent, S: Sampler, C: OnPolicyAdapters<A::Actor, S
 
 impl<A: Agent, S: Sampler, C: OnPolicyAdapters<A::Actor, S>> OnPolicyRuntime<A, S, C> {
     /// Collects a fresh set of rollouts using the adapted actor.
-    pub fn collect(&mut self) {
+    pub fn collect(&mut self) -> Result<()> {
         let actor = self.agent.actor();
         let actor = self.adapter.adapt_actor(actor);
-        self.sampler.collect_rollouts(actor);
+        self.sampler.collect_rollouts(actor)
     }
 
     /// Returns the last collected trajectory containers from the sampler.
@@ -142,6 +162,11 @@ impl<A: Agent, S: Sampler, C: OnPolicyAdapters<A::Actor, S>> OnPolicyRuntime<A,
     }
 }
 
+/// Lifecycle hooks of an [`OnPolicyAlgorithm`].
+///
+/// Tuples of up to four hooks with the same agent, sampler and adapter types
+/// are hooks too: they run their hooks in order at every boundary and break
+/// if any of them does.
 pub trait OnPolicyAlgorithmHooks {
     /// Agent type controlled by the training loop.
     type A: Agent;
@@ -195,7 +220,7 @@ impl<
     pub fn train(&mut self) -> Result<()> {
         return_on_hook_result!(self.hooks.init_hook(&mut self.runtime));
         loop {
-            self.runtime.collect();
+            self.runtime.collect()?;
             break_on_hook_result!(self.hooks.post_rollout_hook(&mut self.runtime));
 
             self.runtime.learn()?;
@@ -205,3 +230,233 @@ impl<
         self.hooks.shutdown_hook(&mut self.runtime)
     }
 }
+
+impl<
+    A: Agent,
+    S: Sampler,
+    H: OnPolicyAlgorithmHooks<A = A, S = S, C = C>,
+    C: OnPolicyAdapters<A::Actor, S>,
+> OnPolicyAlgorithm<A, S, H, C>
+{
+    /// Runs `hook` after the current hooks at every hook boundary.
+    ///
+    /// See the tuple implementations of [`OnPolicyAlgorithmHooks`] for the
+    /// ordering and [`HookResult::Break`] semantics.
+    pub fn chain_hook<H2: OnPolicyAlgorithmHooks<A = A, S = S, C = C>>(
+        self,
+        hook: H2,
+    ) -> OnPolicyAlgorithm<A, S, (H, H2), C> {
+        OnPolicyAlgorithm {
+            runtime: self.runtime,
+            hooks: (self.hooks, hook),
+        }
+    }
+}
+
+// Tuples of hooks run every hook in order at every boundary, even after an
+// earlier one returned `Break`, so stateful hooks see the whole loop. The tuple
+// breaks if any of its hooks does. `shutdown_hook` also runs every hook and
+// returns the first error.
+macro_rules! on_policy_hooks_tuple {
+    ($first:ident $(, $idx:tt $hook:ident)+) => {
+        impl<
+            $first: OnPolicyAlgorithmHooks,
+            $($hook: OnPolicyAlgorithmHooks<
+                A = <$first as OnPolicyAlgorithmHooks>::A,
+                S = <$first as OnPolicyAlgorithmHooks>::S,
+                C = <$first as OnPolicyAlgorithmHooks>::C,
+            >,)+
+        > OnPolicyAlgorithmHooks for ($first, $($hook,)+)
+        {
+            type A = <$first as OnPolicyAlgorithmHooks>::A;
+            type S = <$first as OnPolicyAlgorithmHooks>::S;
+            type C = <$first as OnPolicyAlgorithmHooks>::C;
+
+            fn init_hook(
+                &mut self,
+                runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
+            ) -> HookResult {
+                let mut result = self.0.init_hook(runtime);
+                $(result = result.merge(self.$idx.init_hook(runtime));)+
+                result
+            }
+
+            fn post_rollout_hook(
+                &mut self,
+                runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
+            ) -> HookResult {
+                let mut result = self.0.post_rollout_hook(runtime);
+                $(result = result.merge(self.$idx.post_rollout_hook(runtime));)+
+                result
+            }
+
+            fn post_training_hook(
+                &mut self,
+                runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
+            ) -> HookResult {
+                let mut result = self.0.post_training_hook(runtime);
+                $(result = result.merge(self.$idx.post_training_hook(runtime));)+
+                result
+            }
+
+            fn shutdown_hook(
+                &mut self,
+                runtime: &mut OnPolicyRuntime<Self::A, Self::S, Self::C>,
+            ) -> Result<()> {
+                let mut result = self.0.shutdown_hook(runtime);
+                $(result = result.and(self.$idx.shutdown_hook(runtime));)+
+                result
+            }
+        }
+    };
+}
+
+on_policy_hooks_tuple!(H0, 1 H1);
+on_policy_hooks_tuple!(H0, 1 H1, 2 H2);
+on_policy_hooks_tuple!(H0, 1 H1, 2 H2, 3 H3);
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::Result;
+
+    use super::{
+        Agent, DefaultAdapter, OnPolicyAlgorithm, OnPolicyAlgorithmHooks, OnPolicyRuntime, Sampler,
+    };
+    use crate::{
+        HookResult,
+        buffers::{TrajectoryBatch, buffer::TrajectoryView},
+        models::Actor,
+        tensor::TensorData,
+    };
+
+    #[derive(Clone)]
+    struct IdentityActor;
+
+    impl Actor for IdentityActor {
+        type Tensor = TensorData;
+
+        fn action(&self, observation: TensorData) -> Result<TensorData> {
+            Ok(observation)
+        }
+    }
+
+    struct NoopAgent;
+
+    impl Agent for NoopAgent {
+        type Tensor = TensorData;
+        type Actor = IdentityActor;
+
+        fn actor(&self) -> IdentityActor {
+            IdentityActor
+        }
+
+        fn learn<B: TrajectoryBatch<TensorData>>(&mut self, _buffers: &[B]) -> Result<()> {
+            Ok(())
+        }
+
+        fn set_learning_rate(&mut self, _learning_rate: f64) {}
+    }
+
+    struct NoopSampler;
+
+    impl Sampler for NoopSampler {
+        type Tensor = TensorData;
+
+        fn collect_rollouts<A: Actor<Tensor = TensorData> + Clone>(
+            &mut self,
+            _actor: A,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn trajectory_views<'a>(&'a mut self) -> impl AsRef<[TrajectoryView<'a, TensorData>]> {
+            Vec::new()
+        }
+    }
+
+    // Records its calls and breaks after `rollouts` training steps.
+    struct Recorder {
+        name: &'static str,
+        rollouts: usize,
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn record(&self, event: &str) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{}:{event}", self.name));
+        }
+    }
+
+    impl OnPolicyAlgorithmHooks for Recorder {
+        type A = NoopAgent;
+        type S = NoopSampler;
+        type C = DefaultAdapter;
+
+        fn init_hook(
+            &mut self,
+            _runtime: &mut OnPolicyRuntime<NoopAgent, NoopSampler>,
+        ) -> HookResult {
+            self.record("init");
+            HookResult::Continue
+        }
+
+        fn post_rollout_hook(
+            &mut self,
+            _runtime: &mut OnPolicyRuntime<NoopAgent, NoopSampler>,
+        ) -> HookResult {
+            self.record("rollout");
+            HookResult::Continue
+        }
+
+        fn post_training_hook(
+            &mut self,
+            _runtime: &mut OnPolicyRuntime<NoopAgent, NoopSampler>,
+        ) -> HookResult {
+            self.record("training");
+            self.rollouts -= 1;
+            if self.rollouts == 0 {
+                HookResult::Break
+            } else {
+                HookResult::Continue
+            }
+        }
+
+        fn shutdown_hook(
+            &mut self,
+            _runtime: &mut OnPolicyRuntime<NoopAgent, NoopSampler>,
+        ) -> Result<()> {
+            self.record("shutdown");
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn chained_hooks_run_in_order_until_one_breaks() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let recorder = |name, rollouts| Recorder {
+            name,
+            rollouts,
+            calls: calls.clone(),
+        };
+        let mut algorithm = OnPolicyAlgorithm {
+            runtime: OnPolicyRuntime {
+                agent: NoopAgent,
+                sampler: NoopSampler,
+                adapter: DefaultAdapter,
+            },
+            hooks: recorder("a", 3),
+        }
+        .chain_hook(recorder("b", 2));
+        algorithm.train().unwrap();
+        assert_eq!(
+            calls.lock().unwrap().join(" "),
+            "a:init b:init a:rollout b:rollout a:training b:training \
+             a:rollout b:rollout a:training b:training a:shutdown b:shutdown"
+        );
+    }
+}
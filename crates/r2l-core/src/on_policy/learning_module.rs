@@ -1,3 +1,5 @@
+use anyhow::Result;
+
 use crate::{
     models::{LearningModule, Policy, ValueFunction},
     on_policy::losses::FromPolicyValueLosses,
@@ -38,3 +40,118 @@ pub trait OnPolicyLearningModule:
     /// Sets the learning rate used by future updates.
     fn set_learning_rate(&mut self, learning_rate: f64);
 }
+
+// Length of the parameter-space step used by the central differences of
+// `fisher_vector_product`. The KL divergence is quadratic around the current
+// policy, so its gradient is close to linear along the step and longer steps
+// lose less of the f32 gradients to rounding.
+const FINITE_DIFFERENCE_STEP: f32 = 1e-2;
+
+/// Learning module that exposes its policy parameters as one flat vector.
+///
+/// Second-order methods such as TRPO step the policy parameters directly
+/// instead of through an optimizer, and train the value function on its own.
+/// The flat layout only has to be consistent between
+/// [`policy_parameters`](TrustRegionLearningModule::policy_parameters),
+/// [`set_policy_parameters`](TrustRegionLearningModule::set_policy_parameters)
+/// and [`policy_gradient`](TrustRegionLearningModule::policy_gradient).
+pub trait TrustRegionLearningModule: OnPolicyLearningModule {
+    /// Returns the policy parameters flattened into one vector.
+    fn policy_parameters(&self) -> Result<Vec<f32>>;
+
+    /// Overwrites the policy parameters from a flat vector.
+    fn set_policy_parameters(&mut self, parameters: &[f32]) -> Result<()>;
+
+    /// Returns the gradient of a scalar `loss` with respect to the policy
+    /// parameters, flattened.
+    fn policy_gradient(&mut self, loss: &Self::LearningTensor) -> Result<Vec<f32>>;
+
+    /// Applies one optimizer update to the value function only.
+    fn update_value_function(&mut self, value_loss: Self::LearningTensor) -> Result<()>;
+
+    /// Multiplies `vector` by the Fisher information matrix of the policy,
+    /// the Hessian of the KL divergence that TRPO constrains.
+    ///
+    /// The default implementation differences the gradient of the analytic
+    /// [`Policy::kl_divergence`] from the current policy:
+    /// `(grad KL(θ + εv) - grad KL(θ - εv)) / 2ε`. The gradient vanishes at
+    /// `θ` and is close to linear around it, so the central difference only
+    /// needs first-order gradients and stays accurate in f32. Modules whose
+    /// backend supports double backward can override this with an exact
+    /// product.
+    fn fisher_vector_product(
+        &mut self,
+        observations: &[Self::LearningTensor],
+        vector: &[f32],
+    ) -> Result<Vec<f32>> {
+        let parameters = self.policy_parameters()?;
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm == 0. {
+            return Ok(vec![0.; vector.len()]);
+        }
+        // rebuilt from its values so no gradient flows into the reference
+        let old = self
+            .policy()
+            .distribution_parameters(observations)?
+            .to_vec();
+        let old = self.tensor_from_slice(&old);
+        let epsilon = FINITE_DIFFERENCE_STEP / norm;
+        let mut kl_gradient = |sign: f32| -> Result<Vec<f32>> {
+            let shifted: Vec<f32> = parameters
+                .iter()
+                .zip(vector)
+                .map(|(p, v)| p + sign * epsilon * v)
+                .collect();
+            self.set_policy_parameters(&shifted)?;
+            let kl = self.policy().kl_divergence(observations, &old)?;
+            self.policy_gradient(&kl)
+        };
+        let plus = kl_gradient(1.);
+        let minus = kl_gradient(-1.);
+        self.set_policy_parameters(&parameters)?;
+        Ok(plus?
+            .iter()
+            .zip(&minus?)
+            .map(|(plus, minus)| (plus - minus) / (2. * epsilon))
+            .collect())
+    }
+}
+
+/// Learning module whose policy network carries an auxiliary value head.
+///
+/// Phasic Policy Gradient distills the value function into the policy network
+/// through this head, so the policy features learn to predict values without
+/// the value loss interfering with the policy phase.
+pub trait AuxiliaryValueLearningModule: OnPolicyLearningModule {
+    /// Estimates values for a batch of observations with the auxiliary head.
+    fn auxiliary_values(
+        &self,
+        observations: &[Self::LearningTensor],
+    ) -> Result<Self::LearningTensor>;
+
+    /// Trains the policy network and its auxiliary head on `joint_loss`, and
+    /// the value function on `value_loss`.
+    fn auxiliary_update(
+        &mut self,
+        joint_loss: Self::LearningTensor,
+        value_loss: Self::LearningTensor,
+    ) -> Result<()>;
+}
+
+/// Learning module whose parameters and optimizer state can be copied into
+/// another module of the same architecture.
+///
+/// Population-Based Training uses this to restart a member from a better one.
+/// Learning rates are hyperparameters and are not part of the state.
+pub trait StatefulLearningModule: OnPolicyLearningModule {
+    /// Copy of the parameters and optimizer state, independent of the module
+    /// it was taken from.
+    type State: Clone;
+
+    /// Copies the parameters and optimizer state.
+    fn state(&self) -> Result<Self::State>;
+
+    /// Overwrites the parameters and optimizer state with `state`, which must
+    /// come from a module of the same architecture and optimizer.
+    fn load_state(&mut self, state: &Self::State) -> Result<()>;
+}
@@ -5,7 +5,7 @@ use crate::tensor::{R2lTensor, TensorData};
 
 impl R2lTensor for Tensor {
     fn to_vec(&self) -> Vec<f32> {
-        self.to_vec1().unwrap()
+        self.flatten_all().unwrap().to_vec1().unwrap()
     }
 
     fn to_shape(&self) -> Vec<usize> {
@@ -79,6 +79,12 @@ impl TensorData {
 mod test {
     use candle_core::Tensor;
 
+    #[test]
+    fn scalars_convert_to_one_element_vec() {
+        let scalar = Tensor::new(1.5f32, &candle_core::Device::Cpu).unwrap();
+        assert_eq!(crate::tensor::R2lTensor::to_vec(&scalar), vec![1.5]);
+    }
+
     #[test]
     fn mean_things() {
         // what we have here is the following:
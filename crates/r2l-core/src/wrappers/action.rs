@@ -0,0 +1,88 @@
+use anyhow::Result;
+
+use crate::{
+    env::{Env, EnvDescription, Snapshot, Space},
+    tensor::R2lTensor,
+    wrappers::EnvWrapper,
+};
+
+/// Clips continuous actions to the bounds of the inner `Box` action space.
+///
+/// The wrapped action space is reported without bounds, since any action is
+/// accepted. Actions for other spaces, or for unbounded dimensions, are passed
+/// through unchanged.
+pub struct ClipAction<E: Env> {
+    env: E,
+    bounds: Option<(Vec<f32>, Vec<f32>)>,
+    description: EnvDescription<E::Tensor>,
+}
+
+impl<E: Env> ClipAction<E> {
+    /// Wraps `env` so that actions are clipped to the action-space bounds.
+    pub fn new(env: E) -> Self {
+        let mut description = env.env_description();
+        let bounds = match &mut description.action_space {
+            Space::Box { min, max, shape } => {
+                let size = shape.iter().product();
+                let bound = |bound: Option<E::Tensor>, fill: f32| {
+                    bound.map_or_else(|| vec![fill; size], |bound| bound.to_vec())
+                };
+                Some((
+                    bound(min.take(), f32::NEG_INFINITY),
+                    bound(max.take(), f32::INFINITY),
+                ))
+            }
+            _ => None,
+        };
+        Self {
+            env,
+            bounds,
+            description,
+        }
+    }
+
+    /// Returns the wrapped environment.
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Env> Env for ClipAction<E> {
+    type Tensor = E::Tensor;
+
+    fn reset(&mut self, seed: u64) -> Result<Self::Tensor> {
+        self.env.reset(seed)
+    }
+
+    fn step(&mut self, action: Self::Tensor) -> Result<Snapshot<Self::Tensor>> {
+        let action = match &self.bounds {
+            Some((min, max)) => {
+                let (data, shape) = action.to_vec_and_shape();
+                let data = data
+                    .into_iter()
+                    .zip(min.iter().zip(max))
+                    .map(|(value, (min, max))| value.max(*min).min(*max))
+                    .collect();
+                E::Tensor::from_vec_and_shape(data, shape)
+            }
+            None => action,
+        };
+        self.env.step(action)
+    }
+
+    fn env_description(&self) -> EnvDescription<Self::Tensor> {
+        self.description.clone()
+    }
+}
+
+/// [`EnvWrapper`] configuration for [`ClipAction`].
+#[derive(Debug, Clone, Copy)]
+pub struct ClipActionWrapper;
+
+impl<E: Env> EnvWrapper<E> for ClipActionWrapper {
+    type Env = ClipAction<E>;
+
+    fn wrap(&self, env: E) -> Result<Self::Env> {
+        Ok(ClipAction::new(env))
+    }
+}
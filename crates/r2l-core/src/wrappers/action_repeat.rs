@@ -0,0 +1,87 @@
+use anyhow::{Result, bail};
+
+use crate::{
+    env::{Env, EnvDescription, Snapshot},
+    wrappers::EnvWrapper,
+};
+
+/// Repeats every action for a fixed number of inner steps.
+///
+/// Rewards of the repeated steps are summed. Repetition stops early when the
+/// inner episode ends, and the last observation is returned.
+pub struct ActionRepeat<E: Env> {
+    env: E,
+    repeat: usize,
+}
+
+/// Frame skipping is action repetition under its Atari name.
+pub type FrameSkip<E> = ActionRepeat<E>;
+
+impl<E: Env> ActionRepeat<E> {
+    /// Wraps `env` so that each action is applied `repeat` times.
+    ///
+    /// `repeat` must be at least one.
+    pub fn new(env: E, repeat: usize) -> Result<Self> {
+        if repeat == 0 {
+            bail!("actions must be repeated at least once");
+        }
+        Ok(Self { env, repeat })
+    }
+
+    /// Returns the wrapped environment.
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Env> Env for ActionRepeat<E> {
+    type Tensor = E::Tensor;
+
+    fn reset(&mut self, seed: u64) -> Result<Self::Tensor> {
+        self.env.reset(seed)
+    }
+
+    fn step(&mut self, action: Self::Tensor) -> Result<Snapshot<Self::Tensor>> {
+        let mut total_reward = 0.;
+        for _ in 1..self.repeat {
+            let snapshot = self.env.step(action.clone())?;
+            total_reward += snapshot.reward;
+            if snapshot.done() {
+                return Ok(Snapshot {
+                    reward: total_reward,
+                    ..snapshot
+                });
+            }
+        }
+        let snapshot = self.env.step(action)?;
+        Ok(Snapshot {
+            reward: total_reward + snapshot.reward,
+            ..snapshot
+        })
+    }
+
+    fn env_description(&self) -> EnvDescription<Self::Tensor> {
+        self.env.env_description()
+    }
+}
+
+/// [`EnvWrapper`] configuration for [`ActionRepeat`].
+#[derive(Debug, Clone, Copy)]
+pub struct ActionRepeatWrapper {
+    repeat: usize,
+}
+
+impl ActionRepeatWrapper {
+    /// Creates an action repeat configuration.
+    pub fn new(repeat: usize) -> Self {
+        Self { repeat }
+    }
+}
+
+impl<E: Env> EnvWrapper<E> for ActionRepeatWrapper {
+    type Env = ActionRepeat<E>;
+
+    fn wrap(&self, env: E) -> Result<Self::Env> {
+        ActionRepeat::new(env, self.repeat)
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+
+use anyhow::{Result, bail};
+
+use crate::{
+    env::{Env, EnvDescription, Snapshot, Space},
+    tensor::R2lTensor,
+    wrappers::EnvWrapper,
+};
+
+/// Stacks the last `n_frames` observations into one flat observation.
+///
+/// After a reset, the stack is filled with copies of the initial observation.
+/// An inner observation of `d` values becomes a stacked observation of shape
+/// `[n_frames * d]`, with the oldest frame first, as SB3's `VecFrameStack`
+/// does for flat observations. Keeping observations one-dimensional lets them
+/// batch into the `[batch, features]` input of the policy networks.
+pub struct FrameStack<E: Env> {
+    env: E,
+    n_frames: usize,
+    frames: VecDeque<Vec<f32>>,
+    description: EnvDescription<E::Tensor>,
+}
+
+impl<E: Env> FrameStack<E> {
+    /// Wraps `env` so that observations contain the last `n_frames` frames.
+    ///
+    /// `n_frames` must be at least one.
+    pub fn new(env: E, n_frames: usize) -> Result<Self> {
+        if n_frames == 0 {
+            bail!("at least one frame must be stacked");
+        }
+        let inner = env.env_description();
+        let description = EnvDescription::new(
+            stack_space(inner.observation_space, n_frames),
+            inner.action_space,
+        );
+        Ok(Self {
+            env,
+            n_frames,
+            frames: VecDeque::with_capacity(n_frames),
+            description,
+        })
+    }
+
+    /// Returns the wrapped environment.
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+
+    fn stacked(&self) -> E::Tensor {
+        let data: Vec<f32> = self.frames.iter().flatten().copied().collect();
+        let size = data.len();
+        E::Tensor::from_vec_and_shape(data, vec![size])
+    }
+}
+
+impl<E: Env> Env for FrameStack<E> {
+    type Tensor = E::Tensor;
+
+    fn reset(&mut self, seed: u64) -> Result<Self::Tensor> {
+        let frame = self.env.reset(seed)?.to_vec();
+        self.frames.clear();
+        self.frames.resize(self.n_frames, frame);
+        Ok(self.stacked())
+    }
+
+    fn step(&mut self, action: Self::Tensor) -> Result<Snapshot<Self::Tensor>> {
+        let snapshot = self.env.step(action)?;
+        self.frames.pop_front();
+        self.frames.push_back(snapshot.state.to_vec());
+        Ok(Snapshot {
+            state: self.stacked(),
+            ..snapshot
+        })
+    }
+
+    fn env_description(&self) -> EnvDescription<Self::Tensor> {
+        self.description.clone()
+    }
+}
+
+fn tile<T: R2lTensor>(tensor: &T, n_frames: usize, shape: &[usize]) -> T {
+    T::from_vec_and_shape(tensor.to_vec().repeat(n_frames), shape.to_vec())
+}
+
+fn stack_space<T: R2lTensor>(space: Space<T>, n_frames: usize) -> Space<T> {
+    let stacked_shape = |shape: &[usize]| vec![n_frames * shape.iter().product::<usize>()];
+    match space {
+        Space::Box { min, max, shape } => {
+            let shape = stacked_shape(&shape);
+            Space::Box {
+                min: min.map(|min| tile(&min, n_frames, &shape)),
+                max: max.map(|max| tile(&max, n_frames, &shape)),
+                shape,
+            }
+        }
+        Space::MultiDiscrete { nvec, shape } => {
+            let shape = stacked_shape(&shape);
+            Space::MultiDiscrete {
+                nvec: tile(&nvec, n_frames, &shape),
+                shape,
+            }
+        }
+        Space::MultiBinary { shape } => Space::MultiBinary {
+            shape: stacked_shape(&shape),
+        },
+        // discrete observations are one-hot encoded, so a stack is a box of one-hot frames
+        Space::Discrete(size) => {
+            let shape = vec![n_frames * size];
+            Space::Box {
+                min: Some(T::zeros(shape.clone())),
+                max: Some(T::from_vec_and_shape(
+                    vec![1.; n_frames * size],
+                    shape.clone(),
+                )),
+                shape,
+            }
+        }
+        space @ (Space::Tuple(_) | Space::Dict(_)) => Space::Box {
+            min: None,
+            max: None,
+            shape: vec![n_frames * space.size()],
+        },
+    }
+}
+
+/// [`EnvWrapper`] configuration for [`FrameStack`].
+#[derive(Debug, Clone, Copy)]
+pub struct FrameStackWrapper {
+    n_frames: usize,
+}
+
+impl FrameStackWrapper {
+    /// Creates a frame stack configuration.
+    pub fn new(n_frames: usize) -> Self {
+        Self { n_frames }
+    }
+}
+
+impl<E: Env> EnvWrapper<E> for FrameStackWrapper {
+    type Env = FrameStack<E>;
+
+    fn wrap(&self, env: E) -> Result<Self::Env> {
+        FrameStack::new(env, self.n_frames)
+    }
+}
@@ -0,0 +1,435 @@
+//! Composable wrappers around [`Env`] implementations.
+//!
+//! Each wrapper owns an inner environment, changes one aspect of its behavior,
+//! and reports an [`EnvDescription`] that matches the wrapped observations and
+//! actions. Wrappers can be applied directly to an environment, or chained on
+//! any [`EnvBuilder`] through [`EnvBuilderExt`]:
+//!
+//! ```ignore
+//! use r2l_core::wrappers::EnvBuilderExt;
+//!
+//! let env_builder = GymEnvBuilder::new("Pendulum-v1")
+//!     .with_time_limit(200)
+//!     .with_frame_stack(4)
+//!     .with_reward_scale(0.1);
+//! ```
+//!
+//! Wrappers are applied in call order, so the last wrapper in the chain is the
+//! outermost one seen by the sampler.
+//! Invalid wrapper parameters, such as an empty clipping range, are reported
+//! as errors when the environments are built.
+
+mod action;
+mod action_repeat;
+mod frame_stack;
+mod observation;
+mod reward;
+mod time_limit;
+
+use anyhow::{Result, bail};
+
+pub use action::{ClipAction, ClipActionWrapper};
+pub use action_repeat::{ActionRepeat, ActionRepeatWrapper, FrameSkip};
+pub use frame_stack::{FrameStack, FrameStackWrapper};
+pub use observation::{
+    ObservationClip, ObservationClipWrapper, TransformObservation, TransformObservationWrapper,
+};
+pub use reward::{
+    RewardClip, RewardClipWrapper, RewardScale, RewardScaleWrapper, TransformReward,
+    TransformRewardWrapper,
+};
+pub use time_limit::{TimeLimit, TimeLimitWrapper};
+
+use crate::env::{Env, EnvBuilder, EnvTensor, Space};
+
+/// Wrapper configuration that can be applied to freshly built environments.
+///
+/// Implementations hold the wrapper parameters, while [`wrap`](Self::wrap)
+/// creates the wrapped environment. [`WrappedEnvBuilder`] uses this trait to
+/// apply the wrapper to every environment produced by an inner builder.
+pub trait EnvWrapper<E: Env>: Send + Sync + 'static {
+    /// Wrapped environment type.
+    type Env: Env;
+
+    /// Wraps an environment.
+    fn wrap(&self, env: E) -> Result<Self::Env>;
+}
+
+/// Environment builder that applies an [`EnvWrapper`] to every built environment.
+pub struct WrappedEnvBuilder<EB, W> {
+    builder: EB,
+    wrapper: W,
+}
+
+impl<EB: EnvBuilder, W: EnvWrapper<EB::Env>> WrappedEnvBuilder<EB, W> {
+    /// Creates a builder that wraps environments produced by `builder`.
+    pub fn new(builder: EB, wrapper: W) -> Self {
+        Self { builder, wrapper }
+    }
+
+    /// Returns the inner environment builder.
+    pub fn inner(&self) -> &EB {
+        &self.builder
+    }
+}
+
+impl<EB: EnvBuilder, W: EnvWrapper<EB::Env>> EnvBuilder for WrappedEnvBuilder<EB, W> {
+    type Env = W::Env;
+
+    fn build_env(&self) -> Result<Self::Env> {
+        let env = self.builder.build_env()?;
+        self.wrapper.wrap(env)
+    }
+}
+
+/// Builder extension trait for chaining environment wrappers.
+///
+/// This trait is implemented for every [`EnvBuilder`].
+pub trait EnvBuilderExt: EnvBuilder + Sized {
+    /// Applies an arbitrary wrapper configuration.
+    fn with_wrapper<W: EnvWrapper<Self::Env>>(self, wrapper: W) -> WrappedEnvBuilder<Self, W> {
+        WrappedEnvBuilder::new(self, wrapper)
+    }
+
+    /// Truncates episodes after `max_episode_steps` steps. See [`TimeLimit`].
+    fn with_time_limit(
+        self,
+        max_episode_steps: usize,
+    ) -> WrappedEnvBuilder<Self, TimeLimitWrapper> {
+        self.with_wrapper(TimeLimitWrapper::new(max_episode_steps))
+    }
+
+    /// Repeats every action `repeat` times. See [`ActionRepeat`].
+    fn with_action_repeat(self, repeat: usize) -> WrappedEnvBuilder<Self, ActionRepeatWrapper> {
+        self.with_wrapper(ActionRepeatWrapper::new(repeat))
+    }
+
+    /// Alias of [`with_action_repeat`](Self::with_action_repeat).
+    fn with_frame_skip(self, skip: usize) -> WrappedEnvBuilder<Self, ActionRepeatWrapper> {
+        self.with_action_repeat(skip)
+    }
+
+    /// Stacks the last `n_frames` observations. See [`FrameStack`].
+    fn with_frame_stack(self, n_frames: usize) -> WrappedEnvBuilder<Self, FrameStackWrapper> {
+        self.with_wrapper(FrameStackWrapper::new(n_frames))
+    }
+
+    /// Multiplies every reward by `scale`. See [`RewardScale`].
+    fn with_reward_scale(self, scale: f32) -> WrappedEnvBuilder<Self, RewardScaleWrapper> {
+        self.with_wrapper(RewardScaleWrapper::new(scale))
+    }
+
+    /// Clips every reward to `[min, max]`. See [`RewardClip`].
+    fn with_reward_clip(self, min: f32, max: f32) -> WrappedEnvBuilder<Self, RewardClipWrapper> {
+        self.with_wrapper(RewardClipWrapper::new(min, max))
+    }
+
+    /// Clips every observation to `[min, max]`. See [`ObservationClip`].
+    fn with_observation_clip(
+        self,
+        min: f32,
+        max: f32,
+    ) -> WrappedEnvBuilder<Self, ObservationClipWrapper> {
+        self.with_wrapper(ObservationClipWrapper::new(min, max))
+    }
+
+    /// Clips actions to the bounds of a `Box` action space. See [`ClipAction`].
+    fn with_clip_action(self) -> WrappedEnvBuilder<Self, ClipActionWrapper> {
+        self.with_wrapper(ClipActionWrapper)
+    }
+
+    /// Maps every observation through `f`. See [`TransformObservation`].
+    ///
+    /// The observation space is kept as is. Use
+    /// [`with_transform_observation_space`](Self::with_transform_observation_space)
+    /// when `f` changes the observation shape or bounds.
+    fn with_transform_observation<F>(
+        self,
+        f: F,
+    ) -> WrappedEnvBuilder<Self, TransformObservationWrapper<F, EnvTensor<Self::Env>>>
+    where
+        F: Fn(EnvTensor<Self::Env>) -> EnvTensor<Self::Env> + Send + Sync + 'static,
+    {
+        self.with_wrapper(TransformObservationWrapper::new(f))
+    }
+
+    /// Maps every observation through `f` and reports `observation_space` instead
+    /// of the inner observation space.
+    fn with_transform_observation_space<F>(
+        self,
+        f: F,
+        observation_space: Space<EnvTensor<Self::Env>>,
+    ) -> WrappedEnvBuilder<Self, TransformObservationWrapper<F, EnvTensor<Self::Env>>>
+    where
+        F: Fn(EnvTensor<Self::Env>) -> EnvTensor<Self::Env> + Send + Sync + 'static,
+    {
+        self.with_wrapper(
+            TransformObservationWrapper::new(f).with_observation_space(observation_space),
+        )
+    }
+
+    /// Maps every reward through `f`. See [`TransformReward`].
+    fn with_transform_reward<F>(self, f: F) -> WrappedEnvBuilder<Self, TransformRewardWrapper<F>>
+    where
+        F: Fn(f32) -> f32 + Send + Sync + 'static,
+    {
+        self.with_wrapper(TransformRewardWrapper::new(f))
+    }
+}
+
+impl<EB: EnvBuilder> EnvBuilderExt for EB {}
+
+// Rejects clipping ranges `f32::clamp` would panic on.
+fn check_clip_range(min: f32, max: f32) -> Result<()> {
+    if min.is_nan() || max.is_nan() || min > max {
+        bail!("invalid clipping range [{min}, {max}]");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::marker::PhantomData;
+
+    use anyhow::Result;
+    use burn::backend::NdArray;
+
+    use super::{EnvBuilderExt, FrameStack};
+    use crate::{
+        env::{Env, EnvBuilder, EnvDescription, Snapshot, Space},
+        tensor::{R2lTensor, TensorData},
+    };
+
+    // Observation is the step counter, reward is 1 per step and the episode never terminates.
+    struct CounterEnv<T = TensorData> {
+        steps: usize,
+        tensor: PhantomData<T>,
+    }
+
+    impl<T: R2lTensor> Env for CounterEnv<T> {
+        type Tensor = T;
+
+        fn reset(&mut self, _seed: u64) -> Result<T> {
+            self.steps = 0;
+            Ok(T::from_vec_and_shape(vec![0., 0.], vec![2]))
+        }
+
+        fn step(&mut self, _action: T) -> Result<Snapshot<T>> {
+            self.steps += 1;
+            let state =
+                T::from_vec_and_shape(vec![self.steps as f32, -(self.steps as f32)], vec![2]);
+            Ok(Snapshot::new(state, 1., false, false))
+        }
+
+        fn env_description(&self) -> EnvDescription<T> {
+            let bound = |value: f32| Some(T::from_vec_and_shape(vec![value; 2], vec![2]));
+            EnvDescription::new(
+                Space::Box {
+                    min: bound(-100.),
+                    max: bound(100.),
+                    shape: vec![2],
+                },
+                Space::Discrete(2),
+            )
+        }
+    }
+
+    fn counter_env() -> Result<CounterEnv> {
+        Ok(CounterEnv {
+            steps: 0,
+            tensor: PhantomData,
+        })
+    }
+
+    // Observation is the last action, reward is its sum and actions are
+    // bounded to [-1, 1].
+    struct EchoEnv;
+
+    impl Env for EchoEnv {
+        type Tensor = TensorData;
+
+        fn reset(&mut self, _seed: u64) -> Result<TensorData> {
+            Ok(TensorData::from_vec(vec![0., 0.]))
+        }
+
+        fn step(&mut self, action: TensorData) -> Result<Snapshot<TensorData>> {
+            let reward = action.to_vec().iter().sum();
+            Ok(Snapshot::new(action, reward, false, false))
+        }
+
+        fn env_description(&self) -> EnvDescription<TensorData> {
+            let bound = |value: f32| Some(TensorData::from_vec(vec![value; 2]));
+            let space = |low, high| Space::Box {
+                min: bound(low),
+                max: bound(high),
+                shape: vec![2],
+            };
+            EnvDescription::new(space(-1., 1.), space(-1., 1.))
+        }
+    }
+
+    fn echo_env() -> Result<EchoEnv> {
+        Ok(EchoEnv)
+    }
+
+    #[test]
+    fn wrappers_chain_through_builders() -> Result<()> {
+        let builder = counter_env
+            .with_action_repeat(2)
+            .with_time_limit(3)
+            .with_frame_stack(3)
+            .with_reward_scale(0.5)
+            .with_observation_clip(-4., 4.);
+        let description = builder.env_description()?;
+        let Space::Box { min, max, shape } = &description.observation_space else {
+            panic!("expected a box observation space");
+        };
+        assert_eq!(shape, &vec![6]);
+        assert_eq!(description.observation_size(), 6);
+        assert_eq!(min.as_ref().unwrap().to_vec(), vec![-4.; 6]);
+        assert_eq!(max.as_ref().unwrap().to_vec(), vec![4.; 6]);
+
+        let mut env = builder.build_env()?;
+        let state = env.reset(0)?;
+        assert_eq!(state.to_shape(), vec![6]);
+        let action = TensorData::from_vec(vec![1., 0.]);
+
+        let snapshot = env.step(action.clone())?;
+        assert_eq!(snapshot.reward, 1.);
+        assert_eq!(snapshot.state.to_vec(), vec![0., 0., 0., 0., 2., -2.]);
+        assert!(!snapshot.truncated);
+
+        env.step(action.clone())?;
+        let snapshot = env.step(action)?;
+        assert_eq!(snapshot.state.to_vec(), vec![2., -2., 4., -4., 4., -4.]);
+        assert!(snapshot.truncated);
+        assert!(!snapshot.terminated);
+        Ok(())
+    }
+
+    #[test]
+    fn clip_action_clips_to_the_action_bounds() -> Result<()> {
+        let builder = echo_env.with_clip_action();
+        let Space::Box { min, max, .. } = builder.env_description()?.action_space else {
+            panic!("expected a box action space");
+        };
+        assert!(min.is_none() && max.is_none());
+        let mut env = builder.build_env()?;
+        env.reset(0)?;
+        let snapshot = env.step(TensorData::from_vec(vec![3., -0.5]))?;
+        assert_eq!(snapshot.state.to_vec(), vec![1., -0.5]);
+        Ok(())
+    }
+
+    #[test]
+    fn reward_clip_clips_rewards() -> Result<()> {
+        let mut env = echo_env.with_reward_clip(-1., 1.).build_env()?;
+        env.reset(0)?;
+        assert_eq!(env.step(TensorData::from_vec(vec![1., 1.]))?.reward, 1.);
+        assert_eq!(env.step(TensorData::from_vec(vec![-1., -1.]))?.reward, -1.);
+        assert_eq!(env.step(TensorData::from_vec(vec![0.25, 0.]))?.reward, 0.25);
+        Ok(())
+    }
+
+    #[test]
+    fn transforms_map_observations_and_rewards() -> Result<()> {
+        let double = |state: TensorData| {
+            TensorData::from_vec(state.to_vec().iter().map(|value| value * 2.).collect())
+        };
+        let builder = counter_env
+            .with_transform_observation(double)
+            .with_transform_reward(|reward| reward * 3. - 1.);
+        let Space::Box { max, .. } = builder.env_description()?.observation_space else {
+            panic!("expected a box observation space");
+        };
+        assert_eq!(max.unwrap().to_vec(), vec![100.; 2]);
+        let mut env = builder.build_env()?;
+        assert_eq!(env.reset(0)?.to_vec(), vec![0., 0.]);
+        let snapshot = env.step(TensorData::from_vec(vec![1., 0.]))?;
+        assert_eq!(snapshot.state.to_vec(), vec![2., -2.]);
+        assert_eq!(snapshot.reward, 2.);
+
+        let builder = counter_env.with_transform_observation_space(double, Space::Discrete(3));
+        assert!(matches!(
+            builder.env_description()?.observation_space,
+            Space::Discrete(3)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_parameters_are_errors() {
+        assert!(counter_env.with_action_repeat(0).build_env().is_err());
+        assert!(counter_env.with_time_limit(0).build_env().is_err());
+        assert!(counter_env.with_frame_stack(0).build_env().is_err());
+        assert!(counter_env.with_reward_clip(1., -1.).build_env().is_err());
+        assert!(
+            counter_env
+                .with_observation_clip(1., -1.)
+                .build_env()
+                .is_err()
+        );
+        assert!(
+            counter_env
+                .with_observation_clip(f32::NAN, 1.)
+                .build_env()
+                .is_err()
+        );
+    }
+
+    // Stacked observations and their bounds stay flat, so backends whose
+    // tensors are one-dimensional can hold them.
+    fn check_flat_frame_stack<T: R2lTensor>() -> Result<()> {
+        let counter = CounterEnv::<T> {
+            steps: 0,
+            tensor: PhantomData,
+        };
+        let mut env = FrameStack::new(counter, 3)?;
+        let Space::Box { min, max, shape } = env.env_description().observation_space else {
+            panic!("expected a box observation space");
+        };
+        assert_eq!(shape, vec![6]);
+        assert_eq!(min.unwrap().to_shape(), vec![6]);
+        assert_eq!(max.unwrap().to_vec(), vec![100.; 6]);
+
+        assert_eq!(env.reset(0)?.to_shape(), vec![6]);
+        let state = env.step(T::from_vec_and_shape(vec![1.], vec![1]))?.state;
+        assert_eq!(state.to_shape(), vec![6]);
+        assert_eq!(state.to_vec(), vec![0., 0., 0., 0., 1., -1.]);
+
+        let discrete = FrameStack::new(DiscreteEnv::<T>(PhantomData), 2)?;
+        let Space::Box { min, max, shape } = discrete.env_description().observation_space else {
+            panic!("expected a box observation space");
+        };
+        assert_eq!(shape, vec![6]);
+        assert_eq!(min.unwrap().to_vec(), vec![0.; 6]);
+        assert_eq!(max.unwrap().to_vec(), vec![1.; 6]);
+        Ok(())
+    }
+
+    // Only describes a discrete observation space.
+    struct DiscreteEnv<T>(PhantomData<T>);
+
+    impl<T: R2lTensor> Env for DiscreteEnv<T> {
+        type Tensor = T;
+
+        fn reset(&mut self, _seed: u64) -> Result<T> {
+            unimplemented!()
+        }
+
+        fn step(&mut self, _action: T) -> Result<Snapshot<T>> {
+            unimplemented!()
+        }
+
+        fn env_description(&self) -> EnvDescription<T> {
+            EnvDescription::new(Space::Discrete(3), Space::Discrete(2))
+        }
+    }
+
+    #[test]
+    fn frame_stacks_are_flat_for_every_backend() -> Result<()> {
+        check_flat_frame_stack::<TensorData>()?;
+        check_flat_frame_stack::<candle_core::Tensor>()?;
+        check_flat_frame_stack::<burn::tensor::Tensor<NdArray, 1>>()
+    }
+}
@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::{
+    env::{Env, EnvDescription, Snapshot, Space},
+    tensor::R2lTensor,
+    wrappers::{EnvWrapper, check_clip_range},
+};
+
+/// Clips every observation elementwise to a fixed range.
+///
+/// `Box` observation bounds are tightened to the clipping range, other
+/// observation spaces are reported unchanged.
+pub struct ObservationClip<E: Env> {
+    env: E,
+    min: f32,
+    max: f32,
+    description: EnvDescription<E::Tensor>,
+}
+
+impl<E: Env> ObservationClip<E> {
+    /// Wraps `env` so that observations are clipped to `[min, max]`.
+    ///
+    /// `min` must not be greater than `max`.
+    pub fn new(env: E, min: f32, max: f32) -> Result<Self> {
+        check_clip_range(min, max)?;
+        let inner = env.env_description();
+        let observation_space = match inner.observation_space {
+            Space::Box {
+                min: low,
+                max: high,
+                shape,
+            } => Space::Box {
+                min: Some(clip_bound(low, &shape, min, |value| value.max(min))),
+                max: Some(clip_bound(high, &shape, max, |value| value.min(max))),
+                shape,
+            },
+            space => space,
+        };
+        Ok(Self {
+            env,
+            min,
+            max,
+            description: EnvDescription::new(observation_space, inner.action_space),
+        })
+    }
+
+    /// Returns the wrapped environment.
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Env> Env for ObservationClip<E> {
+    type Tensor = E::Tensor;
+
+    fn reset(&mut self, seed: u64) -> Result<Self::Tensor> {
+        self.env.reset(seed)?.clamp(self.min, self.max)
+    }
+
+    fn step(&mut self, action: Self::Tensor) -> Result<Snapshot<Self::Tensor>> {
+        let snapshot = self.env.step(action)?;
+        Ok(Snapshot {
+            state: snapshot.state.clamp(self.min, self.max)?,
+            ..snapshot
+        })
+    }
+
+    fn env_description(&self) -> EnvDescription<Self::Tensor> {
+        self.description.clone()
+    }
+}
+
+// Tightens an optional bound with `clip`, or fills a missing bound with `fill`.
+fn clip_bound<T: R2lTensor>(
+    bound: Option<T>,
+    shape: &[usize],
+    fill: f32,
+    clip: impl Fn(f32) -> f32,
+) -> T {
+    let data = match bound {
+        Some(bound) => bound.to_vec().into_iter().map(clip).collect(),
+        None => vec![fill; shape.iter().product()],
+    };
+    T::from_vec_and_shape(data, shape.to_vec())
+}
+
+/// Maps every observation through a user-provided function.
+///
+/// The reported observation space is the inner one unless a replacement was
+/// provided, which is required whenever the function changes the observation
+/// shape or bounds.
+pub struct TransformObservation<E: Env, F> {
+    env: E,
+    f: Arc<F>,
+    description: EnvDescription<E::Tensor>,
+}
+
+impl<E: Env, F: Fn(E::Tensor) -> E::Tensor> TransformObservation<E, F> {
+    /// Wraps `env` so that observations are mapped through `f`.
+    pub fn new(env: E, f: F, observation_space: Option<Space<E::Tensor>>) -> Self {
+        Self::from_shared(env, Arc::new(f), observation_space)
+    }
+
+    fn from_shared(env: E, f: Arc<F>, observation_space: Option<Space<E::Tensor>>) -> Self {
+        let mut description = env.env_description();
+        if let Some(observation_space) = observation_space {
+            description.observation_space = observation_space;
+        }
+        Self {
+            env,
+            f,
+            description,
+        }
+    }
+
+    /// Returns the wrapped environment.
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Env, F: Fn(E::Tensor) -> E::Tensor> Env for TransformObservation<E, F> {
+    type Tensor = E::Tensor;
+
+    fn reset(&mut self, seed: u64) -> Result<Self::Tensor> {
+        Ok((self.f)(self.env.reset(seed)?))
+    }
+
+    fn step(&mut self, action: Self::Tensor) -> Result<Snapshot<Self::Tensor>> {
+        let snapshot = self.env.step(action)?;
+        Ok(Snapshot {
+            state: (self.f)(snapshot.state),
+            ..snapshot
+        })
+    }
+
+    fn env_description(&self) -> EnvDescription<Self::Tensor> {
+        self.description.clone()
+    }
+}
+
+/// [`EnvWrapper`] configuration for [`ObservationClip`].
+#[derive(Debug, Clone, Copy)]
+pub struct ObservationClipWrapper {
+    min: f32,
+    max: f32,
+}
+
+impl ObservationClipWrapper {
+    /// Creates an observation clip configuration.
+    pub fn new(min: f32, max: f32) -> Self {
+        Self { min, max }
+    }
+}
+
+impl<E: Env> EnvWrapper<E> for ObservationClipWrapper {
+    type Env = ObservationClip<E>;
+
+    fn wrap(&self, env: E) -> Result<Self::Env> {
+        ObservationClip::new(env, self.min, self.max)
+    }
+}
+
+/// [`EnvWrapper`] configuration for [`TransformObservation`].
+///
+/// The function is shared between all environments built from the same builder.
+pub struct TransformObservationWrapper<F, T: R2lTensor> {
+    f: Arc<F>,
+    observation_space: Option<Space<T>>,
+}
+
+impl<F, T: R2lTensor> TransformObservationWrapper<F, T> {
+    /// Creates an observation transform configuration.
+    pub fn new(f: F) -> Self {
+        Self {
+            f: Arc::new(f),
+            observation_space: None,
+        }
+    }
+
+    /// Reports `observation_space` instead of the inner observation space.
+    pub fn with_observation_space(mut self, observation_space: Space<T>) -> Self {
+        self.observation_space = Some(observation_space);
+        self
+    }
+}
+
+impl<E, F> EnvWrapper<E> for TransformObservationWrapper<F, E::Tensor>
+where
+    E: Env,
+    F: Fn(E::Tensor) -> E::Tensor + Send + Sync + 'static,
+{
+    type Env = TransformObservation<E, F>;
+
+    fn wrap(&self, env: E) -> Result<Self::Env> {
+        Ok(TransformObservation::from_shared(
+            env,
+            self.f.clone(),
+            self.observation_space.clone(),
+        ))
+    }
+}
@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::{
+    env::{Env, EnvDescription, Snapshot},
+    wrappers::{EnvWrapper, check_clip_range},
+};
+
+/// Multiplies every reward by a constant factor.
+pub struct RewardScale<E: Env> {
+    env: E,
+    scale: f32,
+}
+
+impl<E: Env> RewardScale<E> {
+    /// Wraps `env` so that rewards are multiplied by `scale`.
+    pub fn new(env: E, scale: f32) -> Self {
+        Self { env, scale }
+    }
+
+    /// Returns the wrapped environment.
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Env> Env for RewardScale<E> {
+    type Tensor = E::Tensor;
+
+    fn reset(&mut self, seed: u64) -> Result<Self::Tensor> {
+        self.env.reset(seed)
+    }
+
+    fn step(&mut self, action: Self::Tensor) -> Result<Snapshot<Self::Tensor>> {
+        let snapshot = self.env.step(action)?;
+        Ok(Snapshot {
+            reward: snapshot.reward * self.scale,
+            ..snapshot
+        })
+    }
+
+    fn env_description(&self) -> EnvDescription<Self::Tensor> {
+        self.env.env_description()
+    }
+}
+
+/// Clips every reward to a fixed range.
+pub struct RewardClip<E: Env> {
+    env: E,
+    min: f32,
+    max: f32,
+}
+
+impl<E: Env> RewardClip<E> {
+    /// Wraps `env` so that rewards are clipped to `[min, max]`.
+    ///
+    /// `min` must not be greater than `max`.
+    pub fn new(env: E, min: f32, max: f32) -> Result<Self> {
+        check_clip_range(min, max)?;
+        Ok(Self { env, min, max })
+    }
+
+    /// Returns the wrapped environment.
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Env> Env for RewardClip<E> {
+    type Tensor = E::Tensor;
+
+    fn reset(&mut self, seed: u64) -> Result<Self::Tensor> {
+        self.env.reset(seed)
+    }
+
+    fn step(&mut self, action: Self::Tensor) -> Result<Snapshot<Self::Tensor>> {
+        let snapshot = self.env.step(action)?;
+        Ok(Snapshot {
+            reward: snapshot.reward.clamp(self.min, self.max),
+            ..snapshot
+        })
+    }
+
+    fn env_description(&self) -> EnvDescription<Self::Tensor> {
+        self.env.env_description()
+    }
+}
+
+/// Maps every reward through a user-provided function.
+pub struct TransformReward<E: Env, F> {
+    env: E,
+    f: Arc<F>,
+}
+
+impl<E: Env, F: Fn(f32) -> f32> TransformReward<E, F> {
+    /// Wraps `env` so that rewards are mapped through `f`.
+    pub fn new(env: E, f: F) -> Self {
+        Self {
+            env,
+            f: Arc::new(f),
+        }
+    }
+
+    /// Returns the wrapped environment.
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Env, F: Fn(f32) -> f32> Env for TransformReward<E, F> {
+    type Tensor = E::Tensor;
+
+    fn reset(&mut self, seed: u64) -> Result<Self::Tensor> {
+        self.env.reset(seed)
+    }
+
+    fn step(&mut self, action: Self::Tensor) -> Result<Snapshot<Self::Tensor>> {
+        let snapshot = self.env.step(action)?;
+        Ok(Snapshot {
+            reward: (self.f)(snapshot.reward),
+            ..snapshot
+        })
+    }
+
+    fn env_description(&self) -> EnvDescription<Self::Tensor> {
+        self.env.env_description()
+    }
+}
+
+/// [`EnvWrapper`] configuration for [`RewardScale`].
+#[derive(Debug, Clone, Copy)]
+pub struct RewardScaleWrapper {
+    scale: f32,
+}
+
+impl RewardScaleWrapper {
+    /// Creates a reward scale configuration.
+    pub fn new(scale: f32) -> Self {
+        Self { scale }
+    }
+}
+
+impl<E: Env> EnvWrapper<E> for RewardScaleWrapper {
+    type Env = RewardScale<E>;
+
+    fn wrap(&self, env: E) -> Result<Self::Env> {
+        Ok(RewardScale::new(env, self.scale))
+    }
+}
+
+/// [`EnvWrapper`] configuration for [`RewardClip`].
+#[derive(Debug, Clone, Copy)]
+pub struct RewardClipWrapper {
+    min: f32,
+    max: f32,
+}
+
+impl RewardClipWrapper {
+    /// Creates a reward clip configuration.
+    pub fn new(min: f32, max: f32) -> Self {
+        Self { min, max }
+    }
+}
+
+impl<E: Env> EnvWrapper<E> for RewardClipWrapper {
+    type Env = RewardClip<E>;
+
+    fn wrap(&self, env: E) -> Result<Self::Env> {
+        RewardClip::new(env, self.min, self.max)
+    }
+}
+
+/// [`EnvWrapper`] configuration for [`TransformReward`].
+///
+/// The function is shared between all environments built from the same builder.
+pub struct TransformRewardWrapper<F> {
+    f: Arc<F>,
+}
+
+impl<F> TransformRewardWrapper<F> {
+    /// Creates a reward transform configuration.
+    pub fn new(f: F) -> Self {
+        Self { f: Arc::new(f) }
+    }
+}
+
+impl<E: Env, F: Fn(f32) -> f32 + Send + Sync + 'static> EnvWrapper<E>
+    for TransformRewardWrapper<F>
+{
+    type Env = TransformReward<E, F>;
+
+    fn wrap(&self, env: E) -> Result<Self::Env> {
+        Ok(TransformReward {
+            env,
+            f: self.f.clone(),
+        })
+    }
+}
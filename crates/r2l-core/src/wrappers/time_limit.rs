@@ -0,0 +1,80 @@
+use anyhow::{Result, bail};
+
+use crate::{
+    env::{Env, EnvDescription, Snapshot},
+    wrappers::EnvWrapper,
+};
+
+/// Truncates episodes after a fixed number of steps.
+///
+/// When the step budget runs out on a non-terminal transition, the returned
+/// snapshot is marked as `truncated`. The counter restarts on every reset.
+pub struct TimeLimit<E: Env> {
+    env: E,
+    max_episode_steps: usize,
+    elapsed_steps: usize,
+}
+
+impl<E: Env> TimeLimit<E> {
+    /// Wraps `env` with a step budget of `max_episode_steps`.
+    ///
+    /// `max_episode_steps` must be at least one.
+    pub fn new(env: E, max_episode_steps: usize) -> Result<Self> {
+        if max_episode_steps == 0 {
+            bail!("the time limit must allow at least one step");
+        }
+        Ok(Self {
+            env,
+            max_episode_steps,
+            elapsed_steps: 0,
+        })
+    }
+
+    /// Returns the wrapped environment.
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Env> Env for TimeLimit<E> {
+    type Tensor = E::Tensor;
+
+    fn reset(&mut self, seed: u64) -> Result<Self::Tensor> {
+        self.elapsed_steps = 0;
+        self.env.reset(seed)
+    }
+
+    fn step(&mut self, action: Self::Tensor) -> Result<Snapshot<Self::Tensor>> {
+        let mut snapshot = self.env.step(action)?;
+        self.elapsed_steps += 1;
+        if self.elapsed_steps >= self.max_episode_steps && !snapshot.terminated {
+            snapshot.truncated = true;
+        }
+        Ok(snapshot)
+    }
+
+    fn env_description(&self) -> EnvDescription<Self::Tensor> {
+        self.env.env_description()
+    }
+}
+
+/// [`EnvWrapper`] configuration for [`TimeLimit`].
+#[derive(Debug, Clone, Copy)]
+pub struct TimeLimitWrapper {
+    max_episode_steps: usize,
+}
+
+impl TimeLimitWrapper {
+    /// Creates a time limit configuration.
+    pub fn new(max_episode_steps: usize) -> Self {
+        Self { max_episode_steps }
+    }
+}
+
+impl<E: Env> EnvWrapper<E> for TimeLimitWrapper {
+    type Env = TimeLimit<E>;
+
+    fn wrap(&self, env: E) -> Result<Self::Env> {
+        TimeLimit::new(env, self.max_episode_steps)
+    }
+}
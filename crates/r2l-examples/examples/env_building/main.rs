@@ -1,6 +1,8 @@
 // ANCHOR: env_builders
 use anyhow::{Ok, Result};
-use r2l_api::{Env, EnvBuilder, EnvDescription, PPOAlgorithmBuilder, Snapshot, Space, TensorData};
+use r2l_api::{
+    Env, EnvBuilder, EnvDescription, PPOAlgorithmBuilder, Snapshot, Space, TensorData, check_env,
+};
 use r2l_gym::GymEnvBuilder;
 
 // Not a working implementation an actual env
@@ -48,6 +50,9 @@ fn build_env() -> Result<MyEnv> {
 }
 
 fn main() {
+    // Custom environments can be validated against their own description before training
+    check_env(&MyEnvBuilder).unwrap().into_result().unwrap();
+
     // Anything that implement Into<GymEnvBuilder> can be used with the PPOAlgorithmBuilder::gym
     // method. This includes &str, String and GymEnvBuilder itself (or your own implementation)
     let ppo_builder0 = PPOAlgorithmBuilder::gym("Pendulum-v1", 10);
@@ -34,7 +34,7 @@ fn main() {
     let distribution = DiagGaussianDistribution::<NdArray>::from_store(&mut store);
     let (episodes, environments) = (10, 10);
     let mut evaluator = Evaluator::gym(ENV_NAME, episodes, environments, SamplerExecutionMode::Vec);
-    let results = evaluator.eval(distribution);
+    let results = evaluator.eval(distribution).unwrap();
     let total_rewards = results
         .as_ref()
         .iter()
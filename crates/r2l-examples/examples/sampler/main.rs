@@ -1,6 +1,5 @@
 use candle_core::Device;
-use candle_nn::ParamsAdamW;
-use r2l_api::{EpisodeHookBound, PPOAgentBuilder, SamplerBuilder, StepHookBound};
+use r2l_api::{EpisodeHookBound, OptimizerConfig, PPOAgentBuilder, SamplerBuilder, StepHookBound};
 use r2l_gym::GymEnvBuilder;
 use r2l_sampler::SamplerExecutionMode;
 
@@ -34,7 +33,7 @@ fn main() {
         .with_weight_decay(1e-4)
         .with_joint(
             None,
-            ParamsAdamW {
+            OptimizerConfig::AdamW {
                 lr: 3e-4,
                 beta1: 0.9,
                 beta2: 0.99,
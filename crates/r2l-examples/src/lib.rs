@@ -17,6 +17,6 @@ mod test {
         let all_tensors = store.get_all_snapshots().unwrap();
         println!("{all_tensors:#?}");
 
-        let model = DiagGaussianDistribution::<NdArray>::from_store(&mut store);
+        let _model = DiagGaussianDistribution::<NdArray>::from_store(&mut store);
     }
 }
@@ -9,7 +9,10 @@
 //! The main entry points are:
 //! - [`GymEnv`], a concrete environment wrapper around a Python Gymnasium env
 //! - [`GymEnvBuilder`], an [`EnvBuilder`]
-//!   implementation that constructs named Gymnasium environments
+//!   implementation that constructs named Gymnasium environments, optionally
+//!   with `gymnasium.make` keyword arguments and Python wrappers
+//! - [`GymVectorEnv`], a `VectorEnv` over Gymnasium's sync or async vector
+//!   environments that steps every sub-environment in one Python call
 //!
 //! The adapter maps Gymnasium `Discrete`, `Box`, `MultiDiscrete`,
 //! `MultiBinary`, `Tuple`, and `Dict` spaces into `r2l-core` space metadata.
@@ -17,6 +20,8 @@
 //! observations are one-hot encoded, while structured `Tuple` and `Dict`
 //! observations are flattened recursively.
 
+use std::collections::BTreeMap;
+
 use anyhow::Result;
 use pyo3::{
     PyObject, PyResult, Python,
@@ -27,8 +32,14 @@ use r2l_core::{
     tensor::TensorData,
 };
 
+mod options;
 mod parse;
+mod vector;
+
+pub use options::{GymValue, GymWrapper};
+pub use vector::{GymVectorEnv, GymVectorMode};
 
+use options::kwargs_dict;
 use parse::{parse_action, parse_gym_space, parse_obs};
 
 /// Python-backed Gymnasium environment implementing `r2l`'s [`Env`] trait.
@@ -51,16 +62,27 @@ pub struct GymEnv {
 impl GymEnv {
     /// Creates a Gymnasium environment by name.
     ///
-    /// `render_mode` is forwarded to `gymnasium.make` when provided.
+    /// `render_mode` is forwarded to `gymnasium.make` when provided. Use
+    /// [`GymEnvBuilder`] to pass other `gymnasium.make` keyword arguments or
+    /// to apply Python wrappers.
     pub fn new(name: &str, render_mode: Option<String>) -> Result<GymEnv> {
+        let mut builder = GymEnvBuilder::new(name);
+        builder.render_mode = render_mode;
+        builder.build_env()
+    }
+
+    fn make(builder: &GymEnvBuilder) -> Result<GymEnv> {
         let env = Python::with_gil(|py| {
             let gym = py.import("gymnasium")?;
-            let kwargs = PyDict::new(py);
-            if let Some(render_mode) = render_mode {
+            let kwargs = kwargs_dict(py, &builder.kwargs)?;
+            if let Some(render_mode) = &builder.render_mode {
                 kwargs.set_item("render_mode", render_mode)?;
             }
             let make = gym.getattr("make")?;
-            let env = make.call((name,), Some(&kwargs))?;
+            let mut env = make.call((&builder.name,), Some(&kwargs))?;
+            for wrapper in &builder.wrappers {
+                env = wrapper.apply(env)?;
+            }
             let gym_spaces = py.import("gymnasium.spaces")?;
             let action_space = env.getattr("action_space")?;
             let action_space = parse_gym_space(&action_space, &gym_spaces)?;
@@ -118,24 +140,92 @@ impl Env for GymEnv {
 /// This is the standard way to plug Gymnasium environments into higher-level
 /// `r2l` builders such as `r2l_api::PPOAlgorithmBuilder` and
 /// `r2l_api::A2CAlgorithmBuilder`.
-pub struct GymEnvBuilder(String);
+///
+/// Besides the environment id, the builder can carry keyword arguments for
+/// `gymnasium.make`, a render mode, and an ordered list of Python wrappers
+/// that are applied after the environment is created:
+///
+/// ```ignore
+/// let builder = GymEnvBuilder::new("LunarLander-v3")
+///     .with_kwarg("continuous", true)
+///     .with_max_episode_steps(500)
+///     .with_python_wrapper("gymnasium.wrappers.NormalizeObservation");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GymEnvBuilder {
+    name: String,
+    kwargs: BTreeMap<String, GymValue>,
+    wrappers: Vec<GymWrapper>,
+    render_mode: Option<String>,
+}
 
 impl GymEnvBuilder {
     /// Creates a builder for the given Gymnasium environment id.
     pub fn new(name: &str) -> Self {
-        Self(name.to_owned())
+        Self {
+            name: name.to_owned(),
+            kwargs: BTreeMap::new(),
+            wrappers: vec![],
+            render_mode: None,
+        }
+    }
+
+    /// Returns the Gymnasium environment id.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Adds a keyword argument forwarded to `gymnasium.make`.
+    pub fn with_kwarg(mut self, key: &str, value: impl Into<GymValue>) -> Self {
+        self.kwargs.insert(key.to_owned(), value.into());
+        self
+    }
+
+    /// Adds several keyword arguments forwarded to `gymnasium.make`.
+    pub fn with_kwargs<K: Into<String>, V: Into<GymValue>>(
+        mut self,
+        kwargs: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        self.kwargs.extend(
+            kwargs
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into())),
+        );
+        self
+    }
+
+    /// Sets the `max_episode_steps` argument of `gymnasium.make`.
+    pub fn with_max_episode_steps(self, max_episode_steps: usize) -> Self {
+        self.with_kwarg("max_episode_steps", max_episode_steps)
+    }
+
+    /// Sets the render mode, e.g. `human` or `rgb_array`.
+    pub fn with_render_mode(mut self, render_mode: &str) -> Self {
+        self.render_mode = Some(render_mode.to_owned());
+        self
+    }
+
+    /// Appends a Python wrapper. Wrappers are applied in the order they are added.
+    pub fn with_python_wrapper(mut self, wrapper: impl Into<GymWrapper>) -> Self {
+        self.wrappers.push(wrapper.into());
+        self
+    }
+
+    /// Builds a [`GymVectorEnv`] with `num_envs` copies of this environment.
+    pub fn build_vector_env(&self, num_envs: usize, mode: GymVectorMode) -> Result<GymVectorEnv> {
+        GymVectorEnv::new(self, num_envs, mode)
     }
 }
 
 impl From<String> for GymEnvBuilder {
     fn from(value: String) -> Self {
-        Self(value)
+        Self::new(&value)
     }
 }
 
 impl From<&str> for GymEnvBuilder {
     fn from(value: &str) -> Self {
-        Self(value.to_owned())
+        Self::new(value)
     }
 }
 
@@ -143,6 +233,6 @@ impl EnvBuilder for GymEnvBuilder {
     type Env = GymEnv;
 
     fn build_env(&self) -> Result<Self::Env> {
-        GymEnv::new(&self.0, None)
+        GymEnv::make(self)
     }
 }
@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use pyo3::{
+    Bound, IntoPyObjectExt, PyAny, PyResult, Python,
+    types::{PyAnyMethods, PyDict, PyList, PyListMethods},
+};
+
+/// Python value passed as a keyword argument to `gymnasium.make` or to a
+/// Gymnasium wrapper constructor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GymValue {
+    /// Python `None`.
+    None,
+    /// Python `bool`.
+    Bool(bool),
+    /// Python `int`.
+    Int(i64),
+    /// Python `float`.
+    Float(f64),
+    /// Python `str`.
+    Str(String),
+    /// Python `list`.
+    List(Vec<GymValue>),
+    /// Python `dict` with string keys.
+    Dict(BTreeMap<String, GymValue>),
+}
+
+impl GymValue {
+    pub(crate) fn to_python<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        match self {
+            Self::None => Ok(py.None().into_bound(py)),
+            Self::Bool(value) => value.into_bound_py_any(py),
+            Self::Int(value) => value.into_bound_py_any(py),
+            Self::Float(value) => value.into_bound_py_any(py),
+            Self::Str(value) => value.into_bound_py_any(py),
+            Self::List(values) => {
+                let list = PyList::empty(py);
+                for value in values {
+                    list.append(value.to_python(py)?)?;
+                }
+                Ok(list.into_any())
+            }
+            Self::Dict(values) => Ok(kwargs_dict(py, values)?.into_any()),
+        }
+    }
+}
+
+impl From<bool> for GymValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for GymValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<i32> for GymValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value as i64)
+    }
+}
+
+impl From<usize> for GymValue {
+    fn from(value: usize) -> Self {
+        Self::Int(value as i64)
+    }
+}
+
+impl From<f64> for GymValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<f32> for GymValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value as f64)
+    }
+}
+
+impl From<&str> for GymValue {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_owned())
+    }
+}
+
+impl From<String> for GymValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl<T: Into<GymValue>> From<Vec<T>> for GymValue {
+    fn from(values: Vec<T>) -> Self {
+        Self::List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<GymValue>> From<Option<T>> for GymValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::None, Into::into)
+    }
+}
+
+pub(crate) fn kwargs_dict<'py>(
+    py: Python<'py>,
+    kwargs: &BTreeMap<String, GymValue>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for (key, value) in kwargs {
+        dict.set_item(key, value.to_python(py)?)?;
+    }
+    Ok(dict)
+}
+
+/// Python wrapper applied to a Gymnasium environment after `gymnasium.make`.
+///
+/// The wrapper is referenced by its fully qualified Python path, such as
+/// `gymnasium.wrappers.TimeAwareObservation` or
+/// `minigrid.wrappers.FlatObsWrapper`, and is constructed as
+/// `Wrapper(env, **kwargs)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GymWrapper {
+    path: String,
+    kwargs: BTreeMap<String, GymValue>,
+}
+
+impl GymWrapper {
+    /// Creates a wrapper from its fully qualified Python path.
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+            kwargs: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a keyword argument passed to the wrapper constructor.
+    pub fn with_kwarg(mut self, key: &str, value: impl Into<GymValue>) -> Self {
+        self.kwargs.insert(key.to_owned(), value.into());
+        self
+    }
+
+    /// Returns the fully qualified Python path of the wrapper.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub(crate) fn kwargs(&self) -> &BTreeMap<String, GymValue> {
+        &self.kwargs
+    }
+
+    pub(crate) fn module_and_name(&self) -> PyResult<(&str, &str)> {
+        self.path.rsplit_once('.').ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "wrapper path must be of the form `module.Wrapper`, got `{}`",
+                self.path
+            ))
+        })
+    }
+
+    pub(crate) fn apply<'py>(&self, env: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let py = env.py();
+        let (module, name) = self.module_and_name()?;
+        let wrapper = py.import(module)?.getattr(name)?;
+        let kwargs = kwargs_dict(py, &self.kwargs)?;
+        wrapper.call((env,), Some(&kwargs))
+    }
+}
+
+impl From<&str> for GymWrapper {
+    fn from(path: &str) -> Self {
+        Self::new(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use pyo3::{PyResult, Python, types::PyAnyMethods};
+
+    use super::{GymValue, GymWrapper, kwargs_dict};
+
+    #[test]
+    fn values_convert_to_python_literals() -> PyResult<()> {
+        let kwargs: BTreeMap<String, GymValue> = [
+            ("continuous", GymValue::from(true)),
+            ("gravity", (-9.5).into()),
+            ("max_episode_steps", 500usize.into()),
+            ("render_mode", Option::<&str>::None.into()),
+            ("shape", vec![64, 64].into()),
+            (
+                "wind",
+                GymValue::Dict([("power".to_owned(), "high".into())].into_iter().collect()),
+            ),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value))
+        .collect();
+        Python::with_gil(|py| {
+            let dict = kwargs_dict(py, &kwargs)?;
+            let repr: String = dict.repr()?.extract()?;
+            assert_eq!(
+                repr,
+                "{'continuous': True, 'gravity': -9.5, 'max_episode_steps': 500, \
+                 'render_mode': None, 'shape': [64, 64], 'wind': {'power': 'high'}}"
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn wrapper_paths_need_a_module() {
+        let wrapper = GymWrapper::new("rl_zoo3.wrappers.FrameSkip").with_kwarg("skip", 2);
+        assert_eq!(
+            wrapper.module_and_name().unwrap(),
+            ("rl_zoo3.wrappers", "FrameSkip")
+        );
+        assert_eq!(wrapper.kwargs()["skip"], GymValue::Int(2));
+        assert!(GymWrapper::from("FrameSkip").module_and_name().is_err());
+    }
+}
@@ -132,6 +132,18 @@ fn parse_child_actions<'py, 'space>(
     Ok(actions)
 }
 
+// One-hot encodes a Discrete observation, rejecting indices outside the space.
+fn one_hot(idx: usize, size: usize) -> PyResult<Vec<f32>> {
+    if idx >= size {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "discrete observation {idx} is outside of a space of size {size}"
+        )));
+    }
+    let mut values = vec![0.; size];
+    values[idx] = 1.;
+    Ok(values)
+}
+
 fn flatten_extract<'py, T: FromPyObject<'py>>(value: &Bound<'py, PyAny>) -> PyResult<Vec<T>> {
     value
         .call_method0("flatten")?
@@ -146,9 +158,7 @@ pub(crate) fn parse_obs(
     match space {
         Space::Discrete(size) => {
             let idx: usize = observation.extract()?;
-            let mut values = vec![0.; *size];
-            values[idx] = 1.;
-            Ok(TensorData::new(values, vec![*size]))
+            Ok(TensorData::new(one_hot(idx, *size)?, vec![*size]))
         }
         Space::Box { shape, .. }
         | Space::MultiDiscrete { shape, .. }
@@ -181,11 +191,157 @@ fn parse_obs_fields<'py>(
     Ok(TensorData::from_vec(data))
 }
 
+// Converts a batch of flat actions, one per vector-env slot, into the batched Python value expected
+// by a Gymnasium vector environment. Values are gathered into one numpy array per leaf space.
+pub(crate) fn parse_batched_actions<'py>(
+    py: Python<'py>,
+    actions: &[&[f32]],
+    space: &Space<TensorData>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let batched_shape = |shape: &[usize]| [&[actions.len()], shape].concat();
+    match space {
+        Space::Box {
+            min: Some(min),
+            max: Some(max),
+            shape,
+        } => {
+            let (min, max) = (&min.data, &max.data);
+            let values = actions
+                .iter()
+                .flat_map(|action| {
+                    action
+                        .iter()
+                        .zip(min.iter().zip(max))
+                        .map(|(value, (min, max))| value.clamp(*min, *max))
+                })
+                .collect();
+            action_array(py, values, &batched_shape(shape), "float32")
+        }
+        Space::Box { shape, .. } => {
+            action_array(py, actions.concat(), &batched_shape(shape), "float32")
+        }
+        Space::Discrete(_) => {
+            let indices: Vec<f32> = actions
+                .iter()
+                .map(|action| action.iter().position(|i| *i > 0.).unwrap() as f32)
+                .collect();
+            action_array(py, indices, &[actions.len()], "int64")
+        }
+        Space::MultiDiscrete { shape, .. } => {
+            action_array(py, actions.concat(), &batched_shape(shape), "int64")
+        }
+        Space::MultiBinary { shape } => action_array(
+            py,
+            actions
+                .iter()
+                .flat_map(|action| action.iter().map(|v| if *v > 0. { 1. } else { 0. }))
+                .collect(),
+            &batched_shape(shape),
+            "int8",
+        ),
+        Space::Tuple(spaces) => {
+            let actions = parse_batched_child_actions(py, actions, spaces)?;
+            Ok(PyTuple::new(py, actions)?.into_any())
+        }
+        Space::Dict(spaces) => {
+            let parsed_actions = parse_batched_child_actions(py, actions, spaces.values())?;
+            let batched = PyDict::new(py);
+            for (key, action) in spaces.keys().zip(parsed_actions) {
+                batched.set_item(key, action)?;
+            }
+            Ok(batched.into_any())
+        }
+    }
+}
+
+fn parse_batched_child_actions<'py, 'space>(
+    py: Python<'py>,
+    actions: &[&[f32]],
+    spaces: impl IntoIterator<Item = &'space Space<TensorData>>,
+) -> PyResult<Vec<Bound<'py, PyAny>>> {
+    let mut offset = 0;
+    let mut parsed = Vec::new();
+    for space in spaces {
+        let end = offset + space.size();
+        let children: Vec<&[f32]> = actions.iter().map(|action| &action[offset..end]).collect();
+        parsed.push(parse_batched_actions(py, &children, space)?);
+        offset = end;
+    }
+    Ok(parsed)
+}
+
+// Converts a batched observation of a Gymnasium vector environment into one flat observation per
+// slot, reading every leaf array with a single conversion.
+pub(crate) fn parse_batched_obs(
+    observations: &Bound<'_, PyAny>,
+    space: &Space<TensorData>,
+    num_envs: usize,
+) -> PyResult<Vec<Vec<f32>>> {
+    match space {
+        Space::Discrete(size) => {
+            let indices: Vec<usize> = flatten_extract(observations)?;
+            indices.into_iter().map(|idx| one_hot(idx, *size)).collect()
+        }
+        Space::Box { .. } | Space::MultiDiscrete { .. } | Space::MultiBinary { .. } => {
+            let values: Vec<f32> = flatten_extract(observations)?;
+            Ok(values
+                .chunks(space.size())
+                .map(|chunk| chunk.to_vec())
+                .collect())
+        }
+        Space::Tuple(spaces) => parse_batched_obs_fields(
+            spaces
+                .iter()
+                .enumerate()
+                .map(|(idx, space)| Ok((observations.get_item(idx)?, space))),
+            num_envs,
+        ),
+        Space::Dict(spaces) => parse_batched_obs_fields(
+            spaces
+                .iter()
+                .map(|(key, space)| Ok((observations.get_item(key)?, space))),
+            num_envs,
+        ),
+    }
+}
+
+fn parse_batched_obs_fields<'py>(
+    fields: impl IntoIterator<Item = PyResult<(Bound<'py, PyAny>, &'py Space<TensorData>)>>,
+    num_envs: usize,
+) -> PyResult<Vec<Vec<f32>>> {
+    let mut data = vec![Vec::new(); num_envs];
+    for field in fields {
+        let (value, space) = field?;
+        for (env_data, field_data) in data
+            .iter_mut()
+            .zip(parse_batched_obs(&value, space, num_envs)?)
+        {
+            env_data.extend(field_data);
+        }
+    }
+    Ok(data)
+}
+
 #[cfg(test)]
 mod tests {
-    use pyo3::{PyResult, Python, types::PyAnyMethods};
+    use pyo3::{
+        Bound, PyAny, PyResult, Python,
+        types::{PyAnyMethods, PyModule},
+    };
+    use r2l_core::env::Space;
 
-    use super::{parse_action, parse_gym_space};
+    use super::{parse_action, parse_batched_obs, parse_gym_space};
+
+    // a stand-in for the numpy array a vector environment returns
+    fn batch<'py>(py: Python<'py>, values: Vec<usize>) -> PyResult<Bound<'py, PyAny>> {
+        let module = PyModule::from_code(
+            py,
+            c"class Batch(list):\n    def flatten(self):\n        return self\n\n    def tolist(self):\n        return list(self)\n",
+            c"batch.py",
+            c"batch",
+        )?;
+        module.getattr("Batch")?.call1((values,))
+    }
 
     #[test]
     fn fundamental_space_shapes_match_gymnasium() -> PyResult<()> {
@@ -221,4 +377,25 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn batched_discrete_observations_are_one_hot() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let observations = batch(py, vec![2, 0])?;
+            let parsed = parse_batched_obs(&observations, &Space::Discrete(3), 2)?;
+
+            assert_eq!(parsed, vec![vec![0., 0., 1.], vec![1., 0., 0.]]);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn batched_discrete_observations_outside_the_space_are_rejected() -> PyResult<()> {
+        Python::with_gil(|py| {
+            let observations = batch(py, vec![1, 3])?;
+
+            assert!(parse_batched_obs(&observations, &Space::Discrete(3), 2).is_err());
+            Ok(())
+        })
+    }
 }
@@ -0,0 +1,172 @@
+use anyhow::{Result, bail};
+use pyo3::{
+    PyObject, PyResult, Python,
+    types::{PyAnyMethods, PyDict, PyDictMethods},
+};
+use r2l_core::{
+    env::{EnvDescription, Snapshot, Space, VectorEnv},
+    tensor::TensorData,
+};
+
+use crate::{
+    GymEnvBuilder,
+    options::kwargs_dict,
+    parse::{parse_batched_actions, parse_batched_obs, parse_gym_space},
+};
+
+/// Gymnasium vectorization backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GymVectorMode {
+    /// `gymnasium.vector.SyncVectorEnv`: sub-environments are stepped sequentially in-process.
+    #[default]
+    Sync,
+    /// `gymnasium.vector.AsyncVectorEnv`: sub-environments run in Python subprocesses.
+    Async,
+}
+
+impl GymVectorMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Sync => "sync",
+            Self::Async => "async",
+        }
+    }
+}
+
+/// Gymnasium vector environment implementing `r2l`'s [`VectorEnv`] trait.
+///
+/// All sub-environments are stepped with a single Python call, and batched
+/// numpy observations are converted into [`TensorData`] leaf by leaf instead
+/// of slot by slot. Use it with `R2lSampler::from_vector_env`, where one
+/// worker serves every slot.
+///
+/// The vector environment is created through `gymnasium.make_vec` with
+/// `AutoresetMode.SAME_STEP`, so a slot that finishes an episode returns the
+/// first observation of its next episode in the same step, which is the
+/// `next_state` convention of the `r2l` samplers. Gymnasium 1.1 or newer is
+/// required.
+pub struct GymVectorEnv {
+    env: PyObject,
+    num_envs: usize,
+    action_space: Space<TensorData>,
+    observation_space: Space<TensorData>,
+}
+
+impl GymVectorEnv {
+    /// Creates `num_envs` copies of the environment described by `builder`.
+    ///
+    /// Keyword arguments, render mode, and Python wrappers of the builder are
+    /// applied to every sub-environment.
+    pub fn new(builder: &GymEnvBuilder, num_envs: usize, mode: GymVectorMode) -> Result<Self> {
+        Python::with_gil(|py| {
+            let gym = py.import("gymnasium")?;
+            let vector = py.import("gymnasium.vector")?;
+            if !vector.hasattr("AutoresetMode")? {
+                bail!("GymVectorEnv requires gymnasium >= 1.1 for same-step autoreset");
+            }
+            let same_step = vector.getattr("AutoresetMode")?.getattr("SAME_STEP")?;
+            let vector_kwargs = PyDict::new(py);
+            vector_kwargs.set_item("autoreset_mode", &same_step)?;
+
+            let partial = py.import("functools")?.getattr("partial")?;
+            let mut wrappers = Vec::with_capacity(builder.wrappers.len());
+            for wrapper in &builder.wrappers {
+                let (module, name) = wrapper.module_and_name()?;
+                let wrapper_class = py.import(module)?.getattr(name)?;
+                let wrapper_kwargs = kwargs_dict(py, wrapper.kwargs())?;
+                wrappers.push(partial.call((wrapper_class,), Some(&wrapper_kwargs))?);
+            }
+
+            let kwargs = kwargs_dict(py, &builder.kwargs)?;
+            if let Some(render_mode) = &builder.render_mode {
+                kwargs.set_item("render_mode", render_mode)?;
+            }
+            kwargs.set_item("num_envs", num_envs)?;
+            kwargs.set_item("vectorization_mode", mode.as_str())?;
+            kwargs.set_item("vector_kwargs", vector_kwargs)?;
+            kwargs.set_item("wrappers", wrappers)?;
+            let env = gym
+                .getattr("make_vec")?
+                .call((&builder.name,), Some(&kwargs))?;
+
+            let autoreset_mode = env.getattr("metadata")?.get_item("autoreset_mode")?;
+            if !autoreset_mode.eq(&same_step)? {
+                bail!("vector environment does not use same-step autoreset: {autoreset_mode}");
+            }
+            let gym_spaces = py.import("gymnasium.spaces")?;
+            let action_space = env.getattr("single_action_space")?;
+            let action_space = parse_gym_space(&action_space, &gym_spaces)?;
+            let observation_space = env.getattr("single_observation_space")?;
+            let observation_space = parse_gym_space(&observation_space, &gym_spaces)?;
+            Ok(GymVectorEnv {
+                env: env.into(),
+                num_envs,
+                action_space,
+                observation_space,
+            })
+        })
+    }
+
+    fn tensors(&self, observations: Vec<Vec<f32>>) -> Vec<TensorData> {
+        let shape = self.observation_space.tensor_shape();
+        observations
+            .into_iter()
+            .map(|observation| TensorData::new(observation, shape.clone()))
+            .collect()
+    }
+
+    /// Closes the Python vector environment and its sub-environments.
+    pub fn close(&mut self) -> Result<()> {
+        Python::with_gil(|py| self.env.call_method0(py, "close"))?;
+        Ok(())
+    }
+}
+
+impl VectorEnv for GymVectorEnv {
+    type Tensor = TensorData;
+
+    fn num_envs(&self) -> usize {
+        self.num_envs
+    }
+
+    fn reset(&mut self, seeds: &[u64]) -> Result<Vec<TensorData>> {
+        let observations = Python::with_gil(|py| {
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("seed", seeds.to_vec())?;
+            let reset = self.env.call_method(py, "reset", (), Some(&kwargs))?;
+            let reset = reset.bind(py);
+            parse_batched_obs(&reset.get_item(0)?, &self.observation_space, self.num_envs)
+        })?;
+        Ok(self.tensors(observations))
+    }
+
+    fn step(&mut self, actions: Vec<TensorData>) -> Result<Vec<Snapshot<TensorData>>> {
+        let (observations, rewards, terminated, truncated) = Python::with_gil(|py| {
+            let actions: Vec<&[f32]> = actions.iter().map(|action| &action.data[..]).collect();
+            let actions = parse_batched_actions(py, &actions, &self.action_space)?;
+            let step = self.env.call_method1(py, "step", (actions,))?;
+            let step = step.bind(py);
+            let observations =
+                parse_batched_obs(&step.get_item(0)?, &self.observation_space, self.num_envs)?;
+            let tolist = |idx: usize| step.get_item(idx)?.call_method0("tolist");
+            let rewards: Vec<f32> = tolist(1)?.extract()?;
+            let terminated: Vec<bool> = tolist(2)?.extract()?;
+            let truncated: Vec<bool> = tolist(3)?.extract()?;
+            PyResult::Ok((observations, rewards, terminated, truncated))
+        })?;
+        let snapshots = self
+            .tensors(observations)
+            .into_iter()
+            .zip(rewards)
+            .zip(terminated.into_iter().zip(truncated))
+            .map(|((state, reward), (terminated, truncated))| {
+                Snapshot::new(state, reward, terminated, truncated)
+            })
+            .collect();
+        Ok(snapshots)
+    }
+
+    fn env_description(&self) -> EnvDescription<TensorData> {
+        EnvDescription::new(self.observation_space.clone(), self.action_space.clone())
+    }
+}
@@ -1,6 +1,7 @@
 // R2l sampler where each worker writes directly to the output buffer. This is preferred, when the
 // raw observations and rewards are to be stored.
 
+pub mod vector;
 pub mod worker;
 
 use std::sync::Arc;
@@ -12,12 +13,15 @@ use r2l_core::buffers::buffer::TrajectoryView;
 use r2l_core::env::Env;
 use r2l_core::env::EnvBuilder;
 use r2l_core::env::EnvBuilderType;
+use r2l_core::env::VectorEnv;
+use r2l_core::episode::EpisodeMonitor;
 use r2l_core::models::Actor;
 use r2l_core::on_policy::algorithm::Sampler;
 use r2l_core::rng::{sample_u64, set_seed};
 
 use crate::RolloutMode;
 use crate::SamplerExecutionMode;
+use crate::direct::vector::VectorWorker;
 use crate::direct::worker::ThreadHandle;
 use crate::direct::worker::ThreadWorker;
 use crate::direct::worker::ThreadWorkers;
@@ -40,11 +44,12 @@ pub trait SamplerHook {
 pub struct R2lSamplerCore<E: Env> {
     pub buffers: ArrayHandle<TrajectoryBuffer<E::Tensor>>,
     pub worker_pool: WorkerPool<E>,
+    pub episode_monitor: EpisodeMonitor,
 }
 
 impl<E: Env> R2lSamplerCore<E> {
-    pub fn reset_all_envs(&mut self) {
-        self.worker_pool.reset_all_envs();
+    pub fn reset_all_envs(&mut self) -> anyhow::Result<()> {
+        self.worker_pool.reset_all_envs()
     }
 
     pub fn build<EB: EnvBuilder<Env = E>>(
@@ -54,6 +59,7 @@ impl<E: Env> R2lSamplerCore<E> {
         let num_envs = env_builder.num_envs();
         let buffers: Vec<TrajectoryBuffer<E::Tensor>> = vec![TrajectoryBuffer::default(); num_envs];
         let (buffers, buffer_handlers) = bimodal_array(buffers);
+        let episode_monitor = EpisodeMonitor::new();
         let worker_pool = match execution_mode {
             SamplerExecutionMode::Vec => {
                 let workers: Vec<_> = buffer_handlers
@@ -61,7 +67,7 @@ impl<E: Env> R2lSamplerCore<E> {
                     .enumerate()
                     .map(|(idx, element_handle)| {
                         let env = env_builder.build_idx(idx).unwrap();
-                        Worker::new(env, element_handle)
+                        Worker::new(env, element_handle, episode_monitor.clone())
                     })
                     .collect();
                 WorkerPool::Vec(workers)
@@ -76,10 +82,11 @@ impl<E: Env> R2lSamplerCore<E> {
                         let (res_tx, res_rx) = crossbeam::channel::unbounded();
                         let env_builder = env_builder.clone();
                         let worker_seed = sample_u64();
+                        let episode_monitor = episode_monitor.clone();
                         let handle = std::thread::spawn(move || {
                             set_seed(worker_seed);
                             let env = env_builder.build_idx(idx).unwrap();
-                            let worker = Worker::new(env, element_handle);
+                            let worker = Worker::new(env, element_handle, episode_monitor);
                             let mut thread_worker = ThreadWorker::new(worker, command_rx, res_tx);
                             thread_worker.work();
                         });
@@ -92,6 +99,27 @@ impl<E: Env> R2lSamplerCore<E> {
         Self {
             buffers,
             worker_pool,
+            episode_monitor,
+        }
+    }
+
+    /// Builds a core in which a single [`VectorWorker`] steps every slot of `vector_env`.
+    pub fn from_vector_env<V: VectorEnv<Tensor = E::Tensor> + Send + 'static>(
+        vector_env: V,
+    ) -> Self {
+        let num_envs = vector_env.num_envs();
+        let buffers: Vec<TrajectoryBuffer<E::Tensor>> = vec![TrajectoryBuffer::default(); num_envs];
+        let (buffers, buffer_handlers) = bimodal_array(buffers);
+        let episode_monitor = EpisodeMonitor::new();
+        let worker = VectorWorker::new(
+            Box::new(vector_env),
+            buffer_handlers,
+            episode_monitor.clone(),
+        );
+        Self {
+            buffers,
+            worker_pool: WorkerPool::Vector(worker),
+            episode_monitor,
         }
     }
 }
@@ -112,24 +140,43 @@ impl<E: Env, H: SamplerHook<E = E>> R2lSampler<E, H> {
             hook,
         }
     }
+
+    /// Builds a sampler over a [`VectorEnv`], served by a single worker.
+    ///
+    /// `E` only fixes the tensor type and the hook, e.g. `GymEnv` for a
+    /// Gymnasium vector environment.
+    pub fn from_vector_env<V: VectorEnv<Tensor = E::Tensor> + Send + 'static>(
+        vector_env: V,
+        hook: H,
+    ) -> Self {
+        Self {
+            core: R2lSamplerCore::from_vector_env(vector_env),
+            hook,
+        }
+    }
 }
 
 impl<E: Env, H: SamplerHook<E = E>> Sampler for R2lSampler<E, H> {
     type Tensor = E::Tensor;
 
-    fn reset_all_envs(&mut self) {
-        self.core.reset_all_envs();
+    fn reset_all_envs(&mut self) -> anyhow::Result<()> {
+        self.core.reset_all_envs()?;
         self.hook.reset();
+        Ok(())
     }
 
-    fn collect_rollouts<A: Actor<Tensor = Self::Tensor> + Clone>(&mut self, actor: A) {
+    fn collect_rollouts<A: Actor<Tensor = Self::Tensor> + Clone>(
+        &mut self,
+        actor: A,
+    ) -> anyhow::Result<()> {
         self.core.worker_pool.clear_buffers();
         self.core.worker_pool.set_actor(actor.clone());
+        self.core.episode_monitor.begin_rollout();
         loop {
             let result = self.hook.hook(&mut self.core);
             match result {
-                SamplerHookResult::Bound(bound) => self.core.worker_pool.collect(bound),
-                SamplerHookResult::Stop => break,
+                SamplerHookResult::Bound(bound) => self.core.worker_pool.collect(bound)?,
+                SamplerHookResult::Stop => return Ok(()),
             }
         }
     }
@@ -141,7 +188,95 @@ impl<E: Env, H: SamplerHook<E = E>> Sampler for R2lSampler<E, H> {
             .unwrap()
     }
 
+    fn episode_monitor(&self) -> Option<EpisodeMonitor> {
+        Some(self.core.episode_monitor.clone())
+    }
+
     fn shutdown(&mut self) {
         self.core.worker_pool.shutdown();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use r2l_core::{env::EnvBuilderType, on_policy::algorithm::Sampler};
+
+    use super::{R2lSampler, R2lSamplerCore, SamplerHook, SamplerHookResult};
+    use crate::{
+        NormalizerMode, RewardNormalizer, RolloutMode, SamplerExecutionMode,
+        test::{FixedRewardEnv, ZeroActor},
+    };
+
+    // Collects `n_steps` per environment once per rollout, and normalizes the
+    // collected rewards the way the step-bound hooks of `r2l-api` do.
+    struct StepBound {
+        n_steps: usize,
+        collected: bool,
+        reward_normalizer: Option<RewardNormalizer>,
+    }
+
+    impl SamplerHook for StepBound {
+        type E = FixedRewardEnv;
+
+        fn hook(&mut self, core: &mut R2lSamplerCore<FixedRewardEnv>) -> SamplerHookResult {
+            self.collected = !self.collected;
+            if !self.collected {
+                if let Some(normalizer) = &mut self.reward_normalizer {
+                    normalizer.normalize_buffers(&mut core.buffers.lock().unwrap());
+                }
+                SamplerHookResult::Stop
+            } else {
+                SamplerHookResult::Bound(RolloutMode::StepBound {
+                    n_steps: self.n_steps,
+                })
+            }
+        }
+    }
+
+    fn sampler(
+        build_env: fn() -> FixedRewardEnv,
+        execution_mode: SamplerExecutionMode,
+        reward_normalizer: Option<RewardNormalizer>,
+    ) -> R2lSampler<FixedRewardEnv, StepBound> {
+        let hook = StepBound {
+            n_steps: 8,
+            collected: false,
+            reward_normalizer,
+        };
+        let env_builder = EnvBuilderType::homogenous(move || Ok(build_env()), 2);
+        R2lSampler::build(env_builder, hook, execution_mode)
+    }
+
+    #[test]
+    fn env_errors_reach_the_caller() {
+        for execution_mode in [SamplerExecutionMode::Vec, SamplerExecutionMode::Thread] {
+            let mut sampler = sampler(|| FixedRewardEnv::failing_at(3), execution_mode, None);
+            let err = sampler.collect_rollouts(ZeroActor).unwrap_err();
+            assert_eq!(err.to_string(), "the environment broke");
+        }
+    }
+
+    #[test]
+    fn recorded_returns_are_raw_reward_sums() {
+        for execution_mode in [SamplerExecutionMode::Vec, SamplerExecutionMode::Thread] {
+            let reward_normalizer = RewardNormalizer::new(NormalizerMode::Update, 0.99, 10.);
+            let mut sampler = sampler(
+                || FixedRewardEnv::new(2., 3),
+                execution_mode,
+                Some(reward_normalizer),
+            );
+            sampler.collect_rollouts(ZeroActor).unwrap();
+
+            for buffer in sampler.core.buffers.lock().unwrap().iter() {
+                assert!(buffer.rewards().iter().all(|reward| *reward != 2.));
+            }
+            // two episodes of three steps end in each environment's eight steps
+            let episodes = sampler.episode_monitor().unwrap().rollout_episodes();
+            assert_eq!(episodes.len(), 4);
+            for episode in episodes {
+                assert_eq!(episode.reward, 6.);
+                assert_eq!(episode.length, 3);
+            }
+        }
+    }
+}
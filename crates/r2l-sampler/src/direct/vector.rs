@@ -0,0 +1,302 @@
+use anyhow::bail;
+use bimodal_array::ElementHandle;
+use r2l_core::{
+    buffers::{Memory, buffer::TrajectoryBuffer},
+    env::{EnvDescription, Snapshot, VectorEnv},
+    episode::{EpisodeMonitor, EpisodeTracker},
+    models::Actor,
+    rng::sample_u64,
+    tensor::R2lTensor,
+};
+
+use crate::{direct::RolloutMode, track_episode};
+
+/// Single worker serving every slot of a [`VectorEnv`].
+///
+/// Slot `i` of the vector environment writes into the `i`-th trajectory
+/// buffer, so hooks and trajectory views see one buffer per slot, exactly as
+/// with one worker per environment.
+pub struct VectorWorker<T: R2lTensor> {
+    pub env: Box<dyn VectorEnv<Tensor = T> + Send>,
+    pub buffers: Vec<ElementHandle<TrajectoryBuffer<T>>>,
+    pub actor: Option<Box<dyn Actor<Tensor = T>>>,
+    pub last_states: Option<Vec<T>>,
+    pub episodes: Vec<EpisodeTracker>,
+    pub episode_monitor: EpisodeMonitor,
+}
+
+impl<T: R2lTensor> VectorWorker<T> {
+    pub fn new(
+        env: Box<dyn VectorEnv<Tensor = T> + Send>,
+        buffers: Vec<ElementHandle<TrajectoryBuffer<T>>>,
+        episode_monitor: EpisodeMonitor,
+    ) -> Self {
+        assert_eq!(env.num_envs(), buffers.len());
+        Self {
+            episodes: vec![EpisodeTracker::new(); buffers.len()],
+            env,
+            buffers,
+            actor: None,
+            last_states: None,
+            episode_monitor,
+        }
+    }
+
+    pub fn num_envs(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn env_description(&self) -> EnvDescription<T> {
+        self.env.env_description()
+    }
+
+    pub fn clear(&mut self) {
+        for buffer in &mut self.buffers {
+            buffer.lock().unwrap().clear();
+        }
+    }
+
+    /// Steps every slot until `bound` is reached.
+    ///
+    /// Slots cannot be paused independently, so with an episode bound the
+    /// slots that already completed their episodes keep stepping until every
+    /// slot is done, and their transitions are recorded too.
+    pub fn collect(&mut self, bound: RolloutMode) -> anyhow::Result<()> {
+        let Some(actor) = &self.actor else {
+            bail!("the vector worker has no actor to collect rollouts with");
+        };
+        let mut buffers: Vec<_> = self
+            .buffers
+            .iter_mut()
+            .map(|buffer| buffer.lock().unwrap())
+            .collect();
+        let mut episodes = vec![0; buffers.len()];
+        let mut step = |episodes: &mut [usize]| -> anyhow::Result<()> {
+            let memories = step_envs(self.env.as_mut(), actor.as_ref(), &mut self.last_states)?;
+            for (idx, memory) in memories.into_iter().enumerate() {
+                if memory.is_done() {
+                    episodes[idx] += 1;
+                }
+                track_episode(&mut self.episodes[idx], &self.episode_monitor, &memory);
+                buffers[idx].push(memory);
+            }
+            Ok(())
+        };
+        match bound {
+            RolloutMode::EpisodeBound { n_episodes } => {
+                while episodes.iter().any(|episodes| *episodes < n_episodes) {
+                    step(&mut episodes)?;
+                }
+            }
+            RolloutMode::StepBound { n_steps } => {
+                for _ in 0..n_steps {
+                    step(&mut episodes)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // resets the initial states and clears the buffers. Used by the Evaluator hook
+    pub fn reset(&mut self) -> anyhow::Result<()> {
+        let states = self.env.reset(&seeds(self.num_envs()))?;
+        self.last_states = Some(states);
+        self.episodes.iter_mut().for_each(EpisodeTracker::reset);
+        self.clear();
+        Ok(())
+    }
+
+    pub fn set_last_states(&mut self, states: Vec<T>) {
+        assert_eq!(states.len(), self.num_envs());
+        self.last_states = Some(states);
+    }
+
+    pub fn replace_last_next_states(&mut self, states: Vec<T>) {
+        for (buffer, state) in self.buffers.iter_mut().zip(states) {
+            buffer.lock().unwrap().replace_last_next_state(state);
+        }
+    }
+
+    pub fn reset_envs_uninserted(&mut self) -> anyhow::Result<Vec<T>> {
+        self.env.reset(&seeds(self.num_envs()))
+    }
+}
+
+fn seeds(num_envs: usize) -> Vec<u64> {
+    (0..num_envs).map(|_| sample_u64()).collect()
+}
+
+// Steps every slot once and returns the transitions, in slot order.
+fn step_envs<T: R2lTensor>(
+    env: &mut (dyn VectorEnv<Tensor = T> + Send),
+    actor: &dyn Actor<Tensor = T>,
+    last_states: &mut Option<Vec<T>>,
+) -> anyhow::Result<Vec<Memory<T>>> {
+    let states = match last_states.take() {
+        Some(states) => states,
+        None => env.reset(&seeds(env.num_envs()))?,
+    };
+    let actions = states
+        .iter()
+        .map(|state| actor.action(state.clone()))
+        .collect::<anyhow::Result<Vec<T>>>()?;
+    let snapshots = env.step(actions.clone())?;
+    let memories: Vec<Memory<T>> = states
+        .into_iter()
+        .zip(actions)
+        .zip(snapshots)
+        .map(|((state, action), snapshot)| {
+            let Snapshot {
+                state: next_state,
+                reward,
+                terminated,
+                truncated,
+            } = snapshot;
+            Memory {
+                state,
+                next_state,
+                action,
+                reward,
+                terminated,
+                truncated,
+            }
+        })
+        .collect();
+    *last_states = Some(
+        memories
+            .iter()
+            .map(|memory| memory.next_state.clone())
+            .collect(),
+    );
+    Ok(memories)
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use bimodal_array::bimodal_array;
+    use r2l_core::{
+        buffers::buffer::TrajectoryBuffer,
+        env::{EnvDescription, Snapshot, Space, VectorEnv},
+        episode::EpisodeMonitor,
+        models::Actor,
+        tensor::TensorData,
+    };
+
+    use super::VectorWorker;
+    use crate::direct::RolloutMode;
+
+    // Slot `i` terminates every `episode_lengths[i]` steps and auto-resets.
+    struct CountdownEnv {
+        episode_lengths: Vec<usize>,
+        steps: Vec<usize>,
+    }
+
+    impl VectorEnv for CountdownEnv {
+        type Tensor = TensorData;
+
+        fn num_envs(&self) -> usize {
+            self.episode_lengths.len()
+        }
+
+        fn reset(&mut self, _seeds: &[u64]) -> Result<Vec<TensorData>> {
+            self.steps.iter_mut().for_each(|steps| *steps = 0);
+            Ok(vec![TensorData::from_vec(vec![0.]); self.num_envs()])
+        }
+
+        fn step(&mut self, _actions: Vec<TensorData>) -> Result<Vec<Snapshot<TensorData>>> {
+            Ok(self
+                .steps
+                .iter_mut()
+                .zip(&self.episode_lengths)
+                .map(|(steps, length)| {
+                    *steps += 1;
+                    let terminated = *steps == *length;
+                    if terminated {
+                        *steps = 0;
+                    }
+                    Snapshot {
+                        state: TensorData::from_vec(vec![*steps as f32]),
+                        reward: 1.,
+                        terminated,
+                        truncated: false,
+                    }
+                })
+                .collect())
+        }
+
+        fn env_description(&self) -> EnvDescription<TensorData> {
+            EnvDescription::new(
+                Space::Box {
+                    min: None,
+                    max: None,
+                    shape: vec![1],
+                },
+                Space::Discrete(2),
+            )
+        }
+    }
+
+    struct ZeroActor;
+
+    impl Actor for ZeroActor {
+        type Tensor = TensorData;
+
+        fn action(&self, _observation: TensorData) -> Result<TensorData> {
+            Ok(TensorData::from_vec(vec![0.]))
+        }
+    }
+
+    fn collect(
+        episode_lengths: Vec<usize>,
+        actor: Option<Box<dyn Actor<Tensor = TensorData>>>,
+        bound: RolloutMode,
+    ) -> Result<Vec<TrajectoryBuffer<TensorData>>> {
+        let num_envs = episode_lengths.len();
+        let env = CountdownEnv {
+            steps: vec![0; num_envs],
+            episode_lengths,
+        };
+        let (mut buffers, handles) = bimodal_array(vec![TrajectoryBuffer::default(); num_envs]);
+        let mut worker = VectorWorker::new(Box::new(env), handles, EpisodeMonitor::new());
+        worker.actor = actor;
+        worker.collect(bound)?;
+        drop(worker);
+        Ok(buffers.lock().unwrap().to_vec())
+    }
+
+    #[test]
+    fn collect_without_actor_returns_an_error() {
+        let result = collect(vec![2, 3], None, RolloutMode::StepBound { n_steps: 4 });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn step_bound_fills_every_slot_with_n_steps() -> Result<()> {
+        let buffers = collect(
+            vec![2, 3],
+            Some(Box::new(ZeroActor)),
+            RolloutMode::StepBound { n_steps: 4 },
+        )?;
+        assert!(buffers.iter().all(|buffer| buffer.len() == 4));
+        assert_eq!(buffers[0].terminated(), [false, true, false, true]);
+        assert_eq!(buffers[1].terminated(), [false, false, true, false]);
+        Ok(())
+    }
+
+    #[test]
+    fn episode_bound_keeps_transitions_of_finished_slots() -> Result<()> {
+        let buffers = collect(
+            vec![1, 3],
+            Some(Box::new(ZeroActor)),
+            RolloutMode::EpisodeBound { n_episodes: 2 },
+        )?;
+        // The slow slot needs six steps for two episodes; the fast slot keeps
+        // stepping alongside it and records all six transitions.
+        assert_eq!(buffers[0].len(), 6);
+        assert_eq!(buffers[1].len(), 6);
+        assert!(buffers[0].terminated().iter().all(|terminated| *terminated));
+        assert_eq!(buffers[1].terminated().iter().filter(|t| **t).count(), 2);
+        Ok(())
+    }
+}
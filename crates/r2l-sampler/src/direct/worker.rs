@@ -1,16 +1,21 @@
 use std::thread::JoinHandle;
 
+use anyhow::{Result, bail};
 use bimodal_array::ElementHandle;
 use crossbeam::channel::{Receiver, Sender};
 use r2l_core::{
     buffers::{Memory, buffer::TrajectoryBuffer},
     env::{Env, EnvDescription, Snapshot},
+    episode::{EpisodeMonitor, EpisodeTracker},
     models::Actor,
     rng::sample_u64,
     tensor::R2lTensor,
 };
 
-use crate::direct::RolloutMode;
+use crate::{
+    direct::{RolloutMode, vector::VectorWorker},
+    track_episode,
+};
 
 pub(crate) type CommandSender<T> = Sender<WorkerCommand<T>>;
 pub(crate) type CommandReceiver<T> = Receiver<WorkerCommand<T>>;
@@ -22,31 +27,31 @@ pub fn step_env<T: R2lTensor, E: Env<Tensor = T>>(
     env: &mut E,
     actor: &mut Box<dyn Actor<Tensor = T>>,
     last_state: Option<T>,
-) -> Memory<T> {
+) -> Result<Memory<T>> {
     let state = if let Some(state) = last_state {
         state
     } else {
-        env.reset(sample_u64()).unwrap()
+        env.reset(sample_u64())?
     };
-    let action = actor.action(state.clone()).unwrap();
+    let action = actor.action(state.clone())?;
     let Snapshot {
         state: mut next_state,
         reward,
         terminated,
         truncated,
-    } = env.step(action.clone()).unwrap();
+    } = env.step(action.clone())?;
     let done = terminated || truncated;
     if done {
-        next_state = env.reset(sample_u64()).unwrap();
+        next_state = env.reset(sample_u64())?;
     }
-    Memory {
+    Ok(Memory {
         state,
         next_state,
         action,
         reward,
         terminated,
         truncated,
-    }
+    })
 }
 
 pub enum WorkerCommand<T: R2lTensor> {
@@ -64,14 +69,14 @@ pub enum WorkerCommand<T: R2lTensor> {
 
 pub enum WorkerResult<T: R2lTensor> {
     PolicySet,
-    Collected,
-    EnvReset,
+    Collected(Result<()>),
+    EnvReset(Result<()>),
     BufferCleared,
     EnvDescription(EnvDescription<T>),
     Shutdown,
     LastState(Option<T>),
     LastStateSet,
-    ResetEnvUninsertedResult(T),
+    ResetEnvUninsertedResult(Result<T>),
     LastNextStateReplaced,
 }
 
@@ -124,15 +129,23 @@ pub struct Worker<E: Env> {
     pub buffer: ElementHandle<TrajectoryBuffer<E::Tensor>>,
     pub actor: Option<Box<dyn Actor<Tensor = E::Tensor>>>,
     pub last_state: Option<E::Tensor>,
+    pub episode: EpisodeTracker,
+    pub episode_monitor: EpisodeMonitor,
 }
 
 impl<E: Env> Worker<E> {
-    pub fn new(env: E, buffer: ElementHandle<TrajectoryBuffer<E::Tensor>>) -> Self {
+    pub fn new(
+        env: E,
+        buffer: ElementHandle<TrajectoryBuffer<E::Tensor>>,
+        episode_monitor: EpisodeMonitor,
+    ) -> Self {
         Self {
             env,
             buffer,
             actor: None,
             last_state: None,
+            episode: EpisodeTracker::new(),
+            episode_monitor,
         }
     }
 
@@ -151,9 +164,9 @@ impl<E: Env> Worker<E> {
         self.buffer.lock().unwrap().clear();
     }
 
-    pub fn collect(&mut self, bound: RolloutMode) {
+    pub fn collect(&mut self, bound: RolloutMode) -> Result<()> {
         let Some(actor) = &mut self.actor else {
-            todo!()
+            bail!("the worker has no actor to collect rollouts with");
         };
         let mut buffer = self.buffer.lock().unwrap();
         match bound {
@@ -161,9 +174,10 @@ impl<E: Env> Worker<E> {
                 let mut episodes = 0;
                 loop {
                     let last_state = self.last_state.take();
-                    let memory = step_env(&mut self.env, actor, last_state);
+                    let memory = step_env(&mut self.env, actor, last_state)?;
                     let terminates = memory.is_done();
                     self.last_state = Some(memory.next_state.clone());
+                    track_episode(&mut self.episode, &self.episode_monitor, &memory);
                     buffer.push(memory);
                     if terminates {
                         episodes += 1;
@@ -176,23 +190,27 @@ impl<E: Env> Worker<E> {
             RolloutMode::StepBound { n_steps } => {
                 for _ in 0..n_steps {
                     let last_state = self.last_state.take();
-                    let memory = step_env(&mut self.env, actor, last_state);
+                    let memory = step_env(&mut self.env, actor, last_state)?;
                     self.last_state = Some(memory.next_state.clone());
+                    track_episode(&mut self.episode, &self.episode_monitor, &memory);
                     buffer.push(memory);
                 }
             }
         }
+        Ok(())
     }
 
     // resets the initial state and clears the buffer. Used by the Evaluator hook
-    pub fn reset(&mut self, seed: u64) {
-        let state = self.env.reset(seed).unwrap();
+    pub fn reset(&mut self, seed: u64) -> Result<()> {
+        let state = self.env.reset(seed)?;
         self.last_state = Some(state);
+        self.episode.reset();
         self.buffer.lock().unwrap().clear();
+        Ok(())
     }
 
-    pub fn reset_env_uninserted(&mut self, seed: u64) -> E::Tensor {
-        self.env.reset(seed).unwrap()
+    pub fn reset_env_uninserted(&mut self, seed: u64) -> Result<E::Tensor> {
+        self.env.reset(seed)
     }
 }
 
@@ -220,8 +238,8 @@ impl<E: Env> ThreadWorker<E> {
                     self.tx.send(WorkerResult::PolicySet).unwrap();
                 }
                 WorkerCommand::Collect(bound) => {
-                    self.worker.collect(bound);
-                    self.tx.send(WorkerResult::Collected).unwrap();
+                    let result = self.worker.collect(bound);
+                    self.tx.send(WorkerResult::Collected(result)).unwrap();
                 }
                 WorkerCommand::GetEnvDescription => {
                     let environment_descriotion = self.worker.env.env_description();
@@ -234,8 +252,8 @@ impl<E: Env> ThreadWorker<E> {
                     break;
                 }
                 WorkerCommand::ResetEnv(seed) => {
-                    self.worker.reset(seed);
-                    self.tx.send(WorkerResult::EnvReset).unwrap();
+                    let result = self.worker.reset(seed);
+                    self.tx.send(WorkerResult::EnvReset(result)).unwrap();
                 }
                 WorkerCommand::ClearBuffer => {
                     self.worker.clear();
@@ -286,22 +304,38 @@ impl<T: R2lTensor> ThreadWorkers<T> {
         }
     }
 
-    pub fn collect_rollout(&self, bound: RolloutMode) {
+    pub fn collect_rollout(&self, bound: RolloutMode) -> Result<()> {
         for worker_handle in self.worker_handles.iter() {
             worker_handle.send(WorkerCommand::Collect(bound));
         }
-        for worker_handle in self.worker_handles.iter() {
-            worker_handle.recv();
-        }
+        let results: Vec<_> = self
+            .worker_handles
+            .iter()
+            .map(|worker_handle| {
+                let WorkerResult::Collected(result) = worker_handle.recv() else {
+                    unreachable!()
+                };
+                result
+            })
+            .collect();
+        results.into_iter().collect()
     }
 
-    pub fn reset_all(&self) {
+    pub fn reset_all(&self) -> Result<()> {
         for worker_handle in self.worker_handles.iter() {
             worker_handle.send(WorkerCommand::ResetEnv(sample_u64()));
         }
-        for worker_handle in self.worker_handles.iter() {
-            worker_handle.recv();
-        }
+        let results: Vec<_> = self
+            .worker_handles
+            .iter()
+            .map(|worker_handle| {
+                let WorkerResult::EnvReset(result) = worker_handle.recv() else {
+                    unreachable!()
+                };
+                result
+            })
+            .collect();
+        results.into_iter().collect()
     }
 
     pub fn get_last_states(&self) -> Option<Vec<T>> {
@@ -328,11 +362,13 @@ impl<T: R2lTensor> ThreadWorkers<T> {
         }
     }
 
-    pub fn reset_envs_uninserted(&self) -> Vec<T> {
+    pub fn reset_envs_uninserted(&self) -> Result<Vec<T>> {
         for worker_handle in self.worker_handles.iter() {
             worker_handle.send(WorkerCommand::ResetEnvUninserted(sample_u64()));
         }
-        self.worker_handles
+        // every result is received before the first error is returned
+        let states: Vec<_> = self
+            .worker_handles
             .iter()
             .map(|wh| {
                 let WorkerResult::ResetEnvUninsertedResult(state) = wh.recv() else {
@@ -340,7 +376,8 @@ impl<T: R2lTensor> ThreadWorkers<T> {
                 };
                 state
             })
-            .collect()
+            .collect();
+        states.into_iter().collect()
     }
 
     pub fn replace_last_next_states(&self, states: Vec<T>) {
@@ -372,6 +409,7 @@ impl<T: R2lTensor> ThreadWorkers<T> {
 pub enum WorkerPool<E: Env> {
     Vec(Vec<Worker<E>>),
     Thread(ThreadWorkers<E::Tensor>),
+    Vector(VectorWorker<E::Tensor>),
 }
 
 impl<E: Env> WorkerPool<E> {
@@ -383,6 +421,7 @@ impl<E: Env> WorkerPool<E> {
             Self::Thread(thread) => {
                 thread.clear_buffers();
             }
+            Self::Vector(worker) => worker.clear(),
         }
     }
 
@@ -390,6 +429,7 @@ impl<E: Env> WorkerPool<E> {
         match self {
             Self::Vec(workers) => workers[0].env.env_description(),
             Self::Thread(tw) => tw.env_description(),
+            Self::Vector(worker) => worker.env_description(),
         }
     }
 
@@ -403,29 +443,30 @@ impl<E: Env> WorkerPool<E> {
             Self::Thread(thread_workers) => {
                 thread_workers.set_policy(policy);
             }
+            Self::Vector(worker) => worker.actor = Some(Box::new(policy)),
         }
     }
 
-    pub fn collect(&mut self, bound: RolloutMode) {
+    pub fn collect(&mut self, bound: RolloutMode) -> Result<()> {
         match self {
             Self::Vec(workers) => {
                 for worker in workers {
-                    worker.collect(bound);
+                    worker.collect(bound)?;
                 }
+                Ok(())
             }
-            Self::Thread(thread_workers) => {
-                thread_workers.collect_rollout(bound);
-            }
+            Self::Thread(thread_workers) => thread_workers.collect_rollout(bound),
+            Self::Vector(worker) => worker.collect(bound),
         }
     }
 
-    pub fn single_step(&mut self) {
-        self.collect(RolloutMode::StepBound { n_steps: 1 });
+    pub fn single_step(&mut self) -> Result<()> {
+        self.collect(RolloutMode::StepBound { n_steps: 1 })
     }
 
     pub fn shutdown(&mut self) {
         match self {
-            Self::Vec(_) => {
+            Self::Vec(_) | Self::Vector(_) => {
                 // No need to explicitly shut down
             }
             Self::Thread(workers) => {
@@ -434,16 +475,16 @@ impl<E: Env> WorkerPool<E> {
         }
     }
 
-    pub fn reset_all_envs(&mut self) {
+    pub fn reset_all_envs(&mut self) -> Result<()> {
         match self {
             Self::Vec(workers) => {
                 for worker in workers {
-                    worker.reset(sample_u64());
+                    worker.reset(sample_u64())?;
                 }
+                Ok(())
             }
-            Self::Thread(workers) => {
-                workers.reset_all();
-            }
+            Self::Thread(workers) => workers.reset_all(),
+            Self::Vector(worker) => worker.reset(),
         }
     }
 
@@ -457,6 +498,8 @@ impl<E: Env> WorkerPool<E> {
                 // worker pools ensures the order
                 workers.get_last_states()
             }
+            // in the order of the slots
+            Self::Vector(worker) => worker.last_states.clone(),
         }
     }
 
@@ -470,6 +513,7 @@ impl<E: Env> WorkerPool<E> {
             Self::Thread(workers) => {
                 workers.set_last_states(states);
             }
+            Self::Vector(worker) => worker.set_last_states(states),
         }
     }
 
@@ -481,10 +525,11 @@ impl<E: Env> WorkerPool<E> {
                 }
             }
             Self::Thread(workers) => workers.replace_last_next_states(states),
+            Self::Vector(worker) => worker.replace_last_next_states(states),
         }
     }
 
-    pub fn reset_envs_uninserted(&mut self) -> Vec<E::Tensor> {
+    pub fn reset_envs_uninserted(&mut self) -> Result<Vec<E::Tensor>> {
         match self {
             Self::Vec(workers) => {
                 // resets all the envs but does not set it as a last state
@@ -494,6 +539,7 @@ impl<E: Env> WorkerPool<E> {
                     .collect()
             }
             Self::Thread(workers) => workers.reset_envs_uninserted(),
+            Self::Vector(worker) => worker.reset_envs_uninserted(),
         }
     }
 }
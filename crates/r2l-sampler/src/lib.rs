@@ -1,18 +1,29 @@
 mod direct;
 mod normalized;
 
+use r2l_core::{
+    buffers::Memory,
+    episode::{EpisodeMonitor, EpisodeTracker},
+};
+
+pub use direct::vector::VectorWorker;
 pub use direct::worker::WorkerPool;
 pub use direct::{R2lSampler, R2lSamplerCore, SamplerHook, SamplerHookResult};
 pub use normalized::{
     NormalizedSamplerHook, NormalizerMode, R2lNormalizedSampler, R2lNormalizedSamplerCore,
-    clipped_normalizer::ClippedNormalizer,
+    clipped_normalizer::ClippedNormalizer, reward_normalizer::RewardNormalizer,
 };
 
 /// Execution strategy used by the sampler.
 ///
 /// This controls whether environment workers run inline in the current thread
 /// or in dedicated background threads.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum SamplerExecutionMode {
     /// Run sampler workers inline in a local vector on the current thread.
     Vec,
@@ -25,3 +36,99 @@ pub enum RolloutMode {
     EpisodeBound { n_episodes: usize },
     StepBound { n_steps: usize },
 }
+
+// Records the raw reward of a transition. Both samplers call this before the sampler hooks get the
+// chance to rewrite the rewards in the buffers.
+pub(crate) fn track_episode<T>(
+    tracker: &mut EpisodeTracker,
+    monitor: &EpisodeMonitor,
+    memory: &Memory<T>,
+) {
+    if let Some(stats) = tracker.record(memory.reward, memory.is_done()) {
+        monitor.push(stats);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use anyhow::{Result, bail};
+    use r2l_core::{
+        env::{Env, EnvDescription, Snapshot, Space},
+        models::Actor,
+        tensor::TensorData,
+    };
+
+    // Pays `reward` on every step and terminates every `episode_length` steps. Stepping fails once
+    // `fail_at` steps have been taken.
+    pub(crate) struct FixedRewardEnv {
+        pub(crate) reward: f32,
+        pub(crate) episode_length: usize,
+        pub(crate) fail_at: Option<usize>,
+        pub(crate) steps: usize,
+        pub(crate) total_steps: usize,
+    }
+
+    impl FixedRewardEnv {
+        pub(crate) fn new(reward: f32, episode_length: usize) -> Self {
+            Self {
+                reward,
+                episode_length,
+                fail_at: None,
+                steps: 0,
+                total_steps: 0,
+            }
+        }
+
+        pub(crate) fn failing_at(fail_at: usize) -> Self {
+            Self {
+                fail_at: Some(fail_at),
+                ..Self::new(1., 4)
+            }
+        }
+    }
+
+    impl Env for FixedRewardEnv {
+        type Tensor = TensorData;
+
+        fn reset(&mut self, _seed: u64) -> Result<TensorData> {
+            self.steps = 0;
+            Ok(TensorData::from_vec(vec![0.]))
+        }
+
+        fn step(&mut self, _action: TensorData) -> Result<Snapshot<TensorData>> {
+            if self.fail_at == Some(self.total_steps) {
+                bail!("the environment broke");
+            }
+            self.steps += 1;
+            self.total_steps += 1;
+            Ok(Snapshot {
+                state: TensorData::from_vec(vec![self.steps as f32]),
+                reward: self.reward,
+                terminated: self.steps == self.episode_length,
+                truncated: false,
+            })
+        }
+
+        fn env_description(&self) -> EnvDescription<TensorData> {
+            EnvDescription::new(
+                Space::Box {
+                    min: None,
+                    max: None,
+                    shape: vec![1],
+                },
+                Space::Discrete(2),
+            )
+        }
+    }
+
+    #[derive(Clone)]
+    pub(crate) struct ZeroActor;
+
+    impl Actor for ZeroActor {
+        type Tensor = TensorData;
+
+        fn action(&self, _observation: TensorData) -> Result<TensorData> {
+            Ok(TensorData::from_vec(vec![0.]))
+        }
+    }
+}
@@ -5,13 +5,18 @@
 // afterwards.
 
 pub mod clipped_normalizer;
+pub mod reward_normalizer;
 mod worker;
 
 use bimodal_array::{ArrayHandle, bimodal_array, bimodal_array_with_factory};
 use itertools::Itertools;
 use r2l_core::{
-    buffers::buffer::{TrajectoryBuffer, TrajectoryView},
+    buffers::{
+        Memory,
+        buffer::{TrajectoryBuffer, TrajectoryView},
+    },
     env::{Env, EnvBuilder, EnvBuilderType},
+    episode::{EpisodeMonitor, EpisodeTracker},
     models::Actor,
     on_policy::algorithm::Sampler,
     rng::sample_u64,
@@ -22,9 +27,11 @@ use crate::{
     RolloutMode, SamplerExecutionMode, SamplerHookResult,
     normalized::{
         clipped_normalizer::ClippedNormalizer,
+        reward_normalizer::RewardNormalizer,
         worker::ThreadHandle,
         worker::{ThreadWorkerFactory, ThreadWorkers, VecWorkers, WorkerPool},
     },
+    track_episode,
 };
 
 pub trait NormalizedSamplerHook {
@@ -45,8 +52,13 @@ pub enum NormalizerMode {
 pub struct R2lNormalizedSamplerCore<E: Env<Tensor: R2lTensor>> {
     pub pool: WorkerPool<E>,
     pub obs_normalizer: Option<ClippedNormalizer<E::Tensor>>,
+    pub reward_normalizer: Option<RewardNormalizer>,
     pub last_states: ArrayHandle<E::Tensor>,
     pub buffers: Vec<TrajectoryBuffer<E::Tensor>>,
+    /// Unnormalized rewards of the current rollout, one vector per buffer.
+    pub raw_rewards: Vec<Vec<f32>>,
+    pub episode_trackers: Vec<EpisodeTracker>,
+    pub episode_monitor: EpisodeMonitor,
 }
 
 impl<E: Env<Tensor: R2lTensor>> R2lNormalizedSamplerCore<E> {
@@ -54,7 +66,7 @@ impl<E: Env<Tensor: R2lTensor>> R2lNormalizedSamplerCore<E> {
         env_builder: EnvBuilderType<EB>,
         execution_mode: SamplerExecutionMode,
         obs_normalizer: Option<ClippedNormalizer<E::Tensor>>,
-        _with_reward_normalizer: bool,
+        reward_normalizer: Option<RewardNormalizer>,
     ) -> Self {
         let num_envs = env_builder.num_envs();
         let buffers = vec![TrajectoryBuffer::default(); num_envs];
@@ -68,9 +80,13 @@ impl<E: Env<Tensor: R2lTensor>> R2lNormalizedSamplerCore<E> {
         }
         Self {
             buffers,
+            raw_rewards: vec![vec![]; num_envs],
             pool,
             last_states,
             obs_normalizer,
+            reward_normalizer,
+            episode_trackers: vec![EpisodeTracker::new(); num_envs],
+            episode_monitor: EpisodeMonitor::new(),
         }
     }
 
@@ -87,11 +103,7 @@ impl<E: Env<Tensor: R2lTensor>> R2lNormalizedSamplerCore<E> {
             envs.push(env);
         }
         let (last_states, last_state_handles) = bimodal_array(initial_states);
-        let workers = envs
-            .into_iter()
-            .zip(last_state_handles)
-            .map(|(env, handle)| (env, handle))
-            .collect();
+        let workers = envs.into_iter().zip(last_state_handles).collect();
         (last_states, WorkerPool::Vec(VecWorkers::new(workers)))
     }
 
@@ -115,11 +127,11 @@ impl<E: Env<Tensor: R2lTensor>> R2lNormalizedSamplerCore<E> {
         (last_states, WorkerPool::Thread(workers))
     }
 
-    pub fn collect(&mut self, bound: RolloutMode) {
+    pub fn collect(&mut self, bound: RolloutMode) -> anyhow::Result<()> {
         match bound {
             RolloutMode::StepBound { n_steps } => {
                 for _ in 0..n_steps {
-                    self.step();
+                    self.step()?;
                 }
             }
             RolloutMode::EpisodeBound { n_episodes } => {
@@ -132,7 +144,7 @@ impl<E: Env<Tensor: R2lTensor>> R2lNormalizedSamplerCore<E> {
                     if worker_idxs.is_empty() {
                         break;
                     }
-                    let terminations = self.step_indexed(&worker_idxs);
+                    let terminations = self.step_indexed(&worker_idxs)?;
                     for (idx, terminated) in worker_idxs.into_iter().zip(terminations) {
                         if terminated {
                             episode_counts[idx] += 1;
@@ -141,10 +153,11 @@ impl<E: Env<Tensor: R2lTensor>> R2lNormalizedSamplerCore<E> {
                 }
             }
         }
+        Ok(())
     }
 
-    fn step_indexed(&mut self, indices: &[usize]) -> Vec<bool> {
-        let multi_memory = self.pool.step_indexed(indices);
+    fn step_indexed(&mut self, indices: &[usize]) -> anyhow::Result<Vec<bool>> {
+        let multi_memory = self.pool.step_indexed(indices)?;
         if let Some(obs_normalizer) = &self.obs_normalizer {
             let mut last_states = self.last_states.lock().unwrap();
             let mut next_states = indices
@@ -156,36 +169,70 @@ impl<E: Env<Tensor: R2lTensor>> R2lNormalizedSamplerCore<E> {
                 last_states[*idx] = next_state;
             }
         }
-        let last_states = self.last_states.lock().unwrap();
-        let next_states = indices
-            .iter()
-            .map(|idx| last_states[*idx].clone())
-            .collect::<Vec<_>>();
-        let memories = multi_memory.into_memories(&next_states);
+        let next_states = {
+            let last_states = self.last_states.lock().unwrap();
+            indices
+                .iter()
+                .map(|idx| last_states[*idx].clone())
+                .collect::<Vec<_>>()
+        };
+        let mut memories = multi_memory.into_memories(&next_states);
+        self.record_raw_rewards(indices, &memories);
+        if let Some(reward_normalizer) = &mut self.reward_normalizer {
+            reward_normalizer.apply_in_place(indices, &mut memories);
+        }
         let terminations = memories.iter().map(|memory| memory.is_done()).collect();
         for (idx, memory) in indices.iter().zip(memories) {
             self.buffers[*idx].push(memory)
         }
-        terminations
+        Ok(terminations)
     }
 
-    fn step(&mut self) -> Vec<bool> {
-        let multi_memory = self.pool.step();
+    fn step(&mut self) -> anyhow::Result<Vec<bool>> {
+        let multi_memory = self.pool.step()?;
         if let Some(obs_normalizer) = &self.obs_normalizer {
             let mut last_states = self.last_states.lock().unwrap();
             obs_normalizer.apply_in_place(&mut last_states)
         }
-        let last_states = self.last_states.lock().unwrap();
-        let memories = multi_memory.into_memories(&last_states);
+        let mut memories = {
+            let last_states = self.last_states.lock().unwrap();
+            multi_memory.into_memories(&last_states)
+        };
+        let indices = (0..memories.len()).collect::<Vec<_>>();
+        self.record_raw_rewards(&indices, &memories);
+        if let Some(reward_normalizer) = &mut self.reward_normalizer {
+            reward_normalizer.apply_in_place(&indices, &mut memories);
+        }
         let terminations = memories.iter().map(|memory| memory.is_done()).collect();
         for (idx, memory) in memories.into_iter().enumerate() {
             self.buffers[idx].push(memory);
         }
-        terminations
+        Ok(terminations)
+    }
+
+    // episode statistics and raw rewards are recorded before reward normalization
+    fn record_raw_rewards(&mut self, indices: &[usize], memories: &[Memory<E::Tensor>]) {
+        for (idx, memory) in indices.iter().zip(memories) {
+            track_episode(
+                &mut self.episode_trackers[*idx],
+                &self.episode_monitor,
+                memory,
+            );
+            self.raw_rewards[*idx].push(memory.reward);
+        }
+    }
+
+    pub fn reset_episode_trackers(&mut self) {
+        self.episode_trackers
+            .iter_mut()
+            .for_each(|tracker| tracker.reset());
     }
 
     pub fn clear_buffers(&mut self) {
         self.buffers.iter_mut().for_each(|buffer| buffer.clear());
+        self.raw_rewards
+            .iter_mut()
+            .for_each(|rewards| rewards.clear());
     }
 
     pub fn set_policy<A: Actor<Tensor = E::Tensor> + Clone>(&mut self, policy: A) {
@@ -216,7 +263,7 @@ impl<E: Env<Tensor: R2lTensor>, H: NormalizedSamplerHook<E = E>> R2lNormalizedSa
         execution_mode: SamplerExecutionMode,
         with_obs_normalizer: Option<f32>,
         obs_normalizer_mode: NormalizerMode,
-        with_reward_normalizer: bool,
+        reward_normalizer: Option<RewardNormalizer>,
     ) -> Self {
         let env_description = env_builder.env_description().unwrap();
         let obs_normalizer = with_obs_normalizer.map(|clip| {
@@ -228,7 +275,7 @@ impl<E: Env<Tensor: R2lTensor>, H: NormalizedSamplerHook<E = E>> R2lNormalizedSa
                 env_builder,
                 execution_mode,
                 obs_normalizer,
-                with_reward_normalizer,
+                reward_normalizer,
             ),
             hook,
         }
@@ -239,14 +286,14 @@ impl<E: Env<Tensor: R2lTensor>, H: NormalizedSamplerHook<E = E>> R2lNormalizedSa
         hook: H,
         execution_mode: SamplerExecutionMode,
         obs_normalizer: Option<ClippedNormalizer<E::Tensor>>,
-        with_reward_normalizer: bool,
+        reward_normalizer: Option<RewardNormalizer>,
     ) -> Self {
         Self {
             core: R2lNormalizedSamplerCore::build(
                 env_builder,
                 execution_mode,
                 obs_normalizer,
-                with_reward_normalizer,
+                reward_normalizer,
             ),
             hook,
         }
@@ -258,6 +305,18 @@ impl<E: Env<Tensor: R2lTensor>, H: NormalizedSamplerHook<E = E>> R2lNormalizedSa
             .as_ref()
             .map(|normalizer| normalizer.with_mode(mode))
     }
+
+    pub fn reward_normalizer(&self, mode: NormalizerMode) -> Option<RewardNormalizer> {
+        self.core
+            .reward_normalizer
+            .as_ref()
+            .map(|normalizer| normalizer.with_mode(mode))
+    }
+
+    /// Unnormalized rewards of the latest rollout, one vector per environment.
+    pub fn raw_rewards(&self) -> &[Vec<f32>] {
+        &self.core.raw_rewards
+    }
 }
 
 impl<E: Env<Tensor: R2lTensor>, H: NormalizedSamplerHook<E = E>> Sampler
@@ -265,24 +324,33 @@ impl<E: Env<Tensor: R2lTensor>, H: NormalizedSamplerHook<E = E>> Sampler
 {
     type Tensor = E::Tensor;
 
-    fn reset_all_envs(&mut self) {
-        self.core.pool.reset_all();
+    fn reset_all_envs(&mut self) -> anyhow::Result<()> {
+        self.core.pool.reset_all()?;
         if let Some(obs_normalizer) = &self.core.obs_normalizer {
             let mut last_states = self.core.last_states.lock().unwrap();
             obs_normalizer.apply_in_place(&mut last_states);
         }
+        if let Some(reward_normalizer) = &mut self.core.reward_normalizer {
+            reward_normalizer.reset_returns();
+        }
         self.core.clear_buffers();
+        self.core.reset_episode_trackers();
         self.hook.reset();
+        Ok(())
     }
 
-    fn collect_rollouts<A: Actor<Tensor = Self::Tensor> + Clone>(&mut self, actor: A) {
+    fn collect_rollouts<A: Actor<Tensor = Self::Tensor> + Clone>(
+        &mut self,
+        actor: A,
+    ) -> anyhow::Result<()> {
         self.core.clear_buffers();
         self.core.set_policy(actor.clone());
+        self.core.episode_monitor.begin_rollout();
         loop {
             let result = self.hook.hook(&mut self.core);
             match result {
-                SamplerHookResult::Bound(bound) => self.core.collect(bound),
-                SamplerHookResult::Stop => break,
+                SamplerHookResult::Bound(bound) => self.core.collect(bound)?,
+                SamplerHookResult::Stop => return Ok(()),
             }
         }
     }
@@ -291,7 +359,118 @@ impl<E: Env<Tensor: R2lTensor>, H: NormalizedSamplerHook<E = E>> Sampler
         self.core.trajectory_views()
     }
 
+    fn episode_monitor(&self) -> Option<EpisodeMonitor> {
+        Some(self.core.episode_monitor.clone())
+    }
+
     fn shutdown(&mut self) {
         self.core.shutdown();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use r2l_core::{env::EnvBuilderType, on_policy::algorithm::Sampler};
+
+    use super::{
+        NormalizedSamplerHook, NormalizerMode, R2lNormalizedSampler, R2lNormalizedSamplerCore,
+        RewardNormalizer,
+    };
+    use crate::{
+        RolloutMode, SamplerExecutionMode, SamplerHookResult,
+        test::{FixedRewardEnv, ZeroActor},
+    };
+
+    // Collects `n_steps` per environment once per rollout.
+    struct StepBound {
+        n_steps: usize,
+        collected: bool,
+    }
+
+    impl NormalizedSamplerHook for StepBound {
+        type E = FixedRewardEnv;
+
+        fn hook(
+            &mut self,
+            _core: &mut R2lNormalizedSamplerCore<FixedRewardEnv>,
+        ) -> SamplerHookResult {
+            self.collected = !self.collected;
+            if !self.collected {
+                SamplerHookResult::Stop
+            } else {
+                SamplerHookResult::Bound(RolloutMode::StepBound {
+                    n_steps: self.n_steps,
+                })
+            }
+        }
+    }
+
+    fn sampler(
+        build_env: fn() -> FixedRewardEnv,
+        execution_mode: SamplerExecutionMode,
+        reward_normalizer: Option<RewardNormalizer>,
+    ) -> R2lNormalizedSampler<FixedRewardEnv, StepBound> {
+        let hook = StepBound {
+            n_steps: 8,
+            collected: false,
+        };
+        let env_builder = EnvBuilderType::homogenous(move || Ok(build_env()), 2);
+        R2lNormalizedSampler::build_with_obs_normalizer(
+            env_builder,
+            hook,
+            execution_mode,
+            None,
+            reward_normalizer,
+        )
+    }
+
+    #[test]
+    fn env_errors_reach_the_caller() {
+        for execution_mode in [SamplerExecutionMode::Vec, SamplerExecutionMode::Thread] {
+            let mut sampler = sampler(|| FixedRewardEnv::failing_at(3), execution_mode, None);
+            let err = sampler.collect_rollouts(ZeroActor).unwrap_err();
+            assert_eq!(err.to_string(), "the environment broke");
+        }
+    }
+
+    #[test]
+    fn raw_rewards_are_kept_next_to_normalized_ones() {
+        let reward_normalizer = RewardNormalizer::new(NormalizerMode::Update, 0.99, 10.);
+        let mut sampler = sampler(
+            || FixedRewardEnv::new(2., 3),
+            SamplerExecutionMode::Vec,
+            Some(reward_normalizer),
+        );
+        sampler.collect_rollouts(ZeroActor).unwrap();
+
+        assert_eq!(sampler.raw_rewards(), &[vec![2.; 8], vec![2.; 8]]);
+        for buffer in &sampler.core.buffers {
+            assert_eq!(buffer.rewards().len(), 8);
+            assert!(buffer.rewards().iter().all(|reward| *reward != 2.));
+        }
+    }
+
+    #[test]
+    fn recorded_returns_are_raw_reward_sums() {
+        for execution_mode in [SamplerExecutionMode::Vec, SamplerExecutionMode::Thread] {
+            let reward_normalizer = RewardNormalizer::new(NormalizerMode::Update, 0.99, 10.);
+            let mut sampler = sampler(
+                || FixedRewardEnv::new(2., 3),
+                execution_mode,
+                Some(reward_normalizer),
+            );
+            sampler.collect_rollouts(ZeroActor).unwrap();
+
+            for buffer in &sampler.core.buffers {
+                assert!(buffer.rewards().iter().all(|reward| *reward != 2.));
+            }
+            // two episodes of three steps end in each environment's eight steps
+            let episodes = sampler.episode_monitor().unwrap().rollout_episodes();
+            assert_eq!(episodes.len(), 4);
+            for episode in episodes {
+                assert_eq!(episode.reward, 6.);
+                assert_eq!(episode.length, 3);
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::sync::{Arc, Mutex};
+
+use r2l_core::{
+    buffers::{Memory, buffer::TrajectoryBuffer},
+    running_mean::RunningMeanStdF32,
+    tensor::R2lTensor,
+};
+
+use crate::NormalizerMode;
+
+const EPSILON: f32 = 1e-8;
+
+// Return statistics shared by every clone of a normalizer.
+#[derive(Clone)]
+struct RewardNormalizerInner {
+    return_rms: RunningMeanStdF32,
+    clip: f32,
+}
+
+impl RewardNormalizerInner {
+    fn update(&mut self, returns: &[f32]) {
+        self.return_rms.update(returns);
+    }
+
+    fn normalize(&self, reward: f32) -> f32 {
+        let scale = (self.return_rms.var + EPSILON).sqrt();
+        (reward / scale).clamp(-self.clip, self.clip)
+    }
+}
+
+/// Scales rewards by the running standard deviation of discounted returns.
+///
+/// The return statistics are shared between clones, while the discounted
+/// return of every environment is local to the normalizer. In
+/// [`NormalizerMode::Update`] the statistics are updated with the returns of
+/// the environments stepped in each call. In [`NormalizerMode::ReadOnly`]
+/// rewards are only scaled.
+///
+/// The normalized sampler scales rewards step by step with
+/// [`apply_in_place`](Self::apply_in_place). A rollout that was collected with
+/// raw rewards is scaled the same way with
+/// [`normalize_buffers`](Self::normalize_buffers).
+#[derive(Clone)]
+pub struct RewardNormalizer {
+    normalizer_mode: NormalizerMode,
+    gamma: f32,
+    returns: Vec<f32>,
+    inner: Arc<Mutex<RewardNormalizerInner>>,
+}
+
+impl RewardNormalizer {
+    pub fn new(normalizer_mode: NormalizerMode, gamma: f32, clip: f32) -> Self {
+        let inner = RewardNormalizerInner {
+            return_rms: RunningMeanStdF32::new(),
+            clip,
+        };
+        Self {
+            normalizer_mode,
+            gamma,
+            returns: vec![],
+            inner: Arc::new(Mutex::new(inner)),
+        }
+    }
+
+    pub fn with_mode(&self, normalizer_mode: NormalizerMode) -> Self {
+        Self {
+            normalizer_mode,
+            gamma: self.gamma,
+            returns: vec![],
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Normalizes the rewards of one step, where `memories[i]` was produced by environment `indices[i]`.
+    pub fn apply_in_place<T: R2lTensor>(&mut self, indices: &[usize], memories: &mut [Memory<T>]) {
+        let mut rewards = memories
+            .iter()
+            .map(|memory| memory.reward)
+            .collect::<Vec<_>>();
+        let dones = memories.iter().map(Memory::is_done).collect::<Vec<_>>();
+        self.normalize_step(indices, &mut rewards, &dones);
+        for (memory, reward) in memories.iter_mut().zip(rewards) {
+            memory.reward = reward;
+        }
+    }
+
+    /// Normalizes the rewards of a whole rollout step by step, where `buffers[i]` holds the
+    /// transitions of environment `i`. Every buffer must hold the same number of steps.
+    pub fn normalize_buffers<T: R2lTensor>(&mut self, buffers: &mut [TrajectoryBuffer<T>]) {
+        let indices = (0..buffers.len()).collect::<Vec<_>>();
+        let n_steps = buffers.first().map_or(0, TrajectoryBuffer::len);
+        for step in 0..n_steps {
+            let mut rewards = buffers
+                .iter()
+                .map(|buffer| buffer.rewards()[step])
+                .collect::<Vec<_>>();
+            let dones = buffers
+                .iter()
+                .map(|buffer| buffer.terminated()[step] || buffer.truncated()[step])
+                .collect::<Vec<_>>();
+            self.normalize_step(&indices, &mut rewards, &dones);
+            for (buffer, reward) in buffers.iter_mut().zip(rewards) {
+                buffer.rewards_mut()[step] = reward;
+            }
+        }
+    }
+
+    fn normalize_step(&mut self, indices: &[usize], rewards: &mut [f32], dones: &[bool]) {
+        let mut inner = self.inner.lock().unwrap();
+        if let NormalizerMode::Update = self.normalizer_mode {
+            if let Some(max_idx) = indices.iter().max()
+                && *max_idx >= self.returns.len()
+            {
+                self.returns.resize(max_idx + 1, 0.);
+            }
+            let returns = indices
+                .iter()
+                .zip(rewards.iter())
+                .map(|(idx, reward)| {
+                    self.returns[*idx] = self.returns[*idx] * self.gamma + reward;
+                    self.returns[*idx]
+                })
+                .collect::<Vec<_>>();
+            inner.update(&returns);
+            for (idx, done) in indices.iter().zip(dones) {
+                if *done {
+                    self.returns[*idx] = 0.;
+                }
+            }
+        }
+        for reward in rewards {
+            *reward = inner.normalize(*reward);
+        }
+    }
+
+    /// Clears the discounted returns while preserving the running statistics.
+    pub fn reset_returns(&mut self) {
+        self.returns.fill(0.);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use r2l_core::{buffers::Memory, tensor::TensorData};
+
+    use super::RewardNormalizer;
+    use crate::NormalizerMode;
+
+    fn memory(reward: f32, terminated: bool) -> Memory<TensorData> {
+        let tensor = TensorData::from_vec(vec![0.]);
+        Memory {
+            state: tensor.clone(),
+            next_state: tensor.clone(),
+            action: tensor,
+            reward,
+            terminated,
+            truncated: false,
+        }
+    }
+
+    fn return_var(normalizer: &RewardNormalizer) -> f32 {
+        normalizer.inner.lock().unwrap().return_rms.var
+    }
+
+    #[test]
+    fn read_only_normalizers_share_but_never_update_the_stats() {
+        let mut normalizer = RewardNormalizer::new(NormalizerMode::Update, 1., 10.);
+        let mut read_only = normalizer.with_mode(NormalizerMode::ReadOnly);
+        let initial_var = return_var(&normalizer);
+
+        read_only.apply_in_place(&[0, 1], &mut [memory(1., false), memory(3., false)]);
+        assert_eq!(return_var(&normalizer), initial_var);
+        assert!(read_only.returns.is_empty());
+
+        normalizer.apply_in_place(&[0, 1], &mut [memory(1., false), memory(3., false)]);
+        let var = return_var(&normalizer);
+        assert_ne!(var, initial_var);
+
+        // the read-only clone scales with the updated stats
+        let mut memories = [memory(2., false)];
+        read_only.apply_in_place(&[0], &mut memories);
+        assert_eq!(memories[0].reward, 2. / (var + super::EPSILON).sqrt());
+    }
+
+    #[test]
+    fn returns_restart_after_done() {
+        let mut normalizer = RewardNormalizer::new(NormalizerMode::Update, 0.5, 10.);
+
+        normalizer.apply_in_place(&[0, 1], &mut [memory(2., false), memory(2., false)]);
+        assert_eq!(normalizer.returns, vec![2., 2.]);
+
+        normalizer.apply_in_place(&[0, 1], &mut [memory(2., true), memory(2., false)]);
+        assert_eq!(normalizer.returns, vec![0., 3.]);
+
+        normalizer.apply_in_place(&[0], &mut [memory(2., false)]);
+        assert_eq!(normalizer.returns, vec![2., 3.]);
+    }
+
+    #[test]
+    fn step_and_buffer_normalization_agree() {
+        let steps = [(1., false), (4., true), (2., false), (-3., false)];
+        let mut by_step = RewardNormalizer::new(NormalizerMode::Update, 0.9, 10.);
+        let mut expected = vec![];
+        for (reward, terminated) in steps {
+            let mut memories = [memory(reward, terminated)];
+            by_step.apply_in_place(&[0], &mut memories);
+            expected.push(memories[0].reward);
+        }
+
+        let mut buffers = [r2l_core::buffers::buffer::TrajectoryBuffer::default()];
+        for (reward, terminated) in steps {
+            buffers[0].push(memory(reward, terminated));
+        }
+        let mut by_buffer = RewardNormalizer::new(NormalizerMode::Update, 0.9, 10.);
+        by_buffer.normalize_buffers(&mut buffers);
+
+        assert_eq!(buffers[0].rewards(), expected.as_slice());
+    }
+}
@@ -4,17 +4,27 @@
 //  -- with seeds not set
 // - generate statistics, generate figures etc
 
+mod policy_kwargs;
+mod report;
 mod zoo_parser;
 
-use std::{path::PathBuf, process::Command};
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    process::{Child, Command},
+    thread,
+    time::Duration,
+};
 
 use anyhow::{Context, bail};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use r2l_api::BackendConfig;
 
-use crate::zoo_parser::ZooConfig;
+use crate::zoo_parser::{RlZooAlgorithm, ZooConfig};
 
 const SEED: u64 = 0;
-const CONFIG_PATH: &str = "../../assets/ppo.yaml";
+const ASSETS_DIR: &str = "../../assets";
+const REFERENCE_FILE: &str = "sb3_reference.yaml";
 const LOG_DIR: &str = "../../logs";
 const SMALL_ENVIRONMENTS: [&str; 8] = [
     "MountainCarContinuous-v0",
@@ -26,10 +36,49 @@ const SMALL_ENVIRONMENTS: [&str; 8] = [
     "LunarLander-v3",
     "LunarLanderContinuous-v3",
 ];
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Backend the evaluated agents are built on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Backend {
+    Burn,
+    Candle,
+}
+
+impl Backend {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Burn => "burn",
+            Self::Candle => "candle",
+        }
+    }
+
+    fn config(self) -> BackendConfig {
+        match self {
+            Self::Burn => BackendConfig::Burn,
+            Self::Candle => BackendConfig::Candle,
+        }
+    }
+}
 
 #[derive(Parser)]
 #[command(about = "Evaluate r2l against Stable Baselines3 Zoo configurations")]
 struct Args {
+    /// Algorithm whose Zoo configuration is evaluated.
+    #[arg(long, value_enum, default_value_t = RlZooAlgorithm::Ppo, global = true)]
+    algo: RlZooAlgorithm,
+    /// Backend the agents are built on.
+    #[arg(long, value_enum, default_value_t = Backend::Burn, global = true)]
+    backend: Backend,
+    /// Seeds per environment, starting from 0.
+    #[arg(long, default_value_t = 1)]
+    seeds: u64,
+    /// Maximum number of training processes running at once.
+    #[arg(long)]
+    jobs: Option<usize>,
+    /// `curves.csv` of an earlier report to compare against.
+    #[arg(long)]
+    baseline: Option<PathBuf>,
     #[command(subcommand)]
     command: Option<Cli>,
 }
@@ -40,32 +89,81 @@ enum Cli {
     Evaluate {
         /// Gymnasium environment ID.
         env: Vec<String>,
+        /// Seed of the run.
+        #[arg(long, default_value_t = SEED)]
+        seed: u64,
     },
+    /// Aggregates the logged seeds into a report without training.
+    Report,
 }
 
-fn evaluate_all() -> anyhow::Result<()> {
+fn crate_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Log directory of one algorithm and backend, with a run directory per
+/// environment and seed.
+fn log_dir(algo: RlZooAlgorithm, backend: Backend) -> PathBuf {
+    crate_dir()
+        .join(LOG_DIR)
+        .join(format!("{}-{}", algo.name(), backend.name()))
+}
+
+fn kill_all(running: &mut [(String, Child)]) {
+    for (_, child) in running {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+fn evaluate_all(
+    algo: RlZooAlgorithm,
+    backend: Backend,
+    seeds: u64,
+    jobs: usize,
+) -> anyhow::Result<()> {
     let executable = std::env::current_exe().context("failed to locate evaluator executable")?;
-    let mut children = Vec::with_capacity(SMALL_ENVIRONMENTS.len());
-    for env in SMALL_ENVIRONMENTS {
-        let command = Command::new(&executable).args(["evaluate", env]).spawn();
-        match command {
-            Ok(child) => children.push((env, child)),
-            Err(error) => {
-                for (_, child) in &mut children {
-                    let _ = child.kill();
-                    let _ = child.wait();
+    let mut pending: VecDeque<(&str, u64)> = SMALL_ENVIRONMENTS
+        .into_iter()
+        .flat_map(|env| (SEED..SEED + seeds).map(move |seed| (env, seed)))
+        .collect();
+    let mut running: Vec<(String, Child)> = Vec::with_capacity(jobs);
+    let mut failures = Vec::new();
+    while !pending.is_empty() || !running.is_empty() {
+        while running.len() < jobs
+            && let Some((env, seed)) = pending.pop_front()
+        {
+            let run = format!("{env} (seed {seed})");
+            let command = Command::new(&executable)
+                .args(["evaluate", env, "--seed", &seed.to_string()])
+                .args(["--algo", algo.name(), "--backend", backend.name()])
+                .spawn();
+            match command {
+                Ok(child) => running.push((run, child)),
+                Err(error) => {
+                    kill_all(&mut running);
+                    return Err(error)
+                        .with_context(|| format!("failed to start evaluation for {run}"));
                 }
-                return Err(error).with_context(|| format!("failed to start evaluation for {env}"));
             }
         }
-    }
-    let mut failures = Vec::new();
-    for (env, mut child) in children {
-        match child.wait() {
-            Ok(status) if status.success() => {}
-            Ok(status) => failures.push(format!("{env} exited with {status}")),
-            Err(error) => failures.push(format!("failed to wait for {env}: {error}")),
+        let mut idx = 0;
+        while idx < running.len() {
+            match running[idx].1.try_wait() {
+                Ok(None) => idx += 1,
+                Ok(Some(status)) => {
+                    let (run, _) = running.swap_remove(idx);
+                    if !status.success() {
+                        failures.push(format!("{run} exited with {status}"));
+                    }
+                }
+                Err(error) => {
+                    let (run, _) = running.swap_remove(idx);
+                    failures.push(format!("failed to wait for {run}: {error}"));
+                }
+            }
         }
+        thread::sleep(POLL_INTERVAL);
     }
     if failures.is_empty() {
         Ok(())
@@ -74,35 +172,120 @@ fn evaluate_all() -> anyhow::Result<()> {
     }
 }
 
-fn evaluate(envs: Vec<String>) -> anyhow::Result<()> {
-    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    let config_path = crate_dir.join(CONFIG_PATH);
-    let zoo_config = ZooConfig::parse_rl_zoo_config(config_path);
+fn evaluate(
+    algo: RlZooAlgorithm,
+    backend: Backend,
+    envs: Vec<String>,
+    seed: u64,
+) -> anyhow::Result<()> {
+    let config_path = crate_dir()
+        .join(ASSETS_DIR)
+        .join(format!("{}.yaml", algo.name()));
+    let zoo_config = ZooConfig::parse_rl_zoo_config(config_path, algo)?;
     for env in envs {
-        let log_file = crate_dir.join(LOG_DIR).join(format!("{env}.csv"));
-        let env_config = zoo_config.supported_envs.get(&env).unwrap();
-        println!("Evaluating {env}");
+        let Some(env_config) = zoo_config.supported_envs.get(&env) else {
+            match zoo_config.unsupported_envs.get(&env) {
+                Some(reason) => bail!("{env} is not supported: {reason}"),
+                None => bail!("no {} configuration for {env}", algo.name()),
+            }
+        };
+        let run_dir = log_dir(algo, backend)
+            .join(&env)
+            .join(format!("seed_{seed}"));
+        println!(
+            "Evaluating {} on {env} with {} and seed {seed}",
+            algo.name(),
+            backend.name()
+        );
         let mut algorithm = env_config
-            .build_burn_ppo_algorithm(&env, log_file, SEED)
-            .unwrap();
-        algorithm.train().unwrap();
+            .experiment_config(&env, backend.config(), seed, run_dir)?
+            .build()?;
+        algorithm.train()?;
+    }
+    Ok(())
+}
+
+fn report(algo: RlZooAlgorithm, backend: Backend, baseline: Option<PathBuf>) -> anyhow::Result<()> {
+    let log_dir = log_dir(algo, backend);
+    let curves = report::collect_curves(&log_dir)?;
+    if curves.is_empty() {
+        bail!(
+            "no `{}` logs found in {}",
+            report::METRIC,
+            log_dir.display()
+        );
+    }
+    let baseline = baseline
+        .map(|path| report::read_curves_csv(&path))
+        .transpose()?;
+    let references = report::read_references(
+        &crate_dir().join(ASSETS_DIR).join(REFERENCE_FILE),
+        algo.name(),
+    )?;
+    let report_path = report::write_report(
+        &curves,
+        baseline.as_ref(),
+        Some(&references),
+        &log_dir.join("report"),
+    )?;
+    println!("Wrote {}", report_path.display());
+    println!("{}", report::reference_table(&curves, &references));
+    let failures = report::reference_failures(&curves, &references);
+    if !failures.is_empty() {
+        bail!("below the reference threshold: {}", failures.join(", "));
     }
     Ok(())
 }
 
 fn main() -> anyhow::Result<()> {
-    match Args::parse().command {
-        Some(Cli::Evaluate { env }) => evaluate(env),
-        None => evaluate_all(),
+    let args = Args::parse();
+    match args.command {
+        Some(Cli::Evaluate { env, seed }) => evaluate(args.algo, args.backend, env, seed),
+        Some(Cli::Report) => report(args.algo, args.backend, args.baseline),
+        None => {
+            let jobs = match args.jobs {
+                Some(jobs) => jobs.max(1),
+                None => thread::available_parallelism().map_or(1, usize::from),
+            };
+            evaluate_all(args.algo, args.backend, args.seeds, jobs)?;
+            report(args.algo, args.backend, args.baseline)
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::evaluate;
+    use crate::{
+        ASSETS_DIR, Backend, SEED, SMALL_ENVIRONMENTS, crate_dir, evaluate,
+        zoo_parser::RlZooAlgorithm, zoo_parser::ZooConfig,
+    };
+
+    #[test]
+    fn small_environments_are_supported() {
+        for algo in [RlZooAlgorithm::Ppo, RlZooAlgorithm::A2c] {
+            let config_path = crate_dir()
+                .join(ASSETS_DIR)
+                .join(format!("{}.yaml", algo.name()));
+            let zoo_config = ZooConfig::parse_rl_zoo_config(config_path, algo).unwrap();
+            for env in SMALL_ENVIRONMENTS {
+                assert!(
+                    zoo_config.supported_envs.contains_key(env),
+                    "{} {env}: {:?}",
+                    algo.name(),
+                    zoo_config.unsupported_envs.get(env)
+                );
+            }
+        }
+    }
 
     #[test]
     fn pendulum_v1() {
-        evaluate(vec!["Pendulum-v1".into()]).unwrap();
+        evaluate(
+            RlZooAlgorithm::Ppo,
+            Backend::Burn,
+            vec!["Pendulum-v1".into()],
+            SEED,
+        )
+        .unwrap();
     }
 }
@@ -0,0 +1,315 @@
+// Zoo writes `policy_kwargs` as Python source, such as
+// `dict(log_std_init=-2, net_arch=dict(pi=[256, 256], vf=[256, 256]))`. This
+// parses the literal subset Zoo uses and maps the keys r2l supports.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, anyhow, bail};
+use r2l_api::{ActivationFunction, NetworkConfig, WeightInit};
+
+/// A Python literal.
+#[derive(Debug, Clone, PartialEq)]
+enum PyValue {
+    None,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    /// A bare, possibly dotted, name such as `nn.ReLU`.
+    Name(String),
+    List(Vec<PyValue>),
+    Dict(BTreeMap<String, PyValue>),
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(source: &'a str) -> anyhow::Result<PyValue> {
+        let mut parser = Self { source, pos: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != source.len() {
+            bail!("unexpected `{}` in `{source}`", &source[parser.pos..]);
+        }
+        Ok(value)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.source.len() - self.rest().trim_start().len();
+    }
+
+    fn eat(&mut self, token: char) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: char) -> anyhow::Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            bail!(
+                "expected `{token}` at `{}` in `{}`",
+                self.rest(),
+                self.source
+            )
+        }
+    }
+
+    /// Parses comma-separated items until `close`, allowing a trailing comma.
+    fn items(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        while !self.eat(close) {
+            item(self)?;
+            if !self.eat(',') {
+                return self.expect(close);
+            }
+        }
+        Ok(())
+    }
+
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '+')))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn value(&mut self) -> anyhow::Result<PyValue> {
+        if self.eat('[') || self.eat('(') {
+            let close = if self.source[..self.pos].ends_with('[') {
+                ']'
+            } else {
+                ')'
+            };
+            let mut values = vec![];
+            self.items(close, |parser| {
+                values.push(parser.value()?);
+                Ok(())
+            })?;
+            return Ok(PyValue::List(values));
+        }
+        if self.eat('{') {
+            let mut entries = BTreeMap::new();
+            self.items('}', |parser| {
+                let PyValue::Str(key) = parser.value()? else {
+                    bail!("dict keys must be strings in `{}`", parser.source);
+                };
+                parser.expect(':')?;
+                entries.insert(key, parser.value()?);
+                Ok(())
+            })?;
+            return Ok(PyValue::Dict(entries));
+        }
+        if let Some(quote) = ['\'', '"']
+            .into_iter()
+            .find(|quote| self.rest().starts_with(*quote))
+        {
+            let rest = &self.rest()[1..];
+            let len = rest
+                .find(quote)
+                .ok_or_else(|| anyhow!("unterminated string in `{}`", self.source))?;
+            self.pos += len + 2;
+            return Ok(PyValue::Str(rest[..len].to_owned()));
+        }
+        let word = self.word();
+        if word == "dict" && self.eat('(') {
+            let mut entries = BTreeMap::new();
+            self.items(')', |parser| {
+                let key = parser.word().to_owned();
+                parser.expect('=')?;
+                entries.insert(key, parser.value()?);
+                Ok(())
+            })?;
+            return Ok(PyValue::Dict(entries));
+        }
+        match word {
+            "" => bail!("expected a value at `{}` in `{}`", self.rest(), self.source),
+            "None" => Ok(PyValue::None),
+            "True" => Ok(PyValue::Bool(true)),
+            "False" => Ok(PyValue::Bool(false)),
+            _ => Ok(word
+                .parse()
+                .map(PyValue::Number)
+                .unwrap_or_else(|_| PyValue::Name(word.to_owned()))),
+        }
+    }
+}
+
+impl PyValue {
+    fn as_f32(&self) -> anyhow::Result<f32> {
+        match self {
+            Self::Number(value) => Ok(*value as f32),
+            _ => bail!("expected a number, found {self:?}"),
+        }
+    }
+
+    fn as_layers(&self) -> anyhow::Result<Vec<usize>> {
+        let Self::List(values) = self else {
+            bail!("expected a list of layer sizes, found {self:?}");
+        };
+        values
+            .iter()
+            .map(|value| match value {
+                Self::Number(size) if size.fract() == 0. && *size > 0. => Ok(*size as usize),
+                _ => bail!("invalid layer size {value:?}"),
+            })
+            .collect()
+    }
+}
+
+/// The `policy_kwargs` keys r2l can reproduce.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RlZooPolicyKwargs {
+    pub policy_hidden_layers: Option<Vec<usize>>,
+    pub value_hidden_layers: Option<Vec<usize>>,
+    pub activation: Option<ActivationFunction>,
+    pub log_std_init: Option<f32>,
+    /// SB3 initializes orthogonally unless `ortho_init=False`.
+    pub ortho_init: bool,
+}
+
+impl Default for RlZooPolicyKwargs {
+    fn default() -> Self {
+        Self {
+            policy_hidden_layers: None,
+            value_hidden_layers: None,
+            activation: None,
+            log_std_init: None,
+            ortho_init: true,
+        }
+    }
+}
+
+fn activation(name: &str) -> anyhow::Result<ActivationFunction> {
+    let activation = match name.rsplit('.').next().unwrap_or(name) {
+        "ReLU" => ActivationFunction::Relu,
+        "Tanh" => ActivationFunction::Tanh,
+        "ELU" => ActivationFunction::Elu,
+        "GELU" => ActivationFunction::Gelu,
+        "LeakyReLU" => ActivationFunction::LeakyRelu,
+        "Sigmoid" => ActivationFunction::Sigmoid,
+        "Hardsigmoid" => ActivationFunction::HardSigmoid,
+        "Hardswish" => ActivationFunction::HardSwish,
+        _ => bail!("unsupported activation_fn {name}"),
+    };
+    Ok(activation)
+}
+
+impl RlZooPolicyKwargs {
+    /// Parses Zoo's `policy_kwargs` string, rejecting keys r2l does not
+    /// support.
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        let PyValue::Dict(entries) = Parser::parse(source)? else {
+            bail!("policy_kwargs must be a dict: {source}");
+        };
+        let mut kwargs = Self::default();
+        for (key, value) in &entries {
+            let context = || format!("invalid policy_kwargs {key} in `{source}`");
+            match (key.as_str(), value) {
+                ("log_std_init", value) => {
+                    kwargs.log_std_init = Some(value.as_f32().with_context(context)?)
+                }
+                ("ortho_init", PyValue::Bool(ortho_init)) => kwargs.ortho_init = *ortho_init,
+                ("activation_fn", PyValue::Name(name)) => {
+                    kwargs.activation = Some(activation(name).with_context(context)?)
+                }
+                // Since SB3 1.8 a list is the architecture of both networks.
+                ("net_arch", PyValue::List(_)) => {
+                    let layers = value.as_layers().with_context(context)?;
+                    kwargs.policy_hidden_layers = Some(layers.clone());
+                    kwargs.value_hidden_layers = Some(layers);
+                }
+                ("net_arch", PyValue::Dict(arch)) => {
+                    for (network, layers) in arch {
+                        let layers = Some(layers.as_layers().with_context(context)?);
+                        match network.as_str() {
+                            "pi" => kwargs.policy_hidden_layers = layers,
+                            "vf" => kwargs.value_hidden_layers = layers,
+                            _ => bail!("unsupported net_arch network {network} in `{source}`"),
+                        }
+                    }
+                }
+                _ => bail!("unsupported policy_kwargs {key}={value:?} in `{source}`"),
+            }
+        }
+        Ok(kwargs)
+    }
+
+    /// Applies the network settings on top of `network`.
+    pub fn apply(&self, network: &mut NetworkConfig) {
+        if self.policy_hidden_layers.is_some() {
+            network.policy_hidden_layers = self.policy_hidden_layers.clone();
+        }
+        if self.value_hidden_layers.is_some() {
+            network.value_hidden_layers = self.value_hidden_layers.clone();
+        }
+        if self.activation.is_some() {
+            network.activation = self.activation;
+        }
+        if self.log_std_init.is_some() {
+            network.log_std_init = self.log_std_init;
+        }
+        network.weight_init = self.ortho_init.then(WeightInit::orthogonal);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use r2l_api::ActivationFunction;
+
+    use super::{Parser, PyValue, RlZooPolicyKwargs};
+
+    #[test]
+    fn parses_python_literals() {
+        let value = Parser::parse("dict(a=[1, -2.5], b={'c': None}, d=nn.ReLU, e=True, )").unwrap();
+        let PyValue::Dict(entries) = value else {
+            panic!("expected a dict");
+        };
+        assert_eq!(
+            entries["a"],
+            PyValue::List(vec![PyValue::Number(1.), PyValue::Number(-2.5)])
+        );
+        assert_eq!(
+            entries["b"],
+            PyValue::Dict([("c".to_owned(), PyValue::None)].into_iter().collect())
+        );
+        assert_eq!(entries["d"], PyValue::Name("nn.ReLU".into()));
+        assert_eq!(entries["e"], PyValue::Bool(true));
+        assert!(Parser::parse("dict(a=[1, 2)").is_err());
+    }
+
+    #[test]
+    fn maps_net_arch_and_activation() {
+        let kwargs = RlZooPolicyKwargs::parse(
+            "dict( log_std_init=-2, ortho_init=False, activation_fn=nn.ReLU, net_arch=dict(pi=[256, 256], vf=[128]) )",
+        )
+        .unwrap();
+        assert_eq!(kwargs.policy_hidden_layers, Some(vec![256, 256]));
+        assert_eq!(kwargs.value_hidden_layers, Some(vec![128]));
+        assert_eq!(kwargs.activation, Some(ActivationFunction::Relu));
+        assert_eq!(kwargs.log_std_init, Some(-2.));
+        assert!(!kwargs.ortho_init);
+
+        let kwargs = RlZooPolicyKwargs::parse("dict(net_arch=[64, 64])").unwrap();
+        assert_eq!(kwargs.value_hidden_layers, Some(vec![64, 64]));
+        assert!(kwargs.ortho_init);
+        assert!(RlZooPolicyKwargs::parse("dict(optimizer_class=RMSpropTFLike)").is_err());
+    }
+}
@@ -0,0 +1,663 @@
+// Aggregates the learning curves of several seeds per environment and renders
+// them as a Markdown and an HTML report with SVG plots.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, bail};
+use r2l_api::read_csv_metric;
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+use serde::Deserialize;
+
+/// Metric the learning curves are read from.
+pub const METRIC: &str = "eval/mean_reward";
+const N_CHECKPOINTS: usize = 20;
+const BOOTSTRAP_RESAMPLES: usize = 2000;
+const CONFIDENCE: f64 = 0.95;
+const BOOTSTRAP_SEED: u64 = 0;
+const CURVES_FILE: &str = "curves.csv";
+/// Metrics file of a run directory.
+const METRICS_FILE: &str = "metrics.csv";
+
+/// Values of one metric of a single seed, ordered by step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve {
+    points: Vec<(u64, f64)>,
+}
+
+impl Curve {
+    /// Reads the rows of `metric` from a metrics CSV written by `CsvSink`.
+    pub fn from_metrics_csv(path: &Path, metric: &str) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read metrics {}", path.display()))?;
+        let mut points = read_csv_metric(&content, metric)
+            .with_context(|| format!("invalid metrics {}", path.display()))?;
+        points.sort_by_key(|(step, _)| *step);
+        Ok(Self { points })
+    }
+
+    fn last_step(&self) -> Option<u64> {
+        self.points.last().map(|(step, _)| *step)
+    }
+
+    /// The latest value logged at or before `step`.
+    fn value_at(&self, step: u64) -> Option<f64> {
+        let idx = self.points.partition_point(|(s, _)| *s <= step);
+        idx.checked_sub(1).map(|idx| self.points[idx].1)
+    }
+}
+
+/// Statistics across seeds at one step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CheckpointStats {
+    pub step: u64,
+    pub n_seeds: usize,
+    pub mean: f64,
+    /// Interquartile mean, the mean of the middle 50% of seeds.
+    pub iqm: f64,
+    /// Bootstrap confidence interval of the IQM.
+    pub ci_low: f64,
+    pub ci_high: f64,
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Mean of the values left after dropping the lowest and highest quarter.
+pub fn iqm(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let trim = sorted.len() / 4;
+    mean(&sorted[trim..sorted.len() - trim])
+}
+
+/// Percentile bootstrap confidence interval of `statistic`, resampling
+/// `values` with replacement.
+pub fn bootstrap_ci(
+    values: &[f64],
+    statistic: impl Fn(&[f64]) -> f64,
+    resamples: usize,
+    confidence: f64,
+    rng: &mut StdRng,
+) -> (f64, f64) {
+    let mut estimates: Vec<f64> = (0..resamples)
+        .map(|_| {
+            let resample: Vec<f64> = (0..values.len())
+                .map(|_| values[rng.random_range(0..values.len())])
+                .collect();
+            statistic(&resample)
+        })
+        .collect();
+    estimates.sort_by(f64::total_cmp);
+    let tail = (1. - confidence) / 2.;
+    let quantile = |q: f64| estimates[((resamples - 1) as f64 * q).round() as usize];
+    (quantile(tail), quantile(1. - tail))
+}
+
+/// Aggregates the curves of all seeds at evenly spaced steps, up to the last
+/// step every seed reached.
+pub fn aggregate(curves: &[Curve], n_checkpoints: usize) -> Vec<CheckpointStats> {
+    let Some(last_step) = curves.iter().map(|curve| curve.last_step()).min().flatten() else {
+        return vec![];
+    };
+    let mut rng = StdRng::seed_from_u64(BOOTSTRAP_SEED);
+    (1..=n_checkpoints)
+        .map(|idx| last_step * idx as u64 / n_checkpoints as u64)
+        .filter_map(|step| {
+            let values: Vec<f64> = curves.iter().filter_map(|c| c.value_at(step)).collect();
+            if values.is_empty() {
+                return None;
+            }
+            let (ci_low, ci_high) =
+                bootstrap_ci(&values, iqm, BOOTSTRAP_RESAMPLES, CONFIDENCE, &mut rng);
+            Some(CheckpointStats {
+                step,
+                n_seeds: values.len(),
+                mean: mean(&values),
+                iqm: iqm(&values),
+                ci_low,
+                ci_high,
+            })
+        })
+        .collect()
+}
+
+/// Aggregated curves of every environment, keyed by environment.
+pub type Curves = BTreeMap<String, Vec<CheckpointStats>>;
+
+/// Reads `<log_dir>/<env>/seed_*/metrics.csv` for every environment directory.
+pub fn collect_curves(log_dir: &Path) -> anyhow::Result<Curves> {
+    let mut curves = Curves::new();
+    let entries =
+        fs::read_dir(log_dir).with_context(|| format!("failed to read {}", log_dir.display()))?;
+    for entry in entries {
+        let env_dir = entry?.path();
+        let Some(env) = env_dir.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !env_dir.is_dir() {
+            continue;
+        }
+        let mut seed_curves = vec![];
+        for seed_dir in fs::read_dir(&env_dir)? {
+            let seed_dir = seed_dir?.path();
+            let is_seed_run = seed_dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("seed_"));
+            let path = seed_dir.join(METRICS_FILE);
+            if is_seed_run && path.is_file() {
+                seed_curves.push(Curve::from_metrics_csv(&path, METRIC)?);
+            }
+        }
+        let stats = aggregate(&seed_curves, N_CHECKPOINTS);
+        if !stats.is_empty() {
+            curves.insert(env.to_owned(), stats);
+        }
+    }
+    Ok(curves)
+}
+
+fn write_curves_csv(curves: &Curves) -> String {
+    let mut csv = String::from("env,step,seeds,mean,iqm,ci_low,ci_high\n");
+    for (env, stats) in curves {
+        for s in stats {
+            writeln!(
+                csv,
+                "{env},{},{},{},{},{},{}",
+                s.step, s.n_seeds, s.mean, s.iqm, s.ci_low, s.ci_high
+            )
+            .unwrap();
+        }
+    }
+    csv
+}
+
+/// Reads the `curves.csv` of an earlier report.
+pub fn read_curves_csv(path: &Path) -> anyhow::Result<Curves> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("failed to read baseline {}", path.display()))?;
+    let mut curves = Curves::new();
+    for line in content.lines().skip(1) {
+        let columns: Vec<&str> = line.split(',').collect();
+        let [env, step, n_seeds, mean, iqm, ci_low, ci_high] = columns[..] else {
+            bail!("invalid row in {}: {line}", path.display());
+        };
+        let parse = |value: &str| -> anyhow::Result<f64> {
+            value
+                .parse()
+                .with_context(|| format!("invalid value in {}: {line}", path.display()))
+        };
+        curves
+            .entry(env.to_owned())
+            .or_default()
+            .push(CheckpointStats {
+                step: step.parse()?,
+                n_seeds: n_seeds.parse()?,
+                mean: parse(mean)?,
+                iqm: parse(iqm)?,
+                ci_low: parse(ci_low)?,
+                ci_high: parse(ci_high)?,
+            });
+    }
+    Ok(curves)
+}
+
+/// Compares the final confidence intervals of a run and its baseline.
+fn verdict(current: &CheckpointStats, baseline: &CheckpointStats) -> &'static str {
+    if current.ci_high < baseline.ci_low {
+        "regression"
+    } else if current.ci_low > baseline.ci_high {
+        "improvement"
+    } else {
+        "within noise"
+    }
+}
+
+/// Final return of a Stable-Baselines3 agent, and the return r2l has to reach.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReferenceScore {
+    /// Mean final episode return of SB3.
+    pub reward: f64,
+    /// Standard deviation of SB3's final episode returns.
+    pub std: f64,
+    /// Mean final return across seeds a run needs to pass.
+    pub min_reward: f64,
+}
+
+/// Reference scores by environment.
+pub type References = BTreeMap<String, ReferenceScore>;
+
+/// Reads the reference scores of `algorithm` from a YAML file mapping
+/// algorithms to environments to scores.
+pub fn read_references(path: &Path, algorithm: &str) -> anyhow::Result<References> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("failed to read reference scores {}", path.display()))?;
+    let mut references: BTreeMap<String, References> = yaml_serde::from_str(&content)
+        .with_context(|| format!("invalid reference scores {}", path.display()))?;
+    references
+        .remove(algorithm)
+        .with_context(|| format!("no {algorithm} reference scores in {}", path.display()))
+}
+
+fn passes(current: &CheckpointStats, reference: &ReferenceScore) -> bool {
+    current.mean >= reference.min_reward
+}
+
+/// Environments whose final mean return is below their reference threshold.
+///
+/// Environments without a run are not counted as failures.
+pub fn reference_failures(curves: &Curves, references: &References) -> Vec<String> {
+    references
+        .iter()
+        .filter_map(|(env, reference)| {
+            let last = curves.get(env)?.last()?;
+            (!passes(last, reference)).then(|| env.clone())
+        })
+        .collect()
+}
+
+fn reference_rows(curves: &Curves, references: &References) -> Vec<Vec<String>> {
+    references
+        .iter()
+        .map(|(env, reference)| {
+            let last = curves.get(env).and_then(|stats| stats.last());
+            let mut row = vec![env.clone()];
+            match last {
+                Some(last) => row.extend([format!("{:.2}", last.mean), format!("{:.2}", last.iqm)]),
+                None => row.extend(["-".to_owned(), "-".to_owned()]),
+            }
+            row.push(format!("{:.2} ± {:.2}", reference.reward, reference.std));
+            row.push(format!("{:.2}", reference.min_reward));
+            let result = match last {
+                Some(last) if passes(last, reference) => "pass",
+                Some(_) => "fail",
+                None => "not run",
+            };
+            row.push(result.to_owned());
+            row
+        })
+        .collect()
+}
+
+const REFERENCE_HEADER: [&str; 6] = [
+    "Environment",
+    "Final mean",
+    "Final IQM",
+    "SB3 reward",
+    "Threshold",
+    "Result",
+];
+const REFERENCE_DESCRIPTION: &str = "Final returns compared with Stable-Baselines3 trained on the \
+     same Zoo hyperparameters. An environment passes when the mean final return across seeds \
+     reaches its threshold.";
+
+/// Markdown table comparing the final returns with the reference scores.
+pub fn reference_table(curves: &Curves, references: &References) -> String {
+    markdown_table(&REFERENCE_HEADER, &reference_rows(curves, references))
+}
+
+const WIDTH: f64 = 640.;
+const HEIGHT: f64 = 360.;
+const MARGIN: f64 = 56.;
+
+/// Plots the IQM and its confidence band, and the baseline IQM when given.
+pub fn svg_plot(
+    env: &str,
+    stats: &[CheckpointStats],
+    baseline: Option<&[CheckpointStats]>,
+) -> String {
+    let all = stats.iter().chain(baseline.into_iter().flatten());
+    let max_step = all.clone().map(|s| s.step).max().unwrap_or(1).max(1) as f64;
+    let mut y_min = all
+        .clone()
+        .map(|s| s.ci_low.min(s.iqm))
+        .fold(f64::INFINITY, f64::min);
+    let mut y_max = all
+        .map(|s| s.ci_high.max(s.iqm))
+        .fold(f64::NEG_INFINITY, f64::max);
+    if y_max <= y_min {
+        y_min -= 1.;
+        y_max += 1.;
+    }
+    let x = |step: u64| MARGIN + step as f64 / max_step * (WIDTH - 2. * MARGIN);
+    let y =
+        |value: f64| HEIGHT - MARGIN - (value - y_min) / (y_max - y_min) * (HEIGHT - 2. * MARGIN);
+    let line = |stats: &[CheckpointStats]| {
+        stats
+            .iter()
+            .map(|s| format!("{:.1},{:.1}", x(s.step), y(s.iqm)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" font-family="sans-serif" font-size="12">"#
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"<text x="{}" y="20" text-anchor="middle" font-size="14">{}</text>"#,
+        WIDTH / 2.,
+        escape_xml(env)
+    )
+    .unwrap();
+    for tick in 0..=4 {
+        let fraction = tick as f64 / 4.;
+        let value = y_min + fraction * (y_max - y_min);
+        let step = (fraction * max_step) as u64;
+        writeln!(
+            svg,
+            r##"<line x1="{MARGIN}" x2="{}" y1="{py:.1}" y2="{py:.1}" stroke="#ddd"/><text x="{}" y="{py:.1}" text-anchor="end" dy="4">{value:.1}</text><text x="{px:.1}" y="{}" text-anchor="middle">{step}</text>"##,
+            WIDTH - MARGIN,
+            MARGIN - 6.,
+            HEIGHT - MARGIN + 18.,
+            py = y(value),
+            px = x(step),
+        )
+        .unwrap();
+    }
+    if let Some(baseline) = baseline {
+        writeln!(
+            svg,
+            r##"<polyline points="{}" fill="none" stroke="#888" stroke-width="2" stroke-dasharray="6 4"/>"##,
+            line(baseline)
+        )
+        .unwrap();
+    }
+    let band = stats
+        .iter()
+        .map(|s| format!("{:.1},{:.1}", x(s.step), y(s.ci_high)))
+        .chain(
+            stats
+                .iter()
+                .rev()
+                .map(|s| format!("{:.1},{:.1}", x(s.step), y(s.ci_low))),
+        )
+        .collect::<Vec<_>>()
+        .join(" ");
+    writeln!(
+        svg,
+        r##"<polygon points="{band}" fill="#1f77b4" fill-opacity="0.2"/><polyline points="{}" fill="none" stroke="#1f77b4" stroke-width="2"/>"##,
+        line(stats)
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"<text x="{}" y="{}" text-anchor="middle">environment steps</text>"#,
+        WIDTH / 2.,
+        HEIGHT - 12.
+    )
+    .unwrap();
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn format_ci(stats: &CheckpointStats) -> String {
+    format!("[{:.2}, {:.2}]", stats.ci_low, stats.ci_high)
+}
+
+/// Rows of the summary table: the final checkpoint of every environment.
+fn summary_rows(curves: &Curves, baseline: Option<&Curves>) -> Vec<Vec<String>> {
+    curves
+        .iter()
+        .filter_map(|(env, stats)| {
+            let last = stats.last()?;
+            let mut row = vec![
+                env.clone(),
+                last.n_seeds.to_string(),
+                last.step.to_string(),
+                format!("{:.2}", last.mean),
+                format!("{:.2}", last.iqm),
+                format_ci(last),
+            ];
+            if let Some(baseline) = baseline {
+                match baseline.get(env).and_then(|stats| stats.last()) {
+                    Some(base) => {
+                        row.push(format!("{:.2} {}", base.iqm, format_ci(base)));
+                        row.push(verdict(last, base).to_owned());
+                    }
+                    None => row.extend(["-".to_owned(), "-".to_owned()]),
+                }
+            }
+            Some(row)
+        })
+        .collect()
+}
+
+fn checkpoint_rows(stats: &[CheckpointStats]) -> Vec<Vec<String>> {
+    stats
+        .iter()
+        .map(|s| {
+            vec![
+                s.step.to_string(),
+                s.n_seeds.to_string(),
+                format!("{:.2}", s.mean),
+                format!("{:.2}", s.iqm),
+                format_ci(s),
+            ]
+        })
+        .collect()
+}
+
+fn markdown_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut table = format!(
+        "| {} |\n|{}\n",
+        header.join(" | "),
+        "---|".repeat(header.len())
+    );
+    for row in rows {
+        writeln!(table, "| {} |", row.join(" | ")).unwrap();
+    }
+    table
+}
+
+fn html_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut table = String::from("<table>\n<tr>");
+    for column in header {
+        write!(table, "<th>{}</th>", escape_xml(column)).unwrap();
+    }
+    table.push_str("</tr>\n");
+    for row in rows {
+        table.push_str("<tr>");
+        for cell in row {
+            write!(table, "<td>{}</td>", escape_xml(cell)).unwrap();
+        }
+        table.push_str("</tr>\n");
+    }
+    table.push_str("</table>\n");
+    table
+}
+
+const SUMMARY_HEADER: [&str; 6] = ["Environment", "Seeds", "Step", "Mean", "IQM", "95% CI"];
+const BASELINE_HEADER: [&str; 2] = ["Baseline IQM", "Change"];
+const CHECKPOINT_HEADER: [&str; 5] = ["Step", "Seeds", "Mean", "IQM", "95% CI"];
+
+fn description() -> String {
+    format!(
+        "Learning curves of `{METRIC}` across seeds, at {N_CHECKPOINTS} evenly spaced steps up to \
+         the last step every seed reached. The IQM is the mean of the middle 50% of seeds, and \
+         the interval is a {:.0}% percentile bootstrap interval of the IQM.",
+        CONFIDENCE * 100.
+    )
+}
+
+/// Writes `report.md`, `report.html`, one SVG plot per environment, and the
+/// aggregated `curves.csv`, which later reports can use as their baseline.
+pub fn write_report(
+    curves: &Curves,
+    baseline: Option<&Curves>,
+    references: Option<&References>,
+    out_dir: &Path,
+) -> anyhow::Result<PathBuf> {
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("failed to create {}", out_dir.display()))?;
+    let mut header = SUMMARY_HEADER.to_vec();
+    if baseline.is_some() {
+        header.extend(BASELINE_HEADER);
+    }
+    let summary = summary_rows(curves, baseline);
+
+    let mut markdown = format!("# r2l zoo evaluation\n\n{}\n\n", description());
+    markdown.push_str(&markdown_table(&header, &summary));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>r2l zoo evaluation</title></head>\n<body>\n<h1>r2l zoo evaluation</h1>\n<p>{}</p>\n",
+        escape_xml(&description())
+    );
+    html.push_str(&html_table(&header, &summary));
+    if let Some(references) = references {
+        write!(
+            markdown,
+            "\n## Stable-Baselines3 reference\n\n{REFERENCE_DESCRIPTION}\n\n{}",
+            reference_table(curves, references)
+        )?;
+        write!(
+            html,
+            "<h2>Stable-Baselines3 reference</h2>\n<p>{}</p>\n{}",
+            escape_xml(REFERENCE_DESCRIPTION),
+            html_table(&REFERENCE_HEADER, &reference_rows(curves, references))
+        )?;
+    }
+
+    for (env, stats) in curves {
+        let baseline_stats = baseline
+            .and_then(|baseline| baseline.get(env))
+            .map(Vec::as_slice);
+        let svg = svg_plot(env, stats, baseline_stats);
+        let svg_file = format!("{env}.svg");
+        fs::write(out_dir.join(&svg_file), &svg)?;
+        let rows = checkpoint_rows(stats);
+        write!(
+            markdown,
+            "\n## {env}\n\n![{env}]({svg_file})\n\n{}",
+            markdown_table(&CHECKPOINT_HEADER, &rows)
+        )?;
+        write!(
+            html,
+            "<h2>{}</h2>\n{svg}{}",
+            escape_xml(env),
+            html_table(&CHECKPOINT_HEADER, &rows)
+        )?;
+    }
+    html.push_str("</body>\n</html>\n");
+
+    fs::write(out_dir.join("report.html"), html)?;
+    fs::write(out_dir.join(CURVES_FILE), write_curves_csv(curves))?;
+    let report_path = out_dir.join("report.md");
+    fs::write(&report_path, markdown)?;
+    Ok(report_path)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::{
+        Curve, aggregate, bootstrap_ci, iqm, read_curves_csv, read_references, reference_failures,
+        write_curves_csv,
+    };
+
+    fn curve(points: &[(u64, f64)]) -> Curve {
+        Curve {
+            points: points.to_vec(),
+        }
+    }
+
+    #[test]
+    fn iqm_drops_the_outer_quartiles() {
+        assert_eq!(iqm(&[1., 2., 3.]), 2.);
+        assert_eq!(iqm(&[100., 1., 2., 3., 4., 5., 6., -100.]), 3.5);
+    }
+
+    #[test]
+    fn bootstrap_interval_contains_the_statistic() {
+        let values = [1., 2., 3., 4., 5., 6., 7., 8.];
+        let mut rng = StdRng::seed_from_u64(0);
+        let (low, high) = bootstrap_ci(&values, iqm, 1000, 0.95, &mut rng);
+        assert!(low <= iqm(&values) && iqm(&values) <= high);
+        assert!(low >= 1. && high <= 8.);
+        let (low, high) = bootstrap_ci(&[3.], iqm, 100, 0.95, &mut rng);
+        assert_eq!((low, high), (3., 3.));
+    }
+
+    #[test]
+    fn curves_are_aggregated_at_the_latest_logged_value() {
+        let curves = [
+            curve(&[(100, 1.), (200, 2.), (300, 3.), (400, 4.)]),
+            curve(&[(150, 10.), (300, 30.)]),
+        ];
+        let stats = aggregate(&curves, 3);
+        let steps: Vec<u64> = stats.iter().map(|s| s.step).collect();
+        assert_eq!(steps, vec![100, 200, 300]);
+        assert_eq!(stats[0].n_seeds, 1);
+        assert_eq!(stats[1].mean, 6.);
+        assert_eq!(stats[2].mean, 16.5);
+    }
+
+    #[test]
+    fn metric_names_with_commas_are_read_from_quoted_fields() {
+        let path = std::env::temp_dir().join("r2l_zoo_quoted_metrics.csv");
+        std::fs::write(
+            &path,
+            "step,wall_time,name,value,count,min,max\n\
+             200,2.5,eval/mean_reward,2,,,\n\
+             100,1.5,\"eval/mean_reward, raw\",9,,,\n\
+             100,1.5,eval/mean_reward,1,,,\n",
+        )
+        .unwrap();
+        let plain = Curve::from_metrics_csv(&path, "eval/mean_reward").unwrap();
+        let quoted = Curve::from_metrics_csv(&path, "eval/mean_reward, raw").unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(plain, curve(&[(100, 1.), (200, 2.)]));
+        assert_eq!(quoted, curve(&[(100, 9.)]));
+    }
+
+    #[test]
+    fn curves_csv_round_trips() {
+        let curves = [(
+            "CartPole-v1".to_owned(),
+            aggregate(&[curve(&[(10, 1.)])], 2),
+        )]
+        .into_iter()
+        .collect();
+        let path = std::env::temp_dir().join("r2l_zoo_curves_round_trip.csv");
+        std::fs::write(&path, write_curves_csv(&curves)).unwrap();
+        assert_eq!(read_curves_csv(&path).unwrap(), curves);
+    }
+
+    #[test]
+    fn runs_below_the_reference_threshold_fail() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../assets/sb3_reference.yaml");
+        let references = read_references(&path, "a2c").unwrap();
+        assert!(read_references(&path, "ppo").is_ok());
+        assert!(read_references(&path, "sac").is_err());
+        let curves = [
+            (
+                "CartPole-v1".to_owned(),
+                aggregate(&[curve(&[(10, 500.)])], 1),
+            ),
+            (
+                "Acrobot-v1".to_owned(),
+                aggregate(&[curve(&[(10, -500.)])], 1),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(reference_failures(&curves, &references), vec!["Acrobot-v1"]);
+    }
+}
@@ -1,26 +1,41 @@
 use std::{collections::BTreeMap, fs, path::PathBuf};
 
+use anyhow::{Context, anyhow, bail};
+use clap::ValueEnum;
 use r2l_api::{
-    BurnBackend, DefaultOnPolicyAlgorithmHooks, EpisodeBoundHook, LearningRateSchedule,
-    LearningSchedule, PPOAlgorithmBuilder, PPOBurnAgent, StepBoundHook, StepHookBound,
+    A2CConfig, AlgorithmConfig, BackendConfig, EnvConfig, EnvKwarg, EvaluatorConfig,
+    ExperimentConfig, GymWrapperConfig, NetworkConfig, OptimizerKind, OptimizerSettings, PPOConfig,
+    RewardNormalizerParams, RolloutBound, SamplerConfig, ScheduleConfig, ScheduleShape,
+    TrainingBudget, TrainingConfig,
 };
-use r2l_core::on_policy::algorithm::{DefaultAdapter, OnPolicyAlgorithm};
-use r2l_gym::GymEnv;
-use r2l_sampler::R2lNormalizedSampler;
 use serde::{Deserialize, Deserializer, Serialize, de};
 use yaml_serde::Value;
 
-pub type RlZooPpoAlgorithm = OnPolicyAlgorithm<
-    PPOBurnAgent<BurnBackend>,
-    R2lNormalizedSampler<GymEnv, StepBoundHook<GymEnv>>,
-    DefaultOnPolicyAlgorithmHooks<
-        PPOBurnAgent<BurnBackend>,
-        R2lNormalizedSampler<GymEnv, StepBoundHook<GymEnv>>,
-        DefaultAdapter,
-        GymEnv,
-        R2lNormalizedSampler<GymEnv, EpisodeBoundHook<GymEnv>>,
-    >,
->;
+use crate::policy_kwargs::RlZooPolicyKwargs;
+
+/// Episodes per environment of each evaluation pass.
+const EVAL_EPISODES: usize = 5;
+/// Clip bound of normalized observations and rewards, as in SB3's `VecNormalize`.
+const NORMALIZE_CLIP: f32 = 10.0;
+/// Epsilon SB3 passes to Adam.
+const ADAM_EPS: f64 = 1e-5;
+
+/// Algorithm whose Zoo hyperparameters are evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RlZooAlgorithm {
+    Ppo,
+    A2c,
+}
+
+impl RlZooAlgorithm {
+    /// Zoo's name of the algorithm, which also names its config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ppo => "ppo",
+            Self::A2c => "a2c",
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, Serialize)]
 pub enum RlZooSchedule {
@@ -36,11 +51,14 @@ impl RlZooSchedule {
     }
 }
 
-impl From<RlZooSchedule> for LearningRateSchedule {
+impl From<RlZooSchedule> for ScheduleConfig {
     fn from(schedule: RlZooSchedule) -> Self {
         match schedule {
             RlZooSchedule::Constant(value) => Self::Constant(value),
-            RlZooSchedule::Linear(value) => Self::Linear(value),
+            RlZooSchedule::Linear(value) => Self::Shaped(ScheduleShape::Linear {
+                start: value,
+                end: 0.0,
+            }),
         }
     }
 }
@@ -132,87 +150,521 @@ fn parse_python_bool_option(value: &str, key: &str) -> Option<bool> {
     }
 }
 
+/// Settings shared by every algorithm of a Zoo environment entry.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct RlZooEnvironmentConfig {
+pub struct RlZooCommonConfig {
     n_envs: usize,
     n_timesteps: usize,
     policy: String,
+    normalize: RlZooNormalize,
     n_steps: usize,
-    batch_size: usize,
-    gae_lambda: f32,
     gamma: f32,
-    n_epochs: usize,
+    gae_lambda: f32,
     ent_coef: f32,
-    learning_rate: RlZooSchedule,
-    clip_range: RlZooSchedule,
     vf_coef: f32,
     max_grad_norm: f32,
-    log_std_init: f32,
-    normalize: RlZooNormalize,
+    learning_rate: RlZooSchedule,
+    #[serde(default)]
+    normalize_advantage: Option<bool>,
+    #[serde(default)]
+    policy_kwargs: Option<String>,
+    #[serde(default)]
+    frame_stack: Option<usize>,
+    // r2l has no generalized state-dependent exploration, environments that
+    // use it are trained with a state-independent Gaussian policy instead.
+    #[serde(default)]
     use_sde: bool,
+    #[serde(default)]
     sde_sample_freq: i32,
+    #[serde(default)]
+    env_kwargs: BTreeMap<String, Value>,
+    #[serde(default)]
+    env_wrapper: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RlZooPpoConfig {
+    batch_size: usize,
+    n_epochs: usize,
+    clip_range: RlZooSchedule,
+    #[serde(default)]
+    clip_range_vf: Option<RlZooSchedule>,
+    #[serde(default)]
+    target_kl: Option<f64>,
+    #[serde(default)]
+    log_std_init: Option<f32>,
+}
+
+fn default_use_rms_prop() -> bool {
+    true
+}
+
+fn default_rms_prop_eps() -> f64 {
+    1e-5
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RlZooA2cConfig {
+    #[serde(default = "default_use_rms_prop")]
+    use_rms_prop: bool,
+    #[serde(default = "default_rms_prop_eps")]
+    rms_prop_eps: f64,
+}
+
+/// Hyperparameters only one algorithm has.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum RlZooAlgorithmConfig {
+    Ppo(RlZooPpoConfig),
+    A2c(RlZooA2cConfig),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RlZooEnvironmentConfig {
+    common: RlZooCommonConfig,
+    algorithm: RlZooAlgorithmConfig,
 }
 
 impl RlZooEnvironmentConfig {
-    fn suppoerted(&self) -> bool {
-        self.policy == "MlpPolicy"
+    fn parse(value: Value, algorithm: RlZooAlgorithm) -> anyhow::Result<Self> {
+        let common = yaml_serde::from_value(value.clone())?;
+        let algorithm = match algorithm {
+            RlZooAlgorithm::Ppo => RlZooAlgorithmConfig::Ppo(yaml_serde::from_value(value)?),
+            RlZooAlgorithm::A2c => RlZooAlgorithmConfig::A2c(yaml_serde::from_value(value)?),
+        };
+        Ok(Self { common, algorithm })
     }
 
-    pub fn build_burn_ppo_algorithm(
+    fn policy_kwargs(&self) -> anyhow::Result<RlZooPolicyKwargs> {
+        match &self.common.policy_kwargs {
+            Some(policy_kwargs) => RlZooPolicyKwargs::parse(policy_kwargs),
+            None => Ok(RlZooPolicyKwargs::default()),
+        }
+    }
+
+    /// Fails with the reason when r2l cannot reproduce the configuration.
+    fn check_supported(&self) -> anyhow::Result<()> {
+        if self.common.policy != "MlpPolicy" {
+            bail!("unsupported policy {}", self.common.policy);
+        }
+        if self.common.frame_stack.is_some() {
+            bail!("frame stacking is not supported");
+        }
+        self.policy_kwargs()?;
+        Ok(())
+    }
+
+    fn env_config(&self, env_name: &str) -> anyhow::Result<EnvConfig> {
+        let wrappers = match &self.common.env_wrapper {
+            Some(env_wrapper) => gym_wrappers(env_wrapper)?,
+            None => vec![],
+        };
+        Ok(EnvConfig::Gym {
+            id: env_name.to_owned(),
+            kwargs: self
+                .common
+                .env_kwargs
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), env_kwarg(value)?)))
+                .collect::<anyhow::Result<_>>()?,
+            max_episode_steps: None,
+            wrappers,
+        })
+    }
+
+    fn network_config(&self) -> anyhow::Result<NetworkConfig> {
+        let mut network = NetworkConfig::default();
+        if let RlZooAlgorithmConfig::Ppo(ppo) = &self.algorithm {
+            network.log_std_init = ppo.log_std_init;
+        }
+        self.policy_kwargs()?.apply(&mut network);
+        Ok(network)
+    }
+
+    /// Optimizer SB3 builds for the algorithm.
+    fn optimizer(&self) -> OptimizerSettings {
+        let lr = self.common.learning_rate.initial_value();
+        let (kind, eps) = match &self.algorithm {
+            RlZooAlgorithmConfig::A2c(a2c) if a2c.use_rms_prop => {
+                (OptimizerKind::RmsProp, a2c.rms_prop_eps)
+            }
+            _ => (OptimizerKind::Adam, ADAM_EPS),
+        };
+        OptimizerSettings {
+            kind,
+            lr,
+            beta1: None,
+            beta2: None,
+            eps: Some(eps),
+            weight_decay: None,
+            momentum: None,
+        }
+    }
+
+    fn algorithm_config(&self) -> AlgorithmConfig {
+        let common = &self.common;
+        match &self.algorithm {
+            RlZooAlgorithmConfig::Ppo(ppo) => AlgorithmConfig::Ppo(PPOConfig {
+                gamma: Some(common.gamma),
+                lambda: Some(common.gae_lambda),
+                sample_size: Some(ppo.batch_size),
+                total_epochs: Some(ppo.n_epochs),
+                normalize_advantage: common.normalize_advantage,
+                clip_range: Some(ppo.clip_range.into()),
+                clip_range_vf: ppo.clip_range_vf.map(Into::into),
+                entropy_coeff: Some(ScheduleConfig::Constant(common.ent_coef.into())),
+                vf_coeff: Some(ScheduleConfig::Constant(common.vf_coef.into())),
+                target_kl: ppo.target_kl.map(ScheduleConfig::Constant),
+                max_grad_norm: Some(common.max_grad_norm),
+                log_progress: None,
+            }),
+            // SB3's A2C takes one gradient step on the whole rollout.
+            RlZooAlgorithmConfig::A2c(_) => AlgorithmConfig::A2c(A2CConfig {
+                gamma: Some(common.gamma),
+                lambda: Some(common.gae_lambda),
+                sample_size: Some(common.n_steps * common.n_envs),
+                normalize_advantage: common.normalize_advantage,
+                entropy_coeff: Some(common.ent_coef),
+                vf_coeff: Some(common.vf_coef),
+                max_grad_norm: Some(common.max_grad_norm),
+                log_progress: None,
+            }),
+        }
+    }
+
+    /// Translates the Zoo configuration of `env_name` into an experiment that
+    /// logs its metrics and best policy into `run_dir`.
+    pub fn experiment_config(
         &self,
         env_name: &str,
-        csv_path: PathBuf,
+        backend: BackendConfig,
         seed: u64,
-    ) -> anyhow::Result<RlZooPpoAlgorithm> {
-        let obs_clip = self.normalize.norm_obs().then_some(10.0);
-        let mut builder = PPOAlgorithmBuilder::gym(env_name, self.n_envs)
-            .with_burn()
-            .with_rollout_bound(StepHookBound::new(self.n_steps))
-            .with_learning_schedule(LearningSchedule::total_step_bound(self.n_timesteps))
-            .with_csv_states(csv_path)
-            .with_observation_normalizer(obs_clip)
-            .with_lambda(self.gae_lambda)
-            .with_gamma(self.gamma)
-            .with_total_epochs(self.n_epochs)
-            .with_entropy_coeff(self.ent_coef)
-            .with_sample_size(self.batch_size)
-            .with_learning_rate_schedule(self.learning_rate.into())
-            .with_clip_range(self.clip_range.initial_value() as f32)
-            .with_log_std_init(self.log_std_init)
-            .with_vf_coeff(Some(self.vf_coef))
-            .with_seed(seed)
-            .with_gradient_clipping(Some(self.max_grad_norm));
-        if self.normalize.norm_reward() {
-            builder = builder.with_reward_normalizer(self.gamma, 10.0);
-        }
-        builder.build()
+        run_dir: PathBuf,
+    ) -> anyhow::Result<ExperimentConfig> {
+        let common = &self.common;
+        let reward_normalizer = common
+            .normalize
+            .norm_reward()
+            .then_some(RewardNormalizerParams {
+                gamma: common.gamma,
+                clip_reward: NORMALIZE_CLIP,
+            });
+        Ok(ExperimentConfig {
+            seed: Some(seed),
+            backend,
+            run_dir: Some(run_dir),
+            env: self.env_config(env_name)?,
+            sampler: SamplerConfig {
+                n_envs: common.n_envs,
+                rollout_bound: RolloutBound::Steps {
+                    steps: common.n_steps,
+                },
+                execution_mode: None,
+                obs_clip: common.normalize.norm_obs().then_some(NORMALIZE_CLIP),
+                reward_normalizer,
+            },
+            training: TrainingConfig {
+                budget: Some(TrainingBudget::TotalSteps {
+                    total_steps: common.n_timesteps,
+                }),
+                learning_rate: Some(common.learning_rate.into()),
+            },
+            algorithm: self.algorithm_config(),
+            network: self.network_config()?,
+            optimizer: Some(self.optimizer()),
+            value_optimizer: None,
+            evaluator: Some(EvaluatorConfig {
+                n_episodes: Some(EVAL_EPISODES),
+                ..EvaluatorConfig::default()
+            }),
+        })
+    }
+}
+
+fn env_kwarg(value: &Value) -> anyhow::Result<EnvKwarg> {
+    let value = match value {
+        Value::Null => bail!("None keyword arguments are not supported"),
+        Value::Bool(value) => EnvKwarg::Bool(*value),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => EnvKwarg::Int(value),
+            None => EnvKwarg::Float(
+                number
+                    .as_f64()
+                    .ok_or_else(|| anyhow!("unsupported number in RL Zoo config: {number}"))?,
+            ),
+        },
+        Value::String(value) => EnvKwarg::Str(value.clone()),
+        Value::Sequence(values) => EnvKwarg::List(
+            values
+                .iter()
+                .map(env_kwarg)
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Value::Mapping(mapping) => EnvKwarg::Dict(env_kwargs(mapping)?),
+        Value::Tagged(tagged) => bail!("unsupported tagged value in RL Zoo config: {tagged:?}"),
+    };
+    Ok(value)
+}
+
+fn env_kwargs(mapping: &yaml_serde::Mapping) -> anyhow::Result<BTreeMap<String, EnvKwarg>> {
+    mapping
+        .iter()
+        .map(|(key, value)| {
+            let key = key
+                .as_str()
+                .ok_or_else(|| anyhow!("RL Zoo keyword argument names must be strings: {key:?}"))?;
+            Ok((key.to_owned(), env_kwarg(value)?))
+        })
+        .collect()
+}
+
+/// Parses Zoo's `env_wrapper` value, which is either a wrapper path, a
+/// `{path: kwargs}` mapping, or a list of those.
+fn gym_wrappers(value: &Value) -> anyhow::Result<Vec<GymWrapperConfig>> {
+    match value {
+        Value::String(path) => Ok(vec![GymWrapperConfig {
+            path: path.clone(),
+            kwargs: BTreeMap::new(),
+        }]),
+        Value::Sequence(values) => {
+            let mut wrappers = vec![];
+            for value in values {
+                wrappers.extend(gym_wrappers(value)?);
+            }
+            Ok(wrappers)
+        }
+        Value::Mapping(mapping) => mapping
+            .iter()
+            .map(|(path, kwargs)| {
+                let path = path
+                    .as_str()
+                    .ok_or_else(|| anyhow!("RL Zoo wrapper paths must be strings: {path:?}"))?;
+                let kwargs = match kwargs {
+                    Value::Mapping(kwargs) => env_kwargs(kwargs)?,
+                    Value::Null => BTreeMap::new(),
+                    _ => bail!("RL Zoo wrapper arguments must be a mapping: {kwargs:?}"),
+                };
+                Ok(GymWrapperConfig {
+                    path: path.to_owned(),
+                    kwargs,
+                })
+            })
+            .collect(),
+        _ => bail!("unsupported RL Zoo env_wrapper value: {value:?}"),
     }
 }
 
 #[derive(Debug)]
 pub struct ZooConfig {
     pub supported_envs: BTreeMap<String, RlZooEnvironmentConfig>,
-    pub unsupported_envs: BTreeMap<String, RlZooEnvironmentConfig>,
+    /// Environments r2l cannot reproduce, with the reason.
+    pub unsupported_envs: BTreeMap<String, String>,
 }
 
 impl ZooConfig {
-    pub fn parse_rl_zoo_config(path: PathBuf) -> Self {
-        let content = fs::read_to_string(path).unwrap();
-        let mut parsed_content: BTreeMap<String, Value> = yaml_serde::from_str(&content).unwrap();
+    pub fn parse_rl_zoo_config(path: PathBuf, algorithm: RlZooAlgorithm) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut parsed_content: BTreeMap<String, Value> = yaml_serde::from_str(&content)?;
         parsed_content.remove("atari");
         let mut supported_envs = BTreeMap::new();
         let mut unsupported_envs = BTreeMap::new();
         for (env_name, val) in parsed_content {
-            let rl_zoo_config = yaml_serde::from_value::<RlZooEnvironmentConfig>(val).unwrap();
-            if rl_zoo_config.suppoerted() {
-                supported_envs.insert(env_name, rl_zoo_config);
-            } else {
-                unsupported_envs.insert(env_name, rl_zoo_config);
+            let rl_zoo_config = RlZooEnvironmentConfig::parse(val, algorithm)
+                .with_context(|| format!("invalid RL Zoo config of {env_name}"))?;
+            match rl_zoo_config.check_supported() {
+                Ok(()) => {
+                    supported_envs.insert(env_name, rl_zoo_config);
+                }
+                Err(reason) => {
+                    unsupported_envs.insert(env_name, format!("{reason:#}"));
+                }
             }
         }
-        Self {
+        Ok(Self {
             supported_envs,
             unsupported_envs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use r2l_api::{BackendConfig, EnvConfig, EnvKwarg, GymWrapperConfig};
+    use yaml_serde::Value;
+
+    use super::{RlZooAlgorithm, RlZooEnvironmentConfig, ZooConfig, env_kwarg, gym_wrappers};
+    use crate::{ASSETS_DIR, crate_dir};
+
+    // The CarRacing entry of the Zoo's ppo.yml, and a LunarLander entry
+    // written the way the Zoo passes `gymnasium.make` arguments.
+    const ENTRIES: &str = r#"
+CarRacing-v3:
+  n_envs: 8
+  n_timesteps: 4000000
+  normalize: "{'norm_obs': False, 'norm_reward': True}"
+  env_wrapper:
+    - rl_zoo3.wrappers.FrameSkip:
+        skip: 2
+    - rl_zoo3.wrappers.YAMLCompatResizeObservation:
+        shape:
+          - 64
+          - 64
+    - gymnasium.wrappers.transform_observation.GrayscaleObservation:
+        keep_dim: true
+  frame_stack: 2
+  policy: CnnPolicy
+  learning_rate: lin_1e-4
+  n_steps: 512
+  batch_size: 128
+  n_epochs: 10
+  gamma: 0.99
+  gae_lambda: 0.95
+  clip_range: 0.2
+  ent_coef: 0.0
+  vf_coef: 0.5
+  max_grad_norm: 0.5
+  use_sde: true
+  sde_sample_freq: 4
+LunarLander-v3:
+  env_kwargs:
+    continuous: true
+    gravity: -9.5
+    wind: {enable: false, power: 15}
+    tags: [a, 2]
+  env_wrapper:
+    - sb3_contrib.common.wrappers.TimeFeatureWrapper
+    - gymnasium.wrappers.ClipAction:
+  n_envs: 16
+  n_timesteps: 1000000
+  normalize: false
+  policy: MlpPolicy
+  learning_rate: 0.0003
+  n_steps: 1024
+  batch_size: 64
+  n_epochs: 4
+  gamma: 0.999
+  gae_lambda: 0.98
+  clip_range: 0.2
+  ent_coef: 0.01
+  vf_coef: 0.5
+  max_grad_norm: 0.5
+"#;
+
+    fn entry(name: &str) -> RlZooEnvironmentConfig {
+        let mut entries: BTreeMap<String, Value> = yaml_serde::from_str(ENTRIES).unwrap();
+        RlZooEnvironmentConfig::parse(entries.remove(name).unwrap(), RlZooAlgorithm::Ppo).unwrap()
+    }
+
+    fn wrapper(path: &str, kwargs: &[(&str, EnvKwarg)]) -> GymWrapperConfig {
+        GymWrapperConfig {
+            path: path.into(),
+            kwargs: kwargs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
         }
     }
+
+    #[test]
+    fn wrapper_lists_keep_their_order_and_arguments() {
+        let car_racing = entry("CarRacing-v3");
+        assert!(car_racing.check_supported().is_err());
+        let EnvConfig::Gym {
+            id,
+            kwargs,
+            wrappers,
+            ..
+        } = car_racing.env_config("CarRacing-v3").unwrap()
+        else {
+            panic!("expected a gym environment");
+        };
+        assert_eq!(id, "CarRacing-v3");
+        assert!(kwargs.is_empty());
+        assert_eq!(
+            wrappers,
+            vec![
+                wrapper("rl_zoo3.wrappers.FrameSkip", &[("skip", EnvKwarg::Int(2))]),
+                wrapper(
+                    "rl_zoo3.wrappers.YAMLCompatResizeObservation",
+                    &[(
+                        "shape",
+                        EnvKwarg::List(vec![EnvKwarg::Int(64), EnvKwarg::Int(64)])
+                    )]
+                ),
+                wrapper(
+                    "gymnasium.wrappers.transform_observation.GrayscaleObservation",
+                    &[("keep_dim", EnvKwarg::Bool(true))]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn env_kwargs_keep_their_python_types() {
+        let lunar_lander = entry("LunarLander-v3");
+        lunar_lander.check_supported().unwrap();
+        let config = lunar_lander
+            .experiment_config("LunarLander-v3", BackendConfig::Burn, 0, PathBuf::new())
+            .unwrap();
+        let EnvConfig::Gym {
+            kwargs, wrappers, ..
+        } = config.env
+        else {
+            panic!("expected a gym environment");
+        };
+        assert_eq!(kwargs["continuous"], EnvKwarg::Bool(true));
+        assert_eq!(kwargs["gravity"], EnvKwarg::Float(-9.5));
+        assert_eq!(
+            kwargs["wind"],
+            EnvKwarg::Dict(
+                [
+                    ("enable".to_owned(), EnvKwarg::Bool(false)),
+                    ("power".to_owned(), EnvKwarg::Int(15)),
+                ]
+                .into_iter()
+                .collect()
+            )
+        );
+        assert_eq!(
+            kwargs["tags"],
+            EnvKwarg::List(vec![EnvKwarg::Str("a".into()), EnvKwarg::Int(2)])
+        );
+        assert_eq!(
+            wrappers,
+            vec![
+                wrapper("sb3_contrib.common.wrappers.TimeFeatureWrapper", &[]),
+                wrapper("gymnasium.wrappers.ClipAction", &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_env_values_are_errors() {
+        let value = |yaml: &str| yaml_serde::from_str::<Value>(yaml).unwrap();
+        assert!(env_kwarg(&value("null")).is_err());
+        assert!(env_kwarg(&value("[1, null]")).is_err());
+        assert!(env_kwarg(&value("{1: true}")).is_err());
+        assert!(gym_wrappers(&value("3")).is_err());
+        assert!(gym_wrappers(&value("{a.Wrapper: [1]}")).is_err());
+        assert!(gym_wrappers(&value("[a.Wrapper, {b.Wrapper: 1}]")).is_err());
+    }
+
+    #[test]
+    fn asset_wrappers_reach_the_experiment_config() {
+        let config_path = crate_dir().join(ASSETS_DIR).join("ppo.yaml");
+        let zoo_config = ZooConfig::parse_rl_zoo_config(config_path, RlZooAlgorithm::Ppo).unwrap();
+        let env = "MiniGrid-Empty-Random-5x5-v0";
+        let config = zoo_config.supported_envs[env]
+            .experiment_config(env, BackendConfig::Burn, 0, PathBuf::new())
+            .unwrap();
+        let EnvConfig::Gym { wrappers, .. } = config.env else {
+            panic!("expected a gym environment");
+        };
+        assert_eq!(
+            wrappers,
+            vec![wrapper("minigrid.wrappers.FlatObsWrapper", &[])]
+        );
+    }
 }